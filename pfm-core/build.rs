@@ -0,0 +1,53 @@
+// build.rs set build information read by global::build_info: git hash, build date and enabled features of pfm-core.
+
+use std::env;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let git_dir = Path::new(&manifest_dir).join("../.git");
+    // rebuilt on new commits, missing paths would rerun this on every build
+    for path in [git_dir.join("HEAD"), git_dir.join("refs/heads")] {
+        if path.exists() {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+    }
+
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .current_dir(&manifest_dir)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .filter(|hash| !hash.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=PFM_GIT_HASH={}", git_hash);
+
+    // reproducible builds set the date through SOURCE_DATE_EPOCH
+    let build_date = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<i64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs() as i64)
+        });
+    println!("cargo:rustc-env=PFM_BUILD_DATE={}", build_date);
+
+    let mut features: Vec<String> = env::vars()
+        .filter_map(|(key, _)| {
+            key.strip_prefix("CARGO_FEATURE_")
+                .map(|feature| feature.to_lowercase().replace('_', "-"))
+        })
+        .filter(|feature| feature != "default")
+        .collect();
+    features.sort();
+    println!("cargo:rustc-env=PFM_CORE_FEATURES={}", features.join(","));
+}
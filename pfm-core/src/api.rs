@@ -0,0 +1,162 @@
+// api.rs stable facade of high-level operations supported for binaries and downstream crates.
+// modules behind these paths may be moved or split, paths here are kept. services and implementations like
+// `forex::service` or `forex_impl::currencybeacon` are crate private, so operations are only reachable from here.
+
+/// operations on forex rates, e.g. polling, conversion and jobs.
+pub mod forex {
+    pub use crate::forex::service::{
+        BASE_RATE_REPAIR_SOURCE, BASE_RATE_TOLERANCE, CONVERT_ON_DATES_MAX, CRYPTO_MAX_JUMP,
+        RATE_ON_MAX_STALE_DAYS, TIMESERIES_CHUNK_INTERVAL, TimeseriesQuery, audit_base_rates,
+        backfill_metals, batch_convert, bootstrap, capture_crypto_prices,
+        check_provider_consistency, convert, convert_historical, convert_on_dates,
+        enforce_disk_quota, fetch_timeseries, get_historical_range, get_job_runs,
+        get_provider_health, get_rates, get_storage_stats, ingest_crypto_history, latest_delta,
+        lock_job, pair_rate, plan_historical_rates, poll_crypto_rates, poll_historical_rates,
+        poll_rates, publish_rates, rate_on, rates_at, rates_sources, rates_staleness, run_job,
+        update_historical_rates_data, warm_cache,
+    };
+}
+
+/// 3rd party providers of rates, selected by name or constructed directly.
+pub mod providers {
+    pub use crate::forex_impl::registry::{
+        Provider, ProviderChain, ProviderRegistry, provider_from_config, provider_from_name,
+    };
+
+    #[cfg(feature = "provider-currencyapi")]
+    pub use crate::forex_impl::currency_api::{Api as CurrencyApi, NAME as CURRENCY_API_NAME};
+    #[cfg(feature = "provider-currencybeacon")]
+    pub use crate::forex_impl::currencybeacon::{
+        Api as CurrencyBeacon, NAME as CURRENCY_BEACON_NAME,
+    };
+    #[cfg(feature = "provider-openexchange")]
+    pub use crate::forex_impl::open_exchange_api::{
+        Api as OpenExchangeRates, NAME as OPEN_EXCHANGE_RATES_NAME,
+    };
+    #[cfg(feature = "provider-tradermade")]
+    pub use crate::forex_impl::tradermade::Api as TraderMade;
+    pub use crate::forex_impl::twelvedata::Api as TwelveData;
+}
+
+/// integrations notified or authenticated with rates, e.g. webhooks and signed internal requests.
+pub mod integrations {
+    pub use crate::forex_impl::internal_signing::{
+        INTERNAL_SIGNATURE_HEADER, INTERNAL_TIMESTAMP_HEADER, InternalClient, InternalSigner,
+    };
+    #[cfg(feature = "mqtt")]
+    pub use crate::forex_impl::mqtt::Mqtt;
+    pub use crate::forex_impl::signing::{RatesSigner, RatesVerifier};
+    #[cfg(feature = "email")]
+    pub use crate::forex_impl::smtp::Smtp;
+    pub use crate::forex_impl::sync_remote::HttpSyncRemote;
+    pub use crate::forex_impl::webhook::{SIGNATURE_HEADER, Webhook};
+}
+
+/// server side storage and its setup.
+pub mod storage {
+    #[cfg(feature = "fault-injection")]
+    pub use crate::forex_impl::faulty_storage::{FaultyStorage, INJECTED_FAULT};
+    pub use crate::forex_impl::forex_storage::ForexStorageImpl;
+    pub use crate::global::{storage_fs, validate_storage};
+}
+
+/// static datasets of rates before providers' coverage, ingested into historical rates.
+pub mod datasets {
+    pub use crate::forex_impl::crypto_dataset::{
+        SOURCE as CRYPTO_SOURCE, from_csv as crypto_from_csv, from_dir as crypto_from_dir,
+    };
+    pub use crate::forex_impl::metals_dataset::{MetalsDataset, SOURCE as METALS_SOURCE};
+}
+
+/// analytics over stored rates, e.g. extremes, aggregates and sparklines.
+pub mod analytics {
+    pub use crate::forex::analytics::{
+        SPARKLINE_MAX_DAYS, TRAVEL_LOOKBACK_DAYS, aggregate, apply_shock, completeness, extremes,
+        monthly_rates, monthly_rates_csv, pair_sparkline, rebuild_sparklines, shock_scenario,
+        sparklines, travel_budget,
+    };
+}
+
+/// resumable backfill of historical rates.
+pub mod backfill {
+    pub use crate::forex::backfill::{
+        DEFAULT_RATE_LIMIT, limits, plan, read_checkpoint, run, schedule,
+    };
+}
+
+/// arithmetic expressions of money, e.g. `USD 100 + EUR 20 in IDR`.
+pub mod expression {
+    pub use crate::forex::expression::{Expression, evaluate};
+}
+
+/// freshness SLO evaluations and their compliance.
+pub mod slo {
+    pub use crate::forex::slo::{
+        SLO_COMPLIANCE_MAX_REPORTS, SLO_RETENTION_DAYS, compliance, evaluate,
+    };
+}
+
+/// digests of rates emailed to subscribers.
+pub mod digest {
+    pub use crate::digest::service::{
+        build_digest, create_digest_subscription, delete_digest_subscription,
+        get_digest_subscriptions, render_digest, send_digests,
+    };
+}
+
+/// saving goals and their contributions.
+pub mod goals {
+    pub use crate::goals::service::{
+        add_contribution, create_goal, delete_goal, get_goal_progress, get_goals, progress,
+        remove_contribution, update_goal,
+    };
+}
+
+/// ledger of transactions imported from bank statements, and its reports.
+pub mod ledger {
+    pub use crate::ledger::export::{anonymize, transactions_csv};
+    pub use crate::ledger::import::{
+        CsvMapping, SOURCE_CSV, SOURCE_OFX, SOURCE_QIF, parse_csv, parse_ofx, parse_qif,
+    };
+    pub use crate::ledger::service::{
+        add_account, export_ledger, get_accounts, get_balance_sheet, get_category_rules,
+        get_exposure_report, get_trial_balance, import_ledger, import_transactions, post_entry,
+        reclassify_transactions, set_category_rules,
+    };
+}
+
+/// export and import of client records as a sealed profile.
+pub mod profile {
+    pub use crate::profile::service::{export_profile, import_profile};
+}
+
+/// bundles of historical rates seeding new deployments.
+pub mod seed {
+    pub use crate::seed::service::{build_bundle, install_bundle};
+}
+
+/// sync of client records between storages, locally or through pfm-http.
+pub mod sync {
+    pub use crate::sync::remote::{SERVER_REVISION_LOG, remote_changes, remote_push, sync_remote};
+    pub use crate::sync::service::{TOMBSTONE_RETENTION_DAYS, sync};
+}
+
+/// tax reports of realized gains.
+pub mod tax {
+    pub use crate::tax::service::{tax_report, tax_report_csv};
+}
+
+/// users of client storage.
+pub mod users {
+    pub use crate::users::service::{
+        authenticate, change_password, create_user, delete_user, get_usernames,
+    };
+}
+
+/// watchlists of pairs and their quotes.
+pub mod watchlist {
+    pub use crate::watchlist::service::{
+        add_pair, delete_watchlist, get_watchlist, get_watchlist_quotes, quotes, remove_pair,
+        reorder_pairs,
+    };
+}
@@ -0,0 +1,115 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::forex::{Currency, Money};
+
+/// How often digest is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestFrequency {
+    Daily,
+    Weekly,
+}
+
+impl DigestFrequency {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+        }
+    }
+
+    /// period compared by the digest, also interval between digests.
+    pub fn period(&self) -> TimeDelta {
+        match self {
+            Self::Daily => TimeDelta::days(1),
+            Self::Weekly => TimeDelta::weeks(1),
+        }
+    }
+}
+
+/// Pair watched in digest, rate is price of 1 base in quote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DigestPair {
+    pub base: Currency,
+    pub quote: Currency,
+}
+
+/// Digest subscription of a user with their watchlist and holdings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestSubscription {
+    pub id: Uuid,
+    pub email: String,
+    pub frequency: DigestFrequency,
+    pub watchlist: Vec<DigestPair>,
+
+    /// holdings in any currency, valued in currency for portfolio delta.
+    pub holdings: Vec<Money>,
+    pub currency: Currency,
+    pub last_sent: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DigestSubscription {
+    pub(crate) fn new(
+        email: &str,
+        frequency: DigestFrequency,
+        watchlist: Vec<DigestPair>,
+        holdings: Vec<Money>,
+        currency: Currency,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            email: email.trim().to_string(),
+            frequency,
+            watchlist,
+            holdings,
+            currency,
+            last_sent: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// digest is due once its period passed since last sent, with an hour of slack for cron jitter.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.last_sent.is_none_or(|last_sent| {
+            now - last_sent >= self.frequency.period() - TimeDelta::hours(1)
+        })
+    }
+}
+
+/// Latest rate of watched pair and its change over the digest period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestPairChange {
+    pub base: Currency,
+    pub quote: Currency,
+    pub rate: Decimal,
+
+    /// None if no rates stored at start of period.
+    pub previous: Option<Decimal>,
+    pub change_percent: Option<Decimal>,
+}
+
+/// Value of holdings now and at start of period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioDelta {
+    pub value: Money,
+    pub previous: Option<Money>,
+    pub change: Option<Money>,
+}
+
+/// Summary sent to a subscriber.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Digest {
+    pub subscription_id: Uuid,
+    pub email: String,
+    pub frequency: DigestFrequency,
+    pub date: DateTime<Utc>,
+    pub previous_date: Option<DateTime<Utc>>,
+    pub pairs: Vec<DigestPairChange>,
+
+    /// None if subscription has no holdings.
+    pub portfolio: Option<PortfolioDelta>,
+}
@@ -0,0 +1,26 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::entity::DigestSubscription;
+use crate::forex::ForexResult;
+
+#[async_trait]
+pub trait DigestStorage {
+    /// insert new subscription or replace existing one with the same id.
+    async fn upsert_digest_subscription(
+        &self,
+        subscription: &DigestSubscription,
+    ) -> ForexResult<()>;
+
+    /// get all subscriptions ordered by creation.
+    async fn get_digest_subscriptions(&self) -> ForexResult<Vec<DigestSubscription>>;
+
+    /// delete subscription by its id.
+    async fn delete_digest_subscription(&self, id: Uuid) -> ForexResult<()>;
+}
+
+/// Delivery of rendered digests, e.g. SMTP.
+#[async_trait]
+pub trait DigestSender {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> ForexResult<()>;
+}
@@ -0,0 +1,9 @@
+// digest scheduled email summary of watched pairs and holdings, sent by pfm-cron.
+
+pub mod entity;
+
+pub mod interface;
+
+pub(crate) mod service;
+#[cfg(test)]
+mod service_test;
@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+use super::{
+    entity::{
+        Digest, DigestFrequency, DigestPair, DigestPairChange, DigestSubscription, PortfolioDelta,
+    },
+    interface::{DigestSender, DigestStorage},
+};
+use crate::error::BaseError;
+use crate::forex::{
+    Currency, ForexError, ForexResult, Money,
+    entity::{Job, JobRun, JobTrigger, Rates},
+    interface::{ForexStorage, ForexStorageJobRuns},
+    service as forex_service,
+};
+
+pub async fn create_digest_subscription<DS>(
+    storage: &DS,
+    email: &str,
+    frequency: DigestFrequency,
+    watchlist: Vec<DigestPair>,
+    holdings: Vec<Money>,
+    currency: Currency,
+) -> ForexResult<DigestSubscription>
+where
+    DS: DigestStorage,
+{
+    if !email.contains('@') {
+        return Err(ForexError::client_error("invalid digest email address"));
+    }
+    if watchlist.is_empty() && holdings.is_empty() {
+        return Err(ForexError::client_error(
+            "digest must watch at least a pair or a holding",
+        ));
+    }
+    let subscription = DigestSubscription::new(email, frequency, watchlist, holdings, currency);
+    storage.upsert_digest_subscription(&subscription).await?;
+
+    Ok(subscription)
+}
+
+pub async fn get_digest_subscriptions<DS>(storage: &DS) -> ForexResult<Vec<DigestSubscription>>
+where
+    DS: DigestStorage,
+{
+    storage.get_digest_subscriptions().await
+}
+
+pub async fn delete_digest_subscription<DS>(storage: &DS, id: Uuid) -> ForexResult<()>
+where
+    DS: DigestStorage,
+{
+    storage.delete_digest_subscription(id).await
+}
+
+/// send digests of subscriptions due at now, comparing latest rates with historical rates a period before.
+/// failed deliveries are retried on next run, the run is recorded as job run with number of digests sent.
+pub async fn send_digests<DS, FS, S>(
+    digest_storage: &DS,
+    forex_storage: &FS,
+    sender: &S,
+    trigger: JobTrigger,
+    now: DateTime<Utc>,
+) -> ForexResult<u32>
+where
+    DS: DigestStorage,
+    FS: ForexStorage + ForexStorageJobRuns,
+    S: DigestSender,
+{
+    let mut job_run = JobRun::start(Job::SendDigest, trigger);
+    let ret = send_due_digests(digest_storage, forex_storage, sender, now).await;
+    job_run.finish(match &ret {
+        Ok((sent, 0)) => Ok(*sent),
+        Ok((sent, failed)) => Err(format!("{} digests sent, {} failed", sent, failed)),
+        Err(err) => Err(err.detail()),
+    });
+    if let Err(err) = forex_storage.insert_job_run(&job_run).await {
+        tracing::error!(
+            "failed storing run of job {}: {}",
+            Job::SendDigest.name(),
+            err
+        );
+    }
+
+    ret.map(|(sent, _)| sent)
+}
+
+async fn send_due_digests<DS, FS, S>(
+    digest_storage: &DS,
+    forex_storage: &FS,
+    sender: &S,
+    now: DateTime<Utc>,
+) -> ForexResult<(u32, u32)>
+where
+    DS: DigestStorage,
+    FS: ForexStorage,
+    S: DigestSender,
+{
+    let subscriptions: Vec<DigestSubscription> = digest_storage
+        .get_digest_subscriptions()
+        .await?
+        .into_iter()
+        .filter(|subscription| subscription.is_due(now))
+        .collect();
+    if subscriptions.is_empty() {
+        return Ok((0, 0));
+    }
+
+    let latest = forex_storage.get_latest().await?;
+    if let Some(err) = latest.error {
+        return Err(ForexError::internal_error(err.as_str()));
+    }
+    let mut previous: HashMap<&'static str, Option<Rates>> = HashMap::new();
+    let (mut sent, mut failed) = (0, 0);
+    for mut subscription in subscriptions {
+        let frequency = subscription.frequency;
+        if !previous.contains_key(frequency.name()) {
+            let rates = forex_storage
+                .get_historical(latest.data.date - frequency.period())
+                .await
+                .ok()
+                .filter(|rates| rates.error.is_none())
+                .map(|rates| rates.data);
+            previous.insert(frequency.name(), rates);
+        }
+        let previous = previous.get(frequency.name()).and_then(Option::as_ref);
+
+        let digest = match build_digest(&subscription, &latest.data, previous) {
+            Ok(digest) => digest,
+            Err(err) => {
+                tracing::error!("failed building digest {}: {}", subscription.id, err);
+                failed += 1;
+                continue;
+            }
+        };
+        let (subject, body) = render_digest(&digest);
+        if let Err(err) = sender.send(&subscription.email, &subject, &body).await {
+            tracing::error!(
+                "failed sending digest {} to {}: {}",
+                subscription.id,
+                subscription.email,
+                err
+            );
+            failed += 1;
+            continue;
+        }
+        sent += 1;
+        subscription.last_sent = Some(now);
+        // already delivered, at worst sent again on next run
+        if let Err(err) = digest_storage
+            .upsert_digest_subscription(&subscription)
+            .await
+        {
+            tracing::error!(
+                "failed recording digest {} as sent: {}",
+                subscription.id,
+                err
+            );
+        }
+    }
+
+    Ok((sent, failed))
+}
+
+/// digest of subscription from latest rates, changes are None without previous rates.
+pub fn build_digest(
+    subscription: &DigestSubscription,
+    latest: &Rates,
+    previous: Option<&Rates>,
+) -> ForexResult<Digest> {
+    let mut pairs = Vec::with_capacity(subscription.watchlist.len());
+    for pair in &subscription.watchlist {
+        let rate = forex_service::pair_rate(latest, pair.base, pair.quote)?;
+        let previous = previous
+            .map(|rates| forex_service::pair_rate(rates, pair.base, pair.quote))
+            .transpose()?;
+        pairs.push(DigestPairChange {
+            base: pair.base,
+            quote: pair.quote,
+            rate,
+            previous,
+            change_percent: previous.and_then(|previous| change_percent(previous, rate)),
+        });
+    }
+
+    let portfolio = if subscription.holdings.is_empty() {
+        None
+    } else {
+        let currency = subscription.currency;
+        let value = portfolio_value(&subscription.holdings, latest, currency)?;
+        let previous = previous
+            .map(|rates| portfolio_value(&subscription.holdings, rates, currency))
+            .transpose()?;
+        Some(PortfolioDelta {
+            value: Money::new_money(currency, value),
+            previous: previous.map(|previous| Money::new_money(currency, previous)),
+            change: previous.map(|previous| Money::new_money(currency, value - previous)),
+        })
+    };
+
+    Ok(Digest {
+        subscription_id: subscription.id,
+        email: subscription.email.clone(),
+        frequency: subscription.frequency,
+        date: latest.date,
+        previous_date: previous.map(|rates| rates.date),
+        pairs,
+        portfolio,
+    })
+}
+
+/// plain text subject and body of digest.
+pub fn render_digest(digest: &Digest) -> (String, String) {
+    let subject = format!(
+        "[pfm] {} rates digest {}",
+        digest.frequency.name(),
+        digest.date.date_naive()
+    );
+
+    let mut body = format!("Rates as of {}\n", digest.date.format("%Y-%m-%d %H:%M UTC"));
+    if let Some(previous_date) = digest.previous_date {
+        let _ = writeln!(body, "Changes since {}", previous_date.date_naive());
+    }
+    body.push('\n');
+    for pair in &digest.pairs {
+        let _ = write!(
+            body,
+            "{}/{}: {}",
+            pair.base.code(),
+            pair.quote.code(),
+            pair.rate.round_dp(6).normalize()
+        );
+        if let Some(change) = pair.change_percent {
+            let _ = write!(body, " ({:+}%)", change.round_dp(2));
+        }
+        body.push('\n');
+    }
+    if let Some(portfolio) = &digest.portfolio {
+        let _ = write!(
+            body,
+            "\nPortfolio: {} {}",
+            portfolio.value.currency().code(),
+            portfolio.value.amount().round_dp(2)
+        );
+        if let Some(change) = &portfolio.change {
+            let _ = write!(body, " ({:+})", change.amount().round_dp(2));
+        }
+        body.push('\n');
+    }
+
+    (subject, body)
+}
+
+fn portfolio_value(holdings: &[Money], rates: &Rates, currency: Currency) -> ForexResult<Decimal> {
+    let mut value = Decimal::ZERO;
+    for holding in holdings {
+        let rate = forex_service::pair_rate(rates, holding.currency(), currency)?;
+        value += holding.amount() * rate;
+    }
+
+    Ok(value)
+}
+
+fn change_percent(previous: Decimal, rate: Decimal) -> Option<Decimal> {
+    if previous.is_zero() {
+        return None;
+    }
+
+    Some((rate - previous) / previous * dec!(100))
+}
@@ -0,0 +1,87 @@
+use chrono::{TimeDelta, TimeZone, Utc};
+use rust_decimal_macros::dec;
+
+use super::entity::{DigestFrequency, DigestPair, DigestSubscription};
+use super::service::{build_digest, render_digest};
+use crate::forex::{Currency, Money, entity::Rates};
+use crate::global::test_support;
+
+fn rates(day: u32, idr: rust_decimal::Decimal) -> Rates {
+    let date = Utc.with_ymd_and_hms(2025, 3, day, 0, 0, 0).unwrap();
+    test_support::rates(date, &[Money::IDR(idr), Money::EUR(dec!(0.5))]).data
+}
+
+fn subscription() -> DigestSubscription {
+    DigestSubscription::new(
+        "user@example.com",
+        DigestFrequency::Daily,
+        vec![
+            DigestPair {
+                base: Currency::USD,
+                quote: Currency::IDR,
+            },
+            DigestPair {
+                base: Currency::EUR,
+                quote: Currency::IDR,
+            },
+        ],
+        vec![Money::USD(dec!(100)), Money::IDR(dec!(1000000))],
+        Currency::IDR,
+    )
+}
+
+#[test]
+fn test_build_digest() {
+    let subscription = subscription();
+    let digest = build_digest(
+        &subscription,
+        &rates(4, dec!(16500)),
+        Some(&rates(3, dec!(16000))),
+    )
+    .unwrap();
+
+    assert_eq!(digest.pairs[0].rate, dec!(16500));
+    assert_eq!(digest.pairs[0].previous, Some(dec!(16000)));
+    assert_eq!(digest.pairs[0].change_percent, Some(dec!(3.125)));
+    assert_eq!(digest.pairs[1].rate, dec!(33000));
+    let portfolio = digest.portfolio.unwrap();
+    assert_eq!(portfolio.value, Money::IDR(dec!(2650000)));
+    assert_eq!(portfolio.previous, Some(Money::IDR(dec!(2600000))));
+    assert_eq!(portfolio.change, Some(Money::IDR(dec!(50000))));
+
+    let digest = build_digest(&subscription, &rates(4, dec!(16500)), None).unwrap();
+    assert!(digest.previous_date.is_none());
+    assert!(digest.pairs[0].change_percent.is_none());
+    assert!(digest.portfolio.unwrap().change.is_none());
+}
+
+#[test]
+fn test_render_digest() {
+    let digest = build_digest(
+        &subscription(),
+        &rates(4, dec!(16500)),
+        Some(&rates(3, dec!(16000))),
+    )
+    .unwrap();
+    let (subject, body) = render_digest(&digest);
+
+    assert_eq!(subject, "[pfm] daily rates digest 2025-03-04");
+    assert!(body.contains("Changes since 2025-03-03"));
+    assert!(body.contains("USD/IDR: 16500 (+3.12%)"));
+    assert!(body.contains("Portfolio: IDR 2650000 (+50000)"));
+}
+
+#[test]
+fn test_subscription_is_due() {
+    let now = Utc.with_ymd_and_hms(2025, 3, 4, 2, 0, 0).unwrap();
+    let mut subscription = subscription();
+    assert!(subscription.is_due(now));
+
+    subscription.last_sent = Some(now - TimeDelta::days(1) + TimeDelta::minutes(5));
+    assert!(subscription.is_due(now));
+
+    subscription.frequency = DigestFrequency::Weekly;
+    assert!(!subscription.is_due(now));
+    subscription.last_sent = Some(now - TimeDelta::weeks(1));
+    assert!(subscription.is_due(now));
+}
@@ -30,6 +30,18 @@ pub trait BaseError: Sized + std::error::Error + Send + Sync + 'static {
             .unwrap_or("Error undefined".to_string())
     }
 
+    /// causes of the error below its cause, outermost first.
+    fn causes(&self) -> Vec<String> {
+        let mut causes = vec![];
+        let mut source = self.source().and_then(|err| err.source());
+        while let Some(err) = source {
+            causes.push(err.to_string());
+            source = err.source();
+        }
+
+        causes
+    }
+
     fn detail(&self) -> String {
         let error = self.to_string();
         let cause = BaseError::cause(self);
@@ -57,6 +69,17 @@ impl BaseError for ClientError {
     }
 }
 
+/// requested resource doesn't exist, or no longer does.
+#[derive(Debug, Error)]
+#[error("Not found error: {0}")]
+pub struct NotFoundError(#[from] anyhow::Error);
+
+impl BaseError for NotFoundError {
+    fn new(err: impl Into<anyhow::Error>) -> Self {
+        Self(err.into())
+    }
+}
+
 #[derive(Debug, Error)]
 #[error("Internal error: {0}")]
 pub struct InternalError(#[from] anyhow::Error);
@@ -0,0 +1,594 @@
+// analytics.rs insights derived from stored rates, e.g. best time to exchange.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::Context;
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, TimeDelta, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal_macros::dec;
+
+use super::{
+    Currency, ForexError, ForexResult, Money,
+    entity::{
+        AggregationPeriod, CompletenessReport, CurrencyCompleteness, MonthlyRates,
+        MonthlyRatesTable, PairAggregates, PairSparkline, RateAggregate, RateExtremes, RatePoint,
+        RateShock, Rates, ShockScenario, ShockedHolding, Sparkline, TravelBudget,
+    },
+    interface::{ForexStorage, ForexStorageSparklines, ForexStorageSparse},
+    redenomination, service,
+};
+use crate::error::AsInternalError;
+use crate::global;
+
+/// lowest and highest rate of pair (base, quote) in historical rates between start and end,
+/// and percentile of current rate from latest rates within the period.
+pub async fn extremes<FS>(
+    storage: &FS,
+    pair: (Currency, Currency),
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> ForexResult<RateExtremes>
+where
+    FS: ForexStorage + ForexStorageSparse,
+{
+    let (base, quote) = pair;
+    if start > end {
+        return Err(ForexError::client_error("start must not be after end"));
+    }
+
+    let mut points = vec![];
+    for mut rates in storage
+        .get_historical_range_of(&[base, quote], start, end)
+        .await?
+    {
+        if rates.error.is_some() {
+            continue;
+        }
+        redenomination::to_current_units(&mut rates.data);
+        let rate = service::pair_rate(&rates.data, base, quote)?;
+        if rate.is_zero() {
+            continue;
+        }
+        points.push(RatePoint {
+            date: rates.data.date,
+            rate,
+        });
+    }
+
+    let (Some(lowest), Some(highest)) = (
+        points.iter().min_by_key(|point| point.rate).cloned(),
+        points.iter().max_by_key(|point| point.rate).cloned(),
+    ) else {
+        return Err(ForexError::client_error(
+            format!(
+                "no historical rates of {}/{} between {} and {}",
+                base.code(),
+                quote.code(),
+                start.date_naive(),
+                end.date_naive()
+            )
+            .as_str(),
+        ));
+    };
+
+    let latest = storage.get_latest().await?;
+    if let Some(err) = latest.error {
+        return Err(ForexError::internal_error(err.as_str()));
+    }
+    let current = RatePoint {
+        date: latest.data.date,
+        rate: service::pair_rate(&latest.data, base, quote)?,
+    };
+
+    let lower_or_equal = points
+        .iter()
+        .filter(|point| point.rate <= current.rate)
+        .count();
+    let percentile =
+        (Decimal::from(lower_or_equal) * dec!(100) / Decimal::from(points.len())).round_dp(2);
+
+    Ok(RateExtremes {
+        base,
+        quote,
+        start,
+        end,
+        lowest,
+        highest,
+        current,
+        percentile,
+    })
+}
+
+/// days of historical rates before latest rates used to plan travel budget.
+pub const TRAVEL_LOOKBACK_DAYS: i64 = 90;
+
+/// plan spending of budget in destination currency for trip between start and end inclusive,
+/// using average and volatility of rates in recent historical rates.
+pub async fn travel_budget<FS>(
+    storage: &FS,
+    budget: Money,
+    destination: Currency,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> ForexResult<TravelBudget>
+where
+    FS: ForexStorage + ForexStorageSparse,
+{
+    if start > end {
+        return Err(ForexError::client_error("start must not be after end"));
+    }
+    let days = (end.date_naive() - start.date_naive()).num_days() + 1;
+    let home = budget.currency();
+
+    let latest = storage.get_latest().await?;
+    if let Some(err) = latest.error {
+        return Err(ForexError::internal_error(err.as_str()));
+    }
+    let history_end = latest.data.date;
+    let history_start = history_end - TimeDelta::days(TRAVEL_LOOKBACK_DAYS);
+
+    let mut rates = vec![];
+    for historical in storage
+        .get_historical_range_of(&[home, destination], history_start, history_end)
+        .await?
+    {
+        if historical.error.is_some() {
+            continue;
+        }
+        let rate = service::pair_rate(&historical.data, home, destination)?;
+        if !rate.is_zero() {
+            rates.push(rate);
+        }
+    }
+    // fallback to latest rates when there's no recent history
+    if rates.is_empty() {
+        rates.push(service::pair_rate(&latest.data, home, destination)?);
+    }
+
+    let count = Decimal::from(rates.len());
+    let average_rate = rates.iter().sum::<Decimal>() / count;
+    let variance = rates
+        .iter()
+        .map(|rate| (rate - average_rate) * (rate - average_rate))
+        .sum::<Decimal>()
+        / count;
+    let volatility = variance
+        .to_f64()
+        .and_then(|variance| Decimal::from_f64(variance.sqrt()))
+        .unwrap_or_default();
+    let conservative_rate = (average_rate - volatility).max(Decimal::ZERO);
+
+    let days_dec = Decimal::from(days);
+    let total = budget.amount() * average_rate;
+    let conservative_total = budget.amount() * conservative_rate;
+
+    Ok(TravelBudget {
+        budget,
+        destination,
+        start,
+        end,
+        days: days as u32,
+        average_rate,
+        volatility,
+        conservative_rate,
+        total: Money::new_money(destination, total),
+        daily: Money::new_money(destination, total / days_dec),
+        conservative_total: Money::new_money(destination, conservative_total),
+        conservative_daily: Money::new_money(destination, conservative_total / days_dec),
+    })
+}
+
+/// apply hypothetical shocks to rates, each shock is percent change of the currency's value against base of the rates,
+/// e.g. IDR -10 weakens IDR by 10%, so 1 base buys about 11% more IDR. base can't be shocked, shock the rest instead.
+pub fn apply_shock(rates: &Rates, shocks: &HashMap<Currency, Decimal>) -> ForexResult<Rates> {
+    let mut ret = rates.clone();
+    for (currency, percent) in shocks {
+        if *currency == rates.base {
+            return Err(ForexError::client_error(
+                format!(
+                    "base currency {} can't be shocked, shock other currencies against it instead",
+                    currency.code()
+                )
+                .as_str(),
+            ));
+        }
+        if *percent <= dec!(-100) {
+            return Err(ForexError::client_error(
+                format!("shock of {} must be above -100%", currency.code()).as_str(),
+            ));
+        }
+        let rate = rates.rates.rate(*currency);
+        if rate.is_zero() {
+            return Err(ForexError::client_error(
+                format!("no rate of {} to shock", currency.code()).as_str(),
+            ));
+        }
+        // rate is units of currency per base, stronger currency takes fewer units
+        let shocked = rate / (Decimal::ONE + percent / dec!(100));
+        ret.rates.set_rate(Money::new_money(*currency, shocked));
+    }
+
+    Ok(ret)
+}
+
+/// revalue holdings in the currency under shocks of latest rates, shocks are against the currency.
+pub async fn shock_scenario<FS>(
+    storage: &FS,
+    shocks: &HashMap<Currency, Decimal>,
+    holdings: &[Money],
+    currency: Currency,
+) -> ForexResult<ShockScenario>
+where
+    FS: ForexStorage,
+{
+    let latest = storage.get_latest().await?;
+    if let Some(err) = latest.error {
+        return Err(ForexError::internal_error(err.as_str()));
+    }
+    let rates = latest.rebased(currency)?.data;
+    let shocked = apply_shock(&rates, shocks)?;
+
+    let value = |rates: &Rates, holding: &Money| -> ForexResult<Decimal> {
+        match holding.currency() == currency {
+            true => Ok(holding.amount()),
+            false => {
+                Ok(holding.amount() * service::pair_rate(rates, holding.currency(), currency)?)
+            }
+        }
+    };
+    let mut valued = Vec::with_capacity(holdings.len());
+    for holding in holdings {
+        valued.push(ShockedHolding {
+            holding: *holding,
+            value: Money::new_money(currency, value(&rates, holding)?),
+            shocked_value: Money::new_money(currency, value(&shocked, holding)?),
+        });
+    }
+
+    let total: Decimal = valued.iter().map(|holding| holding.value.amount()).sum();
+    let shocked_total: Decimal = valued
+        .iter()
+        .map(|holding| holding.shocked_value.amount())
+        .sum();
+    let change = shocked_total - total;
+    let change_percent = match total.is_zero() {
+        true => Decimal::ZERO,
+        false => (change / total * dec!(100)).round_dp(2),
+    };
+    let mut shocks: Vec<RateShock> = shocks
+        .iter()
+        .map(|(currency, percent)| RateShock {
+            currency: *currency,
+            percent: *percent,
+        })
+        .collect();
+    shocks.sort_by_key(|shock| shock.currency.code());
+
+    Ok(ShockScenario {
+        currency,
+        rates_date: rates.date,
+        shocks,
+        shocked,
+        holdings: valued,
+        total: Money::new_money(currency, total),
+        shocked_total: Money::new_money(currency, shocked_total),
+        change: Money::new_money(currency, change),
+        change_percent,
+    })
+}
+
+/// percentage of days between start and end inclusive each currency enabled in this deployment has non-zero historical rate,
+/// e.g. crypto currencies have no rates before they're listed.
+pub async fn completeness<FS>(
+    storage: &FS,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> ForexResult<CompletenessReport>
+where
+    FS: ForexStorage,
+{
+    if start > end {
+        return Err(ForexError::client_error("start must not be after end"));
+    }
+    let (first_day, last_day) = (start.date_naive(), end.date_naive());
+    let days = (last_day - first_day).num_days() + 1;
+
+    let enabled = global::config().enabled_currencies();
+    let mut days_with_rate: HashMap<Currency, HashSet<NaiveDate>> = HashMap::new();
+    for rates in storage.get_historical_range(start, end).await? {
+        let day = rates.data.date.date_naive();
+        if rates.error.is_some() || day < first_day || day > last_day {
+            continue;
+        }
+        for &currency in enabled {
+            if !rates.data.rates.rate(currency).is_zero() {
+                days_with_rate.entry(currency).or_default().insert(day);
+            }
+        }
+    }
+
+    let currencies = enabled
+        .iter()
+        .map(|&currency| {
+            let count = days_with_rate.get(&currency).map_or(0, HashSet::len);
+            CurrencyCompleteness {
+                currency,
+                days_with_rate: count as u32,
+                percent: (Decimal::from(count) * dec!(100) / Decimal::from(days)).round_dp(2),
+            }
+        })
+        .collect();
+
+    Ok(CompletenessReport {
+        start,
+        end,
+        days: days as u32,
+        currencies,
+    })
+}
+
+/// open, close, min, max and average rate of pair (base, quote) per ISO week or calendar month
+/// of historical rates between start and end. periods without rates are left out.
+pub async fn aggregate<FS>(
+    storage: &FS,
+    pair: (Currency, Currency),
+    period: AggregationPeriod,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> ForexResult<PairAggregates>
+where
+    FS: ForexStorage + ForexStorageSparse,
+{
+    let (base, quote) = pair;
+    if start > end {
+        return Err(ForexError::client_error("start must not be after end"));
+    }
+
+    let mut points = vec![];
+    for mut rates in storage
+        .get_historical_range_of(&[base, quote], start, end)
+        .await?
+    {
+        if rates.error.is_some() {
+            continue;
+        }
+        redenomination::to_current_units(&mut rates.data);
+        let rate = service::pair_rate(&rates.data, base, quote)?;
+        if rate.is_zero() {
+            continue;
+        }
+        points.push(RatePoint {
+            date: rates.data.date,
+            rate,
+        });
+    }
+    points.sort_by_key(|point| point.date);
+
+    let mut aggregates: Vec<RateAggregate> = vec![];
+    let mut sum = Decimal::ZERO;
+    for point in points {
+        let label = period.label(point.date);
+        match aggregates.last_mut() {
+            Some(aggregate) if aggregate.period == label => {
+                aggregate.end = point.date;
+                aggregate.close = point.rate;
+                aggregate.min = aggregate.min.min(point.rate);
+                aggregate.max = aggregate.max.max(point.rate);
+                aggregate.days += 1;
+                sum += point.rate;
+            }
+            last => {
+                if let Some(aggregate) = last {
+                    aggregate.average = sum / Decimal::from(aggregate.days);
+                }
+                sum = point.rate;
+                aggregates.push(RateAggregate {
+                    period: label,
+                    start: point.date,
+                    end: point.date,
+                    open: point.rate,
+                    close: point.rate,
+                    min: point.rate,
+                    max: point.rate,
+                    average: point.rate,
+                    days: 1,
+                });
+            }
+        }
+    }
+    if let Some(aggregate) = aggregates.last_mut() {
+        aggregate.average = sum / Decimal::from(aggregate.days);
+    }
+
+    Ok(PairAggregates {
+        base,
+        quote,
+        period,
+        start,
+        end,
+        aggregates,
+    })
+}
+
+/// average and end of month rate of 1 base in each currency enabled in this deployment per calendar month of the year,
+/// from historical rates, e.g. for accounting.
+pub async fn monthly_rates<FS>(
+    storage: &FS,
+    year: i32,
+    base: Currency,
+) -> ForexResult<MonthlyRatesTable>
+where
+    FS: ForexStorage,
+{
+    let (Some(first_day), Some(last_day)) = (
+        NaiveDate::from_ymd_opt(year, 1, 1),
+        NaiveDate::from_ymd_opt(year, 12, 31),
+    ) else {
+        return Err(ForexError::client_error("year is out of range"));
+    };
+    let start = first_day.and_time(NaiveTime::MIN).and_utc();
+    let end = last_day.and_time(NaiveTime::MIN).and_utc() + TimeDelta::days(1);
+
+    let mut by_month: BTreeMap<String, Vec<Rates>> = BTreeMap::new();
+    for mut rates in storage.get_historical_range(start, end).await? {
+        if rates.error.is_some() || rates.data.date.year() != year {
+            continue;
+        }
+        redenomination::to_current_units(&mut rates.data);
+        by_month
+            .entry(AggregationPeriod::Month.label(rates.data.date))
+            .or_default()
+            .push(rates.data);
+    }
+
+    let currencies = global::config().enabled_currencies();
+    let months = by_month
+        .into_iter()
+        .map(|(month, mut days)| {
+            days.sort_by_key(|rates| rates.date);
+            let (average, end_of_month) = currencies
+                .iter()
+                .map(|&currency| {
+                    let rates: Vec<Decimal> = days
+                        .iter()
+                        .filter_map(|rates| day_rate(rates, base, currency))
+                        .collect();
+                    let average = (!rates.is_empty())
+                        .then(|| rates.iter().sum::<Decimal>() / Decimal::from(rates.len()));
+                    (average, rates.last().copied())
+                })
+                .unzip();
+            MonthlyRates {
+                month,
+                days: days.len() as u32,
+                average,
+                end_of_month,
+            }
+        })
+        .collect();
+
+    Ok(MonthlyRatesTable {
+        year,
+        base,
+        currencies: currencies.to_vec(),
+        months,
+    })
+}
+
+/// rate of 1 base in the currency, none if either has no rate.
+fn day_rate(rates: &Rates, base: Currency, currency: Currency) -> Option<Decimal> {
+    if rates.rates.rate(base).is_zero() || rates.rates.rate(currency).is_zero() {
+        return None;
+    }
+
+    service::pair_rate(rates, base, currency).ok()
+}
+
+/// monthly rates table as csv, a row per month with average and end of month columns per currency.
+/// currencies without rate in a month are left empty.
+pub fn monthly_rates_csv(table: &MonthlyRatesTable) -> ForexResult<String> {
+    let mut wtr = csv::Writer::from_writer(vec![]);
+
+    let mut header = vec!["month".to_string(), "days".to_string()];
+    for currency in &table.currencies {
+        header.push(format!("{} average", currency.code()));
+        header.push(format!("{} end of month", currency.code()));
+    }
+    wtr.write_record(&header)
+        .context("monthly rates csv write header")
+        .as_internal_err()?;
+
+    let cell =
+        |rate: Option<Decimal>| rate.map_or(String::new(), |rate| rate.normalize().to_string());
+    for month in &table.months {
+        let mut record = vec![month.month.clone(), month.days.to_string()];
+        for (&average, &end_of_month) in month.average.iter().zip(&month.end_of_month) {
+            record.push(cell(average));
+            record.push(cell(end_of_month));
+        }
+        wtr.write_record(&record)
+            .context("monthly rates csv write row")
+            .as_internal_err()?;
+    }
+
+    let bytes = wtr
+        .into_inner()
+        .context("monthly rates csv flush")
+        .as_internal_err()?;
+
+    let content = String::from_utf8(bytes)
+        .context("monthly rates csv into string")
+        .as_internal_err()?;
+
+    Ok(content)
+}
+
+/// max days of sparklines, as many daily rates as kept by storage.
+pub const SPARKLINE_MAX_DAYS: usize = 90;
+
+/// daily rates of base in each quote over latest days, read from sparklines precomputed by storage.
+pub async fn sparklines<FS>(
+    storage: &FS,
+    base: Currency,
+    quotes: &[Currency],
+    days: usize,
+) -> ForexResult<Vec<PairSparkline>>
+where
+    FS: ForexStorageSparklines,
+{
+    if days == 0 || days > SPARKLINE_MAX_DAYS {
+        return Err(ForexError::client_error(
+            format!("days must be between 1 and {}", SPARKLINE_MAX_DAYS).as_str(),
+        ));
+    }
+
+    let base_sparkline = storage.get_sparkline(base).await?;
+    let mut ret = Vec::with_capacity(quotes.len());
+    for &quote in quotes {
+        let quote_sparkline = match quote == base {
+            true => base_sparkline.clone(),
+            false => storage.get_sparkline(quote).await?,
+        };
+        ret.push(pair_sparkline(&base_sparkline, &quote_sparkline, days));
+    }
+
+    Ok(ret)
+}
+
+/// rates of 1 base in quote on latest days both sparklines have rates of.
+pub fn pair_sparkline(base: &Sparkline, quote: &Sparkline, days: usize) -> PairSparkline {
+    let base_rates: HashMap<_, _> = base
+        .points
+        .iter()
+        .map(|point| (point.date.date_naive(), point.rate))
+        .collect();
+    let mut points: Vec<RatePoint> = quote
+        .points
+        .iter()
+        .filter_map(|point| {
+            let base_rate = base_rates.get(&point.date.date_naive())?;
+            (!base_rate.is_zero()).then(|| RatePoint {
+                date: point.date,
+                rate: point.rate / base_rate,
+            })
+        })
+        .collect();
+    if points.len() > days {
+        points.drain(..points.len() - days);
+    }
+
+    PairSparkline {
+        base: base.currency,
+        quote: quote.currency,
+        points,
+    }
+}
+
+/// rebuild sparklines from latest historical rates, e.g. for rates stored before sparklines were maintained.
+pub async fn rebuild_sparklines<FS>(storage: &FS) -> ForexResult<()>
+where
+    FS: ForexStorageSparklines,
+{
+    storage.rebuild_sparklines().await
+}
@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+
+use chrono::{TimeZone, Utc};
+use rust_decimal_macros::dec;
+
+use super::{
+    Currency, Money,
+    analytics::{apply_shock, extremes, pair_sparkline, shock_scenario},
+    entity::{RatePoint, RateShock, Rates, RatesData, Sparkline},
+};
+
+#[tokio::test]
+async fn test_extremes() {
+    let storage = super::mock::ForexStorageSuccessMock;
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let end = Utc.with_ymd_and_hms(2022, 12, 31, 0, 0, 0).unwrap();
+
+    let ret = extremes(&storage, (Currency::USD, Currency::IDR), start, end)
+        .await
+        .unwrap();
+    assert_eq!(ret.lowest.rate, dec!(13893.633074));
+    assert_eq!(
+        ret.lowest.date,
+        "2020-01-01T23:59:58Z"
+            .parse::<chrono::DateTime<Utc>>()
+            .unwrap()
+    );
+    assert_eq!(ret.highest.rate, dec!(15588.665563));
+    assert_eq!(ret.current.rate, dec!(16461));
+    assert_eq!(ret.percentile, dec!(100));
+
+    let ret = extremes(&storage, (Currency::USD, Currency::IDR), end, start).await;
+    assert!(ret.is_err());
+}
+
+#[tokio::test]
+async fn test_travel_budget() {
+    use super::{Money, analytics::travel_budget};
+
+    let storage = super::mock::ForexStorageSuccessMock;
+    let start = Utc.with_ymd_and_hms(2025, 4, 1, 0, 0, 0).unwrap();
+    let end = Utc.with_ymd_and_hms(2025, 4, 10, 0, 0, 0).unwrap();
+
+    let ret = travel_budget(&storage, Money::USD(dec!(1000)), Currency::IDR, start, end)
+        .await
+        .unwrap();
+    assert_eq!(ret.days, 10);
+    assert_eq!(ret.average_rate, dec!(14595.93715925));
+    assert_eq!(ret.total, Money::IDR(dec!(14595937.15925)));
+    assert_eq!(ret.daily, Money::IDR(dec!(1459593.715925)));
+    assert!(ret.volatility > dec!(0));
+    assert!(ret.conservative_rate < ret.average_rate);
+    assert!(ret.conservative_daily.amount() < ret.daily.amount());
+
+    let ret = travel_budget(&storage, Money::USD(dec!(1000)), Currency::IDR, end, start).await;
+    assert!(ret.is_err());
+}
+
+#[test]
+fn test_pair_sparkline() {
+    let point = |day: u32, rate| RatePoint {
+        date: Utc.with_ymd_and_hms(2025, 3, day, 0, 0, 0).unwrap(),
+        rate,
+    };
+    let mut eur = Sparkline::new(Currency::EUR);
+    let mut idr = Sparkline::new(Currency::IDR);
+    for (day, eur_rate, idr_rate) in [
+        (1, dec!(0.5), dec!(16000)),
+        (2, dec!(0.4), dec!(16400)),
+        (3, dec!(0.5), dec!(16500)),
+    ] {
+        eur.record(point(day, eur_rate), 90);
+        idr.record(point(day, idr_rate), 90);
+    }
+    // IDR has a day EUR doesn't have
+    idr.record(point(4, dec!(16600)), 90);
+
+    let ret = pair_sparkline(&eur, &idr, 30);
+    assert_eq!(ret.base, Currency::EUR);
+    assert_eq!(ret.quote, Currency::IDR);
+    let rates: Vec<_> = ret.points.iter().map(|point| point.rate).collect();
+    assert_eq!(rates, vec![dec!(32000), dec!(41000), dec!(33000)]);
+
+    let ret = pair_sparkline(&eur, &idr, 2);
+    assert_eq!(ret.points.len(), 2);
+    assert_eq!(ret.points[0].rate, dec!(41000));
+}
+
+#[test]
+fn test_sparkline_record() {
+    let date = |day: u32| Utc.with_ymd_and_hms(2025, 3, day, 0, 0, 0).unwrap();
+    let mut sparkline = Sparkline::new(Currency::IDR);
+    for day in [3, 1, 2, 4] {
+        sparkline.record(
+            RatePoint {
+                date: date(day),
+                rate: dec!(16000),
+            },
+            3,
+        );
+    }
+    // same date replaces its rate
+    sparkline.record(
+        RatePoint {
+            date: date(4),
+            rate: dec!(16500),
+        },
+        3,
+    );
+
+    let dates: Vec<_> = sparkline.points.iter().map(|point| point.date).collect();
+    assert_eq!(dates, vec![date(2), date(3), date(4)]);
+    assert_eq!(sparkline.points[2].rate, dec!(16500));
+}
+
+#[tokio::test]
+async fn test_completeness() {
+    use super::analytics::completeness;
+
+    let storage = super::mock::ForexStorageSuccessMock;
+    let start = Utc.with_ymd_and_hms(2021, 12, 16, 0, 0, 0).unwrap();
+    let end = Utc.with_ymd_and_hms(2021, 12, 25, 0, 0, 0).unwrap();
+
+    // only 2021-12-20 of mocked historical rates is within the period
+    let ret = completeness(&storage, start, end).await.unwrap();
+    assert_eq!(ret.days, 10);
+    let idr = ret
+        .currencies
+        .iter()
+        .find(|completeness| completeness.currency == Currency::IDR)
+        .unwrap();
+    assert_eq!(idr.days_with_rate, 1);
+    assert_eq!(idr.percent, dec!(10));
+
+    let ret = completeness(&storage, end, start).await;
+    assert!(ret.is_err());
+}
+
+#[tokio::test]
+async fn test_aggregate() {
+    use super::{analytics::aggregate, entity::AggregationPeriod};
+
+    let storage = super::mock::ForexStorageSuccessMock;
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let end = Utc.with_ymd_and_hms(2022, 12, 31, 0, 0, 0).unwrap();
+    let pair = (Currency::USD, Currency::IDR);
+
+    let ret = aggregate(&storage, pair, AggregationPeriod::Month, start, end)
+        .await
+        .unwrap();
+    let periods: Vec<_> = ret.aggregates.iter().map(|a| a.period.as_str()).collect();
+    assert_eq!(periods, vec!["2020-01", "2021-07", "2021-12", "2022-12"]);
+    assert_eq!(ret.aggregates[0].open, dec!(13893.633074));
+    assert_eq!(ret.aggregates[0].average, dec!(13893.633074));
+    assert_eq!(ret.aggregates[3].close, dec!(15588.665563));
+    assert!(ret.aggregates.iter().all(|a| a.days == 1));
+
+    let ret = aggregate(&storage, pair, AggregationPeriod::Week, start, end)
+        .await
+        .unwrap();
+    assert_eq!(ret.aggregates[0].period, "2020-W01");
+    assert_eq!(ret.aggregates[3].period, "2022-W51");
+
+    // first days of year may belong to last ISO week of previous year
+    let date = Utc.with_ymd_and_hms(2021, 1, 1, 0, 0, 0).unwrap();
+    assert_eq!(AggregationPeriod::Week.label(date), "2020-W53");
+
+    let ret = aggregate(&storage, pair, AggregationPeriod::Month, end, start).await;
+    assert!(ret.is_err());
+}
+
+#[tokio::test]
+async fn test_monthly_rates() {
+    use super::analytics::{monthly_rates, monthly_rates_csv};
+
+    let storage = super::mock::ForexStorageSuccessMock;
+
+    let ret = monthly_rates(&storage, 2021, Currency::USD).await.unwrap();
+    assert_eq!(ret.year, 2021);
+    let months: Vec<&str> = ret
+        .months
+        .iter()
+        .map(|month| month.month.as_str())
+        .collect();
+    assert_eq!(months, ["2021-07", "2021-12"]);
+    let idr = ret
+        .currencies
+        .iter()
+        .position(|&currency| currency == Currency::IDR)
+        .unwrap();
+    assert_eq!(ret.months[0].days, 1);
+    assert_eq!(ret.months[0].average[idr], Some(dec!(14512.7)));
+    assert_eq!(ret.months[1].end_of_month[idr], Some(dec!(14388.75)));
+
+    let csv = monthly_rates_csv(&ret).unwrap();
+    let mut lines = csv.lines();
+    let header = lines.next().unwrap();
+    assert!(header.starts_with("month,days,"));
+    assert!(header.contains("IDR average,IDR end of month"));
+    assert!(lines.next().unwrap().starts_with("2021-07,1,"));
+    assert_eq!(lines.count(), 1);
+
+    // rebased
+    let ret = monthly_rates(&storage, 2021, Currency::IDR).await.unwrap();
+    let usd = ret
+        .currencies
+        .iter()
+        .position(|&currency| currency == Currency::USD)
+        .unwrap();
+    assert_eq!(ret.months[1].average[usd], Some(dec!(1) / dec!(14388.75)));
+}
+
+#[test]
+fn test_apply_shock() {
+    let rates = Rates {
+        date: Utc.with_ymd_and_hms(2025, 3, 4, 0, 0, 0).unwrap(),
+        base: Currency::USD,
+        rates: RatesData {
+            usd: dec!(1),
+            eur: dec!(0.5),
+            idr: dec!(16000),
+            ..Default::default()
+        },
+    };
+
+    // idr weakens 20%, eur strengthens 25%
+    let shocks = HashMap::from([(Currency::IDR, dec!(-20)), (Currency::EUR, dec!(25))]);
+    let ret = apply_shock(&rates, &shocks).unwrap();
+    assert_eq!(ret.rates.idr, dec!(20000));
+    assert_eq!(ret.rates.eur, dec!(0.4));
+    assert_eq!(ret.rates.usd, dec!(1));
+
+    let invalid = vec![
+        HashMap::from([(Currency::USD, dec!(10))]),
+        HashMap::from([(Currency::IDR, dec!(-100))]),
+        HashMap::from([(Currency::JPY, dec!(10))]),
+    ];
+    for shocks in invalid {
+        assert!(apply_shock(&rates, &shocks).is_err(), "{:?}", shocks);
+    }
+}
+
+#[tokio::test]
+async fn test_shock_scenario() {
+    let storage = super::mock::ForexStorageSuccessMock;
+    let shocks = HashMap::from([(Currency::USD, dec!(10))]);
+    let holdings = vec![Money::USD(dec!(100)), Money::IDR(dec!(1000000))];
+
+    // latest USD/IDR of mock is 16461, usd strengthens 10% against idr
+    let ret = shock_scenario(&storage, &shocks, &holdings, Currency::IDR)
+        .await
+        .unwrap();
+    assert_eq!(ret.shocked.base, Currency::IDR);
+    assert_eq!(ret.holdings[0].value.amount().round_dp(4), dec!(1646100));
+    assert_eq!(
+        ret.holdings[0].shocked_value.amount().round_dp(4),
+        dec!(1810710)
+    );
+    assert_eq!(ret.holdings[1].shocked_value, Money::IDR(dec!(1000000)));
+    assert_eq!(ret.change.amount().round_dp(4), dec!(164610));
+    assert_eq!(ret.change_percent, dec!(6.22));
+    assert_eq!(
+        ret.shocks,
+        vec![RateShock {
+            currency: Currency::USD,
+            percent: dec!(10)
+        }]
+    );
+}
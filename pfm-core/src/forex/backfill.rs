@@ -0,0 +1,238 @@
+// backfill.rs throttle-aware backfill of missing historical rates: dates split into batches within provider rate limit
+// and remaining quota, fetched batch by batch with progress checkpoint stored per provider and base, so interrupted backfill
+// resumes where it stopped.
+
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::{DateTime, NaiveDate, Utc};
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+use tracing::instrument;
+
+use super::{
+    Currency, ForexError, ForexResult,
+    entity::{
+        BackfillBatch, BackfillCheckpoint, BackfillPlan, BackfillReport, PollMode, RateLimit,
+    },
+    interface::{
+        ForexHistoricalRates, ForexProviderCapabilities, ForexStorage, ForexStorageBackfill,
+    },
+    service,
+};
+use crate::error::AsInternalError;
+
+/// calls of providers without rate limit in their capabilities.
+pub const DEFAULT_RATE_LIMIT: RateLimit = RateLimit {
+    calls: 5,
+    window_secs: 1,
+};
+
+/// rate limit and remaining quota of the provider to plan backfill within, all dates are within quota of unmetered provider.
+/// exhausted quota fails early, before planning and calling the provider.
+pub async fn limits<FX>(forex: &FX) -> ForexResult<(RateLimit, u32)>
+where
+    FX: ForexProviderCapabilities + Sync,
+{
+    let rate_limit = forex
+        .capabilities()
+        .rate_limit
+        .unwrap_or(DEFAULT_RATE_LIMIT);
+    match forex.quota_remaining().await? {
+        Some(0) => Err(ForexError::client_error(
+            "quota of the provider is exhausted, backfill after it renews",
+        )),
+        Some(quota_remaining) => Ok((rate_limit, quota_remaining)),
+        None => Ok((rate_limit, u32::MAX)),
+    }
+}
+
+/// split missing dates into batches of rate limit calls, the oldest first.
+/// dates beyond remaining quota are deferred, eta counts pauses between batches assuming calls finish within the window.
+pub fn schedule(
+    mut dates: Vec<DateTime<Utc>>,
+    quota_remaining: u32,
+    rate_limit: RateLimit,
+) -> ForexResult<BackfillPlan> {
+    if rate_limit.calls == 0 {
+        return Err(ForexError::client_error(
+            "rate limit of backfill must allow at least 1 call",
+        ));
+    }
+
+    dates.sort();
+    dates.dedup_by_key(|date| date.date_naive());
+    let deferred = dates.split_off(dates.len().min(quota_remaining as usize));
+
+    let chunks = dates.chunks(rate_limit.calls as usize);
+    let last = chunks.len().saturating_sub(1);
+    let batches: Vec<BackfillBatch> = chunks
+        .enumerate()
+        .map(|(index, dates)| BackfillBatch {
+            dates: dates.to_vec(),
+            sleep_secs: match index == last {
+                true => 0,
+                false => rate_limit.window_secs,
+            },
+        })
+        .collect();
+
+    Ok(BackfillPlan {
+        rate_limit,
+        quota_remaining,
+        mode: PollMode::SkipExisting,
+        eta_secs: batches.iter().map(|batch| batch.sleep_secs).sum(),
+        batches,
+        skipped: vec![],
+        deferred,
+        quota_cost: dates.len(),
+    })
+}
+
+/// plan backfill of the dates missing from storage or stored with error, and not yet done according to checkpoint,
+/// without calling the provider nor writing anything.
+/// with PollMode::Always every date served by the provider is fetched again regardless of storage and checkpoint.
+#[instrument(skip(forex, storage, dates, checkpoint))]
+pub async fn plan<FX, FS>(
+    forex: &FX,
+    storage: &FS,
+    dates: Vec<DateTime<Utc>>,
+    base: Currency,
+    mode: PollMode,
+    quota_remaining: u32,
+    rate_limit: RateLimit,
+    checkpoint: &BackfillCheckpoint,
+) -> ForexResult<BackfillPlan>
+where
+    FX: ForexProviderCapabilities,
+    FS: ForexStorage,
+{
+    let done: BTreeSet<NaiveDate> = match mode {
+        PollMode::Always => BTreeSet::new(),
+        PollMode::SkipExisting => checkpoint
+            .done
+            .iter()
+            .map(|date| date.date_naive())
+            .collect(),
+    };
+    let (mut skipped, dates): (Vec<_>, Vec<_>) = dates
+        .into_iter()
+        .partition(|date| done.contains(&date.date_naive()));
+
+    let report = service::plan_historical_rates(forex, storage, dates, base, mode).await?;
+    let mut plan = schedule(report.dates, quota_remaining, rate_limit)?;
+    skipped.extend(report.skipped);
+    skipped.sort();
+    plan.skipped = skipped;
+    plan.mode = mode;
+
+    Ok(plan)
+}
+
+/// fetch and store dates of the plan batch by batch, pausing after each batch as planned.
+/// checkpoint of the provider and base is updated after every batch, and stopped between batches once cancelled.
+/// unless fetching again with PollMode::Always, dates stored since planning are skipped without calling the provider,
+/// so only provider calls count against quota.
+#[instrument(skip(forex, storage, plan, cancel))]
+pub async fn run<FX, FS>(
+    forex: &FX,
+    storage: &FS,
+    plan: &BackfillPlan,
+    provider: &str,
+    base: Currency,
+    cancel: &CancellationToken,
+) -> ForexResult<BackfillReport>
+where
+    FX: ForexHistoricalRates + ForexProviderCapabilities + Clone + Send + Sync + 'static,
+    FS: ForexStorage + ForexStorageBackfill + Clone + Send + Sync + 'static,
+{
+    let mut checkpoint = read_checkpoint(storage, provider, base).await?;
+    let mut report = BackfillReport {
+        skipped: plan.skipped.clone(),
+        ..Default::default()
+    };
+    let mode = plan.mode;
+
+    for (index, batch) in plan.batches.iter().enumerate() {
+        if cancel.is_cancelled() {
+            break;
+        }
+
+        let mut tasks = JoinSet::new();
+        for date in batch.dates.iter().copied() {
+            let forex = forex.clone();
+            let storage = storage.clone();
+            let cancel = cancel.clone();
+            tasks.spawn(async move {
+                if mode == PollMode::SkipExisting
+                    && storage
+                        .get_historical(date)
+                        .await
+                        .is_ok_and(|rates| rates.error.is_none())
+                {
+                    return (date, None);
+                }
+                let ret =
+                    service::poll_historical_rates(&forex, &storage, date, base, mode, &cancel)
+                        .await;
+                (date, Some(ret))
+            });
+        }
+
+        while let Some(ret) = tasks.join_next().await {
+            let (date, ret) = ret.context("backfill join poll task").as_internal_err()?;
+            let Some(ret) = ret else {
+                report.skipped.push(date);
+                continue;
+            };
+            report.quota_cost += 1;
+            match ret {
+                Ok(rates) if rates.error.is_none() => report.dates.push(date),
+                Ok(rates) => {
+                    tracing::warn!("backfill of {} failed: {:?}", date, rates.error);
+                    report.failed.push(date);
+                }
+                Err(err) => {
+                    tracing::warn!("backfill of {} failed: {}", date, err);
+                    report.failed.push(date);
+                }
+            }
+        }
+        report.dates.sort();
+        report.skipped.sort();
+        report.failed.sort();
+
+        checkpoint.record(&report.dates);
+        storage
+            .insert_backfill_checkpoint(provider, base, &checkpoint)
+            .await?;
+        tracing::info!(
+            "backfill batch {}/{} done, {} dates fetched",
+            index + 1,
+            plan.batches.len(),
+            report.dates.len()
+        );
+
+        if batch.sleep_secs > 0 {
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = tokio::time::sleep(Duration::from_secs(batch.sleep_secs)) => {}
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// checkpoint of backfill of the provider's rates in the base, empty if backfill hasn't started.
+pub async fn read_checkpoint<FS>(
+    storage: &FS,
+    provider: &str,
+    base: Currency,
+) -> ForexResult<BackfillCheckpoint>
+where
+    FS: ForexStorageBackfill,
+{
+    storage.get_backfill_checkpoint(provider, base).await
+}
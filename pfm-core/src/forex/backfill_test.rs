@@ -0,0 +1,287 @@
+use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use rust_decimal_macros::dec;
+use tokio_util::sync::CancellationToken;
+
+use crate::forex::{
+    Currency, ForexError, ForexResult, Money,
+    backfill::{self, read_checkpoint},
+    entity::{BackfillCheckpoint, PollMode, ProviderCapabilities, RateLimit, Rates, RatesResponse},
+    interface::{ForexHistoricalRates, ForexProviderCapabilities, ForexStorage},
+};
+use crate::global::test_support::temp_storage;
+
+const PROVIDER: &str = "backfill_mock";
+
+fn day(day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 1, day, 0, 0, 0).unwrap()
+}
+
+/// rates at the requested date, failing on the dates.
+#[derive(Clone)]
+struct ProviderMock {
+    failing: Vec<DateTime<Utc>>,
+}
+
+impl ForexProviderCapabilities for ProviderMock {
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            history_start: Some(day(2)),
+            ..Default::default()
+        }
+    }
+}
+
+/// provider reporting its remaining quota.
+struct MeteredMock(u32);
+
+#[async_trait]
+impl ForexProviderCapabilities for MeteredMock {
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities::default().with_rate_limit(RateLimit {
+            calls: 3,
+            window_secs: 10,
+        })
+    }
+
+    async fn quota_remaining(&self) -> ForexResult<Option<u32>> {
+        Ok(Some(self.0))
+    }
+}
+
+#[async_trait]
+impl ForexHistoricalRates for ProviderMock {
+    async fn historical_rates(
+        &self,
+        date: DateTime<Utc>,
+        base: Currency,
+    ) -> ForexResult<RatesResponse<Rates>> {
+        if self.failing.contains(&date) {
+            return Err(ForexError::internal_error("provider mock failing"));
+        }
+        let mut rates = RatesResponse::new(
+            "backfill_mock".to_string(),
+            Rates {
+                date,
+                base,
+                rates: Default::default(),
+            },
+        );
+        rates.data.rates.set_rate(Money::USD(dec!(1)));
+        rates.data.rates.set_rate(Money::IDR(dec!(16000)));
+
+        Ok(rates)
+    }
+}
+
+#[test]
+fn test_schedule_backfill() {
+    let rate_limit = RateLimit {
+        calls: 2,
+        window_secs: 60,
+    };
+    let dates = vec![day(5), day(1), day(3), day(2), day(4), day(3)];
+
+    let plan = backfill::schedule(dates.clone(), 4, rate_limit).unwrap();
+    let batches: Vec<(Vec<DateTime<Utc>>, u64)> = plan
+        .batches
+        .iter()
+        .map(|batch| (batch.dates.clone(), batch.sleep_secs))
+        .collect();
+    assert_eq!(
+        batches,
+        vec![(vec![day(1), day(2)], 60), (vec![day(3), day(4)], 0)]
+    );
+    assert_eq!(plan.deferred, vec![day(5)]);
+    assert_eq!(plan.quota_cost, 4);
+    assert_eq!(plan.eta_secs, 60);
+
+    let plan = backfill::schedule(dates.clone(), 0, rate_limit).unwrap();
+    assert!(plan.batches.is_empty());
+    assert_eq!(plan.deferred.len(), 5);
+    assert_eq!(plan.eta_secs, 0);
+
+    let no_calls = RateLimit {
+        calls: 0,
+        window_secs: 60,
+    };
+    assert!(backfill::schedule(dates, 10, no_calls).is_err());
+}
+
+#[tokio::test]
+async fn test_backfill_limits() {
+    let rate_limit = RateLimit {
+        calls: 3,
+        window_secs: 10,
+    };
+    let limits = backfill::limits(&MeteredMock(42)).await.unwrap();
+    assert_eq!(limits, (rate_limit, 42));
+    assert!(backfill::limits(&MeteredMock(0)).await.is_err());
+
+    // unmetered provider without rate limit
+    let forex = ProviderMock { failing: vec![] };
+    let limits = backfill::limits(&forex).await.unwrap();
+    assert_eq!(limits, (backfill::DEFAULT_RATE_LIMIT, u32::MAX));
+
+    // providers not reporting usage have whole quota of their plan
+    let forex = crate::forex_impl::registry::Provider::new("planned")
+        .with_capabilities(ProviderCapabilities::default().with_quota(5000));
+    let limits = backfill::limits(&forex).await.unwrap();
+    assert_eq!(limits.1, 5000);
+}
+
+#[tokio::test]
+async fn test_run_backfill_resumes() {
+    let (storage, _root) = temp_storage("backfill");
+    let rate_limit = RateLimit {
+        calls: 2,
+        window_secs: 0,
+    };
+    let dates: Vec<DateTime<Utc>> = (0..6).map(|n| day(1) + TimeDelta::days(n)).collect();
+    let cancel = CancellationToken::new();
+
+    // day 1 is before provider's history, day 4 fails
+    let forex = ProviderMock {
+        failing: vec![day(4)],
+    };
+    let checkpoint = read_checkpoint(&storage, PROVIDER, Currency::USD)
+        .await
+        .unwrap();
+    assert_eq!(checkpoint, BackfillCheckpoint::default());
+    let plan = backfill::plan(
+        &forex,
+        &storage,
+        dates.clone(),
+        Currency::USD,
+        PollMode::SkipExisting,
+        3,
+        rate_limit,
+        &checkpoint,
+    )
+    .await
+    .unwrap();
+    assert_eq!(plan.skipped, vec![day(1)]);
+    assert_eq!(plan.deferred, vec![day(5), day(6)]);
+    assert_eq!(plan.quota_cost, 3);
+
+    // day 3 stored since planning is skipped without calling the provider
+    let stored = forex.historical_rates(day(3), Currency::USD).await.unwrap();
+    storage.insert_historical(day(3), &stored).await.unwrap();
+    let report = backfill::run(&forex, &storage, &plan, PROVIDER, Currency::USD, &cancel)
+        .await
+        .unwrap();
+    assert_eq!(report.dates, vec![day(2)]);
+    assert_eq!(report.skipped, vec![day(1), day(3)]);
+    assert_eq!(report.failed, vec![day(4)]);
+    assert_eq!(report.quota_cost, 2);
+
+    let checkpoint = read_checkpoint(&storage, PROVIDER, Currency::USD)
+        .await
+        .unwrap();
+    assert_eq!(checkpoint.done, vec![day(2)]);
+    // checkpoint is kept per provider and base
+    let other = read_checkpoint(&storage, "other", Currency::USD)
+        .await
+        .unwrap();
+    assert_eq!(other, BackfillCheckpoint::default());
+    let other = read_checkpoint(&storage, PROVIDER, Currency::EUR)
+        .await
+        .unwrap();
+    assert_eq!(other, BackfillCheckpoint::default());
+
+    // next run continues with failed and deferred dates
+    let forex = ProviderMock { failing: vec![] };
+    let plan = backfill::plan(
+        &forex,
+        &storage,
+        dates,
+        Currency::USD,
+        PollMode::SkipExisting,
+        10,
+        rate_limit,
+        &checkpoint,
+    )
+    .await
+    .unwrap();
+    assert_eq!(plan.skipped, vec![day(1), day(2), day(3)]);
+    assert_eq!(plan.quota_cost, 3);
+    let report = backfill::run(&forex, &storage, &plan, PROVIDER, Currency::USD, &cancel)
+        .await
+        .unwrap();
+    assert_eq!(report.dates, vec![day(4), day(5), day(6)]);
+    assert!(report.failed.is_empty());
+
+    let checkpoint = read_checkpoint(&storage, PROVIDER, Currency::USD)
+        .await
+        .unwrap();
+    assert_eq!(checkpoint.done, vec![day(2), day(4), day(5), day(6)]);
+    assert!(storage.get_historical(day(6)).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_run_backfill_fetches_error_marked_dates_again() {
+    let (storage, _root) = temp_storage("backfill-again");
+    let rate_limit = RateLimit {
+        calls: 5,
+        window_secs: 0,
+    };
+    let cancel = CancellationToken::new();
+    let forex = ProviderMock { failing: vec![] };
+
+    // day 2 is stored, day 3 is stored marked with error of the provider
+    let stored = forex.historical_rates(day(2), Currency::USD).await.unwrap();
+    storage.insert_historical(day(2), &stored).await.unwrap();
+    let failed = RatesResponse::err(day(3), ForexError::internal_error("provider down"));
+    storage.insert_historical(day(3), &failed).await.unwrap();
+
+    let dates = vec![day(2), day(3), day(4)];
+    let plan = backfill::plan(
+        &forex,
+        &storage,
+        dates.clone(),
+        Currency::USD,
+        PollMode::SkipExisting,
+        10,
+        rate_limit,
+        &BackfillCheckpoint::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(plan.skipped, vec![day(2)]);
+    assert_eq!(plan.quota_cost, 2);
+    let report = backfill::run(&forex, &storage, &plan, PROVIDER, Currency::USD, &cancel)
+        .await
+        .unwrap();
+    assert_eq!(report.dates, vec![day(3), day(4)]);
+    assert!(
+        storage
+            .get_historical(day(3))
+            .await
+            .unwrap()
+            .error
+            .is_none()
+    );
+
+    // fetching again ignores stored dates and checkpoint
+    let checkpoint = read_checkpoint(&storage, PROVIDER, Currency::USD)
+        .await
+        .unwrap();
+    let plan = backfill::plan(
+        &forex,
+        &storage,
+        dates.clone(),
+        Currency::USD,
+        PollMode::Always,
+        10,
+        rate_limit,
+        &checkpoint,
+    )
+    .await
+    .unwrap();
+    assert!(plan.skipped.is_empty());
+    let report = backfill::run(&forex, &storage, &plan, PROVIDER, Currency::USD, &cancel)
+        .await
+        .unwrap();
+    assert_eq!(report.dates, dates);
+    assert_eq!(report.quota_cost, 3);
+}
@@ -2,13 +2,14 @@ use anyhow::Context;
 use std::{fmt::Display, str::FromStr};
 
 use iso_currency::Currency as CurrencyLib;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use strum::{EnumIter, IntoEnumIterator};
 
 use super::{interface::ForexError, money::Money};
 use crate::error::AsClientError;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, EnumIter)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, EnumIter, JsonSchema)]
 pub enum Currency {
     //// fiat
 
@@ -59,6 +60,15 @@ pub enum Currency {
     ADA,
 }
 
+/// Group of currencies, providers usually support only some of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, EnumIter, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CurrencyKind {
+    Fiat,
+    Metal,
+    Crypto,
+}
+
 impl Currency {
     pub fn code(&self) -> &'static str {
         match self {
@@ -93,16 +103,65 @@ impl Currency {
         }
     }
 
+    pub fn kind(&self) -> CurrencyKind {
+        match self {
+            Self::XAU | Self::XAG | Self::XPT => CurrencyKind::Metal,
+            Self::BTC | Self::ETH | Self::SOL | Self::XRP | Self::ADA => CurrencyKind::Crypto,
+            _ => CurrencyKind::Fiat,
+        }
+    }
+
+    /// thousands and fraction separators of amounts written in locale of the currency, e.g. Rp1.250.000,50.
+    pub fn separators(&self) -> (char, char) {
+        match self {
+            Self::IDR => ('.', ','),
+            _ => (',', '.'),
+        }
+    }
+
+    /// all currencies of the kinds, in declaration order.
+    pub fn of_kinds(kinds: &[CurrencyKind]) -> impl Iterator<Item = Currency> + '_ {
+        Currency::iter().filter(|c| kinds.contains(&c.kind()))
+    }
+
+    pub fn fiat() -> impl Iterator<Item = Currency> {
+        Currency::of_kinds(&[CurrencyKind::Fiat])
+    }
+
+    pub fn metals() -> impl Iterator<Item = Currency> {
+        Currency::of_kinds(&[CurrencyKind::Metal])
+    }
+
+    pub fn crypto() -> impl Iterator<Item = Currency> {
+        Currency::of_kinds(&[CurrencyKind::Crypto])
+    }
+
     pub fn to_comma_separated_list_str() -> String {
-        let ret = Currency::iter()
+        Currency::to_comma_separated_list_str_of(Currency::iter())
+    }
+
+    /// codes of the currencies, e.g. symbols requested from provider supporting only some currencies.
+    pub fn to_comma_separated_list_str_of(
+        currencies: impl IntoIterator<Item = Currency>,
+    ) -> String {
+        currencies
+            .into_iter()
             .map(|c| c.to_string())
             .collect::<Vec<_>>()
-            .join(",");
-        ret
+            .join(",")
     }
 
     pub fn to_comma_separated_pair_list_str(base: Currency) -> String {
-        Currency::iter()
+        Currency::to_comma_separated_pair_list_str_of(base, Currency::iter())
+    }
+
+    /// pairs of base with each of the currencies other than base.
+    pub fn to_comma_separated_pair_list_str_of(
+        base: Currency,
+        currencies: impl IntoIterator<Item = Currency>,
+    ) -> String {
+        currencies
+            .into_iter()
             .filter(|&c| c != base)
             .map(|c| format!("{}{}", base.code(), format!("{:?}", c)))
             .collect::<Vec<_>>()
@@ -104,3 +104,31 @@ fn test_to_comma_separated_pair_list_str() {
     println!("{ret}");
     assert_eq!(ret.as_str(), expected);
 }
+
+#[test]
+fn test_currency_kinds() {
+    use super::currency::CurrencyKind;
+
+    assert_eq!(Currency::fiat().count(), 20);
+    assert_eq!(
+        Currency::to_comma_separated_list_str_of(Currency::metals()),
+        "XAU,XAG,XPT"
+    );
+    assert_eq!(
+        Currency::to_comma_separated_list_str_of(Currency::crypto()),
+        "BTC,ETH,SOL,XRP,ADA"
+    );
+    assert_eq!(
+        Currency::of_kinds(&[
+            CurrencyKind::Fiat,
+            CurrencyKind::Metal,
+            CurrencyKind::Crypto
+        ])
+        .count(),
+        Currency::currencies_count()
+    );
+    assert!(Currency::iter().all(|c| Currency::of_kinds(&[c.kind()]).any(|k| k == c)));
+
+    let ret = Currency::to_comma_separated_pair_list_str_of(Currency::BTC, Currency::crypto());
+    assert_eq!(ret.as_str(), "BTCETH,BTCSOL,BTCXRP,BTCADA");
+}
@@ -1,13 +1,21 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, TimeDelta, Utc};
 use rust_decimal::Decimal;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
 use std::fmt::Debug;
+use strum::IntoEnumIterator;
 use uuid::Uuid;
 
-use super::{currency::Currency, interface::ForexError, money::Money};
+use super::{
+    currency::{Currency, CurrencyKind},
+    interface::ForexError,
+    money::Money,
+};
 use crate::error::BaseError;
+use crate::global;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RatesResponse<T> {
     #[serde(alias = "id")]
     pub id: Uuid,
@@ -23,6 +31,10 @@ pub struct RatesResponse<T> {
 
     #[serde(alias = "error")]
     pub error: Option<String>,
+
+    /// sources of rates patched after polling, e.g. gaps filled from datasets or other providers.
+    #[serde(alias = "provenance", default, skip_serializing_if = "Vec::is_empty")]
+    pub provenance: Vec<RateProvenance>,
 }
 
 impl<T> RatesResponse<T>
@@ -36,11 +48,116 @@ where
             poll_date: Utc::now(),
             data,
             error: None,
+            provenance: vec![],
         }
     }
 }
 
 impl RatesResponse<Rates> {
+    /// record source of currency's rate, replacing previously recorded one.
+    pub fn record_provenance(
+        &mut self,
+        currency: Currency,
+        source: &str,
+        updated_at: DateTime<Utc>,
+    ) {
+        self.provenance
+            .retain(|provenance| provenance.currency != currency);
+        self.provenance.push(RateProvenance {
+            currency,
+            source: source.to_string(),
+            updated_at,
+        });
+    }
+
+    /// steps of converting from one currency to another through base of the rates.
+    /// each rate is dated by its provenance if it was patched after polling, otherwise by date of the rates.
+    pub fn conversion_steps(
+        &self,
+        from: Currency,
+        to: Currency,
+        base_amount: Decimal,
+    ) -> ConversionSteps {
+        let rate = |currency: Currency| ConversionRate {
+            currency,
+            rate: self.data.rates.rate(currency),
+            date: self
+                .provenance
+                .iter()
+                .find(|provenance| provenance.currency == currency)
+                .map_or(self.data.date, |provenance| provenance.updated_at),
+        };
+
+        ConversionSteps {
+            base: Money::new_money(self.data.base, base_amount),
+            from_rate: rate(from),
+            to_rate: rate(to),
+        }
+    }
+
+    /// rates of 1 base in each currency, converted through current base of the rates.
+    pub fn rebased(&self, base: Currency) -> Result<Self, ForexError> {
+        let mut rates = RatesData::default();
+        for currency in Currency::iter() {
+            let rate = match currency == base {
+                true => Money::new_money(base, Decimal::ONE),
+                false => Money::convert(
+                    &self.data.rates,
+                    Money::new_money(base, Decimal::ONE),
+                    currency,
+                )?,
+            };
+            rates.set_rate(rate);
+        }
+
+        Ok(Self {
+            id: self.id,
+            source: self.source.clone(),
+            poll_date: self.poll_date,
+            data: Rates {
+                date: self.data.date,
+                base,
+                rates,
+            },
+            error: self.error.clone(),
+            provenance: self.provenance.clone(),
+        })
+    }
+
+    /// check the rates are sane to be stored, rates of failed polls are stored as they are.
+    pub fn validate(&self) -> Result<(), Vec<RatesViolation>> {
+        if self.error.is_some() {
+            return Ok(());
+        }
+
+        let mut violations = vec![];
+        let rates = &self.data.rates;
+        if rates.rate(self.data.base) <= Decimal::ZERO {
+            violations.push(RatesViolation::EmptyBase {
+                base: self.data.base,
+            });
+        }
+        let usd = rates.rate(Currency::USD);
+        if usd <= Decimal::ZERO || (self.data.base == Currency::USD && usd != Decimal::ONE) {
+            violations.push(RatesViolation::UsdRate { rate: usd });
+        }
+        for currency in Currency::iter() {
+            let rate = rates.rate(currency);
+            if rate < Decimal::ZERO {
+                violations.push(RatesViolation::NegativeRate { currency, rate });
+            }
+        }
+        let date = self.data.date;
+        if date < RATES_MIN_DATE || date > Utc::now() + TimeDelta::days(1) {
+            violations.push(RatesViolation::ImplausibleDate { date });
+        }
+
+        match violations.is_empty() {
+            true => Ok(()),
+            false => Err(violations),
+        }
+    }
+
     pub(crate) fn err(date: DateTime<Utc>, err: ForexError) -> Self {
         Self {
             id: Uuid::new_v4(),
@@ -52,11 +169,61 @@ impl RatesResponse<Rates> {
                 rates: RatesData::default(),
             },
             error: Some(err.detail()),
+            provenance: vec![],
+        }
+    }
+}
+
+/// earliest date rates are plausibly stored for.
+pub const RATES_MIN_DATE: DateTime<Utc> = DateTime::from_timestamp(631152000, 0).unwrap();
+
+/// Reason rates are rejected from being stored, unless forced.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RatesViolation {
+    /// rate of base currency is missing.
+    EmptyBase {
+        base: Currency,
+    },
+
+    /// rate of USD is missing, or not 1 in USD based rates.
+    UsdRate {
+        rate: Decimal,
+    },
+
+    NegativeRate {
+        currency: Currency,
+        rate: Decimal,
+    },
+
+    /// date is before [`RATES_MIN_DATE`] or in the future.
+    ImplausibleDate {
+        date: DateTime<Utc>,
+    },
+}
+
+impl std::fmt::Display for RatesViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyBase { base } => write!(f, "rate of base {} is empty", base.code()),
+            Self::UsdRate { rate } => write!(f, "rate of USD {} is invalid", rate),
+            Self::NegativeRate { currency, rate } => {
+                write!(f, "rate of {} {} is negative", currency.code(), rate)
+            }
+            Self::ImplausibleDate { date } => write!(f, "date {} is implausible", date),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+/// Source of single rate patched into stored rates, distinguishing it from rates polled from provider.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RateProvenance {
+    pub currency: Currency,
+    pub source: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct Rates {
     #[serde(alias = "date")]
     pub date: DateTime<Utc>,
@@ -68,7 +235,7 @@ pub struct Rates {
     pub rates: RatesData,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct RatesData {
     #[serde(alias = "USD", default)]
     pub usd: Decimal,
@@ -203,7 +370,107 @@ impl From<Vec<Money>> for RatesData {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl RatesData {
+    /// rate of the currency relative to base currency.
+    pub fn rate(&self, currency: Currency) -> Decimal {
+        match currency {
+            Currency::USD => self.usd,
+            Currency::CAD => self.cad,
+            Currency::EUR => self.eur,
+            Currency::GBP => self.gbp,
+            Currency::CHF => self.chf,
+            Currency::RUB => self.rub,
+            Currency::CNY => self.cny,
+            Currency::JPY => self.jpy,
+            Currency::KRW => self.krw,
+            Currency::HKD => self.hkd,
+            Currency::IDR => self.idr,
+            Currency::MYR => self.myr,
+            Currency::SGD => self.sgd,
+            Currency::THB => self.thb,
+            Currency::SAR => self.sar,
+            Currency::AED => self.aed,
+            Currency::KWD => self.kwd,
+            Currency::INR => self.inr,
+            Currency::AUD => self.aud,
+            Currency::NZD => self.nzd,
+            Currency::XAU => self.xau,
+            Currency::XAG => self.xag,
+            Currency::XPT => self.xpt,
+            Currency::BTC => self.btc,
+            Currency::ETH => self.eth,
+            Currency::SOL => self.sol,
+            Currency::XRP => self.xrp,
+            Currency::ADA => self.ada,
+        }
+    }
+
+    /// replace rate of the money's currency with its amount.
+    pub fn set_rate(&mut self, money: Money) {
+        match money {
+            Money::USD(v) => self.usd = v,
+            Money::CAD(v) => self.cad = v,
+            Money::EUR(v) => self.eur = v,
+            Money::GBP(v) => self.gbp = v,
+            Money::CHF(v) => self.chf = v,
+            Money::RUB(v) => self.rub = v,
+
+            Money::CNY(v) => self.cny = v,
+            Money::JPY(v) => self.jpy = v,
+            Money::KRW(v) => self.krw = v,
+            Money::HKD(v) => self.hkd = v,
+
+            Money::IDR(v) => self.idr = v,
+            Money::MYR(v) => self.myr = v,
+            Money::SGD(v) => self.sgd = v,
+            Money::THB(v) => self.thb = v,
+
+            Money::SAR(v) => self.sar = v,
+            Money::AED(v) => self.aed = v,
+            Money::KWD(v) => self.kwd = v,
+
+            Money::INR(v) => self.inr = v,
+
+            Money::AUD(v) => self.aud = v,
+            Money::NZD(v) => self.nzd = v,
+
+            Money::XAU(v) => self.xau = v,
+            Money::XAG(v) => self.xag = v,
+            Money::XPT(v) => self.xpt = v,
+
+            Money::BTC(v) => self.btc = v,
+            Money::ETH(v) => self.eth = v,
+            Money::SOL(v) => self.sol = v,
+            Money::XRP(v) => self.xrp = v,
+            Money::ADA(v) => self.ada = v,
+        }
+    }
+
+    /// divide all rates with the divisor, e.g. to make rate of base currency 1.
+    pub(crate) fn rescale(&self, divisor: Decimal) -> Option<Self> {
+        let mut list = vec![];
+        for currency in Currency::iter() {
+            let rate = self.rate(currency).checked_div(divisor)?;
+            list.push(Money::new_money(currency, rate));
+        }
+
+        Some(list.into())
+    }
+
+    /// share of currencies enabled in this deployment having non zero rate, 0 to 1.
+    /// disabled currencies aren't requested, so they don't count as missing.
+    pub fn completeness(&self) -> Decimal {
+        let enabled = global::config().enabled_currencies();
+        let filled = enabled
+            .iter()
+            .filter(|currency| !self.rate(**currency).is_zero())
+            .count();
+
+        Decimal::from(filled) / Decimal::from(enabled.len())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ConversionResponse {
     /// latest update of the currency of conversion target.
     pub date: DateTime<Utc>,
@@ -219,6 +486,51 @@ pub struct ConversionResponse {
 
     /// result in form of $1,000.00
     pub symbol: String,
+
+    /// seconds since rates used in conversion were published.
+    pub rates_age_seconds: i64,
+
+    /// rates used in conversion are older than freshness threshold.
+    pub stale: bool,
+
+    /// intermediate steps of conversion, only when requested for debugging.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub steps: Option<ConversionSteps>,
+}
+
+/// Intermediate steps of conversion, from is divided by its rate into base, then multiplied by rate of target.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ConversionSteps {
+    /// converted amount in base currency of the rates.
+    pub base: Money,
+
+    pub from_rate: ConversionRate,
+    pub to_rate: ConversionRate,
+}
+
+/// Rate of currency relative to base of the rates, as used in conversion.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ConversionRate {
+    pub currency: Currency,
+    pub rate: Decimal,
+    pub date: DateTime<Utc>,
+}
+
+/// Result of rate expression, e.g. (USD 100 + EUR 50) in IDR.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ExpressionResponse {
+    pub expression: String,
+
+    /// date of rates used in evaluation.
+    pub date: DateTime<Utc>,
+
+    pub result: Money,
+
+    /// result in form of USD 1,000.00
+    pub code: String,
+
+    /// result in form of $1,000.00
+    pub symbol: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -228,8 +540,1459 @@ pub struct RatesList<T> {
     pub has_next: bool,
 }
 
+impl<T: Clone> RatesList<T> {
+    /// take page(starting from 1) of items with given size.
+    pub fn paginate(items: &[T], page: u32, size: u32) -> Self {
+        let start = (page.saturating_sub(1) as usize)
+            .saturating_mul(size as usize)
+            .min(items.len());
+        let end = start.saturating_add(size as usize).min(items.len());
+
+        RatesList {
+            has_prev: start > 0,
+            rates_list: items[start..end].to_vec(),
+            has_next: end < items.len(),
+        }
+    }
+}
+
+/// opaque pagination cursor pointing to the last item of a page.
+/// it encodes rates date and poll date of that item, so pages stay stable when new files are added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Cursor {
+    date: DateTime<Utc>,
+    poll_date: DateTime<Utc>,
+}
+
+impl Cursor {
+    pub fn of(rates: &RatesResponse<Rates>) -> Self {
+        Self {
+            date: rates.data.date,
+            poll_date: rates.poll_date,
+        }
+    }
+
+    /// cursor right after rates dated at the instant, rates at or before it come after it in descending order by data date.
+    pub fn at(date: DateTime<Utc>) -> Self {
+        Self {
+            date,
+            poll_date: DateTime::<Utc>::MAX_UTC,
+        }
+    }
+
+    pub fn date(&self) -> DateTime<Utc> {
+        self.date
+    }
+
+    pub fn poll_date(&self) -> DateTime<Utc> {
+        self.poll_date
+    }
+
+    /// key cursors are compared with when ordering by given date.
+    pub fn key(&self, order_by: OrderBy) -> (DateTime<Utc>, DateTime<Utc>) {
+        match order_by {
+            OrderBy::DataDate => (self.date, self.poll_date),
+            OrderBy::PollDate => (self.poll_date, self.date),
+        }
+    }
+}
+
+impl std::fmt::Display for Cursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:x}.{:x}",
+            self.date.timestamp_micros(),
+            self.poll_date.timestamp_micros()
+        )
+    }
+}
+
+impl std::str::FromStr for Cursor {
+    type Err = ForexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |part: &str| -> Option<DateTime<Utc>> {
+            let micros = i64::from_str_radix(part, 16).ok()?;
+            DateTime::from_timestamp_micros(micros)
+        };
+
+        let (date, poll_date) = s
+            .split_once('.')
+            .and_then(|(date, poll_date)| Some((parse(date)?, parse(poll_date)?)))
+            .ok_or(ForexError::client_error("invalid cursor"))?;
+
+        Ok(Self { date, poll_date })
+    }
+}
+
+impl Serialize for Cursor {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Cursor {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// sort rates by given date, then by the other one, in given order.
+pub fn sort_rates(rates: &mut [RatesResponse<Rates>], order: Order, order_by: OrderBy) {
+    let key = |rate: &RatesResponse<Rates>| Cursor::of(rate).key(order_by);
+    match order {
+        Order::ASC => rates.sort_by_key(key),
+        Order::DESC => rates.sort_by_key(|rate| std::cmp::Reverse(key(rate))),
+    }
+}
+
+/// cursor based page of rates.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RatesPage<T> {
+    pub rates_list: Vec<T>,
+    pub next_cursor: Option<Cursor>,
+}
+
+impl RatesPage<RatesResponse<Rates>> {
+    /// take `size` rates coming after the cursor in given order, or from the first one if no cursor.
+    pub fn after(
+        mut rates: Vec<RatesResponse<Rates>>,
+        cursor: Option<Cursor>,
+        size: u32,
+        order: Order,
+        order_by: OrderBy,
+    ) -> Self {
+        sort_rates(&mut rates, order, order_by);
+
+        let start = match cursor {
+            Some(cursor) => rates
+                .iter()
+                .position(|rate| match order {
+                    Order::ASC => Cursor::of(rate).key(order_by) > cursor.key(order_by),
+                    Order::DESC => Cursor::of(rate).key(order_by) < cursor.key(order_by),
+                })
+                .unwrap_or(rates.len()),
+            None => 0,
+        };
+        let end = start.saturating_add(size as usize).min(rates.len());
+        let has_next = end < rates.len();
+
+        rates.truncate(end);
+        let rates_list = rates.split_off(start);
+        let next_cursor = rates_list.last().filter(|_| has_next).map(Cursor::of);
+
+        RatesPage {
+            rates_list,
+            next_cursor,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Order {
     ASC,
     DESC,
 }
+
+/// date rates are ordered by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum OrderBy {
+    /// date of the rates data.
+    #[default]
+    DataDate,
+
+    /// date the rates were polled from provider, useful to debug polling.
+    PollDate,
+}
+
+/// Mode of polling historical rates from 3rd API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PollMode {
+    /// always fetch from API and overwrite stored data.
+    #[default]
+    Always,
+
+    /// consult storage first, fetch only if data for the date is missing or stored with error.
+    SkipExisting,
+}
+
+/// Provenance of stored historical rates, kept in a sidecar metadata file next to the data.
+/// Used for auditing where the data came from and whether it was changed after first insert.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RatesProvenance {
+    /// date of the historical rates.
+    pub date: DateTime<Utc>,
+
+    /// number of times rates for this date have been fetched and written.
+    pub fetch_attempts: u32,
+
+    /// provider of the currently stored data.
+    pub source: String,
+
+    /// when the data first inserted.
+    pub inserted_at: DateTime<Utc>,
+
+    /// when the data last modified after initial insert, None if never modified.
+    pub updated_at: Option<DateTime<Utc>>,
+
+    /// true if the data ever written from backfill(batch/timeseries import) instead of daily polling.
+    pub backfilled: bool,
+
+    /// every write happened to the data, ordered from the oldest.
+    pub events: Vec<ProvenanceEvent>,
+}
+
+impl RatesProvenance {
+    pub(crate) fn new(date: DateTime<Utc>, event: ProvenanceEvent) -> Self {
+        Self {
+            date,
+            fetch_attempts: if event.kind == ProvenanceKind::Update {
+                0
+            } else {
+                1
+            },
+            source: event.source.clone(),
+            inserted_at: event.at,
+            updated_at: None,
+            backfilled: event.kind == ProvenanceKind::Backfill,
+            events: vec![event],
+        }
+    }
+
+    /// record new write into existing provenance.
+    pub(crate) fn record(&mut self, event: ProvenanceEvent) {
+        if event.kind != ProvenanceKind::Update {
+            self.fetch_attempts += 1;
+        }
+        if event.kind == ProvenanceKind::Backfill {
+            self.backfilled = true;
+        }
+        if !event.source.is_empty() {
+            self.source = event.source.clone();
+        }
+        self.updated_at = Some(event.at);
+        self.events.push(event);
+    }
+
+    /// data has been modified after initial insert.
+    pub fn is_modified(&self) -> bool {
+        self.updated_at.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvenanceEvent {
+    pub kind: ProvenanceKind,
+
+    /// provider of the written data, empty if unknown.
+    pub source: String,
+
+    pub at: DateTime<Utc>,
+
+    /// error of the written data if fetching failed.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProvenanceKind {
+    /// polled from API
+    Insert,
+
+    /// imported in batch, e.g. from timeseries API
+    Backfill,
+
+    /// some rates data updated
+    Update,
+}
+
+/// Attribution of latest or historical rates to where their data came from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RatesSources {
+    pub kind: RatesKind,
+    pub rates_id: Uuid,
+    pub date: DateTime<Utc>,
+
+    /// provider the rates were polled from.
+    pub source: String,
+
+    pub poll_date: DateTime<Utc>,
+
+    /// rates of currencies patched from other sources after polling.
+    pub overrides: Vec<RateProvenance>,
+
+    /// write history of historical rates, none for latest rates or if never recorded.
+    pub provenance: Option<RatesProvenance>,
+}
+
+/// Kind of change made to stored rates file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeKind {
+    /// file written from polling or backfill.
+    Insert,
+
+    /// some rates data of existing file updated.
+    Update,
+
+    /// file removed, e.g. on clearing latest directory.
+    Delete,
+
+    /// file moved into archive, e.g. on rotating latest directory.
+    Archive,
+}
+
+/// Entry of append-only journal of every change made to stored rates files, for forensic audits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RatesChange {
+    pub at: DateTime<Utc>,
+    pub kind: ChangeKind,
+    pub rates_kind: RatesKind,
+
+    /// date of the rates, e.g. day of historical rates or poll date of latest rates.
+    pub date: DateTime<Utc>,
+
+    /// path of the file relative to storage root, before it's archived.
+    pub path: String,
+
+    /// hex sha256 checksum of the file before and after the change, None if the file didn't or doesn't exist.
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl RatesChange {
+    pub(crate) fn new(
+        kind: ChangeKind,
+        rates_kind: RatesKind,
+        date: DateTime<Utc>,
+        path: String,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> Self {
+        Self {
+            at: Utc::now(),
+            kind,
+            rates_kind,
+            date,
+            path,
+            before,
+            after,
+        }
+    }
+}
+
+/// Changes of historical rates of a date replayed from the journal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RatesChangeReplay {
+    pub date: DateTime<Utc>,
+
+    /// ordered from the oldest.
+    pub changes: Vec<RatesChange>,
+
+    /// checksum of currently stored file, None if not stored.
+    pub current: Option<String>,
+
+    /// index of the first change not starting from checksum left by previous change,
+    /// or number of changes if stored file doesn't match the last change, e.g. modified outside storage.
+    pub broken_at: Option<usize>,
+}
+
+impl RatesChangeReplay {
+    pub(crate) fn replay(
+        date: DateTime<Utc>,
+        changes: Vec<RatesChange>,
+        current: Option<String>,
+    ) -> Self {
+        let broken_at = changes
+            .windows(2)
+            .position(|pair| pair[1].before != pair[0].after)
+            .map(|index| index + 1)
+            .or_else(|| {
+                changes
+                    .last()
+                    .filter(|last| last.after != current)
+                    .map(|_| changes.len())
+            });
+
+        Self {
+            date,
+            changes,
+            current,
+            broken_at,
+        }
+    }
+
+    /// every change continues from the previous one and stored file matches the last one.
+    pub fn is_consistent(&self) -> bool {
+        self.broken_at.is_none()
+    }
+}
+
+/// Stored version of historical rates of a date, immutable storage writes next version of each change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoricalVersion {
+    pub date: DateTime<Utc>,
+
+    /// 1 for the first written file.
+    pub version: u32,
+
+    /// path of the file relative to storage root.
+    pub path: String,
+
+    /// hex sha256 checksum of the file.
+    pub checksum: String,
+
+    pub size: u64,
+
+    /// last modified time of the file.
+    pub written_at: DateTime<Utc>,
+
+    /// the newest version, read as historical rates of the date.
+    pub current: bool,
+}
+
+/// Background jobs whose runs are recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Job {
+    PollLatestRates,
+    PollHistoricalRates,
+    SendDigest,
+    CaptureCryptoPrices,
+    PollCryptoRates,
+    EvaluateSlo,
+}
+
+impl Job {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PollLatestRates => "poll_latest_rates",
+            Self::PollHistoricalRates => "poll_historical_rates",
+            Self::SendDigest => "send_digest",
+            Self::CaptureCryptoPrices => "capture_crypto_prices",
+            Self::PollCryptoRates => "poll_crypto_rates",
+            Self::EvaluateSlo => "evaluate_slo",
+        }
+    }
+}
+
+/// What started a job run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobTrigger {
+    /// scheduled by pfm-cron.
+    Cron,
+
+    /// rerun requested through admin endpoint.
+    Admin,
+}
+
+/// Outcome of single job execution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRun {
+    pub id: Uuid,
+    pub job: Job,
+    pub trigger: JobTrigger,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub items_processed: u32,
+}
+
+impl JobRun {
+    pub(crate) fn start(job: Job, trigger: JobTrigger) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            job,
+            trigger,
+            started_at: Utc::now(),
+            finished_at: None,
+            success: false,
+            error: None,
+            items_processed: 0,
+        }
+    }
+
+    /// mark run as finished with number of items processed or the error.
+    pub(crate) fn finish(&mut self, result: Result<u32, String>) {
+        self.finished_at = Some(Utc::now());
+        match result {
+            Ok(items_processed) => {
+                self.success = true;
+                self.items_processed = items_processed;
+            }
+            Err(error) => {
+                self.success = false;
+                self.error = Some(error);
+            }
+        }
+    }
+}
+
+/// Lease of a job held by single pfm-cron instance, so only one instance runs the job at a time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobLock {
+    pub job: Job,
+    pub holder: String,
+    pub acquired_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl JobLock {
+    pub(crate) fn new(job: Job, holder: &str, ttl: TimeDelta) -> Self {
+        let now = Utc::now();
+        Self {
+            job,
+            holder: holder.to_string(),
+            acquired_at: now,
+            expires_at: now + ttl,
+        }
+    }
+
+    /// lock can be taken by holder if it's already the holder or the lock is expired.
+    pub(crate) fn can_acquire(&self, holder: &str, now: DateTime<Utc>) -> bool {
+        self.holder == holder || self.expires_at <= now
+    }
+}
+
+/// Outcome of delivering rates to single webhook url.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub url: String,
+
+    /// id of the delivered rates.
+    pub rates_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub attempts: u32,
+    pub success: bool,
+
+    /// http status code of the last attempt, if any response received.
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+}
+
+impl WebhookDelivery {
+    pub(crate) fn new(url: &str, rates_id: Uuid) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            url: url.to_string(),
+            rates_id,
+            created_at: Utc::now(),
+            delivered_at: None,
+            attempts: 0,
+            success: false,
+            status_code: None,
+            error: None,
+        }
+    }
+}
+
+/// Unit of crypto prices in imported datasets, must be stated explicitly since datasets differ.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PriceUnit {
+    /// USD price of 1 coin, e.g. 60000 for BTC.
+    UsdPerCoin,
+
+    /// amount of coin per 1 USD, same direction as stored rates.
+    CoinPerUsd,
+}
+
+impl PriceUnit {
+    /// convert price in this unit into USD based rate, None if price isn't positive.
+    pub fn to_rate(self, price: Decimal) -> Option<Decimal> {
+        if price <= Decimal::ZERO {
+            return None;
+        }
+        match self {
+            Self::UsdPerCoin => Decimal::ONE.checked_div(price),
+            Self::CoinPerUsd => Some(price),
+        }
+    }
+}
+
+/// USD based rate of crypto on a date, imported from dataset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CryptoPrice {
+    pub currency: Currency,
+    pub date: DateTime<Utc>,
+    pub rate: Decimal,
+
+    /// label of where the price comes from, recorded in provenance of patched rates.
+    pub source: String,
+}
+
+/// Change to single crypto rate of stored historical rates, made or rejected by ingestion.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CryptoRateChange {
+    pub date: DateTime<Utc>,
+    pub currency: Currency,
+    pub old: Decimal,
+    pub new: Decimal,
+
+    /// reason the change is rejected.
+    pub reason: Option<String>,
+}
+
+/// Result of crypto history ingestion, on dry run nothing is written.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CryptoIngestReport {
+    pub dry_run: bool,
+    pub applied: Vec<CryptoRateChange>,
+    pub rejected: Vec<CryptoRateChange>,
+}
+
+/// Dates fetched and written by backfill or poll, on dry run nothing is fetched nor written,
+/// reported before spending limited provider quota.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackfillReport {
+    pub dry_run: bool,
+
+    /// dates fetched and written, or would be on dry run.
+    pub dates: Vec<DateTime<Utc>>,
+
+    /// dates already stored or not served by the provider.
+    pub skipped: Vec<DateTime<Utc>>,
+
+    /// dates the provider failed on, planned again by the next backfill.
+    pub failed: Vec<DateTime<Utc>>,
+
+    /// provider calls counted against its quota.
+    pub quota_cost: usize,
+}
+
+/// Provider calls allowed within a window, e.g. 4 per second or 9 per minute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub calls: u32,
+    pub window_secs: u64,
+}
+
+/// Dates fetched concurrently, followed by a pause so the next batch stays within rate limit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackfillBatch {
+    pub dates: Vec<DateTime<Utc>>,
+
+    /// pause after the batch, 0 after the last one.
+    pub sleep_secs: u64,
+}
+
+/// Schedule of missing historical rates fetched within rate limit and remaining quota of provider.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackfillPlan {
+    pub rate_limit: RateLimit,
+    pub quota_remaining: u32,
+
+    /// with PollMode::Always stored dates are fetched again, otherwise only missing and error-marked ones.
+    pub mode: PollMode,
+
+    /// ordered from the oldest date.
+    pub batches: Vec<BackfillBatch>,
+
+    /// dates already stored, already fetched according to checkpoint, or not served by the provider.
+    pub skipped: Vec<DateTime<Utc>>,
+
+    /// missing dates beyond remaining quota, left for the next run.
+    pub deferred: Vec<DateTime<Utc>>,
+
+    /// provider calls counted against its quota.
+    pub quota_cost: usize,
+
+    /// estimated duration of the whole schedule.
+    pub eta_secs: u64,
+}
+
+/// Progress of backfill of a provider's rates in a base, stored after each batch,
+/// so interrupted backfill resumes without refetching. dates not done, e.g. failed ones, are planned again.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BackfillCheckpoint {
+    /// dates fetched and stored without error, ordered.
+    pub done: Vec<DateTime<Utc>>,
+
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl BackfillCheckpoint {
+    /// add fetched dates.
+    pub(crate) fn record(&mut self, fetched: &[DateTime<Utc>]) {
+        let done: BTreeSet<DateTime<Utc>> = self.done.iter().chain(fetched).copied().collect();
+
+        self.done = done.into_iter().collect();
+        self.updated_at = Some(Utc::now());
+    }
+}
+
+/// Rate of a currency changed between two latest rates snapshots.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurrencyRateDelta {
+    pub currency: Currency,
+    pub old: Decimal,
+    pub new: Decimal,
+}
+
+/// Changed rates of current latest rates since an earlier snapshot,
+/// so frequently polling clients don't need to fetch all rates again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RatesDelta {
+    pub since: Uuid,
+    pub since_date: DateTime<Utc>,
+    pub id: Uuid,
+    pub date: DateTime<Utc>,
+    pub base: Currency,
+
+    /// empty when nothing changed.
+    pub changes: Vec<CurrencyRateDelta>,
+}
+
+impl RatesDelta {
+    /// changed rates from old into new snapshot, they must have the same base currency.
+    pub fn between(
+        old: &RatesResponse<Rates>,
+        new: &RatesResponse<Rates>,
+    ) -> Result<Self, ForexError> {
+        if old.data.base != new.data.base {
+            return Err(ForexError::client_error(
+                format!(
+                    "cannot compare rates of different base {} and {}",
+                    old.data.base.code(),
+                    new.data.base.code()
+                )
+                .as_str(),
+            ));
+        }
+
+        let changes = Currency::iter()
+            .filter_map(|currency| {
+                let (old, new) = (old.data.rates.rate(currency), new.data.rates.rate(currency));
+                (old != new).then_some(CurrencyRateDelta { currency, old, new })
+            })
+            .collect();
+
+        Ok(Self {
+            since: old.id,
+            since_date: old.data.date,
+            id: new.id,
+            date: new.data.date,
+            base: new.data.base,
+            changes,
+        })
+    }
+}
+
+/// Kind of stored rates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RatesKind {
+    Latest,
+    Historical,
+}
+
+/// Stored rates whose base currency rate isn't 1, e.g. because of provider quirks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaseRateViolation {
+    pub kind: RatesKind,
+    pub rates_id: Uuid,
+    pub date: DateTime<Utc>,
+    pub base: Currency,
+    pub base_rate: Decimal,
+
+    /// whether all rates have been rescaled so base currency rate is 1.
+    pub repaired: bool,
+}
+
+/// Rate of a pair on a date.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RatePoint {
+    pub date: DateTime<Utc>,
+    pub rate: Decimal,
+}
+
+/// Rolling window of latest daily rates of a currency against USD, maintained on each historical insert,
+/// so sparklines are read from a single file instead of a file per day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sparkline {
+    pub currency: Currency,
+
+    /// ordered by date, a point per day.
+    pub points: Vec<RatePoint>,
+}
+
+impl Sparkline {
+    pub(crate) fn new(currency: Currency) -> Self {
+        Self {
+            currency,
+            points: vec![],
+        }
+    }
+
+    /// insert or replace rate of the date, keeping only latest window points.
+    pub(crate) fn record(&mut self, point: RatePoint, window: usize) {
+        let date = point.date.date_naive();
+        self.points.retain(|p| p.date.date_naive() != date);
+        self.points.push(point);
+        self.points.sort_by_key(|p| p.date);
+        if self.points.len() > window {
+            self.points.drain(..self.points.len() - window);
+        }
+    }
+}
+
+/// Outcome of verifying signatures of stored rates files, paths are relative to storage root.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SignatureReport {
+    /// number of files with valid signature.
+    pub verified: usize,
+
+    /// files without signature, e.g. written before signing was enabled.
+    pub unsigned: Vec<String>,
+
+    /// files whose content doesn't match their signature.
+    pub invalid: Vec<String>,
+}
+
+impl SignatureReport {
+    /// no file was tampered, unsigned files aren't considered tampered.
+    pub fn is_valid(&self) -> bool {
+        self.invalid.is_empty()
+    }
+}
+
+/// Daily rates of a pair over latest days, for drawing sparklines.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairSparkline {
+    pub base: Currency,
+    pub quote: Currency,
+    pub points: Vec<RatePoint>,
+}
+
+/// Lowest and highest rate of a pair over a period, with position of current rate within the period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateExtremes {
+    pub base: Currency,
+    pub quote: Currency,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub lowest: RatePoint,
+    pub highest: RatePoint,
+    pub current: RatePoint,
+
+    /// percentage of rates in the period lower than or equal to current rate, 0 to 100.
+    pub percentile: Decimal,
+}
+
+/// Spending plan of home currency budget converted into destination currency for a trip.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TravelBudget {
+    pub budget: Money,
+    pub destination: Currency,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub days: u32,
+
+    /// average and standard deviation of rates in recent historical rates.
+    pub average_rate: Decimal,
+    pub volatility: Decimal,
+
+    /// average rate lowered by volatility, planning for unfavorable rate.
+    pub conservative_rate: Decimal,
+
+    pub total: Money,
+    pub daily: Money,
+    pub conservative_total: Money,
+    pub conservative_daily: Money,
+}
+
+/// Hypothetical scenario of rates shocks, holdings revalued in report currency before and after the shocks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShockScenario {
+    pub currency: Currency,
+    pub rates_date: DateTime<Utc>,
+
+    /// percent change of each shocked currency's value against report currency.
+    pub shocks: Vec<RateShock>,
+
+    /// latest rates based in report currency after the shocks.
+    pub shocked: Rates,
+
+    pub holdings: Vec<ShockedHolding>,
+    pub total: Money,
+    pub shocked_total: Money,
+    pub change: Money,
+
+    /// change relative to total, 0 if total is 0.
+    pub change_percent: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateShock {
+    pub currency: Currency,
+    pub percent: Decimal,
+}
+
+/// Holding valued in report currency before and after the shocks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShockedHolding {
+    pub holding: Money,
+    pub value: Money,
+    pub shocked_value: Money,
+}
+
+/// Days a currency has non-zero rate in historical rates of a period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrencyCompleteness {
+    pub currency: Currency,
+    pub days_with_rate: u32,
+
+    /// percentage of days in the period having non-zero rate, 0 to 100.
+    pub percent: Decimal,
+}
+
+/// Completeness of historical rates of every currency over a period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletenessReport {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+
+    /// days in the period, start and end inclusive.
+    pub days: u32,
+
+    pub currencies: Vec<CurrencyCompleteness>,
+}
+
+/// Objectives of rates freshness in a deployment.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FreshnessSlo {
+    /// latest rates must not be older than this.
+    pub latest_max_age_secs: u64,
+
+    /// days before today whose historical rates must be complete, 0 disables the objective.
+    pub historical_days: u32,
+
+    /// percentage of those days each enabled currency must have rate, 0 to 100.
+    pub historical_min_percent: Decimal,
+}
+
+/// Single evaluation of freshness SLO.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloReport {
+    pub id: Uuid,
+    pub evaluated_at: DateTime<Utc>,
+    pub slo: FreshnessSlo,
+
+    /// age of latest rates, None if they can't be read or were polled with error.
+    pub latest_age_seconds: Option<i64>,
+
+    /// latest rates are fresh enough, or markets of all enabled currencies are closed.
+    pub latest_met: bool,
+
+    /// lowest percentage of days having rate among enabled currencies.
+    pub historical_percent: Decimal,
+
+    /// enabled currencies having rate on fewer days than required.
+    pub incomplete: Vec<Currency>,
+    pub historical_met: bool,
+
+    /// SLO is breached and previous evaluation met it, or there was none.
+    pub breach_started: bool,
+
+    /// alerting the started breach failed, it is started again at next evaluation.
+    #[serde(default)]
+    pub breach_unalerted: bool,
+}
+
+impl SloReport {
+    pub fn is_met(&self) -> bool {
+        self.latest_met && self.historical_met
+    }
+}
+
+/// Share of evaluations since a date meeting each objective, 0 to 1. all are 1 without evaluations.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloCompliance {
+    pub since: DateTime<Utc>,
+    pub evaluations: u32,
+    pub latest: Decimal,
+    pub historical: Decimal,
+    pub overall: Decimal,
+
+    /// most recent evaluation.
+    pub last: Option<SloReport>,
+}
+
+/// Period daily rates are aggregated into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AggregationPeriod {
+    /// ISO 8601 week, starting on monday, e.g. 2025-W01.
+    Week,
+
+    /// calendar month, e.g. 2025-01.
+    Month,
+}
+
+impl AggregationPeriod {
+    /// label of period the date falls in.
+    pub fn label(&self, date: DateTime<Utc>) -> String {
+        match self {
+            AggregationPeriod::Week => {
+                let week = date.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            AggregationPeriod::Month => date.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+/// Daily rates of a pair within single week or month.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateAggregate {
+    /// e.g. 2025-W01 or 2025-01.
+    pub period: String,
+
+    /// dates of the first and the last daily rate within the period.
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+
+    pub open: Decimal,
+    pub close: Decimal,
+    pub min: Decimal,
+    pub max: Decimal,
+    pub average: Decimal,
+
+    /// number of daily rates aggregated.
+    pub days: u32,
+}
+
+/// Weekly or monthly aggregates of a pair over a range, ordered by period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairAggregates {
+    pub base: Currency,
+    pub quote: Currency,
+    pub period: AggregationPeriod,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub aggregates: Vec<RateAggregate>,
+}
+
+/// Average and end of month rates of currencies per calendar month of a year, e.g. for accounting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthlyRatesTable {
+    pub year: i32,
+
+    /// rates are prices of 1 base in each currency.
+    pub base: Currency,
+
+    /// columns of the table.
+    pub currencies: Vec<Currency>,
+
+    /// rows of the table ordered by month, months without rates are left out.
+    pub months: Vec<MonthlyRates>,
+}
+
+/// Rates of a month, in order of currencies of the table. none if the currency has no rate in the month.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthlyRates {
+    /// e.g. 2025-01.
+    pub month: String,
+
+    /// number of daily rates within the month.
+    pub days: u32,
+
+    pub average: Vec<Option<Decimal>>,
+
+    /// rate of the last day having rate within the month.
+    pub end_of_month: Vec<Option<Decimal>>,
+}
+
+/// Outcome of single call to a 3rd party provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderCallSample {
+    pub at: DateTime<Utc>,
+    pub success: bool,
+    pub latency_ms: u64,
+
+    /// share of currencies having rate in returned rates, 0 to 1. zero for failed calls.
+    pub completeness: Decimal,
+}
+
+/// Latest calls to a 3rd party provider, used to prefer healthier providers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderHealth {
+    pub provider: String,
+
+    /// ordered from the oldest call.
+    pub samples: Vec<ProviderCallSample>,
+}
+
+impl ProviderHealth {
+    pub(crate) fn new(provider: &str) -> Self {
+        Self {
+            provider: provider.to_string(),
+            samples: vec![],
+        }
+    }
+
+    /// append the call, keeping only latest window calls.
+    pub(crate) fn record(&mut self, sample: ProviderCallSample, window: usize) {
+        self.samples.push(sample);
+        if self.samples.len() > window {
+            self.samples.drain(..self.samples.len() - window);
+        }
+    }
+
+    /// share of successful calls, 0 to 1. provider without calls is assumed healthy.
+    pub fn success_rate(&self) -> Decimal {
+        if self.samples.is_empty() {
+            return Decimal::ONE;
+        }
+        let successes = self.samples.iter().filter(|s| s.success).count();
+
+        Decimal::from(successes) / Decimal::from(self.samples.len())
+    }
+
+    /// average completeness of successful calls, 0 to 1.
+    pub fn completeness(&self) -> Decimal {
+        let successes: Vec<&ProviderCallSample> =
+            self.samples.iter().filter(|s| s.success).collect();
+        if successes.is_empty() {
+            return if self.samples.is_empty() {
+                Decimal::ONE
+            } else {
+                Decimal::ZERO
+            };
+        }
+        let sum: Decimal = successes.iter().map(|s| s.completeness).sum();
+
+        sum / Decimal::from(successes.len())
+    }
+
+    /// average latency of successful calls, None when there's none.
+    pub fn avg_latency_ms(&self) -> Option<u64> {
+        let latencies: Vec<u64> = self
+            .samples
+            .iter()
+            .filter(|s| s.success)
+            .map(|s| s.latency_ms)
+            .collect();
+        if latencies.is_empty() {
+            return None;
+        }
+
+        Some(latencies.iter().sum::<u64>() / latencies.len() as u64)
+    }
+
+    /// health score from 0 to 1, success rate weighted by completeness of returned rates.
+    pub fn score(&self) -> Decimal {
+        (self.success_rate() * self.completeness()).round_dp(4)
+    }
+}
+
+/// Currencies and dates a 3rd party provider serves. Symbols outside of it are not requested,
+/// and dates outside of it are not polled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProviderCapabilities {
+    pub supported: HashSet<Currency>,
+
+    /// longest range of single timeseries call, unlimited when None.
+    pub max_timeseries_span: Option<TimeDelta>,
+
+    /// earliest date having rates, unlimited when None.
+    pub history_start: Option<DateTime<Utc>>,
+
+    /// provider calls allowed within a window, unlimited when None.
+    pub rate_limit: Option<RateLimit>,
+
+    /// calls of the provider's plan per quota period, unmetered when None.
+    pub quota: Option<u32>,
+}
+
+impl Default for ProviderCapabilities {
+    /// all currencies without limit of dates.
+    fn default() -> Self {
+        Self {
+            supported: Currency::iter().collect(),
+            max_timeseries_span: None,
+            history_start: None,
+            rate_limit: None,
+            quota: None,
+        }
+    }
+}
+
+impl ProviderCapabilities {
+    /// all currencies of the kinds without limit of dates.
+    pub fn from_kinds(kinds: &[CurrencyKind]) -> Self {
+        Self {
+            supported: Currency::of_kinds(kinds).collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_max_timeseries_span(mut self, span: TimeDelta) -> Self {
+        self.max_timeseries_span = Some(span);
+        self
+    }
+
+    pub fn with_history_start(mut self, start: DateTime<Utc>) -> Self {
+        self.history_start = Some(start);
+        self
+    }
+
+    pub fn with_rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    pub fn with_quota(mut self, quota: u32) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    pub fn supports(&self, currency: Currency) -> bool {
+        self.supported.contains(&currency)
+    }
+
+    /// supported currencies in declaration order, e.g. symbols requested from the provider.
+    pub fn symbols(&self) -> Vec<Currency> {
+        Currency::iter().filter(|c| self.supports(*c)).collect()
+    }
+
+    /// supported currencies among enabled ones, in declaration order.
+    pub fn enabled_symbols(&self, enabled: &[Currency]) -> Vec<Currency> {
+        Currency::iter()
+            .filter(|c| self.supports(*c) && enabled.contains(c))
+            .collect()
+    }
+
+    /// symbols requested from the provider, only currencies enabled in this deployment to save its quota.
+    pub fn requested_symbols(&self) -> Vec<Currency> {
+        self.enabled_symbols(global::config().enabled_currencies())
+    }
+
+    /// whether the provider has rates on the date.
+    pub fn covers(&self, date: DateTime<Utc>) -> bool {
+        self.history_start
+            .is_none_or(|start| date.date_naive() >= start.date_naive())
+    }
+
+    /// consecutive ranges between start and end, each served by single timeseries call.
+    /// empty when the range is before history of the provider.
+    pub fn split_date_range(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        let mut ranges = vec![];
+        let mut current_start = self.history_start.map_or(start, |from| start.max(from));
+        while current_start <= end {
+            // end 1 second before the span to avoid overlapping with the next range
+            let current_end = self.max_timeseries_span.map_or(end, |span| {
+                end.min(current_start + span - TimeDelta::seconds(1))
+            });
+            ranges.push((current_start, current_end));
+            current_start = current_end + TimeDelta::seconds(1);
+        }
+
+        ranges
+    }
+
+    /// capabilities served by either of them, e.g. by chain of providers.
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            supported: self.supported.union(&other.supported).copied().collect(),
+            max_timeseries_span: self
+                .max_timeseries_span
+                .zip(other.max_timeseries_span)
+                .map(|(a, b)| a.max(b)),
+            history_start: self
+                .history_start
+                .zip(other.history_start)
+                .map(|(a, b)| a.min(b)),
+            // limits of the first one having them, it's called first
+            rate_limit: self.rate_limit.or(other.rate_limit),
+            quota: self.quota.or(other.quota),
+        }
+    }
+}
+
+/// Rate of a currency differing between two providers more than the threshold.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateDiscrepancy {
+    pub currency: Currency,
+    pub first: Decimal,
+    pub second: Decimal,
+
+    /// absolute difference relative to rate of the first provider, in percent.
+    pub diff_percent: Decimal,
+}
+
+/// Comparison of historical rates of the same date fetched from two providers,
+/// used to decide which provider to trust for backfills.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsistencyReport {
+    pub date: DateTime<Utc>,
+    pub base: Currency,
+    pub first_provider: String,
+    pub second_provider: String,
+
+    /// max difference in percent not reported as discrepancy.
+    pub threshold_percent: Decimal,
+    pub checked_at: DateTime<Utc>,
+
+    /// number of currencies both providers have rate of.
+    pub compared: usize,
+
+    /// ordered from the biggest difference.
+    pub discrepancies: Vec<RateDiscrepancy>,
+
+    /// currencies only one of providers has rate of.
+    pub missing: Vec<Currency>,
+}
+
+impl ConsistencyReport {
+    /// compare rates of both providers, they must have the same base currency.
+    pub fn compare(
+        first: &RatesResponse<Rates>,
+        second: &RatesResponse<Rates>,
+        threshold_percent: Decimal,
+    ) -> Result<Self, ForexError> {
+        if first.data.base != second.data.base {
+            return Err(ForexError::client_error(
+                format!(
+                    "cannot compare rates of different base {} and {}",
+                    first.data.base.code(),
+                    second.data.base.code()
+                )
+                .as_str(),
+            ));
+        }
+
+        let mut compared = 0;
+        let mut discrepancies = vec![];
+        let mut missing = vec![];
+        for currency in Currency::iter() {
+            let (first_rate, second_rate) = (
+                first.data.rates.rate(currency),
+                second.data.rates.rate(currency),
+            );
+            match (first_rate.is_zero(), second_rate.is_zero()) {
+                (true, true) => continue,
+                (true, false) | (false, true) => {
+                    missing.push(currency);
+                    continue;
+                }
+                (false, false) => compared += 1,
+            }
+
+            let diff_percent =
+                ((first_rate - second_rate).abs() / first_rate * Decimal::ONE_HUNDRED).round_dp(4);
+            if diff_percent > threshold_percent {
+                discrepancies.push(RateDiscrepancy {
+                    currency,
+                    first: first_rate,
+                    second: second_rate,
+                    diff_percent,
+                });
+            }
+        }
+        discrepancies.sort_by_key(|d| std::cmp::Reverse(d.diff_percent));
+
+        Ok(Self {
+            date: first.data.date,
+            base: first.data.base,
+            first_provider: first.source.clone(),
+            second_provider: second.source.clone(),
+            threshold_percent,
+            checked_at: Utc::now(),
+            compared,
+            discrepancies,
+            missing,
+        })
+    }
+
+    /// both providers agree within the threshold on all currencies they have.
+    pub fn is_consistent(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
+/// Disk usage of a storage subtree, e.g. latest or historical directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageSubtreeStats {
+    pub name: String,
+    pub files: u64,
+    pub bytes: u64,
+
+    /// dates of the oldest and newest rates files, none for subtrees without dated files, e.g. metadata.
+    pub oldest: Option<DateTime<Utc>>,
+    pub newest: Option<DateTime<Utc>>,
+}
+
+impl StorageSubtreeStats {
+    pub(crate) fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// count file of the size, dated by its filename if any.
+    pub(crate) fn add_file(&mut self, bytes: u64, date: Option<DateTime<Utc>>) {
+        self.files += 1;
+        self.bytes += bytes;
+        if let Some(date) = date {
+            self.oldest = Some(self.oldest.map_or(date, |oldest| oldest.min(date)));
+            self.newest = Some(self.newest.map_or(date, |newest| newest.max(date)));
+        }
+    }
+}
+
+/// Disk usage of storage, in total and per subtree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageStats {
+    pub files: u64,
+    pub bytes: u64,
+    pub subtrees: Vec<StorageSubtreeStats>,
+}
+
+impl StorageStats {
+    pub(crate) fn new(subtrees: Vec<StorageSubtreeStats>) -> Self {
+        Self {
+            files: subtrees.iter().map(|subtree| subtree.files).sum(),
+            bytes: subtrees.iter().map(|subtree| subtree.bytes).sum(),
+            subtrees,
+        }
+    }
+
+    pub fn subtree(&self, name: &str) -> Option<&StorageSubtreeStats> {
+        self.subtrees.iter().find(|subtree| subtree.name == name)
+    }
+
+    /// bytes counted against max disk usage, metadata(journal, signatures, reports) is excluded since it's never pruned.
+    pub fn quota_bytes(&self) -> u64 {
+        self.subtrees
+            .iter()
+            .filter(|subtree| subtree.name != "metadata")
+            .map(|subtree| subtree.bytes)
+            .sum()
+    }
+}
+
+/// Outcome of cleaning up storage exceeding max disk usage.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskQuotaReport {
+    pub max_bytes: u64,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+
+    /// archived latest rates files deleted, relative to storage root.
+    pub pruned: Vec<String>,
+}
+
+impl DiskQuotaReport {
+    /// storage is still above max disk usage after cleaning up.
+    pub fn is_exceeded(&self) -> bool {
+        self.bytes_after > self.max_bytes
+    }
+}
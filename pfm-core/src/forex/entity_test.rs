@@ -1,6 +1,6 @@
 use strum::IntoEnumIterator;
 
-use super::{entity::RatesData, Currency, Money};
+use super::{Currency, Money, entity::RatesData};
 
 #[test]
 fn test_rates_data_fields() {
@@ -84,3 +84,472 @@ fn test_rates_data_fields() {
     assert_eq!(ret.len(), currency_variants_count);
     assert_eq!(money_variants_count, currency_variants_count);
 }
+
+#[test]
+fn test_rates_provenance_record() {
+    use super::entity::{ProvenanceEvent, ProvenanceKind, RatesProvenance};
+    use chrono::{TimeZone, Utc};
+
+    let date = Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap();
+    let inserted_at = Utc.with_ymd_and_hms(2024, 5, 2, 1, 10, 0).unwrap();
+    let mut provenance = RatesProvenance::new(
+        date,
+        ProvenanceEvent {
+            kind: ProvenanceKind::Insert,
+            source: "".to_string(),
+            at: inserted_at,
+            error: Some("provider timeout".to_string()),
+        },
+    );
+    assert_eq!(provenance.fetch_attempts, 1);
+    assert!(!provenance.is_modified());
+    assert!(!provenance.backfilled);
+
+    let refetched_at = Utc.with_ymd_and_hms(2024, 5, 3, 1, 10, 0).unwrap();
+    provenance.record(ProvenanceEvent {
+        kind: ProvenanceKind::Backfill,
+        source: "currencybeacon.com".to_string(),
+        at: refetched_at,
+        error: None,
+    });
+    assert_eq!(provenance.fetch_attempts, 2);
+    assert_eq!(provenance.source, "currencybeacon.com");
+    assert_eq!(provenance.inserted_at, inserted_at);
+    assert_eq!(provenance.updated_at, Some(refetched_at));
+    assert!(provenance.backfilled);
+
+    provenance.record(ProvenanceEvent {
+        kind: ProvenanceKind::Update,
+        source: "".to_string(),
+        at: refetched_at,
+        error: None,
+    });
+    assert_eq!(provenance.fetch_attempts, 2);
+    assert_eq!(provenance.source, "currencybeacon.com");
+    assert_eq!(provenance.events.len(), 3);
+    assert!(provenance.is_modified());
+}
+
+#[test]
+fn test_rates_list_paginate() {
+    let items = vec![1, 2, 3, 4, 5];
+
+    let ret = super::entity::RatesList::paginate(&items, 2, 2);
+    assert!(ret.has_prev);
+    assert!(ret.has_next);
+    assert_eq!(ret.rates_list, vec![3, 4]);
+
+    let ret = super::entity::RatesList::paginate(&items, 4, 2);
+    assert!(ret.has_prev);
+    assert!(!ret.has_next);
+    assert!(ret.rates_list.is_empty());
+
+    let ret = super::entity::RatesList::paginate(&items, 1, u32::MAX);
+    assert!(!ret.has_prev);
+    assert!(!ret.has_next);
+    assert_eq!(ret.rates_list, items);
+}
+
+#[test]
+fn test_job_run_finish() {
+    use super::entity::{Job, JobRun, JobTrigger};
+
+    let mut run = JobRun::start(Job::PollHistoricalRates, JobTrigger::Cron);
+    assert!(run.finished_at.is_none());
+    run.finish(Ok(1));
+    assert!(run.success);
+    assert_eq!(run.items_processed, 1);
+    assert!(run.finished_at.is_some());
+
+    let mut run = JobRun::start(Job::PollLatestRates, JobTrigger::Admin);
+    run.finish(Err("provider down".to_string()));
+    assert!(!run.success);
+    assert_eq!(run.error.as_deref(), Some("provider down"));
+    assert_eq!(Job::PollLatestRates.name(), "poll_latest_rates");
+}
+
+#[test]
+fn test_job_lock_can_acquire() {
+    use super::entity::{Job, JobLock};
+    use chrono::{TimeDelta, Utc};
+
+    let lock = JobLock::new(Job::PollLatestRates, "instance-a", TimeDelta::seconds(300));
+    let now = Utc::now();
+    assert!(lock.can_acquire("instance-a", now));
+    assert!(!lock.can_acquire("instance-b", now));
+    assert!(lock.can_acquire("instance-b", now + TimeDelta::seconds(301)));
+}
+
+#[test]
+fn test_price_unit_to_rate() {
+    use super::entity::PriceUnit;
+    use rust_decimal_macros::dec;
+
+    assert_eq!(
+        PriceUnit::UsdPerCoin.to_rate(dec!(50000)),
+        Some(dec!(0.00002))
+    );
+    assert_eq!(
+        PriceUnit::CoinPerUsd.to_rate(dec!(0.00002)),
+        Some(dec!(0.00002))
+    );
+    assert_eq!(PriceUnit::UsdPerCoin.to_rate(dec!(0)), None);
+    assert_eq!(PriceUnit::CoinPerUsd.to_rate(dec!(-1)), None);
+}
+
+#[test]
+fn test_rates_data_rescale() {
+    use rust_decimal_macros::dec;
+
+    let rates = RatesData {
+        usd: dec!(2),
+        idr: dec!(32000),
+        xau: dec!(0.001),
+        ..Default::default()
+    };
+    assert_eq!(rates.rate(Currency::IDR), dec!(32000));
+
+    let ret = rates.rescale(dec!(2)).unwrap();
+    assert_eq!(ret.rate(Currency::USD), dec!(1));
+    assert_eq!(ret.idr, dec!(16000));
+    assert_eq!(ret.xau, dec!(0.0005));
+    assert_eq!(ret.btc, dec!(0));
+    assert!(rates.rescale(dec!(0)).is_none());
+}
+
+#[test]
+fn test_rates_page_after() {
+    use super::entity::{Cursor, Order, OrderBy, Rates, RatesPage, RatesResponse};
+    use chrono::{DateTime, TimeZone, Utc};
+
+    let rates = (1..=5)
+        .map(|day| {
+            RatesResponse::new(
+                "test".to_string(),
+                Rates {
+                    date: Utc.with_ymd_and_hms(2025, 1, day, 0, 0, 0).unwrap(),
+                    base: Currency::USD,
+                    rates: RatesData::default(),
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let page = RatesPage::after(rates.clone(), None, 2, Order::DESC, OrderBy::DataDate);
+    let days = |page: &RatesPage<RatesResponse<Rates>>| -> Vec<DateTime<Utc>> {
+        page.rates_list.iter().map(|rate| rate.data.date).collect()
+    };
+    assert_eq!(days(&page), vec![rates[4].data.date, rates[3].data.date]);
+    let cursor = page.next_cursor.unwrap();
+    assert_eq!(cursor.date(), rates[3].data.date);
+
+    // cursor survives encoding and new rates added before it don't shift the next page
+    let cursor: Cursor = cursor.to_string().parse().unwrap();
+    let mut newer = rates.clone();
+    newer.push(RatesResponse::new(
+        "test".to_string(),
+        Rates {
+            date: Utc.with_ymd_and_hms(2025, 1, 6, 0, 0, 0).unwrap(),
+            base: Currency::USD,
+            rates: RatesData::default(),
+        },
+    ));
+    let page = RatesPage::after(newer, Some(cursor), 2, Order::DESC, OrderBy::DataDate);
+    assert_eq!(days(&page), vec![rates[2].data.date, rates[1].data.date]);
+
+    let page = RatesPage::after(
+        rates.clone(),
+        page.next_cursor,
+        2,
+        Order::DESC,
+        OrderBy::DataDate,
+    );
+    assert_eq!(days(&page), vec![rates[0].data.date]);
+    assert!(page.next_cursor.is_none());
+
+    let page = RatesPage::after(rates.clone(), None, 3, Order::ASC, OrderBy::DataDate);
+    let page = RatesPage::after(rates, page.next_cursor, 3, Order::ASC, OrderBy::DataDate);
+    assert_eq!(days(&page).len(), 2);
+
+    assert!("not-a-cursor".parse::<Cursor>().is_err());
+}
+
+#[test]
+fn test_provider_health_score() {
+    use super::entity::{ProviderCallSample, ProviderHealth};
+    use rust_decimal_macros::dec;
+
+    let sample = |success: bool, latency_ms: u64| ProviderCallSample {
+        at: chrono::Utc::now(),
+        success,
+        latency_ms,
+        completeness: if success { dec!(0.8) } else { dec!(0) },
+    };
+
+    let mut health = ProviderHealth::new("mock");
+    assert_eq!(health.score(), dec!(1));
+    assert!(health.avg_latency_ms().is_none());
+
+    health.record(sample(false, 5000), 3);
+    health.record(sample(true, 100), 3);
+    health.record(sample(true, 300), 3);
+    health.record(sample(true, 200), 3);
+    assert_eq!(health.samples.len(), 3);
+    assert_eq!(health.success_rate(), dec!(1));
+    assert_eq!(health.avg_latency_ms(), Some(200));
+    assert_eq!(health.score(), dec!(0.8));
+
+    health.record(sample(false, 5000), 3);
+    assert_eq!(health.score(), dec!(0.5333));
+}
+
+#[test]
+fn test_rates_change_replay() {
+    use super::entity::{ChangeKind, RatesChange, RatesChangeReplay, RatesKind};
+    use chrono::Utc;
+
+    let date = Utc::now();
+    let change = |kind: ChangeKind, before: Option<&str>, after: &str| {
+        RatesChange::new(
+            kind,
+            RatesKind::Historical,
+            date,
+            "historical/2025/historical-2025-01-01Z.json".to_string(),
+            before.map(str::to_string),
+            Some(after.to_string()),
+        )
+    };
+    let changes = vec![
+        change(ChangeKind::Insert, None, "a"),
+        change(ChangeKind::Update, Some("a"), "b"),
+    ];
+
+    let replay = RatesChangeReplay::replay(date, changes.clone(), Some("b".to_string()));
+    assert!(replay.is_consistent());
+
+    // stored file modified outside storage
+    let replay = RatesChangeReplay::replay(date, changes.clone(), Some("c".to_string()));
+    assert_eq!(replay.broken_at, Some(2));
+
+    let mut gap = changes;
+    gap.push(change(ChangeKind::Update, Some("x"), "c"));
+    let replay = RatesChangeReplay::replay(date, gap, Some("c".to_string()));
+    assert_eq!(replay.broken_at, Some(2));
+
+    assert!(RatesChangeReplay::replay(date, vec![], None).is_consistent());
+}
+
+#[test]
+fn test_sort_rates_by_poll_date() {
+    use super::entity::{Order, OrderBy, Rates, RatesPage, RatesResponse, sort_rates};
+    use chrono::{TimeZone, Utc};
+
+    // rates of older date polled later
+    let rates = [(1, 3), (2, 2), (3, 1)]
+        .into_iter()
+        .map(|(day, poll_day)| {
+            let mut rate = RatesResponse::new(
+                "test".to_string(),
+                Rates {
+                    date: Utc.with_ymd_and_hms(2025, 1, day, 0, 0, 0).unwrap(),
+                    base: Currency::USD,
+                    rates: RatesData::default(),
+                },
+            );
+            rate.poll_date = Utc.with_ymd_and_hms(2025, 2, poll_day, 0, 0, 0).unwrap();
+            rate
+        })
+        .collect::<Vec<_>>();
+    let days = |rates: &[RatesResponse<Rates>]| -> Vec<u32> {
+        rates
+            .iter()
+            .map(|rate| chrono::Datelike::day(&rate.data.date))
+            .collect()
+    };
+
+    let mut sorted = rates.clone();
+    sort_rates(&mut sorted, Order::DESC, OrderBy::DataDate);
+    assert_eq!(days(&sorted), vec![3, 2, 1]);
+
+    sort_rates(&mut sorted, Order::DESC, OrderBy::PollDate);
+    assert_eq!(days(&sorted), vec![1, 2, 3]);
+
+    let page = RatesPage::after(rates.clone(), None, 2, Order::ASC, OrderBy::PollDate);
+    assert_eq!(days(&page.rates_list), vec![3, 2]);
+    let page = RatesPage::after(rates, page.next_cursor, 2, Order::ASC, OrderBy::PollDate);
+    assert_eq!(days(&page.rates_list), vec![1]);
+}
+
+#[test]
+fn test_consistency_report_compare() {
+    use super::entity::{ConsistencyReport, Rates, RatesResponse};
+    use chrono::{TimeZone, Utc};
+    use rust_decimal_macros::dec;
+
+    let rates = |source: &str, data: RatesData| {
+        RatesResponse::new(
+            source.to_string(),
+            Rates {
+                date: Utc.with_ymd_and_hms(2025, 1, 2, 0, 0, 0).unwrap(),
+                base: Currency::USD,
+                rates: data,
+            },
+        )
+    };
+    let first = rates(
+        "first",
+        RatesData {
+            usd: dec!(1),
+            eur: dec!(0.9),
+            idr: dec!(16000),
+            jpy: dec!(150),
+            ..Default::default()
+        },
+    );
+    let second = rates(
+        "second",
+        RatesData {
+            usd: dec!(1),
+            eur: dec!(0.91),
+            idr: dec!(16100),
+            gbp: dec!(0.8),
+            ..Default::default()
+        },
+    );
+
+    let report = ConsistencyReport::compare(&first, &second, dec!(0.5)).unwrap();
+    assert_eq!(report.first_provider, "first");
+    assert_eq!(report.second_provider, "second");
+    assert_eq!(report.compared, 3);
+    assert_eq!(report.discrepancies.len(), 2);
+    assert_eq!(report.discrepancies[0].currency, Currency::EUR);
+    assert_eq!(report.discrepancies[0].diff_percent, dec!(1.1111));
+    assert_eq!(report.discrepancies[1].currency, Currency::IDR);
+    assert_eq!(report.missing, vec![Currency::GBP, Currency::JPY]);
+    assert!(!report.is_consistent());
+
+    let report = ConsistencyReport::compare(&first, &second, dec!(2)).unwrap();
+    assert!(report.is_consistent());
+
+    let mut other_base = second.clone();
+    other_base.data.base = Currency::EUR;
+    assert!(ConsistencyReport::compare(&first, &other_base, dec!(1)).is_err());
+}
+
+#[test]
+fn test_record_provenance() {
+    use super::entity::{Rates, RatesResponse};
+    use chrono::{TimeZone, Utc};
+
+    let mut rates = RatesResponse::new("provider".to_string(), Rates::default());
+    // rates without provenance are stored as before
+    let json = serde_json::to_value(&rates).unwrap();
+    assert!(json.get("provenance").is_none());
+
+    let at = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+    rates.record_provenance(Currency::BTC, "csv", at);
+    rates.record_provenance(Currency::ETH, "csv", at);
+    rates.record_provenance(Currency::BTC, "other provider", at);
+    assert_eq!(rates.provenance.len(), 2);
+    assert_eq!(rates.provenance[1].currency, Currency::BTC);
+    assert_eq!(rates.provenance[1].source, "other provider");
+
+    let json = serde_json::to_string(&rates).unwrap();
+    let ret: RatesResponse<Rates> = serde_json::from_str(&json).unwrap();
+    assert_eq!(ret.provenance, rates.provenance);
+}
+
+#[test]
+fn test_provider_capabilities_trim() {
+    use super::currency::CurrencyKind;
+    use super::entity::ProviderCapabilities;
+    use chrono::{TimeDelta, TimeZone, Utc};
+
+    let history_start = Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap();
+    let caps = ProviderCapabilities::from_kinds(&[CurrencyKind::Fiat])
+        .with_history_start(history_start)
+        .with_max_timeseries_span(TimeDelta::days(365));
+    assert!(caps.supports(Currency::USD));
+    assert!(!caps.supports(Currency::BTC));
+    assert!(!caps.symbols().contains(&Currency::XAU));
+    assert_eq!(
+        caps.enabled_symbols(&[Currency::USD, Currency::IDR, Currency::BTC]),
+        vec![Currency::USD, Currency::IDR]
+    );
+    assert!(!caps.covers(Utc.with_ymd_and_hms(1999, 12, 31, 0, 0, 0).unwrap()));
+    assert!(caps.covers(history_start));
+
+    let ranges = caps.split_date_range(
+        Utc.with_ymd_and_hms(1998, 1, 1, 0, 0, 0).unwrap(),
+        Utc.with_ymd_and_hms(2001, 6, 1, 0, 0, 0).unwrap(),
+    );
+    assert_eq!(ranges.len(), 2);
+    assert_eq!(ranges[0].0, history_start);
+    assert_eq!(ranges[1].0, ranges[0].1 + TimeDelta::seconds(1));
+    assert_eq!(
+        ranges[1].1,
+        Utc.with_ymd_and_hms(2001, 6, 1, 0, 0, 0).unwrap()
+    );
+    let ranges = ProviderCapabilities::default()
+        .split_date_range(history_start, history_start + TimeDelta::days(5000));
+    assert_eq!(ranges.len(), 1);
+
+    let union = caps.union(&ProviderCapabilities::from_kinds(&[CurrencyKind::Crypto]));
+    assert!(union.supports(Currency::USD));
+    assert!(union.supports(Currency::BTC));
+    assert_eq!(union.history_start, None);
+    assert_eq!(union.max_timeseries_span, None);
+}
+
+#[test]
+fn test_rates_validate() {
+    use super::entity::{Rates, RatesResponse, RatesViolation};
+    use chrono::{TimeDelta, TimeZone, Utc};
+    use rust_decimal_macros::dec;
+
+    let mut rates = RatesData::default();
+    rates.set_rate(Money::new_money(Currency::USD, dec!(1)));
+    rates.set_rate(Money::new_money(Currency::IDR, dec!(15500)));
+    let date = Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap();
+    let valid = RatesResponse::new(
+        "validate".to_string(),
+        Rates {
+            date,
+            base: Currency::USD,
+            rates,
+        },
+    );
+    assert!(valid.validate().is_ok());
+
+    let mut invalid = valid.clone();
+    invalid
+        .data
+        .rates
+        .set_rate(Money::new_money(Currency::USD, dec!(0)));
+    invalid
+        .data
+        .rates
+        .set_rate(Money::new_money(Currency::EUR, dec!(-0.9)));
+    invalid.data.date = Utc::now() + TimeDelta::days(3);
+    let violations = invalid.validate().unwrap_err();
+    assert_eq!(violations.len(), 4);
+    assert!(violations.contains(&RatesViolation::EmptyBase {
+        base: Currency::USD
+    }));
+    assert!(violations.contains(&RatesViolation::UsdRate { rate: dec!(0) }));
+    assert!(violations.contains(&RatesViolation::NegativeRate {
+        currency: Currency::EUR,
+        rate: dec!(-0.9)
+    }));
+
+    // rates of failed polls are stored as they are
+    invalid.error = Some("provider down".to_string());
+    assert!(invalid.validate().is_ok());
+
+    let mut old = valid.clone();
+    old.data.date = Utc.with_ymd_and_hms(1980, 1, 1, 0, 0, 0).unwrap();
+    assert!(matches!(
+        old.validate().unwrap_err().as_slice(),
+        [RatesViolation::ImplausibleDate { .. }]
+    ));
+}
@@ -0,0 +1,436 @@
+// expression.rs evaluator of rate expressions typed into calculator boxes of CLI and dashboard,
+// e.g. "(USD 100 + EUR 50) in IDR", "XAU 1 * 0.5 in USD @2023-01-01".
+
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use tracing::instrument;
+
+use super::{
+    Currency, ForexError, ForexResult, FormatOptions, Money,
+    entity::{ExpressionResponse, RatesData},
+    interface::ForexStorage,
+};
+
+pub(crate) const ERROR_EXPRESSION_FORMAT: &str = "The expression must be written as <MONEY> [+|-|*|/ <MONEY>|<NUMBER> ...] [in <CURRENCY>] [@YYYY-MM-DD], money as <CODE> <AMOUNT> or <AMOUNT> <CODE>, grouped with parentheses, e.g. (USD 100 + EUR 50) in IDR, XAU 1 * 0.5 in USD @2023-01-01.";
+
+/// longest expression in chars, bounding its tokens and depth of evaluation.
+pub(crate) const MAX_EXPRESSION_LEN: usize = 1000;
+
+/// deepest nesting of parentheses and negations in expression.
+pub(crate) const MAX_EXPRESSION_DEPTH: usize = 32;
+
+/// Parsed rate expression, monies are converted into target currency with rates at the date, latest if none.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    pub(crate) expr: Expr,
+
+    /// currency of result, currency of the first money if not given.
+    pub target: Currency,
+
+    pub date: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Expr {
+    Money(Money),
+    Number(Decimal),
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(Decimal),
+    Word(String),
+    Date(DateTime<Utc>),
+    Op(Op),
+    Open,
+    Close,
+}
+
+/// value while evaluating, money is already in target currency.
+#[derive(Debug, Clone, Copy)]
+enum Value {
+    Money(Decimal),
+    Number(Decimal),
+}
+
+impl FromStr for Expression {
+    type Err = ForexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.chars().count() > MAX_EXPRESSION_LEN {
+            return Err(ForexError::client_error(
+                format!(
+                    "expression must be at most {} characters",
+                    MAX_EXPRESSION_LEN
+                )
+                .as_str(),
+            ));
+        }
+        let tokens = tokenize(s)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+        };
+        let expr = parser.sum()?;
+
+        let target = match parser.peek() {
+            Some(Token::Word(word)) if word == "IN" => {
+                parser.pos += 1;
+                match parser.next() {
+                    Some(Token::Word(code)) => Some(parse_currency(&code)?),
+                    _ => return Err(ForexError::client_error(ERROR_EXPRESSION_FORMAT)),
+                }
+            }
+            _ => None,
+        };
+        let date = match parser.peek() {
+            Some(Token::Date(date)) => {
+                let date = *date;
+                parser.pos += 1;
+                Some(date)
+            }
+            _ => None,
+        };
+        if let Some(token) = parser.peek() {
+            return Err(ForexError::client_error(
+                format!(
+                    "unexpected {:?} in expression. {}",
+                    token, ERROR_EXPRESSION_FORMAT
+                )
+                .as_str(),
+            ));
+        }
+
+        let Some(target) = target.or_else(|| expr.first_currency()) else {
+            return Err(ForexError::client_error(
+                "expression must contain money, e.g. USD 100 * 2",
+            ));
+        };
+
+        Ok(Self { expr, target, date })
+    }
+}
+
+impl Expression {
+    /// evaluate with the rates, result must be money, e.g. dividing money by money is a number.
+    pub fn eval(&self, rates: &RatesData) -> ForexResult<Money> {
+        match self.expr.eval(rates, self.target)? {
+            Value::Money(amount) => Ok(Money::new_money(self.target, amount)),
+            Value::Number(_) => Err(ForexError::client_error(
+                "expression results in a number instead of money, e.g. USD 100 / EUR 50",
+            )),
+        }
+    }
+}
+
+impl Expr {
+    fn first_currency(&self) -> Option<Currency> {
+        match self {
+            Self::Money(money) => Some(money.currency()),
+            Self::Number(_) => None,
+            Self::Neg(expr) => expr.first_currency(),
+            Self::Binary(_, left, right) => {
+                left.first_currency().or_else(|| right.first_currency())
+            }
+        }
+    }
+
+    fn eval(&self, rates: &RatesData, target: Currency) -> ForexResult<Value> {
+        let overflow = || ForexError::client_error("expression overflows");
+        let ret = match self {
+            Self::Money(money) if money.currency() == target => Value::Money(money.amount()),
+            Self::Money(money) => {
+                let converted = Money::convert(rates, *money, target)?;
+                if converted.amount().is_zero() && !money.amount().is_zero() {
+                    let missing = [money.currency(), target]
+                        .into_iter()
+                        .find(|currency| rates.rate(*currency).is_zero())
+                        .unwrap_or(money.currency());
+                    return Err(ForexError::client_error(
+                        format!(
+                            "rate of {} is not available for this expression",
+                            missing.code()
+                        )
+                        .as_str(),
+                    ));
+                }
+                Value::Money(converted.amount())
+            }
+            Self::Number(number) => Value::Number(*number),
+            Self::Neg(expr) => match expr.eval(rates, target)? {
+                Value::Money(amount) => Value::Money(-amount),
+                Value::Number(number) => Value::Number(-number),
+            },
+            Self::Binary(op, left, right) => {
+                let left = left.eval(rates, target)?;
+                let right = right.eval(rates, target)?;
+                match (op, left, right) {
+                    (Op::Add, Value::Money(a), Value::Money(b)) => {
+                        Value::Money(a.checked_add(b).ok_or_else(overflow)?)
+                    }
+                    (Op::Add, Value::Number(a), Value::Number(b)) => {
+                        Value::Number(a.checked_add(b).ok_or_else(overflow)?)
+                    }
+                    (Op::Sub, Value::Money(a), Value::Money(b)) => {
+                        Value::Money(a.checked_sub(b).ok_or_else(overflow)?)
+                    }
+                    (Op::Sub, Value::Number(a), Value::Number(b)) => {
+                        Value::Number(a.checked_sub(b).ok_or_else(overflow)?)
+                    }
+                    (Op::Add | Op::Sub, _, _) => {
+                        return Err(ForexError::client_error(
+                            "money can only be added to or subtracted from money, e.g. USD 100 + EUR 50",
+                        ));
+                    }
+                    (Op::Mul, Value::Money(a), Value::Number(b))
+                    | (Op::Mul, Value::Number(b), Value::Money(a)) => {
+                        Value::Money(a.checked_mul(b).ok_or_else(overflow)?)
+                    }
+                    (Op::Mul, Value::Number(a), Value::Number(b)) => {
+                        Value::Number(a.checked_mul(b).ok_or_else(overflow)?)
+                    }
+                    (Op::Mul, Value::Money(_), Value::Money(_)) => {
+                        return Err(ForexError::client_error(
+                            "money can only be multiplied by number, e.g. XAU 1 * 0.5",
+                        ));
+                    }
+                    (Op::Div, _, Value::Number(b) | Value::Money(b)) if b.is_zero() => {
+                        return Err(ForexError::client_error("expression divides by zero"));
+                    }
+                    (Op::Div, Value::Money(a), Value::Number(b)) => {
+                        Value::Money(a.checked_div(b).ok_or_else(overflow)?)
+                    }
+                    (Op::Div, Value::Money(a), Value::Money(b))
+                    | (Op::Div, Value::Number(a), Value::Number(b)) => {
+                        Value::Number(a.checked_div(b).ok_or_else(overflow)?)
+                    }
+                    (Op::Div, Value::Number(_), Value::Money(_)) => {
+                        return Err(ForexError::client_error(
+                            "number can't be divided by money, e.g. 100 / USD 1",
+                        ));
+                    }
+                }
+            }
+        };
+
+        Ok(ret)
+    }
+}
+
+/// parse and evaluate expression with latest rates, or historical rates at its date.
+#[instrument(skip(storage), ret)]
+pub async fn evaluate<FS>(storage: &FS, expression: &str) -> ForexResult<ExpressionResponse>
+where
+    FS: ForexStorage,
+{
+    let parsed = Expression::from_str(expression)?;
+    let rates = match parsed.date {
+        Some(date) => storage.get_historical(date).await?,
+        None => storage.get_latest().await?,
+    };
+    if rates.error.is_some() {
+        return Err(ForexError::internal_error(
+            "rates of this expression are not available at the moment, please try again later",
+        ));
+    }
+
+    let result = parsed.eval(&rates.data.rates)?;
+
+    Ok(ExpressionResponse {
+        expression: expression.trim().to_string(),
+        date: rates.data.date,
+        code: result.format(FormatOptions::CODE),
+        symbol: result.format(FormatOptions::SYMBOL),
+        result,
+    })
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+
+    /// current nesting of parentheses and negations.
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let ret = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        ret
+    }
+
+    fn nest(&mut self) -> ForexResult<()> {
+        self.depth += 1;
+        if self.depth > MAX_EXPRESSION_DEPTH {
+            return Err(ForexError::client_error(
+                format!(
+                    "expression must be nested at most {} levels deep",
+                    MAX_EXPRESSION_DEPTH
+                )
+                .as_str(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    // sum := term (('+' | '-') term)*
+    fn sum(&mut self) -> ForexResult<Expr> {
+        let mut ret = self.term()?;
+        while let Some(Token::Op(op @ (Op::Add | Op::Sub))) = self.peek() {
+            let op = *op;
+            self.pos += 1;
+            ret = Expr::Binary(op, Box::new(ret), Box::new(self.term()?));
+        }
+
+        Ok(ret)
+    }
+
+    // term := factor (('*' | '/') factor)*
+    fn term(&mut self) -> ForexResult<Expr> {
+        let mut ret = self.factor()?;
+        while let Some(Token::Op(op @ (Op::Mul | Op::Div))) = self.peek() {
+            let op = *op;
+            self.pos += 1;
+            ret = Expr::Binary(op, Box::new(ret), Box::new(self.factor()?));
+        }
+
+        Ok(ret)
+    }
+
+    // factor := '-' factor | '(' sum ')' | CODE NUMBER | NUMBER CODE | NUMBER
+    fn factor(&mut self) -> ForexResult<Expr> {
+        match self.next() {
+            Some(Token::Op(Op::Sub)) => {
+                self.nest()?;
+                let ret = Expr::Neg(Box::new(self.factor()?));
+                self.depth -= 1;
+                Ok(ret)
+            }
+            Some(Token::Open) => {
+                self.nest()?;
+                let ret = self.sum()?;
+                self.depth -= 1;
+                match self.next() {
+                    Some(Token::Close) => Ok(ret),
+                    _ => Err(ForexError::client_error(
+                        "expression is missing closing parenthesis",
+                    )),
+                }
+            }
+            Some(Token::Word(code)) if code != "IN" => match self.next() {
+                Some(Token::Number(amount)) => Ok(Expr::Money(Money::new_money(
+                    parse_currency(&code)?,
+                    amount,
+                ))),
+                _ => Err(ForexError::client_error(ERROR_EXPRESSION_FORMAT)),
+            },
+            Some(Token::Number(amount)) => match self.peek() {
+                Some(Token::Word(code)) if code != "IN" => {
+                    let currency = parse_currency(code)?;
+                    self.pos += 1;
+                    Ok(Expr::Money(Money::new_money(currency, amount)))
+                }
+                _ => Ok(Expr::Number(amount)),
+            },
+            _ => Err(ForexError::client_error(ERROR_EXPRESSION_FORMAT)),
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> ForexResult<Vec<Token>> {
+    let mut ret = vec![];
+    let mut chars = expression.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '+' => Token::Op(Op::Add),
+            '-' => Token::Op(Op::Sub),
+            '*' | '×' => Token::Op(Op::Mul),
+            '/' => Token::Op(Op::Div),
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '@' => {
+                let mut date = String::new();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || *c == '-') {
+                    date.push(c);
+                }
+                let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|_| {
+                    ForexError::client_error("date of expression must be in form of @YYYY-MM-DD")
+                })?;
+                Token::Date(date.and_time(Default::default()).and_utc())
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut number = String::from(c);
+                while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || *c == '.' || *c == ',')
+                {
+                    number.push(c);
+                }
+                let number = parse_number(&number)?;
+                Token::Number(number)
+            }
+            c if c.is_ascii_alphabetic() => {
+                let mut word = String::from(c);
+                while let Some(c) = chars.next_if(char::is_ascii_alphabetic) {
+                    word.push(c);
+                }
+                Token::Word(word.to_uppercase())
+            }
+            c => {
+                return Err(ForexError::client_error(
+                    format!("unexpected character {} in expression", c).as_str(),
+                ));
+            }
+        };
+        ret.push(token);
+    }
+
+    Ok(ret)
+}
+
+fn parse_currency(code: &str) -> ForexResult<Currency> {
+    Currency::from_str(code).map_err(|_| {
+        ForexError::client_error(format!("unknown currency {} in expression", code).as_str())
+    })
+}
+
+/// number with optional commas as thousands separators, e.g. 1,500.25. other commas are ambiguous,
+/// e.g. 1,5 is either 1.5 or 15, and rejected.
+fn parse_number(number: &str) -> ForexResult<Decimal> {
+    let invalid =
+        || ForexError::client_error(format!("invalid number {} in expression", number).as_str());
+    let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if integer.contains(',') {
+        let mut groups = integer.split(',');
+        let first = groups.next().unwrap_or_default();
+        if fraction.contains(',')
+            || first.is_empty()
+            || first.len() > 3
+            || groups.any(|group| group.len() != 3)
+        {
+            return Err(invalid());
+        }
+    } else if fraction.contains(',') {
+        return Err(invalid());
+    }
+
+    Decimal::from_str(&number.replace(',', "")).map_err(|_| invalid())
+}
@@ -0,0 +1,106 @@
+use std::str::FromStr;
+
+use chrono::{TimeZone, Utc};
+use rust_decimal_macros::dec;
+
+use crate::forex::{
+    Currency, ForexError, Money,
+    entity::RatesData,
+    expression::{self, Expression},
+    interface::ForexStorage,
+};
+use crate::global::test_support::{self, temp_storage};
+
+fn rates_data() -> RatesData {
+    let mut ret = RatesData::default();
+    ret.set_rate(Money::USD(dec!(1)));
+    ret.set_rate(Money::EUR(dec!(0.5)));
+    ret.set_rate(Money::IDR(dec!(16000)));
+    ret.set_rate(Money::XAU(dec!(0.0005)));
+    ret
+}
+
+#[test]
+fn test_eval_expression() {
+    let rates = rates_data();
+    let cases = vec![
+        ("(USD 100 + EUR 50) in IDR", Money::IDR(dec!(3200000))),
+        ("XAU 1 * 0.5 in USD", Money::USD(dec!(1000))),
+        ("usd 1,000.50", Money::USD(dec!(1000.50))),
+        ("100 EUR - USD 50", Money::EUR(dec!(75))),
+        ("2 * (10 usd + 5 usd) / 3", Money::USD(dec!(10))),
+        ("-USD 10 + 30 USD", Money::USD(dec!(20))),
+        (
+            "IDR 1,600,000 / (USD 10 / USD 5) in USD",
+            Money::USD(dec!(50)),
+        ),
+    ];
+    for (expression, expected) in cases {
+        let parsed = Expression::from_str(expression).unwrap();
+        assert_eq!(parsed.eval(&rates).unwrap(), expected, "{}", expression);
+    }
+
+    let parsed = Expression::from_str("XAU 1 * 0.5 in USD @2023-01-01").unwrap();
+    assert_eq!(parsed.target, Currency::USD);
+    assert_eq!(
+        parsed.date,
+        Some(Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap())
+    );
+
+    let invalid = vec![
+        "",
+        "100 * 2",
+        "USD 100 + 5",
+        "USD 100 * EUR 2",
+        "USD 100 / EUR 50",
+        "5 / USD 1",
+        "USD 100 / 0",
+        "(USD 100 + EUR 50",
+        "USD 100 in",
+        "USD 100 in XYZ",
+        "USD 100 @yesterday",
+        "USD 100 USD 5",
+        "USD 100 % 5",
+        "USD 1,5",
+        "USD 1,0000",
+        "USD 1.000,50",
+        "USD ,100",
+    ];
+    for expression in invalid {
+        let ret = Expression::from_str(expression).and_then(|parsed| parsed.eval(&rates));
+        assert!(ret.is_err(), "{}", expression);
+    }
+
+    // no rate of GBP
+    let ret = Expression::from_str("USD 100 in GBP").and_then(|parsed| parsed.eval(&rates));
+    let err = ret.unwrap_err();
+    assert!(matches!(err, ForexError::ClientError(_)));
+    assert!(err.message().contains("GBP"), "{}", err.message());
+
+    let nested = format!("{}USD 1{}", "(".repeat(33), ")".repeat(33));
+    assert!(Expression::from_str(&nested).is_err());
+    let nested = format!("{}USD 1{}", "(".repeat(32), ")".repeat(32));
+    assert!(Expression::from_str(&nested).is_ok());
+    assert!(Expression::from_str(&format!("{}USD 1", "-".repeat(40))).is_err());
+    assert!(Expression::from_str(&vec!["USD 1"; 300].join(" + ")).is_err());
+}
+
+#[tokio::test]
+async fn test_evaluate_expression() {
+    let (storage, _root) = temp_storage("expression");
+    let date = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+    let mut rates = test_support::rates(date, &[]);
+    rates.data.rates = rates_data();
+    storage.insert_historical(date, &rates).await.unwrap();
+
+    let ret = expression::evaluate(&storage, " (USD 100 + EUR 50) in IDR @2023-01-01 ")
+        .await
+        .unwrap();
+    assert_eq!(ret.expression, "(USD 100 + EUR 50) in IDR @2023-01-01");
+    assert_eq!(ret.date, date);
+    assert_eq!(ret.result, Money::IDR(dec!(3200000)));
+
+    // no historical rates at the date
+    let ret = expression::evaluate(&storage, "USD 100 in IDR @2023-01-02").await;
+    assert!(ret.is_err());
+}
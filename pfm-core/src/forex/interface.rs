@@ -1,24 +1,47 @@
 use std::fmt::Debug;
 
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeDelta, Utc};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use super::currency::Currency;
+use super::entity::BackfillCheckpoint;
+use super::entity::ConsistencyReport;
 use super::entity::ConversionResponse;
-use super::entity::Order;
+use super::entity::CryptoPrice;
+use super::entity::Cursor;
+use super::entity::HistoricalVersion;
+use super::entity::Job;
+use super::entity::JobRun;
+use super::entity::ProviderCallSample;
+use super::entity::ProviderCapabilities;
+use super::entity::ProviderHealth;
 use super::entity::Rates;
+use super::entity::RatesChange;
+use super::entity::RatesChangeReplay;
 use super::entity::RatesList;
+use super::entity::RatesPage;
+use super::entity::RatesProvenance;
 use super::entity::RatesResponse;
+use super::entity::SignatureReport;
+use super::entity::SloReport;
+use super::entity::Sparkline;
+use super::entity::StorageStats;
+use super::entity::WebhookDelivery;
+use super::entity::{Order, OrderBy};
 use super::money::Money;
 use crate::error::Error;
-use crate::error::{BaseError, ClientError, InternalError};
+use crate::error::{BaseError, ClientError, InternalError, NotFoundError};
 use thiserror::Error;
 
 pub(super) const ERROR_PREFIX: &str = "[FOREX]";
 
 pub type ForexResult<T> = Result<T, ForexError>;
 
+/// new rates of a date, each labeled with its source.
+pub type HistoricalUpdate = (DateTime<Utc>, Vec<(Money, String)>);
+
 #[derive(Debug, Error)]
 pub enum ForexError {
     #[error("{ERROR_PREFIX} generic error: {0}")]
@@ -27,6 +50,9 @@ pub enum ForexError {
     #[error("{ERROR_PREFIX} client error: {0}")]
     ClientError(#[from] ClientError),
 
+    #[error("{ERROR_PREFIX} not found error: {0}")]
+    NotFoundError(#[from] NotFoundError),
+
     #[error("{ERROR_PREFIX} internal error: {0}")]
     InternalError(#[from] InternalError),
 }
@@ -39,9 +65,35 @@ impl ForexError {
         ForexError::ClientError(ClientError::from_msg(err_msg))
     }
 
+    pub fn not_found_error(err_msg: &str) -> Self {
+        ForexError::NotFoundError(NotFoundError::from_msg(err_msg))
+    }
+
     pub fn internal_error(err_msg: &str) -> Self {
         ForexError::InternalError(InternalError::from_msg(err_msg))
     }
+
+    /// message of the error without its kind and causes.
+    pub fn message(&self) -> String {
+        match self {
+            Self::Error(err) => err.cause(),
+            Self::ClientError(err) => err.cause(),
+            Self::NotFoundError(err) => err.cause(),
+            Self::InternalError(err) => err.cause(),
+        }
+    }
+
+    /// causes of the error below its message joined outermost first, none if it has no causes.
+    pub fn details(&self) -> Option<String> {
+        let causes = match self {
+            Self::Error(err) => err.causes(),
+            Self::ClientError(err) => err.causes(),
+            Self::NotFoundError(err) => err.causes(),
+            Self::InternalError(err) => err.causes(),
+        };
+
+        (!causes.is_empty()).then(|| causes.join(": "))
+    }
 }
 
 impl BaseError for ForexError {
@@ -84,6 +136,41 @@ pub trait ForexTimeseriesRates {
         base: Currency,
     ) -> ForexResult<Vec<RatesResponse<Rates>>>;
 }
+
+#[async_trait]
+pub trait ForexCryptoPrices {
+    /// daily close prices of the crypto currencies on the date as USD based rates, like stored rates.
+    /// currencies the provider has no price of are left out.
+    async fn daily_close(
+        &self,
+        date: DateTime<Utc>,
+        currencies: &[Currency],
+    ) -> ForexResult<Vec<CryptoPrice>>;
+
+    /// current prices of the crypto currencies as USD based rates, dated when they're fetched.
+    /// currencies the provider has no price of are left out.
+    async fn latest(&self, currencies: &[Currency]) -> ForexResult<Vec<CryptoPrice>>;
+}
+
+#[async_trait]
+pub trait ForexProviderCapabilities {
+    /// currencies and dates served by the provider, all of them by default.
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities::default()
+    }
+
+    /// calls left in current quota period, unmetered when None.
+    /// providers not reporting their usage are assumed to have whole quota of their plan left.
+    async fn quota_remaining(&self) -> ForexResult<Option<u32>> {
+        Ok(self.capabilities().quota)
+    }
+
+    /// each attempt of a call already times out within provider timeout, e.g. providers of a chain,
+    /// so callers don't bound the whole call.
+    fn times_out_attempts(&self) -> bool {
+        false
+    }
+}
 ///////////////
 
 /////////////// INVOKED FROM HTTP and CRON SERVICE, and APP.
@@ -119,13 +206,21 @@ pub trait ForexStorage {
     async fn insert_historical_batch(&self, rates: Vec<RatesResponse<Rates>>) -> ForexResult<()>;
 
     /// update some existing rates data with new ones
-    /// new_data contains money, the currency and the values.
+    /// new_data contains money, the currency and the values, each labeled with its source.
+    /// the sources are recorded in provenance of stored rates.
     async fn update_historical_rates_data(
         &self,
         date: DateTime<Utc>,
-        new_data: Vec<Money>,
+        new_data: Vec<(Money, String)>,
     ) -> ForexResult<RatesResponse<Rates>>;
 
+    /// update existing rates data of many dates at once, e.g. corrections of long history.
+    /// updates of the same date are merged, later ones win. returns updated rates ordered by date.
+    async fn update_historical_bulk(
+        &self,
+        updates: Vec<HistoricalUpdate>,
+    ) -> ForexResult<Vec<RatesResponse<Rates>>>;
+
     /// get historical rates
     async fn get_historical(&self, date: DateTime<Utc>) -> ForexResult<RatesResponse<Rates>>;
 
@@ -136,25 +231,244 @@ pub trait ForexStorage {
         end: DateTime<Utc>,
     ) -> ForexResult<Vec<RatesResponse<Rates>>>;
 
-    /// get list of latest rates returning list and has next or not
+    /// get list of latest rates returning list and has next or not.
+    /// page based, prefer cursor based [`ForexStoragePagination::get_latest_page`] for stable paging.
     async fn get_latest_list(
         &self,
         page: u32,
         size: u32,
         order: Order,
+        order_by: OrderBy,
     ) -> ForexResult<RatesList<RatesResponse<Rates>>>;
 
-    /// get list of historical rates returning list and has next or not
+    /// get list of historical rates returning list and has next or not.
+    /// page based, prefer cursor based [`ForexStoragePagination::get_historical_page`] for stable paging.
     async fn get_historical_list(
         &self,
         page: u32,
         size: u32,
         order: Order,
+        order_by: OrderBy,
     ) -> ForexResult<RatesList<RatesResponse<Rates>>>;
 }
 
+#[async_trait]
+pub trait ForexStorageRebased {
+    /// get historical rates at the date rebased into base currency, e.g. for non USD based clients
+    async fn get_historical_rebased(
+        &self,
+        date: DateTime<Utc>,
+        base: Currency,
+    ) -> ForexResult<RatesResponse<Rates>>;
+}
+
+#[async_trait]
+pub trait ForexStorageSparse {
+    /// get historical rates between start and end with only rates of the currencies decoded, others are zero.
+    /// cheaper than full decoding for long range queries of few currencies, e.g. single pair.
+    async fn get_historical_range_of(
+        &self,
+        currencies: &[Currency],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> ForexResult<Vec<RatesResponse<Rates>>>;
+}
+
+/// Rates are validated with [`RatesResponse::validate`] before every write, invalid ones are rejected.
+pub trait ForexStorageValidation: Sized {
+    /// storage writing invalid rates too, their violations are only logged.
+    fn forced(&self) -> Self;
+}
+
+#[async_trait]
+pub trait ForexStorageSnapshots {
+    /// get latest rates snapshot with the id, none when it's not stored or already cleared
+    async fn get_latest_by_id(&self, id: Uuid) -> ForexResult<Option<RatesResponse<Rates>>>;
+}
+
+#[async_trait]
+pub trait ForexStoragePagination {
+    /// get page of latest rates coming after the cursor, starting from the first one when no cursor
+    async fn get_latest_page(
+        &self,
+        cursor: Option<Cursor>,
+        size: u32,
+        order: Order,
+        order_by: OrderBy,
+    ) -> ForexResult<RatesPage<RatesResponse<Rates>>>;
+
+    /// get page of historical rates coming after the cursor, starting from the first one when no cursor
+    async fn get_historical_page(
+        &self,
+        cursor: Option<Cursor>,
+        size: u32,
+        order: Order,
+        order_by: OrderBy,
+    ) -> ForexResult<RatesPage<RatesResponse<Rates>>>;
+}
+
 #[async_trait]
 pub trait ForexStorageDeletion {
     /// clear all inside forex latest directory except latest one
     async fn clear_latest(&self) -> ForexResult<()>;
+
+    /// delete oldest archived latest rates files until at least the bytes are freed,
+    /// returning deleted paths relative to storage root.
+    async fn prune_latest_archive(&self, bytes: u64) -> ForexResult<Vec<String>>;
+}
+
+#[async_trait]
+pub trait ForexStorageStats {
+    /// get file counts, sizes and dates of latest, historical and metadata directories
+    async fn stats(&self) -> ForexResult<StorageStats>;
+}
+
+#[async_trait]
+pub trait ForexStorageProvenance {
+    /// get provenance metadata of historical rates at the date
+    async fn get_historical_provenance(&self, date: DateTime<Utc>) -> ForexResult<RatesProvenance>;
+
+    /// get list of historical dates within range whose data was modified after initial insert
+    async fn get_modified_historical_dates(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> ForexResult<Vec<DateTime<Utc>>>;
+}
+
+#[async_trait]
+pub trait ForexStorageMigration {
+    /// rename latest rates files written with outdated filename scheme into current one,
+    /// returning renamed paths relative to storage root.
+    async fn migrate_latest_filenames(&self) -> ForexResult<Vec<String>>;
+}
+
+#[async_trait]
+pub trait ForexStorageSignatures {
+    /// verify signatures of all stored latest and historical rates files against hex encoded Ed25519 public key
+    async fn verify_signatures(&self, public_key: &str) -> ForexResult<SignatureReport>;
+}
+
+#[async_trait]
+pub trait ForexStorageJournal {
+    /// get changes of latest and historical rates files of the date from journal, ordered from the oldest
+    async fn get_changes(&self, date: DateTime<Utc>) -> ForexResult<Vec<RatesChange>>;
+
+    /// replay journaled changes of historical rates at the date against currently stored file
+    async fn replay_changes(&self, date: DateTime<Utc>) -> ForexResult<RatesChangeReplay>;
+}
+
+#[async_trait]
+pub trait ForexStorageVersions {
+    /// get stored versions of historical rates file at the date, ordered from the oldest
+    async fn get_historical_versions(
+        &self,
+        date: DateTime<Utc>,
+    ) -> ForexResult<Vec<HistoricalVersion>>;
+
+    /// get historical rates at the date as written in the version
+    async fn get_historical_version(
+        &self,
+        date: DateTime<Utc>,
+        version: u32,
+    ) -> ForexResult<RatesResponse<Rates>>;
+}
+
+#[async_trait]
+pub trait ForexStorageBootstrap {
+    /// whether there's no latest rates stored yet, e.g. fresh deployment.
+    async fn is_empty(&self) -> ForexResult<bool>;
+}
+
+#[async_trait]
+pub trait ForexStorageSparklines {
+    /// get rolling window of latest daily rates of the currency against USD
+    async fn get_sparkline(&self, currency: Currency) -> ForexResult<Sparkline>;
+
+    /// rebuild sparklines of all currencies from latest historical rates, e.g. for data stored before sparklines existed
+    async fn rebuild_sparklines(&self) -> ForexResult<()>;
+}
+
+#[async_trait]
+pub trait ForexStorageProviderHealth {
+    /// append outcome of a call to the provider into its health
+    async fn record_provider_call(
+        &self,
+        provider: &str,
+        sample: ProviderCallSample,
+    ) -> ForexResult<()>;
+
+    /// get health of providers having recorded calls
+    async fn get_provider_health(&self) -> ForexResult<Vec<ProviderHealth>>;
+}
+
+#[async_trait]
+pub trait ForexStorageJobRuns {
+    /// store outcome of a job run
+    async fn insert_job_run(&self, run: &JobRun) -> ForexResult<()>;
+
+    /// get latest job runs first, optionally only of the job, at most limit runs
+    async fn get_job_runs(&self, job: Option<Job>, limit: u32) -> ForexResult<Vec<JobRun>>;
+}
+
+#[async_trait]
+pub trait ForexStorageJobLock {
+    /// take or renew lock of the job for holder until ttl passed.
+    /// returns false if the lock is held by other holder and not yet expired.
+    async fn try_lock_job(&self, job: Job, holder: &str, ttl: TimeDelta) -> ForexResult<bool>;
+}
+
+#[async_trait]
+pub trait ForexRatesWebhook {
+    /// deliver polled rates to registered webhooks, returning outcome of each delivery
+    async fn publish(&self, rates: &RatesResponse<Rates>) -> Vec<WebhookDelivery>;
+}
+
+#[async_trait]
+pub trait ForexStorageWebhookDeliveries {
+    /// store outcome of a webhook delivery
+    async fn insert_webhook_delivery(&self, delivery: &WebhookDelivery) -> ForexResult<()>;
+}
+
+#[async_trait]
+pub trait ForexStorageSlo {
+    /// store evaluation of freshness SLO, dropping evaluations older than retention
+    async fn insert_slo_report(&self, report: &SloReport) -> ForexResult<()>;
+
+    /// get latest evaluations since the date first, at most limit evaluations
+    async fn get_slo_reports(
+        &self,
+        since: DateTime<Utc>,
+        limit: u32,
+    ) -> ForexResult<Vec<SloReport>>;
+}
+
+#[async_trait]
+pub trait ForexStorageBackfill {
+    /// get progress of backfill of the provider's rates in the base, empty if it hasn't started
+    async fn get_backfill_checkpoint(
+        &self,
+        provider: &str,
+        base: Currency,
+    ) -> ForexResult<BackfillCheckpoint>;
+
+    /// store progress of backfill of the provider's rates in the base
+    async fn insert_backfill_checkpoint(
+        &self,
+        provider: &str,
+        base: Currency,
+        checkpoint: &BackfillCheckpoint,
+    ) -> ForexResult<()>;
+}
+
+#[async_trait]
+pub trait ForexSloAlerts {
+    /// notify integrations of breached freshness SLO
+    async fn alert_slo_breach(&self, report: &SloReport) -> ForexResult<()>;
+}
+
+#[async_trait]
+pub trait ForexStorageConsistency {
+    /// store report of comparing providers, replacing report of the same date and providers
+    async fn insert_consistency_report(&self, report: &ConsistencyReport) -> ForexResult<()>;
 }
@@ -0,0 +1,248 @@
+// market_hours.rs weekly trading sessions per currency kind in UTC, consulted before polling latest rates
+// so polls of closed markets don't waste providers' quota, e.g. FX on weekends.
+// sessions are written as `always`, `never` or `sun 22:00 - fri 22:00`, optionally followed by daily break
+// `break 22:00 - 23:00`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, NaiveTime, Timelike, Utc, Weekday};
+
+use super::{Currency, CurrencyKind, ForexError};
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// Moment of a week in UTC, e.g. sunday 22:00.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeekTime {
+    pub weekday: Weekday,
+    pub time: NaiveTime,
+}
+
+impl WeekTime {
+    pub fn new(weekday: Weekday, hour: u32, minute: u32) -> Self {
+        Self {
+            weekday,
+            time: NaiveTime::from_hms_opt(hour, minute, 0).unwrap_or_default(),
+        }
+    }
+
+    /// minutes since monday 00:00.
+    fn minutes(&self) -> u32 {
+        self.weekday.num_days_from_monday() * MINUTES_PER_DAY + minutes_of_day(self.time)
+    }
+}
+
+/// Trading hours of a market.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketHours {
+    Always,
+    Never,
+
+    /// open from `open` until `close` every week, closed during daily break if any.
+    Weekly {
+        open: WeekTime,
+        close: WeekTime,
+        daily_break: Option<(NaiveTime, NaiveTime)>,
+    },
+}
+
+impl MarketHours {
+    /// FX trades from sydney open on sunday until new york close on friday.
+    pub fn fx() -> Self {
+        Self::Weekly {
+            open: WeekTime::new(Weekday::Sun, 22, 0),
+            close: WeekTime::new(Weekday::Fri, 22, 0),
+            daily_break: None,
+        }
+    }
+
+    /// metals trade on globex sessions, with an hour break every day.
+    pub fn metals() -> Self {
+        Self::Weekly {
+            open: WeekTime::new(Weekday::Sun, 23, 0),
+            close: WeekTime::new(Weekday::Fri, 22, 0),
+            daily_break: NaiveTime::from_hms_opt(22, 0, 0).zip(NaiveTime::from_hms_opt(23, 0, 0)),
+        }
+    }
+
+    pub fn is_open(&self, at: DateTime<Utc>) -> bool {
+        let Self::Weekly {
+            open,
+            close,
+            daily_break,
+        } = self
+        else {
+            return *self == Self::Always;
+        };
+
+        let now = WeekTime {
+            weekday: at.weekday(),
+            time: at.time(),
+        }
+        .minutes();
+        let (open, close) = (open.minutes(), close.minutes());
+        let in_session = if open < close {
+            (open..close).contains(&now)
+        } else {
+            // session spans end of week, e.g. sunday to friday
+            now >= open || now < close
+        };
+        let in_break = daily_break.is_some_and(|(start, end)| {
+            let time = minutes_of_day(at.time());
+            (minutes_of_day(start)..minutes_of_day(end)).contains(&time)
+        });
+
+        in_session && !in_break
+    }
+}
+
+impl fmt::Display for MarketHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Always => f.write_str("always"),
+            Self::Never => f.write_str("never"),
+            Self::Weekly {
+                open,
+                close,
+                daily_break,
+            } => {
+                write!(
+                    f,
+                    "{} {} - {} {}",
+                    weekday_str(open.weekday),
+                    open.time.format("%H:%M"),
+                    weekday_str(close.weekday),
+                    close.time.format("%H:%M")
+                )?;
+                if let Some((start, end)) = daily_break {
+                    write!(
+                        f,
+                        " break {} - {}",
+                        start.format("%H:%M"),
+                        end.format("%H:%M")
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl FromStr for MarketHours {
+    type Err = ForexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || {
+            ForexError::client_error(
+                "market hours must be `always`, `never` or like `sun 22:00 - fri 22:00`, optionally followed by `break 22:00 - 23:00`",
+            )
+        };
+        let s = s.trim().to_lowercase();
+        match s.as_str() {
+            "always" => return Ok(Self::Always),
+            "never" => return Ok(Self::Never),
+            _ => {}
+        }
+
+        let (session, daily_break) = match s.split_once("break") {
+            Some((session, daily_break)) => (session, Some(daily_break)),
+            None => (s.as_str(), None),
+        };
+        let (open, close) = session.split_once('-').ok_or_else(err)?;
+        let open = parse_week_time(open).ok_or_else(err)?;
+        let close = parse_week_time(close).ok_or_else(err)?;
+        let daily_break = match daily_break {
+            Some(daily_break) => {
+                let (start, end) = daily_break.split_once('-').ok_or_else(err)?;
+                let start = parse_time(start).ok_or_else(err)?;
+                let end = parse_time(end).ok_or_else(err)?;
+                if start >= end {
+                    return Err(err());
+                }
+                Some((start, end))
+            }
+            None => None,
+        };
+        if open == close {
+            return Err(err());
+        }
+
+        Ok(Self::Weekly {
+            open,
+            close,
+            daily_break,
+        })
+    }
+}
+
+/// Trading hours of each currency kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarketCalendar {
+    pub fiat: MarketHours,
+    pub metal: MarketHours,
+    pub crypto: MarketHours,
+}
+
+impl Default for MarketCalendar {
+    fn default() -> Self {
+        Self {
+            fiat: MarketHours::fx(),
+            metal: MarketHours::metals(),
+            crypto: MarketHours::Always,
+        }
+    }
+}
+
+impl MarketCalendar {
+    pub fn hours(&self, kind: CurrencyKind) -> MarketHours {
+        match kind {
+            CurrencyKind::Fiat => self.fiat,
+            CurrencyKind::Metal => self.metal,
+            CurrencyKind::Crypto => self.crypto,
+        }
+    }
+
+    pub fn with_hours(mut self, kind: CurrencyKind, hours: MarketHours) -> Self {
+        match kind {
+            CurrencyKind::Fiat => self.fiat = hours,
+            CurrencyKind::Metal => self.metal = hours,
+            CurrencyKind::Crypto => self.crypto = hours,
+        }
+        self
+    }
+
+    pub fn is_open(&self, kind: CurrencyKind, at: DateTime<Utc>) -> bool {
+        self.hours(kind).is_open(at)
+    }
+
+    /// whether market of any of the currencies is open, their rates may have moved since last poll.
+    /// open without currencies, as nothing is known to be closed.
+    pub fn any_open(&self, currencies: &[Currency], at: DateTime<Utc>) -> bool {
+        currencies.is_empty()
+            || currencies
+                .iter()
+                .any(|currency| self.is_open(currency.kind(), at))
+    }
+}
+
+fn minutes_of_day(time: NaiveTime) -> u32 {
+    time.hour() * 60 + time.minute()
+}
+
+fn parse_week_time(s: &str) -> Option<WeekTime> {
+    let (weekday, time) = s.trim().split_once(' ')?;
+
+    Some(WeekTime {
+        weekday: weekday.trim().parse().ok()?,
+        time: parse_time(time)?,
+    })
+}
+
+fn parse_time(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M").ok()
+}
+
+fn weekday_str(weekday: Weekday) -> String {
+    weekday.to_string().to_lowercase()
+}
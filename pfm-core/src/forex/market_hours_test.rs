@@ -0,0 +1,105 @@
+use chrono::{TimeZone, Utc, Weekday};
+
+use super::{
+    Currency, CurrencyKind,
+    market_hours::{MarketCalendar, MarketHours, WeekTime},
+};
+
+#[test]
+fn test_fx_hours() {
+    let fx = MarketHours::fx();
+
+    // 2024-01-05 is friday
+    assert!(fx.is_open(Utc.with_ymd_and_hms(2024, 1, 5, 21, 59, 0).unwrap()));
+    assert!(!fx.is_open(Utc.with_ymd_and_hms(2024, 1, 5, 22, 0, 0).unwrap()));
+    assert!(!fx.is_open(Utc.with_ymd_and_hms(2024, 1, 6, 12, 0, 0).unwrap()));
+    assert!(!fx.is_open(Utc.with_ymd_and_hms(2024, 1, 7, 21, 59, 0).unwrap()));
+    assert!(fx.is_open(Utc.with_ymd_and_hms(2024, 1, 7, 22, 0, 0).unwrap()));
+    assert!(fx.is_open(Utc.with_ymd_and_hms(2024, 1, 8, 0, 0, 0).unwrap()));
+    assert!(fx.is_open(Utc.with_ymd_and_hms(2024, 1, 10, 22, 30, 0).unwrap()));
+}
+
+#[test]
+fn test_metals_hours_daily_break() {
+    let metals = MarketHours::metals();
+
+    // wednesday
+    assert!(metals.is_open(Utc.with_ymd_and_hms(2024, 1, 10, 21, 59, 0).unwrap()));
+    assert!(!metals.is_open(Utc.with_ymd_and_hms(2024, 1, 10, 22, 30, 0).unwrap()));
+    assert!(metals.is_open(Utc.with_ymd_and_hms(2024, 1, 10, 23, 0, 0).unwrap()));
+    // sunday before open
+    assert!(!metals.is_open(Utc.with_ymd_and_hms(2024, 1, 7, 22, 30, 0).unwrap()));
+}
+
+#[test]
+fn test_parse_market_hours() {
+    assert_eq!(
+        "always".parse::<MarketHours>().unwrap(),
+        MarketHours::Always
+    );
+    assert_eq!(
+        " Never ".parse::<MarketHours>().unwrap(),
+        MarketHours::Never
+    );
+    assert_eq!(
+        "sun 22:00 - fri 22:00".parse::<MarketHours>().unwrap(),
+        MarketHours::fx()
+    );
+    assert_eq!(
+        "Sunday 23:00-Friday 22:00 break 22:00-23:00"
+            .parse::<MarketHours>()
+            .unwrap(),
+        MarketHours::metals()
+    );
+    assert_eq!(
+        "mon 08:00 - mon 16:30".parse::<MarketHours>().unwrap(),
+        MarketHours::Weekly {
+            open: WeekTime::new(Weekday::Mon, 8, 0),
+            close: WeekTime::new(Weekday::Mon, 16, 30),
+            daily_break: None,
+        }
+    );
+
+    // formatted hours are parsed back
+    for hours in [
+        MarketHours::fx(),
+        MarketHours::metals(),
+        MarketHours::Always,
+    ] {
+        assert_eq!(hours.to_string().parse::<MarketHours>().unwrap(), hours);
+    }
+
+    for invalid in [
+        "",
+        "weekdays",
+        "sun 22:00",
+        "sun 22:00 - fri",
+        "xyz 22:00 - fri 22:00",
+        "sun 25:00 - fri 22:00",
+        "sun 22:00 - sun 22:00",
+        "sun 22:00 - fri 22:00 break 23:00 - 22:00",
+    ] {
+        assert!(invalid.parse::<MarketHours>().is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_market_calendar_any_open() {
+    let saturday = Utc.with_ymd_and_hms(2024, 1, 6, 12, 0, 0).unwrap();
+    let calendar = MarketCalendar::default();
+
+    assert!(!calendar.is_open(CurrencyKind::Fiat, saturday));
+    assert!(!calendar.is_open(CurrencyKind::Metal, saturday));
+    assert!(calendar.is_open(CurrencyKind::Crypto, saturday));
+
+    assert!(!calendar.any_open(&[Currency::USD, Currency::IDR, Currency::XAU], saturday));
+    // crypto trades on weekends
+    assert!(calendar.any_open(&[Currency::USD, Currency::BTC], saturday));
+    assert!(calendar.any_open(&[], saturday));
+
+    // overridden
+    let calendar = calendar.with_hours(CurrencyKind::Fiat, MarketHours::Always);
+    assert!(calendar.any_open(&[Currency::USD, Currency::IDR], saturday));
+    let calendar = calendar.with_hours(CurrencyKind::Crypto, MarketHours::Never);
+    assert!(!calendar.any_open(&[Currency::BTC], saturday));
+}
@@ -1,15 +1,23 @@
 use std::fmt::Debug;
 
 use async_trait::async_trait;
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
 use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::forex::{
     Currency, ForexResult,
-    entity::{Order, Rates, RatesData, RatesList, RatesResponse},
-    interface::{ForexHistoricalRates, ForexRates, ForexStorage},
+    entity::{
+        CryptoPrice, Cursor, Job, JobRun, Order, OrderBy, ProvenanceEvent, ProvenanceKind, Rates,
+        RatesData, RatesList, RatesPage, RatesProvenance, RatesResponse, sort_rates,
+    },
+    interface::{
+        ForexCryptoPrices, ForexHistoricalRates, ForexProviderCapabilities, ForexRates,
+        ForexStorage, ForexStorageJobLock, ForexStorageJobRuns, ForexStoragePagination,
+        ForexStorageProvenance, ForexStorageRebased, ForexStorageSnapshots, ForexStorageSparse,
+        ForexStorageValidation, ForexTimeseriesRates, HistoricalUpdate,
+    },
 };
 
 use super::Money;
@@ -96,7 +104,12 @@ fn historical_rate() -> Rates {
     Rates { date, base, rates }
 }
 
-fn latest_rate_list(page: u32, size: u32, order: Order) -> RatesList<RatesResponse<Rates>> {
+fn latest_rate_list(
+    page: u32,
+    size: u32,
+    order: Order,
+    order_by: OrderBy,
+) -> RatesList<RatesResponse<Rates>> {
     let mut rates_list: Vec<RatesResponse<Rates>> = vec![
         RatesResponse {
             id: Uuid::parse_str("10324ad3-1caa-4acc-9296-a7b34a6ad010").unwrap(),
@@ -141,6 +154,7 @@ fn latest_rate_list(page: u32, size: u32, order: Order) -> RatesList<RatesRespon
                 },
             },
             error: None,
+            provenance: vec![],
         },
         RatesResponse {
             id: Uuid::parse_str("51d5a6fd-a83c-4fec-980b-e5faae6fc1fa").unwrap(),
@@ -185,6 +199,7 @@ fn latest_rate_list(page: u32, size: u32, order: Order) -> RatesList<RatesRespon
                 },
             },
             error: None,
+            provenance: vec![],
         },
         RatesResponse {
             id: Uuid::parse_str("c385aea1-8e79-4028-b44c-bf26450fc457").unwrap(),
@@ -229,6 +244,7 @@ fn latest_rate_list(page: u32, size: u32, order: Order) -> RatesList<RatesRespon
                 },
             },
             error: None,
+            provenance: vec![],
         },
         RatesResponse {
             id: Uuid::parse_str("1f5624b0-58ad-40d5-9122-6896d80eec53").unwrap(),
@@ -273,6 +289,7 @@ fn latest_rate_list(page: u32, size: u32, order: Order) -> RatesList<RatesRespon
                 },
             },
             error: None,
+            provenance: vec![],
         },
         RatesResponse {
             id: Uuid::parse_str("d95447d8-3935-49d6-855d-d2585365adf0").unwrap(),
@@ -317,6 +334,7 @@ fn latest_rate_list(page: u32, size: u32, order: Order) -> RatesList<RatesRespon
                 },
             },
             error: None,
+            provenance: vec![],
         },
         RatesResponse {
             id: Uuid::parse_str("421d55b4-c3e5-49fb-a816-b89f78a0f275").unwrap(),
@@ -361,6 +379,7 @@ fn latest_rate_list(page: u32, size: u32, order: Order) -> RatesList<RatesRespon
                 },
             },
             error: None,
+            provenance: vec![],
         },
         RatesResponse {
             id: Uuid::parse_str("df80eeda-2552-416e-b1ab-a40e9558beab").unwrap(),
@@ -405,6 +424,7 @@ fn latest_rate_list(page: u32, size: u32, order: Order) -> RatesList<RatesRespon
                 },
             },
             error: None,
+            provenance: vec![],
         },
         RatesResponse {
             id: Uuid::parse_str("bcc3681b-1452-41f7-af18-ccee5ffcaadb").unwrap(),
@@ -449,13 +469,11 @@ fn latest_rate_list(page: u32, size: u32, order: Order) -> RatesList<RatesRespon
                 },
             },
             error: None,
+            provenance: vec![],
         },
     ];
 
-    match order {
-        Order::DESC => rates_list.sort_by(|a, b| b.data.date.cmp(&a.data.date)),
-        Order::ASC => rates_list.sort_by(|a, b| a.data.date.cmp(&b.data.date)),
-    }
+    sort_rates(&mut rates_list, order, order_by);
 
     let start = (page.saturating_sub(1) * size) as usize;
     let end = (start + size as usize).min(rates_list.len());
@@ -471,7 +489,12 @@ fn latest_rate_list(page: u32, size: u32, order: Order) -> RatesList<RatesRespon
     }
 }
 
-fn historical_rate_list(page: u32, size: u32, order: Order) -> RatesList<RatesResponse<Rates>> {
+fn historical_rate_list(
+    page: u32,
+    size: u32,
+    order: Order,
+    order_by: OrderBy,
+) -> RatesList<RatesResponse<Rates>> {
     let mut historical_rates_list: Vec<RatesResponse<Rates>> = vec![
         RatesResponse {
             id: Uuid::parse_str("d06e8e1c-6d64-4bd4-98d6-2758bcbf2d5f").unwrap(),
@@ -516,6 +539,7 @@ fn historical_rate_list(page: u32, size: u32, order: Order) -> RatesList<RatesRe
                 },
             },
             error: None,
+            provenance: vec![],
         },
         RatesResponse {
             id: Uuid::parse_str("7185a19d-55bf-40d6-993d-2d3ee54d0ca4").unwrap(),
@@ -560,6 +584,7 @@ fn historical_rate_list(page: u32, size: u32, order: Order) -> RatesList<RatesRe
                 },
             },
             error: None,
+            provenance: vec![],
         },
         RatesResponse {
             id: Uuid::parse_str("a31994fe-25bd-41ad-9d05-0684c849d87e").unwrap(),
@@ -604,6 +629,7 @@ fn historical_rate_list(page: u32, size: u32, order: Order) -> RatesList<RatesRe
                 },
             },
             error: None,
+            provenance: vec![],
         },
         RatesResponse {
             id: Uuid::parse_str("198fab12-d078-40bf-b403-057019155971").unwrap(),
@@ -648,13 +674,11 @@ fn historical_rate_list(page: u32, size: u32, order: Order) -> RatesList<RatesRe
                 },
             },
             error: None,
+            provenance: vec![],
         },
     ];
 
-    match order {
-        Order::DESC => historical_rates_list.sort_by(|a, b| b.data.date.cmp(&a.data.date)),
-        Order::ASC => historical_rates_list.sort_by(|a, b| a.data.date.cmp(&b.data.date)),
-    }
+    sort_rates(&mut historical_rates_list, order, order_by);
 
     let start = (page.saturating_sub(1) * size) as usize;
     let end = (start + size as usize).min(historical_rates_list.len());
@@ -715,6 +739,7 @@ fn historical_range() -> Vec<RatesResponse<Rates>> {
                 },
             },
             error: None,
+            provenance: vec![],
         },
         RatesResponse {
             id: Uuid::parse_str("7185a19d-55bf-40d6-993d-2d3ee54d0ca4").unwrap(),
@@ -759,6 +784,7 @@ fn historical_range() -> Vec<RatesResponse<Rates>> {
                 },
             },
             error: None,
+            provenance: vec![],
         },
         RatesResponse {
             id: Uuid::parse_str("a31994fe-25bd-41ad-9d05-0684c849d87e").unwrap(),
@@ -803,6 +829,7 @@ fn historical_range() -> Vec<RatesResponse<Rates>> {
                 },
             },
             error: None,
+            provenance: vec![],
         },
         RatesResponse {
             id: Uuid::parse_str("198fab12-d078-40bf-b403-057019155971").unwrap(),
@@ -847,6 +874,7 @@ fn historical_range() -> Vec<RatesResponse<Rates>> {
                 },
             },
             error: None,
+            provenance: vec![],
         },
     ];
 
@@ -855,6 +883,8 @@ fn historical_range() -> Vec<RatesResponse<Rates>> {
 
 pub(crate) struct ForexApiSuccessMock;
 
+impl ForexProviderCapabilities for ForexApiSuccessMock {}
+
 #[async_trait]
 impl ForexRates for ForexApiSuccessMock {
     async fn rates(&self, base: Currency) -> ForexResult<RatesResponse<Rates>> {
@@ -879,6 +909,50 @@ impl ForexHistoricalRates for ForexApiSuccessMock {
     }
 }
 
+#[async_trait]
+impl ForexTimeseriesRates for ForexApiSuccessMock {
+    async fn timeseries_rates(
+        &self,
+        _start_date: DateTime<Utc>,
+        _end_date: DateTime<Utc>,
+        _base: Currency,
+    ) -> ForexResult<Vec<RatesResponse<Rates>>> {
+        Ok(historical_range())
+    }
+}
+
+/// 1 coin is 10 USD.
+#[async_trait]
+impl ForexCryptoPrices for ForexApiSuccessMock {
+    async fn daily_close(
+        &self,
+        date: DateTime<Utc>,
+        currencies: &[Currency],
+    ) -> ForexResult<Vec<CryptoPrice>> {
+        Ok(currencies
+            .iter()
+            .map(|&currency| CryptoPrice {
+                currency,
+                date,
+                rate: dec!(0.1),
+                source: "crypto_daily_close_success".to_string(),
+            })
+            .collect())
+    }
+
+    async fn latest(&self, currencies: &[Currency]) -> ForexResult<Vec<CryptoPrice>> {
+        Ok(currencies
+            .iter()
+            .map(|&currency| CryptoPrice {
+                currency,
+                date: Utc::now(),
+                rate: dec!(0.1),
+                source: "crypto_latest_success".to_string(),
+            })
+            .collect())
+    }
+}
+
 pub(crate) struct ForexStorageSuccessMock;
 
 #[async_trait]
@@ -919,7 +993,7 @@ impl ForexStorage for ForexStorageSuccessMock {
     async fn update_historical_rates_data(
         &self,
         date: DateTime<Utc>,
-        new_data: Vec<Money>,
+        _new_data: Vec<(Money, String)>,
     ) -> ForexResult<RatesResponse<Rates>> {
         Ok(RatesResponse::new(
             "storage_get_historical_success".to_string(),
@@ -927,6 +1001,21 @@ impl ForexStorage for ForexStorageSuccessMock {
         ))
     }
 
+    async fn update_historical_bulk(
+        &self,
+        updates: Vec<HistoricalUpdate>,
+    ) -> ForexResult<Vec<RatesResponse<Rates>>> {
+        Ok(updates
+            .iter()
+            .map(|_| {
+                RatesResponse::new(
+                    "storage_get_historical_success".to_string(),
+                    historical_rate(),
+                )
+            })
+            .collect())
+    }
+
     async fn get_historical(&self, _date: DateTime<Utc>) -> ForexResult<RatesResponse<Rates>> {
         Ok(RatesResponse::new(
             "storage_get_historical_success".to_string(),
@@ -947,8 +1036,9 @@ impl ForexStorage for ForexStorageSuccessMock {
         page: u32,
         size: u32,
         order: Order,
+        order_by: OrderBy,
     ) -> ForexResult<RatesList<RatesResponse<Rates>>> {
-        Ok(latest_rate_list(page, size, order))
+        Ok(latest_rate_list(page, size, order, order_by))
     }
 
     async fn get_historical_list(
@@ -956,7 +1046,135 @@ impl ForexStorage for ForexStorageSuccessMock {
         page: u32,
         size: u32,
         order: Order,
+        order_by: OrderBy,
     ) -> ForexResult<RatesList<RatesResponse<Rates>>> {
-        Ok(historical_rate_list(page, size, order))
+        Ok(historical_rate_list(page, size, order, order_by))
+    }
+}
+
+#[async_trait]
+impl ForexStorageRebased for ForexStorageSuccessMock {
+    async fn get_historical_rebased(
+        &self,
+        date: DateTime<Utc>,
+        base: Currency,
+    ) -> ForexResult<RatesResponse<Rates>> {
+        self.get_historical(date).await?.rebased(base)
+    }
+}
+
+/// rates are fully decoded, as stored by the mock.
+#[async_trait]
+impl ForexStorageSparse for ForexStorageSuccessMock {
+    async fn get_historical_range_of(
+        &self,
+        _currencies: &[Currency],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> ForexResult<Vec<RatesResponse<Rates>>> {
+        self.get_historical_range(start, end).await
+    }
+}
+
+#[async_trait]
+impl ForexStoragePagination for ForexStorageSuccessMock {
+    async fn get_latest_page(
+        &self,
+        cursor: Option<Cursor>,
+        size: u32,
+        order: Order,
+        order_by: OrderBy,
+    ) -> ForexResult<RatesPage<RatesResponse<Rates>>> {
+        let rates = latest_rate_list(1, u32::MAX, order, order_by).rates_list;
+        Ok(RatesPage::after(rates, cursor, size, order, order_by))
+    }
+
+    async fn get_historical_page(
+        &self,
+        cursor: Option<Cursor>,
+        size: u32,
+        order: Order,
+        order_by: OrderBy,
+    ) -> ForexResult<RatesPage<RatesResponse<Rates>>> {
+        Ok(RatesPage::after(
+            historical_range(),
+            cursor,
+            size,
+            order,
+            order_by,
+        ))
+    }
+}
+
+/// snapshot of any id is historical rates, so it differs from latest rates, nil id is of cleared snapshot.
+#[async_trait]
+impl ForexStorageSnapshots for ForexStorageSuccessMock {
+    async fn get_latest_by_id(&self, id: Uuid) -> ForexResult<Option<RatesResponse<Rates>>> {
+        if id.is_nil() {
+            return Ok(None);
+        }
+        let mut rates = RatesResponse::new(
+            "storage_get_latest_by_id_success".to_string(),
+            historical_rate(),
+        );
+        rates.id = id;
+
+        Ok(Some(rates))
+    }
+}
+
+impl ForexStorageValidation for ForexStorageSuccessMock {
+    fn forced(&self) -> Self {
+        Self
+    }
+}
+
+/// historical rates of any date are polled once and patched later.
+#[async_trait]
+impl ForexStorageProvenance for ForexStorageSuccessMock {
+    async fn get_historical_provenance(&self, date: DateTime<Utc>) -> ForexResult<RatesProvenance> {
+        let mut provenance = RatesProvenance::new(
+            date,
+            ProvenanceEvent {
+                kind: ProvenanceKind::Insert,
+                source: "storage_get_historical_success".to_string(),
+                at: date + TimeDelta::hours(1),
+                error: None,
+            },
+        );
+        provenance.record(ProvenanceEvent {
+            kind: ProvenanceKind::Update,
+            source: String::default(),
+            at: date + TimeDelta::days(1),
+            error: None,
+        });
+
+        Ok(provenance)
+    }
+
+    async fn get_modified_historical_dates(
+        &self,
+        start: DateTime<Utc>,
+        _end: DateTime<Utc>,
+    ) -> ForexResult<Vec<DateTime<Utc>>> {
+        Ok(vec![start])
+    }
+}
+
+#[async_trait]
+impl ForexStorageJobLock for ForexStorageSuccessMock {
+    async fn try_lock_job(&self, _job: Job, _holder: &str, _ttl: TimeDelta) -> ForexResult<bool> {
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl ForexStorageJobRuns for ForexStorageSuccessMock {
+    async fn insert_job_run(&self, _run: &JobRun) -> ForexResult<()> {
+        Ok(())
+    }
+
+    async fn get_job_runs(&self, _job: Option<Job>, _limit: u32) -> ForexResult<Vec<JobRun>> {
+        Ok(vec![])
     }
 }
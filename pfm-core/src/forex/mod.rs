@@ -1,5 +1,13 @@
+pub(crate) mod analytics;
+#[cfg(test)]
+mod analytics_test;
+
+pub(crate) mod backfill;
+#[cfg(test)]
+mod backfill_test;
+
 pub mod currency;
-pub use currency::Currency;
+pub use currency::{Currency, CurrencyKind};
 #[cfg(test)]
 mod currency_test;
 
@@ -7,16 +15,41 @@ pub mod entity;
 #[cfg(test)]
 mod entity_test;
 
+pub(crate) mod expression;
+#[cfg(test)]
+mod expression_test;
+
 pub mod interface;
 pub use interface::{ForexError, ForexResult};
 
+pub mod market_hours;
+#[cfg(test)]
+mod market_hours_test;
+
 pub mod money;
-pub use money::Money;
+pub use money::{FormatOptions, Money};
 #[cfg(test)]
 mod money_test;
 
-pub mod service;
+pub mod phrase;
+#[cfg(test)]
+mod phrase_test;
+
+pub mod redenomination;
+#[cfg(test)]
+mod redenomination_test;
+
+pub mod schema;
+#[cfg(test)]
+mod schema_test;
+
+pub(crate) mod service;
 #[cfg(test)]
 mod service_test;
 
-mod mock;
+pub(crate) mod slo;
+#[cfg(test)]
+mod slo_test;
+
+#[cfg(test)]
+pub(crate) mod mock;
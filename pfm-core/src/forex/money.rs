@@ -12,11 +12,10 @@ use iso_currency::Currency as CurrencyLib;
 use lazy_static::lazy_static;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use strum::EnumIter;
 
-use crate::global;
-
 pub(crate) const ERROR_MONEY_FORMAT: &str = "The money must be written in ISO 4217 format: <CODE> <AMOUNT>. Amount may be separated by comma for thousands, and by dot for fraction.";
 
 lazy_static! {
@@ -31,7 +30,9 @@ lazy_static! {
         regex::Regex::new(r"^([A-Z]{3})\s+((?:\d{1,3}(?:,\d{3})*|\d+)(?:\.\d+)?)$").expect("failed compiling money format regex");
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize, EnumIter)]
+#[derive(
+    Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize, EnumIter, JsonSchema,
+)]
 pub enum Money {
     //// fiat
 
@@ -321,18 +322,20 @@ impl Money {
         Ok(Money::new_money(currency, amount))
     }
 
-    pub fn format(&self, use_symbol: bool) -> String {
-        let currency_code: String = if use_symbol {
+    /// format money with comma separated thousands and dot separated fraction.
+    pub fn format(&self, options: FormatOptions) -> String {
+        let currency_code: String = if options.use_symbol {
             self.symbol()
         } else {
             self.code()
         };
 
-        let precision: usize = if self.amount() > dec!(0) { 2 } else { 10 };
+        let default_precision = if self.amount() > dec!(0) { 2 } else { 10 };
+        let precision: usize = options.precision.unwrap_or(default_precision);
 
         let mut ac = Accounting::new_from_seperator(currency_code.as_str(), precision, ",", ".");
 
-        if use_symbol {
+        if options.use_symbol {
             ac.set_format("{s}{v}");
         } else {
             ac.set_format("{s} {v}");
@@ -344,10 +347,15 @@ impl Money {
     }
 
     pub(super) fn convert(rates: &RatesData, from: Money, to: Currency) -> ForexResult<Money> {
-        if from.currency() == to {
-            return Ok(from);
-        }
+        Self::convert_verbose(rates, from, to).map(|(converted, _)| converted)
+    }
 
+    /// conversion result along with intermediate amount in base currency of the rates.
+    pub(super) fn convert_verbose(
+        rates: &RatesData,
+        from: Money,
+        to: Currency,
+    ) -> ForexResult<(Money, Decimal)> {
         // 1. divide from with its rate relative to base currency.
         let to_base = match from {
             Money::USD(amount) => amount.checked_div(rates.usd).unwrap_or_default(),
@@ -379,6 +387,9 @@ impl Money {
             Money::XRP(amount) => amount.checked_div(rates.xrp).unwrap_or_default(),
             Money::ADA(amount) => amount.checked_div(rates.ada).unwrap_or_default(),
         };
+        if from.currency() == to {
+            return Ok((from, to_base));
+        }
 
         // 2. multiply the above result with the rate of target conversion relative to base currency.
         let to_target = match to {
@@ -414,7 +425,7 @@ impl Money {
 
         let result = Money::new_money(to, to_target);
 
-        Ok(result)
+        Ok((result, to_base))
     }
 }
 
@@ -427,9 +438,34 @@ impl FromStr for Money {
     }
 }
 
+/// Options of formatting money into string.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// currency symbol, e.g. $1,000.00, instead of code, e.g. USD 1,000.00
+    pub use_symbol: bool,
+
+    /// digits of fraction, default to 2 for positive amounts and 10 for the rest.
+    pub precision: Option<usize>,
+}
+
+impl FormatOptions {
+    /// e.g. USD 1,000.00
+    pub const CODE: Self = Self {
+        use_symbol: false,
+        precision: None,
+    };
+
+    /// e.g. $1,000.00
+    pub const SYMBOL: Self = Self {
+        use_symbol: true,
+        precision: None,
+    };
+}
+
+/// formatted with currency code, use Money::format for other options.
 impl Display for Money {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let ret = self.format(global::config().forex_use_symbol);
+        let ret = self.format(FormatOptions::default());
         write!(f, "{}", ret)
     }
 }
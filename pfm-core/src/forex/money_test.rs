@@ -89,7 +89,10 @@ fn test_money_format_regex() {
     for v in unhappy_path_tests {
         let ret = MONEY_FORMAT_REGEX.is_match(v);
         if ret {
-            panic!("test_money_format_regex error on unhappy_path_tests: expected '{}' to be validated", v);
+            panic!(
+                "test_money_format_regex error on unhappy_path_tests: expected '{}' to be validated",
+                v
+            );
         }
     }
 }
@@ -100,6 +103,7 @@ use rust_decimal_macros::dec;
 use strum::IntoEnumIterator;
 
 use crate::forex::Currency;
+use crate::forex::FormatOptions;
 use crate::forex::Money;
 
 #[test]
@@ -237,3 +241,25 @@ fn test_money_equality() {
     let b = Money::new_money(Currency::IDR, dec!(1.234));
     assert_ne!(a, b);
 }
+
+#[test]
+fn test_money_format_options() {
+    let money = Money::new_money(Currency::USD, dec!(1000));
+    assert!(money.format(FormatOptions::CODE).starts_with("USD "));
+    assert!(
+        money
+            .format(FormatOptions::SYMBOL)
+            .starts_with(money.symbol().as_str())
+    );
+
+    // display doesn't depend on configuration
+    assert_eq!(money.to_string(), money.format(FormatOptions::CODE));
+    assert_eq!(FormatOptions::default(), FormatOptions::CODE);
+
+    let precise = FormatOptions {
+        use_symbol: false,
+        precision: Some(4),
+    };
+    let money = Money::new_money(Currency::BTC, dec!(0.12341234));
+    assert!(money.format(precise).ends_with("0.1234"));
+}
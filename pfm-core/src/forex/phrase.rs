@@ -0,0 +1,143 @@
+// phrase.rs tolerant parsing of conversion phrases typed by users of bots and CLI,
+// e.g. "100 usd to idr", "convert Rp2jt to USD", "5k eur in gbp on 2023-01-05".
+
+use std::str::FromStr;
+
+use anyhow::Context;
+use chrono::{DateTime, NaiveDate, Utc};
+use lazy_static::lazy_static;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::{Currency, ForexError, ForexResult, Money};
+use crate::error::AsClientError;
+
+pub(crate) const ERROR_PHRASE_FORMAT: &str = "The phrase must be written as [convert] <AMOUNT> <CURRENCY> to|in|into <CURRENCY> [on YYYY-MM-DD], e.g. 100 usd to idr, convert Rp2jt to USD, 5k eur in gbp on 2023-01-05.";
+
+lazy_static! {
+    /// amount with optional currency before, e.g. Rp or $, and shorthand or currency after, e.g. k, jt or usd.
+    static ref AMOUNT_REGEX: regex::Regex =
+        regex::Regex::new(r"^([^\d\s]*)\s*(\d[\d,.]*)\s*([^\d\s]*)\s*([^\d\s]*)$")
+            .expect("failed compiling phrase amount regex");
+}
+
+/// words separating amount and target currency.
+const TARGET_SEPARATORS: [&str; 5] = [" to ", " into ", " in ", " -> ", " = "];
+
+/// parse conversion phrase into money to convert, target currency and optional date of rates.
+/// currencies are ISO 4217 codes in any case or common symbols, e.g. $, Rp, €.
+/// amounts may be shortened with k/rb(thousands), jt/m/mn(millions) and b/bn(billions),
+/// and are separated as in locale of their currency, e.g. Rp100.000 and Rp2,5jt.
+pub fn parse_conversion(phrase: &str) -> ForexResult<(Money, Currency, Option<DateTime<Utc>>)> {
+    let phrase = phrase.trim().to_lowercase();
+    let phrase = phrase.strip_prefix("convert ").unwrap_or(&phrase).trim();
+
+    let (phrase, date) = match phrase.rsplit_once(" on ") {
+        Some((rest, date)) => {
+            let date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+                .context("phrase parse date")
+                .as_client_err()?;
+            (rest, Some(date.and_time(Default::default()).and_utc()))
+        }
+        None => (phrase, None),
+    };
+
+    let Some((amount, target)) = TARGET_SEPARATORS
+        .iter()
+        .find_map(|separator| phrase.split_once(separator))
+    else {
+        return Err(ForexError::client_error(ERROR_PHRASE_FORMAT));
+    };
+
+    let from = parse_amount(amount.trim())?;
+    let to = parse_currency(target.trim())?;
+
+    Ok((from, to, date))
+}
+
+/// parse amount with its currency, normalized into ISO 4217 format parsed by [`Money`].
+fn parse_amount(amount: &str) -> ForexResult<Money> {
+    let Some(captures) = AMOUNT_REGEX.captures(amount) else {
+        return Err(ForexError::client_error(ERROR_PHRASE_FORMAT));
+    };
+    let prefix = &captures[1];
+    let number = &captures[2];
+    let (multiplier, suffix) = match (shorthand(&captures[3]), &captures[4]) {
+        (Some(multiplier), suffix) => (Some(multiplier), suffix),
+        (None, "") => (None, &captures[3]),
+        (None, _) => return Err(ForexError::client_error(ERROR_PHRASE_FORMAT)),
+    };
+
+    let currency = match (prefix, suffix) {
+        (currency, "") | ("", currency) if !currency.is_empty() => parse_currency(currency)?,
+        _ => return Err(ForexError::client_error(ERROR_PHRASE_FORMAT)),
+    };
+
+    let value = Decimal::from_str(&plain_number(number, currency))
+        .context("phrase parse amount")
+        .as_client_err()?;
+    let value = match multiplier {
+        Some(multiplier) => value * multiplier,
+        None => value,
+    };
+
+    Money::from_str(&format!("{} {}", currency.code(), value.normalize()))
+}
+
+/// multiplier of shortened amount.
+fn shorthand(unit: &str) -> Option<Decimal> {
+    match unit {
+        "k" | "rb" | "ribu" => Some(dec!(1_000)),
+        "jt" | "juta" | "m" | "mn" => Some(dec!(1_000_000)),
+        "b" | "bn" => Some(dec!(1_000_000_000)),
+        _ => None,
+    }
+}
+
+/// number without thousands separators and with dot for fraction.
+/// the last separator is fraction when both are used, a repeated one is thousands, a single one is as in
+/// locale of the currency, and thousands separator not followed by 3 digits is tolerated as fraction, e.g. 2,5k usd.
+fn plain_number(number: &str, currency: Currency) -> String {
+    let (_, fraction) = currency.separators();
+    let fraction = match number.rfind([',', '.']) {
+        Some(i) => {
+            let separator = number.as_bytes()[i] as char;
+            let other = if separator == ',' { '.' } else { ',' };
+            let fraction_digits = number.len() - i - 1;
+            match (number.contains(other), number.matches(separator).count()) {
+                (true, _) => Some(separator),
+                (false, 1) if separator == fraction || fraction_digits != 3 => Some(separator),
+                _ => None,
+            }
+        }
+        None => None,
+    };
+
+    number
+        .chars()
+        .filter_map(|c| match c {
+            ',' | '.' if Some(c) == fraction => Some('.'),
+            ',' | '.' => None,
+            c => Some(c),
+        })
+        .collect()
+}
+
+/// currency from its code in any case, or common symbol.
+fn parse_currency(currency: &str) -> ForexResult<Currency> {
+    let currency = match currency {
+        "$" | "us$" => Currency::USD,
+        "rp" => Currency::IDR,
+        "€" => Currency::EUR,
+        "£" => Currency::GBP,
+        "¥" => Currency::JPY,
+        "s$" => Currency::SGD,
+        "rm" => Currency::MYR,
+        "₩" => Currency::KRW,
+        "₹" => Currency::INR,
+        "₿" => Currency::BTC,
+        code => code.to_uppercase().parse()?,
+    };
+
+    Ok(currency)
+}
@@ -0,0 +1,57 @@
+use chrono::{TimeZone, Utc};
+use rust_decimal_macros::dec;
+
+use super::{Currency, Money, phrase::parse_conversion};
+
+#[test]
+fn test_parse_conversion() {
+    let cases = vec![
+        ("100 usd to idr", Money::USD(dec!(100)), Currency::IDR),
+        (
+            "convert Rp2jt to USD",
+            Money::IDR(dec!(2000000)),
+            Currency::USD,
+        ),
+        ("Rp2,5jt into sgd", Money::IDR(dec!(2500000)), Currency::SGD),
+        ("$1,250.50 in eur", Money::USD(dec!(1250.50)), Currency::EUR),
+        ("EUR 1,000 -> jpy", Money::EUR(dec!(1000)), Currency::JPY),
+        ("1.5m idr to usd", Money::IDR(dec!(1500000)), Currency::USD),
+        ("0.01btc in $", Money::BTC(dec!(0.01)), Currency::USD),
+        ("Rp100.000 to usd", Money::IDR(dec!(100000)), Currency::USD),
+        (
+            "Rp1.250.000,50 to usd",
+            Money::IDR(dec!(1250000.50)),
+            Currency::USD,
+        ),
+        (
+            "Rp1,000,000 to usd",
+            Money::IDR(dec!(1000000)),
+            Currency::USD,
+        ),
+        ("2,5k usd to idr", Money::USD(dec!(2500)), Currency::IDR),
+    ];
+    for (phrase, from, to) in cases {
+        let ret = parse_conversion(phrase).unwrap();
+        assert_eq!(ret, (from, to, None), "{}", phrase);
+    }
+
+    let ret = parse_conversion("5k eur in gbp on 2023-01-05").unwrap();
+    assert_eq!(ret.0, Money::EUR(dec!(5000)));
+    assert_eq!(ret.1, Currency::GBP);
+    assert_eq!(
+        ret.2,
+        Some(Utc.with_ymd_and_hms(2023, 1, 5, 0, 0, 0).unwrap())
+    );
+
+    let invalid = vec![
+        "100 usd",
+        "usd to idr",
+        "100 usd to xyz",
+        "Rp 100 usd to idr",
+        "5x eur in gbp",
+        "5k eur in gbp on yesterday",
+    ];
+    for phrase in invalid {
+        assert!(parse_conversion(phrase).is_err(), "{}", phrase);
+    }
+}
@@ -0,0 +1,62 @@
+// redenomination.rs currencies replaced by new units, e.g. 1000 old roubles(RUR) into 1 rouble(RUB) in 1998.
+// rates stored before redenomination are in old units, they're adjusted into current units on range queries
+// so long series are continuous, stored rates are never rewritten.
+// only currencies supported by pfm are listed, e.g. TRY(2005) and VEF/VES(2008, 2018, 2021) aren't supported currencies.
+
+use std::sync::LazyLock;
+
+use chrono::{DateTime, TimeZone, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use strum::IntoEnumIterator;
+
+use super::{Currency, Money, entity::Rates};
+
+/// Currency replaced by new unit worth factor of old ones from the effective date.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redenomination {
+    pub currency: Currency,
+
+    /// code of the currency before redenomination, e.g. as quoted by providers back then.
+    pub old_code: &'static str,
+    pub effective: DateTime<Utc>,
+
+    /// old units per 1 new unit.
+    pub factor: Decimal,
+}
+
+static REDENOMINATIONS: LazyLock<Vec<Redenomination>> = LazyLock::new(|| {
+    vec![Redenomination {
+        currency: Currency::RUB,
+        old_code: "RUR",
+        effective: Utc.with_ymd_and_hms(1998, 1, 1, 0, 0, 0).unwrap(),
+        factor: dec!(1000),
+    }]
+});
+
+/// redenominations of supported currencies, ordered by effective date.
+pub fn redenominations() -> &'static [Redenomination] {
+    &REDENOMINATIONS
+}
+
+/// adjust rates dated before redenominations into current units of the currencies.
+pub fn to_current_units(rates: &mut Rates) {
+    for redenomination in redenominations() {
+        if rates.date >= redenomination.effective {
+            continue;
+        }
+        let (currency, factor) = (redenomination.currency, redenomination.factor);
+        if currency == rates.base {
+            // 1 current unit of base is worth factor old units, buying factor times more of others
+            for other in Currency::iter().filter(|other| *other != currency) {
+                let rate = rates.rates.rate(other);
+                rates.rates.set_rate(Money::new_money(other, rate * factor));
+            }
+        } else {
+            let rate = rates.rates.rate(currency);
+            rates
+                .rates
+                .set_rate(Money::new_money(currency, rate / factor));
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use chrono::{TimeZone, Utc};
+use rust_decimal_macros::dec;
+
+use super::{
+    Currency, Money,
+    entity::{Rates, RatesData},
+    redenomination::to_current_units,
+};
+
+fn rates(year: i32, base: Currency, rub: rust_decimal::Decimal) -> Rates {
+    let mut data = RatesData::default();
+    data.set_rate(Money::new_money(base, dec!(1)));
+    data.set_rate(Money::new_money(Currency::RUB, rub));
+    data.set_rate(Money::new_money(Currency::USD, dec!(1)));
+
+    Rates {
+        date: Utc.with_ymd_and_hms(year, 6, 1, 0, 0, 0).unwrap(),
+        base,
+        rates: data,
+    }
+}
+
+#[test]
+fn test_to_current_units() {
+    // 5,800 old roubles per USD in 1997 are 5.8 roubles of current units
+    let mut old = rates(1997, Currency::USD, dec!(5800));
+    to_current_units(&mut old);
+    assert_eq!(old.rates.rate(Currency::RUB), dec!(5.8));
+    assert_eq!(old.rates.rate(Currency::USD), dec!(1));
+
+    // rates after redenomination are left as is
+    let mut new = rates(1999, Currency::USD, dec!(24.6));
+    to_current_units(&mut new);
+    assert_eq!(new.rates.rate(Currency::RUB), dec!(24.6));
+
+    // rouble based rates, 1 current rouble buys 1000 times more than 1 old rouble
+    let mut rub_based = rates(1997, Currency::RUB, dec!(1));
+    rub_based
+        .rates
+        .set_rate(Money::new_money(Currency::USD, dec!(0.0002)));
+    rub_based
+        .rates
+        .set_rate(Money::new_money(Currency::IDR, dec!(0.5)));
+    to_current_units(&mut rub_based);
+    assert_eq!(rub_based.rates.rate(Currency::RUB), dec!(1));
+    assert_eq!(rub_based.rates.rate(Currency::USD), dec!(0.2));
+    assert_eq!(rub_based.rates.rate(Currency::IDR), dec!(500));
+}
@@ -0,0 +1,42 @@
+// schema.rs generate JSON Schema of forex payloads, so external integrations can validate them.
+
+use std::collections::BTreeMap;
+
+use schemars::schema_for;
+use serde_json::Value;
+
+use super::{
+    Currency, Money,
+    entity::{ConversionResponse, Rates, RatesResponse},
+};
+
+/// names of available schemas.
+pub const SCHEMA_NAMES: [&str; 5] = [
+    "rates_response",
+    "rates",
+    "conversion_response",
+    "money",
+    "currency",
+];
+
+/// JSON Schema of payload by its name, None if there's no schema with the name.
+pub fn schema(name: &str) -> Option<Value> {
+    let schema = match name {
+        "rates_response" => schema_for!(RatesResponse<Rates>),
+        "rates" => schema_for!(Rates),
+        "conversion_response" => schema_for!(ConversionResponse),
+        "money" => schema_for!(Money),
+        "currency" => schema_for!(Currency),
+        _ => return None,
+    };
+
+    Some(schema.into())
+}
+
+/// all JSON Schemas keyed by their names.
+pub fn schemas() -> BTreeMap<&'static str, Value> {
+    SCHEMA_NAMES
+        .iter()
+        .filter_map(|name| schema(name).map(|schema| (*name, schema)))
+        .collect()
+}
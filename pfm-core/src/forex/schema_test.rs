@@ -0,0 +1,17 @@
+use super::schema::{SCHEMA_NAMES, schema, schemas};
+
+#[test]
+fn test_schemas() {
+    let all = schemas();
+    assert_eq!(all.len(), SCHEMA_NAMES.len());
+    assert!(schema("unknown").is_none());
+
+    let rates_response = schema("rates_response").unwrap();
+    let properties = rates_response["properties"].as_object().unwrap();
+    for field in ["id", "source", "poll_date", "data", "error"] {
+        assert!(properties.contains_key(field), "missing field {}", field);
+    }
+
+    let conversion = schema("conversion_response").unwrap();
+    assert_eq!(conversion["title"], "ConversionResponse");
+}
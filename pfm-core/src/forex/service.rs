@@ -1,29 +1,76 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+
 use anyhow::Context;
-use chrono::{DateTime, Datelike, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, TimeDelta, Utc, Weekday};
+use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use strum::IntoEnumIterator;
+use tokio_util::sync::CancellationToken;
 use tracing::instrument;
+use uuid::Uuid;
 
-use crate::{error::AsInternalError, forex::entity::RatesData, global::constants};
+use crate::{
+    error::{AsInternalError, BaseError},
+    forex::entity::RatesData,
+    global::{self, Clock, constants},
+};
 
 use super::{
-    currency::Currency,
-    entity::{ConversionResponse, Rates, RatesResponse},
-    interface::{ForexError, ForexHistoricalRates, ForexRates, ForexResult, ForexStorage},
-    money::Money,
+    currency::{Currency, CurrencyKind},
+    entity::{
+        BackfillReport, BaseRateViolation, ConsistencyReport, ConversionResponse,
+        CryptoIngestReport, CryptoPrice, CryptoRateChange, Cursor, DiskQuotaReport, Job, JobRun,
+        JobTrigger, Order, OrderBy, PollMode, ProviderCapabilities, ProviderHealth, Rates,
+        RatesDelta, RatesKind, RatesResponse, RatesSources, StorageStats,
+    },
+    interface::{
+        ForexCryptoPrices, ForexError, ForexHistoricalRates, ForexProviderCapabilities, ForexRates,
+        ForexRatesWebhook, ForexResult, ForexStorage, ForexStorageConsistency,
+        ForexStorageDeletion, ForexStorageJobLock, ForexStorageJobRuns, ForexStoragePagination,
+        ForexStorageProvenance, ForexStorageProviderHealth, ForexStorageRebased,
+        ForexStorageSnapshots, ForexStorageStats, ForexStorageWebhookDeliveries,
+        ForexTimeseriesRates,
+    },
+    money::{FormatOptions, Money},
+    redenomination,
 };
 
-#[instrument(skip(storage), ret)]
+/// reject currencies not enabled in this deployment, their rates are never requested from providers.
+pub fn ensure_enabled(currencies: &[Currency]) -> ForexResult<()> {
+    let enabled = global::config().enabled_currencies();
+    match currencies
+        .iter()
+        .find(|currency| !enabled.contains(currency))
+    {
+        Some(currency) => Err(ForexError::client_error(
+            format!(
+                "currency {} is not enabled in this deployment",
+                currency.code()
+            )
+            .as_str(),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// get latest rates, or historical rates of the date. rates of today are latest rates.
+#[instrument(skip(storage, clock), ret)]
 pub async fn get_rates(
-    storage: &impl ForexStorage,
+    storage: &(impl ForexStorage + ForexStorageRebased),
+    clock: &impl Clock,
     base: Currency,
     date: Option<DateTime<Utc>>,
 ) -> ForexResult<RatesResponse<Rates>> {
+    ensure_enabled(&[base])?;
     match (base, date) {
         (constants::BASE_CURRENCY, None) => get_rates_usd_latest(storage).await,
-        (constants::BASE_CURRENCY, Some(date)) => get_rates_usd_historical(storage, date).await,
+        (constants::BASE_CURRENCY, Some(date)) => {
+            get_rates_usd_historical(storage, clock, date).await
+        }
         (base, None) => get_rates_base_latest(storage, base).await,
-        (base, Some(date)) => get_rates_base_historical(storage, base, date).await,
+        (base, Some(date)) => get_rates_base_historical(storage, clock, base, date).await,
     }
 }
 
@@ -42,27 +89,18 @@ async fn get_rates_usd_latest(storage: &impl ForexStorage) -> ForexResult<RatesR
     Ok(latest_ret)
 }
 
-#[instrument(skip(storage), ret)]
+#[instrument(skip(storage, clock), ret)]
 async fn get_rates_usd_historical(
     storage: &impl ForexStorage,
+    clock: &impl Clock,
     date: DateTime<Utc>,
 ) -> ForexResult<RatesResponse<Rates>> {
-    let now = Utc::now();
+    let now = clock.now();
     if date.year() == now.year() && date.month() == now.month() && date.day() == now.day() {
         return get_rates_usd_latest(storage).await;
     }
 
-    let historical_rates = storage
-        .get_historical(date)
-        .await
-        .context("get historical usd based rates")
-        .as_internal_err()?;
-
-    if let Some(err) = historical_rates.error {
-        return Err(ForexError::internal_error(err.as_str()));
-    }
-
-    Ok(historical_rates)
+    get_historical_usd(storage, date).await
 }
 
 #[instrument(skip(storage), ret)]
@@ -71,91 +109,197 @@ async fn get_rates_base_latest(
     base: Currency,
 ) -> ForexResult<RatesResponse<Rates>> {
     let usd_based_latest_rates = get_rates_usd_latest(storage).await?;
-    let date = usd_based_latest_rates.data.date;
-    let mut rates_result: Vec<Money> = vec![];
-    for target_curr in Currency::iter() {
-        if target_curr != base {
-            let ret = Money::convert(
-                &usd_based_latest_rates.data.rates,
-                Money::new_money(base, dec!(1)),
-                target_curr,
-            )
-            .context("get rates base latest conversion")
-            .as_internal_err()?;
-
-            rates_result.push(ret);
-        } else {
-            rates_result.push(Money::new_money(base, dec!(1)));
-        }
-    }
-    let rates_data: RatesData = rates_result.into();
-    let rates = Rates {
-        date,
-        base,
-        rates: rates_data,
-    };
-    let rates_response = RatesResponse {
-        id: usd_based_latest_rates.id,
-        source: usd_based_latest_rates.source,
-        poll_date: usd_based_latest_rates.poll_date,
-        data: rates,
-        error: usd_based_latest_rates.error,
-    };
+    let rates_response = usd_based_latest_rates
+        .rebased(base)
+        .context("get rates base latest conversion")
+        .as_internal_err()?;
 
     Ok(rates_response)
 }
 
-#[instrument(skip(storage), ret)]
+/// rebased historical rates are cached by storage along with the stored rates.
+#[instrument(skip(storage, clock), ret)]
 async fn get_rates_base_historical(
-    storage: &impl ForexStorage,
+    storage: &(impl ForexStorage + ForexStorageRebased),
+    clock: &impl Clock,
     base: Currency,
     date: DateTime<Utc>,
 ) -> ForexResult<RatesResponse<Rates>> {
-    let now = Utc::now();
+    let now = clock.now();
     if date.year() == now.year() && date.month() == now.month() && date.day() == now.day() {
         return get_rates_base_latest(storage, base).await;
     }
 
-    let usd_based_historical_rates = get_rates_usd_historical(storage, date).await?;
-    let date = usd_based_historical_rates.data.date;
-    let mut rates_result: Vec<Money> = vec![];
-    for target_curr in Currency::iter() {
-        if target_curr != base {
-            let ret = Money::convert(
-                &usd_based_historical_rates.data.rates,
-                Money::new_money(base, dec!(1)),
-                target_curr,
+    let rates_response = storage
+        .get_historical_rebased(date, base)
+        .await
+        .context("get historical rebased rates")
+        .as_internal_err()?;
+
+    if let Some(err) = rates_response.error {
+        return Err(ForexError::internal_error(err.as_str()));
+    }
+
+    Ok(rates_response)
+}
+
+/// latest snapshots looked through for rates at an instant, skipping failed polls.
+const RATES_AT_SNAPSHOTS: u32 = 8;
+
+/// rates in effect at the instant, hiding the split between latest and historical rates from callers.
+/// on the day of current latest rates it's the most recent latest snapshot at or before the instant,
+/// falling back to historical rates of the day before when none is. past days get their historical rates.
+#[instrument(skip(storage), ret)]
+pub async fn rates_at(
+    storage: &(impl ForexStorage + ForexStoragePagination),
+    instant: DateTime<Utc>,
+) -> ForexResult<RatesResponse<Rates>> {
+    let latest = get_rates_usd_latest(storage).await?;
+    if instant >= latest.data.date {
+        return Ok(latest);
+    }
+
+    let day = instant.date_naive();
+    if day < latest.data.date.date_naive() {
+        return get_historical_usd(storage, instant).await;
+    }
+
+    let snapshots = storage
+        .get_latest_page(
+            Some(Cursor::at(instant)),
+            RATES_AT_SNAPSHOTS,
+            Order::DESC,
+            OrderBy::DataDate,
+        )
+        .await
+        .context("rates at get latest snapshots")
+        .as_internal_err()?;
+    let snapshot = snapshots
+        .rates_list
+        .into_iter()
+        .take_while(|rates| rates.data.date.date_naive() == day)
+        .find(|rates| rates.error.is_none());
+
+    match snapshot {
+        Some(snapshot) => Ok(snapshot),
+        None => get_historical_usd(storage, instant - TimeDelta::days(1)).await,
+    }
+}
+
+/// historical rates of the date, failed polls are errors.
+async fn get_historical_usd(
+    storage: &impl ForexStorage,
+    date: DateTime<Utc>,
+) -> ForexResult<RatesResponse<Rates>> {
+    let historical_rates = storage
+        .get_historical(date)
+        .await
+        .context("get historical usd based rates")
+        .as_internal_err()?;
+
+    if let Some(err) = historical_rates.error {
+        return Err(ForexError::internal_error(err.as_str()));
+    }
+
+    Ok(historical_rates)
+}
+
+/// changed rates of current latest rates since the snapshot with the id.
+#[instrument(skip(storage), ret)]
+pub async fn latest_delta(
+    storage: &(impl ForexStorage + ForexStorageSnapshots),
+    since: Uuid,
+) -> ForexResult<RatesDelta> {
+    let latest = get_rates_usd_latest(storage).await?;
+    if latest.id == since {
+        return RatesDelta::between(&latest, &latest);
+    }
+
+    let Some(snapshot) = storage
+        .get_latest_by_id(since)
+        .await
+        .context("latest delta get snapshot")
+        .as_internal_err()?
+    else {
+        return Err(ForexError::not_found_error(
+            format!(
+                "latest rates {} not found, it may have been cleared, get latest rates instead",
+                since
             )
-            .context("get rates base latest conversion")
-            .as_internal_err()?;
+            .as_str(),
+        ));
+    };
+
+    RatesDelta::between(&snapshot, &latest)
+}
 
-            rates_result.push(ret);
-        } else {
-            rates_result.push(Money::new_money(base, dec!(1)));
+/// sources of latest rates, or of historical rates of the date. rates of today are latest rates.
+#[instrument(skip(storage, clock), ret)]
+pub async fn rates_sources(
+    storage: &(impl ForexStorage + ForexStorageProvenance),
+    clock: &impl Clock,
+    date: Option<DateTime<Utc>>,
+) -> ForexResult<RatesSources> {
+    let today = clock.now().date_naive();
+    let (kind, rates, provenance) = match date.filter(|date| date.date_naive() != today) {
+        None => (
+            RatesKind::Latest,
+            get_rates_usd_latest(storage).await?,
+            None,
+        ),
+        Some(date) => {
+            let rates = get_rates_usd_historical(storage, clock, date).await?;
+            // rates stored before provenance was recorded have no sidecar
+            let provenance = match storage.get_historical_provenance(date).await {
+                Ok(provenance) => Some(provenance),
+                Err(err) => {
+                    tracing::warn!("rates sources provenance of {} not found: {}", date, err);
+                    None
+                }
+            };
+            (RatesKind::Historical, rates, provenance)
         }
-    }
-    let rates_data: RatesData = rates_result.into();
-    let rates = Rates {
-        date,
-        base,
-        rates: rates_data,
     };
-    let rates_response = RatesResponse {
-        id: usd_based_historical_rates.id,
-        source: usd_based_historical_rates.source,
-        poll_date: usd_based_historical_rates.poll_date,
-        data: rates,
-        error: usd_based_historical_rates.error,
+
+    Ok(RatesSources {
+        kind,
+        rates_id: rates.id,
+        date: rates.data.date,
+        source: rates.source,
+        poll_date: rates.poll_date,
+        overrides: rates.provenance,
+        provenance,
+    })
+}
+
+/// seconds since rates of the date were published until now, and whether they're older than freshness threshold.
+/// rates of past requested date are measured from the date, as they're as fresh as requested.
+pub fn rates_staleness(
+    rates_date: DateTime<Utc>,
+    requested: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> (i64, bool) {
+    let at = match requested {
+        Some(date) if date.date_naive() < now.date_naive() => date,
+        _ => now,
     };
+    let age = (at - rates_date).num_seconds().max(0);
 
-    Ok(rates_response)
+    (age, age as u64 > global::config().forex_freshness_secs)
 }
 
-#[instrument(skip(storage), ret)]
-pub async fn convert<FS>(storage: &FS, from: Money, to: Currency) -> ForexResult<ConversionResponse>
+#[instrument(skip(storage, clock), ret)]
+pub async fn convert<FS>(
+    storage: &FS,
+    clock: &impl Clock,
+    from: Money,
+    to: Currency,
+    verbose: bool,
+) -> ForexResult<ConversionResponse>
 where
     FS: ForexStorage,
 {
+    ensure_enabled(&[from.currency(), to])?;
     let latest_rates = storage.get_latest().await?;
     if let Some(_) = latest_rates.error {
         return Err(ForexError::internal_error(
@@ -164,15 +308,18 @@ where
     }
 
     let ret = {
-        let res = Money::convert(&latest_rates.data.rates, from, to)?;
+        let (res, base_amount) = Money::convert_verbose(&latest_rates.data.rates, from, to)?;
         if res.amount() == dec!(0) {
             return Err(ForexError::internal_error(
                 "service convert rate not available at the moment",
             ));
         }
         let date = latest_rates.data.date;
-        let code = res.format(false);
-        let symbol = res.format(true);
+        let code = res.format(FormatOptions::CODE);
+        let symbol = res.format(FormatOptions::SYMBOL);
+        let (rates_age_seconds, stale) = rates_staleness(date, None, clock.now());
+        let steps =
+            verbose.then(|| latest_rates.conversion_steps(from.currency(), to, base_amount));
 
         ConversionResponse {
             date,
@@ -180,33 +327,44 @@ where
             to: res,
             code,
             symbol,
+            rates_age_seconds,
+            stale,
+            steps,
         }
     };
 
     Ok(ret)
 }
 
-#[instrument(skip(storage), ret)]
+#[instrument(skip(storage, clock), ret)]
 pub async fn convert_historical(
     storage: &impl ForexStorage,
+    clock: &impl Clock,
     from: Money,
     to: Currency,
     date: DateTime<Utc>,
+    verbose: bool,
 ) -> ForexResult<ConversionResponse> {
+    ensure_enabled(&[from.currency(), to])?;
     let historical_rates = storage.get_historical(date).await?;
     if let Some(_) = historical_rates.error {
         return Err(ForexError::internal_error(
             "historical rates for this date not available, please contact the web master",
         ));
     }
-    let converted_money = Money::convert(&historical_rates.data.rates, from, to)?;
+    let (converted_money, base_amount) =
+        Money::convert_verbose(&historical_rates.data.rates, from, to)?;
     if converted_money.amount() == dec!(0) {
         return Err(ForexError::internal_error(
             "service convert historical rate not available for this date, try again or another date, or contact web master",
         ));
     }
-    let code = converted_money.format(false);
-    let symbol = converted_money.format(true);
+    let code = converted_money.format(FormatOptions::CODE);
+    let symbol = converted_money.format(FormatOptions::SYMBOL);
+    let (rates_age_seconds, stale) =
+        rates_staleness(historical_rates.data.date, Some(date), clock.now());
+    let steps =
+        verbose.then(|| historical_rates.conversion_steps(from.currency(), to, base_amount));
 
     Ok(ConversionResponse {
         date: historical_rates.data.date,
@@ -214,11 +372,15 @@ pub async fn convert_historical(
         to: converted_money,
         code,
         symbol,
+        rates_age_seconds,
+        stale,
+        steps,
     })
 }
 
 pub async fn batch_convert<FS>(
     storage: &FS,
+    clock: &impl Clock,
     from: Vec<Money>,
     to: Currency,
 ) -> ForexResult<Vec<ConversionResponse>>
@@ -228,7 +390,7 @@ where
     let mut results: Vec<ConversionResponse> = vec![];
 
     for x in from {
-        let ret = convert(storage, x, to).await?;
+        let ret = convert(storage, clock, x, to, false).await?;
         if ret.to.amount() == dec!(0) {
             return Err(ForexError::internal_error(
                 format!(
@@ -245,6 +407,211 @@ where
     Ok(results)
 }
 
+/// most dates converted by [`convert_on_dates`] in one call, 30 years of monthly installments.
+pub const CONVERT_ON_DATES_MAX: usize = 360;
+
+/// convert the same money on each date, e.g. revaluing monthly installments of foreign currency loan.
+/// historical rates of all dates are read in one range. dates without rates, e.g. weekends, use most recent
+/// earlier rates up to [`RATE_ON_MAX_STALE_DAYS`] business days, dates on or after latest rates use latest rates.
+#[instrument(skip(storage, clock, dates), ret)]
+pub async fn convert_on_dates<FS>(
+    storage: &FS,
+    clock: &impl Clock,
+    from: Money,
+    to: Currency,
+    dates: &[DateTime<Utc>],
+) -> ForexResult<Vec<ConversionResponse>>
+where
+    FS: ForexStorage,
+{
+    if dates.is_empty() {
+        return Err(ForexError::client_error(
+            "dates to convert on must not be empty",
+        ));
+    }
+    if dates.len() > CONVERT_ON_DATES_MAX {
+        return Err(ForexError::client_error(
+            format!(
+                "at most {} dates can be converted on at once",
+                CONVERT_ON_DATES_MAX
+            )
+            .as_str(),
+        ));
+    }
+    ensure_enabled(&[from.currency(), to])?;
+
+    let has_pair = |rates: &RatesResponse<Rates>| {
+        rates.error.is_none()
+            && !rates.data.rates.rate(from.currency()).is_zero()
+            && !rates.data.rates.rate(to).is_zero()
+    };
+    let latest = storage
+        .get_latest()
+        .await
+        .context("convert on dates get latest rates")
+        .as_internal_err()?;
+    // each date before latest rates reads its day and enough days to cover a weekend on top of the stale days,
+    // overlapping lookbacks merged, so far apart dates don't read every day between them.
+    let mut days: Vec<NaiveDate> = dates
+        .iter()
+        .map(DateTime::date_naive)
+        .filter(|day| latest.error.is_some() || *day < latest.data.date.date_naive())
+        .collect();
+    days.sort_unstable();
+    let mut ranges: Vec<(NaiveDate, NaiveDate)> = vec![];
+    for day in days {
+        let start = day - TimeDelta::days(RATE_ON_MAX_STALE_DAYS + 2);
+        match ranges.last_mut() {
+            Some((_, end)) if start <= *end => *end = day,
+            _ => ranges.push((start, day)),
+        }
+    }
+    let mut historical: Vec<RatesResponse<Rates>> = vec![];
+    for (start, end) in ranges {
+        let rates = storage
+            .get_historical_range(
+                start.and_time(NaiveTime::MIN).and_utc(),
+                end.and_time(NaiveTime::MIN).and_utc(),
+            )
+            .await
+            .context("convert on dates get historical rates")
+            .as_internal_err()?;
+        historical.extend(rates.into_iter().filter(has_pair));
+    }
+
+    let now = clock.now();
+    let mut ret = Vec::with_capacity(dates.len());
+    for date in dates {
+        let day = date.date_naive();
+        let rates = match latest.error.is_none() && day >= latest.data.date.date_naive() {
+            true => Some(&latest).filter(|latest| has_pair(latest)),
+            false => historical
+                .iter()
+                .filter(|rates| rates.data.date.date_naive() <= day)
+                .max_by_key(|rates| rates.data.date),
+        };
+        let Some(rates) = rates else {
+            return Err(ForexError::client_error(
+                format!("no rates of {}/{} on {}", from.code(), to.code(), day).as_str(),
+            ));
+        };
+        if business_days_between(rates.data.date.date_naive(), day) > RATE_ON_MAX_STALE_DAYS {
+            return Err(ForexError::client_error(
+                format!(
+                    "rates of {}/{} on {} are stale, latest are of {}",
+                    from.code(),
+                    to.code(),
+                    day,
+                    rates.data.date.date_naive()
+                )
+                .as_str(),
+            ));
+        }
+
+        let converted = Money::convert(&rates.data.rates, from, to)?;
+        let (rates_age_seconds, stale) = rates_staleness(rates.data.date, Some(*date), now);
+        ret.push(ConversionResponse {
+            date: rates.data.date,
+            from,
+            to: converted,
+            code: converted.format(FormatOptions::CODE),
+            symbol: converted.format(FormatOptions::SYMBOL),
+            rates_age_seconds,
+            stale,
+            steps: None,
+        });
+    }
+
+    Ok(ret)
+}
+
+/// Price of 1 unit of base in quote currency from the rates.
+pub fn pair_rate(rates: &Rates, base: Currency, quote: Currency) -> ForexResult<Decimal> {
+    let ret = Money::convert(&rates.rates, Money::new_money(base, dec!(1)), quote)?;
+
+    Ok(ret.amount())
+}
+
+/// business days rates of [`rate_on`] may lag behind the requested date.
+/// weekend days are not counted, rates of friday are fresh over the weekend.
+pub const RATE_ON_MAX_STALE_DAYS: i64 = 1;
+
+/// price of 1 base in quote on the date, from latest rates if date is on or after them, else historical rates.
+/// falls back to most recent earlier rates having the pair, e.g. weekends or missed polls,
+/// erroring when they're older than [`RATE_ON_MAX_STALE_DAYS`] business days.
+#[instrument(skip(storage), ret)]
+pub async fn rate_on<FS>(
+    storage: &FS,
+    date: DateTime<Utc>,
+    base: Currency,
+    quote: Currency,
+) -> ForexResult<Decimal>
+where
+    FS: ForexStorage,
+{
+    let day = date.date_naive();
+    let latest = storage
+        .get_latest()
+        .await
+        .context("rate on get latest rates")
+        .as_internal_err()?;
+    let found = match latest.error.is_none() && day >= latest.data.date.date_naive() {
+        true => Some((latest.data.date, pair_rate(&latest.data, base, quote)?)),
+        false => {
+            // enough days to cover a weekend on top of the stale days
+            let lookback = date - TimeDelta::days(RATE_ON_MAX_STALE_DAYS + 2);
+            let mut found = None;
+            for rates in storage
+                .get_historical_range(lookback, date)
+                .await
+                .context("rate on get historical rates")
+                .as_internal_err()?
+            {
+                let rates_day = rates.data.date.date_naive();
+                if rates.error.is_some() || rates_day > day || rates_day < lookback.date_naive() {
+                    continue;
+                }
+                let rate = pair_rate(&rates.data, base, quote)?;
+                if !rate.is_zero()
+                    && found.is_none_or(|(found_date, _)| rates.data.date > found_date)
+                {
+                    found = Some((rates.data.date, rate));
+                }
+            }
+            found
+        }
+    };
+
+    let Some((rates_date, rate)) = found.filter(|(_, rate)| !rate.is_zero()) else {
+        return Err(ForexError::client_error(
+            format!("no rates of {}/{} on {}", base.code(), quote.code(), day).as_str(),
+        ));
+    };
+    if business_days_between(rates_date.date_naive(), day) > RATE_ON_MAX_STALE_DAYS {
+        return Err(ForexError::client_error(
+            format!(
+                "rates of {}/{} on {} are stale, latest are of {}",
+                base.code(),
+                quote.code(),
+                day,
+                rates_date.date_naive()
+            )
+            .as_str(),
+        ));
+    }
+
+    Ok(rate)
+}
+
+/// weekdays after from up to and including to.
+fn business_days_between(from: NaiveDate, to: NaiveDate) -> i64 {
+    from.iter_days()
+        .skip(1)
+        .take_while(|day| *day <= to)
+        .filter(|day| !matches!(day.weekday(), Weekday::Sat | Weekday::Sun))
+        .count() as i64
+}
+
 pub async fn update_historical_rates_data<FX, FS>(
     forex: &FX,
     storage: &FS,
@@ -252,14 +619,20 @@ pub async fn update_historical_rates_data<FX, FS>(
     currencies_to_update: Vec<Currency>,
 ) -> ForexResult<RatesResponse<Rates>>
 where
-    FX: ForexHistoricalRates,
+    FX: ForexHistoricalRates + ForexProviderCapabilities,
     FS: ForexStorage,
 {
     let historical_data = storage.get_historical(date).await?;
     let base = historical_data.data.base;
+    let capabilities = forex.capabilities();
+    check_capabilities(&capabilities, base, Some(date))?;
     let ret = forex.historical_rates(date, base).await?;
     let mut new_rates: Vec<Money> = vec![];
-    for c in currencies_to_update {
+    // currencies the provider doesn't quote are kept as stored
+    for c in currencies_to_update
+        .into_iter()
+        .filter(|c| capabilities.supports(*c))
+    {
         match c {
             // fiat
 
@@ -367,6 +740,10 @@ where
         }
     }
 
+    let new_rates = new_rates
+        .into_iter()
+        .map(|money| (money, ret.source.clone()))
+        .collect();
     let updated_historical_data = storage
         .update_historical_rates_data(date, new_rates)
         .await?;
@@ -374,20 +751,614 @@ where
     Ok(updated_historical_data)
 }
 
+/// error when the provider doesn't quote the base or has no rates on the date, so it's not called in vain.
+fn check_capabilities(
+    capabilities: &ProviderCapabilities,
+    base: Currency,
+    date: Option<DateTime<Utc>>,
+) -> ForexResult<()> {
+    if !capabilities.supports(base) {
+        return Err(ForexError::client_error(
+            format!("provider doesn't support base currency {}", base.code()).as_str(),
+        ));
+    }
+    if let Some(date) = date
+        && !capabilities.covers(date)
+    {
+        return Err(ForexError::client_error(
+            format!(
+                "provider has no rates on {}, its history starts on {}",
+                date.date_naive(),
+                capabilities
+                    .history_start
+                    .map(|start| start.date_naive().to_string())
+                    .unwrap_or_default()
+            )
+            .as_str(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Outcome of provider call bounded by timeout and cancellation.
+enum ProviderCall<T> {
+    Done(ForexResult<T>),
+    Cancelled,
+}
+
+/// Invoke 3rd API call, giving up once it takes longer than configured timeout,
+/// or once cancel token is cancelled(e.g. on shutdown).
+/// call timing out its attempts itself, e.g. of chain trying providers one by one, is only bounded by cancellation.
+async fn call_provider<T, F>(
+    call: F,
+    times_out_attempts: bool,
+    cancel: &CancellationToken,
+) -> ProviderCall<T>
+where
+    F: Future<Output = ForexResult<T>>,
+{
+    if times_out_attempts {
+        return tokio::select! {
+            biased;
+            _ = cancel.cancelled() => ProviderCall::Cancelled,
+            ret = call => ProviderCall::Done(ret),
+        };
+    }
+
+    let timeout = Duration::from_secs(global::config().forex_provider_timeout_secs);
+    tokio::select! {
+        biased;
+        _ = cancel.cancelled() => ProviderCall::Cancelled,
+        ret = tokio::time::timeout(timeout, call) => match ret {
+            Ok(ret) => ProviderCall::Done(ret),
+            Err(_) => ProviderCall::Done(Err(ForexError::internal_error(&format!(
+                "provider call timed out after {} seconds",
+                timeout.as_secs()
+            )))),
+        },
+    }
+}
+
+/// capture daily close prices of crypto currencies enabled in this deployment on the date,
+/// and merge them into stored historical rates of the date. rates already stored from providers are kept.
+/// the run is recorded, as processed items the number of rates merged.
+pub async fn capture_crypto_prices<CP, FS>(
+    crypto: &CP,
+    storage: &FS,
+    date: DateTime<Utc>,
+    trigger: JobTrigger,
+    cancel: &CancellationToken,
+) -> ForexResult<CryptoIngestReport>
+where
+    CP: ForexCryptoPrices,
+    FS: ForexStorage + ForexStorageJobRuns,
+{
+    let enabled = global::config().enabled_currencies();
+    let currencies: Vec<Currency> = Currency::of_kinds(&[CurrencyKind::Crypto])
+        .filter(|currency| enabled.contains(currency))
+        .collect();
+
+    let mut job_run = JobRun::start(Job::CaptureCryptoPrices, trigger);
+    let ret = match call_provider(crypto.daily_close(date, &currencies), false, cancel).await {
+        ProviderCall::Done(Ok(prices)) => {
+            ingest_crypto_history(storage, prices, CRYPTO_MAX_JUMP, false).await
+        }
+        ProviderCall::Done(Err(err)) => Err(err),
+        ProviderCall::Cancelled => Err(ForexError::internal_error(
+            "capture crypto prices cancelled",
+        )),
+    };
+    job_run.finish(match &ret {
+        Ok(report) => Ok(report.applied.len() as u32),
+        Err(err) => Err(err.detail()),
+    });
+    if let Err(err) = storage.insert_job_run(&job_run).await {
+        tracing::error!(
+            "failed storing run of job {}: {}",
+            Job::CaptureCryptoPrices.name(),
+            err
+        );
+    }
+
+    ret
+}
+
+/// Refresh crypto rates of latest rates with current prices from crypto provider, without polling fiat rates again.
+/// Invoked from Cron service more often than latest polls, crypto trades 24/7.
+/// Merged rates are stored as new latest snapshot with their sources in provenance, fiat rates and their date are kept.
+/// Prices jumping more than CRYPTO_MAX_JUMP times from current rates are skipped.
+pub async fn poll_crypto_rates<CP, FS>(
+    crypto: &CP,
+    storage: &FS,
+    clock: &impl Clock,
+    trigger: JobTrigger,
+    cancel: &CancellationToken,
+) -> ForexResult<RatesResponse<Rates>>
+where
+    CP: ForexCryptoPrices,
+    FS: ForexStorage + ForexStorageJobRuns,
+{
+    let enabled = global::config().enabled_currencies();
+    let currencies: Vec<Currency> = Currency::crypto()
+        .filter(|currency| enabled.contains(currency))
+        .collect();
+
+    let mut job_run = JobRun::start(Job::PollCryptoRates, trigger);
+    let ret = merge_latest_crypto(crypto, storage, clock, &currencies, cancel).await;
+    job_run.finish(match &ret {
+        Ok((_, merged)) => Ok(*merged),
+        Err(err) => Err(err.detail()),
+    });
+    if let Err(err) = storage.insert_job_run(&job_run).await {
+        tracing::error!(
+            "failed storing run of job {}: {}",
+            Job::PollCryptoRates.name(),
+            err
+        );
+    }
+
+    ret.map(|(rates, _)| rates)
+}
+
+/// attempts of merging crypto prices into latest rates replaced meanwhile, e.g. by concurrent poll of fiat rates.
+const CRYPTO_MERGE_ATTEMPTS: usize = 3;
+
+/// latest rates with current crypto prices merged, and number of merged prices.
+/// merged into latest rates read again right before storing, so rates polled meanwhile aren't overwritten.
+async fn merge_latest_crypto<CP, FS>(
+    crypto: &CP,
+    storage: &FS,
+    clock: &impl Clock,
+    currencies: &[Currency],
+    cancel: &CancellationToken,
+) -> ForexResult<(RatesResponse<Rates>, u32)>
+where
+    CP: ForexCryptoPrices,
+    FS: ForexStorage,
+{
+    if currencies.is_empty() {
+        return Err(ForexError::client_error("no crypto currency is enabled"));
+    }
+    let mut latest = mergeable_latest(storage).await?;
+    let prices = match call_provider(crypto.latest(currencies), false, cancel).await {
+        ProviderCall::Done(ret) => ret?,
+        ProviderCall::Cancelled => {
+            return Err(ForexError::internal_error("poll crypto rates cancelled"));
+        }
+    };
+
+    let now = clock.now();
+    for _ in 0..CRYPTO_MERGE_ATTEMPTS {
+        let (merged, count) = merge_crypto_prices(&latest, &prices, now);
+        if count == 0 {
+            return Err(ForexError::internal_error(
+                "no crypto price merged into latest rates",
+            ));
+        }
+
+        let current = mergeable_latest(storage).await?;
+        if current.id != latest.id {
+            tracing::info!("latest rates changed while fetching crypto prices, merging again");
+            latest = current;
+            continue;
+        }
+        storage.insert_latest(now, &merged).await?;
+
+        return Ok((merged, count));
+    }
+
+    Err(ForexError::internal_error(
+        "latest rates kept changing while merging crypto prices",
+    ))
+}
+
+async fn mergeable_latest(storage: &impl ForexStorage) -> ForexResult<RatesResponse<Rates>> {
+    let latest = storage.get_latest().await?;
+    if latest.error.is_some() {
+        return Err(ForexError::internal_error(
+            "latest rates are of failed poll, no rates to merge crypto prices into",
+        ));
+    }
+
+    Ok(latest)
+}
+
+/// latest rates with the prices merged as new snapshot, and number of merged prices.
+fn merge_crypto_prices(
+    latest: &RatesResponse<Rates>,
+    prices: &[CryptoPrice],
+    now: DateTime<Utc>,
+) -> (RatesResponse<Rates>, u32) {
+    // prices are USD based, latest rates may not be
+    let usd = latest.data.rates.rate(Currency::USD);
+    let mut merged = latest.clone();
+    merged.id = Uuid::new_v4();
+    merged.poll_date = now;
+    let mut count = 0;
+    for price in prices {
+        let Some(old) = crypto_rate(&latest.data.rates, price.currency) else {
+            continue;
+        };
+        let new = price.rate * usd;
+        if new <= Decimal::ZERO {
+            tracing::warn!("crypto price of {} is not positive", price.currency);
+            continue;
+        }
+        if !old.is_zero() && (new / old).max(old / new) > CRYPTO_MAX_JUMP {
+            tracing::warn!(
+                "crypto price of {} jumped more than {}x from {} to {}, skipped",
+                price.currency,
+                CRYPTO_MAX_JUMP,
+                old,
+                new
+            );
+            continue;
+        }
+        merged
+            .data
+            .rates
+            .set_rate(Money::new_money(price.currency, new));
+        merged.record_provenance(price.currency, &price.source, now);
+        count += 1;
+    }
+
+    (merged, count)
+}
+
+/// fill metal rates missing from stored historical rates with rates from static dataset, e.g. before providers' coverage.
+/// dates without stored historical rates are skipped, dataset's source is appended to source of filled ones.
+/// on dry run nothing is written, the report lists dates that would be.
+pub async fn backfill_metals<FX, FS>(
+    dataset: &FX,
+    storage: &FS,
+    dates: Vec<DateTime<Utc>>,
+    dry_run: bool,
+) -> ForexResult<BackfillReport>
+where
+    FX: ForexHistoricalRates,
+    FS: ForexStorage,
+{
+    // dataset is read locally, no provider quota is spent
+    let mut report = BackfillReport {
+        dry_run,
+        ..Default::default()
+    };
+    let (Some(start), Some(end)) = (dates.iter().min(), dates.iter().max()) else {
+        return Ok(report);
+    };
+    let stored: HashMap<_, _> = storage
+        .get_historical_range(*start, *end)
+        .await?
+        .into_iter()
+        .map(|rates| (rates.data.date.date_naive(), rates))
+        .collect();
+
+    for date in dates {
+        let fix = dataset.historical_rates(date, Currency::USD).await?;
+        let rates = match stored.get(&date.date_naive()) {
+            // dataset has only metal rates, storing them alone would leave USD and fiats zero
+            None => {
+                tracing::warn!("backfill metals skipping {}, no stored rates to fill", date);
+                report.skipped.push(date);
+                continue;
+            }
+            Some(stored) if stored.data.base != Currency::USD => {
+                tracing::warn!(
+                    "backfill metals skipping {}, stored rates aren't USD based",
+                    date
+                );
+                report.skipped.push(date);
+                continue;
+            }
+            Some(stored) => {
+                let mut rates = stored.clone();
+                let mut filled = vec![];
+                for (currency, stored_rate, fix_rate) in [
+                    (Currency::XAU, &mut rates.data.rates.xau, fix.data.rates.xau),
+                    (Currency::XAG, &mut rates.data.rates.xag, fix.data.rates.xag),
+                    (Currency::XPT, &mut rates.data.rates.xpt, fix.data.rates.xpt),
+                ] {
+                    if stored_rate.is_zero() && !fix_rate.is_zero() {
+                        *stored_rate = fix_rate;
+                        filled.push(currency);
+                    }
+                }
+                if filled.is_empty() {
+                    report.skipped.push(date);
+                    continue;
+                }
+                let updated_at = Utc::now();
+                for currency in filled {
+                    rates.record_provenance(currency, &fix.source, updated_at);
+                }
+                rates.source = format!("{}+{}", rates.source, fix.source);
+                rates
+            }
+        };
+
+        if !dry_run {
+            storage.insert_historical(rates.data.date, &rates).await?;
+        }
+        report.dates.push(date);
+    }
+
+    Ok(report)
+}
+
+/// max ratio between consecutive rates of the same crypto accepted by ingestion.
+/// larger jump is most likely price in wrong unit or broken row.
+pub const CRYPTO_MAX_JUMP: Decimal = dec!(10);
+
+/// fill zero crypto rates of stored historical rates with imported USD based prices.
+/// prices on dates without stored historical rates, or jumping more than max_jump times from previous known rate
+/// of the same crypto are rejected. on dry run nothing is written, the report is the diff.
+pub async fn ingest_crypto_history<FS>(
+    storage: &FS,
+    prices: Vec<CryptoPrice>,
+    max_jump: Decimal,
+    dry_run: bool,
+) -> ForexResult<CryptoIngestReport>
+where
+    FS: ForexStorage,
+{
+    let mut report = CryptoIngestReport {
+        dry_run,
+        ..Default::default()
+    };
+    let (Some(start), Some(end)) = (
+        prices.iter().map(|price| price.date).min(),
+        prices.iter().map(|price| price.date).max(),
+    ) else {
+        return Ok(report);
+    };
+
+    let mut imported = HashMap::new();
+    for price in prices {
+        if crypto_rate(&RatesData::default(), price.currency).is_none() {
+            return Err(ForexError::client_error(
+                format!("{} is not crypto currency", price.currency.code()).as_str(),
+            ));
+        }
+        imported.insert(
+            (price.date.date_naive(), price.currency),
+            (price.rate, price.source),
+        );
+    }
+
+    // look back a few days, so first imported rates are checked against known rates too.
+    let mut stored = storage
+        .get_historical_range(start - TimeDelta::days(7), end)
+        .await?;
+    stored.sort_by_key(|rates| rates.data.date);
+
+    let mut previous: HashMap<Currency, Decimal> = HashMap::new();
+    let mut updates = vec![];
+    for rates in stored {
+        if rates.data.base != Currency::USD {
+            continue;
+        }
+        let date = rates.data.date;
+        let mut new_rates: Vec<(Money, String)> = vec![];
+        for currency in Currency::iter() {
+            let Some(old) = crypto_rate(&rates.data.rates, currency) else {
+                continue;
+            };
+            let Some((new, source)) = imported.remove(&(date.date_naive(), currency)) else {
+                if !old.is_zero() {
+                    previous.insert(currency, old);
+                }
+                continue;
+            };
+            // rates from providers take precedence
+            if !old.is_zero() {
+                previous.insert(currency, old);
+                continue;
+            }
+
+            let mut change = CryptoRateChange {
+                date,
+                currency,
+                old,
+                new,
+                reason: None,
+            };
+            if new <= Decimal::ZERO {
+                change.reason = Some("rate is not positive".to_string());
+                report.rejected.push(change);
+                continue;
+            }
+            if let Some(prev) = previous.get(&currency).copied()
+                && (new / prev).max(prev / new) > max_jump
+            {
+                change.reason = Some(format!(
+                    "rate jumped more than {}x from previous rate {}",
+                    max_jump, prev
+                ));
+                report.rejected.push(change);
+                continue;
+            }
+
+            previous.insert(currency, new);
+            new_rates.push((Money::new_money(currency, new), source));
+            report.applied.push(change);
+        }
+
+        if !new_rates.is_empty() {
+            updates.push((date, new_rates));
+        }
+    }
+    if !dry_run && !updates.is_empty() {
+        storage.update_historical_bulk(updates).await?;
+    }
+
+    for ((date, currency), (new, _)) in imported {
+        report.rejected.push(CryptoRateChange {
+            date: date.and_time(NaiveTime::MIN).and_utc(),
+            currency,
+            old: Decimal::ZERO,
+            new,
+            reason: Some("no stored historical rates on the date".to_string()),
+        });
+    }
+    report
+        .rejected
+        .sort_by_key(|change| (change.date, change.currency.code()));
+
+    Ok(report)
+}
+
+fn crypto_rate(rates: &RatesData, currency: Currency) -> Option<Decimal> {
+    match currency {
+        Currency::BTC => Some(rates.btc),
+        Currency::ETH => Some(rates.eth),
+        Currency::SOL => Some(rates.sol),
+        Currency::XRP => Some(rates.xrp),
+        Currency::ADA => Some(rates.ada),
+        _ => None,
+    }
+}
+
+/// max difference of base currency rate from 1 still considered normalized.
+pub const BASE_RATE_TOLERANCE: Decimal = dec!(0.000001);
+
+/// source recorded in provenance of historical rates rescaled by audit_base_rates.
+pub const BASE_RATE_REPAIR_SOURCE: &str = "base rate normalization";
+
+/// check all stored latest and historical rates have rate of their base currency equal to 1.
+/// on repair, violating rates are rescaled by their base currency rate and overwritten, except those having zero base rate.
+pub async fn audit_base_rates<FS>(storage: &FS, repair: bool) -> ForexResult<Vec<BaseRateViolation>>
+where
+    FS: ForexStorage,
+{
+    let latest = storage
+        .get_latest_list(1, u32::MAX, Order::ASC, OrderBy::DataDate)
+        .await?;
+    let historical = storage
+        .get_historical_list(1, u32::MAX, Order::ASC, OrderBy::DataDate)
+        .await?;
+
+    let mut violations = vec![];
+    let mut historical_repairs = vec![];
+    for (kind, list) in [
+        (RatesKind::Latest, latest.rates_list),
+        (RatesKind::Historical, historical.rates_list),
+    ] {
+        for mut rates in list {
+            if rates.error.is_some() {
+                continue;
+            }
+            let base = rates.data.base;
+            let base_rate = rates.data.rates.rate(base);
+            if (base_rate - dec!(1)).abs() <= BASE_RATE_TOLERANCE {
+                continue;
+            }
+
+            let mut violation = BaseRateViolation {
+                kind,
+                rates_id: rates.id,
+                date: rates.data.date,
+                base,
+                base_rate,
+                repaired: false,
+            };
+            if repair && let Some(rescaled) = rates.data.rates.rescale(base_rate) {
+                match kind {
+                    RatesKind::Latest => {
+                        rates.data.rates = rescaled;
+                        storage.insert_latest(rates.data.date, &rates).await?
+                    }
+                    RatesKind::Historical => {
+                        let new_rates = Currency::iter()
+                            .map(|currency| {
+                                (
+                                    Money::new_money(currency, rescaled.rate(currency)),
+                                    BASE_RATE_REPAIR_SOURCE.to_string(),
+                                )
+                            })
+                            .collect();
+                        historical_repairs.push((rates.data.date, new_rates));
+                    }
+                }
+                violation.repaired = true;
+            }
+            tracing::warn!(
+                "{:?} rates {} on {} has {} rate {}, repaired: {}",
+                kind,
+                violation.rates_id,
+                violation.date,
+                base.code(),
+                base_rate,
+                violation.repaired
+            );
+            violations.push(violation);
+        }
+    }
+    if !historical_repairs.is_empty() {
+        storage.update_historical_bulk(historical_repairs).await?;
+    }
+
+    Ok(violations)
+}
+
+/// fetch historical rates of the date from both providers and store report of currencies whose rates differ more than threshold percent.
+/// provider names in the report are sources of their rates.
+#[instrument(skip(first, second, storage), ret)]
+pub async fn check_provider_consistency<A, B, FS>(
+    first: &A,
+    second: &B,
+    storage: &FS,
+    date: DateTime<Utc>,
+    threshold_percent: Decimal,
+) -> ForexResult<ConsistencyReport>
+where
+    A: ForexHistoricalRates,
+    B: ForexHistoricalRates,
+    FS: ForexStorageConsistency,
+{
+    let base = Currency::USD;
+    let (first_rates, second_rates) = tokio::try_join!(
+        first.historical_rates(date, base),
+        second.historical_rates(date, base)
+    )?;
+
+    let report = ConsistencyReport::compare(&first_rates, &second_rates, threshold_percent)?;
+    if !report.is_consistent() {
+        tracing::warn!(
+            "providers {} and {} disagree on {} currencies on {}",
+            report.first_provider,
+            report.second_provider,
+            report.discrepancies.len(),
+            date
+        );
+    }
+    storage.insert_consistency_report(&report).await?;
+
+    Ok(report)
+}
+
 /// Get rates from 3rd API.
 /// Invoked from Cron service.
+/// Nothing is stored if cancelled before the API returns.
+/// Failed call is stored as error rates dated by the clock.
 pub async fn poll_rates<FX, FS>(
     forex: &FX,
     storage: &FS,
+    clock: &impl Clock,
     base: Currency,
+    cancel: &CancellationToken,
 ) -> ForexResult<RatesResponse<Rates>>
 where
-    FX: ForexRates,
+    FX: ForexRates + ForexProviderCapabilities,
     FS: ForexStorage,
 {
-    let ret = match forex.rates(base).await {
-        Ok(val) => val,
-        Err(error) => RatesResponse::<Rates>::err(Utc::now(), error),
+    check_capabilities(&forex.capabilities(), base, None)?;
+    let ret = match call_provider(forex.rates(base), forex.times_out_attempts(), cancel).await {
+        ProviderCall::Done(Ok(val)) => val,
+        ProviderCall::Done(Err(error)) => RatesResponse::<Rates>::err(clock.now(), error),
+        ProviderCall::Cancelled => {
+            return Err(ForexError::internal_error("poll rates cancelled"));
+        }
     };
 
     storage.insert_latest(ret.data.date, &ret).await?;
@@ -395,29 +1366,463 @@ where
     Ok(ret)
 }
 
+/// Fill empty storage with latest rates and historical rates of the last `days` days.
+/// Invoked on startup of fresh deployment, before cron polls for the first time.
+/// Failing historical dates are only logged, they're polled again by cron.
+pub async fn bootstrap<FX, FS>(
+    forex: &FX,
+    storage: &FS,
+    clock: &impl Clock,
+    base: Currency,
+    days: u32,
+    cancel: &CancellationToken,
+) -> ForexResult<()>
+where
+    FX: ForexRates + ForexHistoricalRates + ForexProviderCapabilities,
+    FS: ForexStorage,
+{
+    let latest = poll_rates(forex, storage, clock, base, cancel).await?;
+    if let Some(err) = latest.error {
+        return Err(ForexError::internal_error(
+            format!("bootstrap latest rates: {}", err).as_str(),
+        ));
+    }
+
+    let today = clock.now();
+    for day in 1..=days {
+        let date = today - TimeDelta::days(day as i64);
+        match poll_historical_rates(forex, storage, date, base, PollMode::SkipExisting, cancel)
+            .await
+        {
+            Ok(rates) if rates.error.is_none() => {}
+            Ok(rates) => tracing::warn!(
+                "bootstrap historical rates of {}: {}",
+                date.date_naive(),
+                rates.error.unwrap_or_default()
+            ),
+            Err(err) => tracing::warn!(
+                "bootstrap historical rates of {}: {}",
+                date.date_naive(),
+                err
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+/// Read latest rates and historical rates of the last `days` days, so caching storage keeps them in memory.
+/// Invoked on startup of read heavy services before serving requests. Missing rates are skipped.
+/// Returns number of rates read.
+pub async fn warm_cache<FS>(storage: &FS, clock: &impl Clock, days: u32) -> usize
+where
+    FS: ForexStorage,
+{
+    let mut warmed = 0;
+    match storage.get_latest().await {
+        Ok(_) => warmed += 1,
+        Err(err) => tracing::warn!("warm cache latest rates: {}", err),
+    }
+
+    let today = clock.now();
+    for day in 1..=days {
+        let date = today - TimeDelta::days(day as i64);
+        if storage.get_historical(date).await.is_ok() {
+            warmed += 1;
+        }
+    }
+
+    warmed
+}
+
 /// Get historical rates from 3rd API.
 /// Invoked from Cron service.
+/// With PollMode::SkipExisting, stored valid rates for the date are returned without calling the API.
+/// Dates before history of the provider are rejected without calling the API.
+/// Nothing is stored if cancelled before the API returns.
 pub async fn poll_historical_rates<FX, FS>(
     forex: &FX,
     storage: &FS,
     date: DateTime<Utc>,
     base: Currency,
+    mode: PollMode,
+    cancel: &CancellationToken,
 ) -> ForexResult<RatesResponse<Rates>>
 where
-    FX: ForexHistoricalRates,
+    FX: ForexHistoricalRates + ForexProviderCapabilities,
     FS: ForexStorage,
 {
-    let ret = match forex.historical_rates(date, base).await {
-        Ok(val) => {
+    if mode == PollMode::SkipExisting
+        && let Ok(existing) = storage.get_historical(date).await
+        && existing.error.is_none()
+    {
+        tracing::info!(
+            "poll historical rates for {} skipped, data already exist",
+            date
+        );
+        return Ok(existing);
+    }
+
+    check_capabilities(&forex.capabilities(), base, Some(date))?;
+    let ret = match call_provider(
+        forex.historical_rates(date, base),
+        forex.times_out_attempts(),
+        cancel,
+    )
+    .await
+    {
+        ProviderCall::Done(Ok(val)) => {
             storage.insert_historical(val.data.date, &val).await?;
             val
         }
-        Err(error) => {
+        ProviderCall::Done(Err(error)) => {
             let err = RatesResponse::<Rates>::err(date, error);
             storage.insert_historical(date, &err).await?;
             err
         }
+        ProviderCall::Cancelled => {
+            return Err(ForexError::internal_error(
+                "poll historical rates cancelled",
+            ));
+        }
     };
 
     Ok(ret)
 }
+
+/// pause between timeseries calls, most providers allow 1 call per minute on free plans.
+pub const TIMESERIES_CHUNK_INTERVAL: Duration = Duration::from_secs(62);
+
+/// Range of rates fetched by [`fetch_timeseries`].
+#[derive(Debug, Clone, Copy)]
+pub struct TimeseriesQuery {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub base: Currency,
+
+    /// pause between chunks.
+    pub interval: Duration,
+
+    /// provider isn't called and nothing is written, the report lists dates that would be.
+    pub dry_run: bool,
+}
+
+impl TimeseriesQuery {
+    /// query pausing [`TIMESERIES_CHUNK_INTERVAL`] between chunks.
+    pub fn new(from: DateTime<Utc>, to: DateTime<Utc>, base: Currency) -> Self {
+        Self {
+            from,
+            to,
+            base,
+            interval: TIMESERIES_CHUNK_INTERVAL,
+            dry_run: false,
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+}
+
+/// Get historical rates of the query from timeseries API and store them.
+/// Range is split into chunks the provider can serve, fetched one by one with pause of the query in between.
+/// Chunks stored before an error or cancellation are kept.
+pub async fn fetch_timeseries<FX, FS>(
+    forex: &FX,
+    storage: &FS,
+    query: TimeseriesQuery,
+    cancel: &CancellationToken,
+) -> ForexResult<BackfillReport>
+where
+    FX: ForexTimeseriesRates + ForexProviderCapabilities,
+    FS: ForexStorage,
+{
+    let TimeseriesQuery {
+        from,
+        to,
+        base,
+        interval,
+        dry_run,
+    } = query;
+    if from > to {
+        return Err(ForexError::client_error("from must not be after to"));
+    }
+    let capabilities = forex.capabilities();
+    check_capabilities(&capabilities, base, None)?;
+    let ranges = capabilities.split_date_range(from, to);
+    if ranges.is_empty() {
+        check_capabilities(&capabilities, base, Some(to))?;
+    }
+
+    let mut report = BackfillReport {
+        dry_run,
+        quota_cost: ranges.len(),
+        ..Default::default()
+    };
+    if dry_run {
+        for date in days_between(from, to) {
+            match capabilities.covers(date) {
+                true => report.dates.push(date),
+                false => report.skipped.push(date),
+            }
+        }
+        return Ok(report);
+    }
+
+    for (index, (start, end)) in ranges.into_iter().enumerate() {
+        if index > 0 {
+            tokio::select! {
+                _ = cancel.cancelled() => {
+                    return Err(ForexError::internal_error("fetch timeseries cancelled"));
+                }
+                _ = tokio::time::sleep(interval) => {}
+            }
+        }
+
+        tracing::info!(
+            "fetching timeseries rates from {} till {}",
+            start.date_naive(),
+            end.date_naive()
+        );
+        let rates = match call_provider(
+            forex.timeseries_rates(start, end, base),
+            forex.times_out_attempts(),
+            cancel,
+        )
+        .await
+        {
+            ProviderCall::Done(ret) => ret?,
+            ProviderCall::Cancelled => {
+                return Err(ForexError::internal_error("fetch timeseries cancelled"));
+            }
+        };
+        report
+            .dates
+            .extend(rates.iter().map(|rates| rates.data.date));
+        storage.insert_historical_batch(rates).await?;
+    }
+
+    Ok(report)
+}
+
+/// Dates poll_historical_rates would call the provider for, without calling it nor writing anything.
+/// Each polled date costs 1 provider call.
+pub async fn plan_historical_rates<FX, FS>(
+    forex: &FX,
+    storage: &FS,
+    dates: Vec<DateTime<Utc>>,
+    base: Currency,
+    mode: PollMode,
+) -> ForexResult<BackfillReport>
+where
+    FX: ForexProviderCapabilities,
+    FS: ForexStorage,
+{
+    let capabilities = forex.capabilities();
+    check_capabilities(&capabilities, base, None)?;
+
+    let mut report = BackfillReport {
+        dry_run: true,
+        ..Default::default()
+    };
+    for date in dates {
+        let existing = mode == PollMode::SkipExisting
+            && storage
+                .get_historical(date)
+                .await
+                .is_ok_and(|rates| rates.error.is_none());
+        match !existing && capabilities.covers(date) {
+            true => report.dates.push(date),
+            false => report.skipped.push(date),
+        }
+    }
+    report.quota_cost = report.dates.len();
+
+    Ok(report)
+}
+
+/// every day between from and to inclusive, at the time of from.
+fn days_between(from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    let mut days = vec![];
+    let mut date = from;
+    while date <= to {
+        days.push(date);
+        date += TimeDelta::days(1);
+    }
+
+    days
+}
+
+/// Run job polling rates and persist its outcome.
+/// Run is recorded as failed if job returns error or the rates polled with error.
+pub async fn run_job<FS, F>(
+    storage: &FS,
+    job: Job,
+    trigger: JobTrigger,
+    run: F,
+) -> ForexResult<RatesResponse<Rates>>
+where
+    FS: ForexStorageJobRuns,
+    F: Future<Output = ForexResult<RatesResponse<Rates>>>,
+{
+    let mut job_run = JobRun::start(job, trigger);
+    let ret = run.await;
+    let outcome = match &ret {
+        Ok(rates) => match &rates.error {
+            Some(err) => Err(err.clone()),
+            None => Ok(1),
+        },
+        Err(err) => Err(err.detail()),
+    };
+    job_run.finish(outcome);
+
+    if let Err(err) = storage.insert_job_run(&job_run).await {
+        tracing::error!("failed storing run of job {}: {}", job.name(), err);
+    }
+
+    ret
+}
+
+/// Deliver newly polled rates to webhooks and persist outcome of each delivery.
+/// Rates polled with error are not published.
+pub async fn publish_rates<W, FS>(webhook: &W, storage: &FS, rates: &RatesResponse<Rates>)
+where
+    W: ForexRatesWebhook,
+    FS: ForexStorageWebhookDeliveries,
+{
+    if rates.error.is_some() {
+        return;
+    }
+
+    for delivery in webhook.publish(rates).await {
+        if !delivery.success {
+            tracing::error!(
+                "failed delivering rates {} to webhook {} after {} attempts: {:?}",
+                rates.id,
+                delivery.url,
+                delivery.attempts,
+                delivery.error
+            );
+        }
+        if let Err(err) = storage.insert_webhook_delivery(&delivery).await {
+            tracing::error!("failed storing webhook delivery {}: {}", delivery.id, err);
+        }
+    }
+}
+
+/// Take lock of the job for holder, so the job runs only on one instance sharing the storage.
+/// Lock is kept until ttl passed, failing to take it is treated as held by other instance.
+pub async fn lock_job<FS>(storage: &FS, job: Job, holder: &str, ttl: TimeDelta) -> bool
+where
+    FS: ForexStorageJobLock,
+{
+    match storage.try_lock_job(job, holder, ttl).await {
+        Ok(locked) => locked,
+        Err(err) => {
+            tracing::error!("failed taking lock of job {}: {}", job.name(), err);
+            false
+        }
+    }
+}
+
+/// Get latest runs of background jobs.
+pub async fn get_job_runs<FS>(
+    storage: &FS,
+    job: Option<Job>,
+    limit: u32,
+) -> ForexResult<Vec<JobRun>>
+where
+    FS: ForexStorageJobRuns,
+{
+    storage.get_job_runs(job, limit).await
+}
+
+/// Get historical rates between start and end with rates before redenominations adjusted into current units,
+/// so long series are continuous. raw keeps rates as stored.
+pub async fn get_historical_range<FS>(
+    storage: &FS,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    raw: bool,
+) -> ForexResult<Vec<RatesResponse<Rates>>>
+where
+    FS: ForexStorage,
+{
+    let mut rates = storage.get_historical_range(start, end).await?;
+    if !raw {
+        for rates in rates.iter_mut() {
+            redenomination::to_current_units(&mut rates.data);
+        }
+    }
+
+    Ok(rates)
+}
+
+/// Get disk usage of storage.
+pub async fn get_storage_stats<FS>(storage: &FS) -> ForexResult<StorageStats>
+where
+    FS: ForexStorageStats,
+{
+    storage.stats().await
+}
+
+/// Keep storage under max disk usage in MB, 0 means unlimited, metadata isn't counted.
+/// older latest rates are cleared first, then oldest archived latest rates are pruned until usage fits.
+/// returns none when usage is already within the limit.
+pub async fn enforce_disk_quota<FS>(
+    storage: &FS,
+    max_mb: u64,
+) -> ForexResult<Option<DiskQuotaReport>>
+where
+    FS: ForexStorageStats + ForexStorageDeletion,
+{
+    if max_mb == 0 {
+        return Ok(None);
+    }
+    let max_bytes = max_mb.saturating_mul(1024 * 1024);
+    let bytes_before = storage.stats().await?.quota_bytes();
+    if bytes_before <= max_bytes {
+        return Ok(None);
+    }
+
+    storage.clear_latest().await?;
+    let bytes = storage.stats().await?.quota_bytes();
+    let pruned = if bytes > max_bytes {
+        storage.prune_latest_archive(bytes - max_bytes).await?
+    } else {
+        vec![]
+    };
+
+    let report = DiskQuotaReport {
+        max_bytes,
+        bytes_before,
+        bytes_after: storage.stats().await?.quota_bytes(),
+        pruned,
+    };
+    if report.is_exceeded() {
+        tracing::warn!(
+            "storage disk usage of {} bytes still exceeds max {} bytes after cleanup",
+            report.bytes_after,
+            report.max_bytes
+        );
+    }
+
+    Ok(Some(report))
+}
+
+/// Get health of providers having recorded calls, from the healthiest.
+pub async fn get_provider_health<FS>(storage: &FS) -> ForexResult<Vec<ProviderHealth>>
+where
+    FS: ForexStorageProviderHealth,
+{
+    let mut healths = storage.get_provider_health().await?;
+    healths.sort_by_key(|health| std::cmp::Reverse(health.score()));
+
+    Ok(healths)
+}
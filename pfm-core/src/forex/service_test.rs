@@ -1,14 +1,25 @@
 use chrono::{DateTime, TimeZone, Utc};
 use rust_decimal_macros::dec;
+use tokio_util::sync::CancellationToken;
 
 use crate::{
     forex::{
-        Currency, Money,
-        entity::ConversionResponse,
-        interface::ForexStorage,
-        service::{batch_convert, convert, convert_historical, poll_historical_rates, poll_rates},
+        Currency, ForexError, ForexResult, FormatOptions, Money,
+        entity::{
+            ConversionResponse, Job, JobTrigger, PollMode, RatesKind, StorageStats,
+            StorageSubtreeStats,
+        },
+        interface::{ForexStorage, ForexStorageDeletion, ForexStorageStats},
+        service::{
+            TimeseriesQuery, batch_convert, convert, convert_historical, convert_on_dates,
+            enforce_disk_quota, fetch_timeseries, get_rates, latest_delta, plan_historical_rates,
+            poll_historical_rates, poll_rates, rate_on, rates_at, rates_sources, run_job,
+        },
+    },
+    global::{
+        self,
+        test_support::{rates, temp_storage},
     },
-    global,
 };
 
 #[tokio::test]
@@ -18,7 +29,9 @@ async fn test_convert() {
 
     let from = Money::new_money(crate::forex::Currency::GBP, dec!(1000));
     let to = Currency::SAR;
-    let ret = convert(&storage, from, to).await;
+    // latest rates of forex_mock are of 2025-03-04T02:00:00Z
+    let clock = global::MockClock::new(Utc.with_ymd_and_hms(2025, 3, 4, 3, 0, 0).unwrap());
+    let ret = convert(&storage, &clock, from, to, false).await;
     dbg!(&ret);
 
     assert!(ret.is_ok());
@@ -27,6 +40,13 @@ async fn test_convert() {
     // expected data come from forex_mock
     let expected = Money::new_money(Currency::SAR, dec!(4762.0152292578498482026199809));
     assert_eq!(ret.to, expected);
+    assert_eq!(ret.rates_age_seconds, 3600);
+    assert!(!ret.stale);
+
+    clock.advance(chrono::TimeDelta::hours(2));
+    let ret = convert(&storage, &clock, from, to, false).await.unwrap();
+    assert_eq!(ret.rates_age_seconds, 3 * 3600);
+    assert!(ret.stale);
 }
 
 #[tokio::test]
@@ -37,7 +57,7 @@ async fn test_convert_historical() {
     let from = Money::new_money(crate::forex::Currency::GBP, dec!(1000));
     let to = Currency::SAR;
     let date = Utc.with_ymd_and_hms(2022, 12, 25, 0, 0, 0).unwrap();
-    let ret = convert_historical(&storage, from, to, date).await;
+    let ret = convert_historical(&storage, &global::SystemClock, from, to, date, false).await;
     dbg!(&ret);
 
     assert!(ret.is_ok());
@@ -46,6 +66,44 @@ async fn test_convert_historical() {
     // expected data come from forex_mock
     let expected = Money::new_money(Currency::SAR, dec!(4533.0433702899590250394500024));
     assert_eq!(ret.to, expected);
+    // rates of past date are as fresh as requested
+    assert!(!ret.stale);
+}
+
+#[tokio::test]
+async fn test_convert_debug_steps() {
+    let storage = super::mock::ForexStorageSuccessMock;
+    let latest = storage.get_latest().await.unwrap();
+
+    let from = Money::new_money(Currency::GBP, dec!(1000));
+    let ret = convert(&storage, &global::SystemClock, from, Currency::SAR, false)
+        .await
+        .unwrap();
+    assert!(ret.steps.is_none());
+
+    let ret = convert(&storage, &global::SystemClock, from, Currency::SAR, true)
+        .await
+        .unwrap();
+    let steps = ret.steps.unwrap();
+    assert_eq!(steps.from_rate.currency, Currency::GBP);
+    assert_eq!(steps.from_rate.rate, latest.data.rates.gbp);
+    assert_eq!(steps.from_rate.date, latest.data.date);
+    assert_eq!(steps.to_rate.currency, Currency::SAR);
+    assert_eq!(steps.to_rate.rate, latest.data.rates.sar);
+    // from divided by its rate into base, then multiplied by rate of target
+    assert_eq!(
+        steps.base,
+        Money::new_money(latest.data.base, dec!(1000) / latest.data.rates.gbp)
+    );
+    assert_eq!(steps.base.amount() * steps.to_rate.rate, ret.to.amount());
+
+    // rate patched after polling is dated by its provenance
+    let patched_at = Utc.with_ymd_and_hms(2025, 3, 4, 5, 0, 0).unwrap();
+    let mut patched = latest.clone();
+    patched.record_provenance(Currency::SAR, "dataset", patched_at);
+    let steps = patched.conversion_steps(Currency::GBP, Currency::SAR, steps.base.amount());
+    assert_eq!(steps.from_rate.date, latest.data.date);
+    assert_eq!(steps.to_rate.date, patched_at);
 }
 
 #[tokio::test]
@@ -60,7 +118,7 @@ async fn test_batch_convert() {
     let from_sgd = Money::new_money(crate::forex::Currency::SGD, dec!(1300));
     let from = vec![from_gbp, from_usd, from_idr, from_chf, from_sgd];
     let to = Currency::SAR;
-    let ret = batch_convert(&storage, from, to).await;
+    let ret = batch_convert(&storage, &global::SystemClock, from, to).await;
     dbg!(&ret);
 
     // expected data come from forex_mock
@@ -71,8 +129,11 @@ async fn test_batch_convert() {
                 .with_timezone(&Utc),
             from: from_gbp,
             to: Money::SAR(dec!(4762.0152292578498482026199809)),
-            code: Money::SAR(dec!(4762.0152292578498482026199809)).format(false),
-            symbol: Money::SAR(dec!(4762.0152292578498482026199809)).format(true),
+            code: Money::SAR(dec!(4762.0152292578498482026199809)).format(FormatOptions::CODE),
+            symbol: Money::SAR(dec!(4762.0152292578498482026199809)).format(FormatOptions::SYMBOL),
+            rates_age_seconds: 0,
+            stale: false,
+            steps: None,
         },
         ConversionResponse {
             date: DateTime::parse_from_rfc3339("2025-03-04T02:00:00Z")
@@ -80,8 +141,11 @@ async fn test_batch_convert() {
                 .with_timezone(&Utc),
             from: from_usd,
             to: Money::SAR(dec!(15001.548000)),
-            code: Money::SAR(dec!(15001.548000)).format(false),
-            symbol: Money::SAR(dec!(15001.548000)).format(true),
+            code: Money::SAR(dec!(15001.548000)).format(FormatOptions::CODE),
+            symbol: Money::SAR(dec!(15001.548000)).format(FormatOptions::SYMBOL),
+            rates_age_seconds: 0,
+            stale: false,
+            steps: None,
         },
         ConversionResponse {
             date: DateTime::parse_from_rfc3339("2025-03-04T02:00:00Z")
@@ -89,8 +153,11 @@ async fn test_batch_convert() {
                 .with_timezone(&Utc),
             from: from_idr,
             to: Money::SAR(dec!(5.2401981046108984873336978311)),
-            code: Money::SAR(dec!(5.2401981046108984873336978311)).format(false),
-            symbol: Money::SAR(dec!(5.2401981046108984873336978311)).format(true),
+            code: Money::SAR(dec!(5.2401981046108984873336978311)).format(FormatOptions::CODE),
+            symbol: Money::SAR(dec!(5.2401981046108984873336978311)).format(FormatOptions::SYMBOL),
+            rates_age_seconds: 0,
+            stale: false,
+            steps: None,
         },
         ConversionResponse {
             date: DateTime::parse_from_rfc3339("2025-03-04T02:00:00Z")
@@ -98,8 +165,11 @@ async fn test_batch_convert() {
                 .with_timezone(&Utc),
             from: from_chf,
             to: Money::SAR(dec!(4186.4940892803322058872777200)),
-            code: Money::SAR(dec!(4186.4940892803322058872777200)).format(false),
-            symbol: Money::SAR(dec!(4186.4940892803322058872777200)).format(true),
+            code: Money::SAR(dec!(4186.4940892803322058872777200)).format(FormatOptions::CODE),
+            symbol: Money::SAR(dec!(4186.4940892803322058872777200)).format(FormatOptions::SYMBOL),
+            rates_age_seconds: 0,
+            stale: false,
+            steps: None,
         },
         ConversionResponse {
             date: DateTime::parse_from_rfc3339("2025-03-04T02:00:00Z")
@@ -107,8 +177,11 @@ async fn test_batch_convert() {
                 .with_timezone(&Utc),
             from: from_sgd,
             to: Money::SAR(dec!(3625.2651561342823236183774170)),
-            code: Money::SAR(dec!(3625.2651561342823236183774170)).format(false),
-            symbol: Money::SAR(dec!(3625.2651561342823236183774170)).format(true),
+            code: Money::SAR(dec!(3625.2651561342823236183774170)).format(FormatOptions::CODE),
+            symbol: Money::SAR(dec!(3625.2651561342823236183774170)).format(FormatOptions::SYMBOL),
+            rates_age_seconds: 0,
+            stale: false,
+            steps: None,
         },
     ];
 
@@ -129,7 +202,14 @@ async fn test_poll_rates() {
     let forex = super::mock::ForexApiSuccessMock;
 
     let base = Currency::USD;
-    let ret = poll_rates(&forex, &storage, base).await;
+    let ret = poll_rates(
+        &forex,
+        &storage,
+        &global::SystemClock,
+        base,
+        &CancellationToken::new(),
+    )
+    .await;
     dbg!(&ret);
 
     assert!(ret.is_ok());
@@ -147,7 +227,8 @@ async fn test_poll_historical_rates() {
 
     let base = Currency::USD;
     let date = Utc.with_ymd_and_hms(2022, 12, 25, 0, 0, 0).unwrap();
-    let ret = poll_historical_rates(&forex, &storage, date, base).await;
+    let cancel = CancellationToken::new();
+    let ret = poll_historical_rates(&forex, &storage, date, base, PollMode::Always, &cancel).await;
     dbg!(&ret);
 
     assert!(ret.is_ok());
@@ -155,13 +236,122 @@ async fn test_poll_historical_rates() {
     assert_eq!(ret.unwrap().data.base, Currency::USD);
 }
 
+#[tokio::test]
+async fn test_fetch_timeseries() {
+    let storage = super::mock::ForexStorageSuccessMock;
+    let forex = super::mock::ForexApiSuccessMock;
+
+    let from = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+    let to = Utc.with_ymd_and_hms(2025, 1, 31, 0, 0, 0).unwrap();
+    let cancel = CancellationToken::new();
+    let interval = std::time::Duration::ZERO;
+    let query = TimeseriesQuery::new(from, to, Currency::USD).with_interval(interval);
+    let ret = fetch_timeseries(&forex, &storage, query, &cancel)
+        .await
+        .unwrap();
+    assert!(!ret.dry_run);
+    assert!(!ret.dates.is_empty());
+    assert_eq!(ret.quota_cost, 1);
+
+    let ret = fetch_timeseries(&forex, &storage, query.with_dry_run(true), &cancel)
+        .await
+        .unwrap();
+    assert!(ret.dry_run);
+    assert_eq!(ret.dates.len(), 31);
+    assert_eq!(ret.quota_cost, 1);
+
+    let query = TimeseriesQuery::new(to, from, Currency::USD).with_dry_run(true);
+    let ret = fetch_timeseries(&forex, &storage, query, &cancel).await;
+    assert!(ret.is_err());
+}
+
+#[tokio::test]
+async fn test_plan_historical_rates() {
+    let storage = super::mock::ForexStorageSuccessMock;
+    let forex = super::mock::ForexApiSuccessMock;
+
+    let dates = vec![
+        Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+        Utc.with_ymd_and_hms(2025, 1, 2, 0, 0, 0).unwrap(),
+    ];
+    let ret = plan_historical_rates(
+        &forex,
+        &storage,
+        dates.clone(),
+        Currency::USD,
+        PollMode::Always,
+    )
+    .await
+    .unwrap();
+    assert!(ret.dry_run);
+    assert_eq!(ret.dates, dates);
+    assert_eq!(ret.quota_cost, 2);
+
+    // mock storage has valid rates of every date
+    let ret = plan_historical_rates(
+        &forex,
+        &storage,
+        dates.clone(),
+        Currency::USD,
+        PollMode::SkipExisting,
+    )
+    .await
+    .unwrap();
+    assert!(ret.dates.is_empty());
+    assert_eq!(ret.skipped, dates);
+    assert_eq!(ret.quota_cost, 0);
+}
+
+#[tokio::test]
+async fn test_poll_historical_rates_skip_existing() {
+    let storage = super::mock::ForexStorageSuccessMock;
+    let forex = super::mock::ForexApiSuccessMock;
+
+    let base = Currency::USD;
+    let date = Utc.with_ymd_and_hms(2022, 12, 25, 0, 0, 0).unwrap();
+    let cancel = CancellationToken::new();
+    let mode = PollMode::SkipExisting;
+    let ret = poll_historical_rates(&forex, &storage, date, base, mode, &cancel).await;
+    dbg!(&ret);
+
+    // data returned from storage, API not invoked.
+    let ret = ret.unwrap();
+    assert!(ret.error.is_none());
+    assert_eq!(ret.source, "storage_get_historical_success");
+}
+
+#[tokio::test]
+async fn test_poll_rates_cancelled() {
+    let storage = super::mock::ForexStorageSuccessMock;
+    let forex = super::mock::ForexApiSuccessMock;
+
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let ret = poll_rates(
+        &forex,
+        &storage,
+        &global::SystemClock,
+        Currency::USD,
+        &cancel,
+    )
+    .await;
+    dbg!(&ret);
+
+    assert!(ret.is_err());
+}
+
 #[tokio::test]
 async fn test_get_rates_list() {
     let fs = global::storage_fs();
     let storage = super::mock::ForexStorageSuccessMock;
 
     let ret = storage
-        .get_latest_list(1, 5, crate::forex::entity::Order::DESC)
+        .get_latest_list(
+            1,
+            5,
+            crate::forex::entity::Order::DESC,
+            crate::forex::entity::OrderBy::DataDate,
+        )
         .await;
     dbg!(&ret);
     let ret = ret.unwrap();
@@ -177,7 +367,12 @@ async fn test_get_historical_list() {
     let storage = super::mock::ForexStorageSuccessMock;
 
     let ret = storage
-        .get_historical_list(1, 5, crate::forex::entity::Order::DESC)
+        .get_historical_list(
+            1,
+            5,
+            crate::forex::entity::Order::DESC,
+            crate::forex::entity::OrderBy::DataDate,
+        )
         .await;
     dbg!(&ret);
     let ret = ret.unwrap();
@@ -186,3 +381,547 @@ async fn test_get_historical_list() {
     assert_eq!(ret.has_next, false);
     assert!(ret.rates_list[0].data.date > ret.rates_list[1].data.date);
 }
+
+#[tokio::test]
+async fn test_run_job() {
+    let storage = super::mock::ForexStorageSuccessMock;
+    let forex = super::mock::ForexApiSuccessMock;
+    let cancel = CancellationToken::new();
+
+    let ret = run_job(
+        &storage,
+        Job::PollLatestRates,
+        JobTrigger::Cron,
+        poll_rates(
+            &forex,
+            &storage,
+            &global::SystemClock,
+            Currency::USD,
+            &cancel,
+        ),
+    )
+    .await;
+    assert!(ret.unwrap().error.is_none());
+
+    let ret = run_job(&storage, Job::PollLatestRates, JobTrigger::Admin, async {
+        Err(ForexError::internal_error("job failed"))
+    })
+    .await;
+    assert!(ret.is_err());
+}
+
+#[tokio::test]
+async fn test_ingest_crypto_history() {
+    use super::entity::CryptoPrice;
+    use super::service::{CRYPTO_MAX_JUMP, ingest_crypto_history};
+
+    let storage = super::mock::ForexStorageSuccessMock;
+
+    // stored rates from providers are kept, dates without stored rates are rejected
+    let prices = vec![
+        CryptoPrice {
+            currency: Currency::BTC,
+            date: Utc.with_ymd_and_hms(2022, 12, 25, 0, 0, 0).unwrap(),
+            rate: dec!(0.00005),
+            source: "test".to_string(),
+        },
+        CryptoPrice {
+            currency: Currency::ETH,
+            date: Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap(),
+            rate: dec!(0.0008),
+            source: "test".to_string(),
+        },
+    ];
+    let ret = ingest_crypto_history(&storage, prices, CRYPTO_MAX_JUMP, true)
+        .await
+        .unwrap();
+    assert!(ret.dry_run);
+    assert!(ret.applied.is_empty());
+    assert_eq!(ret.rejected.len(), 1);
+    assert_eq!(ret.rejected[0].currency, Currency::ETH);
+
+    let prices = vec![CryptoPrice {
+        currency: Currency::IDR,
+        date: Utc.with_ymd_and_hms(2022, 12, 25, 0, 0, 0).unwrap(),
+        rate: dec!(16000),
+        source: "test".to_string(),
+    }];
+    let ret = ingest_crypto_history(&storage, prices, CRYPTO_MAX_JUMP, true).await;
+    assert!(ret.is_err());
+}
+
+#[tokio::test]
+async fn test_capture_crypto_prices() {
+    use super::service::capture_crypto_prices;
+
+    let crypto = super::mock::ForexApiSuccessMock;
+    let storage = super::mock::ForexStorageSuccessMock;
+    let cancel = CancellationToken::new();
+
+    // no stored rates to merge into on the date
+    let date = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+    let ret = capture_crypto_prices(&crypto, &storage, date, JobTrigger::Cron, &cancel)
+        .await
+        .unwrap();
+    assert!(!ret.dry_run);
+    assert!(ret.applied.is_empty());
+    assert!(!ret.rejected.is_empty());
+
+    // stored rates from providers are kept
+    let date = Utc.with_ymd_and_hms(2022, 12, 25, 0, 0, 0).unwrap();
+    let ret = capture_crypto_prices(&crypto, &storage, date, JobTrigger::Cron, &cancel)
+        .await
+        .unwrap();
+    assert!(ret.applied.is_empty());
+}
+
+#[tokio::test]
+async fn test_audit_base_rates() {
+    use super::service::audit_base_rates;
+
+    let storage = super::mock::ForexStorageSuccessMock;
+    let ret = audit_base_rates(&storage, false).await.unwrap();
+    assert!(ret.is_empty());
+}
+
+#[tokio::test]
+async fn test_get_rates_of_today_by_clock() {
+    let storage = super::mock::ForexStorageSuccessMock;
+    let date = Utc.with_ymd_and_hms(2022, 12, 25, 0, 0, 0).unwrap();
+    let clock = global::MockClock::new(date + chrono::TimeDelta::hours(10));
+
+    // rates of today are latest rates
+    let ret = get_rates(&storage, &clock, Currency::USD, Some(date))
+        .await
+        .unwrap();
+    assert_eq!(ret.source, "storage_get_latest_success");
+    let ret = get_rates(&storage, &clock, Currency::EUR, Some(date))
+        .await
+        .unwrap();
+    assert_eq!(ret.source, "storage_get_latest_success");
+
+    clock.advance(chrono::TimeDelta::days(1));
+    let ret = get_rates(&storage, &clock, Currency::USD, Some(date))
+        .await
+        .unwrap();
+    assert_eq!(ret.source, "storage_get_historical_success");
+}
+
+#[tokio::test]
+async fn test_get_rates_rebased_historical() {
+    let storage = super::mock::ForexStorageSuccessMock;
+    let date = Utc.with_ymd_and_hms(2022, 12, 25, 0, 0, 0).unwrap();
+    let clock = global::MockClock::new(date + chrono::TimeDelta::days(10));
+
+    let ret = get_rates(&storage, &clock, Currency::EUR, Some(date))
+        .await
+        .unwrap();
+    assert_eq!(ret.source, "storage_get_historical_success");
+    assert_eq!(ret.data.base, Currency::EUR);
+    assert_eq!(ret.data.rates.eur, dec!(1));
+}
+
+#[tokio::test]
+async fn test_rate_on() {
+    let storage = super::mock::ForexStorageSuccessMock;
+
+    // monday after rates of sunday
+    let date = Utc.with_ymd_and_hms(2022, 12, 26, 10, 0, 0).unwrap();
+    let ret = rate_on(&storage, date, Currency::USD, Currency::IDR)
+        .await
+        .unwrap();
+    assert_eq!(ret, dec!(15588.665563));
+
+    // tuesday after rates of monday, rebased into IDR
+    let date = Utc.with_ymd_and_hms(2021, 12, 21, 0, 0, 0).unwrap();
+    let ret = rate_on(&storage, date, Currency::IDR, Currency::USD)
+        .await
+        .unwrap();
+    assert_eq!(ret, dec!(1) / dec!(14388.75));
+
+    // rates of monday are stale on friday
+    let date = Utc.with_ymd_and_hms(2021, 12, 24, 0, 0, 0).unwrap();
+    let ret = rate_on(&storage, date, Currency::USD, Currency::IDR).await;
+    assert!(ret.is_err());
+
+    // on and after latest rates
+    let date = Utc.with_ymd_and_hms(2025, 3, 4, 12, 0, 0).unwrap();
+    let ret = rate_on(&storage, date, Currency::USD, Currency::IDR)
+        .await
+        .unwrap();
+    assert_eq!(ret, dec!(16461));
+    let date = Utc.with_ymd_and_hms(2025, 3, 20, 0, 0, 0).unwrap();
+    let ret = rate_on(&storage, date, Currency::USD, Currency::IDR).await;
+    assert!(ret.is_err());
+}
+
+#[tokio::test]
+async fn test_convert_on_dates() {
+    let storage = super::mock::ForexStorageSuccessMock;
+    let clock = global::MockClock::new(Utc.with_ymd_and_hms(2025, 3, 4, 13, 0, 0).unwrap());
+    let from = Money::new_money(Currency::USD, dec!(10));
+
+    // monday after rates of sunday, and on latest rates
+    let dates = vec![
+        Utc.with_ymd_and_hms(2022, 12, 26, 10, 0, 0).unwrap(),
+        Utc.with_ymd_and_hms(2025, 3, 4, 12, 0, 0).unwrap(),
+    ];
+    let ret = convert_on_dates(&storage, &clock, from, Currency::IDR, &dates)
+        .await
+        .unwrap();
+    assert_eq!(ret.len(), 2);
+    assert_eq!(ret[0].from, from);
+    assert_eq!(ret[0].to, Money::IDR(dec!(155886.65563)));
+    assert_eq!(
+        ret[0].date.date_naive(),
+        dates[0].date_naive().pred_opt().unwrap()
+    );
+    assert_eq!(ret[1].to, Money::IDR(dec!(164610)));
+
+    // rates of monday are stale on friday
+    let dates = vec![Utc.with_ymd_and_hms(2021, 12, 24, 0, 0, 0).unwrap()];
+    let ret = convert_on_dates(&storage, &clock, from, Currency::IDR, &dates).await;
+    assert!(ret.is_err());
+
+    let ret = convert_on_dates(&storage, &clock, from, Currency::IDR, &[]).await;
+    assert!(ret.is_err());
+}
+
+#[tokio::test]
+async fn test_latest_delta() {
+    let storage = super::mock::ForexStorageSuccessMock;
+    let since = uuid::Uuid::new_v4();
+
+    let ret = latest_delta(&storage, since).await.unwrap();
+    assert_eq!(ret.since, since);
+    assert_eq!(ret.base, Currency::USD);
+    let idr = ret
+        .changes
+        .iter()
+        .find(|change| change.currency == Currency::IDR)
+        .unwrap();
+    assert_eq!(idr.new, dec!(16461));
+    assert_ne!(idr.old, idr.new);
+    assert!(ret.changes.iter().all(|change| change.old != change.new));
+    assert!(
+        ret.changes
+            .iter()
+            .all(|change| change.currency != Currency::USD)
+    );
+
+    // cleared snapshot
+    let ret = latest_delta(&storage, uuid::Uuid::nil()).await;
+    assert!(matches!(ret, Err(ForexError::NotFoundError(_))));
+}
+
+#[tokio::test]
+async fn test_rates_sources() {
+    let storage = super::mock::ForexStorageSuccessMock;
+    let clock = global::MockClock::new(Utc.with_ymd_and_hms(2025, 3, 4, 3, 0, 0).unwrap());
+
+    let ret = rates_sources(&storage, &clock, None).await.unwrap();
+    assert_eq!(ret.kind, RatesKind::Latest);
+    assert_eq!(ret.source, "storage_get_latest_success");
+    assert!(ret.provenance.is_none());
+
+    // today is latest
+    let today = Utc.with_ymd_and_hms(2025, 3, 4, 0, 0, 0).unwrap();
+    let ret = rates_sources(&storage, &clock, Some(today)).await.unwrap();
+    assert_eq!(ret.kind, RatesKind::Latest);
+
+    let date = Utc.with_ymd_and_hms(2022, 12, 25, 0, 0, 0).unwrap();
+    let ret = rates_sources(&storage, &clock, Some(date)).await.unwrap();
+    assert_eq!(ret.kind, RatesKind::Historical);
+    assert_eq!(ret.source, "storage_get_historical_success");
+    let provenance = ret.provenance.unwrap();
+    assert_eq!(provenance.source, "storage_get_historical_success");
+    assert!(provenance.is_modified());
+}
+
+#[tokio::test]
+async fn test_rates_at() {
+    let storage = super::mock::ForexStorageSuccessMock;
+
+    // at and after current latest rates of 2025-03-04T02:00:00Z
+    let instant = Utc.with_ymd_and_hms(2025, 3, 4, 5, 0, 0).unwrap();
+    let ret = rates_at(&storage, instant).await.unwrap();
+    assert_eq!(ret.source, "storage_get_latest_success");
+
+    // earlier latest snapshot of the same day
+    let instant = Utc.with_ymd_and_hms(2025, 3, 4, 1, 30, 0).unwrap();
+    let ret = rates_at(&storage, instant).await.unwrap();
+    assert_eq!(
+        ret.id,
+        uuid::Uuid::parse_str("10324ad3-1caa-4acc-9296-a7b34a6ad010").unwrap()
+    );
+    assert_eq!(
+        ret.data.date,
+        Utc.with_ymd_and_hms(2025, 3, 4, 1, 0, 0).unwrap()
+    );
+
+    // before the first snapshot of the day
+    let instant = Utc.with_ymd_and_hms(2025, 3, 4, 0, 30, 0).unwrap();
+    let ret = rates_at(&storage, instant).await.unwrap();
+    assert_eq!(ret.source, "storage_get_historical_success");
+
+    // past days
+    let instant = Utc.with_ymd_and_hms(2022, 12, 25, 12, 0, 0).unwrap();
+    let ret = rates_at(&storage, instant).await.unwrap();
+    assert_eq!(ret.source, "storage_get_historical_success");
+}
+
+#[tokio::test]
+async fn test_backfill_metals() {
+    use crate::forex::service::backfill_metals;
+    use crate::forex_impl::metals_dataset::MetalsDataset;
+
+    let (storage, _root) = temp_storage("backfill-metals");
+    let dataset = MetalsDataset::from_csv(
+        "date,xau,xag,xpt\n2010-01-04,1000,,\n2010-01-05,1250,,\n".as_bytes(),
+    )
+    .unwrap();
+    let stored_date = Utc.with_ymd_and_hms(2010, 1, 4, 0, 0, 0).unwrap();
+    let missing_date = Utc.with_ymd_and_hms(2010, 1, 5, 0, 0, 0).unwrap();
+    let stored = rates(stored_date, &[Money::IDR(dec!(9400))]);
+    storage
+        .insert_historical(stored_date, &stored)
+        .await
+        .unwrap();
+
+    let report = backfill_metals(&dataset, &storage, dataset.dates(), false)
+        .await
+        .unwrap();
+    assert_eq!(report.dates, vec![stored_date]);
+    // metal rates alone aren't stored
+    assert_eq!(report.skipped, vec![missing_date]);
+    let filled = storage.get_historical(stored_date).await.unwrap();
+    assert_eq!(filled.data.rates.xau, dec!(0.001));
+    assert_eq!(filled.data.rates.idr, dec!(9400));
+    assert!(storage.get_historical(missing_date).await.is_err());
+}
+
+/// storage of latest files sizes, the last one is current latest and the rest are archived.
+struct QuotaStorage {
+    latest: std::sync::Mutex<Vec<u64>>,
+}
+
+#[async_trait::async_trait]
+impl ForexStorageStats for QuotaStorage {
+    async fn stats(&self) -> ForexResult<StorageStats> {
+        let mut latest = StorageSubtreeStats::new("latest");
+        for bytes in self.latest.lock().unwrap().iter() {
+            latest.add_file(*bytes, None);
+        }
+        // journal and signatures, never pruned
+        let mut metadata = StorageSubtreeStats::new("metadata");
+        metadata.add_file(8 * 1024 * 1024, None);
+
+        Ok(StorageStats::new(vec![latest, metadata]))
+    }
+}
+
+#[async_trait::async_trait]
+impl ForexStorageDeletion for QuotaStorage {
+    async fn clear_latest(&self) -> ForexResult<()> {
+        Ok(())
+    }
+
+    async fn prune_latest_archive(&self, bytes: u64) -> ForexResult<Vec<String>> {
+        let mut latest = self.latest.lock().unwrap();
+        let mut freed = 0;
+        let mut pruned = vec![];
+        while freed < bytes && latest.len() > 1 {
+            freed += latest.remove(0);
+            pruned.push(format!("archived-{}", pruned.len()));
+        }
+
+        Ok(pruned)
+    }
+}
+
+#[tokio::test]
+async fn test_enforce_disk_quota() {
+    const MB: u64 = 1024 * 1024;
+    let storage = QuotaStorage {
+        latest: std::sync::Mutex::new(vec![MB, MB, MB, MB]),
+    };
+
+    // unlimited
+    assert!(enforce_disk_quota(&storage, 0).await.unwrap().is_none());
+    // within limit
+    assert!(enforce_disk_quota(&storage, 4).await.unwrap().is_none());
+
+    let report = enforce_disk_quota(&storage, 2).await.unwrap().unwrap();
+    assert_eq!(report.bytes_before, 4 * MB);
+    assert_eq!(report.bytes_after, 2 * MB);
+    assert_eq!(report.pruned.len(), 2);
+    assert!(!report.is_exceeded());
+
+    // current latest alone is above the limit
+    let storage = QuotaStorage {
+        latest: std::sync::Mutex::new(vec![MB, 3 * MB]),
+    };
+    let report = enforce_disk_quota(&storage, 2).await.unwrap().unwrap();
+    assert_eq!(report.pruned.len(), 1);
+    assert!(report.is_exceeded());
+}
+
+#[tokio::test]
+async fn test_poll_crypto_rates() {
+    use crate::forex::{interface::ForexStorageJobRuns, service::poll_crypto_rates};
+
+    let (storage, _root) = temp_storage("poll-crypto");
+    let crypto = super::mock::ForexApiSuccessMock;
+    let cancel = CancellationToken::new();
+    let polled_at = Utc.with_ymd_and_hms(2024, 1, 6, 10, 0, 0).unwrap();
+    let clock = global::MockClock::new(polled_at + chrono::TimeDelta::minutes(5));
+
+    // nothing to merge into
+    assert!(
+        poll_crypto_rates(&crypto, &storage, &clock, JobTrigger::Cron, &cancel)
+            .await
+            .is_err()
+    );
+
+    let latest = rates(
+        polled_at,
+        &[
+            Money::IDR(dec!(15500)),
+            Money::BTC(dec!(0.12)),
+            Money::SOL(dec!(100)),
+        ],
+    );
+    storage.insert_latest(polled_at, &latest).await.unwrap();
+
+    // 1 coin is 10 USD
+    let ret = poll_crypto_rates(&crypto, &storage, &clock, JobTrigger::Cron, &cancel)
+        .await
+        .unwrap();
+    assert_ne!(ret.id, latest.id);
+    assert_eq!(ret.data.date, polled_at);
+    assert_eq!(ret.data.rates.rate(Currency::IDR), dec!(15500));
+    assert_eq!(ret.data.rates.rate(Currency::BTC), dec!(0.1));
+    assert_eq!(ret.data.rates.rate(Currency::ETH), dec!(0.1));
+    // jumped too far from current rate
+    assert_eq!(ret.data.rates.rate(Currency::SOL), dec!(100));
+    assert!(
+        ret.provenance
+            .iter()
+            .any(|p| p.currency == Currency::BTC && p.source == "crypto_latest_success")
+    );
+    assert!(!ret.provenance.iter().any(|p| p.currency == Currency::SOL));
+
+    // stored as latest snapshot, fiat rates untouched
+    let stored = storage.get_latest().await.unwrap();
+    assert_eq!(stored.id, ret.id);
+    assert_eq!(stored.data.rates.rate(Currency::BTC), dec!(0.1));
+
+    let runs = storage
+        .get_job_runs(Some(Job::PollCryptoRates), 10)
+        .await
+        .unwrap();
+    assert_eq!(runs.len(), 2);
+    assert!(
+        runs.iter()
+            .any(|run| run.success && run.items_processed > 0)
+    );
+}
+
+/// crypto prices fetched while fiat rates are polled, storing them as latest once.
+struct PolledMeanwhile<FS> {
+    storage: FS,
+    polled:
+        std::sync::Mutex<Option<crate::forex::entity::RatesResponse<crate::forex::entity::Rates>>>,
+}
+
+#[async_trait::async_trait]
+impl<FS: ForexStorage + Send + Sync> crate::forex::interface::ForexCryptoPrices
+    for PolledMeanwhile<FS>
+{
+    async fn daily_close(
+        &self,
+        date: DateTime<Utc>,
+        currencies: &[Currency],
+    ) -> ForexResult<Vec<crate::forex::entity::CryptoPrice>> {
+        super::mock::ForexApiSuccessMock
+            .daily_close(date, currencies)
+            .await
+    }
+
+    async fn latest(
+        &self,
+        currencies: &[Currency],
+    ) -> ForexResult<Vec<crate::forex::entity::CryptoPrice>> {
+        let polled = self.polled.lock().unwrap().take();
+        if let Some(polled) = polled {
+            self.storage
+                .insert_latest(polled.data.date, &polled)
+                .await?;
+        }
+        super::mock::ForexApiSuccessMock.latest(currencies).await
+    }
+}
+
+#[tokio::test]
+async fn test_poll_crypto_rates_keeps_rates_polled_meanwhile() {
+    use crate::forex::service::poll_crypto_rates;
+
+    let (storage, _root) = temp_storage("poll-crypto-race");
+    let polled_at = Utc.with_ymd_and_hms(2024, 1, 6, 10, 0, 0).unwrap();
+    let clock = global::MockClock::new(polled_at + chrono::TimeDelta::minutes(5));
+    let latest = |idr, date| rates(date, &[Money::IDR(idr), Money::BTC(dec!(0.12))]);
+    storage
+        .insert_latest(polled_at, &latest(dec!(15500), polled_at))
+        .await
+        .unwrap();
+    let repolled_at = polled_at + chrono::TimeDelta::minutes(1);
+    let crypto = PolledMeanwhile {
+        storage: storage.clone(),
+        polled: std::sync::Mutex::new(Some(latest(dec!(16000), repolled_at))),
+    };
+
+    let ret = poll_crypto_rates(
+        &crypto,
+        &storage,
+        &clock,
+        JobTrigger::Cron,
+        &CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+    // merged into rates polled meanwhile instead of overwriting them with stale ones
+    assert_eq!(ret.data.date, repolled_at);
+    assert_eq!(ret.data.rates.rate(Currency::IDR), dec!(16000));
+    assert_eq!(ret.data.rates.rate(Currency::BTC), dec!(0.1));
+    assert_eq!(storage.get_latest().await.unwrap().id, ret.id);
+}
+
+#[tokio::test]
+async fn test_lock_job_expired_taken_over_once() {
+    use crate::forex::service::lock_job;
+    use crate::forex_impl::forex_storage::ForexStorageImpl;
+    use crate::global::test_support::TempRoot;
+
+    let root = TempRoot::new("lock-job");
+    // instances sharing the storage don't share its in-process lock
+    let instance = || ForexStorageImpl::new(global::storage_fs_at(root.to_path_buf()).unwrap());
+    let job = Job::PollLatestRates;
+
+    assert!(lock_job(&instance(), job, "expired", chrono::TimeDelta::zero()).await);
+    let mut tasks = tokio::task::JoinSet::new();
+    for i in 0..16 {
+        let storage = instance();
+        tasks.spawn(async move {
+            lock_job(
+                &storage,
+                job,
+                &format!("holder-{}", i),
+                chrono::TimeDelta::hours(1),
+            )
+            .await
+        });
+    }
+    let locked = tasks.join_all().await;
+    assert_eq!(locked.into_iter().filter(|locked| *locked).count(), 1);
+    assert!(!lock_job(&instance(), job, "late", chrono::TimeDelta::hours(1)).await);
+}
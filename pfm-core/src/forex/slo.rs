@@ -0,0 +1,170 @@
+// slo.rs objectives of rates freshness per deployment, evaluated periodically by pfm-cron.
+// every evaluation is stored, so pfm-http exports compliance over a period, and breaches are alerted once when they start.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::global::{self, Clock};
+
+use super::{
+    analytics,
+    entity::{FreshnessSlo, SloCompliance, SloReport},
+    interface::{ForexResult, ForexSloAlerts, ForexStorage, ForexStorageSlo},
+    market_hours::MarketCalendar,
+};
+
+/// days evaluations are kept, the longest period compliance is computed over.
+pub const SLO_RETENTION_DAYS: u32 = 90;
+
+/// most evaluations read when computing compliance, evaluations of the retention every 10 minutes.
+pub const SLO_COMPLIANCE_MAX_REPORTS: u32 = SLO_RETENTION_DAYS * 24 * 6;
+
+/// evaluate freshness SLO now and store the evaluation, alerting breach when it starts if alerts are given.
+/// latest rates aren't expected to be fresh while markets of all enabled currencies are closed, if calendar is given.
+/// historical rates are expected up to yesterday, as rates of today are polled tomorrow.
+#[instrument(skip(storage, clock, alerts), ret)]
+pub async fn evaluate<FS, A>(
+    storage: &FS,
+    clock: &impl Clock,
+    slo: &FreshnessSlo,
+    calendar: Option<&MarketCalendar>,
+    alerts: Option<&A>,
+) -> ForexResult<SloReport>
+where
+    FS: ForexStorage + ForexStorageSlo,
+    A: ForexSloAlerts,
+{
+    let now = clock.now();
+    let enabled = global::config().enabled_currencies();
+
+    let latest_age_seconds = match storage.get_latest().await {
+        Ok(latest) if latest.error.is_none() => Some((now - latest.data.date).num_seconds().max(0)),
+        Ok(_) => None,
+        Err(err) => {
+            tracing::warn!("slo failed reading latest rates: {}", err);
+            None
+        }
+    };
+    let markets_closed = calendar.is_some_and(|calendar| !calendar.any_open(enabled, now));
+    let latest_met = markets_closed
+        || latest_age_seconds.is_some_and(|age| age as u64 <= slo.latest_max_age_secs);
+
+    let (historical_percent, incomplete) = match slo.historical_days {
+        0 => (dec!(100), vec![]),
+        days => {
+            let report = analytics::completeness(
+                storage,
+                now - TimeDelta::days(days as i64),
+                now - TimeDelta::days(1),
+            )
+            .await?;
+            let percent = report
+                .currencies
+                .iter()
+                .map(|completeness| completeness.percent)
+                .min()
+                .unwrap_or(dec!(100));
+            let incomplete = report
+                .currencies
+                .iter()
+                .filter(|completeness| completeness.percent < slo.historical_min_percent)
+                .map(|completeness| completeness.currency)
+                .collect();
+            (percent, incomplete)
+        }
+    };
+    let historical_met = historical_percent >= slo.historical_min_percent;
+
+    // breach whose alert failed is started again, so alerting it is retried
+    let previous_met = storage
+        .get_slo_reports(DateTime::<Utc>::MIN_UTC, 1)
+        .await?
+        .first()
+        .is_none_or(|previous| previous.is_met() || previous.breach_unalerted);
+    let mut report = SloReport {
+        id: Uuid::new_v4(),
+        evaluated_at: now,
+        slo: *slo,
+        latest_age_seconds,
+        latest_met,
+        historical_percent,
+        incomplete,
+        historical_met,
+        breach_started: false,
+        breach_unalerted: false,
+    };
+    report.breach_started = !report.is_met() && previous_met;
+    if !report.is_met() {
+        tracing::warn!(
+            "slo breached, latest rates age {:?} seconds, lowest historical completeness {}%",
+            report.latest_age_seconds,
+            report.historical_percent
+        );
+    }
+    if report.breach_started
+        && let Some(alerts) = alerts
+    {
+        report.breach_unalerted = !alert_breach(alerts, &report).await;
+    }
+    storage.insert_slo_report(&report).await?;
+
+    Ok(report)
+}
+
+/// alert breach to integrations, returning whether it was delivered.
+async fn alert_breach<A>(alerts: &A, report: &SloReport) -> bool
+where
+    A: ForexSloAlerts,
+{
+    match alerts.alert_slo_breach(report).await {
+        Ok(()) => true,
+        Err(err) => {
+            tracing::error!("failed alerting slo breach {}: {}", report.id, err);
+            false
+        }
+    }
+}
+
+/// share of evaluations since the date meeting each objective.
+/// since is moved to the oldest evaluation read when there are more evaluations than SLO_COMPLIANCE_MAX_REPORTS.
+pub async fn compliance<FS>(storage: &FS, since: DateTime<Utc>) -> ForexResult<SloCompliance>
+where
+    FS: ForexStorageSlo,
+{
+    let reports = storage
+        .get_slo_reports(since, SLO_COMPLIANCE_MAX_REPORTS)
+        .await?;
+    let since = match reports.last() {
+        Some(oldest) if reports.len() as u32 == SLO_COMPLIANCE_MAX_REPORTS => {
+            tracing::warn!(
+                "slo compliance read at most {} evaluations, since {} instead of {}",
+                SLO_COMPLIANCE_MAX_REPORTS,
+                oldest.evaluated_at,
+                since
+            );
+            oldest.evaluated_at
+        }
+        _ => since,
+    };
+    let ratio = |met: usize| match reports.len() {
+        0 => Decimal::ONE,
+        total => (Decimal::from(met) / Decimal::from(total)).round_dp(4),
+    };
+
+    Ok(SloCompliance {
+        since,
+        evaluations: reports.len() as u32,
+        latest: ratio(reports.iter().filter(|report| report.latest_met).count()),
+        historical: ratio(
+            reports
+                .iter()
+                .filter(|report| report.historical_met)
+                .count(),
+        ),
+        overall: ratio(reports.iter().filter(|report| report.is_met()).count()),
+        last: reports.into_iter().next(),
+    })
+}
@@ -0,0 +1,186 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use async_trait::async_trait;
+use chrono::{TimeDelta, TimeZone, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use strum::IntoEnumIterator;
+use uuid::Uuid;
+
+use crate::forex::{
+    Currency, CurrencyKind, ForexError, ForexResult, Money,
+    entity::{FreshnessSlo, SloReport},
+    interface::{ForexSloAlerts, ForexStorage},
+    market_hours::{MarketCalendar, MarketHours},
+    slo,
+};
+use crate::global::{
+    self, Clock,
+    test_support::{rates, temp_storage},
+};
+
+/// rate of 1 of every currency.
+fn every_rate() -> Vec<Money> {
+    Currency::iter()
+        .map(|currency| Money::new_money(currency, Decimal::ONE))
+        .collect()
+}
+
+/// alerts recording delivered breaches, failing while fail is set.
+#[derive(Default)]
+struct RecordingAlerts(Mutex<Vec<Uuid>>, AtomicBool);
+
+#[async_trait]
+impl ForexSloAlerts for RecordingAlerts {
+    async fn alert_slo_breach(&self, report: &SloReport) -> ForexResult<()> {
+        if self.1.load(Ordering::SeqCst) {
+            return Err(ForexError::internal_error("webhook unreachable"));
+        }
+        self.0.lock().unwrap().push(report.id);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_evaluate_latest_freshness() {
+    let (storage, _root) = temp_storage("slo-latest");
+    let polled_at = Utc.with_ymd_and_hms(2024, 1, 10, 10, 0, 0).unwrap();
+    storage
+        .insert_latest(polled_at, &rates(polled_at, &every_rate()))
+        .await
+        .unwrap();
+    let slo = FreshnessSlo {
+        latest_max_age_secs: 2 * 3600,
+        historical_days: 0,
+        historical_min_percent: dec!(100),
+    };
+    let clock = global::MockClock::new(polled_at + TimeDelta::hours(1));
+    let alerts = RecordingAlerts::default();
+
+    let report = slo::evaluate(&storage, &clock, &slo, None, Some(&alerts))
+        .await
+        .unwrap();
+    assert_eq!(report.latest_age_seconds, Some(3600));
+    assert!(report.is_met());
+
+    // breach is alerted once when it starts
+    clock.advance(TimeDelta::hours(2));
+    let breached = slo::evaluate(&storage, &clock, &slo, None, Some(&alerts))
+        .await
+        .unwrap();
+    assert!(!breached.latest_met);
+    assert!(breached.breach_started);
+    assert!(!breached.breach_unalerted);
+    clock.advance(TimeDelta::minutes(10));
+    let report = slo::evaluate(&storage, &clock, &slo, None, Some(&alerts))
+        .await
+        .unwrap();
+    assert!(!report.is_met());
+    assert!(!report.breach_started);
+    assert_eq!(*alerts.0.lock().unwrap(), vec![breached.id]);
+
+    // stale latest rates are expected while markets are closed
+    clock.advance(TimeDelta::minutes(10));
+    let closed = MarketCalendar::default()
+        .with_hours(CurrencyKind::Fiat, MarketHours::Never)
+        .with_hours(CurrencyKind::Metal, MarketHours::Never)
+        .with_hours(CurrencyKind::Crypto, MarketHours::Never);
+    let report = slo::evaluate(&storage, &clock, &slo, Some(&closed), Some(&alerts))
+        .await
+        .unwrap();
+    assert!(report.latest_met);
+
+    let compliance = slo::compliance(&storage, polled_at).await.unwrap();
+    assert_eq!(compliance.evaluations, 4);
+    assert_eq!(compliance.latest, dec!(0.5));
+    assert_eq!(compliance.historical, Decimal::ONE);
+    assert_eq!(compliance.last.unwrap().id, report.id);
+
+    let compliance = slo::compliance(&storage, clock.now() + TimeDelta::seconds(1))
+        .await
+        .unwrap();
+    assert_eq!(compliance.evaluations, 0);
+    assert_eq!(compliance.overall, Decimal::ONE);
+    assert!(compliance.last.is_none());
+}
+
+#[tokio::test]
+async fn test_evaluate_historical_completeness() {
+    let (storage, _root) = temp_storage("slo-historical");
+    let now = Utc.with_ymd_and_hms(2024, 1, 10, 10, 0, 0).unwrap();
+    storage
+        .insert_latest(now, &rates(now, &every_rate()))
+        .await
+        .unwrap();
+    // yesterday is polled, the day before isn't
+    let yesterday = Utc.with_ymd_and_hms(2024, 1, 9, 0, 0, 0).unwrap();
+    storage
+        .insert_historical(yesterday, &rates(yesterday, &every_rate()))
+        .await
+        .unwrap();
+    let clock = global::MockClock::new(now);
+    let mut slo = FreshnessSlo {
+        latest_max_age_secs: 3600,
+        historical_days: 2,
+        historical_min_percent: dec!(100),
+    };
+
+    let report = slo::evaluate(&storage, &clock, &slo, None, None::<&RecordingAlerts>)
+        .await
+        .unwrap();
+    assert!(report.latest_met);
+    assert_eq!(report.historical_percent, dec!(50));
+    assert!(!report.historical_met);
+    assert_eq!(
+        report.incomplete,
+        global::config().enabled_currencies().to_vec()
+    );
+
+    slo.historical_min_percent = dec!(50);
+    let report = slo::evaluate(&storage, &clock, &slo, None, None::<&RecordingAlerts>)
+        .await
+        .unwrap();
+    assert!(report.is_met());
+    assert!(report.incomplete.is_empty());
+}
+
+#[tokio::test]
+async fn test_evaluate_retries_failed_alert() {
+    let (storage, _root) = temp_storage("slo-retry-alert");
+    let polled_at = Utc.with_ymd_and_hms(2024, 1, 10, 10, 0, 0).unwrap();
+    storage
+        .insert_latest(polled_at, &rates(polled_at, &every_rate()))
+        .await
+        .unwrap();
+    let slo = FreshnessSlo {
+        latest_max_age_secs: 3600,
+        historical_days: 0,
+        historical_min_percent: dec!(100),
+    };
+    let clock = global::MockClock::new(polled_at + TimeDelta::hours(2));
+    let alerts = RecordingAlerts::default();
+    alerts.1.store(true, Ordering::SeqCst);
+
+    let failed = slo::evaluate(&storage, &clock, &slo, None, Some(&alerts))
+        .await
+        .unwrap();
+    assert!(failed.breach_started);
+    assert!(failed.breach_unalerted);
+    assert!(alerts.0.lock().unwrap().is_empty());
+
+    // breach is started again until its alert is delivered
+    alerts.1.store(false, Ordering::SeqCst);
+    clock.advance(TimeDelta::minutes(10));
+    let alerted = slo::evaluate(&storage, &clock, &slo, None, Some(&alerts))
+        .await
+        .unwrap();
+    assert!(alerted.breach_started);
+    assert!(!alerted.breach_unalerted);
+    clock.advance(TimeDelta::minutes(10));
+    let report = slo::evaluate(&storage, &clock, &slo, None, Some(&alerts))
+        .await
+        .unwrap();
+    assert!(!report.breach_started);
+    assert_eq!(*alerts.0.lock().unwrap(), vec![alerted.id]);
+}
@@ -0,0 +1,100 @@
+// crypto_dataset.rs parse daily crypto prices exported from coinmarketcap, `;` separated csv with timeOpen and close columns.
+// parsed prices are ingested into historical rates with service::ingest_crypto_history.
+
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::Context;
+use chrono::{DateTime, NaiveTime, Utc};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use crate::error::{AsInternalError, BaseError};
+use crate::forex::{
+    Currency, ForexError, ForexResult,
+    entity::{CryptoPrice, PriceUnit},
+};
+
+/// source of parsed prices, recorded in provenance of rates they patch.
+pub const SOURCE: &str = "coinmarketcap.com csv";
+
+#[derive(Debug, Deserialize)]
+struct CryptoRecord {
+    #[serde(rename = "timeOpen")]
+    time_open: DateTime<Utc>,
+    close: Decimal,
+}
+
+/// parse daily prices of the currency, quoted in the unit, into USD based rates dated on start of their day.
+/// rows not opening at midnight UTC are rejected, since they can't be aligned with historical rates dates.
+pub fn from_csv(
+    currency: Currency,
+    reader: impl Read,
+    unit: PriceUnit,
+) -> ForexResult<Vec<CryptoPrice>> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(b';')
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+
+    let mut prices = vec![];
+    for (i, result) in rdr.deserialize::<CryptoRecord>().enumerate() {
+        let record = result.map_err(|err| {
+            ForexError::client_error(format!("crypto dataset row {}: {}", i + 1, err).as_str())
+        })?;
+        if record.time_open.time() != NaiveTime::MIN {
+            return Err(ForexError::client_error(
+                format!(
+                    "crypto dataset row {}: {} is not aligned to start of day",
+                    i + 1,
+                    record.time_open
+                )
+                .as_str(),
+            ));
+        }
+        let rate = unit.to_rate(record.close).ok_or_else(|| {
+            ForexError::client_error(
+                format!("crypto dataset row {}: price is not positive", i + 1).as_str(),
+            )
+        })?;
+        prices.push(CryptoPrice {
+            currency,
+            date: record.time_open,
+            rate,
+            source: SOURCE.to_string(),
+        });
+    }
+
+    Ok(prices)
+}
+
+/// parse all csv files inside the directory.
+pub fn from_dir(
+    currency: Currency,
+    dir: impl AsRef<Path>,
+    unit: PriceUnit,
+) -> ForexResult<Vec<CryptoPrice>> {
+    let mut prices = vec![];
+    let entries = std::fs::read_dir(dir)
+        .context("crypto dataset reading dir")
+        .as_internal_err()?;
+    for entry in entries {
+        let path = entry
+            .context("crypto dataset reading dir entry")
+            .as_internal_err()?
+            .path();
+        if !path.is_file() || path.extension().is_none_or(|ext| ext != "csv") {
+            continue;
+        }
+        let file = std::fs::File::open(&path)
+            .context("crypto dataset opening file")
+            .as_internal_err()?;
+        let parsed = from_csv(currency, std::io::BufReader::new(file), unit).map_err(|err| {
+            ForexError::client_error(format!("{}: {}", path.display(), err.detail()).as_str())
+        })?;
+        prices.extend(parsed);
+    }
+    prices.sort_by_key(|price| price.date);
+
+    Ok(prices)
+}
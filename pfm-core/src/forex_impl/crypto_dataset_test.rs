@@ -0,0 +1,39 @@
+use chrono::{TimeZone, Utc};
+use rust_decimal_macros::dec;
+
+use crate::forex::{Currency, entity::PriceUnit};
+
+use super::crypto_dataset::from_csv;
+
+#[test]
+fn test_crypto_dataset_from_csv() {
+    let csv = "timeOpen;timeClose;name;open;close
+2024-01-01T00:00:00.000Z;2024-01-01T23:59:59.999Z;Bitcoin;42000;50000
+2024-01-02T00:00:00.000Z;2024-01-02T23:59:59.999Z;Bitcoin;50000;40000
+";
+    let ret = from_csv(Currency::BTC, csv.as_bytes(), PriceUnit::UsdPerCoin).unwrap();
+    assert_eq!(ret.len(), 2);
+    assert_eq!(ret[0].currency, Currency::BTC);
+    assert_eq!(
+        ret[0].date,
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+    );
+    assert_eq!(ret[0].rate, dec!(0.00002));
+    assert_eq!(ret[1].rate, dec!(0.000025));
+
+    let ret = from_csv(Currency::BTC, csv.as_bytes(), PriceUnit::CoinPerUsd).unwrap();
+    assert_eq!(ret[0].rate, dec!(50000));
+}
+
+#[test]
+fn test_crypto_dataset_unaligned() {
+    let csv = "timeOpen;close
+2024-01-01T12:00:00.000Z;50000
+";
+    assert!(from_csv(Currency::BTC, csv.as_bytes(), PriceUnit::UsdPerCoin).is_err());
+
+    let csv = "timeOpen;close
+2024-01-01T00:00:00.000Z;0
+";
+    assert!(from_csv(Currency::BTC, csv.as_bytes(), PriceUnit::UsdPerCoin).is_err());
+}
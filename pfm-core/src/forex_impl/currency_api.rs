@@ -7,23 +7,37 @@
 // On this page, we’ll dive into the historical exchange rates endpoint you can use to retrieve historical exchangen rates for a specific date. Data are available all the way back to 1999.
 // gold price start exist on 2014-01-01
 
-use anyhow::{anyhow, Context};
+use anyhow::{Context, anyhow};
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeZone, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 use crate::error::AsInternalError;
-use crate::forex::entity::RatesData;
-use crate::forex::interface::ForexHistoricalRates;
 use crate::forex::ForexResult;
+use crate::forex::entity::RatesData;
+use crate::forex::interface::{ForexHistoricalRates, ForexProviderCapabilities};
 use crate::forex::{
-    entity::{Rates, RatesResponse},
     Currency, ForexError,
+    entity::{ProviderCapabilities, RateLimit, Rates, RatesResponse},
 };
+use crate::global::Secret;
 
 const SOURCE: &str = "currencyapi.com";
 
+/// name of this provider in ProviderRegistry
+pub const NAME: &str = "currencyapi";
+
+/// all currencies, historical rates since 1999, called 9 times per minute to stay below its limit.
+fn capabilities() -> ProviderCapabilities {
+    ProviderCapabilities::default()
+        .with_history_start(Utc.with_ymd_and_hms(1999, 1, 1, 0, 0, 0).unwrap())
+        .with_rate_limit(RateLimit {
+            calls: 9,
+            window_secs: 62,
+        })
+}
+
 const HISTORICAL_ENDPOINT: &str = "https://api.currencyapi.com/v3/historical";
 
 const ERROR_PREFIX: &str = "[FOREX][currencyapi.com]";
@@ -47,12 +61,12 @@ pub struct StatusResponse {
 
 #[derive(Clone)]
 pub struct Api {
-    key: &'static str,
+    key: Secret,
     client: reqwest::Client,
 }
 
 impl Api {
-    pub fn new(api_key: &'static str, client: reqwest::Client) -> Self {
+    pub fn new(api_key: Secret, client: reqwest::Client) -> Self {
         Self {
             key: api_key,
             client,
@@ -61,7 +75,8 @@ impl Api {
 
     pub async fn status(&self) -> ForexResult<StatusResponse> {
         let endpoint = "https://api.currencyapi.com/v3/status";
-        let params = [("apikey", self.key)];
+        let key = self.key.expose();
+        let params = [("apikey", key.as_str())];
 
         let status: StatusResponse = self
             .client
@@ -255,10 +270,12 @@ impl ForexHistoricalRates for Api {
     ) -> ForexResult<RatesResponse<Rates>> {
         let yyyymmdd = date.format("%Y-%m-%d").to_string();
 
-        let currencies = Currency::to_comma_separated_list_str();
+        let currencies =
+            Currency::to_comma_separated_list_str_of(capabilities().requested_symbols());
 
+        let key = self.key.expose();
         let params = [
-            ("apikey", self.key),
+            ("apikey", key.as_str()),
             ("base_currency", base.code()),
             ("date", yyyymmdd.as_str()),
             ("currencies", &currencies),
@@ -295,3 +312,14 @@ impl ForexHistoricalRates for Api {
         Ok(resp.try_into()?)
     }
 }
+
+#[async_trait]
+impl ForexProviderCapabilities for Api {
+    fn capabilities(&self) -> ProviderCapabilities {
+        capabilities()
+    }
+
+    async fn quota_remaining(&self) -> ForexResult<Option<u32>> {
+        Ok(Some(self.status().await?.quotas.month.remaining))
+    }
+}
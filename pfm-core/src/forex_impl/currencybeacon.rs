@@ -2,17 +2,19 @@ use std::{collections::HashMap, str::FromStr};
 
 use anyhow::{Context, anyhow};
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
 use rust_decimal_macros::dec;
 
 use crate::error::AsInternalError;
 use crate::{
     forex::{
         Currency, ForexError, ForexResult,
-        entity::{Rates, RatesData, RatesResponse},
-        interface::{ForexHistoricalRates, ForexRates, ForexTimeseriesRates},
+        entity::{ProviderCapabilities, RateLimit, Rates, RatesData, RatesResponse},
+        interface::{
+            ForexHistoricalRates, ForexProviderCapabilities, ForexRates, ForexTimeseriesRates,
+        },
     },
-    global::{self},
+    global::{self, Secret},
 };
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -32,16 +34,32 @@ const LATEST_ENDPOINT: &str = "https://api.currencybeacon.com/v1/latest";
 const HISTORICAL_ENDPOINT: &str = "https://api.currencybeacon.com/v1/historical";
 const TIMESERIES_ENDPOINT: &str = "https://api.currencybeacon.com/v1/timeseries";
 const SOURCE: &str = "currencybeacon.com";
+
+/// name of this provider in ProviderRegistry
+pub const NAME: &str = "currencybeacon";
+
+/// all currencies, historical rates since 1996 in timeseries of at most 7 years.
+/// its usage isn't reported, so whole 5,000 requests of the plan are assumed left.
+fn capabilities() -> ProviderCapabilities {
+    ProviderCapabilities::default()
+        .with_history_start(Utc.with_ymd_and_hms(1996, 1, 1, 0, 0, 0).unwrap())
+        .with_max_timeseries_span(TimeDelta::days(7 * 365))
+        .with_rate_limit(RateLimit {
+            calls: 5,
+            window_secs: 5,
+        })
+        .with_quota(5000)
+}
 const END_OF_DAY_HOUR: &str = "T23:59:59Z";
 
 #[derive(Clone)]
 pub struct Api {
-    key: &'static str,
+    key: Secret,
     client: reqwest::Client,
 }
 
 impl Api {
-    pub fn new(key: &'static str, http_client: reqwest::Client) -> Self {
+    pub fn new(key: Secret, http_client: reqwest::Client) -> Self {
         Self {
             key,
             client: http_client,
@@ -53,7 +71,9 @@ impl Api {
     /// fetch from twelvedata.com /exchange_rate
     async fn latest_solana(&self, base: Currency) -> ForexResult<Decimal> {
         const TWELVEDATA_LATEST_ENDPOINT: &str = "https://api.twelvedata.com/exchange_rate";
-        let api_key = &global::config().forex_twelvedata_api_key;
+        let api_key = global::secrets()
+            .get(global::SECRET_FOREX_TWELVEDATA_API_KEY)
+            .expose();
         let symbol = format!("{}/SOL", base);
         let params = [("apikey", api_key.as_str()), ("symbol", &symbol)];
 
@@ -92,7 +112,9 @@ impl Api {
     /// fetch from twelvedata.com /time_series
     async fn historical_solana(&self, base: Currency, date: DateTime<Utc>) -> ForexResult<Decimal> {
         const TWELVEDATA_TIMESERIES_ENDPOINT: &str = "https://api.twelvedata.com/time_series";
-        let api_key = &global::config().forex_twelvedata_api_key;
+        let api_key = global::secrets()
+            .get(global::SECRET_FOREX_TWELVEDATA_API_KEY)
+            .expose();
         // symbol for time_series endpoint doesn't provide USD/BTC, use this instead and calculate from it.
         let symbol = format!("SOL/{}", base);
         let date = date.format("%Y-%m-%d").to_string();
@@ -171,7 +193,7 @@ mod api_tests {
     async fn test_solana_latest_price() {
         let base = Currency::USD;
         let api = super::Api {
-            key: &global::config().forex_twelvedata_api_key,
+            key: global::secrets().get(global::SECRET_FOREX_TWELVEDATA_API_KEY),
             client: global::http_client(),
         };
 
@@ -184,7 +206,7 @@ mod api_tests {
     async fn test_solana_historical_price() {
         let base = Currency::USD;
         let api = super::Api {
-            key: &global::config().forex_twelvedata_api_key,
+            key: global::secrets().get(global::SECRET_FOREX_TWELVEDATA_API_KEY),
             client: global::http_client(),
         };
         let date = Utc.with_ymd_and_hms(2022, 8, 1, 0, 0, 0).unwrap();
@@ -264,7 +286,7 @@ impl TryFrom<(Response, Decimal)> for RatesResponse<Rates> {
     }
 }
 
-struct RatesResponseList(Vec<RatesResponse<Rates>>);
+pub(super) struct RatesResponseList(pub(super) Vec<RatesResponse<Rates>>);
 
 // (Currency, ...), Currency is base currency
 impl TryFrom<(Currency, TimeseriesResponse)> for RatesResponseList {
@@ -511,9 +533,10 @@ pub struct ExchangeRates {
 #[async_trait]
 impl ForexRates for Api {
     async fn rates(&self, base: Currency) -> ForexResult<RatesResponse<Rates>> {
-        let symbols = Currency::to_comma_separated_list_str();
+        let symbols = Currency::to_comma_separated_list_str_of(capabilities().requested_symbols());
+        let key = self.key.expose();
         let params = [
-            ("api_key", self.key),
+            ("api_key", key.as_str()),
             ("base", base.code()),
             ("symbols", symbols.as_str()),
         ];
@@ -556,10 +579,11 @@ impl ForexHistoricalRates for Api {
         date: DateTime<Utc>,
         base: Currency,
     ) -> ForexResult<RatesResponse<Rates>> {
-        let symbols = Currency::to_comma_separated_list_str();
+        let symbols = Currency::to_comma_separated_list_str_of(capabilities().requested_symbols());
         let yyyymmdd = date.format("%Y-%m-%d").to_string();
+        let key = self.key.expose();
         let params = [
-            ("api_key", self.key),
+            ("api_key", key.as_str()),
             ("base", base.code()),
             ("date", yyyymmdd.as_str()),
             ("symbols", symbols.as_str()),
@@ -610,12 +634,13 @@ impl ForexTimeseriesRates for Api {
             ));
         }
 
-        let symbols = Currency::to_comma_separated_list_str();
+        let symbols = Currency::to_comma_separated_list_str_of(capabilities().requested_symbols());
         let from = start_date.format("%Y-%m-%d").to_string();
         let to = end_date.format("%Y-%m-%d").to_string();
 
+        let key = self.key.expose();
         let params = [
-            ("api_key", self.key),
+            ("api_key", key.as_str()),
             ("base", base.code()),
             ("start_date", from.as_str()),
             ("end_date", to.as_str()),
@@ -652,3 +677,9 @@ impl ForexTimeseriesRates for Api {
         Ok(resp)
     }
 }
+
+impl ForexProviderCapabilities for Api {
+    fn capabilities(&self) -> ProviderCapabilities {
+        capabilities()
+    }
+}
@@ -0,0 +1,315 @@
+// faulty_storage.rs storage decorator injecting faults into calls of the wrapped storage, for tests of failure handling,
+// e.g. provider fallbacks or mapping of storage errors into responses. enabled in tests and with `fault-injection` feature.
+// faults are drawn from seeded generator, so a test sees the same sequence of faults on every run.
+
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::forex::entity::{
+    Job, JobRun, Order, OrderBy, ProviderCallSample, ProviderHealth, Rates, RatesList,
+    RatesResponse,
+};
+use crate::forex::interface::{
+    ForexStorage, ForexStorageJobRuns, ForexStorageProviderHealth, ForexStorageRebased,
+    HistoricalUpdate,
+};
+use crate::forex::{Currency, ForexError, ForexResult, Money};
+
+/// message of injected errors.
+pub const INJECTED_FAULT: &str = "injected storage fault";
+
+const DEFAULT_SEED: u64 = 0x5eed_f0e5;
+
+/// Storage failing calls of the wrapped storage at configured rates, after configured latency.
+#[derive(Clone)]
+pub struct FaultyStorage<S> {
+    inner: S,
+
+    /// chance of a call failing without reaching wrapped storage, 0 to 1.
+    error_rate: f64,
+
+    /// chance of a write failing after only part of it is written, 0 to 1.
+    /// batches are written up to their half, single writes are written entirely but reported as failed.
+    partial_write_rate: f64,
+
+    /// delay before every call.
+    latency: Duration,
+
+    rng: Arc<Mutex<u64>>,
+    injected: Arc<AtomicU64>,
+}
+
+enum Fault {
+    None,
+    Error,
+    PartialWrite,
+}
+
+impl<S> FaultyStorage<S> {
+    /// wrapped storage without faults until they're configured.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            error_rate: 0.0,
+            partial_write_rate: 0.0,
+            latency: Duration::ZERO,
+            rng: Arc::new(Mutex::new(DEFAULT_SEED)),
+            injected: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn with_error_rate(mut self, rate: f64) -> Self {
+        self.error_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_partial_write_rate(mut self, rate: f64) -> Self {
+        self.partial_write_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// seed of generator drawing faults, storages with the same seed and rates fail the same calls.
+    pub fn with_seed(self, seed: u64) -> Self {
+        // xorshift is stuck at zero
+        *self.rng.lock().unwrap_or_else(|err| err.into_inner()) = seed.max(1);
+        self
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// number of faults injected so far, errors and partial writes.
+    pub fn injected(&self) -> u64 {
+        self.injected.load(Ordering::Relaxed)
+    }
+
+    /// wait for latency, then draw fault of the call.
+    async fn fault(&self, write: bool) -> Fault {
+        if !self.latency.is_zero() {
+            tokio::time::sleep(self.latency).await;
+        }
+
+        let fault = if self.draw() < self.error_rate {
+            Fault::Error
+        } else if write && self.draw() < self.partial_write_rate {
+            Fault::PartialWrite
+        } else {
+            Fault::None
+        };
+        if !matches!(fault, Fault::None) {
+            self.injected.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fault
+    }
+
+    /// uniform number in [0, 1).
+    fn draw(&self) -> f64 {
+        let mut state = self.rng.lock().unwrap_or_else(|err| err.into_inner());
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        (*state >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// fail the read, or let it through.
+    async fn read(&self) -> ForexResult<()> {
+        match self.fault(false).await {
+            Fault::Error => Err(injected_error()),
+            Fault::None | Fault::PartialWrite => Ok(()),
+        }
+    }
+
+    /// fail the write before writing, or write it and fail afterwards if partially written.
+    async fn write<T, F>(&self, write: F) -> ForexResult<T>
+    where
+        F: Future<Output = ForexResult<T>>,
+    {
+        match self.fault(true).await {
+            Fault::Error => Err(injected_error()),
+            Fault::PartialWrite => {
+                write.await?;
+                Err(injected_error())
+            }
+            Fault::None => write.await,
+        }
+    }
+}
+
+fn injected_error() -> ForexError {
+    ForexError::internal_error(INJECTED_FAULT)
+}
+
+#[async_trait]
+impl<S> ForexStorage for FaultyStorage<S>
+where
+    S: ForexStorage + Send + Sync,
+{
+    async fn insert_latest<T>(
+        &self,
+        date: DateTime<Utc>,
+        rates: &RatesResponse<T>,
+    ) -> ForexResult<()>
+    where
+        T: Debug + Serialize + for<'de> Deserialize<'de> + Send + Sync,
+    {
+        self.write(self.inner.insert_latest(date, rates)).await
+    }
+
+    async fn get_latest(&self) -> ForexResult<RatesResponse<Rates>> {
+        self.read().await?;
+        self.inner.get_latest().await
+    }
+
+    async fn insert_historical<T>(
+        &self,
+        date: DateTime<Utc>,
+        rates: &RatesResponse<T>,
+    ) -> ForexResult<()>
+    where
+        T: Debug + Serialize + for<'de> Deserialize<'de> + Send + Sync,
+    {
+        self.write(self.inner.insert_historical(date, rates)).await
+    }
+
+    async fn insert_historical_batch(
+        &self,
+        mut rates: Vec<RatesResponse<Rates>>,
+    ) -> ForexResult<()> {
+        match self.fault(true).await {
+            Fault::Error => Err(injected_error()),
+            Fault::PartialWrite => {
+                rates.truncate(rates.len() / 2);
+                self.inner.insert_historical_batch(rates).await?;
+                Err(injected_error())
+            }
+            Fault::None => self.inner.insert_historical_batch(rates).await,
+        }
+    }
+
+    async fn update_historical_rates_data(
+        &self,
+        date: DateTime<Utc>,
+        new_data: Vec<(Money, String)>,
+    ) -> ForexResult<RatesResponse<Rates>> {
+        self.write(self.inner.update_historical_rates_data(date, new_data))
+            .await
+    }
+
+    async fn update_historical_bulk(
+        &self,
+        mut updates: Vec<HistoricalUpdate>,
+    ) -> ForexResult<Vec<RatesResponse<Rates>>> {
+        match self.fault(true).await {
+            Fault::Error => Err(injected_error()),
+            Fault::PartialWrite => {
+                updates.truncate(updates.len() / 2);
+                self.inner.update_historical_bulk(updates).await?;
+                Err(injected_error())
+            }
+            Fault::None => self.inner.update_historical_bulk(updates).await,
+        }
+    }
+
+    async fn get_historical(&self, date: DateTime<Utc>) -> ForexResult<RatesResponse<Rates>> {
+        self.read().await?;
+        self.inner.get_historical(date).await
+    }
+
+    async fn get_historical_range(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> ForexResult<Vec<RatesResponse<Rates>>> {
+        self.read().await?;
+        self.inner.get_historical_range(start, end).await
+    }
+
+    async fn get_latest_list(
+        &self,
+        page: u32,
+        size: u32,
+        order: Order,
+        order_by: OrderBy,
+    ) -> ForexResult<RatesList<RatesResponse<Rates>>> {
+        self.read().await?;
+        self.inner
+            .get_latest_list(page, size, order, order_by)
+            .await
+    }
+
+    async fn get_historical_list(
+        &self,
+        page: u32,
+        size: u32,
+        order: Order,
+        order_by: OrderBy,
+    ) -> ForexResult<RatesList<RatesResponse<Rates>>> {
+        self.read().await?;
+        self.inner
+            .get_historical_list(page, size, order, order_by)
+            .await
+    }
+}
+
+#[async_trait]
+impl<S> ForexStorageRebased for FaultyStorage<S>
+where
+    S: ForexStorageRebased + Send + Sync,
+{
+    async fn get_historical_rebased(
+        &self,
+        date: DateTime<Utc>,
+        base: Currency,
+    ) -> ForexResult<RatesResponse<Rates>> {
+        self.read().await?;
+        self.inner.get_historical_rebased(date, base).await
+    }
+}
+
+#[async_trait]
+impl<S> ForexStorageProviderHealth for FaultyStorage<S>
+where
+    S: ForexStorageProviderHealth + Send + Sync,
+{
+    async fn record_provider_call(
+        &self,
+        provider: &str,
+        sample: ProviderCallSample,
+    ) -> ForexResult<()> {
+        self.write(self.inner.record_provider_call(provider, sample))
+            .await
+    }
+
+    async fn get_provider_health(&self) -> ForexResult<Vec<ProviderHealth>> {
+        self.read().await?;
+        self.inner.get_provider_health().await
+    }
+}
+
+#[async_trait]
+impl<S> ForexStorageJobRuns for FaultyStorage<S>
+where
+    S: ForexStorageJobRuns + Send + Sync,
+{
+    async fn insert_job_run(&self, run: &JobRun) -> ForexResult<()> {
+        self.write(self.inner.insert_job_run(run)).await
+    }
+
+    async fn get_job_runs(&self, job: Option<Job>, limit: u32) -> ForexResult<Vec<JobRun>> {
+        self.read().await?;
+        self.inner.get_job_runs(job, limit).await
+    }
+}
@@ -0,0 +1,159 @@
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+use rust_decimal_macros::dec;
+use tokio_util::sync::CancellationToken;
+
+use crate::forex::{
+    Currency, ForexError, ForexResult, Money,
+    entity::{Job, JobTrigger, Rates, RatesResponse},
+    interface::{ForexRates, ForexStorage, ForexStorageJobRuns},
+    mock::{ForexApiSuccessMock, ForexStorageSuccessMock},
+    service,
+};
+use crate::forex_impl::registry::{Provider, ProviderChain};
+use crate::global::{
+    self,
+    test_support::{rates, temp_storage},
+};
+
+use super::faulty_storage::{FaultyStorage, INJECTED_FAULT};
+
+struct Failing;
+
+#[async_trait]
+impl ForexRates for Failing {
+    async fn rates(&self, _base: Currency) -> ForexResult<RatesResponse<Rates>> {
+        Err(ForexError::internal_error("provider down"))
+    }
+}
+
+#[tokio::test]
+async fn test_faulty_storage_error_rate() {
+    let healthy = FaultyStorage::new(ForexStorageSuccessMock);
+    assert!(healthy.get_latest().await.is_ok());
+    assert_eq!(healthy.injected(), 0);
+
+    let failing = FaultyStorage::new(ForexStorageSuccessMock).with_error_rate(1.0);
+    let err = failing.get_latest().await.unwrap_err();
+    assert!(matches!(err, ForexError::InternalError(_)));
+    assert!(err.to_string().contains(INJECTED_FAULT));
+    assert_eq!(failing.injected(), 1);
+
+    // same seed fails the same calls
+    let first = FaultyStorage::new(ForexStorageSuccessMock)
+        .with_error_rate(0.5)
+        .with_seed(42);
+    let second = FaultyStorage::new(ForexStorageSuccessMock)
+        .with_error_rate(0.5)
+        .with_seed(42);
+    for _ in 0..50 {
+        assert_eq!(
+            first.get_latest().await.is_ok(),
+            second.get_latest().await.is_ok()
+        );
+    }
+    assert!(first.injected() > 0 && first.injected() < 50);
+}
+
+#[tokio::test]
+async fn test_faulty_storage_latency() {
+    let storage =
+        FaultyStorage::new(ForexStorageSuccessMock).with_latency(Duration::from_millis(20));
+
+    let started = Instant::now();
+    storage.get_latest().await.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(20));
+}
+
+#[tokio::test]
+async fn test_faulty_storage_partial_write() {
+    let (inner, _root) = temp_storage("faulty-storage-partial");
+    let storage = FaultyStorage::new(inner).with_partial_write_rate(1.0);
+    let historical = |day| {
+        rates(
+            Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap(),
+            &[Money::IDR(dec!(15500))],
+        )
+    };
+    let batch: Vec<_> = (1..=4).map(historical).collect();
+
+    assert!(storage.insert_historical_batch(batch).await.is_err());
+    // only first half of the batch landed
+    let stored = storage
+        .inner()
+        .get_historical_range(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 1, 4, 0, 0, 0).unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(stored.len(), 2);
+    assert!(
+        storage
+            .inner()
+            .get_historical(Utc.with_ymd_and_hms(2024, 1, 4, 0, 0, 0).unwrap())
+            .await
+            .is_err()
+    );
+
+    // single write lands, but is reported as failed
+    let rates = historical(5);
+    assert!(
+        storage
+            .insert_historical(rates.data.date, &rates)
+            .await
+            .is_err()
+    );
+    assert!(
+        storage
+            .inner()
+            .get_historical(rates.data.date)
+            .await
+            .is_ok()
+    );
+    assert_eq!(storage.injected(), 2);
+}
+
+#[tokio::test]
+async fn test_poll_rates_storage_failure() {
+    let storage = FaultyStorage::new(ForexStorageSuccessMock).with_error_rate(1.0);
+
+    // polled rates that can't be stored fail the job, failing to record the run is only logged
+    let ret = service::run_job(
+        &storage,
+        Job::PollLatestRates,
+        JobTrigger::Cron,
+        service::poll_rates(
+            &ForexApiSuccessMock,
+            &storage,
+            &global::SystemClock,
+            Currency::USD,
+            &CancellationToken::new(),
+        ),
+    )
+    .await;
+    assert!(ret.is_err());
+    assert!(storage.get_job_runs(None, 10).await.is_err());
+    assert_eq!(storage.injected(), 3);
+}
+
+#[tokio::test]
+async fn test_provider_chain_with_failing_health_storage() {
+    let (inner, _root) = temp_storage("faulty-storage-chain");
+    let storage = FaultyStorage::new(inner).with_error_rate(1.0);
+    let chain = ProviderChain::new(
+        vec![
+            Provider::new("failing").with_rates(Failing),
+            Provider::new("success").with_rates(ForexApiSuccessMock),
+        ],
+        storage.clone(),
+    );
+
+    // configured order is kept when health can't be read, and unrecorded calls don't stop fallback
+    let ordered: Vec<_> = chain.ordered().await.iter().map(|p| p.name()).collect();
+    assert_eq!(ordered, vec!["failing", "success"]);
+    let ret = chain.into_provider().rates(Currency::USD).await.unwrap();
+    assert_eq!(ret.source, "success_latest_mock");
+}
@@ -2,52 +2,356 @@
 // implementations for database to store forex data polled from the APIs.
 // using filesystem with tokio
 
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt::Debug;
-use std::os::unix::fs::PermissionsExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use crate::digest::entity::DigestSubscription;
 use crate::error::AsInternalError;
 use crate::forex::ForexResult;
-use crate::forex::entity::{Order, Rates, RatesList, RatesResponse};
-use crate::forex::interface::{ForexStorage, ForexStorageDeletion};
-use crate::forex::{ForexError, Money};
-use crate::global::StorageFS;
+use crate::forex::entity::{
+    BackfillCheckpoint, ChangeKind, ConsistencyReport, Cursor, HistoricalVersion, Job, JobLock,
+    JobRun, Order, OrderBy, ProvenanceEvent, ProvenanceKind, ProviderCallSample, ProviderHealth,
+    RatePoint, Rates, RatesChange, RatesChangeReplay, RatesKind, RatesList, RatesPage,
+    RatesProvenance, RatesResponse, SignatureReport, SloReport, Sparkline, StorageStats,
+    StorageSubtreeStats, WebhookDelivery, sort_rates,
+};
+use crate::forex::interface::{ForexStorage, ForexStorageDeletion, HistoricalUpdate};
+use crate::forex::slo::SLO_RETENTION_DAYS;
+use crate::forex::{Currency, ForexError, Money};
+use crate::forex_impl::latest_filename::{LatestDailyFilename, LatestFilename};
+use crate::forex_impl::rates_cache::RatesCache;
+use crate::forex_impl::signing::{RatesSigner, RatesVerifier};
+use crate::forex_impl::sparse_rates;
+use crate::global::{
+    self, EventBus, HistoricalMode, LatestLayout, LatestRotation, StorageEvent, StorageFS,
+    TenantId, TenantScoped,
+};
+use crate::goals::entity::Goal;
+use crate::ledger::entity::{Account, CategoryRule, JournalEntry, Transaction};
+use crate::sync::entity::{RevisionLog, SyncKind, Tombstone};
+use crate::users::entity::User;
+use crate::watchlist::entity::Watchlist;
 use anyhow::Context;
 use async_trait::async_trait;
-use chrono::{DateTime, Datelike, TimeZone, Timelike, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, SubsecRound, TimeDelta, TimeZone, Utc};
+use pfm_utils::config_util;
 use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
 use tokio::fs::{self, File, read_dir};
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 use tracing::instrument;
+use uuid::Uuid;
 
 const ERROR_PREFIX: &str = "[FOREX][storage_impl]";
 
-const LATEST_FILENAME_FORMAT: &str = "latest-{YYYY}-{MM}-{DD}T{hh}:{mm}:{ss}Z.json";
-
 const HISTORICAL_FILENAME_FORMAT: &str = "historical-{YYYY}-{MM}-{DD}Z.json";
 
-const FILE_PERMISSION: u32 = 0o640;
+/// max number of files written at once by insert_historical_batch
+const HISTORICAL_BATCH_CONCURRENCY: usize = 16;
+
+/// sub directory inside latest directory for rotated latest rates
+const LATEST_ARCHIVE_DIR_NAME: &str = "archive";
+
+/// bytes read at once from the end of daily latest file until its last line is found
+const LATEST_DAILY_TAIL_CHUNK_BYTES: u64 = 16 * 1024;
+
+/// sub directory inside metadata directory for job runs history
+const JOBS_METADATA_DIR_NAME: &str = "jobs";
+
+/// sub directory inside metadata directory for webhook deliveries
+const WEBHOOKS_METADATA_DIR_NAME: &str = "webhooks";
+
+/// sub directory inside metadata directory for locks of jobs
+const LOCKS_METADATA_DIR_NAME: &str = "locks";
+
+/// sub directory inside metadata directory for historical rates provenance
+const HISTORICAL_METADATA_DIR_NAME: &str = "historical";
+
+/// sub directory inside metadata directory for sparklines, a file per currency
+const SPARKLINES_METADATA_DIR_NAME: &str = "sparklines";
+
+/// number of latest daily rates kept in sparkline of each currency
+const SPARKLINE_WINDOW: usize = 90;
+
+/// sub directory inside metadata directory for health of providers, a file per provider
+const PROVIDERS_METADATA_DIR_NAME: &str = "providers";
+
+/// number of latest calls kept in health of each provider
+const PROVIDER_HEALTH_WINDOW: usize = 100;
+
+/// sub directory inside metadata directory for reports comparing providers, a file per date and pair of providers
+const CONSISTENCY_METADATA_DIR_NAME: &str = "consistency";
+
+/// sub directory inside metadata directory for evaluations of freshness SLO, a file per evaluation
+const SLO_METADATA_DIR_NAME: &str = "slo";
+
+/// sub directory inside metadata directory for progress of backfills, a file per provider and base
+const BACKFILL_METADATA_DIR_NAME: &str = "backfill";
+
+/// append-only journal inside metadata directory written before journal directory existed, only read
+const JOURNAL_FILENAME: &str = "journal.jsonl";
+
+/// sub directory inside metadata directory for append-only journal, a json line per change of rates files,
+/// rotated into a file per month of changed rates' date, e.g. journal/2024-01.jsonl
+const JOURNAL_METADATA_DIR_NAME: &str = "journal";
+
+/// sub directory inside metadata directory for signatures of rates files
+const SIGNATURES_METADATA_DIR_NAME: &str = "signatures";
+
+/// sub directory inside signatures directory for signatures of latest rates files, a file per latest filename
+const LATEST_SIGNATURES_DIR_NAME: &str = "latest";
+
+/// sub directory inside signatures directory for signatures of historical rates files, mirroring historical directory
+const HISTORICAL_SIGNATURES_DIR_NAME: &str = "historical";
+
+/// directory inside storage root and metadata directory for user data of each tenant
+const TENANTS_DIR_NAME: &str = "tenants";
+
+/// directory inside storage root for local user accounts, shared by all tenants
+const USERS_DIR_NAME: &str = "users";
+
+/// directory inside storage root for savings goals
+const GOALS_DIR_NAME: &str = "goals";
+
+/// directory inside storage root for watchlists, a file per profile
+const WATCHLISTS_DIR_NAME: &str = "watchlists";
+
+/// directory inside storage root for email digest subscriptions
+const DIGESTS_DIR_NAME: &str = "digests";
+
+/// directory inside metadata directory for sync tombstones and last syncs with peers
+const SYNC_DIR_NAME: &str = "sync";
+
+/// file inside sync directory containing tombstones of deleted records
+const TOMBSTONES_FILENAME: &str = "tombstones.json";
+
+/// file inside sync directory containing time of last sync by peer
+const PEERS_FILENAME: &str = "peers.json";
+
+/// sub directory inside sync directory for revision logs by their names
+const REVISIONS_DIR_NAME: &str = "revisions";
+
+/// directory inside storage root for ledger transactions, also inside metadata directory for ledger settings
+const LEDGER_DIR_NAME: &str = "ledger";
+
+/// file inside ledger metadata directory containing user defined category rules
+const CATEGORY_RULES_FILENAME: &str = "category_rules.json";
+
+/// sub directory inside ledger directory for double-entry accounts and journal entries
+const DOUBLE_ENTRY_DIR_NAME: &str = "double_entry";
+
+/// file inside double-entry directory containing accounts
+const ACCOUNTS_FILENAME: &str = "accounts.json";
+
+/// sub directory inside double-entry directory for journal entries
+const JOURNAL_ENTRIES_DIR_NAME: &str = "entries";
+
+/// latest rates snapshot, either its own file or line of daily latest file.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum LatestEntry {
+    File(PathBuf),
+    Line(String),
+}
+
+/// only date of snapshot in daily latest file, for ordering lines without decoding their rates.
+#[derive(Deserialize)]
+struct LatestLineDate {
+    data: LatestLineData,
+}
+
+#[derive(Deserialize)]
+struct LatestLineData {
+    date: DateTime<Utc>,
+}
+
+/// content of daily latest file as of the last append, so the next append doesn't read the file again.
+struct LatestDailyTail {
+    path: PathBuf,
+    len: u64,
+    content: String,
+}
 
 #[derive(Clone)]
 pub struct ForexStorageImpl {
     fs: StorageFS,
+    signer: Option<Arc<RatesSigner>>,
+    tenant: Option<TenantId>,
+    cache: Option<Arc<RatesCache>>,
+    force: bool,
+    immutable: bool,
+    events: Option<EventBus>,
+    latest_daily: Arc<std::sync::Mutex<Option<LatestDailyTail>>>,
 }
 
 impl ForexStorageImpl {
     pub fn new(fs: StorageFS) -> Self {
-        Self { fs }
+        Self {
+            fs,
+            signer: None,
+            tenant: None,
+            cache: None,
+            force: false,
+            immutable: global::config().forex_historical_mode == HistoricalMode::Immutable,
+            events: None,
+            latest_daily: Arc::default(),
+        }
     }
 
-    async fn set_permission(pathbuf: &PathBuf) -> ForexResult<()> {
-        // Set permissions to 640 (owner read/write only)
-        let mut perms = fs::metadata(&pathbuf)
-            .await
-            .context("forex storage read metadata")
-            .as_internal_err()?
-            .permissions();
-        perms.set_mode(FILE_PERMISSION);
-        fs::set_permissions(&pathbuf, perms)
+    /// directory of user data inside the base directory, under `tenants/{id}` if scoped to a tenant.
+    fn tenant_dir(&self, base: &Path) -> PathBuf {
+        match &self.tenant {
+            Some(tenant) => base.join(TENANTS_DIR_NAME).join(tenant.as_str()),
+            None => base.to_path_buf(),
+        }
+    }
+
+    /// sign each written latest and historical rates file with the signer.
+    pub fn with_signer(mut self, signer: RatesSigner) -> Self {
+        self.signer = Some(Arc::new(signer));
+        self
+    }
+
+    /// keep at most capacity latest and historical rates files read by get_latest and get_historical in memory.
+    pub fn with_cache(mut self, capacity: usize) -> Self {
+        self.cache = Some(Arc::new(RatesCache::new(capacity)));
+        self
+    }
+
+    /// publish every write of rates and ledger records into the bus.
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    fn publish(&self, event: StorageEvent) {
+        if let Some(events) = &self.events {
+            events.publish(event);
+        }
+    }
+
+    fn publish_ledger_changed(&self) {
+        self.publish(StorageEvent::LedgerChanged {
+            tenant: self.tenant.clone(),
+        });
+    }
+
+    /// storage writing rates failing validation instead of rejecting them.
+    pub fn forced(&self) -> Self {
+        Self {
+            force: true,
+            ..self.clone()
+        }
+    }
+
+    /// storage keeping stored historical rates files, writing next versions of them instead.
+    pub fn immutable(&self) -> Self {
+        Self {
+            immutable: true,
+            ..self.clone()
+        }
+    }
+
+    /// versions of historical rates file at the date currently read and to be written.
+    /// both are the newest version, unless immutable and the newest is stored, then the next one is written.
+    fn historical_write_versions(&self, historical_dir: &Path, date: DateTime<Utc>) -> (u32, u32) {
+        let current = current_historical_version(historical_dir, date);
+        let stored = historical_dir
+            .join(generate_historical_version_file_path(date, current))
+            .is_file();
+        match self.immutable && stored {
+            true => (current, current + 1),
+            false => (current, current),
+        }
+    }
+
+    /// reject rates failing validation before they're written, unless forced.
+    fn check_rates(&self, rates: &RatesResponse<Rates>) -> ForexResult<()> {
+        let Err(violations) = rates.validate() else {
+            return Ok(());
+        };
+        let violations = violations
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        if self.force {
+            tracing::warn!(
+                "storage writing invalid rates of {} by force: {}",
+                rates.data.date,
+                violations
+            );
+            return Ok(());
+        }
+
+        Err(ForexError::client_error(&format!(
+            "invalid rates of {}: {}",
+            rates.data.date, violations
+        )))
+    }
+
+    /// parse back generically written rates to be checked, anything but rates isn't checked.
+    fn check_written(&self, json_string: &str) -> ForexResult<Option<RatesResponse<Rates>>> {
+        let Ok(rates) = serde_json::from_str::<RatesResponse<Rates>>(json_string) else {
+            return Ok(None);
+        };
+        self.check_rates(&rates)?;
+
+        Ok(Some(rates))
+    }
+
+    /// number of rates files kept in memory.
+    pub fn cached(&self) -> usize {
+        self.cache.as_ref().map_or(0, |cache| cache.len())
+    }
+
+    /// read and parse rates file, from cache if enabled and the file is unchanged since cached.
+    async fn read_rates_file(&self, path: &Path) -> ForexResult<RatesResponse<Rates>> {
+        let metadata = match &self.cache {
+            Some(cache) => {
+                let metadata = fs::metadata(path)
+                    .await
+                    .context("storage read rates file metadata")
+                    .as_internal_err()?;
+                if let Some(rates) = cache.get(path, &metadata) {
+                    return Ok(rates);
+                }
+                Some(metadata)
+            }
+            None => None,
+        };
+
+        let content = fs::read_to_string(path)
             .await
+            .context("storage read rates file")
+            .as_internal_err()?;
+        let rates: RatesResponse<Rates> = serde_json::from_str(&content)
+            .context("storage read rates file parse to json")
+            .as_internal_err()?;
+
+        if let (Some(cache), Some(metadata)) = (&self.cache, metadata) {
+            cache.insert(path, &metadata, rates.clone());
+        }
+
+        Ok(rates)
+    }
+
+    /// drop cached rates of the file written by this storage.
+    fn invalidate_cached(&self, path: &Path) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate(path);
+        }
+    }
+
+    async fn set_permission(pathbuf: &PathBuf) -> ForexResult<()> {
+        // permission policy, e.g. 640 (owner read/write, group read only)
+        let Some(mode) = global::config().storage_permissions.file_mode() else {
+            return Ok(());
+        };
+        config_util::set_mode(pathbuf, mode)
             .context("forex storage setting permission")
             .as_internal_err()?;
 
@@ -65,10 +369,20 @@ impl ForexStorageImpl {
         let json_string = serde_json::to_string_pretty(&rates)
             .context("forex storage insert latest parse into json string")
             .as_internal_err()?;
+        let written = self.check_written(&json_string)?;
+
+        if global::config().forex_latest_layout == LatestLayout::Daily {
+            self.append_latest_daily(date, rates).await?;
+            if let Some(rates) = written {
+                self.publish(StorageEvent::LatestInserted(Arc::new(rates)));
+            }
+            return Ok(());
+        }
 
-        let latest_write = self.fs.write().await;
-        let latest_write = latest_write.latest();
-        let latest_write = latest_write.join(generate_latest_file_path(date));
+        let fs_write = self.fs.write().await;
+        let filename = generate_latest_file_path(date);
+        let latest_write = fs_write.latest().join(&filename);
+        let before = file_checksum(&latest_write).await?;
 
         let mut file = File::create(&latest_write)
             .await
@@ -85,9 +399,128 @@ impl ForexStorageImpl {
 
         Self::set_permission(&latest_write).await?;
 
+        Self::write_signature(
+            self.signer.as_deref(),
+            latest_signature_path(fs_write.metadata(), &filename),
+            &json_string,
+        )
+        .await?;
+
+        let change = RatesChange::new(
+            ChangeKind::Insert,
+            RatesKind::Latest,
+            date,
+            relative_path(fs_write.root(), &latest_write),
+            before,
+            Some(checksum(json_string.as_bytes())),
+        );
+        Self::append_change(fs_write.metadata(), &change).await?;
+        drop(fs_write);
+
+        if let Some(rates) = written {
+            self.publish(StorageEvent::LatestInserted(Arc::new(rates)));
+        }
+
+        Ok(())
+    }
+
+    /// append snapshot as line of daily latest file of the date.
+    /// the file is signed and journaled as a whole after each append, from its content kept since the last append
+    /// unless the file's length changed meanwhile, e.g. written by another instance.
+    async fn append_latest_daily<T>(
+        &self,
+        date: DateTime<Utc>,
+        rates: &RatesResponse<T>,
+    ) -> ForexResult<()>
+    where
+        T: Serialize,
+    {
+        let mut line = serde_json::to_string(&rates)
+            .context("forex storage append latest daily parse into json string")
+            .as_internal_err()?;
+        line.push('\n');
+
+        let fs_write = self.fs.write().await;
+        let filename = LatestDailyFilename::new(date).to_string();
+        let latest_write = fs_write.latest().join(&filename);
+        let tail = self
+            .latest_daily
+            .lock()
+            .map_err(|_| ForexError::internal_error("storage latest daily tail lock poisoned"))?
+            .take()
+            .filter(|tail| tail.path == latest_write);
+        let len = match fs::metadata(&latest_write).await {
+            Ok(metadata) => Some(metadata.len()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => Err(err)
+                .context("forex storage append latest daily read metadata")
+                .as_internal_err()?,
+        };
+        let mut content = match (tail, len) {
+            (_, None) => String::new(),
+            (Some(tail), Some(len)) if tail.len == len => tail.content,
+            (_, Some(_)) => fs::read_to_string(&latest_write)
+                .await
+                .context("forex storage append latest daily read file")
+                .as_internal_err()?,
+        };
+        let before = len.map(|_| checksum(content.as_bytes()));
+        // a torn last line, e.g. of a crash mid append, is ended so this line stays parseable
+        if !content.is_empty() && !content.ends_with('\n') {
+            line.insert(0, '\n');
+        }
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&latest_write)
+            .await
+            .context("forex storage append latest daily open file")
+            .as_internal_err()?;
+        file.write_all(line.as_bytes())
+            .await
+            .context("forex storage append latest daily write")
+            .as_internal_err()?;
+        file.flush()
+            .await
+            .context("forex storage append latest daily flush")
+            .as_internal_err()?;
+
+        Self::set_permission(&latest_write).await?;
+
+        content.push_str(&line);
+        Self::write_signature(
+            self.signer.as_deref(),
+            latest_signature_path(fs_write.metadata(), &filename),
+            &content,
+        )
+        .await?;
+
+        let kind = match before {
+            Some(_) => ChangeKind::Update,
+            None => ChangeKind::Insert,
+        };
+        let change = RatesChange::new(
+            kind,
+            RatesKind::Latest,
+            date,
+            relative_path(fs_write.root(), &latest_write),
+            before,
+            Some(checksum(content.as_bytes())),
+        );
+        Self::append_change(fs_write.metadata(), &change).await?;
+        if let Ok(mut tail) = self.latest_daily.lock() {
+            *tail = Some(LatestDailyTail {
+                path: latest_write,
+                len: content.len() as u64,
+                content,
+            });
+        }
+
         Ok(())
     }
 
+    /// newest of latest rates files and last line of newest daily latest file.
     #[instrument(skip(self), ret)]
     async fn get_latest(&self) -> ForexResult<RatesResponse<Rates>> {
         let latest_read = self.fs.read().await;
@@ -99,6 +532,7 @@ impl ForexStorageImpl {
             .as_internal_err()?;
 
         let mut files: Vec<PathBuf> = Vec::new();
+        let mut daily_files: Vec<(LatestDailyFilename, PathBuf)> = Vec::new();
         while let Some(entry) = entries
             .next_entry()
             .await
@@ -106,26 +540,87 @@ impl ForexStorageImpl {
             .as_internal_err()?
         {
             let path = entry.path();
-            files.push(path);
+            if !path.is_file() {
+                continue;
+            }
+            match LatestDailyFilename::parse(&entry.file_name().to_string_lossy()) {
+                Some(daily) => daily_files.push((daily, path)),
+                None => files.push(path),
+            }
         }
 
-        if files.is_empty() {
-            return Err(ForexError::internal_error("storage get latest dir empty"));
-        }
+        // sort descending by date in filename, both current and legacy filenames
+        files.sort_by_cached_key(|path| {
+            let filename = path.file_name().unwrap_or_default().to_string_lossy();
+            std::cmp::Reverse((parse_latest_file_path(&filename), filename.into_owned()))
+        });
+
+        let file = match files.first() {
+            Some(path) => Some(self.read_rates_file(path).await?),
+            None => None,
+        };
+        let daily = match daily_files.into_iter().max() {
+            Some((_, path)) => Self::read_latest_daily_last(&path).await?,
+            None => None,
+        };
 
-        // sort descending
-        files.sort_by(|a, b| b.file_name().cmp(&a.file_name()));
+        match (file, daily) {
+            (Some(file), Some(daily)) if file.data.date > daily.data.date => Ok(file),
+            (_, Some(daily)) => Ok(daily),
+            (Some(file), None) => Ok(file),
+            (None, None) => Err(ForexError::internal_error("storage get latest dir empty")),
+        }
+    }
 
-        let content = fs::read_to_string(&files[0])
+    /// last parseable snapshot of daily latest file, read from its end so earlier snapshots of the day aren't read.
+    /// unparseable lines, e.g. torn by a crash mid append, are skipped. none if the file has no snapshot.
+    async fn read_latest_daily_last(path: &Path) -> ForexResult<Option<RatesResponse<Rates>>> {
+        let mut file = File::open(path)
             .await
-            .context("storage get latest reading content")
+            .context("storage read latest daily open file")
             .as_internal_err()?;
+        let len = file
+            .metadata()
+            .await
+            .context("storage read latest daily metadata")
+            .as_internal_err()?
+            .len();
 
-        let rates: RatesResponse<Rates> = serde_json::from_str(&content)
-            .context("storage get latest parse to json")
-            .as_internal_err()?;
+        let mut chunk_len = LATEST_DAILY_TAIL_CHUNK_BYTES.min(len);
+        loop {
+            file.seek(std::io::SeekFrom::Start(len - chunk_len))
+                .await
+                .context("storage read latest daily seek")
+                .as_internal_err()?;
+            let mut tail = vec![0; chunk_len as usize];
+            file.read_exact(&mut tail)
+                .await
+                .context("storage read latest daily read tail")
+                .as_internal_err()?;
+            let tail = String::from_utf8_lossy(&tail);
 
-        Ok(rates)
+            // first line of partial tail may be cut, it's read whole with a bigger tail
+            let whole = chunk_len == len;
+            let lines: Vec<&str> = tail.lines().skip(usize::from(!whole)).collect();
+            for line in lines
+                .into_iter()
+                .rev()
+                .filter(|line| !line.trim().is_empty())
+            {
+                match serde_json::from_str(line) {
+                    Ok(rates) => return Ok(Some(rates)),
+                    Err(err) => tracing::warn!(
+                        "storage read latest daily skip unparseable line of {}: {}",
+                        path.display(),
+                        err
+                    ),
+                }
+            }
+            if whole {
+                return Ok(None);
+            }
+            chunk_len = (chunk_len * 2).min(len);
+        }
     }
 
     async fn insert_historical<T>(
@@ -139,10 +634,13 @@ impl ForexStorageImpl {
         let json_string = serde_json::to_string_pretty(&rates)
             .context("storage insert historical parse input into json string")
             .as_internal_err()?;
+        let written = self.check_written(&json_string)?;
 
-        let historical_write = self.fs.write().await;
-        let historical_write = historical_write.historical();
-        let historical_write = historical_write.join(generate_historical_file_path(date));
+        let fs_write = self.fs.write().await;
+        let historical_dir = fs_write.historical();
+        let (current, version) = self.historical_write_versions(historical_dir, date);
+        let historical_write =
+            historical_dir.join(generate_historical_version_file_path(date, version));
 
         let year_dir = historical_write.parent();
         if let Some(dir) = year_dir {
@@ -158,6 +656,10 @@ impl ForexStorageImpl {
             ));
         };
 
+        let before = file_checksum(
+            &historical_dir.join(generate_historical_version_file_path(date, current)),
+        )
+        .await?;
         let mut file = File::create(&historical_write)
             .await
             .context("storage insert historical create filepath")
@@ -172,60 +674,188 @@ impl ForexStorageImpl {
             .as_internal_err()?;
 
         Self::set_permission(&historical_write).await?;
+        self.invalidate_cached(&historical_write);
+
+        Self::write_signature(
+            self.signer.as_deref(),
+            historical_signature_path(fs_write.metadata(), date, version),
+            &json_string,
+        )
+        .await?;
+
+        let change = RatesChange::new(
+            ChangeKind::Insert,
+            RatesKind::Historical,
+            date,
+            relative_path(fs_write.root(), &historical_write),
+            before,
+            Some(checksum(json_string.as_bytes())),
+        );
+        Self::append_change(fs_write.metadata(), &change).await?;
+
+        let event = ProvenanceEvent {
+            kind: ProvenanceKind::Insert,
+            source: rates.source.clone(),
+            at: Utc::now(),
+            error: rates.error.clone(),
+        };
+        Self::record_provenance(fs_write.metadata(), date, event).await?;
+
+        // T is rates in practice, recorded from the value already parsed back to validate it
+        if let Some(rates) = &written
+            && rates.error.is_none()
+        {
+            Self::record_sparklines(fs_write.metadata(), &[&rates.data]).await?;
+        }
+        drop(fs_write);
+
+        if let Some(rates) = written {
+            self.publish(StorageEvent::HistoricalInserted(Arc::new(rates)));
+        }
 
         Ok(())
     }
 
     async fn insert_historical_batch(&self, rates: Vec<RatesResponse<Rates>>) -> ForexResult<()> {
-        let historical_write = self.fs.write().await;
-        let historical_write = historical_write.historical();
+        for rate in &rates {
+            self.check_rates(rate)?;
+        }
 
-        for rate in rates {
-            let date = rate.data.date;
+        let fs_write = self.fs.write().await;
+        let historical_dir = fs_write.historical().clone();
+
+        // create all year directories up front, so concurrent writes below don't race on them.
+        let year_dirs: BTreeSet<PathBuf> = rates
+            .iter()
+            .filter_map(|rate| {
+                historical_dir
+                    .join(generate_historical_file_path(rate.data.date))
+                    .parent()
+                    .map(Path::to_path_buf)
+            })
+            .collect();
+        for dir in year_dirs {
+            if !dir.is_dir() {
+                tokio::fs::create_dir_all(&dir)
+                    .await
+                    .context("storage insert historical batch create year dir")
+                    .as_internal_err()?;
+            }
+        }
 
-            let file_full_path = historical_write.join(generate_historical_file_path(date));
+        // sparklines are read-modify-write, recorded from the input while still holding the write lock,
+        // ahead of the files like the journal.
+        let valid_rates: Vec<&Rates> = rates
+            .iter()
+            .filter(|rate| rate.error.is_none())
+            .map(|rate| &rate.data)
+            .collect();
+        Self::record_sparklines(fs_write.metadata(), &valid_rates).await?;
 
-            let year_dir = file_full_path.parent();
-            if let Some(dir) = year_dir {
-                if !dir.is_dir() {
-                    tokio::fs::create_dir_all(dir)
-                        .await
-                        .context("storage insert historical batch create year dir")
-                        .as_internal_err()?;
-                }
-            } else {
-                return Err(ForexError::internal_error(
-                    "storage insert historical batch create year dir",
-                ));
-            };
+        // other writers stay blocked, readers may go on while files are written.
+        let fs_read = fs_write.downgrade();
 
-            let json_string = serde_json::to_string_pretty(&rate)
+        let semaphore = Arc::new(Semaphore::new(HISTORICAL_BATCH_CONCURRENCY));
+        let mut tasks = JoinSet::new();
+        for rate in dedup_by_date(&rates) {
+            let date = rate.data.date;
+            let (current, version) = self.historical_write_versions(&historical_dir, date);
+            let file_full_path =
+                historical_dir.join(generate_historical_version_file_path(date, version));
+            let json_string = serde_json::to_string_pretty(rate)
                 .context("storage insert historical batch parse input into json string")
                 .as_internal_err()?;
-
-            let mut file = File::create(&file_full_path)
-                .await
-                .context("storage insert historical batch create filepath")
-                .as_internal_err()?;
-            file.write_all(json_string.as_bytes())
-                .await
-                .context("storage insert historical batch write content")
-                .as_internal_err()?;
-            file.flush()
+            let change = RatesChange::new(
+                ChangeKind::Insert,
+                RatesKind::Historical,
+                date,
+                relative_path(fs_read.root(), &file_full_path),
+                file_checksum(
+                    &historical_dir.join(generate_historical_version_file_path(date, current)),
+                )
+                .await?,
+                Some(checksum(json_string.as_bytes())),
+            );
+            let signer = self.signer.clone();
+            let signature_path = historical_signature_path(fs_read.metadata(), date, version);
+            let permit = semaphore
+                .clone()
+                .acquire_owned()
                 .await
-                .context("storage insert historical batch flush")
+                .context("storage insert historical batch acquire permit")
                 .as_internal_err()?;
 
-            Self::set_permission(&file_full_path).await?;
+            self.invalidate_cached(&file_full_path);
+            // journaled ahead of its write, so a failed batch leaves no unjournaled file
+            Self::append_change(fs_read.metadata(), &change).await?;
+            tasks.spawn(async move {
+                // signed after the file, so a signature never covers content not written
+                let ret = match Self::write_file_atomic(&file_full_path, json_string.clone()).await
+                {
+                    Ok(()) => {
+                        Self::write_signature(signer.as_deref(), signature_path, &json_string).await
+                    }
+                    Err(err) => Err(err),
+                };
+                drop(permit);
+                ret
+            });
+        }
+        while let Some(ret) = tasks.join_next().await {
+            ret.context("storage insert historical batch join write task")
+                .as_internal_err()??;
+        }
+
+        // provenance is read-modify-write per date, recorded in input order.
+        for rate in &rates {
+            let event = ProvenanceEvent {
+                kind: ProvenanceKind::Backfill,
+                source: rate.source.clone(),
+                at: Utc::now(),
+                error: rate.error.clone(),
+            };
+            Self::record_provenance(fs_read.metadata(), rate.data.date, event).await?;
+        }
+        drop(fs_read);
+
+        for rate in dedup_by_date(&rates) {
+            self.publish(StorageEvent::HistoricalInserted(Arc::new(rate.clone())));
         }
 
         Ok(())
     }
 
+    /// write into temporary file then rename it, so readers never see partially written file.
+    async fn write_file_atomic(filepath: &PathBuf, content: String) -> ForexResult<()> {
+        let tmp_filepath = filepath.with_extension("json.tmp");
+
+        let mut file = File::create(&tmp_filepath)
+            .await
+            .context("storage write file create temporary filepath")
+            .as_internal_err()?;
+        file.write_all(content.as_bytes())
+            .await
+            .context("storage write file write content")
+            .as_internal_err()?;
+        file.flush()
+            .await
+            .context("storage write file flush")
+            .as_internal_err()?;
+
+        Self::set_permission(&tmp_filepath).await?;
+
+        fs::rename(&tmp_filepath, filepath)
+            .await
+            .context("storage write file rename temporary file")
+            .as_internal_err()?;
+
+        Ok(())
+    }
+
     async fn update_historical_rates_data(
         &self,
         date: DateTime<Utc>,
-        new_rates: Vec<Money>,
+        new_rates: Vec<(Money, String)>,
     ) -> ForexResult<RatesResponse<Rates>> {
         let mut historical_rates = {
             let before_historical_rates = self
@@ -236,121 +866,26 @@ impl ForexStorageImpl {
             before_historical_rates
         };
 
-        for v in new_rates {
-            match v {
-                // fiat
-
-                // north america
-                Money::USD(value) => {
-                    historical_rates.data.rates.usd = value;
-                }
-                Money::CAD(value) => {
-                    historical_rates.data.rates.cad = value;
-                }
-
-                // europe
-                Money::EUR(value) => {
-                    historical_rates.data.rates.eur = value;
-                }
-                Money::GBP(value) => {
-                    historical_rates.data.rates.gbp = value;
-                }
-                Money::CHF(value) => {
-                    historical_rates.data.rates.chf = value;
-                }
-                Money::RUB(value) => {
-                    historical_rates.data.rates.rub = value;
-                }
-
-                // east asia
-                Money::CNY(value) => {
-                    historical_rates.data.rates.cny = value;
-                }
-                Money::JPY(value) => {
-                    historical_rates.data.rates.jpy = value;
-                }
-                Money::KRW(value) => {
-                    historical_rates.data.rates.krw = value;
-                }
-                Money::HKD(value) => {
-                    historical_rates.data.rates.hkd = value;
-                }
-
-                // south-east asia
-                Money::IDR(value) => {
-                    historical_rates.data.rates.idr = value;
-                }
-                Money::MYR(value) => {
-                    historical_rates.data.rates.myr = value;
-                }
-                Money::SGD(value) => {
-                    historical_rates.data.rates.sgd = value;
-                }
-                Money::THB(value) => {
-                    historical_rates.data.rates.thb = value;
-                }
-
-                // middle-east
-                Money::SAR(value) => {
-                    historical_rates.data.rates.sar = value;
-                }
-                Money::AED(value) => {
-                    historical_rates.data.rates.aed = value;
-                }
-                Money::KWD(value) => {
-                    historical_rates.data.rates.kwd = value;
-                }
-
-                // south asia
-                Money::INR(value) => {
-                    historical_rates.data.rates.inr = value;
-                }
-
-                // apac
-                Money::AUD(value) => {
-                    historical_rates.data.rates.aud = value;
-                }
-                Money::NZD(value) => {
-                    historical_rates.data.rates.nzd = value;
-                }
-
-                //// precious metals
-                Money::XAU(value) => {
-                    historical_rates.data.rates.xau = value;
-                }
-                Money::XAG(value) => {
-                    historical_rates.data.rates.xag = value;
-                }
-                Money::XPT(value) => {
-                    historical_rates.data.rates.xpt = value;
-                }
-
-                //// crypto
-                Money::BTC(value) => {
-                    historical_rates.data.rates.btc = value;
-                }
-                Money::ETH(value) => {
-                    historical_rates.data.rates.eth = value;
-                }
-                Money::SOL(value) => {
-                    historical_rates.data.rates.sol = value;
-                }
-                Money::XRP(value) => {
-                    historical_rates.data.rates.xrp = value;
-                }
-                Money::ADA(value) => {
-                    historical_rates.data.rates.ada = value;
-                }
-            }
+        let updated_at = Utc::now();
+        for (v, source) in new_rates {
+            historical_rates.record_provenance(v.currency(), &source, updated_at);
+            historical_rates.data.rates.set_rate(v);
         }
+        self.check_rates(&historical_rates)?;
 
         let json_string = serde_json::to_string_pretty(&historical_rates)
             .context("storage update historical parse input into json string")
             .as_internal_err()?;
 
         let historical_write_guard = self.fs.write().await;
-        let historical_write = historical_write_guard.historical();
-        let historical_write = historical_write.join(generate_historical_file_path(date));
+        let historical_dir = historical_write_guard.historical();
+        let (current, version) = self.historical_write_versions(historical_dir, date);
+        let historical_write =
+            historical_dir.join(generate_historical_version_file_path(date, version));
+        let before = file_checksum(
+            &historical_dir.join(generate_historical_version_file_path(date, current)),
+        )
+        .await?;
 
         let mut file = File::create(&historical_write)
             .await
@@ -364,6 +899,36 @@ impl ForexStorageImpl {
             .await
             .context("storage update historical flush")
             .as_internal_err()?;
+        self.invalidate_cached(&historical_write);
+
+        Self::write_signature(
+            self.signer.as_deref(),
+            historical_signature_path(historical_write_guard.metadata(), date, version),
+            &json_string,
+        )
+        .await?;
+
+        let change = RatesChange::new(
+            ChangeKind::Update,
+            RatesKind::Historical,
+            date,
+            relative_path(historical_write_guard.root(), &historical_write),
+            before,
+            Some(checksum(json_string.as_bytes())),
+        );
+        Self::append_change(historical_write_guard.metadata(), &change).await?;
+
+        let event = ProvenanceEvent {
+            kind: ProvenanceKind::Update,
+            source: historical_rates.source.clone(),
+            at: Utc::now(),
+            error: historical_rates.error.clone(),
+        };
+        Self::record_provenance(historical_write_guard.metadata(), date, event).await?;
+        if historical_rates.error.is_none() {
+            Self::record_sparklines(historical_write_guard.metadata(), &[&historical_rates.data])
+                .await?;
+        }
         drop(historical_write_guard);
 
         let updated_historical_rates = self
@@ -371,26 +936,194 @@ impl ForexStorageImpl {
             .await
             .context("storage update historical get historical")
             .as_internal_err()?;
+        self.publish(StorageEvent::HistoricalUpdated(Arc::new(
+            updated_historical_rates.clone(),
+        )));
 
         Ok(updated_historical_rates)
     }
 
+    async fn update_historical_bulk(
+        &self,
+        updates: Vec<HistoricalUpdate>,
+    ) -> ForexResult<Vec<RatesResponse<Rates>>> {
+        // corrections of the same date are merged in input order, grouped by year of historical dirs.
+        let mut by_year: BTreeMap<i32, BTreeMap<NaiveDate, Vec<(Money, String)>>> = BTreeMap::new();
+        for (date, new_rates) in updates {
+            by_year
+                .entry(date.year())
+                .or_default()
+                .entry(date.date_naive())
+                .or_default()
+                .extend(new_rates);
+        }
+
+        let fs_write = self.fs.write().await;
+        let historical_dir = fs_write.historical().clone();
+        let semaphore = Arc::new(Semaphore::new(HISTORICAL_BATCH_CONCURRENCY));
+        let updated_at = Utc::now();
+        let mut updated = vec![];
+        for (_, dates) in by_year {
+            let mut reads = JoinSet::new();
+            for (day, new_rates) in dates {
+                let date = day.and_time(NaiveTime::MIN).and_utc();
+                let (current, version) = self.historical_write_versions(&historical_dir, date);
+                let filepath =
+                    historical_dir.join(generate_historical_version_file_path(date, current));
+                let permit = semaphore
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .context("storage update historical bulk acquire permit")
+                    .as_internal_err()?;
+                reads.spawn(async move {
+                    let content = fs::read_to_string(&filepath).await;
+                    drop(permit);
+                    (date, version, new_rates, content)
+                });
+            }
+
+            let mut writes = JoinSet::new();
+            let mut year_updated = vec![];
+            while let Some(ret) = reads.join_next().await {
+                let (date, version, new_rates, content) = ret
+                    .context("storage update historical bulk join read task")
+                    .as_internal_err()?;
+                let content = content
+                    .context("storage update historical bulk read file")
+                    .as_internal_err()?;
+                let mut historical_rates: RatesResponse<Rates> = serde_json::from_str(&content)
+                    .context("storage update historical bulk parse file")
+                    .as_internal_err()?;
+                for (v, source) in new_rates {
+                    historical_rates.record_provenance(v.currency(), &source, updated_at);
+                    historical_rates.data.rates.set_rate(v);
+                }
+                self.check_rates(&historical_rates)?;
+
+                let json_string = serde_json::to_string_pretty(&historical_rates)
+                    .context("storage update historical bulk parse input into json string")
+                    .as_internal_err()?;
+                let filepath =
+                    historical_dir.join(generate_historical_version_file_path(date, version));
+                let change = RatesChange::new(
+                    ChangeKind::Update,
+                    RatesKind::Historical,
+                    date,
+                    relative_path(fs_write.root(), &filepath),
+                    Some(checksum(content.as_bytes())),
+                    Some(checksum(json_string.as_bytes())),
+                );
+                let signer = self.signer.clone();
+                let signature_path = historical_signature_path(fs_write.metadata(), date, version);
+                let permit = semaphore
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .context("storage update historical bulk acquire permit")
+                    .as_internal_err()?;
+                self.invalidate_cached(&filepath);
+                Self::append_change(fs_write.metadata(), &change).await?;
+                writes.spawn(async move {
+                    let ret = match Self::write_file_atomic(&filepath, json_string.clone()).await {
+                        Ok(()) => {
+                            Self::write_signature(signer.as_deref(), signature_path, &json_string)
+                                .await
+                        }
+                        Err(err) => Err(err),
+                    };
+                    drop(permit);
+                    ret
+                });
+                year_updated.push(historical_rates);
+            }
+            while let Some(ret) = writes.join_next().await {
+                ret.context("storage update historical bulk join write task")
+                    .as_internal_err()??;
+            }
+
+            year_updated.sort_by_key(|rates| rates.data.date);
+            for rates in &year_updated {
+                let event = ProvenanceEvent {
+                    kind: ProvenanceKind::Update,
+                    source: rates.source.clone(),
+                    at: updated_at,
+                    error: rates.error.clone(),
+                };
+                Self::record_provenance(fs_write.metadata(), rates.data.date, event).await?;
+            }
+            updated.extend(year_updated);
+        }
+
+        let valid_rates: Vec<&Rates> = updated
+            .iter()
+            .filter(|rates| rates.error.is_none())
+            .map(|rates| &rates.data)
+            .collect();
+        Self::record_sparklines(fs_write.metadata(), &valid_rates).await?;
+        drop(fs_write);
+
+        for rates in &updated {
+            self.publish(StorageEvent::HistoricalUpdated(Arc::new(rates.clone())));
+        }
+
+        Ok(updated)
+    }
+
     #[instrument(skip(self), ret)]
     async fn get_historical(&self, date: DateTime<Utc>) -> ForexResult<RatesResponse<Rates>> {
         let historical_read = self.fs.read().await;
         let historical_read = historical_read.historical();
-        let filepath = historical_read.join(&generate_historical_file_path(date));
+        let filepath = historical_read.join(generate_historical_version_file_path(
+            date,
+            current_historical_version(historical_read, date),
+        ));
 
-        let content = fs::read_to_string(&filepath)
-            .await
-            .context("storage get historical read file")
-            .as_internal_err()?;
+        self.read_rates_file(&filepath).await
+    }
 
-        let rates: RatesResponse<Rates> = serde_json::from_str(&content)
-            .context("storage get historical parse to json")
+    /// historical rates at the date rebased into base, cached along with the file if cache is enabled.
+    /// rates with error are returned as stored.
+    pub async fn get_historical_rebased(
+        &self,
+        date: DateTime<Utc>,
+        base: Currency,
+    ) -> ForexResult<RatesResponse<Rates>> {
+        let historical_read = self.fs.read().await;
+        let historical_dir = historical_read.historical();
+        let filepath = historical_dir.join(generate_historical_version_file_path(
+            date,
+            current_historical_version(historical_dir, date),
+        ));
+
+        let metadata = match &self.cache {
+            Some(cache) => {
+                let metadata = fs::metadata(&filepath)
+                    .await
+                    .context("storage get historical rebased read metadata")
+                    .as_internal_err()?;
+                if let Some(rates) = cache.get_rebased(&filepath, base, &metadata) {
+                    return Ok(rates);
+                }
+                Some(metadata)
+            }
+            None => None,
+        };
+
+        let rates = self.read_rates_file(&filepath).await?;
+        if rates.error.is_some() {
+            return Ok(rates);
+        }
+        let rebased = rates
+            .rebased(base)
+            .context("storage get historical rebased conversion")
             .as_internal_err()?;
 
-        Ok(rates)
+        if let (Some(cache), Some(metadata)) = (&self.cache, metadata) {
+            cache.insert_rebased(&filepath, base, &metadata, rebased.clone());
+        }
+
+        Ok(rebased)
     }
 
     #[instrument(skip(self), ret)]
@@ -399,14 +1132,66 @@ impl ForexStorageImpl {
         start_date: DateTime<Utc>,
         end_date: DateTime<Utc>,
     ) -> ForexResult<Vec<RatesResponse<Rates>>> {
-        let start_year = start_date.year();
-        let end_year = end_date.year();
+        let mut resp = vec![];
+
+        let historical_read = self.fs.read().await;
+        for (_, path) in
+            Self::historical_files(historical_read.historical(), start_date, end_date).await?
+        {
+            // read the content of the file
+            let content = fs::read_to_string(path)
+                .await
+                .context("get historical range read file content")
+                .as_internal_err()?;
+            let rates: RatesResponse<Rates> = serde_json::from_str(&content)
+                .context("get historical range parse content to json")
+                .as_internal_err()?;
 
+            resp.push(rates);
+        }
+
+        resp.sort_by_key(|v| v.data.date);
+
+        Ok(resp)
+    }
+
+    /// historical rates between start and end with only rates of the currencies decoded, others are zero.
+    #[instrument(skip(self))]
+    pub async fn get_historical_range_of(
+        &self,
+        currencies: &[Currency],
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) -> ForexResult<Vec<RatesResponse<Rates>>> {
         let mut resp = vec![];
 
         let historical_read = self.fs.read().await;
-        let historical_read_path = historical_read.historical();
-        let mut entries = read_dir(historical_read_path)
+        for (_, path) in
+            Self::historical_files(historical_read.historical(), start_date, end_date).await?
+        {
+            let content = fs::read_to_string(path)
+                .await
+                .context("get historical range of currencies read file content")
+                .as_internal_err()?;
+            resp.push(sparse_rates::decode_sparse(&content, currencies)?);
+        }
+
+        resp.sort_by_key(|v| v.data.date);
+
+        Ok(resp)
+    }
+
+    /// newest versions of historical rates files with dates between start and end, with their dates parsed from filenames, unordered.
+    async fn historical_files(
+        historical_dir: &Path,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) -> ForexResult<Vec<(DateTime<Utc>, PathBuf)>> {
+        let start_year = start_date.year();
+        let end_year = end_date.year();
+
+        let mut files: HashMap<DateTime<Utc>, (u32, PathBuf)> = HashMap::new();
+        let mut entries = read_dir(historical_dir)
             .await
             .context("get historical range reading historical path")
             .as_internal_err()?;
@@ -459,50 +1244,54 @@ impl ForexStorageImpl {
                         "some sub historical entries content are not files",
                     ));
                 }
-                let file_date: DateTime<Utc> = parse_historical_file_path(
-                    sub_historical_entry.file_name().to_string_lossy().trim(),
-                )
-                .ok_or(ForexError::internal_error(
-                    "get historical range parsing filename",
-                ))?;
+                let filename = sub_historical_entry.file_name();
+                let filename = filename.to_string_lossy();
+                // left by interrupted atomic write
+                if filename.ends_with(".tmp") {
+                    tracing::warn!(
+                        "get historical range skip temporary file {}",
+                        sub_historical_entry.path().display()
+                    );
+                    continue;
+                }
+                let (file_date, version) = parse_historical_file_version(filename.trim()).ok_or(
+                    ForexError::internal_error("get historical range parsing filename"),
+                )?;
 
                 if file_date < start_date || file_date > end_date {
                     continue;
                 }
 
-                // read the content of the file
-                let content = fs::read_to_string(sub_historical_entry.path())
-                    .await
-                    .context("get historical range read file content")
-                    .as_internal_err()?;
-                let rates: RatesResponse<Rates> = serde_json::from_str(&content)
-                    .context("get historical range parse content to json")
-                    .as_internal_err()?;
-
-                resp.push(rates);
+                let path = sub_historical_entry.path();
+                files
+                    .entry(file_date)
+                    .and_modify(|newest| {
+                        if version > newest.0 {
+                            *newest = (version, path.clone());
+                        }
+                    })
+                    .or_insert((version, path));
             }
         }
 
-        resp.sort_by_key(|v| v.data.date);
-
-        Ok(resp)
+        Ok(files
+            .into_iter()
+            .map(|(date, (_, path))| (date, path))
+            .collect())
     }
 
-    async fn get_latest_list(
-        &self,
-        page: u32,
-        size: u32,
+    /// latest rates snapshots with their dates truncated to seconds, ordered.
+    /// dates of files are parsed from their filenames without reading them, daily files are read for dates of their lines.
+    async fn latest_files(
+        latest_dir: &Path,
         order: Order,
-    ) -> ForexResult<RatesList<RatesResponse<Rates>>> {
-        let latest_read = self.fs.read().await;
-        let latest_read = latest_read.latest();
-
-        let mut entries = fs::read_dir(latest_read)
+    ) -> ForexResult<Vec<(DateTime<Utc>, LatestEntry)>> {
+        let mut entries = fs::read_dir(latest_dir)
             .await
             .context("storage get latest list read dir")
             .as_internal_err()?;
 
-        let mut files: Vec<RatesResponse<Rates>> = Vec::new();
+        let mut files = Vec::new();
         while let Some(entry) = entries
             .next_entry()
             .await
@@ -510,112 +1299,277 @@ impl ForexStorageImpl {
             .as_internal_err()?
         {
             let path = entry.path();
-            let content = tokio::fs::read_to_string(&path)
-                .await
-                .context("storage get latest list reading file")
-                .as_internal_err()?;
-            let resp: RatesResponse<Rates> = serde_json::from_str(&content)
-                .context("storage get latest list parse to json")
-                .as_internal_err()?;
-            files.push(resp);
+            if !path.is_file() {
+                continue;
+            }
+            let filename = entry.file_name().to_string_lossy().into_owned();
+            if LatestDailyFilename::parse(&filename).is_some() {
+                let content = fs::read_to_string(&path)
+                    .await
+                    .context("storage get latest list read daily file")
+                    .as_internal_err()?;
+                for line in content.lines().filter(|line| !line.trim().is_empty()) {
+                    let Ok(dated) = serde_json::from_str::<LatestLineDate>(line) else {
+                        tracing::warn!(
+                            "storage get latest list skip unparseable line of {}",
+                            path.display()
+                        );
+                        continue;
+                    };
+                    files.push((
+                        dated.data.date.trunc_subsecs(0),
+                        LatestEntry::Line(line.to_string()),
+                    ));
+                }
+                continue;
+            }
+            let Some(date) = parse_latest_file_path(&filename) else {
+                tracing::warn!(
+                    "storage get latest list skip unknown file {}",
+                    path.display()
+                );
+                continue;
+            };
+            files.push((date, LatestEntry::File(path)));
         }
 
-        if files.is_empty() {
-            return Ok(RatesList {
-                has_prev: false,
-                rates_list: vec![],
-                has_next: false,
-            });
+        files.sort();
+        if order == Order::DESC {
+            files.reverse();
         }
 
-        match order {
-            Order::ASC => files.sort_by_key(|rate| rate.data.date),
-            Order::DESC => files.sort_by(|a, b| b.data.date.cmp(&a.data.date)),
-        }
+        Ok(files)
+    }
 
-        let paginated = Self::paginate_rates_list(&files, page, size);
+    async fn read_latest_entry(entry: &LatestEntry) -> ForexResult<RatesResponse<Rates>> {
+        match entry {
+            LatestEntry::File(path) => Self::read_latest_file(path).await,
+            LatestEntry::Line(line) => Ok(serde_json::from_str(line)
+                .context("storage get latest list parse daily line to json")
+                .as_internal_err()?),
+        }
+    }
 
-        let resp = RatesList {
-            has_prev: paginated.has_prev,
-            rates_list: paginated.rates_list,
-            has_next: paginated.has_next,
-        };
+    async fn read_latest_file(path: &Path) -> ForexResult<RatesResponse<Rates>> {
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .context("storage get latest list reading file")
+            .as_internal_err()?;
+        let resp: RatesResponse<Rates> = serde_json::from_str(&content)
+            .context("storage get latest list parse to json")
+            .as_internal_err()?;
 
         Ok(resp)
     }
 
-    async fn get_historical_list(
+    /// latest rates files are read from the newest until the one with the id is found.
+    pub async fn get_latest_by_id(&self, id: Uuid) -> ForexResult<Option<RatesResponse<Rates>>> {
+        let latest_read = self.fs.read().await;
+        let files = Self::latest_files(latest_read.latest(), Order::DESC).await?;
+        for (_, entry) in files {
+            let rates = Self::read_latest_entry(&entry).await?;
+            if rates.id == id {
+                return Ok(Some(rates));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// when ordered by data date only files of the requested page are read.
+    /// poll date is only known from file content, so all files are read when ordered by it.
+    async fn get_latest_list(
         &self,
         page: u32,
         size: u32,
         order: Order,
+        order_by: OrderBy,
     ) -> ForexResult<RatesList<RatesResponse<Rates>>> {
+        let latest_read = self.fs.read().await;
+        let files = Self::latest_files(latest_read.latest(), order).await?;
+        if order_by == OrderBy::PollDate {
+            let mut rates_list = Vec::with_capacity(files.len());
+            for (_, entry) in &files {
+                rates_list.push(Self::read_latest_entry(entry).await?);
+            }
+            sort_rates(&mut rates_list, order, order_by);
+
+            return Ok(Self::paginate_rates_list(&rates_list, page, size));
+        }
+        let page_files = Self::paginate_rates_list(&files, page, size);
+
+        let mut rates_list = Vec::with_capacity(page_files.rates_list.len());
+        for (_, entry) in &page_files.rates_list {
+            rates_list.push(Self::read_latest_entry(entry).await?);
+        }
+        sort_rates(&mut rates_list, order, order_by);
+
+        Ok(RatesList {
+            has_prev: page_files.has_prev,
+            rates_list,
+            has_next: page_files.has_next,
+        })
+    }
+
+    /// when ordered by data date, files up to the cursor are skipped by their filenames, then read until the page and its next one are found.
+    /// all files are read when ordered by poll date.
+    pub(crate) async fn get_latest_page(
+        &self,
+        cursor: Option<Cursor>,
+        size: u32,
+        order: Order,
+        order_by: OrderBy,
+    ) -> ForexResult<RatesPage<RatesResponse<Rates>>> {
+        let latest_read = self.fs.read().await;
+        let files = Self::latest_files(latest_read.latest(), order).await?;
+
+        // filenames carry rates date truncated to seconds, files of cursor's second are read and compared precisely
+        let by_filename = order_by == OrderBy::DataDate;
+        let boundary = cursor
+            .filter(|_| by_filename)
+            .map(|cursor| cursor.date().trunc_subsecs(0));
+        let is_after_cursor = |rates: &RatesResponse<Rates>| match (cursor, order) {
+            (None, _) => true,
+            (Some(cursor), Order::ASC) => Cursor::of(rates).key(order_by) > cursor.key(order_by),
+            (Some(cursor), Order::DESC) => Cursor::of(rates).key(order_by) < cursor.key(order_by),
+        };
+
+        let mut rates = vec![];
+        let mut after_cursor = 0;
+        for (date, entry) in files {
+            let skip = match (boundary, order) {
+                (None, _) => false,
+                (Some(boundary), Order::ASC) => date < boundary,
+                (Some(boundary), Order::DESC) => date > boundary,
+            };
+            if skip {
+                continue;
+            }
+
+            let rate = Self::read_latest_entry(&entry).await?;
+            if is_after_cursor(&rate) {
+                after_cursor += 1;
+            }
+            rates.push(rate);
+
+            // one more than page size tells whether there's next page
+            if by_filename && after_cursor > size as usize {
+                break;
+            }
+        }
+
+        Ok(RatesPage::after(rates, cursor, size, order, order_by))
+    }
+
+    async fn read_historical_list(&self) -> ForexResult<Vec<RatesResponse<Rates>>> {
         let historical_read = self.fs.read().await;
         let historical_read = historical_read.historical();
 
-        let mut entries = fs::read_dir(historical_read)
-            .await
-            .context("storage get historical list read dir")
-            .as_internal_err()?;
-
         let mut files: Vec<RatesResponse<Rates>> = Vec::new();
-        while let Some(entry) = entries
-            .next_entry()
-            .await
-            .context("storage get historical list reading entries")
-            .as_internal_err()?
+        for (_, path) in Self::historical_files(
+            historical_read,
+            DateTime::<Utc>::MIN_UTC,
+            DateTime::<Utc>::MAX_UTC,
+        )
+        .await?
         {
-            let path = entry.path();
-            let mut sub_entries = fs::read_dir(&path)
-                .await
-                .context("storage get historical list read sub entry")
-                .as_internal_err()?;
-            while let Some(sub_entry) = sub_entries
-                .next_entry()
-                .await
-                .context("storage get historical list read subentries")
-                .as_internal_err()?
-            {
-                let sub_entry_path = sub_entry.path();
-                let content = tokio::fs::read_to_string(&sub_entry_path)
-                    .await
-                    .context("storage get historical list read subentry content")
-                    .as_internal_err()?;
-                let resp: RatesResponse<Rates> = serde_json::from_str(&content)
-                    .context("storage get historical list parse subentry to json")
-                    .as_internal_err()?;
-                files.push(resp);
-            }
+            files.push(Self::read_historical_file(&path).await?);
         }
 
-        if files.is_empty() {
-            return Ok(RatesList {
-                has_prev: false,
-                rates_list: vec![],
-                has_next: false,
-            });
-        }
+        Ok(files)
+    }
 
-        match order {
-            Order::ASC => files.sort_by_key(|rate| rate.data.date),
-            Order::DESC => files.sort_by(|a, b| b.data.date.cmp(&a.data.date)),
-        }
+    async fn read_historical_file(path: &Path) -> ForexResult<RatesResponse<Rates>> {
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .context("storage get historical list read subentry content")
+            .as_internal_err()?;
+        let resp: RatesResponse<Rates> = serde_json::from_str(&content)
+            .context("storage get historical list parse subentry to json")
+            .as_internal_err()?;
+
+        Ok(resp)
+    }
+
+    async fn get_historical_list(
+        &self,
+        page: u32,
+        size: u32,
+        order: Order,
+        order_by: OrderBy,
+    ) -> ForexResult<RatesList<RatesResponse<Rates>>> {
+        let mut files = self.read_historical_list().await?;
+        sort_rates(&mut files, order, order_by);
+
+        Ok(Self::paginate_rates_list(&files, page, size))
+    }
+
+    /// when ordered by data date, files before the cursor are skipped by their filenames, then read until the page and its next one are found.
+    /// all files are read when ordered by poll date.
+    pub(crate) async fn get_historical_page(
+        &self,
+        cursor: Option<Cursor>,
+        size: u32,
+        order: Order,
+        order_by: OrderBy,
+    ) -> ForexResult<RatesPage<RatesResponse<Rates>>> {
+        let historical_read = self.fs.read().await;
 
-        let paginated = Self::paginate_rates_list(&files, page, size);
+        // filenames carry rates date truncated to day, files of cursor's day are read and compared precisely
+        let by_filename = order_by == OrderBy::DataDate;
+        let boundary = cursor.filter(|_| by_filename).map(|cursor| {
+            cursor
+                .date()
+                .date_naive()
+                .and_time(NaiveTime::MIN)
+                .and_utc()
+        });
+        let (start_date, end_date) = match (boundary, order) {
+            (None, _) => (DateTime::<Utc>::MIN_UTC, DateTime::<Utc>::MAX_UTC),
+            (Some(boundary), Order::ASC) => (boundary, DateTime::<Utc>::MAX_UTC),
+            (Some(boundary), Order::DESC) => (DateTime::<Utc>::MIN_UTC, boundary),
+        };
+        let mut files =
+            Self::historical_files(historical_read.historical(), start_date, end_date).await?;
+        files.sort();
+        if order == Order::DESC {
+            files.reverse();
+        }
 
-        let resp = RatesList {
-            has_prev: paginated.has_prev,
-            rates_list: paginated.rates_list,
-            has_next: paginated.has_next,
+        let is_after_cursor = |rates: &RatesResponse<Rates>| match (cursor, order) {
+            (None, _) => true,
+            (Some(cursor), Order::ASC) => Cursor::of(rates).key(order_by) > cursor.key(order_by),
+            (Some(cursor), Order::DESC) => Cursor::of(rates).key(order_by) < cursor.key(order_by),
         };
 
-        Ok(resp)
+        let mut rates = vec![];
+        let mut after_cursor = 0;
+        for (_, path) in files {
+            let rate = Self::read_historical_file(&path).await?;
+            if is_after_cursor(&rate) {
+                after_cursor += 1;
+            }
+            rates.push(rate);
+
+            // one more than page size tells whether there's next page
+            if by_filename && after_cursor > size as usize {
+                break;
+            }
+        }
+
+        Ok(RatesPage::after(rates, cursor, size, order, order_by))
     }
 
     // deletions impls
     async fn clear_latest(&self) -> ForexResult<()> {
-        let latest_write = self.fs.write().await;
-        let latest_write = latest_write.latest();
+        self.clear_latest_by(global::config().forex_latest_rotation)
+            .await
+    }
+
+    /// delete or archive all but the newest latest rates file, by the rotation.
+    async fn clear_latest_by(&self, rotation: LatestRotation) -> ForexResult<()> {
+        let fs_write = self.fs.write().await;
+        let latest_write = fs_write.latest();
 
         let mut entries = fs::read_dir(latest_write)
             .await
@@ -641,157 +1595,2341 @@ impl ForexStorageImpl {
             }
         }
 
-        // Sort files by filename (ascending order)
-        files.sort_by(|a, b| a.0.cmp(&b.0));
+        // Sort files by date in filename (ascending order), daily files by the end of their day
+        files.sort_by_cached_key(|(filename, _)| {
+            let date = parse_latest_file_path(filename).or_else(|| {
+                LatestDailyFilename::parse(filename)
+                    .and_then(|daily| daily.day().and_hms_opt(23, 59, 59))
+                    .map(|date| date.and_utc())
+            });
+            (date, filename.clone())
+        });
 
-        for (_filename, entry) in files.iter().take(files.len().saturating_sub(1)) {
-            fs::remove_file(entry.path())
+        for (filename, entry) in files.iter().take(files.len().saturating_sub(1)) {
+            let content = fs::read(entry.path())
                 .await
-                .context("storage clear latest read dir")
+                .context("storage clear latest read file")
                 .as_internal_err()?;
+            let change_date = serde_json::from_slice::<RatesResponse<Rates>>(&content)
+                .map(|rates| rates.data.date)
+                .ok()
+                .or_else(|| latest_file_date(filename));
+            let journal = |kind: ChangeKind, after: Option<String>| {
+                change_date.map(|date| {
+                    RatesChange::new(
+                        kind,
+                        RatesKind::Latest,
+                        date,
+                        relative_path(fs_write.root(), &entry.path()),
+                        Some(checksum(&content)),
+                        after,
+                    )
+                })
+            };
+
+            let change = match rotation {
+                LatestRotation::Delete => {
+                    fs::remove_file(entry.path())
+                        .await
+                        .context("storage clear latest read dir")
+                        .as_internal_err()?;
+                    journal(ChangeKind::Delete, None)
+                }
+                LatestRotation::Archive => {
+                    let Some(archive_path) = generate_latest_archive_path(filename) else {
+                        tracing::warn!(
+                            "storage clear latest skip archiving unknown file {filename}"
+                        );
+                        continue;
+                    };
+                    let archive_path = latest_write.join(archive_path);
+                    if let Some(dir) = archive_path.parent()
+                        && !dir.is_dir()
+                    {
+                        tokio::fs::create_dir_all(dir)
+                            .await
+                            .context("storage clear latest create archive dir")
+                            .as_internal_err()?;
+                    }
+                    fs::rename(entry.path(), &archive_path)
+                        .await
+                        .context("storage clear latest move file into archive")
+                        .as_internal_err()?;
+                    journal(ChangeKind::Archive, Some(checksum(&content)))
+                }
+            };
+
+            match change {
+                Some(change) => Self::append_change(fs_write.metadata(), &change).await?,
+                None => {
+                    tracing::warn!(
+                        "storage clear latest skip journaling unreadable file {filename}"
+                    )
+                }
+            }
         }
 
         Ok(())
     }
 
-    fn paginate_rates_list<T>(rates: &[T], page: u32, size: u32) -> RatesList<T>
-    where
-        T: Clone,
-    {
-        let start = (page.saturating_sub(1) * size) as usize;
-        let end = (start + size as usize).min(rates.len());
+    /// delete oldest archived latest rates files with their signatures, by dates in their filenames,
+    /// until at least the bytes are freed. archived files with unknown filenames are kept.
+    #[instrument(skip(self))]
+    async fn prune_latest_archive(&self, bytes: u64) -> ForexResult<Vec<String>> {
+        let fs_write = self.fs.write().await;
+        let archive_dir = fs_write.latest().join(LATEST_ARCHIVE_DIR_NAME);
+        if !archive_dir.is_dir() {
+            return Ok(vec![]);
+        }
 
-        let has_prev = start > 0;
-        let rates_list = rates[start..end].to_vec();
-        let has_next = end < rates.len(); // If there's more data beyond this page
+        let mut files: Vec<(DateTime<Utc>, PathBuf)> = collect_json_files(&archive_dir)
+            .await?
+            .into_iter()
+            .filter_map(|path| {
+                let date = path
+                    .file_name()
+                    .and_then(|filename| filename.to_str())
+                    .and_then(latest_file_date)?;
+                Some((date, path))
+            })
+            .collect();
+        files.sort();
 
-        RatesList {
-            has_prev,
-            rates_list,
-            has_next,
+        let mut freed = 0;
+        let mut pruned = vec![];
+        for (date, path) in files {
+            if freed >= bytes {
+                break;
+            }
+            let content = fs::read(&path)
+                .await
+                .context("storage prune latest archive read file")
+                .as_internal_err()?;
+            fs::remove_file(&path)
+                .await
+                .context("storage prune latest archive remove file")
+                .as_internal_err()?;
+            freed += content.len() as u64;
+            if let Some(filename) = path.file_name().and_then(|filename| filename.to_str()) {
+                let signature_path = latest_signature_path(fs_write.metadata(), filename);
+                match fs::remove_file(&signature_path).await {
+                    Ok(()) => {}
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(err) => Err(err)
+                        .context("storage prune latest archive remove signature")
+                        .as_internal_err()?,
+                }
+            }
+
+            let relative = relative_path(fs_write.root(), &path);
+            let change = RatesChange::new(
+                ChangeKind::Delete,
+                RatesKind::Latest,
+                date,
+                relative.clone(),
+                Some(checksum(&content)),
+                None,
+            );
+            Self::append_change(fs_write.metadata(), &change).await?;
+            pruned.push(relative);
         }
+
+        Ok(pruned)
     }
-}
 
-/// generate path to file from parent
-fn generate_latest_file_path(date: DateTime<Utc>) -> String {
-    let year = date.year();
-    let month = date.month();
-    let day = date.day();
-    let hour = date.hour();
-    let minute = date.minute();
-    let second = date.second();
+    /// disk usage of latest(archive included), historical, metadata and tenants directories.
+    #[instrument(skip(self))]
+    pub(crate) async fn stats(&self) -> ForexResult<StorageStats> {
+        let fs_read = self.fs.read().await;
+        let subtrees = vec![
+            subtree_stats("latest", fs_read.latest(), latest_file_date).await?,
+            subtree_stats(
+                "historical",
+                fs_read.historical(),
+                parse_historical_file_path,
+            )
+            .await?,
+            subtree_stats("metadata", fs_read.metadata(), |_| None).await?,
+            subtree_stats(
+                TENANTS_DIR_NAME,
+                &fs_read.root().join(TENANTS_DIR_NAME),
+                |_| None,
+            )
+            .await?,
+        ];
 
-    let tostr = |n: u32| -> String {
-        if n < 10 {
-            return format!("0{n}");
-        }
-        n.to_string()
-    };
+        Ok(StorageStats::new(subtrees))
+    }
 
-    let filename = LATEST_FILENAME_FORMAT
-        .replace("{YYYY}", year.to_string().as_str())
-        .replace("{MM}", tostr(month).as_str())
-        .replace("{DD}", tostr(day).as_str())
-        .replace("{hh}", tostr(hour).as_str())
-        .replace("{mm}", tostr(minute).as_str())
-        .replace("{ss}", tostr(second).as_str());
+    /// append change into journal file of its month, which is never rewritten.
+    /// must be invoked while holding storage write lock.
+    async fn append_change(metadata_dir: &Path, change: &RatesChange) -> ForexResult<()> {
+        let mut line = serde_json::to_string(change)
+            .context("storage append change parse into json string")
+            .as_internal_err()?;
+        line.push('\n');
+
+        let filepath = journal_path(metadata_dir, change.date);
+        if let Some(dir) = filepath.parent()
+            && !dir.is_dir()
+        {
+            fs::create_dir_all(dir)
+                .await
+                .context("storage append change create journal dir")
+                .as_internal_err()?;
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&filepath)
+            .await
+            .context("storage append change open journal")
+            .as_internal_err()?;
+        file.write_all(line.as_bytes())
+            .await
+            .context("storage append change write")
+            .as_internal_err()?;
+        file.flush()
+            .await
+            .context("storage append change flush")
+            .as_internal_err()?;
+
+        Ok(())
+    }
+
+    /// changes of the date are read from journal file of its month, after those of journal written before rotation.
+    #[instrument(skip(self))]
+    pub(crate) async fn get_changes(&self, date: DateTime<Utc>) -> ForexResult<Vec<RatesChange>> {
+        let fs_read = self.fs.read().await;
+        let day = date.date_naive();
+        let mut changes = vec![];
+        for filepath in [
+            fs_read.metadata().join(JOURNAL_FILENAME),
+            journal_path(fs_read.metadata(), date),
+        ] {
+            let content = match fs::read_to_string(&filepath).await {
+                Ok(content) => content,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => Err(err)
+                    .context("storage get changes read journal")
+                    .as_internal_err()?,
+            };
+            for line in content.lines().filter(|line| !line.trim().is_empty()) {
+                let change: RatesChange = serde_json::from_str(line)
+                    .context("storage get changes parse to json")
+                    .as_internal_err()?;
+                if change.date.date_naive() == day {
+                    changes.push(change);
+                }
+            }
+        }
+
+        Ok(changes)
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn replay_changes(
+        &self,
+        date: DateTime<Utc>,
+    ) -> ForexResult<RatesChangeReplay> {
+        let changes: Vec<RatesChange> = self
+            .get_changes(date)
+            .await?
+            .into_iter()
+            .filter(|change| change.rates_kind == RatesKind::Historical)
+            .collect();
+
+        let fs_read = self.fs.read().await;
+        let historical_dir = fs_read.historical();
+        let filepath = historical_dir.join(generate_historical_version_file_path(
+            date,
+            current_historical_version(historical_dir, date),
+        ));
+        let current = file_checksum(&filepath).await?;
+
+        Ok(RatesChangeReplay::replay(date, changes, current))
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_historical_versions(
+        &self,
+        date: DateTime<Utc>,
+    ) -> ForexResult<Vec<HistoricalVersion>> {
+        let fs_read = self.fs.read().await;
+        let historical_dir = fs_read.historical();
+        let current = current_historical_version(historical_dir, date);
+
+        let mut versions = vec![];
+        for version in 1..=current {
+            let filepath =
+                historical_dir.join(generate_historical_version_file_path(date, version));
+            let content = match fs::read(&filepath).await {
+                Ok(content) => content,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => Err(err)
+                    .context("storage get historical versions read file")
+                    .as_internal_err()?,
+            };
+            let written_at = fs::metadata(&filepath)
+                .await
+                .and_then(|metadata| metadata.modified())
+                .context("storage get historical versions read modified time")
+                .as_internal_err()?;
+            versions.push(HistoricalVersion {
+                date,
+                version,
+                path: relative_path(fs_read.root(), &filepath),
+                checksum: checksum(&content),
+                size: content.len() as u64,
+                written_at: written_at.into(),
+                current: version == current,
+            });
+        }
+
+        Ok(versions)
+    }
+
+    #[instrument(skip(self), ret)]
+    pub(crate) async fn get_historical_version(
+        &self,
+        date: DateTime<Utc>,
+        version: u32,
+    ) -> ForexResult<RatesResponse<Rates>> {
+        let fs_read = self.fs.read().await;
+        let filepath = fs_read
+            .historical()
+            .join(generate_historical_version_file_path(date, version));
+        if version == 0 || !filepath.is_file() {
+            return Err(ForexError::client_error(&format!(
+                "historical rates of {} have no version {}",
+                date.date_naive(),
+                version
+            )));
+        }
+
+        self.read_rates_file(&filepath).await
+    }
+
+    /// write provenance event of historical rates at date into its sidecar metadata file.
+    /// must be invoked while holding storage write lock.
+    async fn record_provenance(
+        metadata_dir: &Path,
+        date: DateTime<Utc>,
+        event: ProvenanceEvent,
+    ) -> ForexResult<()> {
+        let filepath = metadata_dir
+            .join(HISTORICAL_METADATA_DIR_NAME)
+            .join(generate_historical_file_path(date));
+
+        let provenance = match fs::read_to_string(&filepath).await {
+            Ok(content) => {
+                let mut provenance: RatesProvenance = serde_json::from_str(&content)
+                    .context("storage record provenance parse to json")
+                    .as_internal_err()?;
+                provenance.record(event);
+                provenance
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                RatesProvenance::new(date, event)
+            }
+            Err(err) => Err(err)
+                .context("storage record provenance read file")
+                .as_internal_err()?,
+        };
+
+        if let Some(dir) = filepath.parent()
+            && !dir.is_dir()
+        {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .context("storage record provenance create year dir")
+                .as_internal_err()?;
+        }
+
+        let json_string = serde_json::to_string_pretty(&provenance)
+            .context("storage record provenance parse into json string")
+            .as_internal_err()?;
+
+        let mut file = File::create(&filepath)
+            .await
+            .context("storage record provenance create filepath")
+            .as_internal_err()?;
+        file.write_all(json_string.as_bytes())
+            .await
+            .context("storage record provenance write content")
+            .as_internal_err()?;
+        file.flush()
+            .await
+            .context("storage record provenance flush")
+            .as_internal_err()?;
+
+        Self::set_permission(&filepath).await?;
+
+        Ok(())
+    }
+
+    /// sign content of rates file and write the signature, no-op without signer.
+    /// must be invoked while holding storage write lock.
+    async fn write_signature(
+        signer: Option<&RatesSigner>,
+        signature_path: PathBuf,
+        content: &str,
+    ) -> ForexResult<()> {
+        let Some(signer) = signer else {
+            return Ok(());
+        };
+
+        if let Some(dir) = signature_path.parent()
+            && !dir.is_dir()
+        {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .context("storage write signature create signatures dir")
+                .as_internal_err()?;
+        }
+
+        Self::write_file_atomic(&signature_path, signer.sign(content.as_bytes())).await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn migrate_latest_filenames(&self) -> ForexResult<Vec<String>> {
+        let fs_write = self.fs.write().await;
+        let mut migrated = vec![];
+
+        // archived latest files included, their signatures are renamed along
+        for path in collect_json_files(fs_write.latest()).await? {
+            let filename = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            let Some(outdated) = LatestFilename::parse(&filename).filter(|f| f.is_outdated())
+            else {
+                continue;
+            };
+            let current = outdated.to_current().to_string();
+            let target = path.with_file_name(&current);
+            if target.exists() {
+                tracing::warn!(
+                    "storage migrate latest filenames skip {filename}, {current} already exists"
+                );
+                continue;
+            }
+
+            fs::rename(&path, &target)
+                .await
+                .context("storage migrate latest filenames rename file")
+                .as_internal_err()?;
+            let signature_path = latest_signature_path(fs_write.metadata(), &filename);
+            if signature_path.is_file() {
+                fs::rename(
+                    &signature_path,
+                    latest_signature_path(fs_write.metadata(), &current),
+                )
+                .await
+                .context("storage migrate latest filenames rename signature")
+                .as_internal_err()?;
+            }
+            migrated.push(relative_path(fs_write.root(), &target));
+        }
+
+        Ok(migrated)
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn verify_signatures(&self, public_key: &str) -> ForexResult<SignatureReport> {
+        let verifier = RatesVerifier::from_public_key_hex(public_key)?;
+        let fs_read = self.fs.read().await;
+        let mut report = SignatureReport::default();
+
+        // archived latest files keep their filenames, hence their signatures
+        for path in collect_json_files(fs_read.latest()).await? {
+            let filename = path.file_name().unwrap_or_default().to_string_lossy();
+            let signature_path = latest_signature_path(fs_read.metadata(), &filename);
+            Self::verify_file(
+                &verifier,
+                fs_read.root(),
+                &path,
+                &signature_path,
+                &mut report,
+            )
+            .await?;
+        }
+
+        for path in collect_json_files(fs_read.historical()).await? {
+            let filename = path.file_name().unwrap_or_default().to_string_lossy();
+            let Some((date, version)) = parse_historical_file_version(&filename) else {
+                continue;
+            };
+            let signature_path = historical_signature_path(fs_read.metadata(), date, version);
+            Self::verify_file(
+                &verifier,
+                fs_read.root(),
+                &path,
+                &signature_path,
+                &mut report,
+            )
+            .await?;
+        }
+
+        Ok(report)
+    }
+
+    async fn verify_file(
+        verifier: &RatesVerifier,
+        root: &Path,
+        path: &Path,
+        signature_path: &Path,
+        report: &mut SignatureReport,
+    ) -> ForexResult<()> {
+        let relative_path = relative_path(root, path);
+
+        let signature = match fs::read_to_string(signature_path).await {
+            Ok(signature) => signature,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                report.unsigned.push(relative_path);
+                return Ok(());
+            }
+            Err(err) => Err(err)
+                .context("storage verify signatures read signature")
+                .as_internal_err()?,
+        };
+        let content = fs::read(path)
+            .await
+            .context("storage verify signatures read file")
+            .as_internal_err()?;
+
+        if verifier.verify(&content, &signature) {
+            report.verified += 1;
+        } else {
+            report.invalid.push(relative_path);
+        }
+
+        Ok(())
+    }
+
+    /// record rates into sparkline of each currency, zero rates of unsupported currencies are skipped.
+    async fn record_sparklines(metadata_dir: &Path, rates: &[&Rates]) -> ForexResult<()> {
+        if rates.is_empty() {
+            return Ok(());
+        }
+        let dir = metadata_dir.join(SPARKLINES_METADATA_DIR_NAME);
+        if !dir.is_dir() {
+            tokio::fs::create_dir_all(&dir)
+                .await
+                .context("storage record sparklines create sparklines dir")
+                .as_internal_err()?;
+        }
+
+        for currency in Currency::iter() {
+            let filepath = dir.join(format!("{}.json", currency.code()));
+            let mut sparkline = Self::read_sparkline(&filepath, currency).await?;
+            for rates in rates {
+                let rate = rates.rates.rate(currency);
+                if rate.is_zero() {
+                    continue;
+                }
+                sparkline.record(
+                    RatePoint {
+                        date: rates.date,
+                        rate,
+                    },
+                    SPARKLINE_WINDOW,
+                );
+            }
+            if sparkline.points.is_empty() {
+                continue;
+            }
+
+            let json_string = serde_json::to_string(&sparkline)
+                .context("storage record sparklines parse into json string")
+                .as_internal_err()?;
+            Self::write_file_atomic(&filepath, json_string).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn read_sparkline(filepath: &Path, currency: Currency) -> ForexResult<Sparkline> {
+        let sparkline = match fs::read_to_string(filepath).await {
+            Ok(content) => serde_json::from_str(&content)
+                .context("storage read sparkline parse to json")
+                .as_internal_err()?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Sparkline::new(currency),
+            Err(err) => Err(err)
+                .context("storage read sparkline read file")
+                .as_internal_err()?,
+        };
+
+        Ok(sparkline)
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_sparkline(&self, currency: Currency) -> ForexResult<Sparkline> {
+        let fs_read = self.fs.read().await;
+        let filepath = fs_read
+            .metadata()
+            .join(SPARKLINES_METADATA_DIR_NAME)
+            .join(format!("{}.json", currency.code()));
+
+        Self::read_sparkline(&filepath, currency).await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn rebuild_sparklines(&self) -> ForexResult<()> {
+        let latest_historical = self
+            .get_historical_list(1, SPARKLINE_WINDOW as u32, Order::DESC, OrderBy::DataDate)
+            .await?;
+        let rates: Vec<&Rates> = latest_historical
+            .rates_list
+            .iter()
+            .filter(|rates| rates.error.is_none())
+            .map(|rates| &rates.data)
+            .collect();
+
+        let fs_write = self.fs.write().await;
+        let dir = fs_write.metadata().join(SPARKLINES_METADATA_DIR_NAME);
+        if dir.is_dir() {
+            fs::remove_dir_all(&dir)
+                .await
+                .context("storage rebuild sparklines remove sparklines dir")
+                .as_internal_err()?;
+        }
+
+        Self::record_sparklines(fs_write.metadata(), &rates).await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn record_provider_call(
+        &self,
+        provider: &str,
+        sample: ProviderCallSample,
+    ) -> ForexResult<()> {
+        let fs_write = self.fs.write().await;
+        let dir = fs_write.metadata().join(PROVIDERS_METADATA_DIR_NAME);
+        if !dir.is_dir() {
+            tokio::fs::create_dir_all(&dir)
+                .await
+                .context("storage record provider call create providers dir")
+                .as_internal_err()?;
+        }
+        let filepath = dir.join(format!("{}.json", provider));
+
+        let mut health = match fs::read_to_string(&filepath).await {
+            Ok(content) => serde_json::from_str(&content)
+                .context("storage record provider call parse to json")
+                .as_internal_err()?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => ProviderHealth::new(provider),
+            Err(err) => Err(err)
+                .context("storage record provider call read file")
+                .as_internal_err()?,
+        };
+        health.record(sample, PROVIDER_HEALTH_WINDOW);
+
+        let json_string = serde_json::to_string(&health)
+            .context("storage record provider call parse into json string")
+            .as_internal_err()?;
+
+        Self::write_file_atomic(&filepath, json_string).await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_provider_health(&self) -> ForexResult<Vec<ProviderHealth>> {
+        let fs_read = self.fs.read().await;
+        let dir = fs_read.metadata().join(PROVIDERS_METADATA_DIR_NAME);
+        if !dir.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut entries = fs::read_dir(&dir)
+            .await
+            .context("storage get provider health read dir")
+            .as_internal_err()?;
+        let mut healths = vec![];
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .context("storage get provider health read entries")
+            .as_internal_err()?
+        {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let content = fs::read_to_string(&path)
+                .await
+                .context("storage get provider health read file")
+                .as_internal_err()?;
+            let health: ProviderHealth = serde_json::from_str(&content)
+                .context("storage get provider health parse to json")
+                .as_internal_err()?;
+            healths.push(health);
+        }
+        healths.sort_by(|a, b| a.provider.cmp(&b.provider));
+
+        Ok(healths)
+    }
+
+    #[instrument(skip(self), ret)]
+    pub(crate) async fn get_historical_provenance(
+        &self,
+        date: DateTime<Utc>,
+    ) -> ForexResult<RatesProvenance> {
+        let fs_read = self.fs.read().await;
+        let filepath = fs_read
+            .metadata()
+            .join(HISTORICAL_METADATA_DIR_NAME)
+            .join(generate_historical_file_path(date));
+
+        let content = fs::read_to_string(&filepath)
+            .await
+            .context("storage get historical provenance read file")
+            .as_internal_err()?;
+
+        let provenance: RatesProvenance = serde_json::from_str(&content)
+            .context("storage get historical provenance parse to json")
+            .as_internal_err()?;
+
+        Ok(provenance)
+    }
+
+    #[instrument(skip(self), ret)]
+    pub(crate) async fn get_modified_historical_dates(
+        &self,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) -> ForexResult<Vec<DateTime<Utc>>> {
+        let fs_read = self.fs.read().await;
+        let provenance_dir = fs_read.metadata().join(HISTORICAL_METADATA_DIR_NAME);
+
+        let mut dates = vec![];
+        if !provenance_dir.is_dir() {
+            return Ok(dates);
+        }
+
+        let mut entries = read_dir(&provenance_dir)
+            .await
+            .context("storage get modified historical dates read dir")
+            .as_internal_err()?;
+        while let Some(year_entry) = entries
+            .next_entry()
+            .await
+            .context("storage get modified historical dates read entries")
+            .as_internal_err()?
+        {
+            let Ok(year) = year_entry
+                .file_name()
+                .to_string_lossy()
+                .trim()
+                .parse::<i32>()
+            else {
+                continue;
+            };
+            if year < start_date.year() || year > end_date.year() {
+                continue;
+            }
+
+            let mut year_entries = read_dir(year_entry.path())
+                .await
+                .context("storage get modified historical dates read year dir")
+                .as_internal_err()?;
+            while let Some(entry) = year_entries
+                .next_entry()
+                .await
+                .context("storage get modified historical dates read year entries")
+                .as_internal_err()?
+            {
+                let Some(file_date) =
+                    parse_historical_file_path(entry.file_name().to_string_lossy().trim())
+                else {
+                    continue;
+                };
+                if file_date < start_date || file_date > end_date {
+                    continue;
+                }
+
+                let content = fs::read_to_string(entry.path())
+                    .await
+                    .context("storage get modified historical dates read file")
+                    .as_internal_err()?;
+                let provenance: RatesProvenance = serde_json::from_str(&content)
+                    .context("storage get modified historical dates parse to json")
+                    .as_internal_err()?;
+
+                if provenance.is_modified() {
+                    dates.push(file_date);
+                }
+            }
+        }
+
+        dates.sort();
+
+        Ok(dates)
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn insert_job_run(&self, run: &JobRun) -> ForexResult<()> {
+        let json_string = serde_json::to_string_pretty(run)
+            .context("storage insert job run parse into json string")
+            .as_internal_err()?;
+
+        let fs_write = self.fs.write().await;
+        let filepath = fs_write
+            .metadata()
+            .join(JOBS_METADATA_DIR_NAME)
+            .join(generate_job_run_file_path(run));
+
+        if let Some(dir) = filepath.parent()
+            && !dir.is_dir()
+        {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .context("storage insert job run create job dir")
+                .as_internal_err()?;
+        }
+
+        let mut file = File::create(&filepath)
+            .await
+            .context("storage insert job run create filepath")
+            .as_internal_err()?;
+        file.write_all(json_string.as_bytes())
+            .await
+            .context("storage insert job run write content")
+            .as_internal_err()?;
+        file.flush()
+            .await
+            .context("storage insert job run flush")
+            .as_internal_err()?;
+
+        Self::set_permission(&filepath).await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_job_runs(
+        &self,
+        job: Option<Job>,
+        limit: u32,
+    ) -> ForexResult<Vec<JobRun>> {
+        let fs_read = self.fs.read().await;
+        let jobs_dir = fs_read.metadata().join(JOBS_METADATA_DIR_NAME);
+
+        let job_dirs: Vec<PathBuf> = match job {
+            Some(job) => vec![jobs_dir.join(job.name())],
+            None if jobs_dir.is_dir() => {
+                let mut dirs = vec![];
+                let mut entries = read_dir(&jobs_dir)
+                    .await
+                    .context("storage get job runs read jobs dir")
+                    .as_internal_err()?;
+                while let Some(entry) = entries
+                    .next_entry()
+                    .await
+                    .context("storage get job runs read jobs entries")
+                    .as_internal_err()?
+                {
+                    dirs.push(entry.path());
+                }
+                dirs
+            }
+            None => vec![],
+        };
+
+        // file names start with run start time, so sorting them sorts the runs
+        let mut files: Vec<(String, PathBuf)> = vec![];
+        for dir in job_dirs.iter().filter(|dir| dir.is_dir()) {
+            let mut entries = read_dir(dir)
+                .await
+                .context("storage get job runs read job dir")
+                .as_internal_err()?;
+            while let Some(entry) = entries
+                .next_entry()
+                .await
+                .context("storage get job runs read job entries")
+                .as_internal_err()?
+            {
+                let filename = entry.file_name().to_string_lossy().into_owned();
+                if filename.ends_with(".json") {
+                    files.push((filename, entry.path()));
+                }
+            }
+        }
+        files.sort_by(|a, b| b.0.cmp(&a.0));
+
+        let mut runs = vec![];
+        for (_, path) in files.into_iter().take(limit as usize) {
+            let content = fs::read_to_string(&path)
+                .await
+                .context("storage get job runs read file")
+                .as_internal_err()?;
+            let run: JobRun = serde_json::from_str(&content)
+                .context("storage get job runs parse to json")
+                .as_internal_err()?;
+            runs.push(run);
+        }
+
+        Ok(runs)
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn insert_webhook_delivery(
+        &self,
+        delivery: &WebhookDelivery,
+    ) -> ForexResult<()> {
+        let json_string = serde_json::to_string_pretty(delivery)
+            .context("storage insert webhook delivery parse into json string")
+            .as_internal_err()?;
+
+        let fs_write = self.fs.write().await;
+        let webhooks_dir = fs_write.metadata().join(WEBHOOKS_METADATA_DIR_NAME);
+        if !webhooks_dir.is_dir() {
+            tokio::fs::create_dir_all(&webhooks_dir)
+                .await
+                .context("storage insert webhook delivery create webhooks dir")
+                .as_internal_err()?;
+        }
+        let filepath = webhooks_dir.join(format!(
+            "{}-{}.json",
+            delivery.created_at.format("%Y%m%dT%H%M%S%.6fZ"),
+            delivery.id
+        ));
+
+        Self::write_file_atomic(&filepath, json_string).await
+    }
+
+    #[instrument(skip(self, report))]
+    pub(crate) async fn insert_consistency_report(
+        &self,
+        report: &ConsistencyReport,
+    ) -> ForexResult<()> {
+        let json_string = serde_json::to_string_pretty(report)
+            .context("storage insert consistency report parse into json string")
+            .as_internal_err()?;
+
+        let fs_write = self.fs.write().await;
+        let dir = fs_write.metadata().join(CONSISTENCY_METADATA_DIR_NAME);
+        if !dir.is_dir() {
+            tokio::fs::create_dir_all(&dir)
+                .await
+                .context("storage insert consistency report create consistency dir")
+                .as_internal_err()?;
+        }
+        let filepath = dir.join(format!(
+            "{}_{}_{}.json",
+            report.date.format("%Y-%m-%d"),
+            report.first_provider,
+            report.second_provider
+        ));
+
+        Self::write_file_atomic(&filepath, json_string).await
+    }
+
+    /// evaluations are named by their time, so sorting names sorts them by time.
+    #[instrument(skip(self, report))]
+    pub(crate) async fn insert_slo_report(&self, report: &SloReport) -> ForexResult<()> {
+        let json_string = serde_json::to_string_pretty(report)
+            .context("storage insert slo report parse into json string")
+            .as_internal_err()?;
+
+        let fs_write = self.fs.write().await;
+        let dir = fs_write.metadata().join(SLO_METADATA_DIR_NAME);
+        if !dir.is_dir() {
+            tokio::fs::create_dir_all(&dir)
+                .await
+                .context("storage insert slo report create slo dir")
+                .as_internal_err()?;
+        }
+        let filepath = dir.join(slo_report_filename(report.evaluated_at, report.id));
+        Self::write_file_atomic(&filepath, json_string).await?;
+
+        // names older than the cutoff name sort before it
+        let cutoff = slo_report_filename(
+            report.evaluated_at - TimeDelta::days(SLO_RETENTION_DAYS as i64),
+            Uuid::nil(),
+        );
+        for name in Self::slo_report_names(&dir).await? {
+            if name >= cutoff {
+                break;
+            }
+            if let Err(err) = fs::remove_file(dir.join(&name)).await {
+                tracing::warn!(
+                    "storage failed removing expired slo report {}: {}",
+                    name,
+                    err
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_backfill_checkpoint(
+        &self,
+        provider: &str,
+        base: Currency,
+    ) -> ForexResult<BackfillCheckpoint> {
+        let fs_read = self.fs.read().await;
+        let filepath = fs_read
+            .metadata()
+            .join(BACKFILL_METADATA_DIR_NAME)
+            .join(format!("{}-{}.json", provider, base));
+        let content = match fs::read_to_string(&filepath).await {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(BackfillCheckpoint::default());
+            }
+            Err(err) => Err(err)
+                .context("storage get backfill checkpoint read file")
+                .as_internal_err()?,
+        };
+
+        let checkpoint = serde_json::from_str(&content)
+            .context("storage get backfill checkpoint parse to json")
+            .as_internal_err()?;
+
+        Ok(checkpoint)
+    }
+
+    #[instrument(skip(self, checkpoint))]
+    pub(crate) async fn insert_backfill_checkpoint(
+        &self,
+        provider: &str,
+        base: Currency,
+        checkpoint: &BackfillCheckpoint,
+    ) -> ForexResult<()> {
+        let json_string = serde_json::to_string_pretty(checkpoint)
+            .context("storage insert backfill checkpoint parse into json string")
+            .as_internal_err()?;
+
+        let fs_write = self.fs.write().await;
+        let dir = fs_write.metadata().join(BACKFILL_METADATA_DIR_NAME);
+        if !dir.is_dir() {
+            tokio::fs::create_dir_all(&dir)
+                .await
+                .context("storage insert backfill checkpoint create backfill dir")
+                .as_internal_err()?;
+        }
+        let filepath = dir.join(format!("{}-{}.json", provider, base));
+
+        Self::write_file_atomic(&filepath, json_string).await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_slo_reports(
+        &self,
+        since: DateTime<Utc>,
+        limit: u32,
+    ) -> ForexResult<Vec<SloReport>> {
+        let fs_read = self.fs.read().await;
+        let dir = fs_read.metadata().join(SLO_METADATA_DIR_NAME);
+        if !dir.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut reports = vec![];
+        for name in Self::slo_report_names(&dir).await?.into_iter().rev() {
+            if reports.len() >= limit as usize {
+                break;
+            }
+            let content = fs::read_to_string(dir.join(&name))
+                .await
+                .context("storage get slo reports read file")
+                .as_internal_err()?;
+            let report: SloReport = serde_json::from_str(&content)
+                .context("storage get slo reports parse to json")
+                .as_internal_err()?;
+            if report.evaluated_at < since {
+                break;
+            }
+            reports.push(report);
+        }
+
+        Ok(reports)
+    }
+
+    /// names of evaluation files, oldest first.
+    async fn slo_report_names(dir: &Path) -> ForexResult<Vec<String>> {
+        let mut entries = fs::read_dir(dir)
+            .await
+            .context("storage read slo dir")
+            .as_internal_err()?;
+        let mut names = vec![];
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .context("storage read slo dir entries")
+            .as_internal_err()?
+        {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.ends_with(".json") {
+                names.push(name);
+            }
+        }
+        names.sort();
+
+        Ok(names)
+    }
+
+    /// lock of a job is a sequence of generation files `{job}.{generation}.json`, the highest one is the current lock.
+    /// taking over expired lock creates next generation by hard linking fully written temporary file,
+    /// which fails if the generation exists, so only one instance takes it over.
+    /// this works on shared storage mounted by multiple instances, e.g. NFS.
+    #[instrument(skip(self))]
+    pub(crate) async fn try_lock_job(
+        &self,
+        job: Job,
+        holder: &str,
+        ttl: TimeDelta,
+    ) -> ForexResult<bool> {
+        let lock = JobLock::new(job, holder, ttl);
+        let json_string = serde_json::to_string_pretty(&lock)
+            .context("storage try lock job parse into json string")
+            .as_internal_err()?;
+
+        let fs_write = self.fs.write().await;
+        let locks_dir = fs_write.metadata().join(LOCKS_METADATA_DIR_NAME);
+        if !locks_dir.is_dir() {
+            tokio::fs::create_dir_all(&locks_dir)
+                .await
+                .context("storage try lock job create locks dir")
+                .as_internal_err()?;
+        }
+
+        let generation = match Self::job_lock_generations(&locks_dir, job).await?.last() {
+            None => 0,
+            Some(&current) => {
+                let current_path = Self::job_lock_path(&locks_dir, job, current);
+                let content = fs::read_to_string(&current_path)
+                    .await
+                    .context("storage try lock job read lock file")
+                    .as_internal_err()?;
+                match serde_json::from_str::<JobLock>(&content) {
+                    Ok(current_lock) if !current_lock.can_acquire(holder, Utc::now()) => {
+                        return Ok(false);
+                    }
+                    Ok(current_lock) if current_lock.holder == holder => {
+                        // renewing own lock
+                        Self::write_file_atomic(&current_path, json_string).await?;
+                        let latest = Self::job_lock_generations(&locks_dir, job).await?;
+                        return Ok(latest.last() == Some(&current));
+                    }
+                    Ok(_) => {
+                        tracing::info!("lock of job {} expired, taking it over", job.name());
+                    }
+                    Err(err) => {
+                        tracing::warn!(
+                            "invalid lock of job {}, taking it over: {}",
+                            job.name(),
+                            err
+                        );
+                    }
+                }
+                current + 1
+            }
+        };
+
+        let tmp_path = locks_dir.join(format!("{}-{}.json.tmp", job.name(), Uuid::new_v4()));
+        let mut file = File::create(&tmp_path)
+            .await
+            .context("storage try lock job create temporary file")
+            .as_internal_err()?;
+        file.write_all(json_string.as_bytes())
+            .await
+            .context("storage try lock job write content")
+            .as_internal_err()?;
+        file.flush()
+            .await
+            .context("storage try lock job flush")
+            .as_internal_err()?;
+        Self::set_permission(&tmp_path).await?;
+
+        let lock_path = Self::job_lock_path(&locks_dir, job, generation);
+        let linked = fs::hard_link(&tmp_path, &lock_path).await;
+        let _ = fs::remove_file(&tmp_path).await;
+        match linked {
+            Ok(_) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => return Ok(false),
+            Err(err) => Err(err)
+                .context("storage try lock job link lock file")
+                .as_internal_err()?,
+        }
+
+        // instance which listed generations before a newer takeover may create a generation below the current one
+        let generations = Self::job_lock_generations(&locks_dir, job).await?;
+        if generations.last() != Some(&generation) {
+            let _ = fs::remove_file(&lock_path).await;
+            return Ok(false);
+        }
+        // previous generation is kept, so instances which listed it fail creating this generation
+        for old in generations.into_iter().filter(|old| *old + 1 < generation) {
+            let _ = fs::remove_file(Self::job_lock_path(&locks_dir, job, old)).await;
+        }
+
+        Ok(true)
+    }
+
+    fn job_lock_path(locks_dir: &Path, job: Job, generation: u64) -> PathBuf {
+        locks_dir.join(format!("{}.{}.json", job.name(), generation))
+    }
+
+    /// generations of the job lock files, oldest first.
+    async fn job_lock_generations(locks_dir: &Path, job: Job) -> ForexResult<Vec<u64>> {
+        let prefix = format!("{}.", job.name());
+        let mut entries = fs::read_dir(locks_dir)
+            .await
+            .context("storage read locks dir")
+            .as_internal_err()?;
+        let mut generations = vec![];
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .context("storage read locks dir entries")
+            .as_internal_err()?
+        {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if let Some(generation) = name
+                .strip_prefix(&prefix)
+                .and_then(|name| name.strip_suffix(".json"))
+                .and_then(|generation| generation.parse::<u64>().ok())
+            {
+                generations.push(generation);
+            }
+        }
+        generations.sort();
+
+        Ok(generations)
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn is_empty(&self) -> ForexResult<bool> {
+        let fs_read = self.fs.read().await;
+        if !fs_read.latest().is_dir() {
+            return Ok(true);
+        }
+
+        let mut entries = read_dir(fs_read.latest())
+            .await
+            .context("storage is empty read latest dir")
+            .as_internal_err()?;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .context("storage is empty read latest entries")
+            .as_internal_err()?
+        {
+            if entry.path().is_file() {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn upsert_user(&self, user: &User) -> ForexResult<()> {
+        let json_string = serde_json::to_string_pretty(user)
+            .context("storage upsert user parse into json string")
+            .as_internal_err()?;
+
+        let fs_write = self.fs.write().await;
+        let users_dir = fs_write.root().join(USERS_DIR_NAME);
+        if !users_dir.is_dir() {
+            tokio::fs::create_dir_all(&users_dir)
+                .await
+                .context("storage upsert user create users dir")
+                .as_internal_err()?;
+        }
+        let filepath = users_dir.join(format!("{}.json", user.username));
+
+        Self::write_file_atomic(&filepath, json_string).await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_user(&self, username: &str) -> ForexResult<Option<User>> {
+        let fs_read = self.fs.read().await;
+        let filepath = fs_read
+            .root()
+            .join(USERS_DIR_NAME)
+            .join(format!("{}.json", username));
+        if !filepath.is_file() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&filepath)
+            .await
+            .context("storage get user read file")
+            .as_internal_err()?;
+
+        let user = serde_json::from_str(&content)
+            .context("storage get user parse to json")
+            .as_internal_err()?;
+
+        Ok(Some(user))
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_usernames(&self) -> ForexResult<Vec<String>> {
+        let fs_read = self.fs.read().await;
+        let users_dir = fs_read.root().join(USERS_DIR_NAME);
+        if !users_dir.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut usernames: Vec<String> = vec![];
+        let mut entries = read_dir(&users_dir)
+            .await
+            .context("storage get usernames read users dir")
+            .as_internal_err()?;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .context("storage get usernames read users entries")
+            .as_internal_err()?
+        {
+            if let Some(username) = entry.file_name().to_string_lossy().strip_suffix(".json") {
+                usernames.push(username.to_string());
+            }
+        }
+        usernames.sort();
+
+        Ok(usernames)
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn delete_user(&self, username: &str) -> ForexResult<()> {
+        let fs_write = self.fs.write().await;
+        let filepath = fs_write
+            .root()
+            .join(USERS_DIR_NAME)
+            .join(format!("{}.json", username));
+
+        fs::remove_file(&filepath)
+            .await
+            .context("storage delete user remove file")
+            .as_internal_err()?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn upsert_goal(&self, goal: &Goal) -> ForexResult<()> {
+        let json_string = serde_json::to_string_pretty(goal)
+            .context("storage upsert goal parse into json string")
+            .as_internal_err()?;
+
+        let fs_write = self.fs.write().await;
+        let goals_dir = self.tenant_dir(fs_write.root()).join(GOALS_DIR_NAME);
+        if !goals_dir.is_dir() {
+            tokio::fs::create_dir_all(&goals_dir)
+                .await
+                .context("storage upsert goal create goals dir")
+                .as_internal_err()?;
+        }
+        let filepath = goals_dir.join(format!("{}.json", goal.id));
+
+        Self::write_file_atomic(&filepath, json_string).await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_goal(&self, id: Uuid) -> ForexResult<Goal> {
+        let fs_read = self.fs.read().await;
+        let filepath = self
+            .tenant_dir(fs_read.root())
+            .join(GOALS_DIR_NAME)
+            .join(format!("{}.json", id));
+        if !filepath.is_file() {
+            return Err(ForexError::client_error(
+                format!("goal {} not found", id).as_str(),
+            ));
+        }
+
+        let content = fs::read_to_string(&filepath)
+            .await
+            .context("storage get goal read file")
+            .as_internal_err()?;
+
+        let goal = serde_json::from_str(&content)
+            .context("storage get goal parse to json")
+            .as_internal_err()?;
+
+        Ok(goal)
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_goals(&self) -> ForexResult<Vec<Goal>> {
+        let fs_read = self.fs.read().await;
+        let goals_dir = self.tenant_dir(fs_read.root()).join(GOALS_DIR_NAME);
+        if !goals_dir.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut goals: Vec<Goal> = vec![];
+        let mut entries = read_dir(&goals_dir)
+            .await
+            .context("storage get goals read goals dir")
+            .as_internal_err()?;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .context("storage get goals read goals entries")
+            .as_internal_err()?
+        {
+            if !entry.file_name().to_string_lossy().ends_with(".json") {
+                continue;
+            }
+            let content = fs::read_to_string(entry.path())
+                .await
+                .context("storage get goals read file")
+                .as_internal_err()?;
+            let goal = serde_json::from_str(&content)
+                .context("storage get goals parse to json")
+                .as_internal_err()?;
+            goals.push(goal);
+        }
+        goals.sort_by_key(|goal| goal.deadline);
+
+        Ok(goals)
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn delete_goal(&self, id: Uuid) -> ForexResult<()> {
+        let fs_write = self.fs.write().await;
+        let filepath = self
+            .tenant_dir(fs_write.root())
+            .join(GOALS_DIR_NAME)
+            .join(format!("{}.json", id));
+
+        fs::remove_file(&filepath)
+            .await
+            .context("storage delete goal remove file")
+            .as_internal_err()?;
+
+        Self::write_tombstone(
+            &self.tenant_dir(fs_write.metadata()).join(SYNC_DIR_NAME),
+            &Tombstone {
+                kind: SyncKind::Goal,
+                key: id.to_string(),
+                deleted_at: Utc::now(),
+            },
+        )
+        .await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_watchlist(&self, profile: &str) -> ForexResult<Option<Watchlist>> {
+        let fs_read = self.fs.read().await;
+        let filepath = self
+            .tenant_dir(fs_read.root())
+            .join(WATCHLISTS_DIR_NAME)
+            .join(format!("{}.json", profile));
+        if !filepath.is_file() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&filepath)
+            .await
+            .context("storage get watchlist read file")
+            .as_internal_err()?;
+        let watchlist = serde_json::from_str(&content)
+            .context("storage get watchlist parse to json")
+            .as_internal_err()?;
+
+        Ok(Some(watchlist))
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn upsert_watchlist(&self, watchlist: &Watchlist) -> ForexResult<()> {
+        let json_string = serde_json::to_string_pretty(watchlist)
+            .context("storage upsert watchlist parse into json string")
+            .as_internal_err()?;
+
+        let fs_write = self.fs.write().await;
+        let watchlists_dir = self.tenant_dir(fs_write.root()).join(WATCHLISTS_DIR_NAME);
+        if !watchlists_dir.is_dir() {
+            tokio::fs::create_dir_all(&watchlists_dir)
+                .await
+                .context("storage upsert watchlist create watchlists dir")
+                .as_internal_err()?;
+        }
+        let filepath = watchlists_dir.join(format!("{}.json", watchlist.profile));
+
+        Self::write_file_atomic(&filepath, json_string).await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn delete_watchlist(&self, profile: &str) -> ForexResult<()> {
+        let fs_write = self.fs.write().await;
+        let filepath = self
+            .tenant_dir(fs_write.root())
+            .join(WATCHLISTS_DIR_NAME)
+            .join(format!("{}.json", profile));
+        if !filepath.is_file() {
+            return Err(ForexError::client_error(
+                format!("watchlist of {} not found", profile).as_str(),
+            ));
+        }
+
+        fs::remove_file(&filepath)
+            .await
+            .context("storage delete watchlist remove file")
+            .as_internal_err()?;
+
+        Self::write_tombstone(
+            &self.tenant_dir(fs_write.metadata()).join(SYNC_DIR_NAME),
+            &Tombstone {
+                kind: SyncKind::Watchlist,
+                key: profile.to_string(),
+                deleted_at: Utc::now(),
+            },
+        )
+        .await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_watchlists(&self) -> ForexResult<Vec<Watchlist>> {
+        let fs_read = self.fs.read().await;
+        let watchlists_dir = self.tenant_dir(fs_read.root()).join(WATCHLISTS_DIR_NAME);
+        if !watchlists_dir.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut watchlists: Vec<Watchlist> = vec![];
+        let mut entries = read_dir(&watchlists_dir)
+            .await
+            .context("storage get watchlists read watchlists dir")
+            .as_internal_err()?;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .context("storage get watchlists read watchlists entries")
+            .as_internal_err()?
+        {
+            if !entry.file_name().to_string_lossy().ends_with(".json") {
+                continue;
+            }
+            let content = fs::read_to_string(entry.path())
+                .await
+                .context("storage get watchlists read file")
+                .as_internal_err()?;
+            let watchlist = serde_json::from_str(&content)
+                .context("storage get watchlists parse to json")
+                .as_internal_err()?;
+            watchlists.push(watchlist);
+        }
+        watchlists.sort_by(|a, b| a.profile.cmp(&b.profile));
+
+        Ok(watchlists)
+    }
+
+    async fn read_tombstones(dir: &Path) -> ForexResult<Vec<Tombstone>> {
+        let filepath = dir.join(TOMBSTONES_FILENAME);
+        if !filepath.is_file() {
+            return Ok(vec![]);
+        }
+
+        let content = fs::read_to_string(&filepath)
+            .await
+            .context("storage read tombstones read file")
+            .as_internal_err()?;
+        let tombstones = serde_json::from_str(&content)
+            .context("storage read tombstones parse to json")
+            .as_internal_err()?;
+
+        Ok(tombstones)
+    }
+
+    /// insert tombstone into sync directory, replacing the one of the same record.
+    async fn write_tombstone(dir: &Path, tombstone: &Tombstone) -> ForexResult<()> {
+        let mut tombstones = Self::read_tombstones(dir).await?;
+        tombstones.retain(|stored| stored.kind != tombstone.kind || stored.key != tombstone.key);
+        tombstones.push(tombstone.clone());
+        let json_string = serde_json::to_string_pretty(&tombstones)
+            .context("storage write tombstone parse into json string")
+            .as_internal_err()?;
+
+        if !dir.is_dir() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .context("storage write tombstone create sync dir")
+                .as_internal_err()?;
+        }
+
+        Self::write_file_atomic(&dir.join(TOMBSTONES_FILENAME), json_string).await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_tombstones(&self) -> ForexResult<Vec<Tombstone>> {
+        let fs_read = self.fs.read().await;
+        Self::read_tombstones(&self.tenant_dir(fs_read.metadata()).join(SYNC_DIR_NAME)).await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn upsert_tombstone(&self, tombstone: &Tombstone) -> ForexResult<()> {
+        let fs_write = self.fs.write().await;
+        Self::write_tombstone(
+            &self.tenant_dir(fs_write.metadata()).join(SYNC_DIR_NAME),
+            tombstone,
+        )
+        .await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn delete_tombstones_before(
+        &self,
+        before: DateTime<Utc>,
+    ) -> ForexResult<usize> {
+        let fs_write = self.fs.write().await;
+        let dir = self.tenant_dir(fs_write.metadata()).join(SYNC_DIR_NAME);
+        let mut tombstones = Self::read_tombstones(&dir).await?;
+        let count = tombstones.len();
+        tombstones.retain(|tombstone| tombstone.deleted_at >= before);
+        let deleted = count - tombstones.len();
+        if deleted == 0 {
+            return Ok(0);
+        }
+
+        let json_string = serde_json::to_string_pretty(&tombstones)
+            .context("storage delete tombstones parse into json string")
+            .as_internal_err()?;
+        Self::write_file_atomic(&dir.join(TOMBSTONES_FILENAME), json_string).await?;
+
+        Ok(deleted)
+    }
+
+    async fn read_peers(filepath: &Path) -> ForexResult<BTreeMap<String, DateTime<Utc>>> {
+        if !filepath.is_file() {
+            return Ok(BTreeMap::new());
+        }
+
+        let content = fs::read_to_string(filepath)
+            .await
+            .context("storage read sync peers read file")
+            .as_internal_err()?;
+        let peers = serde_json::from_str(&content)
+            .context("storage read sync peers parse to json")
+            .as_internal_err()?;
+
+        Ok(peers)
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_last_sync(&self, peer: &str) -> ForexResult<Option<DateTime<Utc>>> {
+        let fs_read = self.fs.read().await;
+        let filepath = self
+            .tenant_dir(fs_read.metadata())
+            .join(SYNC_DIR_NAME)
+            .join(PEERS_FILENAME);
+
+        Ok(Self::read_peers(&filepath).await?.get(peer).copied())
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_last_syncs(&self) -> ForexResult<BTreeMap<String, DateTime<Utc>>> {
+        let fs_read = self.fs.read().await;
+        let filepath = self
+            .tenant_dir(fs_read.metadata())
+            .join(SYNC_DIR_NAME)
+            .join(PEERS_FILENAME);
+
+        Self::read_peers(&filepath).await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn set_last_sync(&self, peer: &str, at: DateTime<Utc>) -> ForexResult<()> {
+        let fs_write = self.fs.write().await;
+        let dir = self.tenant_dir(fs_write.metadata()).join(SYNC_DIR_NAME);
+        let mut peers = Self::read_peers(&dir.join(PEERS_FILENAME)).await?;
+        peers.insert(peer.to_string(), at);
+        let json_string = serde_json::to_string_pretty(&peers)
+            .context("storage set last sync parse into json string")
+            .as_internal_err()?;
+
+        if !dir.is_dir() {
+            tokio::fs::create_dir_all(&dir)
+                .await
+                .context("storage set last sync create sync dir")
+                .as_internal_err()?;
+        }
+
+        Self::write_file_atomic(&dir.join(PEERS_FILENAME), json_string).await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_revision_log(&self, name: &str) -> ForexResult<RevisionLog> {
+        let fs_read = self.fs.read().await;
+        let filepath = self
+            .tenant_dir(fs_read.metadata())
+            .join(SYNC_DIR_NAME)
+            .join(REVISIONS_DIR_NAME)
+            .join(format!("{}.json", name));
+        if !filepath.is_file() {
+            return Ok(RevisionLog::default());
+        }
+
+        let content = fs::read_to_string(&filepath)
+            .await
+            .context("storage get revision log read file")
+            .as_internal_err()?;
+        let log = serde_json::from_str(&content)
+            .context("storage get revision log parse to json")
+            .as_internal_err()?;
+
+        Ok(log)
+    }
+
+    #[instrument(skip(self, log))]
+    pub(crate) async fn set_revision_log(&self, name: &str, log: &RevisionLog) -> ForexResult<()> {
+        let json_string = serde_json::to_string_pretty(log)
+            .context("storage set revision log parse into json string")
+            .as_internal_err()?;
+
+        let fs_write = self.fs.write().await;
+        let dir = self
+            .tenant_dir(fs_write.metadata())
+            .join(SYNC_DIR_NAME)
+            .join(REVISIONS_DIR_NAME);
+        if !dir.is_dir() {
+            tokio::fs::create_dir_all(&dir)
+                .await
+                .context("storage set revision log create revisions dir")
+                .as_internal_err()?;
+        }
+
+        Self::write_file_atomic(&dir.join(format!("{}.json", name)), json_string).await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn upsert_digest_subscription(
+        &self,
+        subscription: &DigestSubscription,
+    ) -> ForexResult<()> {
+        let json_string = serde_json::to_string_pretty(subscription)
+            .context("storage upsert digest subscription parse into json string")
+            .as_internal_err()?;
+
+        let fs_write = self.fs.write().await;
+        let digests_dir = self.tenant_dir(fs_write.root()).join(DIGESTS_DIR_NAME);
+        if !digests_dir.is_dir() {
+            tokio::fs::create_dir_all(&digests_dir)
+                .await
+                .context("storage upsert digest subscription create digests dir")
+                .as_internal_err()?;
+        }
+        let filepath = digests_dir.join(format!("{}.json", subscription.id));
+
+        Self::write_file_atomic(&filepath, json_string).await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_digest_subscriptions(&self) -> ForexResult<Vec<DigestSubscription>> {
+        let fs_read = self.fs.read().await;
+        let digests_dir = self.tenant_dir(fs_read.root()).join(DIGESTS_DIR_NAME);
+        if !digests_dir.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut subscriptions: Vec<DigestSubscription> = vec![];
+        let mut entries = read_dir(&digests_dir)
+            .await
+            .context("storage get digest subscriptions read digests dir")
+            .as_internal_err()?;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .context("storage get digest subscriptions read digests entries")
+            .as_internal_err()?
+        {
+            if !entry.file_name().to_string_lossy().ends_with(".json") {
+                continue;
+            }
+            let content = fs::read_to_string(entry.path())
+                .await
+                .context("storage get digest subscriptions read file")
+                .as_internal_err()?;
+            let subscription = serde_json::from_str(&content)
+                .context("storage get digest subscriptions parse to json")
+                .as_internal_err()?;
+            subscriptions.push(subscription);
+        }
+        subscriptions.sort_by_key(|subscription| subscription.created_at);
+
+        Ok(subscriptions)
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn delete_digest_subscription(&self, id: Uuid) -> ForexResult<()> {
+        let fs_write = self.fs.write().await;
+        let filepath = self
+            .tenant_dir(fs_write.root())
+            .join(DIGESTS_DIR_NAME)
+            .join(format!("{}.json", id));
+        if !filepath.is_file() {
+            return Err(ForexError::client_error(
+                format!("digest subscription {} not found", id).as_str(),
+            ));
+        }
+
+        fs::remove_file(&filepath)
+            .await
+            .context("storage delete digest subscription remove file")
+            .as_internal_err()?;
+
+        Ok(())
+    }
+
+    /// transaction files are named by their date, so range is filtered without reading file contents.
+    #[instrument(skip(self, transactions))]
+    pub(crate) async fn upsert_transactions(
+        &self,
+        transactions: &[Transaction],
+    ) -> ForexResult<()> {
+        let fs_write = self.fs.write().await;
+        let ledger_dir = self.tenant_dir(fs_write.root()).join(LEDGER_DIR_NAME);
+        if !ledger_dir.is_dir() {
+            tokio::fs::create_dir_all(&ledger_dir)
+                .await
+                .context("storage upsert transactions create ledger dir")
+                .as_internal_err()?;
+        }
+
+        for tx in transactions {
+            let json_string = serde_json::to_string_pretty(tx)
+                .context("storage upsert transactions parse into json string")
+                .as_internal_err()?;
+            let filepath = ledger_dir.join(format!("{}-{}.json", tx.date.date_naive(), tx.id));
+            Self::write_file_atomic(&filepath, json_string).await?;
+        }
+        self.publish_ledger_changed();
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_transactions(
+        &self,
+        range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    ) -> ForexResult<Vec<Transaction>> {
+        let fs_read = self.fs.read().await;
+        let ledger_dir = self.tenant_dir(fs_read.root()).join(LEDGER_DIR_NAME);
+        if !ledger_dir.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut transactions: Vec<Transaction> = vec![];
+        let mut entries = read_dir(&ledger_dir)
+            .await
+            .context("storage get transactions read ledger dir")
+            .as_internal_err()?;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .context("storage get transactions read ledger entries")
+            .as_internal_err()?
+        {
+            let filename = entry.file_name().to_string_lossy().into_owned();
+            if !filename.ends_with(".json") {
+                continue;
+            }
+            if let Some((start, end)) = range {
+                let Some(date) = filename
+                    .get(..10)
+                    .and_then(|date| date.parse::<NaiveDate>().ok())
+                else {
+                    continue;
+                };
+                if date < start.date_naive() || date > end.date_naive() {
+                    continue;
+                }
+            }
+            let content = fs::read_to_string(entry.path())
+                .await
+                .context("storage get transactions read file")
+                .as_internal_err()?;
+            let tx = serde_json::from_str(&content)
+                .context("storage get transactions parse to json")
+                .as_internal_err()?;
+            transactions.push(tx);
+        }
+        transactions.sort_by_key(|tx| tx.date);
+
+        Ok(transactions)
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_category_rules(&self) -> ForexResult<Vec<CategoryRule>> {
+        let fs_read = self.fs.read().await;
+        let filepath = self
+            .tenant_dir(fs_read.metadata())
+            .join(LEDGER_DIR_NAME)
+            .join(CATEGORY_RULES_FILENAME);
+        if !filepath.is_file() {
+            return Ok(vec![]);
+        }
+
+        let content = fs::read_to_string(&filepath)
+            .await
+            .context("storage get category rules read file")
+            .as_internal_err()?;
+        let rules = serde_json::from_str(&content)
+            .context("storage get category rules parse to json")
+            .as_internal_err()?;
+
+        Ok(rules)
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn set_category_rules(&self, rules: &[CategoryRule]) -> ForexResult<()> {
+        let json_string = serde_json::to_string_pretty(rules)
+            .context("storage set category rules parse into json string")
+            .as_internal_err()?;
+
+        let fs_write = self.fs.write().await;
+        let dir = self.tenant_dir(fs_write.metadata()).join(LEDGER_DIR_NAME);
+        if !dir.is_dir() {
+            tokio::fs::create_dir_all(&dir)
+                .await
+                .context("storage set category rules create ledger metadata dir")
+                .as_internal_err()?;
+        }
+
+        Self::write_file_atomic(&dir.join(CATEGORY_RULES_FILENAME), json_string).await
+    }
+
+    #[instrument(skip(self))]
+    pub(crate) async fn get_accounts(&self) -> ForexResult<Vec<Account>> {
+        let fs_read = self.fs.read().await;
+        Self::read_accounts(
+            &self
+                .tenant_dir(fs_read.root())
+                .join(LEDGER_DIR_NAME)
+                .join(DOUBLE_ENTRY_DIR_NAME)
+                .join(ACCOUNTS_FILENAME),
+        )
+        .await
+    }
+
+    /// accounts are few, so they are kept in a single file.
+    #[instrument(skip(self))]
+    pub(crate) async fn upsert_account(&self, account: &Account) -> ForexResult<()> {
+        let fs_write = self.fs.write().await;
+        let dir = self
+            .tenant_dir(fs_write.root())
+            .join(LEDGER_DIR_NAME)
+            .join(DOUBLE_ENTRY_DIR_NAME);
+        if !dir.is_dir() {
+            tokio::fs::create_dir_all(&dir)
+                .await
+                .context("storage upsert account create double-entry dir")
+                .as_internal_err()?;
+        }
+
+        let filepath = dir.join(ACCOUNTS_FILENAME);
+        let mut accounts = Self::read_accounts(&filepath).await?;
+        accounts.retain(|acc| acc.name != account.name);
+        accounts.push(account.clone());
+        accounts.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let json_string = serde_json::to_string_pretty(&accounts)
+            .context("storage upsert account parse into json string")
+            .as_internal_err()?;
+        Self::write_file_atomic(&filepath, json_string).await?;
+        self.publish_ledger_changed();
+
+        Ok(())
+    }
+
+    async fn read_accounts(filepath: &Path) -> ForexResult<Vec<Account>> {
+        if !filepath.is_file() {
+            return Ok(vec![]);
+        }
+
+        let content = fs::read_to_string(filepath)
+            .await
+            .context("storage read accounts read file")
+            .as_internal_err()?;
+        let accounts = serde_json::from_str(&content)
+            .context("storage read accounts parse to json")
+            .as_internal_err()?;
+
+        Ok(accounts)
+    }
+
+    #[instrument(skip(self, entry))]
+    pub(crate) async fn insert_journal_entry(&self, entry: &JournalEntry) -> ForexResult<()> {
+        let json_string = serde_json::to_string_pretty(entry)
+            .context("storage insert journal entry parse into json string")
+            .as_internal_err()?;
+
+        let fs_write = self.fs.write().await;
+        let dir = self
+            .tenant_dir(fs_write.root())
+            .join(LEDGER_DIR_NAME)
+            .join(DOUBLE_ENTRY_DIR_NAME)
+            .join(JOURNAL_ENTRIES_DIR_NAME);
+        if !dir.is_dir() {
+            tokio::fs::create_dir_all(&dir)
+                .await
+                .context("storage insert journal entry create entries dir")
+                .as_internal_err()?;
+        }
+
+        let filepath = dir.join(format!("{}-{}.json", entry.date.date_naive(), entry.id));
+        Self::write_file_atomic(&filepath, json_string).await?;
+        self.publish_ledger_changed();
+
+        Ok(())
+    }
+
+    /// journal entry files are named by their date, same as transactions.
+    #[instrument(skip(self))]
+    pub(crate) async fn get_journal_entries(
+        &self,
+        until: Option<DateTime<Utc>>,
+    ) -> ForexResult<Vec<JournalEntry>> {
+        let fs_read = self.fs.read().await;
+        let dir = self
+            .tenant_dir(fs_read.root())
+            .join(LEDGER_DIR_NAME)
+            .join(DOUBLE_ENTRY_DIR_NAME)
+            .join(JOURNAL_ENTRIES_DIR_NAME);
+        if !dir.is_dir() {
+            return Ok(vec![]);
+        }
+
+        let mut journal: Vec<JournalEntry> = vec![];
+        let mut entries = read_dir(&dir)
+            .await
+            .context("storage get journal entries read entries dir")
+            .as_internal_err()?;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .context("storage get journal entries read dir entries")
+            .as_internal_err()?
+        {
+            let filename = entry.file_name().to_string_lossy().into_owned();
+            if !filename.ends_with(".json") {
+                continue;
+            }
+            if let Some(until) = until {
+                let Some(date) = filename
+                    .get(..10)
+                    .and_then(|date| date.parse::<NaiveDate>().ok())
+                else {
+                    continue;
+                };
+                if date > until.date_naive() {
+                    continue;
+                }
+            }
+            let content = fs::read_to_string(entry.path())
+                .await
+                .context("storage get journal entries read file")
+                .as_internal_err()?;
+            let journal_entry = serde_json::from_str(&content)
+                .context("storage get journal entries parse to json")
+                .as_internal_err()?;
+            journal.push(journal_entry);
+        }
+        journal.sort_by_key(|entry| entry.date);
+
+        Ok(journal)
+    }
+
+    fn paginate_rates_list<T>(rates: &[T], page: u32, size: u32) -> RatesList<T>
+    where
+        T: Clone,
+    {
+        RatesList::paginate(rates, page, size)
+    }
+}
+
+/// path of job run file relative to jobs directory: {job}/{YYYYMMDDThhmmss.ffffff}Z-{id}.json
+fn generate_job_run_file_path(run: &JobRun) -> String {
+    format!(
+        "{}/{}-{}.json",
+        run.job.name(),
+        run.started_at.format("%Y%m%dT%H%M%S%.6fZ"),
+        run.id
+    )
+}
+
+/// name of slo evaluation file: {YYYYMMDDThhmmss.ffffff}Z-{id}.json
+fn slo_report_filename(evaluated_at: DateTime<Utc>, id: Uuid) -> String {
+    format!("{}-{}.json", evaluated_at.format("%Y%m%dT%H%M%S%.6fZ"), id)
+}
+
+/// generate path to file from parent
+fn generate_latest_file_path(date: DateTime<Utc>) -> String {
+    LatestFilename::new(date).to_string()
+}
+
+/// generate path to file from parent
+fn generate_historical_file_path(date: DateTime<Utc>) -> String {
+    let year = date.year();
+    let month = date.month();
+    let day = date.day();
+
+    let tostr = |n: u32| -> String {
+        if n < 10 {
+            format!("0{n}")
+        } else {
+            n.to_string()
+        }
+    };
+
+    let filename = HISTORICAL_FILENAME_FORMAT
+        .replace("{YYYY}", year.to_string().as_str())
+        .replace("{MM}", tostr(month).as_str())
+        .replace("{DD}", tostr(day).as_str());
+
+    format!("{}/{}", year, filename)
+}
+
+/// generate path to file of the version from parent, the first version has no version suffix
+fn generate_historical_version_file_path(date: DateTime<Utc>, version: u32) -> String {
+    let path = generate_historical_file_path(date);
+    match version {
+        0 | 1 => path,
+        _ => path.replace("Z.json", &format!("Z.v{version}.json")),
+    }
+}
+
+/// newest version of historical rates file at the date, 1 if it has no other versions or isn't stored.
+fn current_historical_version(historical_dir: &Path, date: DateTime<Utc>) -> u32 {
+    let mut version = 1;
+    while historical_dir
+        .join(generate_historical_version_file_path(date, version + 1))
+        .is_file()
+    {
+        version += 1;
+    }
+
+    version
+}
+
+/// keep only the last rates of each historical file, so the same file is never written concurrently.
+fn dedup_by_date(rates: &[RatesResponse<Rates>]) -> Vec<&RatesResponse<Rates>> {
+    let last_index: HashMap<String, usize> = rates
+        .iter()
+        .enumerate()
+        .map(|(index, rate)| (generate_historical_file_path(rate.data.date), index))
+        .collect();
+
+    rates
+        .iter()
+        .enumerate()
+        .filter(|(index, rate)| {
+            last_index.get(&generate_historical_file_path(rate.data.date)) == Some(index)
+        })
+        .map(|(_, rate)| rate)
+        .collect()
+}
+
+/// hex sha256 checksum of the content
+pub(crate) fn checksum(content: &[u8]) -> String {
+    ring::digest::digest(&ring::digest::SHA256, content)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// checksum of the file, None if it doesn't exist
+async fn file_checksum(path: &Path) -> ForexResult<Option<String>> {
+    let content = match fs::read(path).await {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => Err(err)
+            .context("storage read file checksum")
+            .as_internal_err()?,
+    };
+
+    Ok(Some(checksum(&content)))
+}
+
+/// path relative to storage root, `/` separated on every platform
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// journal file of the month of changed rates' date.
+fn journal_path(metadata_dir: &Path, date: DateTime<Utc>) -> PathBuf {
+    metadata_dir
+        .join(JOURNAL_METADATA_DIR_NAME)
+        .join(format!("{}.jsonl", date.format("%Y-%m")))
+}
+
+/// path of signature of latest rates file, by its filename so archived files keep their signatures
+fn latest_signature_path(metadata_dir: &Path, filename: &str) -> PathBuf {
+    metadata_dir
+        .join(SIGNATURES_METADATA_DIR_NAME)
+        .join(LATEST_SIGNATURES_DIR_NAME)
+        .join(format!("{}.sig", filename))
+}
+
+/// path of signature of historical rates file at the date of the version
+fn historical_signature_path(metadata_dir: &Path, date: DateTime<Utc>, version: u32) -> PathBuf {
+    metadata_dir
+        .join(SIGNATURES_METADATA_DIR_NAME)
+        .join(HISTORICAL_SIGNATURES_DIR_NAME)
+        .join(format!(
+            "{}.sig",
+            generate_historical_version_file_path(date, version)
+        ))
+}
+
+/// json and ndjson files inside the directory and its sub directories
+async fn collect_json_files(dir: &Path) -> ForexResult<Vec<PathBuf>> {
+    let mut files = vec![];
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let mut entries = fs::read_dir(&dir)
+            .await
+            .context("storage collect json files read dir")
+            .as_internal_err()?;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .context("storage collect json files read entries")
+            .as_internal_err()?
+        {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path
+                .extension()
+                .is_some_and(|ext| ext == "json" || ext == "ndjson")
+            {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+
+    Ok(files)
+}
+
+/// count and sizes of files inside the directory and its sub directories, dated by their filenames.
+/// missing directory has no files.
+async fn subtree_stats(
+    name: &str,
+    dir: &Path,
+    file_date: fn(&str) -> Option<DateTime<Utc>>,
+) -> ForexResult<StorageSubtreeStats> {
+    let mut stats = StorageSubtreeStats::new(name);
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let mut entries = match fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => Err(err)
+                .context("storage subtree stats read dir")
+                .as_internal_err()?,
+        };
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .context("storage subtree stats read entries")
+            .as_internal_err()?
+        {
+            let metadata = entry
+                .metadata()
+                .await
+                .context("storage subtree stats read metadata")
+                .as_internal_err()?;
+            if metadata.is_dir() {
+                dirs.push(entry.path());
+                continue;
+            }
+            let filename = entry.file_name().to_string_lossy().into_owned();
+            stats.add_file(metadata.len(), file_date(&filename));
+        }
+    }
 
-    filename
+    Ok(stats)
 }
 
-/// generate path to file from parent
-fn generate_historical_file_path(date: DateTime<Utc>) -> String {
-    let year = date.year();
-    let month = date.month();
-    let day = date.day();
+/// archive path of latest rates file relative to latest directory: archive/YYYY/MM/latest-...Z.json
+fn generate_latest_archive_path(filename: &str) -> Option<String> {
+    let date = latest_file_date(filename)?;
 
-    let tostr = |n: u32| -> String {
-        if n < 10 {
-            format!("0{n}")
-        } else {
-            n.to_string()
-        }
-    };
+    Some(format!(
+        "{}/{}/{:02}/{}",
+        LATEST_ARCHIVE_DIR_NAME,
+        date.year(),
+        date.month(),
+        filename
+    ))
+}
 
-    let filename = HISTORICAL_FILENAME_FORMAT
-        .replace("{YYYY}", year.to_string().as_str())
-        .replace("{MM}", tostr(month).as_str())
-        .replace("{DD}", tostr(day).as_str());
+/// date of latest rates file from its filename of any version.
+fn parse_latest_file_path(filename: &str) -> Option<DateTime<Utc>> {
+    LatestFilename::parse(filename).map(|filename| filename.date())
+}
 
-    format!("{}/{}", year, filename)
+/// date of latest rates file, or start of the day of daily latest file, from its filename.
+fn latest_file_date(filename: &str) -> Option<DateTime<Utc>> {
+    parse_latest_file_path(filename).or_else(|| {
+        LatestDailyFilename::parse(filename)
+            .map(|daily| daily.day().and_time(NaiveTime::MIN).and_utc())
+    })
 }
 
+/// date of the first version of historical rates file from its filename, versioned snapshots aren't matched.
 fn parse_historical_file_path(filename: &str) -> Option<DateTime<Utc>> {
-    if !filename.starts_with("historical-") || !filename.ends_with("Z.json") {
-        return None;
-    }
+    let name = filename
+        .strip_prefix("historical-")?
+        .strip_suffix("Z.json")?;
+
+    parse_historical_file_date(name)
+}
+
+/// date and version of versioned snapshot of historical rates file from its filename, from version 2.
+fn parse_historical_version_file_path(filename: &str) -> Option<(DateTime<Utc>, u32)> {
+    let (date_part, version) = filename
+        .strip_prefix("historical-")?
+        .strip_suffix(".json")?
+        .split_once("Z.v")?;
+    let version = version.parse().ok().filter(|v| *v > 1)?;
 
-    // Extract only the date part (YYYY-MM-DD)
-    let date_part = &filename["historical-".len()..filename.len() - "Z.json".len()];
+    Some((parse_historical_file_date(date_part)?, version))
+}
+
+/// date and version of historical rates file of any version, the first version has no version suffix.
+fn parse_historical_file_version(filename: &str) -> Option<(DateTime<Utc>, u32)> {
+    parse_historical_file_path(filename)
+        .map(|date| (date, 1))
+        .or_else(|| parse_historical_version_file_path(filename))
+}
 
+/// date of YYYY-MM-DD part of historical rates filename.
+fn parse_historical_file_date(date_part: &str) -> Option<DateTime<Utc>> {
     // Split into year, month, and day
     let mut parts = date_part.split('-');
     let year: i32 = parts.next()?.parse().ok()?;
     let month: u32 = parts.next()?.parse().ok()?;
     let day: u32 = parts.next()?.parse().ok()?;
-
-    let date = Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).single()?;
-    Some(date)
-}
-#[cfg(test)]
-mod forex_storage_impl_tests {
-    use chrono::TimeZone;
-
-    use super::*;
-
-    #[test]
-    fn test_generate_latest_file_name() {
-        let expected = "latest-2020-01-01T04:05:06Z.json";
-        let date = Utc.with_ymd_and_hms(2020, 1, 1, 4, 5, 6).unwrap();
-        let ret = generate_latest_file_path(date);
-        println!("{ret}");
-        assert_eq!(&ret, expected);
-
-        let expected = "latest-2024-10-05T23:00:10Z.json";
-        let date = Utc.with_ymd_and_hms(2024, 10, 5, 23, 0, 10).unwrap();
-        let ret = generate_latest_file_path(date);
-        println!("{ret}");
-        assert_eq!(&ret, expected);
-    }
-
-    #[test]
-    fn test_generate_historical_file_name() {
-        let expected = "2020/historical-2020-01-01Z.json";
-        let date = Utc.with_ymd_and_hms(2020, 1, 1, 4, 5, 6).unwrap();
-        let ret = generate_historical_file_path(date);
-        println!("{ret}");
-        assert_eq!(&ret, expected);
-
-        let expected = "2024/historical-2024-10-05Z.json";
-        let date = Utc.with_ymd_and_hms(2024, 10, 5, 23, 0, 10).unwrap();
-        let ret = generate_historical_file_path(date);
-        println!("{ret}");
-        assert_eq!(&ret, expected);
+    if parts.next().is_some() {
+        return None;
     }
 
-    #[test]
-    fn test_paginate_rates() {
-        let v = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
-        let expected = vec![1, 2, 3, 4, 5, 6, 7, 8];
-        let ret = ForexStorageImpl::paginate_rates_list(&v, 1, 8);
-        dbg!(&ret);
-        assert_eq!(ret.has_prev, false);
-        assert_eq!(ret.has_next, true);
-        assert_eq!(ret.rates_list, expected);
-    }
+    Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).single()
+}
 
-    #[test]
-    fn test_parse_historical_file_path() {
-        let filename = "historical-2023-04-11Z.json";
-        let expected = Utc.with_ymd_and_hms(2023, 4, 11, 0, 0, 0).unwrap();
-        let ret = parse_historical_file_path(filename).unwrap();
-        assert_eq!(ret, expected);
+impl TenantScoped for ForexStorageImpl {
+    fn for_tenant(&self, tenant: &TenantId) -> Self {
+        Self {
+            tenant: Some(tenant.clone()),
+            ..self.clone()
+        }
     }
 }
 
@@ -830,11 +3968,18 @@ impl ForexStorage for ForexStorageImpl {
     async fn update_historical_rates_data(
         &self,
         date: DateTime<Utc>,
-        new_data: Vec<Money>,
+        new_data: Vec<(Money, String)>,
     ) -> ForexResult<RatesResponse<Rates>> {
         self.update_historical_rates_data(date, new_data).await
     }
 
+    async fn update_historical_bulk(
+        &self,
+        updates: Vec<HistoricalUpdate>,
+    ) -> ForexResult<Vec<RatesResponse<Rates>>> {
+        self.update_historical_bulk(updates).await
+    }
+
     async fn get_historical(&self, date: DateTime<Utc>) -> ForexResult<RatesResponse<Rates>> {
         self.get_historical(date).await
     }
@@ -852,8 +3997,9 @@ impl ForexStorage for ForexStorageImpl {
         page: u32,
         size: u32,
         order: Order,
+        order_by: OrderBy,
     ) -> ForexResult<RatesList<RatesResponse<Rates>>> {
-        self.get_latest_list(page, size, order).await
+        self.get_latest_list(page, size, order, order_by).await
     }
 
     async fn get_historical_list(
@@ -861,8 +4007,9 @@ impl ForexStorage for ForexStorageImpl {
         page: u32,
         size: u32,
         order: Order,
+        order_by: OrderBy,
     ) -> ForexResult<RatesList<RatesResponse<Rates>>> {
-        self.get_historical_list(page, size, order).await
+        self.get_historical_list(page, size, order, order_by).await
     }
 }
 
@@ -871,4 +4018,609 @@ impl ForexStorageDeletion for ForexStorageImpl {
     async fn clear_latest(&self) -> ForexResult<()> {
         self.clear_latest().await
     }
+
+    async fn prune_latest_archive(&self, bytes: u64) -> ForexResult<Vec<String>> {
+        self.prune_latest_archive(bytes).await
+    }
+}
+
+#[cfg(test)]
+mod forex_storage_impl_tests {
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    use crate::forex::Currency;
+    use crate::global::test_support::{rates, temp_storage};
+
+    use super::*;
+
+    #[test]
+    fn test_generate_latest_file_name() {
+        let expected = "latest-20200101T040506Z.json";
+        let date = Utc.with_ymd_and_hms(2020, 1, 1, 4, 5, 6).unwrap();
+        let ret = generate_latest_file_path(date);
+        println!("{ret}");
+        assert_eq!(&ret, expected);
+
+        let expected = "latest-20241005T230010Z.json";
+        let date = Utc.with_ymd_and_hms(2024, 10, 5, 23, 0, 10).unwrap();
+        let ret = generate_latest_file_path(date);
+        println!("{ret}");
+        assert_eq!(&ret, expected);
+    }
+
+    #[test]
+    fn test_generate_historical_file_name() {
+        let expected = "2020/historical-2020-01-01Z.json";
+        let date = Utc.with_ymd_and_hms(2020, 1, 1, 4, 5, 6).unwrap();
+        let ret = generate_historical_file_path(date);
+        println!("{ret}");
+        assert_eq!(&ret, expected);
+
+        let expected = "2024/historical-2024-10-05Z.json";
+        let date = Utc.with_ymd_and_hms(2024, 10, 5, 23, 0, 10).unwrap();
+        let ret = generate_historical_file_path(date);
+        println!("{ret}");
+        assert_eq!(&ret, expected);
+    }
+
+    #[test]
+    fn test_paginate_rates() {
+        let v = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        let expected = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        let ret = ForexStorageImpl::paginate_rates_list(&v, 1, 8);
+        dbg!(&ret);
+        assert_eq!(ret.has_prev, false);
+        assert_eq!(ret.has_next, true);
+        assert_eq!(ret.rates_list, expected);
+    }
+
+    #[test]
+    fn test_parse_historical_file_path() {
+        let filename = "historical-2023-04-11Z.json";
+        let expected = Utc.with_ymd_and_hms(2023, 4, 11, 0, 0, 0).unwrap();
+        let ret = parse_historical_file_path(filename).unwrap();
+        assert_eq!(ret, expected);
+
+        let ret = parse_historical_file_version("historical-2023-04-11Z.v12.json").unwrap();
+        assert_eq!(ret, (expected, 12));
+        assert_eq!(
+            generate_historical_version_file_path(expected, 12),
+            "2023/historical-2023-04-11Z.v12.json"
+        );
+        assert!(parse_historical_file_version("historical-2023-04-11Z.v1.json").is_none());
+        assert!(parse_historical_file_version("historical-2023-04-11Z.vx.json").is_none());
+        assert!(parse_historical_file_version("historical-2023-04-11.json").is_none());
+        assert_eq!(
+            parse_historical_file_version(filename).unwrap(),
+            (expected, 1)
+        );
+
+        // listing by date doesn't see versioned snapshots, so each date is listed once
+        assert!(parse_historical_file_path("historical-2023-04-11Z.v12.json").is_none());
+        assert!(parse_historical_version_file_path(filename).is_none());
+        assert_eq!(
+            parse_historical_version_file_path("historical-2023-04-11Z.v12.json").unwrap(),
+            (expected, 12)
+        );
+    }
+
+    #[test]
+    fn test_check_rates() {
+        let storage = ForexStorageImpl::new(global::storage_fs());
+        let invalid = RatesResponse::new(
+            "check".to_string(),
+            Rates {
+                date: Utc.with_ymd_and_hms(2024, 10, 5, 0, 0, 0).unwrap(),
+                base: Currency::USD,
+                rates: Default::default(),
+            },
+        );
+        assert!(storage.check_rates(&invalid).is_err());
+        assert!(storage.forced().check_rates(&invalid).is_ok());
+
+        let content = serde_json::to_string(&invalid).unwrap();
+        assert!(storage.check_written(&content).is_err());
+        assert!(storage.check_written("{}").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_relative_path() {
+        let root = Path::new("pfm-data");
+        let path = root.join("latest").join("archive").join("x.json");
+        assert_eq!(relative_path(root, &path), "latest/archive/x.json");
+    }
+
+    #[test]
+    fn test_generate_latest_archive_path() {
+        let date = Utc.with_ymd_and_hms(2024, 10, 5, 23, 0, 10).unwrap();
+        let filename = generate_latest_file_path(date);
+        let ret = generate_latest_archive_path(&filename);
+        assert_eq!(
+            ret.as_deref(),
+            Some("archive/2024/10/latest-20241005T230010Z.json")
+        );
+
+        let ret = generate_latest_archive_path("latest-2024-10-05T23:00:10Z.json");
+        assert_eq!(
+            ret.as_deref(),
+            Some("archive/2024/10/latest-2024-10-05T23:00:10Z.json")
+        );
+
+        let ret = generate_latest_archive_path("latest-20241005.ndjson");
+        assert_eq!(
+            ret.as_deref(),
+            Some("archive/2024/10/latest-20241005.ndjson")
+        );
+
+        assert!(generate_latest_archive_path(".DS_Store").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_read_latest_daily_last() {
+        let rates = |hour| {
+            RatesResponse::new(
+                format!("daily-{hour}"),
+                Rates {
+                    date: Utc.with_ymd_and_hms(2024, 10, 5, hour, 0, 0).unwrap(),
+                    base: Currency::USD,
+                    rates: Default::default(),
+                },
+            )
+        };
+        let path = std::env::temp_dir().join(format!(
+            "pfm-latest-daily-{}-latest-20241005.ndjson",
+            std::process::id()
+        ));
+
+        std::fs::write(&path, "").unwrap();
+        assert!(
+            ForexStorageImpl::read_latest_daily_last(&path)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // more lines than read at once from the end
+        let lines: String = (0..24)
+            .cycle()
+            .take(200)
+            .map(|hour| serde_json::to_string(&rates(hour)).unwrap() + "\n")
+            .collect();
+        assert!(lines.len() as u64 > LATEST_DAILY_TAIL_CHUNK_BYTES);
+        std::fs::write(&path, &lines).unwrap();
+        let ret = ForexStorageImpl::read_latest_daily_last(&path)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ret.source, "daily-7");
+
+        // torn last line falls back to the last parseable one
+        std::fs::write(&path, lines + r#"{"id":"#).unwrap();
+        let ret = ForexStorageImpl::read_latest_daily_last(&path)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ret.source, "daily-7");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_append_latest_daily() {
+        let (storage, root) = temp_storage("latest-append");
+        let date = |hour| Utc.with_ymd_and_hms(2024, 10, 5, hour, 0, 0).unwrap();
+        let rates = |hour| {
+            RatesResponse::new(
+                format!("daily-{hour}"),
+                Rates {
+                    date: date(hour),
+                    base: Currency::USD,
+                    rates: Default::default(),
+                },
+            )
+        };
+        let path = root.join("latest").join("latest-20241005.ndjson");
+
+        storage
+            .append_latest_daily(date(1), &rates(1))
+            .await
+            .unwrap();
+        storage
+            .append_latest_daily(date(2), &rates(2))
+            .await
+            .unwrap();
+        // torn by other writer, appended line starts on its own line
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        std::io::Write::write_all(&mut file, br#"{"id":"#).unwrap();
+        storage
+            .append_latest_daily(date(3), &rates(3))
+            .await
+            .unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 4);
+        let ret = ForexStorageImpl::read_latest_daily_last(&path)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ret.source, "daily-3");
+
+        // journaled checksums follow the file's content
+        let changes = storage.get_changes(date(3)).await.unwrap();
+        let last = changes.last().unwrap();
+        assert_eq!(last.after, Some(checksum(content.as_bytes())));
+        assert_ne!(last.before, changes[1].after);
+    }
+
+    #[tokio::test]
+    async fn test_stats_and_prune_latest_archive() {
+        let (storage, root) = temp_storage("storage-stats");
+        let write = |path: &str, len: usize| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "x".repeat(len)).unwrap();
+        };
+        write("historical/2024/historical-2024-01-02Z.json", 10);
+        write("historical/2024/historical-2024-03-04Z.json", 20);
+        write("latest/archive/2024/10/latest-20241005T010000Z.json", 100);
+        write("latest/archive/2024/10/latest-20241006T010000Z.json", 100);
+        write("latest/latest-20241007T010000Z.json", 100);
+        write(
+            "metadata/signatures/latest/latest-20241005T010000Z.json.sig",
+            1000,
+        );
+
+        let stats = storage.stats().await.unwrap();
+        assert_eq!(stats.files, 6);
+        assert_eq!(stats.bytes, 1330);
+        // metadata can't be pruned, hence not counted against quota
+        assert_eq!(stats.quota_bytes(), 330);
+        let historical = stats.subtree("historical").unwrap();
+        assert_eq!(historical.files, 2);
+        assert_eq!(historical.bytes, 30);
+        assert_eq!(
+            historical.oldest,
+            Some(Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap())
+        );
+        assert_eq!(
+            historical.newest,
+            Some(Utc.with_ymd_and_hms(2024, 3, 4, 0, 0, 0).unwrap())
+        );
+        let latest = stats.subtree("latest").unwrap();
+        assert_eq!(latest.files, 3);
+        assert_eq!(
+            latest.oldest,
+            Some(Utc.with_ymd_and_hms(2024, 10, 5, 1, 0, 0).unwrap())
+        );
+        assert_eq!(stats.subtree("metadata").unwrap().files, 1);
+        assert_eq!(stats.subtree(TENANTS_DIR_NAME).unwrap().files, 0);
+
+        // only the oldest archived file is needed to free the bytes, current latest is never pruned
+        let pruned = storage.prune_latest_archive(50).await.unwrap();
+        assert_eq!(
+            pruned,
+            vec!["latest/archive/2024/10/latest-20241005T010000Z.json".to_string()]
+        );
+        let pruned = storage.prune_latest_archive(1000).await.unwrap();
+        assert_eq!(pruned.len(), 1);
+        let stats = storage.stats().await.unwrap();
+        assert_eq!(stats.subtree("latest").unwrap().files, 1);
+        // signatures of pruned files are deleted, deletions are journaled
+        assert!(
+            !root
+                .join("metadata/signatures/latest/latest-20241005T010000Z.json.sig")
+                .exists()
+        );
+        assert_eq!(stats.subtree("metadata").unwrap().files, 1);
+    }
+
+    #[tokio::test]
+    async fn test_immutable_historical_versions() {
+        let (storage, root) = temp_storage("storage-versions");
+        let immutable = storage.immutable();
+        let date = Utc.with_ymd_and_hms(2024, 3, 4, 0, 0, 0).unwrap();
+        let rates = |source: &str| {
+            let mut rates = RatesResponse::new(
+                source.to_string(),
+                Rates {
+                    date,
+                    base: Currency::USD,
+                    rates: Default::default(),
+                },
+            );
+            rates.data.rates.set_rate(Money::USD(dec!(1)));
+            rates.data.rates.set_rate(Money::IDR(dec!(16000)));
+            rates
+        };
+
+        immutable
+            .insert_historical(date, &rates("first"))
+            .await
+            .unwrap();
+        let first =
+            std::fs::read(root.join("historical/2024/historical-2024-03-04Z.json")).unwrap();
+        immutable
+            .insert_historical(date, &rates("second"))
+            .await
+            .unwrap();
+        immutable
+            .update_historical_rates_data(date, vec![(Money::EUR(dec!(0.9)), "fix".to_string())])
+            .await
+            .unwrap();
+
+        // stored files are kept as written, the newest version is read
+        assert_eq!(
+            std::fs::read(root.join("historical/2024/historical-2024-03-04Z.json")).unwrap(),
+            first
+        );
+        let versions = storage.get_historical_versions(date).await.unwrap();
+        let paths: Vec<&str> = versions.iter().map(|v| v.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "historical/2024/historical-2024-03-04Z.json",
+                "historical/2024/historical-2024-03-04Z.v2.json",
+                "historical/2024/historical-2024-03-04Z.v3.json",
+            ]
+        );
+        assert!(versions[2].current && !versions[1].current);
+        assert_eq!(versions[0].checksum, checksum(&first));
+
+        let current = storage.get_historical(date).await.unwrap();
+        assert_eq!(current.source, "second");
+        assert_eq!(current.data.rates.eur, dec!(0.9));
+        let range = storage.get_historical_range(date, date).await.unwrap();
+        assert_eq!(range.len(), 1);
+        assert_eq!(range[0].data.rates.eur, dec!(0.9));
+        assert_eq!(storage.read_historical_list().await.unwrap().len(), 1);
+        let first = storage.get_historical_version(date, 1).await.unwrap();
+        assert_eq!(first.source, "first");
+        assert!(storage.get_historical_version(date, 4).await.is_err());
+        assert!(storage.get_historical_version(date, 0).await.is_err());
+
+        // journal chains versions
+        assert!(storage.replay_changes(date).await.unwrap().is_consistent());
+
+        // mutable storage rewrites the newest version
+        storage
+            .insert_historical(date, &rates("third"))
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.get_historical_versions(date).await.unwrap().len(),
+            3
+        );
+        assert_eq!(
+            storage
+                .get_historical_version(date, 3)
+                .await
+                .unwrap()
+                .source,
+            "third"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_historical_page_skips_files_before_cursor() {
+        let (storage, root) = temp_storage("storage-page");
+        let dates: Vec<DateTime<Utc>> = (1..=5)
+            .map(|day| Utc.with_ymd_and_hms(2024, 3, day, 0, 0, 0).unwrap())
+            .collect();
+        for date in &dates {
+            storage
+                .insert_historical(*date, &rates(*date, &[]))
+                .await
+                .unwrap();
+        }
+        let page_dates = |page: &RatesPage<RatesResponse<Rates>>| -> Vec<DateTime<Utc>> {
+            page.rates_list.iter().map(|r| r.data.date).collect()
+        };
+
+        let first = storage
+            .get_historical_page(None, 2, Order::ASC, OrderBy::DataDate)
+            .await
+            .unwrap();
+        assert_eq!(page_dates(&first), dates[0..2]);
+
+        // files before the cursor are not read
+        std::fs::write(
+            root.join("historical/2024/historical-2024-03-01Z.json"),
+            "not json",
+        )
+        .unwrap();
+        let second = storage
+            .get_historical_page(first.next_cursor, 2, Order::ASC, OrderBy::DataDate)
+            .await
+            .unwrap();
+        assert_eq!(page_dates(&second), dates[2..4]);
+        let third = storage
+            .get_historical_page(second.next_cursor, 2, Order::ASC, OrderBy::DataDate)
+            .await
+            .unwrap();
+        assert_eq!(page_dates(&third), dates[4..5]);
+        assert!(third.next_cursor.is_none());
+
+        let desc = storage
+            .get_historical_page(second.next_cursor, 1, Order::DESC, OrderBy::DataDate)
+            .await
+            .unwrap();
+        assert_eq!(page_dates(&desc), vec![dates[2]]);
+        assert!(desc.next_cursor.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_historical_files_skip_temporary_files() {
+        let (storage, root) = temp_storage("storage-tmp");
+        let date = Utc.with_ymd_and_hms(2024, 3, 4, 0, 0, 0).unwrap();
+        storage
+            .insert_historical(date, &rates(date, &[]))
+            .await
+            .unwrap();
+
+        // interrupted atomic write of next day
+        std::fs::write(
+            root.join("historical/2024/historical-2024-03-05Z.json.tmp"),
+            "{\"partial",
+        )
+        .unwrap();
+
+        let range = storage
+            .get_historical_range(date, date + TimeDelta::days(1))
+            .await
+            .unwrap();
+        assert_eq!(range.len(), 1);
+        assert_eq!(storage.read_historical_list().await.unwrap().len(), 1);
+        let page = storage
+            .get_historical_page(None, 10, Order::ASC, OrderBy::DataDate)
+            .await
+            .unwrap();
+        assert_eq!(page.rates_list.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_clear_latest_archive() {
+        let (storage, root) = temp_storage("storage-archive");
+        let dates = [
+            Utc.with_ymd_and_hms(2024, 2, 28, 23, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 3, 1, 1, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2024, 3, 1, 2, 0, 0).unwrap(),
+        ];
+        for date in dates {
+            storage
+                .insert_latest(date, &rates(date, &[]))
+                .await
+                .unwrap();
+        }
+        let filenames: Vec<String> = dates
+            .iter()
+            .map(|d| generate_latest_file_path(*d))
+            .collect();
+
+        storage
+            .clear_latest_by(LatestRotation::Archive)
+            .await
+            .unwrap();
+
+        // only the newest stays, older ones are archived by their months
+        let latest = root.join("latest");
+        let kept: Vec<String> = std::fs::read_dir(&latest)
+            .unwrap()
+            .map(|entry| entry.unwrap())
+            .filter(|entry| entry.path().is_file())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(kept, vec![filenames[2].clone()]);
+        let archive = latest.join(LATEST_ARCHIVE_DIR_NAME);
+        assert!(archive.join("2024/02").join(&filenames[0]).is_file());
+        assert!(archive.join("2024/03").join(&filenames[1]).is_file());
+        assert_eq!(storage.get_latest().await.unwrap().data.date, dates[2]);
+
+        // clearing again keeps the newest
+        storage
+            .clear_latest_by(LatestRotation::Archive)
+            .await
+            .unwrap();
+        assert!(latest.join(&filenames[2]).is_file());
+
+        // oldest archived files are pruned first
+        let pruned = storage.prune_latest_archive(1).await.unwrap();
+        assert_eq!(
+            pruned,
+            vec![format!(
+                "latest/{}/2024/02/{}",
+                LATEST_ARCHIVE_DIR_NAME, filenames[0]
+            )]
+        );
+        assert!(archive.join("2024/03").join(&filenames[1]).is_file());
+    }
+
+    #[test]
+    fn test_dedup_by_date() {
+        let rates = |date, source: &str| {
+            RatesResponse::new(
+                source.to_string(),
+                Rates {
+                    date,
+                    base: Currency::USD,
+                    rates: Default::default(),
+                },
+            )
+        };
+        let first = Utc.with_ymd_and_hms(2023, 4, 11, 0, 0, 0).unwrap();
+        let second = Utc.with_ymd_and_hms(2023, 4, 12, 0, 0, 0).unwrap();
+        let batch = vec![
+            rates(first, "a"),
+            rates(second, "b"),
+            rates(Utc.with_ymd_and_hms(2023, 4, 11, 5, 0, 0).unwrap(), "c"),
+        ];
+
+        let ret: Vec<&str> = dedup_by_date(&batch)
+            .into_iter()
+            .map(|rate| rate.source.as_str())
+            .collect();
+        assert_eq!(ret, vec!["b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_journal_rotated_by_month() {
+        let (storage, root) = temp_storage("storage-journal");
+        let date = |month, day| Utc.with_ymd_and_hms(2024, month, day, 0, 0, 0).unwrap();
+        let rates = |date| rates(date, &[Money::IDR(dec!(15500))]);
+        storage
+            .insert_historical_batch(vec![rates(date(1, 31)), rates(date(2, 1))])
+            .await
+            .unwrap();
+
+        let metadata = root.join("metadata");
+        assert!(metadata.join("journal").join("2024-01.jsonl").is_file());
+        assert!(metadata.join("journal").join("2024-02.jsonl").is_file());
+        assert!(!metadata.join(JOURNAL_FILENAME).exists());
+
+        let changes = storage.get_changes(date(2, 1)).await.unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, ChangeKind::Insert);
+        assert_eq!(changes[0].rates_kind, RatesKind::Historical);
+        assert_eq!(changes[0].date, date(2, 1));
+
+        // changes journaled before rotation are still read
+        let legacy = storage.get_changes(date(1, 31)).await.unwrap();
+        let line = serde_json::to_string(&legacy[0]).unwrap() + "\n";
+        std::fs::write(metadata.join(JOURNAL_FILENAME), line).unwrap();
+        assert_eq!(storage.get_changes(date(1, 31)).await.unwrap().len(), 2);
+        assert!(storage.get_changes(date(3, 1)).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sign_and_verify_historical() {
+        let signer = RatesSigner::from_seed_hex(&"07".repeat(32)).unwrap();
+        let public_key = signer.public_key_hex();
+        let (storage, root) = temp_storage("storage-signing");
+        let storage = storage.with_signer(signer);
+        let date = |day| Utc.with_ymd_and_hms(2024, 3, day, 0, 0, 0).unwrap();
+        let rates = |date| rates(date, &[Money::IDR(dec!(15700))]);
+        storage
+            .insert_historical_batch(vec![rates(date(1)), rates(date(2))])
+            .await
+            .unwrap();
+
+        let report = storage.verify_signatures(&public_key).await.unwrap();
+        assert_eq!(report.verified, 2);
+        assert!(report.unsigned.is_empty());
+        assert!(report.invalid.is_empty());
+
+        let tampered = root
+            .join("historical")
+            .join(generate_historical_file_path(date(2)));
+        let content = std::fs::read_to_string(&tampered)
+            .unwrap()
+            .replace("15700", "15800");
+        std::fs::write(&tampered, content).unwrap();
+        let report = storage.verify_signatures(&public_key).await.unwrap();
+        assert_eq!(report.verified, 1);
+        assert_eq!(report.invalid.len(), 1);
+    }
 }
@@ -0,0 +1,169 @@
+// internal_signing.rs HMAC-SHA256 signed requests between pfm-cron and pfm-http, e.g. cron triggering admin endpoints.
+// signature covers method, path with query, unix timestamp and body. requests outside of allowed clock skew
+// are rejected, and signatures already seen within it are rejected as replays.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context;
+use chrono::{DateTime, TimeDelta, Utc};
+use ring::hmac;
+
+use crate::error::AsInternalError;
+use crate::forex::{ForexError, ForexResult};
+use crate::global::Secret;
+
+/// header containing hex encoded HMAC-SHA256 of the request signed with shared internal secret.
+pub const INTERNAL_SIGNATURE_HEADER: &str = "X-Pfm-Internal-Signature";
+
+/// header containing unix timestamp in seconds the request was signed at.
+pub const INTERNAL_TIMESTAMP_HEADER: &str = "X-Pfm-Internal-Timestamp";
+
+/// Signs and verifies internal requests with shared secret.
+#[derive(Clone)]
+pub struct InternalSigner {
+    secret: Secret,
+    max_skew: TimeDelta,
+
+    /// signatures verified within allowed skew, by their timestamps.
+    seen: Arc<Mutex<HashMap<String, DateTime<Utc>>>>,
+}
+
+impl InternalSigner {
+    pub fn new(secret: Secret, max_skew: TimeDelta) -> Self {
+        Self {
+            secret,
+            max_skew,
+            seen: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// whether shared secret is set, internal requests can't be signed nor verified otherwise.
+    pub fn is_enabled(&self) -> bool {
+        !self.secret.is_empty()
+    }
+
+    /// signature of the request, `path` includes query if any.
+    pub fn sign(&self, method: &str, path: &str, timestamp: i64, body: &[u8]) -> String {
+        let tag = hmac::sign(&self.key(), &message(method, path, timestamp, body));
+        format!("sha256={}", hex(tag.as_ref()))
+    }
+
+    /// verify signature and timestamp headers of the request, then remember the signature to reject its replays.
+    pub fn verify(
+        &self,
+        method: &str,
+        path: &str,
+        timestamp: &str,
+        signature: &str,
+        body: &[u8],
+        now: DateTime<Utc>,
+    ) -> ForexResult<()> {
+        if !self.is_enabled() {
+            return Err(ForexError::client_error("internal requests are disabled"));
+        }
+        let timestamp: i64 = timestamp
+            .trim()
+            .parse()
+            .map_err(|_| ForexError::client_error("invalid internal request timestamp"))?;
+        let signed_at = DateTime::from_timestamp(timestamp, 0).ok_or(ForexError::client_error(
+            "invalid internal request timestamp",
+        ))?;
+        if (now - signed_at).abs() > self.max_skew {
+            return Err(ForexError::client_error("internal request expired"));
+        }
+
+        let tag = signature
+            .trim()
+            .strip_prefix("sha256=")
+            .and_then(parse_hex)
+            .ok_or(ForexError::client_error(
+                "invalid internal request signature",
+            ))?;
+        hmac::verify(&self.key(), &message(method, path, timestamp, body), &tag)
+            .map_err(|_| ForexError::client_error("invalid internal request signature"))?;
+
+        let mut seen = self
+            .seen
+            .lock()
+            .map_err(|_| anyhow::anyhow!("internal signatures lock poisoned"))
+            .as_internal_err()?;
+        seen.retain(|_, signed_at| (now - *signed_at).abs() <= self.max_skew);
+        if seen.insert(hex(&tag), signed_at).is_some() {
+            return Err(ForexError::client_error("internal request replayed"));
+        }
+
+        Ok(())
+    }
+
+    /// key of current secret, rebuilt on each use so rotated secret is picked up.
+    fn key(&self) -> hmac::Key {
+        hmac::Key::new(hmac::HMAC_SHA256, self.secret.expose().as_bytes())
+    }
+}
+
+/// Client of internal endpoints of another pfm service, e.g. pfm-cron calling admin endpoints of pfm-http.
+#[derive(Clone)]
+pub struct InternalClient {
+    base_url: String,
+    signer: InternalSigner,
+    client: reqwest::Client,
+}
+
+impl InternalClient {
+    pub fn new(base_url: &str, signer: InternalSigner, client: reqwest::Client) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            signer,
+            client,
+        }
+    }
+
+    /// send signed POST request with json body to the path, e.g. `/admin/cache/warm`.
+    pub async fn post(&self, path: &str, body: Vec<u8>) -> ForexResult<()> {
+        let timestamp = Utc::now().timestamp();
+        let signature = self.signer.sign("POST", path, timestamp, &body);
+
+        let resp = self
+            .client
+            .post(format!("{}{}", self.base_url, path))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(INTERNAL_TIMESTAMP_HEADER, timestamp.to_string())
+            .header(INTERNAL_SIGNATURE_HEADER, signature)
+            .body(body)
+            .send()
+            .await
+            .context("invoking internal request")
+            .as_internal_err()?;
+        if !resp.status().is_success() {
+            return Err(ForexError::internal_error(&format!(
+                "internal request to {} responded with {}",
+                path,
+                resp.status()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+fn message(method: &str, path: &str, timestamp: i64, body: &[u8]) -> Vec<u8> {
+    let mut message = format!("{}\n{}\n{}\n", method.to_uppercase(), path, timestamp).into_bytes();
+    message.extend_from_slice(body);
+    message
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn parse_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) || !s.is_ascii() {
+        return None;
+    }
+
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
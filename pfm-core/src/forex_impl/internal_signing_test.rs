@@ -0,0 +1,137 @@
+use chrono::{TimeDelta, TimeZone, Utc};
+
+use crate::global::Secret;
+
+use super::internal_signing::InternalSigner;
+
+fn signer(secret: &str) -> InternalSigner {
+    InternalSigner::new(Secret::from(secret), TimeDelta::seconds(300))
+}
+
+#[test]
+fn test_sign_and_verify() {
+    let now = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+    let timestamp = now.timestamp();
+    let body = br#"{"days":7}"#;
+    let signer = signer("secret");
+    let signature = signer.sign("POST", "/admin/cache/warm", timestamp, body);
+    assert!(signature.starts_with("sha256="));
+
+    // receiver has its own signer with the same secret
+    let verifier = self::signer("secret");
+    let ts = timestamp.to_string();
+    verifier
+        .verify(
+            "POST",
+            "/admin/cache/warm",
+            &ts,
+            &signature,
+            body,
+            now + TimeDelta::seconds(10),
+        )
+        .unwrap();
+
+    // the same request again is a replay
+    assert!(
+        verifier
+            .verify("POST", "/admin/cache/warm", &ts, &signature, body, now)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_verify_rejects_tampered_requests() {
+    let now = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+    let timestamp = now.timestamp();
+    let ts = timestamp.to_string();
+    let signature = signer("secret").sign("POST", "/admin/cache/warm", timestamp, b"{}");
+    let verifier = signer("secret");
+
+    // other body, path, method or timestamp
+    assert!(
+        verifier
+            .verify("POST", "/admin/cache/warm", &ts, &signature, b"{ }", now)
+            .is_err()
+    );
+    assert!(
+        verifier
+            .verify("POST", "/admin/jobs/rerun", &ts, &signature, b"{}", now)
+            .is_err()
+    );
+    assert!(
+        verifier
+            .verify("GET", "/admin/cache/warm", &ts, &signature, b"{}", now)
+            .is_err()
+    );
+    let other_ts = (timestamp + 1).to_string();
+    assert!(
+        verifier
+            .verify(
+                "POST",
+                "/admin/cache/warm",
+                &other_ts,
+                &signature,
+                b"{}",
+                now
+            )
+            .is_err()
+    );
+
+    // other secret
+    assert!(
+        signer("other")
+            .verify("POST", "/admin/cache/warm", &ts, &signature, b"{}", now)
+            .is_err()
+    );
+
+    // malformed headers
+    assert!(
+        verifier
+            .verify("POST", "/admin/cache/warm", "now", &signature, b"{}", now)
+            .is_err()
+    );
+    assert!(
+        verifier
+            .verify("POST", "/admin/cache/warm", &ts, "sha256=zz", b"{}", now)
+            .is_err()
+    );
+
+    // untampered is still fine
+    verifier
+        .verify("POST", "/admin/cache/warm", &ts, &signature, b"{}", now)
+        .unwrap();
+}
+
+#[test]
+fn test_verify_rejects_expired_and_disabled() {
+    let now = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+    let signed_at = now - TimeDelta::seconds(301);
+    let signature = signer("secret").sign("POST", "/admin/cache/warm", signed_at.timestamp(), b"");
+    let ts = signed_at.timestamp().to_string();
+    assert!(
+        signer("secret")
+            .verify("POST", "/admin/cache/warm", &ts, &signature, b"", now)
+            .is_err()
+    );
+
+    // signed in the future beyond skew
+    let signed_at = now + TimeDelta::seconds(301);
+    let signature = signer("secret").sign("POST", "/admin/cache/warm", signed_at.timestamp(), b"");
+    let ts = signed_at.timestamp().to_string();
+    assert!(
+        signer("secret")
+            .verify("POST", "/admin/cache/warm", &ts, &signature, b"", now)
+            .is_err()
+    );
+
+    // no secret configured
+    let disabled = signer("");
+    assert!(!disabled.is_enabled());
+    let signature = disabled.sign("POST", "/admin/cache/warm", now.timestamp(), b"");
+    let ts = now.timestamp().to_string();
+    assert!(
+        disabled
+            .verify("POST", "/admin/cache/warm", &ts, &signature, b"", now)
+            .is_err()
+    );
+}
@@ -0,0 +1,141 @@
+// latest_filename.rs versioned filename scheme of latest rates files, the only place they're formatted and parsed.
+
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+const LATEST_FILENAME_PREFIX: &str = "latest-";
+
+const LATEST_FILENAME_SUFFIX: &str = "Z.json";
+
+const LATEST_DAILY_FILENAME_SUFFIX: &str = ".ndjson";
+
+const LATEST_DAILY_DATE_FORMAT: &str = "%Y%m%d";
+
+/// Version of latest rates filename scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LatestFilenameVersion {
+    /// `latest-YYYY-MM-DDThh:mm:ssZ.json`, colons are invalid on NTFS and some mounted filesystems.
+    V1,
+
+    /// `latest-YYYYMMDDThhmmssZ.json`
+    V2,
+}
+
+impl LatestFilenameVersion {
+    /// version of newly written files
+    pub const CURRENT: Self = Self::V2;
+
+    fn date_format(&self) -> &'static str {
+        match self {
+            Self::V1 => "%Y-%m-%dT%H:%M:%S",
+            Self::V2 => "%Y%m%dT%H%M%S",
+        }
+    }
+}
+
+/// Filename of latest rates file, carrying the date it's polled at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatestFilename {
+    date: DateTime<Utc>,
+    version: LatestFilenameVersion,
+}
+
+impl LatestFilename {
+    /// filename of current version, sub-second precision is dropped.
+    pub fn new(date: DateTime<Utc>) -> Self {
+        Self::with_version(date, LatestFilenameVersion::CURRENT)
+    }
+
+    pub fn with_version(date: DateTime<Utc>, version: LatestFilenameVersion) -> Self {
+        Self { date, version }
+    }
+
+    /// parse filename of any version, none if it's not latest rates filename.
+    pub fn parse(filename: &str) -> Option<Self> {
+        let date_part = filename
+            .strip_prefix(LATEST_FILENAME_PREFIX)?
+            .strip_suffix(LATEST_FILENAME_SUFFIX)?;
+
+        [LatestFilenameVersion::V2, LatestFilenameVersion::V1]
+            .into_iter()
+            .find_map(|version| {
+                NaiveDateTime::parse_from_str(date_part, version.date_format())
+                    .ok()
+                    .map(|date| Self::with_version(date.and_utc(), version))
+            })
+    }
+
+    pub fn date(&self) -> DateTime<Utc> {
+        self.date
+    }
+
+    #[cfg(test)]
+    pub fn version(&self) -> LatestFilenameVersion {
+        self.version
+    }
+
+    /// whether written with older scheme, hence to be migrated.
+    pub fn is_outdated(&self) -> bool {
+        self.version < LatestFilenameVersion::CURRENT
+    }
+
+    /// the same filename in current version.
+    pub fn to_current(self) -> Self {
+        Self::new(self.date)
+    }
+}
+
+impl fmt::Display for LatestFilename {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            LATEST_FILENAME_PREFIX,
+            self.date.format(self.version.date_format()),
+            LATEST_FILENAME_SUFFIX
+        )
+    }
+}
+
+/// Filename of daily latest rates file, `latest-YYYYMMDD.ndjson`, each line is a snapshot polled on the day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LatestDailyFilename {
+    day: NaiveDate,
+}
+
+impl LatestDailyFilename {
+    /// filename of the day of the date.
+    pub fn new(date: DateTime<Utc>) -> Self {
+        Self {
+            day: date.date_naive(),
+        }
+    }
+
+    /// parse filename, none if it's not daily latest rates filename.
+    pub fn parse(filename: &str) -> Option<Self> {
+        let date_part = filename
+            .strip_prefix(LATEST_FILENAME_PREFIX)?
+            .strip_suffix(LATEST_DAILY_FILENAME_SUFFIX)?;
+
+        NaiveDate::parse_from_str(date_part, LATEST_DAILY_DATE_FORMAT)
+            .ok()
+            .map(|day| Self { day })
+    }
+
+    pub fn day(&self) -> NaiveDate {
+        self.day
+    }
+}
+
+impl fmt::Display for LatestDailyFilename {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            LATEST_FILENAME_PREFIX,
+            self.day.format(LATEST_DAILY_DATE_FORMAT),
+            LATEST_DAILY_FILENAME_SUFFIX
+        )
+    }
+}
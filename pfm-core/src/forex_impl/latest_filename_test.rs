@@ -0,0 +1,58 @@
+use chrono::{TimeZone, Utc};
+
+use super::latest_filename::{LatestDailyFilename, LatestFilename, LatestFilenameVersion};
+
+#[test]
+fn test_latest_filename_format() {
+    let date = Utc.with_ymd_and_hms(2024, 10, 5, 23, 0, 10).unwrap();
+
+    let filename = LatestFilename::new(date);
+    assert_eq!(filename.to_string(), "latest-20241005T230010Z.json");
+    assert_eq!(filename.version(), LatestFilenameVersion::V2);
+
+    let filename = LatestFilename::with_version(date, LatestFilenameVersion::V1);
+    assert_eq!(filename.to_string(), "latest-2024-10-05T23:00:10Z.json");
+}
+
+#[test]
+fn test_latest_filename_parse() {
+    let date = Utc.with_ymd_and_hms(2024, 10, 5, 23, 0, 10).unwrap();
+
+    let v2 = LatestFilename::parse("latest-20241005T230010Z.json").unwrap();
+    assert_eq!(v2.date(), date);
+    assert_eq!(v2.version(), LatestFilenameVersion::V2);
+    assert!(!v2.is_outdated());
+
+    let v1 = LatestFilename::parse("latest-2024-10-05T23:00:10Z.json").unwrap();
+    assert_eq!(v1.date(), date);
+    assert_eq!(v1.version(), LatestFilenameVersion::V1);
+    assert!(v1.is_outdated());
+    assert_eq!(v1.to_current(), v2);
+
+    // roundtrip
+    assert_eq!(LatestFilename::parse(&v1.to_string()), Some(v1));
+    assert_eq!(LatestFilename::parse(&v2.to_string()), Some(v2));
+
+    assert!(LatestFilename::parse("latest-2024-10-05Z.json").is_none());
+    assert!(LatestFilename::parse("latest-20241005T230010Z.json.sig").is_none());
+    assert!(LatestFilename::parse("historical-2024-10-05Z.json").is_none());
+    assert!(LatestFilename::parse(".DS_Store").is_none());
+}
+
+#[test]
+fn test_latest_daily_filename() {
+    let date = Utc.with_ymd_and_hms(2024, 10, 5, 23, 0, 10).unwrap();
+
+    let filename = LatestDailyFilename::new(date);
+    assert_eq!(filename.to_string(), "latest-20241005.ndjson");
+    assert_eq!(filename.day(), date.date_naive());
+    assert_eq!(
+        LatestDailyFilename::parse("latest-20241005.ndjson"),
+        Some(filename)
+    );
+
+    // snapshot files are not daily files, and the other way around
+    assert!(LatestDailyFilename::parse("latest-20241005T230010Z.json").is_none());
+    assert!(LatestFilename::parse("latest-20241005.ndjson").is_none());
+    assert!(LatestDailyFilename::parse("latest-20241005.ndjson.sig").is_none());
+}
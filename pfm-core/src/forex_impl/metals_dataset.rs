@@ -0,0 +1,118 @@
+// metals_dataset.rs load historical metal rates from static dataset of daily fixes, e.g. exported LBMA prices.
+// used to fill metal rates on dates before 3rd party providers' coverage, e.g. XAU before 2013 in openexchangerates.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Deserialize;
+
+use crate::error::AsInternalError;
+use crate::forex::{
+    Currency, ForexError, ForexResult,
+    entity::{Rates, RatesData, RatesResponse},
+    interface::ForexHistoricalRates,
+};
+
+/// source recorded on rates coming from the dataset, distinct from 3rd party providers.
+pub const SOURCE: &str = "lbma-static-dataset";
+
+/// csv row of daily fixes in USD per troy ounce, empty column means no fix on that date.
+/// header: date,xau,xag,xpt
+#[derive(Debug, Deserialize)]
+struct MetalFixRecord {
+    date: NaiveDate,
+    xau: Option<Decimal>,
+    xag: Option<Decimal>,
+    xpt: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MetalsDataset {
+    fixes: BTreeMap<NaiveDate, RatesData>,
+}
+
+impl MetalsDataset {
+    /// parse dataset from csv with header `date,xau,xag,xpt`, prices in USD per troy ounce.
+    pub fn from_csv(reader: impl Read) -> ForexResult<Self> {
+        let mut rdr = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(reader);
+
+        let mut fixes = BTreeMap::new();
+        for (i, result) in rdr.deserialize::<MetalFixRecord>().enumerate() {
+            let record = result.map_err(|err| {
+                ForexError::client_error(format!("metals dataset row {}: {}", i + 1, err).as_str())
+            })?;
+            let rates = RatesData {
+                xau: usd_based(record.xau, "XAU", record.date)?,
+                xag: usd_based(record.xag, "XAG", record.date)?,
+                xpt: usd_based(record.xpt, "XPT", record.date)?,
+                ..Default::default()
+            };
+            fixes.insert(record.date, rates);
+        }
+
+        Ok(Self { fixes })
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> ForexResult<Self> {
+        let file = std::fs::File::open(path)
+            .context("metals dataset opening file")
+            .as_internal_err()?;
+        Self::from_csv(std::io::BufReader::new(file))
+    }
+
+    /// dates having fixes, ascending.
+    pub fn dates(&self) -> Vec<DateTime<Utc>> {
+        self.fixes.keys().map(|date| to_datetime(*date)).collect()
+    }
+}
+
+/// convert USD price of 1 troy ounce into amount of metal per 1 USD, like rates from providers.
+fn usd_based(price: Option<Decimal>, code: &str, date: NaiveDate) -> ForexResult<Decimal> {
+    match price {
+        None => Ok(Decimal::ZERO),
+        Some(price) if price <= Decimal::ZERO => Err(ForexError::client_error(
+            format!("metals dataset has non positive {} price on {}", code, date).as_str(),
+        )),
+        Some(price) => Ok(dec!(1) / price),
+    }
+}
+
+fn to_datetime(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()
+}
+
+#[async_trait]
+impl ForexHistoricalRates for MetalsDataset {
+    async fn historical_rates(
+        &self,
+        date: DateTime<Utc>,
+        base: Currency,
+    ) -> ForexResult<RatesResponse<Rates>> {
+        if base != Currency::USD {
+            return Err(ForexError::client_error(
+                "metals dataset only provides USD based rates",
+            ));
+        }
+        let fix = self.fixes.get(&date.date_naive()).ok_or_else(|| {
+            ForexError::client_error(
+                format!("metals dataset has no fixes on {}", date.date_naive()).as_str(),
+            )
+        })?;
+
+        let rates = Rates {
+            date: to_datetime(date.date_naive()),
+            base,
+            rates: fix.clone(),
+        };
+
+        Ok(RatesResponse::new(SOURCE.to_string(), rates))
+    }
+}
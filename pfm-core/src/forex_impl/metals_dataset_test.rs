@@ -0,0 +1,50 @@
+use chrono::{TimeZone, Utc};
+use rust_decimal_macros::dec;
+
+use crate::forex::{Currency, interface::ForexHistoricalRates};
+
+use super::metals_dataset::{MetalsDataset, SOURCE};
+
+const CSV: &str = "date,xau,xag,xpt
+2010-01-04,1000,20,
+2010-01-05, 1250 ,,1600
+";
+
+#[tokio::test]
+async fn test_metals_dataset() {
+    let dataset = MetalsDataset::from_csv(CSV.as_bytes()).unwrap();
+    assert_eq!(
+        dataset.dates(),
+        vec![
+            Utc.with_ymd_and_hms(2010, 1, 4, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2010, 1, 5, 0, 0, 0).unwrap(),
+        ]
+    );
+
+    let date = Utc.with_ymd_and_hms(2010, 1, 5, 12, 0, 0).unwrap();
+    let ret = dataset.historical_rates(date, Currency::USD).await.unwrap();
+    assert_eq!(ret.source, SOURCE);
+    assert_eq!(
+        ret.data.date,
+        Utc.with_ymd_and_hms(2010, 1, 5, 0, 0, 0).unwrap()
+    );
+    assert_eq!(ret.data.rates.xau, dec!(0.0008));
+    assert_eq!(ret.data.rates.xag, dec!(0));
+    assert_eq!(ret.data.rates.xpt, dec!(0.000625));
+    assert_eq!(ret.data.rates.idr, dec!(0));
+
+    assert!(dataset.historical_rates(date, Currency::IDR).await.is_err());
+    let missing = Utc.with_ymd_and_hms(2010, 1, 6, 0, 0, 0).unwrap();
+    assert!(
+        dataset
+            .historical_rates(missing, Currency::USD)
+            .await
+            .is_err()
+    );
+}
+
+#[test]
+fn test_metals_dataset_invalid() {
+    assert!(MetalsDataset::from_csv("date,xau,xag,xpt\n2010-01-04,0,,\n".as_bytes()).is_err());
+    assert!(MetalsDataset::from_csv("date,xau,xag,xpt\n2010-13-04,1,,\n".as_bytes()).is_err());
+}
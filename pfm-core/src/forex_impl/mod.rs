@@ -1,18 +1,102 @@
 /// https://currencyapi.com
+#[cfg(feature = "provider-currencyapi")]
 pub mod currency_api;
 // #[cfg(test)]
 // mod currency_api_test;
 
 /// https://openexchangerates.org/
+#[cfg(feature = "provider-openexchange")]
 pub mod open_exchange_api;
 // #[cfg(test)]
 // mod open_exchange_api_test;
 
 /// https://currencybeacon.com/api-documentation
+#[cfg(feature = "provider-currencybeacon")]
 pub mod currencybeacon;
 
 /// https://tradermade.com/
+#[cfg(feature = "provider-tradermade")]
 pub mod tradermade;
 
+/// https://twelvedata.com/ daily close prices of crypto currencies
+pub mod twelvedata;
+
+/// golden files of providers' responses converted into stored rates
+#[cfg(test)]
+mod provider_golden_test;
+
+/// select providers above by name from configuration
+pub mod registry;
+#[cfg(test)]
+mod registry_test;
+
+/// static dataset of metal fixes for dates before providers' coverage
+pub mod metals_dataset;
+#[cfg(test)]
+mod metals_dataset_test;
+
+/// daily crypto prices exported from coinmarketcap, ingested into historical rates
+pub mod crypto_dataset;
+#[cfg(test)]
+mod crypto_dataset_test;
+
 /// SERVER side storage for cron and http services
 pub mod forex_storage;
+
+/// rates capabilities of the storage above
+mod storage_rates;
+
+/// jobs and providers bookkeeping of the storage above
+mod storage_jobs;
+
+/// users and profiles records of the storage above
+mod storage_profile;
+
+/// storage decorator injecting errors, latency and partial writes, enabled in tests and by `fault-injection` feature
+#[cfg(any(test, feature = "fault-injection"))]
+pub mod faulty_storage;
+#[cfg(test)]
+mod faulty_storage_test;
+
+/// in-memory cache of parsed rates files for read heavy services
+pub(crate) mod rates_cache;
+#[cfg(test)]
+mod rates_cache_test;
+
+/// decoding of stored rates files with only rates of wanted currencies
+pub(crate) mod sparse_rates;
+#[cfg(test)]
+mod sparse_rates_test;
+
+/// versioned filenames of latest rates files
+pub mod latest_filename;
+#[cfg(test)]
+mod latest_filename_test;
+
+/// Ed25519 signing and verification of stored rates files
+pub mod signing;
+#[cfg(test)]
+mod signing_test;
+
+/// HMAC-SHA256 signed internal requests between pfm-cron and pfm-http
+pub mod internal_signing;
+#[cfg(test)]
+mod internal_signing_test;
+
+/// outbound webhooks notified with newly polled rates
+pub mod webhook;
+#[cfg(test)]
+mod webhook_test;
+
+/// client of sync endpoints of pfm-http, backing up client records into the server
+pub mod sync_remote;
+
+/// MQTT publisher of selected pairs, enabled by `mqtt` feature
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+#[cfg(all(test, feature = "mqtt"))]
+mod mqtt_test;
+
+/// SMTP sender of rates digests, enabled by `email` feature
+#[cfg(feature = "email")]
+pub mod smtp;
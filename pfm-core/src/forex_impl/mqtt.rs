@@ -0,0 +1,112 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::Context;
+use rumqttc::{AsyncClient, EventLoop, MqttOptions, QoS};
+
+use crate::error::AsClientError;
+use crate::forex::{
+    Currency, ForexResult,
+    entity::{Rates, RatesResponse},
+    service,
+};
+
+/// capacity of outgoing requests queue of mqtt client.
+const REQUESTS_CAPACITY: usize = 64;
+
+/// wait before polling event loop again after connection error, reconnecting on next poll.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Publish rates of selected pairs to MQTT broker, a topic per pair with retained messages,
+/// so subscribers get latest rate right after subscribing.
+#[derive(Clone)]
+pub struct Mqtt {
+    client: AsyncClient,
+    topic_prefix: String,
+    pairs: Vec<(Currency, Currency)>,
+}
+
+impl Mqtt {
+    /// connect to broker, driving connection in background task.
+    pub fn new(
+        host: &str,
+        port: u16,
+        client_id: &str,
+        topic_prefix: &str,
+        pairs: Vec<(Currency, Currency)>,
+    ) -> Self {
+        let mut options = MqttOptions::new(client_id, host, port);
+        options.set_keep_alive(Duration::from_secs(30));
+        let (client, eventloop) = AsyncClient::new(options, REQUESTS_CAPACITY);
+        tokio::spawn(drive(eventloop));
+
+        Self {
+            client,
+            topic_prefix: topic_prefix.trim_end_matches('/').to_string(),
+            pairs,
+        }
+    }
+
+    /// parse comma separated pairs in form of BASE/QUOTE, e.g. USD/IDR,XAU/IDR
+    pub fn parse_pairs(pairs: &str) -> ForexResult<Vec<(Currency, Currency)>> {
+        let mut ret = vec![];
+        for pair in pairs.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (base, quote) = pair
+                .split_once('/')
+                .with_context(|| format!("mqtt invalid pair {}, expected BASE/QUOTE", pair))
+                .as_client_err()?;
+            ret.push((
+                Currency::from_str(base.trim())?,
+                Currency::from_str(quote.trim())?,
+            ));
+        }
+
+        Ok(ret)
+    }
+
+    /// topic of the pair, e.g. pfm/forex/USD/IDR
+    fn topic(&self, base: Currency, quote: Currency) -> String {
+        format!("{}/{}/{}", self.topic_prefix, base.code(), quote.code())
+    }
+
+    /// publish rate of each pair as plain decimal payload.
+    /// rates polled with error are not published.
+    pub async fn publish(&self, rates: &RatesResponse<Rates>) {
+        if rates.error.is_some() {
+            return;
+        }
+
+        for &(base, quote) in &self.pairs {
+            let rate = match service::pair_rate(&rates.data, base, quote) {
+                Ok(rate) => rate,
+                Err(err) => {
+                    tracing::error!(
+                        "mqtt failed calculating rate of {}/{}: {}",
+                        base,
+                        quote,
+                        err
+                    );
+                    continue;
+                }
+            };
+
+            let topic = self.topic(base, quote);
+            if let Err(err) = self
+                .client
+                .publish(&topic, QoS::AtLeastOnce, true, rate.to_string())
+                .await
+            {
+                tracing::error!("mqtt failed publishing to {}: {}", topic, err);
+            }
+        }
+    }
+}
+
+async fn drive(mut eventloop: EventLoop) {
+    loop {
+        if let Err(err) = eventloop.poll().await {
+            tracing::warn!("mqtt connection error: {}", err);
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+}
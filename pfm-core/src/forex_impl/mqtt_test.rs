@@ -0,0 +1,18 @@
+use crate::forex::Currency;
+
+use super::mqtt::Mqtt;
+
+#[test]
+fn test_parse_pairs() {
+    let pairs = Mqtt::parse_pairs("USD/IDR, XAU/IDR,").unwrap();
+    assert_eq!(
+        pairs,
+        vec![
+            (Currency::USD, Currency::IDR),
+            (Currency::XAU, Currency::IDR)
+        ]
+    );
+    assert!(Mqtt::parse_pairs("").unwrap().is_empty());
+    assert!(Mqtt::parse_pairs("USDIDR").is_err());
+    assert!(Mqtt::parse_pairs("USD/XYZ").is_err());
+}
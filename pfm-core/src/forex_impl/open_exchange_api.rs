@@ -13,9 +13,10 @@ use std::str::FromStr;
 use crate::error::AsInternalError;
 use crate::forex::{
     Currency, ForexError, ForexResult,
-    entity::{Rates, RatesData, RatesResponse},
-    interface::{ForexHistoricalRates, ForexRates},
+    entity::{ProviderCapabilities, RateLimit, RatesData, RatesResponse},
+    interface::{ForexHistoricalRates, ForexProviderCapabilities, ForexRates},
 };
+use crate::global::Secret;
 use anyhow::Context;
 use async_trait::async_trait;
 use chrono::{TimeZone, Utc};
@@ -24,6 +25,19 @@ use serde::{Deserialize, Serialize};
 
 const SOURCE: &str = "openexchangerates.org";
 
+/// name of this provider in ProviderRegistry
+pub const NAME: &str = "openexchangerates";
+
+/// all currencies, historical rates since 1999, called 4 times per second to stay below its limit.
+fn capabilities() -> ProviderCapabilities {
+    ProviderCapabilities::default()
+        .with_history_start(Utc.with_ymd_and_hms(1999, 1, 1, 0, 0, 0).unwrap())
+        .with_rate_limit(RateLimit {
+            calls: 4,
+            window_secs: 1,
+        })
+}
+
 const ERROR_PREFIX: &str = "[FOREX][openexchangerates.org]";
 
 const LATEST_ENDPOINT: &str = "https://openexchangerates.org/api/latest.json";
@@ -208,12 +222,12 @@ pub struct Usage {
 
 #[derive(Clone)]
 pub struct Api {
-    key: &'static str,
+    key: Secret,
     client: reqwest::Client,
 }
 
 impl Api {
-    pub fn new(api_key: &'static str, client: reqwest::Client) -> Self {
+    pub fn new(api_key: Secret, client: reqwest::Client) -> Self {
         Self {
             key: api_key,
             client,
@@ -222,7 +236,8 @@ impl Api {
 
     pub async fn status(&self) -> ForexResult<StatusResponse> {
         let endpoint = "https://openexchangerates.org/api/usage.json";
-        let params = [("app_id", self.key)];
+        let key = self.key.expose();
+        let params = [("app_id", key.as_str())];
 
         let status: StatusResponse = self
             .client
@@ -247,10 +262,11 @@ impl ForexRates for Api {
         &self,
         base: Currency,
     ) -> crate::forex::ForexResult<RatesResponse<crate::forex::entity::Rates>> {
-        let symbols = Currency::to_comma_separated_list_str();
+        let symbols = Currency::to_comma_separated_list_str_of(capabilities().requested_symbols());
 
+        let key = self.key.expose();
         let params = [
-            ("app_id", self.key),
+            ("app_id", key.as_str()),
             ("base", base.code()),
             ("symbols", &symbols),
         ];
@@ -292,10 +308,11 @@ impl ForexHistoricalRates for Api {
         let yyyymmdd = date.format("%Y-%m-%d").to_string();
         let endpoint = HISTORICAL_ENDPOINT.replace(":date", yyyymmdd.as_str());
 
-        let symbols = Currency::to_comma_separated_list_str();
+        let symbols = Currency::to_comma_separated_list_str_of(capabilities().requested_symbols());
 
+        let key = self.key.expose();
         let params = [
-            ("app_id", self.key),
+            ("app_id", key.as_str()),
             ("base", base.code()),
             ("symbols", &symbols),
         ];
@@ -325,3 +342,14 @@ impl ForexHistoricalRates for Api {
         Ok(resp.try_into()?)
     }
 }
+
+#[async_trait]
+impl ForexProviderCapabilities for Api {
+    fn capabilities(&self) -> ProviderCapabilities {
+        capabilities()
+    }
+
+    async fn quota_remaining(&self) -> ForexResult<Option<u32>> {
+        Ok(Some(self.status().await?.data.usage.requests_remaining))
+    }
+}
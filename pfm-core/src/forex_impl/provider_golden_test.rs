@@ -0,0 +1,103 @@
+// golden files of providers' responses converted into stored rates.
+// run with UPDATE_GOLDEN=1 to regenerate golden files after intended change of storage format.
+
+use std::path::PathBuf;
+
+use chrono::{TimeZone, Utc};
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+use crate::forex::entity::{Rates, RatesResponse};
+
+fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/providers")
+}
+
+fn read_fixture<T: DeserializeOwned>(name: &str) -> T {
+    let path = fixtures_dir().join(format!("{}.json", name));
+    let content = std::fs::read_to_string(&path).unwrap();
+    serde_json::from_str(&content).unwrap()
+}
+
+/// compare converted rates with golden file of the fixture, ids and poll dates are generated so they're fixed.
+fn assert_golden(name: &str, mut rates: Vec<RatesResponse<Rates>>) {
+    for rates in rates.iter_mut() {
+        rates.id = Uuid::nil();
+        rates.poll_date = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+    }
+    let actual = serde_json::to_string_pretty(&rates).unwrap() + "\n";
+
+    let path = fixtures_dir().join(format!("{}.golden.json", name));
+    if std::env::var("UPDATE_GOLDEN").is_ok() {
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(actual, expected, "{} differs from its golden file", name);
+}
+
+#[cfg(feature = "provider-openexchange")]
+#[test]
+fn test_golden_openexchangerates() {
+    use super::open_exchange_api::Response;
+
+    let name = "openexchangerates_historical";
+    let ret = RatesResponse::try_from(read_fixture::<Response>(name)).unwrap();
+    assert_golden(name, vec![ret]);
+}
+
+#[cfg(feature = "provider-currencyapi")]
+#[test]
+fn test_golden_currencyapi() {
+    use super::currency_api::Response;
+    use crate::forex::Currency;
+
+    let name = "currencyapi_historical";
+    let resp = Response {
+        base: Currency::USD,
+        api_response: read_fixture(name),
+    };
+    let ret = RatesResponse::try_from(resp).unwrap();
+    assert_golden(name, vec![ret]);
+}
+
+#[cfg(feature = "provider-currencybeacon")]
+#[test]
+fn test_golden_currencybeacon() {
+    use rust_decimal_macros::dec;
+
+    use super::currencybeacon::{RatesResponseList, Response, TimeseriesResponse};
+    use crate::forex::Currency;
+
+    let name = "currencybeacon_latest";
+    let ret = RatesResponse::try_from((read_fixture::<Response>(name), dec!(0))).unwrap();
+    assert_golden(name, vec![ret]);
+
+    // missing solana rate is filled with price from twelvedata
+    let name = "currencybeacon_historical";
+    let ret = RatesResponse::try_from((read_fixture::<Response>(name), dec!(0.00529))).unwrap();
+    assert_golden(name, vec![ret]);
+
+    let name = "currencybeacon_timeseries";
+    let resp = read_fixture::<TimeseriesResponse>(name);
+    let ret = RatesResponseList::try_from((Currency::USD, resp)).unwrap();
+    assert_golden(name, ret.0);
+}
+
+#[cfg(feature = "provider-tradermade")]
+#[test]
+fn test_golden_tradermade() {
+    use super::tradermade::{HistoricalResponse, LatestResponse};
+    use crate::forex::Currency;
+
+    let name = "tradermade_latest";
+    let resp = read_fixture::<LatestResponse>(name);
+    let ret = RatesResponse::try_from((Currency::USD, resp)).unwrap();
+    assert_golden(name, vec![ret]);
+
+    // unavailable quotes are left zero
+    let name = "tradermade_historical";
+    let resp = read_fixture::<HistoricalResponse>(name);
+    let ret = RatesResponse::try_from((Currency::USD, resp)).unwrap();
+    assert_golden(name, vec![ret]);
+}
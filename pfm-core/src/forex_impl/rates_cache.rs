@@ -0,0 +1,160 @@
+// rates_cache.rs keep parsed rates files in memory for read heavy services, e.g. pfm-http.
+
+use std::collections::HashMap;
+use std::fs::Metadata;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{Instant, SystemTime};
+
+use crate::forex::{
+    Currency,
+    entity::{Rates, RatesResponse},
+};
+
+/// Parsed rates files kept in memory, each validated against modification time and size of its file on read,
+/// so files rewritten by other processes, e.g. pfm-cron, are parsed again.
+/// Rates of files rebased into other base currencies are cached the same way.
+/// Once full, the least recently cached file is evicted.
+#[derive(Debug)]
+pub struct RatesCache {
+    capacity: usize,
+    entries: RwLock<HashMap<PathBuf, CachedRates>>,
+    rebased: RwLock<HashMap<(PathBuf, Currency), CachedRates>>,
+}
+
+#[derive(Debug)]
+struct CachedRates {
+    modified: SystemTime,
+    len: u64,
+    cached_at: Instant,
+    rates: RatesResponse<Rates>,
+}
+
+impl CachedRates {
+    fn is_fresh(&self, metadata: &Metadata) -> bool {
+        metadata
+            .modified()
+            .is_ok_and(|modified| modified == self.modified && metadata.len() == self.len)
+    }
+}
+
+impl RatesCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: RwLock::new(HashMap::new()),
+            rebased: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// number of cached files.
+    pub fn len(&self) -> usize {
+        self.entries
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .len()
+    }
+
+    /// cached rates of the file if it's unchanged since cached.
+    pub(crate) fn get(&self, path: &Path, metadata: &Metadata) -> Option<RatesResponse<Rates>> {
+        let entries = self.entries.read().unwrap_or_else(|err| err.into_inner());
+        entries
+            .get(path)
+            .filter(|cached| cached.is_fresh(metadata))
+            .map(|cached| cached.rates.clone())
+    }
+
+    /// cache rates parsed from the file, replacing outdated ones.
+    pub(crate) fn insert(&self, path: &Path, metadata: &Metadata, rates: RatesResponse<Rates>) {
+        let mut entries = self.entries.write().unwrap_or_else(|err| err.into_inner());
+        insert_evicting(
+            &mut entries,
+            self.capacity,
+            path.to_path_buf(),
+            metadata,
+            rates,
+        );
+    }
+
+    /// cached rates of the file rebased into base if the file is unchanged since cached.
+    pub(crate) fn get_rebased(
+        &self,
+        path: &Path,
+        base: Currency,
+        metadata: &Metadata,
+    ) -> Option<RatesResponse<Rates>> {
+        let rebased = self.rebased.read().unwrap_or_else(|err| err.into_inner());
+        rebased
+            .get(&(path.to_path_buf(), base))
+            .filter(|cached| cached.is_fresh(metadata))
+            .map(|cached| cached.rates.clone())
+    }
+
+    /// cache rates of the file rebased into base, replacing outdated ones.
+    pub(crate) fn insert_rebased(
+        &self,
+        path: &Path,
+        base: Currency,
+        metadata: &Metadata,
+        rates: RatesResponse<Rates>,
+    ) {
+        let mut rebased = self.rebased.write().unwrap_or_else(|err| err.into_inner());
+        insert_evicting(
+            &mut rebased,
+            self.capacity,
+            (path.to_path_buf(), base),
+            metadata,
+            rates,
+        );
+    }
+
+    /// drop cached rates of the file and all its rebased rates, e.g. after the file is updated.
+    pub(crate) fn invalidate(&self, path: &Path) {
+        self.entries
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(path);
+        self.rebased
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .retain(|(cached_path, _), _| cached_path != path);
+    }
+}
+
+/// insert into entries, evicting the least recently cached one once full.
+fn insert_evicting<K>(
+    entries: &mut HashMap<K, CachedRates>,
+    capacity: usize,
+    key: K,
+    metadata: &Metadata,
+    rates: RatesResponse<Rates>,
+) where
+    K: Eq + Hash + Clone,
+{
+    let Ok(modified) = metadata.modified() else {
+        return;
+    };
+    if capacity == 0 {
+        return;
+    }
+
+    if entries.len() >= capacity
+        && !entries.contains_key(&key)
+        && let Some(oldest) = entries
+            .iter()
+            .min_by_key(|(_, cached)| cached.cached_at)
+            .map(|(key, _)| key.clone())
+    {
+        entries.remove(&oldest);
+    }
+    entries.insert(
+        key,
+        CachedRates {
+            modified,
+            len: metadata.len(),
+            cached_at: Instant::now(),
+            rates,
+        },
+    );
+}
@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+use crate::forex::entity::{Rates, RatesResponse};
+
+use super::rates_cache::RatesCache;
+
+fn write_file(name: &str, content: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("pfm-rates-cache-{}-{}", std::process::id(), name));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn test_rates_cache() {
+    let cache = RatesCache::new(2);
+    let rates = RatesResponse::new("cached".to_string(), Rates::default());
+
+    let first = write_file("first", "{}");
+    let metadata = std::fs::metadata(&first).unwrap();
+    assert!(cache.get(&first, &metadata).is_none());
+
+    cache.insert(&first, &metadata, rates.clone());
+    let cached = cache.get(&first, &metadata).unwrap();
+    assert_eq!(cached.id, rates.id);
+
+    // rewritten file is outdated
+    std::fs::write(&first, "{\"rewritten\": true}").unwrap();
+    let rewritten = std::fs::metadata(&first).unwrap();
+    assert!(cache.get(&first, &rewritten).is_none());
+
+    // least recently cached file is evicted once full
+    let second = write_file("second", "{}");
+    let third = write_file("third", "{}");
+    cache.insert(&second, &std::fs::metadata(&second).unwrap(), rates.clone());
+    cache.insert(&third, &std::fs::metadata(&third).unwrap(), rates.clone());
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get(&first, &rewritten).is_none());
+    assert!(
+        cache
+            .get(&third, &std::fs::metadata(&third).unwrap())
+            .is_some()
+    );
+
+    for path in [first, second, third] {
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_rates_cache_rebased() {
+    use crate::forex::Currency;
+
+    let cache = RatesCache::new(2);
+    let rates = RatesResponse::new("cached".to_string(), Rates::default());
+
+    let path = write_file("rebased", "{}");
+    let metadata = std::fs::metadata(&path).unwrap();
+    cache.insert(&path, &metadata, rates.clone());
+    cache.insert_rebased(&path, Currency::EUR, &metadata, rates.clone());
+    assert!(cache.get_rebased(&path, Currency::EUR, &metadata).is_some());
+    assert!(cache.get_rebased(&path, Currency::JPY, &metadata).is_none());
+
+    // updated file drops its rates and all of its rebased rates
+    cache.invalidate(&path);
+    assert!(cache.get(&path, &metadata).is_none());
+    assert!(cache.get_rebased(&path, Currency::EUR, &metadata).is_none());
+
+    std::fs::remove_file(path).unwrap();
+}
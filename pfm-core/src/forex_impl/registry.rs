@@ -0,0 +1,546 @@
+// registry.rs select 3rd party forex providers by name from configuration.
+// builtin providers are registered by ProviderRegistry::builtin, out-of-tree providers can be added with register.
+
+use std::collections::BTreeMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use crate::forex::{
+    Currency, ForexError, ForexResult,
+    entity::{ProviderCallSample, ProviderCapabilities, Rates, RatesResponse},
+    interface::{
+        ForexHistoricalRates, ForexProviderCapabilities, ForexRates, ForexStorageProviderHealth,
+        ForexTimeseriesRates,
+    },
+};
+use crate::global::{self, Config};
+
+#[cfg(feature = "provider-currencyapi")]
+use super::currency_api;
+#[cfg(feature = "provider-currencybeacon")]
+use super::currencybeacon;
+#[cfg(feature = "provider-openexchange")]
+use super::open_exchange_api;
+
+/// Creates provider from core config and shared http client.
+pub type ProviderFactory = Arc<dyn Fn(&'static Config, reqwest::Client) -> Provider + Send + Sync>;
+
+/// Forex provider selected from registry, wrapping capabilities its implementation supports.
+/// Calling unsupported capability returns error.
+#[derive(Clone)]
+pub struct Provider {
+    name: String,
+    rates: Option<Arc<dyn ForexRates + Send + Sync>>,
+    historical: Option<Arc<dyn ForexHistoricalRates + Send + Sync>>,
+    timeseries: Option<Arc<dyn ForexTimeseriesRates + Send + Sync>>,
+    usage: Option<Arc<dyn ForexProviderCapabilities + Send + Sync>>,
+    capabilities: ProviderCapabilities,
+    times_out_attempts: bool,
+}
+
+impl Provider {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            rates: None,
+            historical: None,
+            timeseries: None,
+            usage: None,
+            capabilities: ProviderCapabilities::default(),
+            times_out_attempts: false,
+        }
+    }
+
+    pub fn with_rates(mut self, rates: impl ForexRates + Send + Sync + 'static) -> Self {
+        self.rates = Some(Arc::new(rates));
+        self
+    }
+
+    pub fn with_historical(
+        mut self,
+        historical: impl ForexHistoricalRates + Send + Sync + 'static,
+    ) -> Self {
+        self.historical = Some(Arc::new(historical));
+        self
+    }
+
+    pub fn with_timeseries(
+        mut self,
+        timeseries: impl ForexTimeseriesRates + Send + Sync + 'static,
+    ) -> Self {
+        self.timeseries = Some(Arc::new(timeseries));
+        self
+    }
+
+    /// currencies and dates served by the provider, all of them unless set.
+    pub fn with_capabilities(mut self, capabilities: ProviderCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// reports calls left in quota period of the provider, quota of capabilities is assumed left unless set.
+    pub fn with_usage(
+        mut self,
+        usage: impl ForexProviderCapabilities + Send + Sync + 'static,
+    ) -> Self {
+        self.usage = Some(Arc::new(usage));
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn unsupported(&self, capability: &str) -> ForexError {
+        ForexError::internal_error(
+            format!("provider {} doesn't support {}", self.name, capability).as_str(),
+        )
+    }
+}
+
+#[async_trait]
+impl ForexProviderCapabilities for Provider {
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.capabilities.clone()
+    }
+
+    async fn quota_remaining(&self) -> ForexResult<Option<u32>> {
+        match &self.usage {
+            Some(usage) => usage.quota_remaining().await,
+            None => Ok(self.capabilities.quota),
+        }
+    }
+
+    fn times_out_attempts(&self) -> bool {
+        self.times_out_attempts
+    }
+}
+
+#[async_trait]
+impl ForexRates for Provider {
+    async fn rates(&self, base: Currency) -> ForexResult<RatesResponse<Rates>> {
+        match &self.rates {
+            Some(rates) => rates.rates(base).await,
+            None => Err(self.unsupported("latest rates")),
+        }
+    }
+}
+
+#[async_trait]
+impl ForexHistoricalRates for Provider {
+    async fn historical_rates(
+        &self,
+        date: DateTime<Utc>,
+        base: Currency,
+    ) -> ForexResult<RatesResponse<Rates>> {
+        match &self.historical {
+            Some(historical) => historical.historical_rates(date, base).await,
+            None => Err(self.unsupported("historical rates")),
+        }
+    }
+}
+
+#[async_trait]
+impl ForexTimeseriesRates for Provider {
+    async fn timeseries_rates(
+        &self,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+        base: Currency,
+    ) -> ForexResult<Vec<RatesResponse<Rates>>> {
+        match &self.timeseries {
+            Some(timeseries) => {
+                timeseries
+                    .timeseries_rates(start_date, end_date, base)
+                    .await
+            }
+            None => Err(self.unsupported("timeseries rates")),
+        }
+    }
+}
+
+/// Providers tried from the healthiest one, falling back to the next one when a call fails or times out.
+/// Outcome of each call is recorded into storage, so the order adapts to health of providers over time.
+#[derive(Clone)]
+pub struct ProviderChain<FS> {
+    providers: Vec<Provider>,
+    storage: FS,
+    timeout: Duration,
+}
+
+type ProviderFuture<'p, T> = Pin<Box<dyn Future<Output = ForexResult<T>> + Send + 'p>>;
+
+impl<FS> ProviderChain<FS>
+where
+    FS: ForexStorageProviderHealth + Clone + Send + Sync + 'static,
+{
+    /// chain of providers, in configured order used until they have recorded health.
+    /// each provider is given configured provider timeout.
+    pub fn new(providers: Vec<Provider>, storage: FS) -> Self {
+        Self {
+            providers,
+            storage,
+            timeout: Duration::from_secs(global::config().forex_provider_timeout_secs),
+        }
+    }
+
+    /// time each provider is given before trying the next one.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// providers ordered from the healthiest, then the fastest, ties keep configured order.
+    /// configured order is used when health can't be read.
+    pub async fn ordered(&self) -> Vec<&Provider> {
+        let healths = match self.storage.get_provider_health().await {
+            Ok(healths) => healths,
+            Err(err) => {
+                tracing::warn!(
+                    "failed reading provider health, using configured order: {}",
+                    err
+                );
+                vec![]
+            }
+        };
+
+        let healths: Vec<_> = self
+            .providers
+            .iter()
+            .map(|provider| healths.iter().find(|h| h.provider == provider.name()))
+            .collect();
+        // provider without recorded calls is assumed as healthy as the healthiest one,
+        // so it's tried in configured order instead of jumping ahead of providers with partial coverage.
+        let best = healths
+            .iter()
+            .flatten()
+            .map(|h| h.score())
+            .max()
+            .unwrap_or(Decimal::ONE);
+
+        let mut ordered: Vec<(usize, &Provider)> = self.providers.iter().enumerate().collect();
+        ordered.sort_by_key(|(index, _)| {
+            let health = healths[*index];
+            let score = health.map_or(best, |h| h.score());
+            let latency = health.and_then(|h| h.avg_latency_ms()).unwrap_or(u64::MAX);
+            (std::cmp::Reverse(score), latency, *index)
+        });
+
+        ordered.into_iter().map(|(_, provider)| provider).collect()
+    }
+
+    /// provider calling the chain, named after providers in the chain.
+    pub fn into_provider(self) -> Provider {
+        let name = self
+            .providers
+            .iter()
+            .map(Provider::name)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let capabilities = self.capabilities();
+        let mut provider = Provider::new(&name)
+            .with_capabilities(capabilities)
+            .with_rates(self.clone())
+            .with_historical(self.clone())
+            .with_timeseries(self);
+        provider.times_out_attempts = true;
+        provider
+    }
+
+    async fn call<T, S, C, F>(
+        &self,
+        capability: &str,
+        supports: S,
+        completeness: C,
+        call: F,
+    ) -> ForexResult<T>
+    where
+        S: Fn(&Provider) -> bool,
+        C: Fn(&T) -> Decimal,
+        F: for<'p> Fn(&'p Provider) -> ProviderFuture<'p, T>,
+    {
+        let mut last_err = None;
+        for provider in self.ordered().await {
+            if !supports(provider) {
+                continue;
+            }
+
+            let started = Instant::now();
+            let ret = match tokio::time::timeout(self.timeout, call(provider)).await {
+                Ok(ret) => ret,
+                Err(_) => Err(ForexError::internal_error(&format!(
+                    "provider {} timed out after {} ms",
+                    provider.name(),
+                    self.timeout.as_millis()
+                ))),
+            };
+            let sample = ProviderCallSample {
+                at: Utc::now(),
+                success: ret.is_ok(),
+                latency_ms: started.elapsed().as_millis() as u64,
+                completeness: ret.as_ref().map_or(Decimal::ZERO, &completeness),
+            };
+            if let Err(err) = self
+                .storage
+                .record_provider_call(provider.name(), sample)
+                .await
+            {
+                tracing::warn!(
+                    "failed recording call of provider {}: {}",
+                    provider.name(),
+                    err
+                );
+            }
+
+            match ret {
+                Ok(ret) => return Ok(ret),
+                Err(err) => {
+                    tracing::warn!(
+                        "provider {} failed calling {}, trying next provider: {}",
+                        provider.name(),
+                        capability,
+                        err
+                    );
+                    last_err = Some(err);
+                }
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| {
+            ForexError::internal_error(
+                format!("no provider in the chain supports {}", capability).as_str(),
+            )
+        }))
+    }
+}
+
+/// served by any provider in the chain.
+impl<FS> ForexProviderCapabilities for ProviderChain<FS> {
+    fn capabilities(&self) -> ProviderCapabilities {
+        let mut providers = self.providers.iter();
+        let Some(first) = providers.next() else {
+            return ProviderCapabilities::default();
+        };
+        providers.fold(first.capabilities.clone(), |capabilities, provider| {
+            capabilities.union(&provider.capabilities)
+        })
+    }
+}
+
+#[async_trait]
+impl<FS> ForexRates for ProviderChain<FS>
+where
+    FS: ForexStorageProviderHealth + Clone + Send + Sync + 'static,
+{
+    async fn rates(&self, base: Currency) -> ForexResult<RatesResponse<Rates>> {
+        self.call(
+            "latest rates",
+            |provider| provider.rates.is_some() && provider.capabilities.supports(base),
+            |rates: &RatesResponse<Rates>| rates.data.rates.completeness(),
+            |provider| provider.rates(base),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<FS> ForexHistoricalRates for ProviderChain<FS>
+where
+    FS: ForexStorageProviderHealth + Clone + Send + Sync + 'static,
+{
+    async fn historical_rates(
+        &self,
+        date: DateTime<Utc>,
+        base: Currency,
+    ) -> ForexResult<RatesResponse<Rates>> {
+        self.call(
+            "historical rates",
+            |provider| {
+                provider.historical.is_some()
+                    && provider.capabilities.supports(base)
+                    && provider.capabilities.covers(date)
+            },
+            |rates: &RatesResponse<Rates>| rates.data.rates.completeness(),
+            |provider| provider.historical_rates(date, base),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<FS> ForexTimeseriesRates for ProviderChain<FS>
+where
+    FS: ForexStorageProviderHealth + Clone + Send + Sync + 'static,
+{
+    async fn timeseries_rates(
+        &self,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+        base: Currency,
+    ) -> ForexResult<Vec<RatesResponse<Rates>>> {
+        self.call(
+            "timeseries rates",
+            |provider| {
+                provider.timeseries.is_some()
+                    && provider.capabilities.supports(base)
+                    && provider.capabilities.covers(start_date)
+            },
+            |list: &Vec<RatesResponse<Rates>>| {
+                if list.is_empty() {
+                    return Decimal::ZERO;
+                }
+                let sum: Decimal = list.iter().map(|r| r.data.rates.completeness()).sum();
+                sum / Decimal::from(list.len())
+            },
+            |provider| provider.timeseries_rates(start_date, end_date, base),
+        )
+        .await
+    }
+}
+
+/// Providers registered under their names.
+#[derive(Clone, Default)]
+pub struct ProviderRegistry {
+    factories: BTreeMap<String, ProviderFactory>,
+}
+
+impl ProviderRegistry {
+    /// registry without any provider.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// registry with providers implemented in pfm-core and enabled by their `provider-*` features.
+    pub fn builtin() -> Self {
+        #[allow(unused_mut)]
+        let mut registry = Self::new();
+        #[cfg(feature = "provider-currencybeacon")]
+        registry.register(
+            currencybeacon::NAME,
+            Arc::new(|_cfg: &'static Config, client| {
+                let api = currencybeacon::Api::new(
+                    global::secrets().get(global::SECRET_FOREX_CURRENCYBEACON_API_KEY),
+                    client,
+                );
+                Provider::new(currencybeacon::NAME)
+                    .with_capabilities(api.capabilities())
+                    .with_rates(api.clone())
+                    .with_historical(api.clone())
+                    .with_timeseries(api)
+            }),
+        );
+        #[cfg(feature = "provider-openexchange")]
+        registry.register(
+            open_exchange_api::NAME,
+            Arc::new(|_cfg: &'static Config, client| {
+                let api = open_exchange_api::Api::new(
+                    global::secrets().get(global::SECRET_FOREX_OPEN_EXCHANGE_API_KEY),
+                    client,
+                );
+                Provider::new(open_exchange_api::NAME)
+                    .with_capabilities(api.capabilities())
+                    .with_usage(api.clone())
+                    .with_rates(api.clone())
+                    .with_historical(api)
+            }),
+        );
+        #[cfg(feature = "provider-currencyapi")]
+        registry.register(
+            currency_api::NAME,
+            Arc::new(|_cfg: &'static Config, client| {
+                let api = currency_api::Api::new(
+                    global::secrets().get(global::SECRET_FOREX_CURRENCY_API_KEY),
+                    client,
+                );
+                Provider::new(currency_api::NAME)
+                    .with_capabilities(api.capabilities())
+                    .with_usage(api.clone())
+                    .with_historical(api)
+            }),
+        );
+
+        registry
+    }
+
+    /// register provider under the name, replacing provider previously registered with the same name.
+    pub fn register(&mut self, name: &str, factory: ProviderFactory) -> &mut Self {
+        self.factories.insert(name.to_string(), factory);
+        self
+    }
+
+    /// names of registered providers.
+    pub fn names(&self) -> Vec<&str> {
+        self.factories.keys().map(String::as_str).collect()
+    }
+
+    /// create provider registered under the name.
+    pub fn create(
+        &self,
+        name: &str,
+        cfg: &'static Config,
+        client: reqwest::Client,
+    ) -> ForexResult<Provider> {
+        let factory = self.factories.get(name).ok_or_else(|| {
+            ForexError::client_error(
+                format!(
+                    "unknown forex provider {}, registered providers: {}",
+                    name,
+                    self.names().join(", ")
+                )
+                .as_str(),
+            )
+        })?;
+
+        Ok(factory(cfg, client))
+    }
+
+    /// create chain of providers registered under the names, first one is preferred until providers have recorded health.
+    pub fn create_chain<FS>(
+        &self,
+        names: &[String],
+        cfg: &'static Config,
+        client: reqwest::Client,
+        storage: FS,
+    ) -> ForexResult<Provider>
+    where
+        FS: ForexStorageProviderHealth + Clone + Send + Sync + 'static,
+    {
+        if names.is_empty() {
+            return Err(ForexError::client_error("no forex provider configured"));
+        }
+        let providers = names
+            .iter()
+            .map(|name| self.create(name, cfg, client.clone()))
+            .collect::<ForexResult<Vec<Provider>>>()?;
+
+        Ok(ProviderChain::new(providers, storage).into_provider())
+    }
+}
+
+/// create builtin provider by its name, e.g. `currencybeacon`.
+/// unknown name or name of provider disabled by its feature returns client error listing available providers.
+pub fn provider_from_name(
+    name: &str,
+    cfg: &'static Config,
+    client: reqwest::Client,
+) -> ForexResult<Provider> {
+    ProviderRegistry::builtin().create(name, cfg, client)
+}
+
+/// create chain of builtin providers configured by `CORE_FOREX_PROVIDER` followed by `CORE_FOREX_PROVIDER_FALLBACKS`.
+pub fn provider_from_config<FS>(
+    cfg: &'static Config,
+    client: reqwest::Client,
+    storage: FS,
+) -> ForexResult<Provider>
+where
+    FS: ForexStorageProviderHealth + Clone + Send + Sync + 'static,
+{
+    ProviderRegistry::builtin().create_chain(&cfg.forex_provider_names(), cfg, client, storage)
+}
@@ -0,0 +1,181 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+
+use crate::forex::{
+    Currency, ForexError, ForexResult,
+    entity::{ProviderCallSample, ProviderHealth, Rates, RatesData, RatesResponse},
+    interface::{
+        ForexHistoricalRates, ForexProviderCapabilities, ForexRates, ForexStorageProviderHealth,
+    },
+};
+
+use super::registry::{Provider, ProviderChain, ProviderRegistry};
+
+struct RatesOnly;
+
+#[async_trait]
+impl ForexRates for RatesOnly {
+    async fn rates(&self, base: Currency) -> ForexResult<RatesResponse<Rates>> {
+        let rates = Rates {
+            base,
+            rates: RatesData {
+                usd: Decimal::ONE,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        Ok(RatesResponse::new("rates_only".to_string(), rates))
+    }
+}
+
+struct Failing;
+
+/// never answering within test timeout.
+struct Hanging;
+
+#[async_trait]
+impl ForexRates for Hanging {
+    async fn rates(&self, _base: Currency) -> ForexResult<RatesResponse<Rates>> {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        Err(ForexError::internal_error("provider hanging"))
+    }
+}
+
+#[async_trait]
+impl ForexRates for Failing {
+    async fn rates(&self, _base: Currency) -> ForexResult<RatesResponse<Rates>> {
+        Err(ForexError::internal_error("provider down"))
+    }
+}
+
+#[derive(Clone, Default)]
+struct HealthStorage(Arc<Mutex<Vec<ProviderHealth>>>);
+
+#[async_trait]
+impl ForexStorageProviderHealth for HealthStorage {
+    async fn record_provider_call(
+        &self,
+        provider: &str,
+        sample: ProviderCallSample,
+    ) -> ForexResult<()> {
+        let mut healths = self.0.lock().unwrap();
+        match healths.iter_mut().find(|h| h.provider == provider) {
+            Some(health) => health.record(sample, 10),
+            None => {
+                let mut health = ProviderHealth::new(provider);
+                health.record(sample, 10);
+                healths.push(health);
+            }
+        }
+        Ok(())
+    }
+
+    async fn get_provider_health(&self) -> ForexResult<Vec<ProviderHealth>> {
+        Ok(self.0.lock().unwrap().clone())
+    }
+}
+
+#[cfg(all(
+    feature = "provider-currencybeacon",
+    feature = "provider-openexchange",
+    feature = "provider-currencyapi"
+))]
+#[test]
+fn test_builtin_names() {
+    let registry = ProviderRegistry::builtin();
+    assert_eq!(
+        registry.names(),
+        vec!["currencyapi", "currencybeacon", "openexchangerates"]
+    );
+}
+
+#[test]
+fn test_provider_from_unknown_name() {
+    let ret = super::registry::provider_from_name(
+        "unknown",
+        crate::global::config(),
+        reqwest::Client::new(),
+    );
+    assert!(matches!(ret.err(), Some(ForexError::ClientError(_))));
+}
+
+#[tokio::test]
+async fn test_provider_capabilities() {
+    assert!(ProviderRegistry::new().names().is_empty());
+
+    let provider = Provider::new("mock").with_rates(RatesOnly);
+    assert_eq!(provider.name(), "mock");
+    assert!(provider.rates(Currency::USD).await.is_ok());
+
+    let ret = provider
+        .historical_rates(chrono::Utc::now(), Currency::USD)
+        .await;
+    assert!(ret.is_err());
+}
+
+#[tokio::test]
+async fn test_provider_chain_prefers_healthy_provider() {
+    let storage = HealthStorage::default();
+    let chain = ProviderChain::new(
+        vec![
+            Provider::new("failing").with_rates(Failing),
+            Provider::new("historical_only"),
+            Provider::new("rates_only").with_rates(RatesOnly),
+        ],
+        storage.clone(),
+    );
+    let names = |providers: Vec<&Provider>| -> Vec<String> {
+        providers.iter().map(|p| p.name().to_string()).collect()
+    };
+    assert_eq!(
+        names(chain.ordered().await),
+        vec!["failing", "historical_only", "rates_only"]
+    );
+
+    let provider = chain.clone().into_provider();
+    assert_eq!(provider.name(), "failing,historical_only,rates_only");
+    let ret = provider.rates(Currency::USD).await.unwrap();
+    assert_eq!(ret.source, "rates_only");
+
+    // unsupported capability isn't recorded as failure
+    let healths = storage.get_provider_health().await.unwrap();
+    assert_eq!(healths.len(), 2);
+    assert_eq!(
+        names(chain.ordered().await),
+        vec!["rates_only", "historical_only", "failing"]
+    );
+
+    let ret = provider
+        .historical_rates(chrono::Utc::now(), Currency::USD)
+        .await;
+    assert!(ret.is_err());
+}
+
+#[tokio::test]
+async fn test_provider_chain_times_out_each_provider() {
+    let storage = HealthStorage::default();
+    let provider = ProviderChain::new(
+        vec![
+            Provider::new("hanging").with_rates(Hanging),
+            Provider::new("rates_only").with_rates(RatesOnly),
+        ],
+        storage.clone(),
+    )
+    .with_timeout(Duration::from_millis(50))
+    .into_provider();
+    assert!(provider.times_out_attempts());
+
+    // hanging provider times out, leaving time to the next one
+    let ret = provider.rates(Currency::USD).await.unwrap();
+    assert_eq!(ret.source, "rates_only");
+
+    let healths = storage.get_provider_health().await.unwrap();
+    let hanging = healths.iter().find(|h| h.provider == "hanging").unwrap();
+    assert_eq!(hanging.samples.len(), 1);
+    assert_eq!(hanging.success_rate(), Decimal::ZERO);
+    let rates_only = healths.iter().find(|h| h.provider == "rates_only").unwrap();
+    assert_eq!(rates_only.success_rate(), Decimal::ONE);
+}
@@ -0,0 +1,93 @@
+// signing.rs sign stored rates files with Ed25519 for tamper evidence, and verify them against public key.
+// keys are exchanged as hex strings: 32 bytes seed for signing, 32 bytes public key for verifying.
+
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{ED25519, Ed25519KeyPair, KeyPair, UnparsedPublicKey};
+
+use crate::forex::{ForexError, ForexResult};
+
+const SEED_LEN: usize = 32;
+
+/// Signs content of stored rates files with private key.
+pub struct RatesSigner {
+    key_pair: Ed25519KeyPair,
+}
+
+impl RatesSigner {
+    /// signer from hex encoded 32 bytes seed.
+    pub fn from_seed_hex(seed: &str) -> ForexResult<Self> {
+        let seed = parse_hex(seed.trim())
+            .filter(|seed| seed.len() == SEED_LEN)
+            .ok_or(ForexError::client_error(
+                "signing key must be hex encoded 32 bytes seed",
+            ))?;
+        let key_pair = Ed25519KeyPair::from_seed_unchecked(&seed)
+            .map_err(|_| ForexError::client_error("invalid signing key"))?;
+
+        Ok(Self { key_pair })
+    }
+
+    /// generate random seed, returned hex encoded.
+    pub fn generate_seed_hex() -> ForexResult<String> {
+        let mut seed = [0u8; SEED_LEN];
+        SystemRandom::new()
+            .fill(&mut seed)
+            .map_err(|_| ForexError::internal_error("failed generating signing key"))?;
+
+        Ok(hex(&seed))
+    }
+
+    /// hex encoded public key to verify signatures of this signer.
+    pub fn public_key_hex(&self) -> String {
+        hex(self.key_pair.public_key().as_ref())
+    }
+
+    /// hex encoded signature of the content.
+    pub fn sign(&self, content: &[u8]) -> String {
+        hex(self.key_pair.sign(content).as_ref())
+    }
+}
+
+/// Verifies signatures of stored rates files with public key.
+pub struct RatesVerifier {
+    public_key: Vec<u8>,
+}
+
+impl RatesVerifier {
+    /// verifier from hex encoded public key.
+    pub fn from_public_key_hex(public_key: &str) -> ForexResult<Self> {
+        let public_key = parse_hex(public_key.trim())
+            .filter(|key| key.len() == SEED_LEN)
+            .ok_or(ForexError::client_error(
+                "public key must be hex encoded 32 bytes",
+            ))?;
+
+        Ok(Self { public_key })
+    }
+
+    /// whether hex encoded signature is valid signature of the content.
+    pub fn verify(&self, content: &[u8], signature: &str) -> bool {
+        let Some(signature) = parse_hex(signature.trim()) else {
+            return false;
+        };
+
+        UnparsedPublicKey::new(&ED25519, &self.public_key)
+            .verify(content, &signature)
+            .is_ok()
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn parse_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) || !s.is_ascii() {
+        return None;
+    }
+
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
@@ -0,0 +1,26 @@
+use super::signing::{RatesSigner, RatesVerifier};
+
+// RFC 8032 test vector 1
+const SEED: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+const PUBLIC_KEY: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+const SIGNATURE: &str = "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b";
+
+#[test]
+fn test_sign_and_verify() {
+    let signer = RatesSigner::from_seed_hex(SEED).unwrap();
+    assert_eq!(signer.public_key_hex(), PUBLIC_KEY);
+    assert_eq!(signer.sign(b""), SIGNATURE);
+
+    let verifier = RatesVerifier::from_public_key_hex(PUBLIC_KEY).unwrap();
+    assert!(verifier.verify(b"", SIGNATURE));
+    assert!(!verifier.verify(b"tampered", SIGNATURE));
+    assert!(!verifier.verify(b"", "not hex"));
+
+    let signature = signer.sign(br#"{"source":"currencybeacon"}"#);
+    assert!(verifier.verify(br#"{"source":"currencybeacon"}"#, &signature));
+
+    assert!(RatesSigner::from_seed_hex("abcd").is_err());
+    assert!(RatesVerifier::from_public_key_hex("zz").is_err());
+    let seed = RatesSigner::generate_seed_hex().unwrap();
+    assert!(RatesSigner::from_seed_hex(&seed).is_ok());
+}
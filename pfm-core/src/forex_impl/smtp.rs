@@ -0,0 +1,69 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor, message::Mailbox,
+    transport::smtp::authentication::Credentials,
+};
+
+use crate::digest::interface::DigestSender;
+use crate::error::{AsClientError, AsInternalError};
+use crate::forex::ForexResult;
+
+/// Send digests through SMTP relay with STARTTLS.
+#[derive(Clone)]
+pub struct Smtp {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl Smtp {
+    pub fn new(
+        host: &str,
+        port: u16,
+        username: &str,
+        password: &str,
+        from: &str,
+    ) -> ForexResult<Self> {
+        let from = from
+            .parse::<Mailbox>()
+            .with_context(|| format!("smtp invalid sender address {}", from))
+            .as_client_err()?;
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+            .context("smtp creating transport")
+            .as_internal_err()?
+            .port(port);
+        if !username.is_empty() {
+            builder =
+                builder.credentials(Credentials::new(username.to_string(), password.to_string()));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+        })
+    }
+}
+
+#[async_trait]
+impl DigestSender for Smtp {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> ForexResult<()> {
+        let to = to
+            .parse::<Mailbox>()
+            .with_context(|| format!("smtp invalid recipient address {}", to))
+            .as_client_err()?;
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(subject)
+            .body(body.to_string())
+            .context("smtp building message")
+            .as_internal_err()?;
+        self.transport
+            .send(message)
+            .await
+            .context("smtp sending message")
+            .as_internal_err()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+// sparse_rates.rs decoding of stored rates files with only rates of wanted currencies,
+// other rates are skipped without being parsed, e.g. for long range queries of single pair.
+
+use std::collections::HashMap;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use uuid::Uuid;
+
+use crate::error::AsInternalError;
+use crate::forex::{
+    Currency, ForexResult, Money,
+    entity::{Rates, RatesData, RatesResponse},
+};
+
+#[derive(Deserialize)]
+struct SparseRatesResponse<'a> {
+    id: Uuid,
+    source: String,
+    poll_date: DateTime<Utc>,
+    #[serde(borrow)]
+    data: SparseRates<'a>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SparseRates<'a> {
+    date: DateTime<Utc>,
+    base: Currency,
+
+    /// rates by currency code as written, kept raw until wanted.
+    #[serde(borrow)]
+    rates: HashMap<&'a str, &'a RawValue>,
+}
+
+/// decode rates file with only rates of the currencies, others are left zero.
+/// provenance is not decoded.
+pub fn decode_sparse(content: &str, currencies: &[Currency]) -> ForexResult<RatesResponse<Rates>> {
+    let sparse: SparseRatesResponse = serde_json::from_str(content)
+        .context("sparse rates parse to json")
+        .as_internal_err()?;
+
+    let mut rates = RatesData::default();
+    for &currency in currencies {
+        let code = currency.code();
+        let raw = sparse
+            .data
+            .rates
+            .get(code.to_lowercase().as_str())
+            .or_else(|| sparse.data.rates.get(code));
+        let Some(raw) = raw else {
+            continue;
+        };
+        let rate: Decimal = serde_json::from_str(raw.get())
+            .context("sparse rates parse rate")
+            .as_internal_err()?;
+        rates.set_rate(Money::new_money(currency, rate));
+    }
+
+    Ok(RatesResponse {
+        id: sparse.id,
+        source: sparse.source,
+        poll_date: sparse.poll_date,
+        data: Rates {
+            date: sparse.data.date,
+            base: sparse.data.base,
+            rates,
+        },
+        error: sparse.error,
+        provenance: vec![],
+    })
+}
@@ -0,0 +1,49 @@
+use chrono::{TimeZone, Utc};
+use rust_decimal_macros::dec;
+
+use crate::forex::{
+    Currency, Money,
+    entity::{Rates, RatesData, RatesResponse},
+    service,
+};
+
+use super::sparse_rates::decode_sparse;
+
+#[test]
+fn test_decode_sparse() {
+    let mut data = RatesData::default();
+    data.set_rate(Money::new_money(Currency::USD, dec!(1)));
+    data.set_rate(Money::new_money(Currency::IDR, dec!(15500.5)));
+    data.set_rate(Money::new_money(Currency::EUR, dec!(0.91)));
+    let rates = Rates {
+        date: Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap(),
+        base: Currency::USD,
+        rates: data,
+    };
+    let full = RatesResponse::new("sparse".to_string(), rates);
+    let content = serde_json::to_string(&full).unwrap();
+
+    let ret = decode_sparse(&content, &[Currency::EUR, Currency::IDR]).unwrap();
+    assert_eq!(ret.id, full.id);
+    assert_eq!(ret.source, full.source);
+    assert_eq!(ret.data.date, full.data.date);
+    assert_eq!(ret.data.base, Currency::USD);
+    assert!(ret.error.is_none());
+    assert_eq!(ret.data.rates.rate(Currency::IDR), dec!(15500.5));
+    assert_eq!(ret.data.rates.rate(Currency::EUR), dec!(0.91));
+    // not wanted, left zero
+    assert!(ret.data.rates.rate(Currency::USD).is_zero());
+
+    // same pair rate as fully decoded
+    assert_eq!(
+        service::pair_rate(&ret.data, Currency::EUR, Currency::IDR).unwrap(),
+        service::pair_rate(&full.data, Currency::EUR, Currency::IDR).unwrap()
+    );
+
+    // rates written with upper case codes
+    let upper = content.replace("\"idr\"", "\"IDR\"");
+    let ret = decode_sparse(&upper, &[Currency::IDR]).unwrap();
+    assert_eq!(ret.data.rates.rate(Currency::IDR), dec!(15500.5));
+
+    assert!(decode_sparse("{}", &[Currency::IDR]).is_err());
+}
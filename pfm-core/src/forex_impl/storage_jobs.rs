@@ -0,0 +1,98 @@
+// storage_jobs.rs jobs and providers bookkeeping of ForexStorageImpl, delegating to its methods in forex_storage.rs.
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::forex::entity::{
+    BackfillCheckpoint, Job, JobRun, ProviderCallSample, ProviderHealth, SloReport, StorageStats,
+    WebhookDelivery,
+};
+use crate::forex::interface::{
+    ForexStorageBackfill, ForexStorageJobLock, ForexStorageJobRuns, ForexStorageProviderHealth,
+    ForexStorageSlo, ForexStorageStats, ForexStorageWebhookDeliveries,
+};
+use crate::forex::{Currency, ForexResult};
+use crate::forex_impl::forex_storage::ForexStorageImpl;
+
+#[async_trait]
+impl ForexStorageProviderHealth for ForexStorageImpl {
+    async fn record_provider_call(
+        &self,
+        provider: &str,
+        sample: ProviderCallSample,
+    ) -> ForexResult<()> {
+        self.record_provider_call(provider, sample).await
+    }
+
+    async fn get_provider_health(&self) -> ForexResult<Vec<ProviderHealth>> {
+        self.get_provider_health().await
+    }
+}
+
+#[async_trait]
+impl ForexStorageStats for ForexStorageImpl {
+    async fn stats(&self) -> ForexResult<StorageStats> {
+        self.stats().await
+    }
+}
+
+#[async_trait]
+impl ForexStorageWebhookDeliveries for ForexStorageImpl {
+    async fn insert_webhook_delivery(&self, delivery: &WebhookDelivery) -> ForexResult<()> {
+        self.insert_webhook_delivery(delivery).await
+    }
+}
+
+#[async_trait]
+impl ForexStorageBackfill for ForexStorageImpl {
+    async fn get_backfill_checkpoint(
+        &self,
+        provider: &str,
+        base: Currency,
+    ) -> ForexResult<BackfillCheckpoint> {
+        self.get_backfill_checkpoint(provider, base).await
+    }
+
+    async fn insert_backfill_checkpoint(
+        &self,
+        provider: &str,
+        base: Currency,
+        checkpoint: &BackfillCheckpoint,
+    ) -> ForexResult<()> {
+        self.insert_backfill_checkpoint(provider, base, checkpoint)
+            .await
+    }
+}
+
+#[async_trait]
+impl ForexStorageSlo for ForexStorageImpl {
+    async fn insert_slo_report(&self, report: &SloReport) -> ForexResult<()> {
+        self.insert_slo_report(report).await
+    }
+
+    async fn get_slo_reports(
+        &self,
+        since: DateTime<Utc>,
+        limit: u32,
+    ) -> ForexResult<Vec<SloReport>> {
+        self.get_slo_reports(since, limit).await
+    }
+}
+
+#[async_trait]
+impl ForexStorageJobLock for ForexStorageImpl {
+    async fn try_lock_job(&self, job: Job, holder: &str, ttl: TimeDelta) -> ForexResult<bool> {
+        self.try_lock_job(job, holder, ttl).await
+    }
+}
+
+#[async_trait]
+impl ForexStorageJobRuns for ForexStorageImpl {
+    async fn insert_job_run(&self, run: &JobRun) -> ForexResult<()> {
+        self.insert_job_run(run).await
+    }
+
+    async fn get_job_runs(&self, job: Option<Job>, limit: u32) -> ForexResult<Vec<JobRun>> {
+        self.get_job_runs(job, limit).await
+    }
+}
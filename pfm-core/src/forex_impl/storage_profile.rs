@@ -0,0 +1,182 @@
+// storage_profile.rs records of users and their profiles in ForexStorageImpl, delegating to its methods in forex_storage.rs.
+
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::digest::{entity::DigestSubscription, interface::DigestStorage};
+use crate::forex::ForexResult;
+use crate::forex_impl::forex_storage::ForexStorageImpl;
+use crate::goals::{entity::Goal, interface::GoalsStorage};
+use crate::ledger::{
+    entity::{Account, CategoryRule, JournalEntry, Transaction},
+    interface::{LedgerStorage, LedgerStorageCategoryRules, LedgerStorageDoubleEntry},
+};
+use crate::sync::{
+    entity::{RevisionLog, Tombstone},
+    interface::{SyncStorage, SyncStorageRevisions},
+};
+use crate::users::{entity::User, interface::UsersStorage};
+use crate::watchlist::{entity::Watchlist, interface::WatchlistStorage};
+
+#[async_trait]
+impl UsersStorage for ForexStorageImpl {
+    async fn upsert_user(&self, user: &User) -> ForexResult<()> {
+        self.upsert_user(user).await
+    }
+
+    async fn get_user(&self, username: &str) -> ForexResult<Option<User>> {
+        self.get_user(username).await
+    }
+
+    async fn get_usernames(&self) -> ForexResult<Vec<String>> {
+        self.get_usernames().await
+    }
+
+    async fn delete_user(&self, username: &str) -> ForexResult<()> {
+        self.delete_user(username).await
+    }
+}
+
+#[async_trait]
+impl GoalsStorage for ForexStorageImpl {
+    async fn upsert_goal(&self, goal: &Goal) -> ForexResult<()> {
+        self.upsert_goal(goal).await
+    }
+
+    async fn get_goal(&self, id: Uuid) -> ForexResult<Goal> {
+        self.get_goal(id).await
+    }
+
+    async fn get_goals(&self) -> ForexResult<Vec<Goal>> {
+        self.get_goals().await
+    }
+
+    async fn delete_goal(&self, id: Uuid) -> ForexResult<()> {
+        self.delete_goal(id).await
+    }
+}
+
+#[async_trait]
+impl WatchlistStorage for ForexStorageImpl {
+    async fn get_watchlist(&self, profile: &str) -> ForexResult<Option<Watchlist>> {
+        self.get_watchlist(profile).await
+    }
+
+    async fn upsert_watchlist(&self, watchlist: &Watchlist) -> ForexResult<()> {
+        self.upsert_watchlist(watchlist).await
+    }
+
+    async fn delete_watchlist(&self, profile: &str) -> ForexResult<()> {
+        self.delete_watchlist(profile).await
+    }
+
+    async fn get_watchlists(&self) -> ForexResult<Vec<Watchlist>> {
+        self.get_watchlists().await
+    }
+}
+
+#[async_trait]
+impl SyncStorage for ForexStorageImpl {
+    async fn get_tombstones(&self) -> ForexResult<Vec<Tombstone>> {
+        self.get_tombstones().await
+    }
+
+    async fn upsert_tombstone(&self, tombstone: &Tombstone) -> ForexResult<()> {
+        self.upsert_tombstone(tombstone).await
+    }
+
+    async fn delete_tombstones_before(&self, before: DateTime<Utc>) -> ForexResult<usize> {
+        self.delete_tombstones_before(before).await
+    }
+
+    async fn get_last_sync(&self, peer: &str) -> ForexResult<Option<DateTime<Utc>>> {
+        self.get_last_sync(peer).await
+    }
+
+    async fn set_last_sync(&self, peer: &str, at: DateTime<Utc>) -> ForexResult<()> {
+        self.set_last_sync(peer, at).await
+    }
+
+    async fn get_last_syncs(&self) -> ForexResult<BTreeMap<String, DateTime<Utc>>> {
+        self.get_last_syncs().await
+    }
+}
+
+#[async_trait]
+impl SyncStorageRevisions for ForexStorageImpl {
+    async fn get_revision_log(&self, name: &str) -> ForexResult<RevisionLog> {
+        self.get_revision_log(name).await
+    }
+
+    async fn set_revision_log(&self, name: &str, log: &RevisionLog) -> ForexResult<()> {
+        self.set_revision_log(name, log).await
+    }
+}
+
+#[async_trait]
+impl DigestStorage for ForexStorageImpl {
+    async fn upsert_digest_subscription(
+        &self,
+        subscription: &DigestSubscription,
+    ) -> ForexResult<()> {
+        self.upsert_digest_subscription(subscription).await
+    }
+
+    async fn get_digest_subscriptions(&self) -> ForexResult<Vec<DigestSubscription>> {
+        self.get_digest_subscriptions().await
+    }
+
+    async fn delete_digest_subscription(&self, id: Uuid) -> ForexResult<()> {
+        self.delete_digest_subscription(id).await
+    }
+}
+
+#[async_trait]
+impl LedgerStorage for ForexStorageImpl {
+    async fn upsert_transactions(&self, transactions: &[Transaction]) -> ForexResult<()> {
+        self.upsert_transactions(transactions).await
+    }
+
+    async fn get_transactions(
+        &self,
+        range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    ) -> ForexResult<Vec<Transaction>> {
+        self.get_transactions(range).await
+    }
+}
+
+#[async_trait]
+impl LedgerStorageCategoryRules for ForexStorageImpl {
+    async fn get_category_rules(&self) -> ForexResult<Vec<CategoryRule>> {
+        self.get_category_rules().await
+    }
+
+    async fn set_category_rules(&self, rules: &[CategoryRule]) -> ForexResult<()> {
+        self.set_category_rules(rules).await
+    }
+}
+
+#[async_trait]
+impl LedgerStorageDoubleEntry for ForexStorageImpl {
+    async fn get_accounts(&self) -> ForexResult<Vec<Account>> {
+        self.get_accounts().await
+    }
+
+    async fn upsert_account(&self, account: &Account) -> ForexResult<()> {
+        self.upsert_account(account).await
+    }
+
+    async fn insert_journal_entry(&self, entry: &JournalEntry) -> ForexResult<()> {
+        self.insert_journal_entry(entry).await
+    }
+
+    async fn get_journal_entries(
+        &self,
+        until: Option<DateTime<Utc>>,
+    ) -> ForexResult<Vec<JournalEntry>> {
+        self.get_journal_entries(until).await
+    }
+}
@@ -0,0 +1,161 @@
+// storage_rates.rs rates capabilities of ForexStorageImpl beyond ForexStorage, delegating to its methods in forex_storage.rs.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::forex::entity::{
+    ConsistencyReport, Cursor, HistoricalVersion, Order, OrderBy, Rates, RatesChange,
+    RatesChangeReplay, RatesPage, RatesProvenance, RatesResponse, SignatureReport, Sparkline,
+};
+use crate::forex::interface::{
+    ForexStorageBootstrap, ForexStorageConsistency, ForexStorageJournal, ForexStorageMigration,
+    ForexStoragePagination, ForexStorageProvenance, ForexStorageRebased, ForexStorageSignatures,
+    ForexStorageSnapshots, ForexStorageSparklines, ForexStorageSparse, ForexStorageValidation,
+    ForexStorageVersions,
+};
+use crate::forex::{Currency, ForexResult};
+use crate::forex_impl::forex_storage::ForexStorageImpl;
+
+#[async_trait]
+impl ForexStorageRebased for ForexStorageImpl {
+    async fn get_historical_rebased(
+        &self,
+        date: DateTime<Utc>,
+        base: Currency,
+    ) -> ForexResult<RatesResponse<Rates>> {
+        self.get_historical_rebased(date, base).await
+    }
+}
+
+#[async_trait]
+impl ForexStorageSparse for ForexStorageImpl {
+    async fn get_historical_range_of(
+        &self,
+        currencies: &[Currency],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> ForexResult<Vec<RatesResponse<Rates>>> {
+        self.get_historical_range_of(currencies, start, end).await
+    }
+}
+
+#[async_trait]
+impl ForexStorageSnapshots for ForexStorageImpl {
+    async fn get_latest_by_id(&self, id: Uuid) -> ForexResult<Option<RatesResponse<Rates>>> {
+        self.get_latest_by_id(id).await
+    }
+}
+
+impl ForexStorageValidation for ForexStorageImpl {
+    fn forced(&self) -> Self {
+        self.forced()
+    }
+}
+
+#[async_trait]
+impl ForexStoragePagination for ForexStorageImpl {
+    async fn get_latest_page(
+        &self,
+        cursor: Option<Cursor>,
+        size: u32,
+        order: Order,
+        order_by: OrderBy,
+    ) -> ForexResult<RatesPage<RatesResponse<Rates>>> {
+        self.get_latest_page(cursor, size, order, order_by).await
+    }
+
+    async fn get_historical_page(
+        &self,
+        cursor: Option<Cursor>,
+        size: u32,
+        order: Order,
+        order_by: OrderBy,
+    ) -> ForexResult<RatesPage<RatesResponse<Rates>>> {
+        self.get_historical_page(cursor, size, order, order_by)
+            .await
+    }
+}
+
+#[async_trait]
+impl ForexStorageJournal for ForexStorageImpl {
+    async fn get_changes(&self, date: DateTime<Utc>) -> ForexResult<Vec<RatesChange>> {
+        self.get_changes(date).await
+    }
+
+    async fn replay_changes(&self, date: DateTime<Utc>) -> ForexResult<RatesChangeReplay> {
+        self.replay_changes(date).await
+    }
+}
+
+#[async_trait]
+impl ForexStorageVersions for ForexStorageImpl {
+    async fn get_historical_versions(
+        &self,
+        date: DateTime<Utc>,
+    ) -> ForexResult<Vec<HistoricalVersion>> {
+        self.get_historical_versions(date).await
+    }
+
+    async fn get_historical_version(
+        &self,
+        date: DateTime<Utc>,
+        version: u32,
+    ) -> ForexResult<RatesResponse<Rates>> {
+        self.get_historical_version(date, version).await
+    }
+}
+
+#[async_trait]
+impl ForexStorageMigration for ForexStorageImpl {
+    async fn migrate_latest_filenames(&self) -> ForexResult<Vec<String>> {
+        self.migrate_latest_filenames().await
+    }
+}
+
+#[async_trait]
+impl ForexStorageSignatures for ForexStorageImpl {
+    async fn verify_signatures(&self, public_key: &str) -> ForexResult<SignatureReport> {
+        self.verify_signatures(public_key).await
+    }
+}
+
+#[async_trait]
+impl ForexStorageBootstrap for ForexStorageImpl {
+    async fn is_empty(&self) -> ForexResult<bool> {
+        self.is_empty().await
+    }
+}
+
+#[async_trait]
+impl ForexStorageSparklines for ForexStorageImpl {
+    async fn get_sparkline(&self, currency: Currency) -> ForexResult<Sparkline> {
+        self.get_sparkline(currency).await
+    }
+
+    async fn rebuild_sparklines(&self) -> ForexResult<()> {
+        self.rebuild_sparklines().await
+    }
+}
+
+#[async_trait]
+impl ForexStorageProvenance for ForexStorageImpl {
+    async fn get_historical_provenance(&self, date: DateTime<Utc>) -> ForexResult<RatesProvenance> {
+        self.get_historical_provenance(date).await
+    }
+
+    async fn get_modified_historical_dates(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> ForexResult<Vec<DateTime<Utc>>> {
+        self.get_modified_historical_dates(start, end).await
+    }
+}
+
+#[async_trait]
+impl ForexStorageConsistency for ForexStorageImpl {
+    async fn insert_consistency_report(&self, report: &ConsistencyReport) -> ForexResult<()> {
+        self.insert_consistency_report(report).await
+    }
+}
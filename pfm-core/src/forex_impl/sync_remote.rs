@@ -0,0 +1,101 @@
+// sync_remote.rs client of sync endpoints of pfm-http, authenticated with api key of writer role.
+
+use anyhow::{Context, anyhow};
+use async_trait::async_trait;
+use serde::{Deserialize, de::DeserializeOwned};
+
+use crate::error::AsInternalError;
+use crate::forex::{ForexError, ForexResult};
+use crate::sync::{
+    entity::{RemoteChanges, RemotePush, RemotePushReport},
+    interface::SyncRemote,
+};
+
+/// path of sync endpoints relative to server url.
+const SYNC_PATH: &str = "/admin/sync";
+
+#[derive(Clone)]
+pub struct HttpSyncRemote {
+    url: String,
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl HttpSyncRemote {
+    /// url of the server, e.g. https://pfm.example.com
+    pub fn new(url: &str, api_key: &str, http_client: reqwest::Client) -> Self {
+        Self {
+            url: format!("{}{}", url.trim_end_matches('/'), SYNC_PATH),
+            api_key: api_key.to_string(),
+            client: http_client,
+        }
+    }
+}
+
+/// response body of pfm-http.
+#[derive(Deserialize)]
+struct Response<T> {
+    data: Option<T>,
+    error: Option<ResponseError>,
+}
+
+#[derive(Deserialize)]
+struct ResponseError {
+    code: String,
+    message: String,
+}
+
+async fn parse<T: DeserializeOwned>(resp: reqwest::Response) -> ForexResult<T> {
+    let status = resp.status();
+    let text = resp
+        .text()
+        .await
+        .context("sync remote string response")
+        .as_internal_err()?;
+    let body: Response<T> = serde_json::from_str(&text)
+        .map_err(|err| anyhow!("sync remote parsing json: {}, err: {}", &text, err))
+        .as_internal_err()?;
+
+    match (body.data, body.error) {
+        (Some(data), None) if status.is_success() => Ok(data),
+        (_, Some(error)) => Err(ForexError::internal_error(&format!(
+            "sync remote responded {}: {}({})",
+            status, error.message, error.code
+        ))),
+        _ => Err(ForexError::internal_error(&format!(
+            "sync remote responded {} without data",
+            status
+        ))),
+    }
+}
+
+#[async_trait]
+impl SyncRemote for HttpSyncRemote {
+    async fn pull(&self, since: u64) -> ForexResult<RemoteChanges> {
+        let resp = self
+            .client
+            .get(&self.url)
+            .header("x-api-key", &self.api_key)
+            .query(&[("since", since)])
+            .send()
+            .await
+            .context("sync remote pull invoking api")
+            .as_internal_err()?;
+
+        parse(resp).await
+    }
+
+    async fn push(&self, pushes: &[RemotePush]) -> ForexResult<RemotePushReport> {
+        let resp = self
+            .client
+            .post(&self.url)
+            .header("x-api-key", &self.api_key)
+            .json(pushes)
+            .send()
+            .await
+            .context("sync remote push invoking api")
+            .as_internal_err()?;
+
+        parse(resp).await
+    }
+}
@@ -8,14 +8,21 @@ use serde::{Deserialize, Serialize};
 use crate::error::AsInternalError;
 use crate::forex::{
     Currency, ForexError, ForexResult,
-    entity::{Rates, RatesData, RatesResponse},
-    interface::{ForexHistoricalRates, ForexRates},
+    entity::{ProviderCapabilities, Rates, RatesData, RatesResponse},
+    interface::{ForexHistoricalRates, ForexProviderCapabilities, ForexRates},
 };
+use crate::global::Secret;
 
 const LATEST_ENDPOINT: &str = "https://marketdata.tradermade.com/api/v1/live";
 const HISTORICAL_ENDPOINT: &str = "https://marketdata.tradermade.com/api/v1/historical";
 const SOURCE: &str = "tradermade.com";
 
+/// all currencies, historical rates since 1990.
+fn capabilities() -> ProviderCapabilities {
+    ProviderCapabilities::default()
+        .with_history_start(Utc.with_ymd_and_hms(1990, 1, 1, 0, 0, 0).unwrap())
+}
+
 // https://tradermade.com/
 /**
 1,000 Requests
@@ -28,7 +35,7 @@ Historical Minute
 // latest rates dto
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
-struct LatestResponse {
+pub(super) struct LatestResponse {
     endpoint: String,
     quotes: Vec<Quote>,
     requested_time: String,
@@ -50,7 +57,7 @@ struct Quote {
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
-struct HistoricalResponse {
+pub(super) struct HistoricalResponse {
     date: String,
     endpoint: String,
     quotes: Vec<QuoteEnum>,
@@ -121,15 +128,24 @@ impl RatesData {
     }
 }
 
+/// number of pairs requested for the base, one per other requested currency.
+fn requested_quotes_count(base: Currency) -> usize {
+    capabilities()
+        .requested_symbols()
+        .into_iter()
+        .filter(|&c| c != base)
+        .count()
+}
+
 impl TryFrom<(Currency, LatestResponse)> for RatesResponse<Rates> {
     type Error = ForexError;
 
     fn try_from(value: (Currency, LatestResponse)) -> Result<Self, Self::Error> {
-        if value.1.quotes.len() != Currency::currencies_count() - 1 {
+        if value.1.quotes.len() != requested_quotes_count(value.0) {
             return Err(ForexError::internal_error(
                 format!(
                     "tradermade mismatch api response number of quotes, expected {}, got {}",
-                    Currency::currencies_count(),
+                    requested_quotes_count(value.0),
                     value.1.quotes.len()
                 )
                 .as_str(),
@@ -212,7 +228,7 @@ impl TryFrom<(Currency, HistoricalResponse)> for RatesResponse<Rates> {
     type Error = ForexError;
 
     fn try_from(value: (Currency, HistoricalResponse)) -> Result<Self, Self::Error> {
-        if value.1.quotes.len() != Currency::currencies_count() - 1 {
+        if value.1.quotes.len() != requested_quotes_count(value.0) {
             return Err(ForexError::internal_error(
                 "tradermade historical incorrect quotes count",
             ));
@@ -296,12 +312,12 @@ impl TryFrom<(Currency, HistoricalResponse)> for RatesResponse<Rates> {
 
 #[derive(Clone)]
 pub struct Api {
-    api_key: &'static str,
+    api_key: Secret,
     client: reqwest::Client,
 }
 
 impl Api {
-    pub fn new(api_key: &'static str, client: reqwest::Client) -> Self {
+    pub fn new(api_key: Secret, client: reqwest::Client) -> Self {
         Self { api_key, client }
     }
 }
@@ -309,9 +325,14 @@ impl Api {
 #[async_trait]
 impl ForexRates for Api {
     async fn rates(&self, base: Currency) -> ForexResult<RatesResponse<Rates>> {
-        let currencies = Currency::to_comma_separated_pair_list_str(base);
+        let currencies =
+            Currency::to_comma_separated_pair_list_str_of(base, capabilities().requested_symbols());
 
-        let params = [("api_key", self.api_key), ("currency", currencies.as_str())];
+        let api_key = self.api_key.expose();
+        let params = [
+            ("api_key", api_key.as_str()),
+            ("currency", currencies.as_str()),
+        ];
 
         let resp_str = self
             .client
@@ -349,11 +370,13 @@ impl ForexHistoricalRates for Api {
         date: DateTime<Utc>,
         base: Currency,
     ) -> ForexResult<RatesResponse<Rates>> {
-        let currencies = Currency::to_comma_separated_pair_list_str(base);
+        let currencies =
+            Currency::to_comma_separated_pair_list_str_of(base, capabilities().requested_symbols());
         let date = date.format("%Y-%m-%d").to_string();
 
+        let api_key = self.api_key.expose();
         let params = [
-            ("api_key", self.api_key),
+            ("api_key", api_key.as_str()),
             ("currency", currencies.as_str()),
             ("date", date.as_str()),
         ];
@@ -386,3 +409,9 @@ impl ForexHistoricalRates for Api {
         Ok(ret.try_into()?)
     }
 }
+
+impl ForexProviderCapabilities for Api {
+    fn capabilities(&self) -> ProviderCapabilities {
+        capabilities()
+    }
+}
@@ -0,0 +1,279 @@
+// twelvedata.com
+// free
+// 800 reqs/day
+// 8 reqs/minute
+// daily OHLC of crypto pairs from /time_series, one call per symbol.
+// real-time prices of crypto pairs from /price, all symbols in one call, each symbol still costs 1 credit.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, anyhow};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::error::AsInternalError;
+use crate::forex::{
+    Currency, ForexError, ForexResult,
+    entity::{CryptoPrice, PriceUnit},
+    interface::ForexCryptoPrices,
+};
+use crate::global::Secret;
+
+const TIMESERIES_ENDPOINT: &str = "https://api.twelvedata.com/time_series";
+
+const PRICE_ENDPOINT: &str = "https://api.twelvedata.com/price";
+
+/// source of captured prices, recorded in provenance of rates they patch.
+pub const SOURCE: &str = "twelvedata.com";
+
+#[derive(Clone)]
+pub struct Api {
+    key: Secret,
+    client: reqwest::Client,
+}
+
+impl Api {
+    pub fn new(key: Secret, http_client: reqwest::Client) -> Self {
+        Self {
+            key,
+            client: http_client,
+        }
+    }
+
+    /// close price in USD of 1 coin on the date.
+    async fn daily_close_usd(&self, currency: Currency, date: &str) -> ForexResult<Decimal> {
+        let symbol = format!("{}/USD", currency.code());
+        let key = self.key.expose();
+        let params = [
+            ("apikey", key.as_str()),
+            ("symbol", symbol.as_str()),
+            ("interval", "1day"),
+            ("date", date),
+        ];
+
+        let ret_text = self
+            .client
+            .get(TIMESERIES_ENDPOINT)
+            .query(&params)
+            .send()
+            .await
+            .context("twelvedata daily close invoking api")
+            .as_internal_err()?
+            .text()
+            .await
+            .context("twelvedata daily close string response")
+            .as_internal_err()?;
+
+        let ret: TimeseriesResponse = serde_json::from_str(&ret_text)
+            .map_err(|err| {
+                anyhow!(
+                    "twelvedata parsing daily close json: {}, err: {}",
+                    &ret_text,
+                    err
+                )
+            })
+            .as_internal_err()?;
+        if let Some(message) = ret.message {
+            return Err(ForexError::internal_error(&format!(
+                "twelvedata daily close of {}: {}",
+                symbol, message
+            )));
+        }
+
+        match ret.values.first() {
+            Some(price) if price.datetime == date => Ok(price.close),
+            Some(price) => Err(ForexError::internal_error(&format!(
+                "twelvedata daily close of {} returned mismatch date, expected: {}, got: {}",
+                symbol, date, price.datetime
+            ))),
+            None => Err(ForexError::internal_error(&format!(
+                "twelvedata daily close of {} is empty",
+                symbol
+            ))),
+        }
+    }
+
+    /// current prices in USD of 1 coin of the currencies, in a single batch call.
+    async fn prices_usd(
+        &self,
+        currencies: &[Currency],
+    ) -> ForexResult<Vec<(Currency, ForexResult<Decimal>)>> {
+        let symbols: Vec<String> = currencies
+            .iter()
+            .map(|currency| format!("{}/USD", currency.code()))
+            .collect();
+        let symbol = symbols.join(",");
+        let key = self.key.expose();
+        let params = [("apikey", key.as_str()), ("symbol", symbol.as_str())];
+
+        let ret_text = self
+            .client
+            .get(PRICE_ENDPOINT)
+            .query(&params)
+            .send()
+            .await
+            .context("twelvedata price invoking api")
+            .as_internal_err()?
+            .text()
+            .await
+            .context("twelvedata price string response")
+            .as_internal_err()?;
+
+        let mut ret = parse_prices(&ret_text, &symbols)?;
+        let prices = currencies
+            .iter()
+            .zip(&symbols)
+            .map(|(&currency, symbol)| {
+                let price = match ret.remove(symbol) {
+                    Some(price) => price.price_of(symbol),
+                    None => Err(ForexError::internal_error(&format!(
+                        "twelvedata price of {} is missing",
+                        symbol
+                    ))),
+                };
+                (currency, price)
+            })
+            .collect();
+
+        Ok(prices)
+    }
+}
+
+/// prices by symbol, response of single symbol is the price itself while batch is keyed by symbols.
+fn parse_prices(text: &str, symbols: &[String]) -> ForexResult<HashMap<String, PriceResponse>> {
+    let parse_err =
+        |err: serde_json::Error| anyhow!("twelvedata parsing price json: {}, err: {}", text, err);
+    // whole request failed, e.g. invalid key or exhausted quota
+    let ret: PriceResponse = serde_json::from_str(text)
+        .map_err(parse_err)
+        .as_internal_err()?;
+    if let Some(message) = ret.message {
+        return Err(ForexError::internal_error(&format!(
+            "twelvedata price: {}",
+            message
+        )));
+    }
+
+    match symbols {
+        [symbol] => Ok(HashMap::from([(symbol.clone(), ret)])),
+        _ => Ok(serde_json::from_str(text)
+            .map_err(parse_err)
+            .as_internal_err()?),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PriceResponse {
+    price: Option<Decimal>,
+
+    /// set on error, e.g. unknown symbol or exhausted quota.
+    message: Option<String>,
+}
+
+impl PriceResponse {
+    fn price_of(self, symbol: &str) -> ForexResult<Decimal> {
+        match (self.price, self.message) {
+            (_, Some(message)) => Err(ForexError::internal_error(&format!(
+                "twelvedata price of {}: {}",
+                symbol, message
+            ))),
+            (Some(price), None) => Ok(price),
+            (None, None) => Err(ForexError::internal_error(&format!(
+                "twelvedata price of {} is empty",
+                symbol
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TimeseriesResponse {
+    #[serde(default)]
+    values: Vec<PriceData>,
+
+    /// set on error, e.g. unknown symbol or exhausted quota.
+    message: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PriceData {
+    datetime: String,
+    open: Decimal,
+    high: Decimal,
+    low: Decimal,
+    close: Decimal,
+}
+
+#[async_trait]
+impl ForexCryptoPrices for Api {
+    /// currencies failing to be fetched are logged and left out, error only if none is fetched.
+    async fn daily_close(
+        &self,
+        date: DateTime<Utc>,
+        currencies: &[Currency],
+    ) -> ForexResult<Vec<CryptoPrice>> {
+        let day = date.format("%Y-%m-%d").to_string();
+        let date = date.date_naive().and_time(Default::default()).and_utc();
+
+        let mut prices = vec![];
+        let mut last_err = None;
+        for &currency in currencies {
+            let rate = match self.daily_close_usd(currency, &day).await {
+                Ok(close) => PriceUnit::UsdPerCoin.to_rate(close),
+                Err(err) => {
+                    tracing::warn!("twelvedata daily close of {} failed: {}", currency, err);
+                    last_err = Some(err);
+                    continue;
+                }
+            };
+            let Some(rate) = rate else {
+                tracing::warn!("twelvedata daily close of {} is not positive", currency);
+                continue;
+            };
+            prices.push(CryptoPrice {
+                currency,
+                date,
+                rate,
+                source: SOURCE.to_string(),
+            });
+        }
+
+        match (prices.is_empty(), last_err) {
+            (true, Some(err)) => Err(err),
+            _ => Ok(prices),
+        }
+    }
+
+    /// currencies failing to be fetched are logged and left out, error only if none is fetched.
+    async fn latest(&self, currencies: &[Currency]) -> ForexResult<Vec<CryptoPrice>> {
+        let mut prices = vec![];
+        let mut last_err = None;
+        for (currency, price) in self.prices_usd(currencies).await? {
+            let rate = match price {
+                Ok(price) => PriceUnit::UsdPerCoin.to_rate(price),
+                Err(err) => {
+                    tracing::warn!("twelvedata price of {} failed: {}", currency, err);
+                    last_err = Some(err);
+                    continue;
+                }
+            };
+            let Some(rate) = rate else {
+                tracing::warn!("twelvedata price of {} is not positive", currency);
+                continue;
+            };
+            prices.push(CryptoPrice {
+                currency,
+                date: Utc::now(),
+                rate,
+                source: SOURCE.to_string(),
+            });
+        }
+
+        match (prices.is_empty(), last_err) {
+            (true, Some(err)) => Err(err),
+            _ => Ok(prices),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::Utc;
+use ring::hmac;
+
+use crate::error::AsInternalError;
+use crate::forex::{
+    ForexError, ForexResult,
+    entity::{Rates, RatesResponse, SloReport, WebhookDelivery},
+    interface::{ForexRatesWebhook, ForexSloAlerts},
+};
+
+/// header containing hex encoded HMAC-SHA256 of request body signed with shared secret.
+pub const SIGNATURE_HEADER: &str = "X-Pfm-Signature";
+
+/// first retry waits this long, doubled on each next retry.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+#[derive(Clone)]
+pub struct Webhook {
+    client: reqwest::Client,
+    urls: Vec<String>,
+    key: hmac::Key,
+    max_attempts: u32,
+}
+
+impl Webhook {
+    pub fn new(
+        urls: Vec<String>,
+        secret: &str,
+        max_attempts: u32,
+        http_client: reqwest::Client,
+    ) -> Self {
+        Self {
+            client: http_client,
+            urls,
+            key: hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()),
+            max_attempts: max_attempts.max(1),
+        }
+    }
+
+    /// parse comma separated urls, ignoring empty entries.
+    pub fn parse_urls(urls: &str) -> Vec<String> {
+        urls.split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub(crate) fn sign(&self, body: &[u8]) -> String {
+        let tag = hmac::sign(&self.key, body);
+        format!("sha256={}", hex(tag.as_ref()))
+    }
+
+    async fn deliver(&self, url: &str, rates_id: uuid::Uuid, body: &[u8]) -> WebhookDelivery {
+        let mut delivery = WebhookDelivery::new(url, rates_id);
+        let signature = self.sign(body);
+        let mut backoff = INITIAL_BACKOFF;
+
+        while delivery.attempts < self.max_attempts {
+            if delivery.attempts > 0 {
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            delivery.attempts += 1;
+
+            let ret = self
+                .client
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(SIGNATURE_HEADER, &signature)
+                .body(body.to_vec())
+                .send()
+                .await;
+
+            match ret {
+                Ok(resp) if resp.status().is_success() => {
+                    delivery.status_code = Some(resp.status().as_u16());
+                    delivery.delivered_at = Some(Utc::now());
+                    delivery.success = true;
+                    delivery.error = None;
+                    break;
+                }
+                Ok(resp) => {
+                    delivery.status_code = Some(resp.status().as_u16());
+                    delivery.error = Some(format!("webhook responded with {}", resp.status()));
+                }
+                Err(err) => {
+                    delivery.status_code = None;
+                    delivery.error = Some(format!("webhook request failed: {}", err));
+                }
+            }
+            tracing::warn!(
+                "webhook delivery to {} attempt {} failed: {:?}",
+                url,
+                delivery.attempts,
+                delivery.error
+            );
+        }
+
+        delivery
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[async_trait]
+impl ForexRatesWebhook for Webhook {
+    async fn publish(&self, rates: &RatesResponse<Rates>) -> Vec<WebhookDelivery> {
+        let body = match serde_json::to_vec(rates) {
+            Ok(body) => body,
+            Err(err) => {
+                tracing::error!("webhook failed serializing rates: {}", err);
+                return vec![];
+            }
+        };
+
+        let mut deliveries = Vec::with_capacity(self.urls.len());
+        for url in &self.urls {
+            deliveries.push(self.deliver(url, rates.id, &body).await);
+        }
+
+        deliveries
+    }
+}
+
+#[async_trait]
+impl ForexSloAlerts for Webhook {
+    async fn alert_slo_breach(&self, report: &SloReport) -> ForexResult<()> {
+        let body = serde_json::to_vec(report)
+            .context("webhook serializing slo report")
+            .as_internal_err()?;
+
+        let mut failed = vec![];
+        for url in &self.urls {
+            let delivery = self.deliver(url, report.id, &body).await;
+            if !delivery.success {
+                failed.push(delivery.url);
+            }
+        }
+        if !failed.is_empty() {
+            return Err(ForexError::internal_error(
+                format!("slo breach not delivered to {}", failed.join(", ")).as_str(),
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,21 @@
+use super::webhook::Webhook;
+
+#[test]
+fn test_parse_urls() {
+    let urls = Webhook::parse_urls(" https://a.example/hook, ,https://b.example/hook ,");
+    assert_eq!(
+        urls,
+        vec!["https://a.example/hook", "https://b.example/hook"]
+    );
+    assert!(Webhook::parse_urls("").is_empty());
+}
+
+#[test]
+fn test_sign() {
+    let webhook = Webhook::new(vec![], "secret", 3, reqwest::Client::new());
+    // HMAC-SHA256("secret", "hello")
+    assert_eq!(
+        webhook.sign(b"hello"),
+        "sha256=88aab3ede8d3adf94d26ab90d3bafd4a2083070c3bcce9c014ee04a443847c0b"
+    );
+}
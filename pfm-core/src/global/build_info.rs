@@ -0,0 +1,93 @@
+// build_info.rs versions and build information of pfm binaries, for debugging deployments running mixed versions.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// version of layout of stored files, bumped when older binaries can't read files written by newer ones.
+/// marked in storage on startup, so binaries refuse storage written with newer layout.
+/// 2 since latest rates filenames have no colons.
+/// 3 since latest rates may be appended into daily NDJSON files.
+/// 4 since historical rates may be written into versioned `.vN.json` files.
+pub const STORAGE_SCHEMA_VERSION: u32 = 4;
+
+/// Versions and build information of a binary and pfm-core it's built with.
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildInfo {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub core_version: &'static str,
+
+    /// short hash of the commit built, `unknown` if built outside git checkout.
+    pub git_hash: &'static str,
+
+    pub build_date: DateTime<Utc>,
+
+    /// enabled features of the binary, followed by those of pfm-core prefixed with `pfm-core/`.
+    pub features: Vec<String>,
+
+    pub storage_schema_version: u32,
+}
+
+impl BuildInfo {
+    /// build information of the binary with its name, version and enabled features.
+    pub fn new(name: &'static str, version: &'static str, features: &[&str]) -> Self {
+        let core_features = env!("PFM_CORE_FEATURES")
+            .split(',')
+            .filter(|feature| !feature.is_empty())
+            .map(|feature| format!("pfm-core/{}", feature));
+
+        Self {
+            name,
+            version,
+            core_version: env!("CARGO_PKG_VERSION"),
+            git_hash: env!("PFM_GIT_HASH"),
+            build_date: env!("PFM_BUILD_DATE")
+                .parse()
+                .ok()
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+                .unwrap_or_default(),
+            features: features
+                .iter()
+                .map(ToString::to_string)
+                .chain(core_features)
+                .collect(),
+            storage_schema_version: STORAGE_SCHEMA_VERSION,
+        }
+    }
+
+    /// build information of the binary with enabled ones of its features, e.g. `("mqtt", cfg!(feature = "mqtt"))`.
+    pub fn from_flags(
+        name: &'static str,
+        version: &'static str,
+        flags: &[(&'static str, bool)],
+    ) -> Self {
+        let features: Vec<&str> = flags
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(feature, _)| *feature)
+            .collect();
+
+        Self::new(name, version, &features)
+    }
+
+    /// multi line detail following name of the binary in `--version` and startup log,
+    /// starting with `0.1.0 (3f2a9c1d0b4e 2025-03-04)`.
+    pub fn detail(&self) -> String {
+        let features = match self.features.is_empty() {
+            true => "-".to_string(),
+            false => self.features.join(", "),
+        };
+
+        format!(
+            "{} ({} {})\npfm-core: {}\ngit hash: {}\nbuild date: {}\nfeatures: {}\nstorage schema: v{}",
+            self.version,
+            self.git_hash,
+            self.build_date.format("%Y-%m-%d"),
+            self.core_version,
+            self.git_hash,
+            self.build_date.to_rfc3339(),
+            features,
+            self.storage_schema_version
+        )
+    }
+}
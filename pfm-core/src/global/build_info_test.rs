@@ -0,0 +1,28 @@
+use super::{BuildInfo, STORAGE_SCHEMA_VERSION};
+
+#[test]
+fn test_build_info() {
+    let info = BuildInfo::new("pfm-test", "1.2.3", &["dashboard"]);
+    assert_eq!(info.core_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(info.storage_schema_version, STORAGE_SCHEMA_VERSION);
+    assert!(!info.git_hash.is_empty());
+    assert!(info.build_date.timestamp() > 0);
+    assert_eq!(info.features[0], "dashboard");
+    assert!(
+        info.features[1..]
+            .iter()
+            .all(|feature| feature.starts_with("pfm-core/"))
+    );
+
+    let detail = info.detail();
+    assert!(detail.starts_with("1.2.3 ("));
+    assert!(detail.contains(&format!("storage schema: v{}", STORAGE_SCHEMA_VERSION)));
+    assert!(detail.contains("features: dashboard"));
+}
+
+#[test]
+fn test_build_info_from_flags() {
+    let info = BuildInfo::from_flags("pfm-test", "1.2.3", &[("mqtt", false), ("email", true)]);
+    assert_eq!(info.features[0], "email");
+    assert!(!info.features.iter().any(|feature| feature == "mqtt"));
+}
@@ -0,0 +1,46 @@
+// clock.rs source of current time, injected into services so time dependent logic can be tested.
+
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+/// Source of current time.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Real time of the system, used by services in production.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Clock standing still at given time until it's set or advanced, for tests.
+/// Clones share the same time.
+#[derive(Debug, Clone)]
+pub struct MockClock(Arc<Mutex<DateTime<Utc>>>);
+
+impl MockClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self(Arc::new(Mutex::new(now)))
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.0.lock().unwrap_or_else(|err| err.into_inner()) = now;
+    }
+
+    pub fn advance(&self, delta: TimeDelta) {
+        let mut now = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        *now += delta;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
@@ -0,0 +1,24 @@
+use chrono::{TimeDelta, TimeZone, Utc};
+
+use super::clock::{Clock, MockClock, SystemClock};
+
+#[test]
+fn test_mock_clock() {
+    let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+    let clock = MockClock::new(start);
+    assert_eq!(clock.now(), start);
+
+    let shared = clock.clone();
+    shared.advance(TimeDelta::hours(25));
+    assert_eq!(clock.now(), start + TimeDelta::hours(25));
+
+    clock.set(start);
+    assert_eq!(shared.now(), start);
+}
+
+#[test]
+fn test_system_clock() {
+    let before = Utc::now();
+    let now = SystemClock.now();
+    assert!(now >= before && now <= Utc::now());
+}
@@ -1,7 +1,15 @@
 use serde::Deserialize;
-use std::{fmt::Debug, sync::LazyLock};
+use std::{
+    fmt::Debug,
+    sync::{LazyLock, OnceLock},
+};
 
 use pfm_utils::config_util;
+use strum::IntoEnumIterator;
+
+use super::constants;
+use super::secrets::SecretsProviderKind;
+use crate::forex::Currency;
 
 /// Get instantiated global config object.
 pub fn config() -> &'static Config {
@@ -25,21 +33,280 @@ where
 /// Configurations for pfm-core
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
-    /// Using symbol or code for displaying money.
-    #[serde(alias = "CORE_FOREX_USE_SYMBOL", default)]
-    pub forex_use_symbol: bool,
-
-    /// API key for https://currencyapi.com
-    #[serde(alias = "CORE_FOREX_CURRENCY_API_KEY")]
+    /// API key for https://currencyapi.com, read through [`super::secrets`].
+    #[serde(alias = "CORE_FOREX_CURRENCY_API_KEY", default)]
     pub forex_currency_api_key: String,
 
-    /// API key for https://openexchangerates.org
-    #[serde(alias = "CORE_FOREX_OPEN_EXCHANGE_API_KEY")]
+    /// API key for https://openexchangerates.org, read through [`super::secrets`].
+    #[serde(alias = "CORE_FOREX_OPEN_EXCHANGE_API_KEY", default)]
     pub forex_open_exchange_api_key: String,
 
-    #[serde(alias = "CORE_FOREX_CURRENCYBEACON_API_KEY")]
+    #[serde(alias = "CORE_FOREX_CURRENCYBEACON_API_KEY", default)]
     pub forex_currencybeacon_api_key: String,
 
-    #[serde(alias = "CORE_FOREX_TWELVEDATA_API_KEY")]
+    #[serde(alias = "CORE_FOREX_TWELVEDATA_API_KEY", default)]
     pub forex_twelvedata_api_key: String,
+
+    /// Where API keys are resolved from, API keys of config are used when missing from the provider.
+    #[serde(alias = "CORE_SECRETS_PROVIDER", default)]
+    pub secrets_provider: SecretsProviderKind,
+
+    /// Directory of secret files, a file per secret named like its config field, e.g. forex_currency_api_key.
+    #[serde(alias = "CORE_SECRETS_DIR", default = "default_secrets_dir")]
+    pub secrets_dir: String,
+
+    /// Service of secrets stored in OS keyring, secret names are their accounts.
+    #[serde(
+        alias = "CORE_SECRETS_KEYRING_SERVICE",
+        default = "default_secrets_keyring_service"
+    )]
+    pub secrets_keyring_service: String,
+
+    /// Address of HashiCorp Vault, e.g. https://vault.example.com:8200
+    #[serde(alias = "CORE_VAULT_ADDR", default)]
+    pub vault_addr: String,
+
+    #[serde(alias = "CORE_VAULT_TOKEN", default)]
+    pub vault_token: String,
+
+    /// Path of KV v2 secret holding secrets as its keys.
+    #[serde(alias = "CORE_VAULT_PATH", default = "default_vault_path")]
+    pub vault_path: String,
+
+    /// Interval in seconds of resolving secrets again to pick up rotated ones, 0 to resolve only on startup.
+    #[serde(alias = "CORE_SECRETS_REFRESH_SECS", default)]
+    pub secrets_refresh_secs: u64,
+
+    /// Shared secret signing internal requests between pfm-cron and pfm-http, internal requests are rejected when empty.
+    #[serde(alias = "CORE_INTERNAL_SECRET", default)]
+    pub internal_secret: String,
+
+    /// Seconds signed internal requests are valid for, either side of their timestamp.
+    #[serde(
+        alias = "CORE_INTERNAL_MAX_SKEW_SECS",
+        default = "default_internal_max_skew_secs"
+    )]
+    pub internal_max_skew_secs: u64,
+
+    /// Name of provider polled for rates, registered in forex_impl::registry::ProviderRegistry.
+    #[serde(alias = "CORE_FOREX_PROVIDER", default = "default_forex_provider")]
+    pub forex_provider: String,
+
+    /// Comma separated names of providers to fall back to when the preferred one fails.
+    /// Providers are tried from the healthiest one, based on their recorded calls.
+    #[serde(alias = "CORE_FOREX_PROVIDER_FALLBACKS", default)]
+    pub forex_provider_fallbacks: String,
+
+    /// Max duration in seconds of each call to 3rd party forex API, per provider of a chain.
+    #[serde(
+        alias = "CORE_FOREX_PROVIDER_TIMEOUT_SECS",
+        default = "default_forex_provider_timeout_secs"
+    )]
+    pub forex_provider_timeout_secs: u64,
+
+    /// Comma separated codes of currencies this deployment cares about, all currencies when empty.
+    /// Only their rates are requested from providers and served, base currency is always enabled.
+    #[serde(alias = "CORE_FOREX_ENABLED_CURRENCIES", default)]
+    pub forex_enabled_currencies: String,
+
+    /// forex_enabled_currencies parsed once on first use.
+    #[serde(skip)]
+    enabled_currencies: OnceLock<Vec<Currency>>,
+
+    /// Max age in seconds of rates before responses relying on them are flagged stale.
+    #[serde(
+        alias = "CORE_FOREX_FRESHNESS_SECS",
+        default = "default_forex_freshness_secs"
+    )]
+    pub forex_freshness_secs: u64,
+
+    /// Hex encoded 32 bytes Ed25519 seed to sign stored rates files with, signing is disabled when empty.
+    #[serde(alias = "CORE_FOREX_SIGNING_KEY", default)]
+    pub forex_signing_key: String,
+
+    /// What to do with older latest rates files when latest directory is cleared.
+    #[serde(alias = "CORE_FOREX_LATEST_ROTATION", default)]
+    pub forex_latest_rotation: LatestRotation,
+
+    /// How latest rates snapshots are laid out in latest directory.
+    #[serde(alias = "CORE_FOREX_LATEST_LAYOUT", default)]
+    pub forex_latest_layout: LatestLayout,
+
+    /// Whether stored historical rates are overwritten or kept when written again.
+    #[serde(alias = "CORE_FOREX_HISTORICAL_MODE", default)]
+    pub forex_historical_mode: HistoricalMode,
+
+    /// Permission policy of storage files and directories.
+    #[serde(alias = "CORE_STORAGE_PERMISSIONS", default)]
+    pub storage_permissions: StoragePermissions,
+
+    /// Directory of server storage, `APP_DATA_PATH` or `~/pfm` joined with `pfm-data` when empty.
+    #[serde(alias = "CORE_STORAGE_ROOT", default)]
+    pub storage_root: String,
+
+    /// Directory of client storage, e.g. goals and watchlists of this device synced with a server.
+    /// Same as server storage when empty.
+    #[serde(alias = "CORE_CLIENT_STORAGE_ROOT", default)]
+    pub client_storage_root: String,
+
+    /// Min free space in MB of storage roots checked on startup, 0 to skip the check.
+    #[serde(
+        alias = "CORE_STORAGE_MIN_FREE_MB",
+        default = "default_storage_min_free_mb"
+    )]
+    pub storage_min_free_mb: u64,
+
+    /// Max disk usage in MB of server storage, 0 for unlimited.
+    /// When exceeded, older latest rates are cleared, then oldest archived latest rates are pruned.
+    #[serde(alias = "CORE_STORAGE_MAX_DISK_MB", default)]
+    pub storage_max_disk_mb: u64,
+}
+
+impl Config {
+    /// names of configured provider followed by its fallbacks, without duplicates.
+    pub fn forex_provider_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = vec![];
+        for name in std::iter::once(self.forex_provider.as_str())
+            .chain(self.forex_provider_fallbacks.split(','))
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+
+        names
+    }
+
+    /// currencies enabled in this deployment in declaration order, unknown codes are ignored.
+    /// parsed once, so unknown codes are warned about once.
+    pub fn enabled_currencies(&self) -> &[Currency] {
+        self.enabled_currencies
+            .get_or_init(|| Self::parse_enabled_currencies(&self.forex_enabled_currencies))
+    }
+
+    pub(super) fn parse_enabled_currencies(codes: &str) -> Vec<Currency> {
+        let enabled: Vec<Currency> = codes
+            .split(',')
+            .map(str::trim)
+            .filter(|code| !code.is_empty())
+            .filter_map(|code| match code.to_uppercase().parse() {
+                Ok(currency) => Some(currency),
+                Err(_) => {
+                    tracing::warn!("global config: ignoring unknown enabled currency {}", code);
+                    None
+                }
+            })
+            .collect();
+        if enabled.is_empty() {
+            return Currency::iter().collect();
+        }
+
+        Currency::iter()
+            .filter(|currency| *currency == constants::BASE_CURRENCY || enabled.contains(currency))
+            .collect()
+    }
+}
+
+/// Rotation policy of latest rates files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LatestRotation {
+    /// delete all but the newest file.
+    #[default]
+    Delete,
+
+    /// move older files into `latest/archive/YYYY/MM/`, preserving intraday rates.
+    Archive,
+}
+
+/// Layout of latest rates snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LatestLayout {
+    /// file per snapshot, `latest-YYYYMMDDThhmmssZ.json`.
+    #[default]
+    Files,
+
+    /// snapshots appended as lines of file per day, `latest-YYYYMMDD.ndjson`,
+    /// keeping latest directory small when polling every few minutes.
+    Daily,
+}
+
+/// Write policy of historical rates files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoricalMode {
+    /// writes of stored date replace its file.
+    #[default]
+    Mutable,
+
+    /// append-only archive, stored files are never rewritten,
+    /// writes of stored date go to its next version, `historical-YYYY-MM-DDZ.v2.json`, and so on.
+    Immutable,
+}
+
+/// Permission policy of storage files and directories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoragePermissions {
+    /// only owner may write, and group may read: 750 for directories, 640 for files.
+    /// no-op on platforms without unix permission bits.
+    #[default]
+    Restricted,
+
+    /// leave permissions as created, e.g. for mounted volumes not supporting chmod.
+    Inherit,
+}
+
+impl StoragePermissions {
+    /// permission mode of directories, none if left as is.
+    pub fn dir_mode(&self) -> Option<u32> {
+        match self {
+            Self::Restricted => Some(0o750),
+            Self::Inherit => None,
+        }
+    }
+
+    /// permission mode of files, none if left as is.
+    pub fn file_mode(&self) -> Option<u32> {
+        match self {
+            Self::Restricted => Some(0o640),
+            Self::Inherit => None,
+        }
+    }
+}
+
+fn default_forex_provider() -> String {
+    "currencybeacon".to_string()
+}
+
+fn default_forex_provider_timeout_secs() -> u64 {
+    30
+}
+
+/// latest rates are polled hourly, they are stale after more than one missed poll.
+fn default_forex_freshness_secs() -> u64 {
+    7200
+}
+
+fn default_storage_min_free_mb() -> u64 {
+    100
+}
+
+fn default_secrets_dir() -> String {
+    "/run/secrets".to_string()
+}
+
+fn default_secrets_keyring_service() -> String {
+    "pfm".to_string()
+}
+
+fn default_vault_path() -> String {
+    "secret/data/pfm".to_string()
+}
+
+fn default_internal_max_skew_secs() -> u64 {
+    300
 }
@@ -0,0 +1,20 @@
+use strum::IntoEnumIterator;
+
+use super::config::Config;
+use super::constants::BASE_CURRENCY;
+use crate::forex::Currency;
+
+#[test]
+fn test_parse_enabled_currencies() {
+    let enabled = Config::parse_enabled_currencies("idr, eur,XYZ");
+    assert_eq!(enabled.len(), 3);
+    assert!(enabled.contains(&Currency::IDR));
+    assert!(enabled.contains(&Currency::EUR));
+    assert!(enabled.contains(&BASE_CURRENCY));
+}
+
+#[test]
+fn test_parse_enabled_currencies_empty_enables_all() {
+    let enabled = Config::parse_enabled_currencies(" , ");
+    assert_eq!(enabled.len(), Currency::iter().count());
+}
@@ -0,0 +1,51 @@
+// events.rs in-process bus of storage mutations, so integrations subscribe to writes instead of being called by every writer.
+
+use std::sync::Arc;
+
+use tokio::sync::broadcast;
+
+use super::TenantId;
+use crate::forex::entity::{Rates, RatesResponse};
+
+/// Mutation of storage, published once it's written.
+#[derive(Debug, Clone)]
+pub enum StorageEvent {
+    LatestInserted(Arc<RatesResponse<Rates>>),
+    HistoricalInserted(Arc<RatesResponse<Rates>>),
+
+    /// rates of existing historical rates changed, e.g. patched by other sources.
+    HistoricalUpdated(Arc<RatesResponse<Rates>>),
+
+    /// ledger transactions, accounts or journal entries of the tenant are written.
+    LedgerChanged {
+        tenant: Option<TenantId>,
+    },
+}
+
+/// Broadcast of storage events to every subscriber.
+/// Events published without subscribers are dropped, slow subscribers miss the oldest ones once capacity is exceeded.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<StorageEvent>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    pub fn publish(&self, event: StorageEvent) {
+        // only fails without subscribers
+        let _ = self.sender.send(event);
+    }
+
+    /// receive events published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<StorageEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn subscribers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
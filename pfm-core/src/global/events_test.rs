@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+use crate::forex::entity::{Rates, RatesResponse};
+
+use super::{EventBus, StorageEvent, TenantId};
+
+#[tokio::test]
+async fn test_event_bus() {
+    let bus = EventBus::new(2);
+    // dropped without subscribers
+    bus.publish(StorageEvent::LedgerChanged { tenant: None });
+    assert_eq!(bus.subscribers(), 0);
+
+    let mut first = bus.subscribe();
+    let mut second = bus.clone().subscribe();
+    assert_eq!(bus.subscribers(), 2);
+
+    let rates = RatesResponse::new("events".to_string(), Rates::default());
+    bus.publish(StorageEvent::LatestInserted(Arc::new(rates.clone())));
+    for receiver in [&mut first, &mut second] {
+        match receiver.recv().await.unwrap() {
+            StorageEvent::LatestInserted(received) => assert_eq!(received.id, rates.id),
+            event => panic!("unexpected event {:?}", event),
+        }
+    }
+    assert_eq!(first.try_recv().unwrap_err(), TryRecvError::Empty);
+
+    // slow subscriber misses the oldest events
+    let tenant = TenantId::parse("family").unwrap();
+    for _ in 0..3 {
+        bus.publish(StorageEvent::LedgerChanged {
+            tenant: Some(tenant.clone()),
+        });
+    }
+    assert_eq!(first.recv().await.unwrap_err(), RecvError::Lagged(1));
+    assert!(matches!(
+        first.recv().await.unwrap(),
+        StorageEvent::LedgerChanged { tenant: Some(received) } if received == tenant
+    ));
+}
+
+#[tokio::test]
+async fn test_storage_writes_publish_events() {
+    use chrono::{TimeZone, Utc};
+    use rust_decimal_macros::dec;
+
+    use super::test_support::{self, temp_storage};
+    use crate::forex::{Money, interface::ForexStorage};
+
+    let bus = EventBus::new(16);
+    let (storage, _root) = temp_storage("storage-events");
+    let storage = storage.with_events(bus.clone());
+    let mut events = bus.subscribe();
+    let rates = |day: u32| {
+        let date = Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap();
+        test_support::rates(date, &[Money::IDR(dec!(15500))])
+    };
+
+    let latest = rates(3);
+    storage
+        .insert_latest(latest.data.date, &latest)
+        .await
+        .unwrap();
+    storage
+        .insert_historical_batch(vec![rates(1), rates(2)])
+        .await
+        .unwrap();
+
+    match events.recv().await.unwrap() {
+        StorageEvent::LatestInserted(received) => assert_eq!(received.id, latest.id),
+        event => panic!("unexpected event {:?}", event),
+    }
+    for day in [1, 2] {
+        match events.recv().await.unwrap() {
+            StorageEvent::HistoricalInserted(received) => {
+                assert_eq!(received.data.date, rates(day).data.date)
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+    }
+    assert_eq!(events.try_recv().unwrap_err(), TryRecvError::Empty);
+}
@@ -1,10 +1,52 @@
+mod build_info;
+pub use build_info::{BuildInfo, STORAGE_SCHEMA_VERSION};
+#[cfg(test)]
+mod build_info_test;
+
+mod clock;
+pub use clock::{Clock, MockClock, SystemClock};
+#[cfg(test)]
+mod clock_test;
+
 mod config;
-pub use config::{config, Config};
+pub use config::{
+    Config, HistoricalMode, LatestLayout, LatestRotation, StoragePermissions, config,
+};
+#[cfg(test)]
+mod config_test;
 
 pub mod constants;
 
+mod events;
+pub use events::{EventBus, StorageEvent};
+#[cfg(test)]
+mod events_test;
+
 mod http_client;
 pub use http_client::http_client;
 
+mod secrets;
+pub use secrets::{
+    EnvSecrets, FileSecrets, KeyringSecrets, SECRET_FOREX_CURRENCY_API_KEY,
+    SECRET_FOREX_CURRENCYBEACON_API_KEY, SECRET_FOREX_OPEN_EXCHANGE_API_KEY,
+    SECRET_FOREX_TWELVEDATA_API_KEY, SECRET_INTERNAL_SECRET, SECRET_NAMES, Secret, SecretProvider,
+    SecretStore, SecretsProviderKind, VaultSecrets, init_secrets, secrets,
+};
+#[cfg(test)]
+mod secrets_test;
+
 mod storage_fs;
-pub use storage_fs::{storage_fs, StorageFS};
+pub use storage_fs::{
+    StorageFS, check_storage_root, check_storage_schema, client_storage_fs, storage_fs,
+    storage_fs_at, validate_storage,
+};
+#[cfg(test)]
+mod storage_fs_test;
+
+mod tenant;
+pub use tenant::{TenantId, TenantScoped};
+#[cfg(test)]
+mod tenant_test;
+
+#[cfg(test)]
+pub(crate) mod test_support;
@@ -0,0 +1,393 @@
+// secrets.rs API keys of 3rd party providers resolved from pluggable secret providers at startup,
+// e.g. env config, files mounted by docker/kubernetes, OS keyring or HashiCorp Vault.
+// resolved secrets are shared by their holders, so refreshed values are used without restarting.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use serde::Deserialize;
+use tokio_util::sync::CancellationToken;
+
+/// API key of currencyapi.com
+pub const SECRET_FOREX_CURRENCY_API_KEY: &str = "forex_currency_api_key";
+
+/// API key of openexchangerates.org
+pub const SECRET_FOREX_OPEN_EXCHANGE_API_KEY: &str = "forex_open_exchange_api_key";
+
+/// API key of currencybeacon.com
+pub const SECRET_FOREX_CURRENCYBEACON_API_KEY: &str = "forex_currencybeacon_api_key";
+
+/// API key of twelvedata.com
+pub const SECRET_FOREX_TWELVEDATA_API_KEY: &str = "forex_twelvedata_api_key";
+
+/// shared secret of internal requests between pfm-cron and pfm-http
+pub const SECRET_INTERNAL_SECRET: &str = "internal_secret";
+
+/// secrets resolved on startup, named after their config fields.
+pub const SECRET_NAMES: &[&str] = &[
+    SECRET_FOREX_CURRENCY_API_KEY,
+    SECRET_FOREX_OPEN_EXCHANGE_API_KEY,
+    SECRET_FOREX_CURRENCYBEACON_API_KEY,
+    SECRET_FOREX_TWELVEDATA_API_KEY,
+    SECRET_INTERNAL_SECRET,
+];
+
+/// Get global secrets resolved from configured secret provider.
+pub fn secrets() -> &'static SecretStore {
+    &SECRETS
+}
+
+/// resolve secrets from configured provider, then keep refreshing them every
+/// `secrets_refresh_secs` until cancelled if configured, so rotated keys are used without restarting.
+pub async fn init_secrets(cancel: CancellationToken) -> Result<()> {
+    let store = secrets();
+    store.refresh().await?;
+    tracing::info!("global: resolved secrets from {}", store.provider.name());
+
+    let refresh_secs = super::config().secrets_refresh_secs;
+    if refresh_secs > 0 {
+        tokio::spawn(store.refresh_every(Duration::from_secs(refresh_secs), cancel));
+    }
+
+    Ok(())
+}
+
+static SECRETS: LazyLock<SecretStore> = LazyLock::new(|| SecretStore::new(init_secret_provider()));
+
+fn init_secret_provider() -> Box<dyn SecretProvider> {
+    let cfg = super::config();
+    match cfg.secrets_provider {
+        SecretsProviderKind::Env => Box::new(EnvSecrets),
+        SecretsProviderKind::File => Box::new(FileSecrets::new(&cfg.secrets_dir)),
+        SecretsProviderKind::Keyring => Box::new(KeyringSecrets::new(&cfg.secrets_keyring_service)),
+        SecretsProviderKind::Vault => Box::new(VaultSecrets::new(
+            &cfg.vault_addr,
+            &cfg.vault_token,
+            &cfg.vault_path,
+            super::http_client(),
+        )),
+    }
+}
+
+/// Where secrets are resolved from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretsProviderKind {
+    /// config fields, e.g. CORE_FOREX_CURRENCY_API_KEY env var.
+    #[default]
+    Env,
+
+    /// file per secret inside secrets directory.
+    File,
+
+    /// OS keyring, macOS keychain or secret service of linux desktops.
+    Keyring,
+
+    /// HashiCorp Vault KV v2 secret.
+    Vault,
+}
+
+/// Secret value shared by its holders, updated in place when rotated.
+/// Debug output never shows the value.
+#[derive(Clone, Default)]
+pub struct Secret(Arc<RwLock<String>>);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(Arc::new(RwLock::new(value.into())))
+    }
+
+    /// current value, read on every use so rotated value is picked up.
+    pub fn expose(&self) -> String {
+        self.0.read().map(|value| value.clone()).unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.read().map(|value| value.is_empty()).unwrap_or(true)
+    }
+
+    /// replace the value, returns whether it changed.
+    fn set(&self, value: String) -> bool {
+        let Ok(mut current) = self.0.write() else {
+            return false;
+        };
+        if *current == value {
+            return false;
+        }
+        *current = value;
+        true
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(***)")
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+#[async_trait]
+pub trait SecretProvider: Send + Sync {
+    /// name of the provider, for logs and errors.
+    fn name(&self) -> &'static str;
+
+    /// value of the secret, none if the provider doesn't have it.
+    async fn get(&self, name: &str) -> Result<Option<String>>;
+}
+
+/// Secrets of config, set from env vars or dev.env for local development.
+pub struct EnvSecrets;
+
+#[async_trait]
+impl SecretProvider for EnvSecrets {
+    fn name(&self) -> &'static str {
+        "env"
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<String>> {
+        Ok(config_value(name).map(str::to_string))
+    }
+}
+
+/// value of config field of the secret, none if empty or unknown.
+fn config_value(name: &str) -> Option<&'static str> {
+    let cfg = super::config();
+    let value = match name {
+        SECRET_FOREX_CURRENCY_API_KEY => &cfg.forex_currency_api_key,
+        SECRET_FOREX_OPEN_EXCHANGE_API_KEY => &cfg.forex_open_exchange_api_key,
+        SECRET_FOREX_CURRENCYBEACON_API_KEY => &cfg.forex_currencybeacon_api_key,
+        SECRET_FOREX_TWELVEDATA_API_KEY => &cfg.forex_twelvedata_api_key,
+        SECRET_INTERNAL_SECRET => &cfg.internal_secret,
+        _ => return None,
+    };
+
+    (!value.is_empty()).then_some(value.as_str())
+}
+
+/// Secrets in files named by secret names inside a directory, e.g. docker secrets at /run/secrets.
+pub struct FileSecrets {
+    dir: PathBuf,
+}
+
+impl FileSecrets {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl SecretProvider for FileSecrets {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<String>> {
+        let path = self.dir.join(name);
+        let value = match tokio::fs::read_to_string(&path).await {
+            Ok(value) => value,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err).with_context(|| format!("reading secret file {}", path.display()));
+            }
+        };
+        let value = value.trim_end_matches(['\r', '\n']);
+
+        Ok((!value.is_empty()).then(|| value.to_string()))
+    }
+}
+
+/// Secrets stored in OS keyring under the service, with secret names as accounts.
+/// uses `security` on macOS and `secret-tool` of libsecret elsewhere.
+pub struct KeyringSecrets {
+    service: String,
+}
+
+impl KeyringSecrets {
+    pub fn new(service: &str) -> Self {
+        Self {
+            service: service.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl SecretProvider for KeyringSecrets {
+    fn name(&self) -> &'static str {
+        "keyring"
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<String>> {
+        let mut command = if cfg!(target_os = "macos") {
+            let mut command = tokio::process::Command::new("security");
+            command.args([
+                "find-generic-password",
+                "-s",
+                &self.service,
+                "-a",
+                name,
+                "-w",
+            ]);
+            command
+        } else {
+            let mut command = tokio::process::Command::new("secret-tool");
+            command.args(["lookup", "service", &self.service, "account", name]);
+            command
+        };
+        let output = command.output().await.context("running keyring command")?;
+        // both commands exit with error when the item doesn't exist
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let value = String::from_utf8(output.stdout).context("keyring secret is not utf-8")?;
+        let value = value.trim_end_matches(['\r', '\n']);
+
+        Ok((!value.is_empty()).then(|| value.to_string()))
+    }
+}
+
+/// Secrets as keys of a HashiCorp Vault KV v2 secret, e.g. path `secret/data/pfm`.
+pub struct VaultSecrets {
+    addr: String,
+    token: Secret,
+    path: String,
+    client: reqwest::Client,
+}
+
+impl VaultSecrets {
+    pub fn new(addr: &str, token: &str, path: &str, client: reqwest::Client) -> Self {
+        Self {
+            addr: addr.trim_end_matches('/').to_string(),
+            token: Secret::new(token),
+            path: path.trim_matches('/').to_string(),
+            client,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct VaultResponse {
+    data: VaultData,
+}
+
+#[derive(Deserialize)]
+struct VaultData {
+    data: HashMap<String, String>,
+}
+
+#[async_trait]
+impl SecretProvider for VaultSecrets {
+    fn name(&self) -> &'static str {
+        "vault"
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<String>> {
+        let url = format!("{}/v1/{}", self.addr, self.path);
+        let resp = self
+            .client
+            .get(&url)
+            .header("X-Vault-Token", self.token.expose())
+            .send()
+            .await
+            .context("invoking vault")?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            return Err(anyhow!("vault responded {} for {}", resp.status(), url));
+        }
+        let mut secret: VaultResponse = resp.json().await.context("parsing vault response")?;
+
+        Ok(secret
+            .data
+            .data
+            .remove(name)
+            .filter(|value| !value.is_empty()))
+    }
+}
+
+/// Secrets resolved from a provider, handed out as shared [`Secret`]s.
+/// Secrets not resolved yet, or missing from the provider, fall back to their config values.
+pub struct SecretStore {
+    provider: Box<dyn SecretProvider>,
+    secrets: RwLock<HashMap<String, Secret>>,
+}
+
+impl SecretStore {
+    pub fn new(provider: Box<dyn SecretProvider>) -> Self {
+        Self {
+            provider,
+            secrets: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// secret of the name, shared with other holders of the same name.
+    pub fn get(&self, name: &str) -> Secret {
+        if let Some(secret) = self.secrets.read().ok().and_then(|s| s.get(name).cloned()) {
+            return secret;
+        }
+
+        let Ok(mut secrets) = self.secrets.write() else {
+            return Secret::new(config_value(name).unwrap_or_default());
+        };
+        secrets
+            .entry(name.to_string())
+            .or_insert_with(|| Secret::new(config_value(name).unwrap_or_default()))
+            .clone()
+    }
+
+    /// resolve known secrets and those handed out from the provider,
+    /// returning names of secrets whose values changed, e.g. rotated.
+    pub async fn refresh(&self) -> Result<Vec<String>> {
+        let mut names: Vec<String> = SECRET_NAMES.iter().map(|name| name.to_string()).collect();
+        if let Ok(secrets) = self.secrets.read() {
+            names.extend(
+                secrets
+                    .keys()
+                    .filter(|name| !SECRET_NAMES.contains(&name.as_str()))
+                    .cloned(),
+            );
+        }
+
+        let mut changed = vec![];
+        for name in names {
+            let value = self.provider.get(&name).await.with_context(|| {
+                format!("resolving secret {} from {}", name, self.provider.name())
+            })?;
+            let Some(value) = value else {
+                continue;
+            };
+            if self.get(&name).set(value) {
+                changed.push(name);
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// refresh secrets every interval until cancelled, failures keep previous values.
+    pub async fn refresh_every(&self, interval: Duration, cancel: CancellationToken) {
+        loop {
+            tokio::select! {
+                _ = cancel.cancelled() => return,
+                _ = tokio::time::sleep(interval) => {}
+            }
+            match self.refresh().await {
+                Ok(changed) if !changed.is_empty() => {
+                    tracing::info!("global: rotated secrets {}", changed.join(", "))
+                }
+                Ok(_) => {}
+                Err(err) => tracing::error!("global: failed refreshing secrets: {:#}", err),
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::{FileSecrets, Secret, SecretProvider, SecretStore};
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pfm-secrets-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// provider of secrets in a map, changed by tests to rotate them.
+#[derive(Clone, Default)]
+struct MapSecrets(Arc<Mutex<HashMap<String, String>>>);
+
+impl MapSecrets {
+    fn set(&self, name: &str, value: &str) {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), value.to_string());
+    }
+}
+
+#[async_trait]
+impl SecretProvider for MapSecrets {
+    fn name(&self) -> &'static str {
+        "map"
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<String>> {
+        Ok(self.0.lock().unwrap().get(name).cloned())
+    }
+}
+
+#[tokio::test]
+async fn test_file_secrets() {
+    let dir = test_dir("file");
+    std::fs::write(dir.join("some_api_key"), "abc123\n").unwrap();
+    std::fs::write(dir.join("empty_api_key"), "\n").unwrap();
+    let provider = FileSecrets::new(&dir);
+
+    assert_eq!(
+        provider.get("some_api_key").await.unwrap().as_deref(),
+        Some("abc123")
+    );
+    assert!(provider.get("empty_api_key").await.unwrap().is_none());
+    assert!(provider.get("missing_api_key").await.unwrap().is_none());
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[tokio::test]
+async fn test_secret_store_refresh_rotates_shared_secret() {
+    let provider = MapSecrets::default();
+    let store = SecretStore::new(Box::new(provider.clone()));
+
+    // not resolved yet and unknown to config
+    let secret = store.get("some_api_key");
+    assert!(secret.is_empty());
+
+    provider.set("some_api_key", "first");
+    let changed = store.refresh().await.unwrap();
+    assert_eq!(changed, vec!["some_api_key".to_string()]);
+    // handed out before refresh, sees the resolved value
+    assert_eq!(secret.expose(), "first");
+    assert_eq!(store.get("some_api_key").expose(), "first");
+
+    // unchanged values are not reported
+    assert!(store.refresh().await.unwrap().is_empty());
+
+    provider.set("some_api_key", "second");
+    let changed = store.refresh().await.unwrap();
+    assert_eq!(changed, vec!["some_api_key".to_string()]);
+    assert_eq!(secret.expose(), "second");
+}
+
+#[test]
+fn test_secret_debug_redacted() {
+    let secret = Secret::from("abc123");
+    let debug = format!("{:?}", secret);
+
+    assert!(!debug.contains("abc123"));
+    assert_eq!(secret.expose(), "abc123");
+}
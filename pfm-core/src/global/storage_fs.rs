@@ -1,5 +1,7 @@
 use anyhow::Context;
 use anyhow::Result;
+use anyhow::bail;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::LazyLock;
@@ -7,6 +9,8 @@ use tokio::sync::RwLock;
 
 use pfm_utils::config_util;
 
+use super::STORAGE_SCHEMA_VERSION;
+
 /// Get instantiated global storage filesystem object for SERVER.
 pub fn storage_fs() -> StorageFS {
     STORAGE_FS.clone()
@@ -15,13 +19,35 @@ pub fn storage_fs() -> StorageFS {
 static STORAGE_FS: LazyLock<StorageFS> =
     LazyLock::new(|| init_storage_fs().expect("global init storage fs"));
 
-const STORAGE_FS_PERMISSION: u32 = 0o750;
+/// Get instantiated global storage filesystem object for CLIENT, e.g. goals and watchlists of this device.
+/// Same as storage of SERVER when client storage root is not configured.
+pub fn client_storage_fs() -> StorageFS {
+    CLIENT_STORAGE_FS.clone()
+}
+
+static CLIENT_STORAGE_FS: LazyLock<StorageFS> = LazyLock::new(|| match client_storage_dir_path() {
+    Some(root) => storage_fs_at(root).expect("global init client storage fs"),
+    None => storage_fs(),
+});
+
 const STORAGE_FS_LATEST_DIR_NAME: &str = "latest";
 const STORAGE_FS_HISTORICAL_DIR_NAME: &str = "historical";
+const STORAGE_FS_METADATA_DIR_NAME: &str = "metadata";
+
+/// written and removed to check storage root is writable.
+const STORAGE_FS_PROBE_FILENAME: &str = ".pfm-probe";
 
-/// Directory for server-side storage.
+/// layout version of stored files inside metadata directory, see STORAGE_SCHEMA_VERSION.
+const STORAGE_FS_SCHEMA_FILENAME: &str = "schema-version";
+
+/// Directory for server-side storage, configured root takes precedence.
 /// For local development, using project's workspace root in test_dir/
 static STORAGE_FS_DIR_PATH: LazyLock<PathBuf> = LazyLock::new(|| {
+    let configured = super::config().storage_root.trim();
+    if !configured.is_empty() {
+        return PathBuf::from(configured);
+    }
+
     if cfg!(debug_assertions) {
         let local_dev_path = "test_dir";
         let workspace_dir =
@@ -30,27 +56,16 @@ static STORAGE_FS_DIR_PATH: LazyLock<PathBuf> = LazyLock::new(|| {
         return path;
     }
 
-    #[cfg(target_os = "windows")]
-    {
-        panic!("Sorry, development and server on Windows not supported at the moment.");
-    }
-
-    #[cfg(any(target_os = "linux", target_os = "macos"))]
-    {
-        let default_location = format!(
-            "{}/pfm",
-            dirs::home_dir()
-                .expect("failed initializing production pfm data path")
-                .to_string_lossy()
-                .to_string()
-        );
-        // APP_DATA_PATH is set from env var in prod to determine where pfm data to be stored.
-        // set APP_DATA_PATH to path to pfm, e.g. /home/myuser/pfm, or /Users/myuser/pfm
-        let location = std::env::var("APP_DATA_PATH").unwrap_or(default_location);
-        let dir_name = "pfm-data";
-        let storage_dir_path = PathBuf::from(location).join(dir_name);
-        return storage_dir_path;
-    }
+    let default_location = dirs::home_dir()
+        .expect("failed initializing production pfm data path")
+        .join("pfm");
+    // APP_DATA_PATH is set from env var in prod to determine where pfm data to be stored.
+    // set APP_DATA_PATH to path to pfm, e.g. /home/myuser/pfm, /Users/myuser/pfm, or C:\Users\myuser\pfm
+    let location = std::env::var("APP_DATA_PATH")
+        .map(PathBuf::from)
+        .unwrap_or(default_location);
+    let dir_name = "pfm-data";
+    location.join(dir_name)
 });
 
 /// Alias for ServerFS, Filesystem for storing data at server side.
@@ -61,11 +76,15 @@ pub struct ServerFS {
     root: PathBuf,
     latest: PathBuf,
     historical: PathBuf,
+    metadata: PathBuf,
 }
 
 impl ServerFS {
     pub(crate) fn is_dir(&self) -> bool {
-        self.root.is_dir() && self.latest.is_dir() && self.historical.is_dir()
+        self.root.is_dir()
+            && self.latest.is_dir()
+            && self.historical.is_dir()
+            && self.metadata.is_dir()
     }
 
     pub(crate) fn root(&self) -> &PathBuf {
@@ -79,26 +98,171 @@ impl ServerFS {
     pub(crate) fn historical(&self) -> &PathBuf {
         &self.historical
     }
+
+    /// sidecar metadata of stored data, e.g. provenance of historical rates.
+    pub(crate) fn metadata(&self) -> &PathBuf {
+        &self.metadata
+    }
+}
+
+/// directory for client-side storage, none if it is the same as server-side storage.
+fn client_storage_dir_path() -> Option<PathBuf> {
+    let configured = super::config().client_storage_root.trim();
+    (!configured.is_empty()).then(|| PathBuf::from(configured))
 }
 
 fn init_storage_fs() -> Result<StorageFS, anyhow::Error> {
-    let root_pb = STORAGE_FS_DIR_PATH.clone();
+    storage_fs_at(STORAGE_FS_DIR_PATH.clone())
+}
+
+/// storage fs of another storage root, e.g. storage of another device to sync with.
+pub fn storage_fs_at(root_pb: PathBuf) -> Result<StorageFS, anyhow::Error> {
+    let permission = super::config().storage_permissions.dir_mode();
 
-    let root = config_util::set_root(root_pb, STORAGE_FS_PERMISSION)
+    let root = config_util::set_root(root_pb, permission)
         .context("global: failed initializing storage fs")?;
 
-    let latest = config_util::set_sub_dir(&root, STORAGE_FS_LATEST_DIR_NAME, STORAGE_FS_PERMISSION)
+    let latest = config_util::set_sub_dir(&root, STORAGE_FS_LATEST_DIR_NAME, permission)
         .context("global: failed initializing latest storage fs")?;
 
-    let historical =
-        config_util::set_sub_dir(&root, STORAGE_FS_HISTORICAL_DIR_NAME, STORAGE_FS_PERMISSION)
-            .context("global: failed initializing historical storage fs")?;
+    let historical = config_util::set_sub_dir(&root, STORAGE_FS_HISTORICAL_DIR_NAME, permission)
+        .context("global: failed initializing historical storage fs")?;
+
+    let metadata = config_util::set_sub_dir(&root, STORAGE_FS_METADATA_DIR_NAME, permission)
+        .context("global: failed initializing metadata storage fs")?;
 
     let storage_fs = Arc::new(RwLock::new(ServerFS {
         root,
         latest,
         historical,
+        metadata,
     }));
 
     Ok(storage_fs)
 }
+
+/// check configured storage roots are usable, creating them on first run.
+/// meant to be called on startup, before storage is accessed.
+pub fn validate_storage() -> Result<(), anyhow::Error> {
+    let min_free_bytes = super::config()
+        .storage_min_free_mb
+        .saturating_mul(1024 * 1024);
+
+    check_storage_root(&STORAGE_FS_DIR_PATH, min_free_bytes)
+        .context("global: invalid server storage root")?;
+    check_storage_schema(&STORAGE_FS_DIR_PATH).context("global: invalid server storage schema")?;
+
+    if let Some(root) = client_storage_dir_path() {
+        check_storage_root(&root, min_free_bytes).context("global: invalid client storage root")?;
+        check_storage_schema(&root).context("global: invalid client storage schema")?;
+    }
+
+    Ok(())
+}
+
+/// check storage at the root isn't written with newer layout than this binary reads,
+/// then mark it with layout of this binary, so older binaries refuse it in turn.
+pub fn check_storage_schema(root: &Path) -> Result<(), anyhow::Error> {
+    let path = root
+        .join(STORAGE_FS_METADATA_DIR_NAME)
+        .join(STORAGE_FS_SCHEMA_FILENAME);
+    match std::fs::read_to_string(&path) {
+        Ok(content) => {
+            let version: u32 = content
+                .trim()
+                .parse()
+                .with_context(|| format!("storage schema version {} is invalid", path.display()))?;
+            if version > STORAGE_SCHEMA_VERSION {
+                bail!(
+                    "storage root {} is written with schema v{}, this binary reads up to v{}",
+                    root.display(),
+                    version,
+                    STORAGE_SCHEMA_VERSION
+                );
+            }
+            if version == STORAGE_SCHEMA_VERSION {
+                return Ok(());
+            }
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => {
+            return Err(err).with_context(|| {
+                format!("storage schema version {} cannot be read", path.display())
+            });
+        }
+    }
+
+    let permission = super::config().storage_permissions.dir_mode();
+    config_util::set_sub_dir(
+        &root.to_path_buf(),
+        STORAGE_FS_METADATA_DIR_NAME,
+        permission,
+    )
+    .with_context(|| format!("storage root {} metadata cannot be created", root.display()))?;
+    std::fs::write(&path, STORAGE_SCHEMA_VERSION.to_string()).with_context(|| {
+        format!(
+            "storage schema version {} cannot be written",
+            path.display()
+        )
+    })?;
+
+    Ok(())
+}
+
+/// create storage root with configured permissions if not exists, then check it is writable
+/// and has at least the free bytes, free space is not checked when 0 or not supported by platform.
+pub fn check_storage_root(root: &Path, min_free_bytes: u64) -> Result<(), anyhow::Error> {
+    let permission = super::config().storage_permissions.dir_mode();
+    config_util::set_root(root.to_path_buf(), permission)
+        .with_context(|| format!("storage root {} cannot be created", root.display()))?;
+    if !root.is_dir() {
+        bail!("storage root {} is not a directory", root.display());
+    }
+
+    let probe = root.join(STORAGE_FS_PROBE_FILENAME);
+    std::fs::write(&probe, b"")
+        .and_then(|_| std::fs::remove_file(&probe))
+        .with_context(|| format!("storage root {} is not writable", root.display()))?;
+
+    if min_free_bytes == 0 {
+        return Ok(());
+    }
+    let free_bytes = free_space(root)
+        .with_context(|| format!("storage root {} free space cannot be read", root.display()))?;
+    match free_bytes {
+        Some(free_bytes) if free_bytes < min_free_bytes => bail!(
+            "storage root {} has {} MB free, required at least {} MB",
+            root.display(),
+            free_bytes / (1024 * 1024),
+            min_free_bytes.div_ceil(1024 * 1024)
+        ),
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// bytes available to unprivileged users in filesystem of the path.
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)]
+fn free_space(path: &Path) -> Result<Option<u64>, anyhow::Error> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())?;
+    // SAFETY: statvfs is plain data, zeroed is a valid value.
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: c_path is nul terminated and stat is valid for writes.
+    let ret = unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) };
+    if ret != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+
+    Ok(Some(
+        (stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64),
+    ))
+}
+
+#[cfg(not(unix))]
+fn free_space(_path: &Path) -> Result<Option<u64>, anyhow::Error> {
+    Ok(None)
+}
@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+
+use super::{STORAGE_SCHEMA_VERSION, check_storage_root, check_storage_schema};
+
+fn test_root(name: &str) -> PathBuf {
+    let root =
+        std::env::temp_dir().join(format!("pfm-storage-root-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&root);
+    root
+}
+
+#[test]
+fn test_check_storage_root_creates_missing() {
+    let root = test_root("missing").join("nested");
+
+    check_storage_root(&root, 0).unwrap();
+    assert!(root.is_dir());
+    // probe file is cleaned up
+    assert_eq!(std::fs::read_dir(&root).unwrap().count(), 0);
+
+    // existing root is fine too
+    check_storage_root(&root, 1).unwrap();
+
+    let _ = std::fs::remove_dir_all(root.parent().unwrap());
+}
+
+#[test]
+fn test_check_storage_root_unusable() {
+    let root = test_root("unusable");
+    std::fs::create_dir_all(&root).unwrap();
+    let file = root.join("file");
+    std::fs::write(&file, b"").unwrap();
+
+    let err = check_storage_root(&file.join("sub"), 0).unwrap_err();
+    assert!(err.to_string().contains("cannot be created"), "{:#}", err);
+
+    let err = check_storage_root(&file, 0).unwrap_err();
+    assert!(err.to_string().contains("not a directory"), "{:#}", err);
+
+    let _ = std::fs::remove_dir_all(root);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_check_storage_root_free_space() {
+    let root = test_root("free");
+
+    let err = check_storage_root(&root, u64::MAX).unwrap_err();
+    assert!(err.to_string().contains("MB free"), "{:#}", err);
+
+    let _ = std::fs::remove_dir_all(root);
+}
+
+#[test]
+fn test_check_storage_schema() {
+    let root = test_root("schema");
+    let marker = root.join("metadata").join("schema-version");
+
+    // marked on first run
+    check_storage_schema(&root).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&marker).unwrap(),
+        STORAGE_SCHEMA_VERSION.to_string()
+    );
+
+    // older layout is upgraded
+    std::fs::write(&marker, "1").unwrap();
+    check_storage_schema(&root).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&marker).unwrap(),
+        STORAGE_SCHEMA_VERSION.to_string()
+    );
+
+    // newer layout is refused and kept
+    let newer = (STORAGE_SCHEMA_VERSION + 1).to_string();
+    std::fs::write(&marker, &newer).unwrap();
+    let err = check_storage_schema(&root).unwrap_err();
+    assert!(err.to_string().contains("reads up to"), "{:#}", err);
+    assert_eq!(std::fs::read_to_string(&marker).unwrap(), newer);
+
+    std::fs::write(&marker, "v2").unwrap();
+    assert!(check_storage_schema(&root).is_err());
+
+    let _ = std::fs::remove_dir_all(root);
+}
@@ -0,0 +1,51 @@
+// tenant.rs identify tenants whose user data is isolated from each other inside shared server storage.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// max length of tenant id
+const TENANT_ID_MAX_LEN: usize = 64;
+
+/// Identifier of a tenant, e.g. a user or a family served by the same deployment.
+/// Only ascii letters, digits, `-` and `_` are allowed, so it's safe as directory name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String")]
+pub struct TenantId(String);
+
+impl TenantId {
+    pub fn parse(id: &str) -> Option<Self> {
+        let valid = !id.is_empty()
+            && id.len() <= TENANT_ID_MAX_LEN
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+        valid.then(|| Self(id.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for TenantId {
+    type Error = String;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        Self::parse(&id).ok_or_else(|| format!("invalid tenant id {}", id))
+    }
+}
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Storage whose user data(goals, watchlists, ledger and digests) can be scoped to a tenant.
+/// Forex rates are reference data shared by all tenants.
+pub trait TenantScoped {
+    /// same storage, reading and writing user data of the tenant only.
+    fn for_tenant(&self, tenant: &TenantId) -> Self;
+}
@@ -0,0 +1,11 @@
+use super::TenantId;
+
+#[test]
+fn test_tenant_id_parse() {
+    assert_eq!(TenantId::parse("family-1").unwrap().as_str(), "family-1");
+    assert_eq!(TenantId::parse("user_A").unwrap().to_string(), "user_A");
+    assert!(TenantId::parse("").is_none());
+    assert!(TenantId::parse("../etc").is_none());
+    assert!(TenantId::parse("a/b").is_none());
+    assert!(TenantId::parse(&"a".repeat(65)).is_none());
+}
@@ -0,0 +1,76 @@
+// test_support.rs helpers shared by unit tests of pfm-core, e.g. storage at temporary root and rates to store.
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use crate::forex::{
+    Currency, Money,
+    entity::{Rates, RatesData, RatesResponse},
+};
+use crate::forex_impl::forex_storage::ForexStorageImpl;
+
+/// temporary storage root, removed with its contents when dropped.
+pub(crate) struct TempRoot(PathBuf);
+
+impl TempRoot {
+    /// empty root unique to the name within this test process.
+    pub(crate) fn new(name: &str) -> Self {
+        let root = std::env::temp_dir().join(format!("pfm-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        Self(root)
+    }
+}
+
+impl Deref for TempRoot {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempRoot {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempRoot {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// storage at temporary root unique to the name, the root is removed when dropped.
+pub(crate) fn temp_storage(name: &str) -> (ForexStorageImpl, TempRoot) {
+    let root = TempRoot::new(name);
+    let storage = ForexStorageImpl::new(super::storage_fs_at(root.to_path_buf()).unwrap());
+
+    (storage, root)
+}
+
+/// USD based rates of the date having the rates, rate of USD is 1.
+pub(crate) fn rates(date: DateTime<Utc>, rates: &[Money]) -> RatesResponse<Rates> {
+    let mut data = RatesData::default();
+    data.set_rate(Money::new_money(Currency::USD, Decimal::ONE));
+    for money in rates {
+        data.set_rate(*money);
+    }
+
+    RatesResponse {
+        id: Uuid::new_v4(),
+        source: "test".to_string(),
+        poll_date: date,
+        data: Rates {
+            date,
+            base: Currency::USD,
+            rates: data,
+        },
+        error: None,
+        provenance: vec![],
+    }
+}
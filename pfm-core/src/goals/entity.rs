@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::forex::Money;
+
+/// Savings target to reach before deadline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Goal {
+    pub id: Uuid,
+    pub name: String,
+    pub target: Money,
+    pub deadline: DateTime<Utc>,
+
+    /// contributions in any currency, valued in target currency at current rates.
+    pub contributions: Vec<Contribution>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Goal {
+    pub(crate) fn new(name: &str, target: Money, deadline: DateTime<Utc>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            target,
+            deadline,
+            contributions: vec![],
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// Money put aside for a goal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Contribution {
+    pub id: Uuid,
+    pub date: DateTime<Utc>,
+    pub amount: Money,
+    pub note: Option<String>,
+}
+
+impl Contribution {
+    pub(crate) fn new(date: DateTime<Utc>, amount: Money, note: Option<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            date,
+            amount,
+            note,
+        }
+    }
+}
+
+/// Progress of goal valued in target currency at rates of rates_date.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoalProgress {
+    pub goal: Goal,
+    pub rates_date: DateTime<Utc>,
+    pub saved: Money,
+    pub remaining: Money,
+
+    /// saved percentage of target, 0 to 100.
+    pub percent: Decimal,
+
+    /// started months left until deadline, 0 if deadline passed.
+    pub months_left: u32,
+
+    /// contribution needed each month to reach target by deadline, the whole remaining if no month left.
+    pub required_monthly: Money,
+}
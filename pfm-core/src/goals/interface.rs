@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::entity::Goal;
+use crate::forex::ForexResult;
+
+#[async_trait]
+pub trait GoalsStorage {
+    /// insert new goal or replace existing one with the same id.
+    async fn upsert_goal(&self, goal: &Goal) -> ForexResult<()>;
+
+    /// get goal by its id.
+    async fn get_goal(&self, id: Uuid) -> ForexResult<Goal>;
+
+    /// get all goals ordered by deadline.
+    async fn get_goals(&self) -> ForexResult<Vec<Goal>>;
+
+    /// delete goal by its id.
+    async fn delete_goal(&self, id: Uuid) -> ForexResult<()>;
+}
@@ -0,0 +1,9 @@
+// goals savings targets in any currency, tracked with contributions and valued at current rates.
+
+pub mod entity;
+
+pub mod interface;
+
+pub(crate) mod service;
+#[cfg(test)]
+mod service_test;
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+
+use chrono::{DateTime, Months, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use super::{
+    entity::{Contribution, Goal, GoalProgress},
+    interface::GoalsStorage,
+};
+use crate::forex::{
+    ForexError, ForexResult, Money, entity::Rates, interface::ForexStorage,
+    service as forex_service,
+};
+
+/// serializes read-modify-write of each goal, e.g. concurrent contributions.
+static GOAL_LOCKS: LazyLock<std::sync::Mutex<HashMap<Uuid, Arc<Mutex<()>>>>> =
+    LazyLock::new(Default::default);
+
+fn goal_lock(id: Uuid) -> Arc<Mutex<()>> {
+    GOAL_LOCKS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .entry(id)
+        .or_default()
+        .clone()
+}
+
+pub async fn create_goal<GS>(
+    storage: &GS,
+    name: &str,
+    target: Money,
+    deadline: DateTime<Utc>,
+) -> ForexResult<Goal>
+where
+    GS: GoalsStorage,
+{
+    validate_goal(name, &target)?;
+    validate_deadline(deadline)?;
+    let goal = Goal::new(name.trim(), target, deadline);
+    storage.upsert_goal(&goal).await?;
+
+    Ok(goal)
+}
+
+/// update goal with provided fields, the rest are kept. Goals past their deadline can still be updated.
+pub async fn update_goal<GS>(
+    storage: &GS,
+    id: Uuid,
+    name: Option<String>,
+    target: Option<Money>,
+    deadline: Option<DateTime<Utc>>,
+) -> ForexResult<Goal>
+where
+    GS: GoalsStorage,
+{
+    let lock = goal_lock(id);
+    let _guard = lock.lock().await;
+    let mut goal = storage.get_goal(id).await?;
+    if let Some(name) = name {
+        goal.name = name.trim().to_string();
+    }
+    if let Some(target) = target {
+        goal.target = target;
+    }
+    if let Some(deadline) = deadline {
+        validate_deadline(deadline)?;
+        goal.deadline = deadline;
+    }
+    validate_goal(&goal.name, &goal.target)?;
+    goal.updated_at = Utc::now();
+    storage.upsert_goal(&goal).await?;
+
+    Ok(goal)
+}
+
+pub async fn delete_goal<GS>(storage: &GS, id: Uuid) -> ForexResult<()>
+where
+    GS: GoalsStorage,
+{
+    storage.get_goal(id).await?;
+    storage.delete_goal(id).await
+}
+
+pub async fn get_goals<GS>(storage: &GS) -> ForexResult<Vec<Goal>>
+where
+    GS: GoalsStorage,
+{
+    storage.get_goals().await
+}
+
+pub async fn add_contribution<GS>(
+    storage: &GS,
+    goal_id: Uuid,
+    amount: Money,
+    date: DateTime<Utc>,
+    note: Option<String>,
+) -> ForexResult<Goal>
+where
+    GS: GoalsStorage,
+{
+    if amount.amount() <= Decimal::ZERO {
+        return Err(ForexError::client_error(
+            "contribution amount must be positive",
+        ));
+    }
+    let lock = goal_lock(goal_id);
+    let _guard = lock.lock().await;
+    let mut goal = storage.get_goal(goal_id).await?;
+    goal.contributions
+        .push(Contribution::new(date, amount, note));
+    goal.contributions
+        .sort_by_key(|contribution| contribution.date);
+    goal.updated_at = Utc::now();
+    storage.upsert_goal(&goal).await?;
+
+    Ok(goal)
+}
+
+pub async fn remove_contribution<GS>(
+    storage: &GS,
+    goal_id: Uuid,
+    contribution_id: Uuid,
+) -> ForexResult<Goal>
+where
+    GS: GoalsStorage,
+{
+    let lock = goal_lock(goal_id);
+    let _guard = lock.lock().await;
+    let mut goal = storage.get_goal(goal_id).await?;
+    let count = goal.contributions.len();
+    goal.contributions
+        .retain(|contribution| contribution.id != contribution_id);
+    if goal.contributions.len() == count {
+        return Err(ForexError::client_error(
+            format!("contribution {} not found", contribution_id).as_str(),
+        ));
+    }
+    goal.updated_at = Utc::now();
+    storage.upsert_goal(&goal).await?;
+
+    Ok(goal)
+}
+
+/// progress of goal valued at latest rates.
+pub async fn get_goal_progress<GS, FS>(
+    goals_storage: &GS,
+    forex_storage: &FS,
+    id: Uuid,
+) -> ForexResult<GoalProgress>
+where
+    GS: GoalsStorage,
+    FS: ForexStorage,
+{
+    let goal = goals_storage.get_goal(id).await?;
+    let latest = forex_storage.get_latest().await?;
+    if let Some(err) = latest.error {
+        return Err(ForexError::internal_error(err.as_str()));
+    }
+
+    progress(goal, &latest.data, Utc::now())
+}
+
+/// value contributions of goal in its target currency with the rates, and contribution required monthly until deadline.
+pub fn progress(goal: Goal, rates: &Rates, now: DateTime<Utc>) -> ForexResult<GoalProgress> {
+    let target_currency = goal.target.currency();
+    let mut saved = Decimal::ZERO;
+    for contribution in &goal.contributions {
+        let rate =
+            forex_service::pair_rate(rates, contribution.amount.currency(), target_currency)?;
+        saved += contribution.amount.amount() * rate;
+    }
+
+    let target = goal.target.amount();
+    let remaining = (target - saved).max(Decimal::ZERO);
+    let percent = if target.is_zero() {
+        dec!(100)
+    } else {
+        (saved * dec!(100) / target).min(dec!(100)).round_dp(2)
+    };
+    let months_left = months_until(now, goal.deadline);
+    let required_monthly = match months_left {
+        0 => remaining,
+        months => remaining / Decimal::from(months),
+    };
+
+    Ok(GoalProgress {
+        rates_date: rates.date,
+        saved: Money::new_money(target_currency, saved),
+        remaining: Money::new_money(target_currency, remaining),
+        percent,
+        months_left,
+        required_monthly: Money::new_money(target_currency, required_monthly),
+        goal,
+    })
+}
+
+/// number of started months from now until deadline.
+fn months_until(now: DateTime<Utc>, deadline: DateTime<Utc>) -> u32 {
+    let mut months = 0;
+    while let Some(date) = now.checked_add_months(Months::new(months))
+        && date < deadline
+    {
+        months += 1;
+    }
+
+    months
+}
+
+fn validate_goal(name: &str, target: &Money) -> ForexResult<()> {
+    if name.trim().is_empty() {
+        return Err(ForexError::client_error("goal name must not be empty"));
+    }
+    if target.amount() <= Decimal::ZERO {
+        return Err(ForexError::client_error("goal target must be positive"));
+    }
+
+    Ok(())
+}
+
+/// new deadline of a goal, existing ones may have passed.
+fn validate_deadline(deadline: DateTime<Utc>) -> ForexResult<()> {
+    if deadline <= Utc::now() {
+        return Err(ForexError::client_error(
+            "goal deadline must be in the future",
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use chrono::{TimeZone, Utc};
+use rust_decimal_macros::dec;
+
+use super::{entity::Goal, service::progress};
+use crate::forex::{Money, entity::Rates};
+use crate::global::test_support;
+
+fn rates() -> Rates {
+    let date = Utc.with_ymd_and_hms(2025, 3, 4, 0, 0, 0).unwrap();
+    test_support::rates(date, &[Money::IDR(dec!(16000))]).data
+}
+
+#[test]
+fn test_goal_progress() {
+    let now = Utc.with_ymd_and_hms(2025, 3, 4, 0, 0, 0).unwrap();
+    let deadline = Utc.with_ymd_and_hms(2025, 12, 31, 0, 0, 0).unwrap();
+    let mut goal = Goal::new("emergency fund", Money::IDR(dec!(100000000)), deadline);
+    let date = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+    goal.contributions.push(super::entity::Contribution::new(
+        date,
+        Money::IDR(dec!(20000000)),
+        None,
+    ));
+    goal.contributions.push(super::entity::Contribution::new(
+        date,
+        Money::USD(dec!(1000)),
+        Some("bonus".to_string()),
+    ));
+
+    let ret = progress(goal.clone(), &rates(), now).unwrap();
+    assert_eq!(ret.saved, Money::IDR(dec!(36000000)));
+    assert_eq!(ret.remaining, Money::IDR(dec!(64000000)));
+    assert_eq!(ret.percent, dec!(36));
+    assert_eq!(ret.months_left, 10);
+    assert_eq!(ret.required_monthly, Money::IDR(dec!(6400000)));
+
+    // deadline passed, whole remaining is required
+    let ret = progress(goal, &rates(), deadline).unwrap();
+    assert_eq!(ret.months_left, 0);
+    assert_eq!(ret.required_monthly, Money::IDR(dec!(64000000)));
+}
+
+#[tokio::test]
+async fn test_update_goal_past_deadline_and_concurrent_contributions() {
+    use std::sync::Arc;
+
+    use super::interface::GoalsStorage;
+    use super::service::{add_contribution, update_goal};
+
+    let (storage, _root) = test_support::temp_storage("goals");
+    let storage = Arc::new(storage);
+    let deadline = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let goal = Goal::new("laptop", Money::USD(dec!(2000)), deadline);
+    storage.upsert_goal(&goal).await.unwrap();
+
+    let updated = update_goal(
+        &*storage,
+        goal.id,
+        Some("new laptop".to_string()),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(updated.name, "new laptop");
+    assert_eq!(updated.deadline, deadline);
+    assert!(
+        update_goal(&*storage, goal.id, None, None, Some(deadline))
+            .await
+            .is_err()
+    );
+
+    let tasks: Vec<_> = (0..8)
+        .map(|_| {
+            let storage = storage.clone();
+            tokio::spawn(async move {
+                add_contribution(&*storage, goal.id, Money::USD(dec!(10)), Utc::now(), None).await
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap().unwrap();
+    }
+    assert_eq!(
+        storage.get_goal(goal.id).await.unwrap().contributions.len(),
+        8
+    );
+}
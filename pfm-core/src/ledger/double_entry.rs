@@ -0,0 +1,178 @@
+// double_entry.rs optional double-entry accounting on top of ledger: balanced journal entries and reports.
+
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+
+use super::entity::{
+    Account, AccountBalance, AccountKind, BalanceSheet, JournalEntry, TrialBalance,
+};
+use crate::forex::{Currency, ForexError, ForexResult, Money, entity::Rates, service};
+
+/// entry must have at least 2 postings to existing accounts in their currencies,
+/// and postings of each currency must sum exactly to zero.
+/// exchanges between currencies are balanced through an account in each currency, e.g. equity:exchange:usd.
+pub fn validate_entry(entry: &JournalEntry, accounts: &[Account]) -> ForexResult<()> {
+    if entry.postings.len() < 2 {
+        return Err(ForexError::client_error(
+            "journal entry must have at least 2 postings",
+        ));
+    }
+    let mut sums: HashMap<Currency, Decimal> = HashMap::new();
+    for posting in &entry.postings {
+        let account = find_account(accounts, &posting.account)?;
+        if account.currency != posting.amount.currency() {
+            return Err(ForexError::client_error(
+                format!(
+                    "posting to {} must be in {}",
+                    account.name,
+                    account.currency.code()
+                )
+                .as_str(),
+            ));
+        }
+        *sums.entry(account.currency).or_default() += posting.amount.amount();
+    }
+    if let Some((currency, sum)) = sums.iter().find(|(_, sum)| !sum.is_zero()) {
+        return Err(ForexError::client_error(
+            format!("journal entry is unbalanced by {} {}", currency.code(), sum).as_str(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// balances of all accounts from the entries, converted into currency with the rates.
+pub fn trial_balance(
+    accounts: &[Account],
+    entries: &[JournalEntry],
+    currency: Currency,
+    rates: &Rates,
+) -> ForexResult<TrialBalance> {
+    let balances = account_balances(accounts, entries, currency, rates)?;
+    let mut total_debit = Decimal::ZERO;
+    let mut total_credit = Decimal::ZERO;
+    for balance in &balances {
+        let converted = balance.converted.amount();
+        if converted.is_sign_positive() {
+            total_debit += converted;
+        } else {
+            total_credit -= converted;
+        }
+    }
+
+    Ok(TrialBalance {
+        currency,
+        rates_date: rates.date,
+        balances,
+        total_debit: Money::new_money(currency, total_debit),
+        total_credit: Money::new_money(currency, total_credit),
+    })
+}
+
+/// balance sheet from the entries, converted into currency with the rates.
+pub fn balance_sheet(
+    accounts: &[Account],
+    entries: &[JournalEntry],
+    currency: Currency,
+    rates: &Rates,
+) -> ForexResult<BalanceSheet> {
+    let mut assets = vec![];
+    let mut liabilities = vec![];
+    let mut equity = vec![];
+    let mut total_assets = Decimal::ZERO;
+    let mut total_liabilities = Decimal::ZERO;
+    let mut total_equity = Decimal::ZERO;
+    let mut net_income = Decimal::ZERO;
+    for mut balance in account_balances(accounts, entries, currency, rates)? {
+        let converted = balance.converted.amount();
+        match balance.account.kind {
+            AccountKind::Asset => {
+                total_assets += converted;
+                assets.push(balance);
+            }
+            AccountKind::Liability => {
+                total_liabilities -= converted;
+                negate(&mut balance);
+                liabilities.push(balance);
+            }
+            AccountKind::Equity => {
+                total_equity -= converted;
+                negate(&mut balance);
+                equity.push(balance);
+            }
+            // income is credited and expense is debited
+            AccountKind::Income | AccountKind::Expense => net_income -= converted,
+        }
+    }
+
+    Ok(BalanceSheet {
+        currency,
+        rates_date: rates.date,
+        assets,
+        liabilities,
+        equity,
+        total_assets: Money::new_money(currency, total_assets),
+        total_liabilities: Money::new_money(currency, total_liabilities),
+        net_income: Money::new_money(currency, net_income),
+        total_equity: Money::new_money(currency, total_equity + net_income),
+    })
+}
+
+pub(super) fn account_balances(
+    accounts: &[Account],
+    entries: &[JournalEntry],
+    currency: Currency,
+    rates: &Rates,
+) -> ForexResult<Vec<AccountBalance>> {
+    // postings in other currency than their account's, e.g. imported before the account changed currency,
+    // are translated into the account's currency with the rates
+    let mut sums: HashMap<&str, Decimal> = HashMap::new();
+    for posting in entries.iter().flat_map(|entry| &entry.postings) {
+        let amount = match accounts
+            .iter()
+            .find(|account| account.name == posting.account)
+        {
+            Some(account) => convert(rates, &posting.amount, account.currency)?,
+            None => continue,
+        };
+        *sums.entry(posting.account.as_str()).or_default() += amount;
+    }
+
+    let mut balances = Vec::with_capacity(accounts.len());
+    for account in accounts {
+        let balance = Money::new_money(
+            account.currency,
+            sums.get(account.name.as_str()).copied().unwrap_or_default(),
+        );
+        let converted = Money::new_money(currency, convert(rates, &balance, currency)?);
+        balances.push(AccountBalance {
+            account: account.clone(),
+            balance,
+            converted,
+        });
+    }
+
+    Ok(balances)
+}
+
+pub(super) fn convert(rates: &Rates, money: &Money, to: Currency) -> ForexResult<Decimal> {
+    if money.currency() == to {
+        return Ok(money.amount());
+    }
+    let rate = service::pair_rate(rates, money.currency(), to)?;
+
+    Ok(money.amount() * rate)
+}
+
+fn negate(balance: &mut AccountBalance) {
+    balance.balance = Money::new_money(balance.balance.currency(), -balance.balance.amount());
+    balance.converted = Money::new_money(balance.converted.currency(), -balance.converted.amount());
+}
+
+fn find_account<'a>(accounts: &'a [Account], name: &str) -> ForexResult<&'a Account> {
+    accounts
+        .iter()
+        .find(|account| account.name == name)
+        .ok_or_else(|| ForexError::client_error(format!("account {} not found", name).as_str()))
+}
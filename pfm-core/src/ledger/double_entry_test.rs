@@ -0,0 +1,153 @@
+use chrono::{TimeZone, Utc};
+use rust_decimal_macros::dec;
+
+use super::double_entry::{balance_sheet, trial_balance, validate_entry};
+use super::entity::{Account, AccountKind, JournalEntry, Posting};
+use crate::forex::{Currency, Money, entity::Rates};
+use crate::global::test_support;
+
+fn rates() -> Rates {
+    let date = Utc.with_ymd_and_hms(2025, 3, 4, 0, 0, 0).unwrap();
+    test_support::rates(date, &[Money::IDR(dec!(16000))]).data
+}
+
+fn accounts() -> Vec<Account> {
+    let account = |name: &str, kind, currency| Account {
+        name: name.to_string(),
+        kind,
+        currency,
+    };
+    vec![
+        account("assets:bank", AccountKind::Asset, Currency::IDR),
+        account("assets:usd", AccountKind::Asset, Currency::USD),
+        account("liabilities:card", AccountKind::Liability, Currency::IDR),
+        account("equity:opening", AccountKind::Equity, Currency::IDR),
+        account("income:salary", AccountKind::Income, Currency::IDR),
+        account("expenses:food", AccountKind::Expense, Currency::IDR),
+        account("equity:exchange:idr", AccountKind::Equity, Currency::IDR),
+        account("equity:exchange:usd", AccountKind::Equity, Currency::USD),
+    ]
+}
+
+fn entry(postings: &[(&str, Money)]) -> JournalEntry {
+    let date = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+    let postings = postings
+        .iter()
+        .map(|(account, amount)| Posting {
+            account: account.to_string(),
+            amount: *amount,
+        })
+        .collect();
+    JournalEntry::new(date, "test", postings)
+}
+
+#[test]
+fn test_validate_entry() {
+    let accounts = accounts();
+    let balanced = entry(&[
+        ("assets:bank", Money::IDR(dec!(-50000))),
+        ("expenses:food", Money::IDR(dec!(50000))),
+    ]);
+    assert!(validate_entry(&balanced, &accounts).is_ok());
+
+    let unbalanced = entry(&[
+        ("assets:bank", Money::IDR(dec!(-50000))),
+        ("expenses:food", Money::IDR(dec!(40000))),
+    ]);
+    assert!(validate_entry(&unbalanced, &accounts).is_err());
+
+    let unknown_account = entry(&[
+        ("assets:cash", Money::IDR(dec!(-50000))),
+        ("expenses:food", Money::IDR(dec!(50000))),
+    ]);
+    assert!(validate_entry(&unknown_account, &accounts).is_err());
+
+    let wrong_currency = entry(&[
+        ("assets:bank", Money::USD(dec!(-5))),
+        ("assets:usd", Money::USD(dec!(5))),
+    ]);
+    assert!(validate_entry(&wrong_currency, &accounts).is_err());
+
+    // buy usd with idr through exchange accounts, balanced in each currency
+    let exchange = entry(&[
+        ("assets:bank", Money::IDR(dec!(-1610000))),
+        ("equity:exchange:idr", Money::IDR(dec!(1610000))),
+        ("equity:exchange:usd", Money::USD(dec!(-100))),
+        ("assets:usd", Money::USD(dec!(100))),
+    ]);
+    assert!(validate_entry(&exchange, &accounts).is_ok());
+
+    // currencies are never netted against each other, even at rates close enough
+    let exchange_unbalanced = entry(&[
+        ("assets:bank", Money::IDR(dec!(-1600000))),
+        ("assets:usd", Money::USD(dec!(100))),
+    ]);
+    assert!(validate_entry(&exchange_unbalanced, &accounts).is_err());
+
+    let off_by_cent = entry(&[
+        ("assets:bank", Money::IDR(dec!(-1610000))),
+        ("equity:exchange:idr", Money::IDR(dec!(1610000))),
+        ("equity:exchange:usd", Money::USD(dec!(-100))),
+        ("assets:usd", Money::USD(dec!(100.01))),
+    ]);
+    assert!(validate_entry(&off_by_cent, &accounts).is_err());
+}
+
+#[test]
+fn test_reports() {
+    let accounts = accounts();
+    let entries = vec![
+        entry(&[
+            ("assets:bank", Money::IDR(dec!(10000000))),
+            ("equity:opening", Money::IDR(dec!(-10000000))),
+        ]),
+        entry(&[
+            ("assets:bank", Money::IDR(dec!(20000000))),
+            ("income:salary", Money::IDR(dec!(-20000000))),
+        ]),
+        entry(&[
+            ("expenses:food", Money::IDR(dec!(500000))),
+            ("liabilities:card", Money::IDR(dec!(-500000))),
+        ]),
+        entry(&[
+            ("assets:bank", Money::IDR(dec!(-1600000))),
+            ("equity:exchange:idr", Money::IDR(dec!(1600000))),
+            ("equity:exchange:usd", Money::USD(dec!(-100))),
+            ("assets:usd", Money::USD(dec!(100))),
+        ]),
+    ];
+
+    let trial = trial_balance(&accounts, &entries, Currency::IDR, &rates()).unwrap();
+    assert_eq!(trial.total_debit, Money::IDR(dec!(32100000)));
+    assert_eq!(trial.total_credit, Money::IDR(dec!(32100000)));
+
+    let sheet = balance_sheet(&accounts, &entries, Currency::IDR, &rates()).unwrap();
+    assert_eq!(sheet.total_assets, Money::IDR(dec!(30000000)));
+    assert_eq!(sheet.total_liabilities, Money::IDR(dec!(500000)));
+    assert_eq!(sheet.net_income, Money::IDR(dec!(19500000)));
+    assert_eq!(sheet.total_equity, Money::IDR(dec!(29500000)));
+    assert_eq!(sheet.liabilities[0].balance, Money::IDR(dec!(500000)));
+
+    let sheet = balance_sheet(&accounts, &entries, Currency::USD, &rates()).unwrap();
+    assert_eq!(sheet.total_assets.amount().round_dp(2), dec!(1875));
+}
+
+#[test]
+fn test_reports_translate_other_currency_postings() {
+    let accounts = accounts();
+    // posted in idr before assets:usd was changed into usd account, e.g. by importing archive
+    let entries = vec![entry(&[
+        ("assets:bank", Money::IDR(dec!(-1600000))),
+        ("assets:usd", Money::IDR(dec!(1600000))),
+    ])];
+
+    let trial = trial_balance(&accounts, &entries, Currency::IDR, &rates()).unwrap();
+    let usd = trial
+        .balances
+        .iter()
+        .find(|balance| balance.account.name == "assets:usd")
+        .unwrap();
+    assert_eq!(usd.balance.amount().round_dp(2), dec!(100));
+    assert_eq!(usd.converted.amount().round_dp(2), dec!(1600000));
+    assert_eq!(trial.total_debit, trial.total_credit);
+}
@@ -0,0 +1,259 @@
+use chrono::{DateTime, Utc};
+use ring::digest;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::forex::{Currency, Money};
+
+/// Single movement of money in an account, negative amount for debit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transaction {
+    pub id: Uuid,
+    pub date: DateTime<Utc>,
+    pub amount: Money,
+    pub memo: String,
+    pub payee: Option<String>,
+    pub category: Option<String>,
+
+    /// where the transaction comes from, e.g. ofx, qif, csv.
+    pub source: String,
+
+    /// hash of date, amount, memo and occurrence, identifying the same transaction imported twice.
+    pub hash: String,
+
+    /// index among earlier transactions of the same statement with the same date, amount and memo,
+    /// so repeated ones, e.g. two coffees of a day, are kept apart.
+    #[serde(default)]
+    pub occurrence: u32,
+}
+
+impl Transaction {
+    pub(crate) fn new(
+        date: DateTime<Utc>,
+        amount: Money,
+        memo: &str,
+        payee: Option<String>,
+        source: &str,
+    ) -> Self {
+        let memo = memo.trim().to_string();
+        Self {
+            id: Uuid::new_v4(),
+            hash: Self::hash(date, &amount, &memo, 0),
+            date,
+            amount,
+            memo,
+            payee,
+            category: None,
+            source: source.to_string(),
+            occurrence: 0,
+        }
+    }
+
+    /// recompute hash after date, amount, memo or occurrence changed, e.g. on anonymizing.
+    pub(crate) fn rehash(&mut self) {
+        self.hash = Self::hash(self.date, &self.amount, &self.memo, self.occurrence);
+    }
+
+    /// first occurrences keep hash without occurrence, as stored before occurrences were counted.
+    fn hash(date: DateTime<Utc>, amount: &Money, memo: &str, occurrence: u32) -> String {
+        let mut key = format!(
+            "{}|{}|{}|{}",
+            date.date_naive(),
+            amount.currency().code(),
+            amount.amount().normalize(),
+            memo.to_lowercase()
+        );
+        if occurrence > 0 {
+            key = format!("{}|{}", key, occurrence);
+        }
+        digest::digest(&digest::SHA256, key.as_bytes())
+            .as_ref()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+}
+
+/// Result of importing transactions, duplicates are skipped.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub imported: Vec<Transaction>,
+    pub duplicates: Vec<Transaction>,
+}
+
+/// Rule assigning category to transactions matching all of its conditions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryRule {
+    pub category: String,
+    pub conditions: Vec<RuleCondition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RuleCondition {
+    /// memo or payee contains the text, case insensitive.
+    Substring { text: String },
+
+    /// memo or payee matches the regex.
+    Regex { pattern: String },
+
+    /// amount within inclusive range, in the currency if set.
+    AmountRange {
+        min: Option<Decimal>,
+        max: Option<Decimal>,
+        currency: Option<Currency>,
+    },
+}
+
+/// Result of applying category rules to stored transactions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClassifyReport {
+    pub dry_run: bool,
+    pub changed: Vec<Transaction>,
+    pub unmatched: Vec<Transaction>,
+}
+
+/// Kind of account in double-entry mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountKind {
+    Asset,
+    Liability,
+    Equity,
+    Income,
+    Expense,
+}
+
+/// Account of double-entry mode, postings to it must be in its currency.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Account {
+    /// unique name, e.g. assets:bank:bca
+    pub name: String,
+    pub kind: AccountKind,
+    pub currency: Currency,
+}
+
+/// Balanced double-entry transaction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: Uuid,
+    pub date: DateTime<Utc>,
+    pub memo: String,
+    pub postings: Vec<Posting>,
+}
+
+impl JournalEntry {
+    pub(crate) fn new(date: DateTime<Utc>, memo: &str, postings: Vec<Posting>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            date,
+            memo: memo.trim().to_string(),
+            postings,
+        }
+    }
+}
+
+/// Amount posted to an account, positive for debit and negative for credit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Posting {
+    pub account: String,
+    pub amount: Money,
+}
+
+/// Balance of account, debit positive, and its value in report currency.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountBalance {
+    pub account: Account,
+    pub balance: Money,
+    pub converted: Money,
+}
+
+/// Balances of all accounts in report currency, total debit equals total credit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrialBalance {
+    pub currency: Currency,
+    pub rates_date: DateTime<Utc>,
+    pub balances: Vec<AccountBalance>,
+    pub total_debit: Money,
+    pub total_credit: Money,
+}
+
+/// Assets, liabilities and equity in report currency, net income of income and expense accounts is part of equity.
+/// balances of liability and equity accounts are shown as positive credit balances.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceSheet {
+    pub currency: Currency,
+    pub rates_date: DateTime<Utc>,
+    pub assets: Vec<AccountBalance>,
+    pub liabilities: Vec<AccountBalance>,
+    pub equity: Vec<AccountBalance>,
+    pub total_assets: Money,
+    pub total_liabilities: Money,
+    pub net_income: Money,
+
+    /// equity accounts and net income.
+    pub total_equity: Money,
+}
+
+/// Holdings of mixed portfolio classified by currency, valued in report currency at latest rates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExposureReport {
+    pub currency: Currency,
+    pub rates_date: DateTime<Utc>,
+
+    /// sum of values of all exposures.
+    pub net_worth: Money,
+
+    /// move of each currency against report currency sensitivity is computed for, e.g. 5 for ±5%.
+    pub move_percent: Decimal,
+
+    /// ordered by absolute value, largest first.
+    pub exposures: Vec<CurrencyExposure>,
+}
+
+/// Holdings in a currency, liabilities are negative.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrencyExposure {
+    pub currency: Currency,
+
+    /// net of imported transactions.
+    pub cash: Money,
+
+    /// balances of asset and liability accounts of double-entry mode.
+    pub accounts: Money,
+
+    /// cash and accounts in report currency.
+    pub value: Money,
+
+    /// share of net worth, 0 to 100 unless some exposures are negative.
+    pub share_percent: Decimal,
+
+    /// change of net worth when the currency strengthens by move percent against report currency,
+    /// weakening changes it by the negation.
+    pub sensitivity: Money,
+}
+
+/// Every ledger record, e.g. to move them between installs or to share them in bug reports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerArchive {
+    pub exported_at: DateTime<Utc>,
+
+    /// descriptions are stripped or amounts are fuzzed, not real data anymore.
+    pub anonymized: bool,
+
+    pub transactions: Vec<Transaction>,
+    pub category_rules: Vec<CategoryRule>,
+    pub accounts: Vec<Account>,
+    pub journal_entries: Vec<JournalEntry>,
+}
+
+/// How ledger records are anonymized on export.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnonymizeOptions {
+    /// replace memos with sequence numbers and remove payees and category rules.
+    pub strip_descriptions: bool,
+
+    /// multiply every amount by the factor, the same factor keeps journal entries balanced.
+    pub fuzz_factor: Option<Decimal>,
+}
@@ -0,0 +1,85 @@
+// export.rs anonymize ledger archives and write their transactions as CSV.
+
+use anyhow::Context;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use super::entity::{AnonymizeOptions, LedgerArchive, Transaction};
+use crate::error::AsInternalError;
+use crate::forex::{ForexError, ForexResult, Money};
+
+/// strip descriptions and fuzz amounts of the archive per options, records keep their dates.
+/// records get new ids, so importing the archive never replaces the real records they come from.
+pub fn anonymize(archive: &mut LedgerArchive, options: &AnonymizeOptions) -> ForexResult<()> {
+    if let Some(factor) = options.fuzz_factor
+        && factor <= Decimal::ZERO
+    {
+        return Err(ForexError::client_error("fuzz factor must be positive"));
+    }
+    if !options.strip_descriptions && options.fuzz_factor.is_none() {
+        return Ok(());
+    }
+
+    let fuzz = |money: Money| match options.fuzz_factor {
+        Some(factor) => Money::new_money(money.currency(), money.amount() * factor),
+        None => money,
+    };
+    for (index, tx) in archive.transactions.iter_mut().enumerate() {
+        if options.strip_descriptions {
+            tx.memo = format!("transaction {}", index + 1);
+            tx.payee = None;
+        }
+        tx.id = Uuid::new_v4();
+        tx.amount = fuzz(tx.amount);
+        tx.rehash();
+    }
+    for (index, entry) in archive.journal_entries.iter_mut().enumerate() {
+        entry.id = Uuid::new_v4();
+        if options.strip_descriptions {
+            entry.memo = format!("entry {}", index + 1);
+        }
+        for posting in &mut entry.postings {
+            posting.amount = fuzz(posting.amount);
+        }
+    }
+    if options.strip_descriptions {
+        // rules match on descriptions
+        archive.category_rules.clear();
+    }
+    archive.anonymized = true;
+
+    Ok(())
+}
+
+/// transactions as csv with header `date,currency,amount,memo,payee,category,source`.
+pub fn transactions_csv(transactions: &[Transaction]) -> ForexResult<String> {
+    let mut wtr = csv::Writer::from_writer(vec![]);
+    wtr.write_record([
+        "date", "currency", "amount", "memo", "payee", "category", "source",
+    ])
+    .context("transactions csv write header")
+    .as_internal_err()?;
+    for tx in transactions {
+        wtr.write_record([
+            tx.date.to_rfc3339(),
+            tx.amount.currency().code().to_string(),
+            tx.amount.amount().normalize().to_string(),
+            tx.memo.clone(),
+            tx.payee.clone().unwrap_or_default(),
+            tx.category.clone().unwrap_or_default(),
+            tx.source.clone(),
+        ])
+        .context("transactions csv write row")
+        .as_internal_err()?;
+    }
+
+    let bytes = wtr
+        .into_inner()
+        .context("transactions csv flush")
+        .as_internal_err()?;
+    let content = String::from_utf8(bytes)
+        .context("transactions csv into string")
+        .as_internal_err()?;
+
+    Ok(content)
+}
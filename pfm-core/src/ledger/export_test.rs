@@ -0,0 +1,107 @@
+use chrono::{TimeZone, Utc};
+use rust_decimal_macros::dec;
+
+use super::entity::{
+    AnonymizeOptions, CategoryRule, JournalEntry, LedgerArchive, Posting, RuleCondition,
+    Transaction,
+};
+use super::export::{anonymize, transactions_csv};
+use crate::forex::Money;
+
+fn archive() -> LedgerArchive {
+    let date = Utc.with_ymd_and_hms(2025, 3, 4, 0, 0, 0).unwrap();
+    LedgerArchive {
+        exported_at: date,
+        anonymized: false,
+        transactions: vec![
+            Transaction::new(
+                date,
+                Money::IDR(dec!(-150000)),
+                "GOPAY TOPUP 0812345678",
+                Some("Gojek".to_string()),
+                "csv",
+            ),
+            Transaction::new(date, Money::USD(dec!(2000)), "SALARY, ACME", None, "ofx"),
+        ],
+        category_rules: vec![CategoryRule {
+            category: "transport".to_string(),
+            conditions: vec![RuleCondition::Substring {
+                text: "gopay".to_string(),
+            }],
+        }],
+        accounts: vec![],
+        journal_entries: vec![JournalEntry::new(
+            date,
+            "rent of march",
+            vec![
+                Posting {
+                    account: "expenses:rent".to_string(),
+                    amount: Money::IDR(dec!(5000000)),
+                },
+                Posting {
+                    account: "assets:bank".to_string(),
+                    amount: Money::IDR(dec!(-5000000)),
+                },
+            ],
+        )],
+    }
+}
+
+#[test]
+fn test_anonymize() {
+    let original = archive();
+
+    let mut ret = original.clone();
+    anonymize(&mut ret, &AnonymizeOptions::default()).unwrap();
+    assert!(!ret.anonymized);
+    assert_eq!(ret.transactions[0].memo, original.transactions[0].memo);
+
+    let options = AnonymizeOptions {
+        strip_descriptions: true,
+        fuzz_factor: Some(dec!(1.5)),
+    };
+    let mut ret = original.clone();
+    anonymize(&mut ret, &options).unwrap();
+    assert!(ret.anonymized);
+    assert_eq!(ret.transactions[0].memo, "transaction 1");
+    assert!(ret.transactions[0].payee.is_none());
+    assert_eq!(ret.transactions[0].amount, Money::IDR(dec!(-225000)));
+    assert_ne!(ret.transactions[0].id, original.transactions[0].id);
+    assert_ne!(ret.journal_entries[0].id, original.journal_entries[0].id);
+    assert_ne!(ret.transactions[0].hash, original.transactions[0].hash);
+    assert_eq!(ret.journal_entries[0].memo, "entry 1");
+    let total: rust_decimal::Decimal = ret.journal_entries[0]
+        .postings
+        .iter()
+        .map(|posting| posting.amount.amount())
+        .sum();
+    assert!(total.is_zero());
+    assert!(ret.category_rules.is_empty());
+
+    // reproducible
+    let mut again = original.clone();
+    anonymize(&mut again, &options).unwrap();
+    assert_eq!(again.transactions[1].hash, ret.transactions[1].hash);
+
+    let mut ret = original.clone();
+    let options = AnonymizeOptions {
+        strip_descriptions: false,
+        fuzz_factor: Some(dec!(0)),
+    };
+    assert!(anonymize(&mut ret, &options).is_err());
+}
+
+#[test]
+fn test_transactions_csv() {
+    let ret = transactions_csv(&archive().transactions).unwrap();
+    let lines: Vec<&str> = ret.lines().collect();
+    assert_eq!(lines[0], "date,currency,amount,memo,payee,category,source");
+    assert_eq!(
+        lines[1],
+        "2025-03-04T00:00:00+00:00,IDR,-150000,GOPAY TOPUP 0812345678,Gojek,,csv"
+    );
+    assert_eq!(
+        lines[2],
+        "2025-03-04T00:00:00+00:00,USD,2000,\"SALARY, ACME\",,,ofx"
+    );
+}
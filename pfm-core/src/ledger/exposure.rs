@@ -0,0 +1,84 @@
+// exposure.rs currency exposure of mixed portfolio: holdings of cash and ledger accounts classified by currency,
+// with each currency's share of net worth and sensitivity of net worth to moves of the currency.
+
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::{
+    double_entry,
+    entity::{Account, AccountKind, CurrencyExposure, ExposureReport, JournalEntry, Transaction},
+};
+use crate::forex::{Currency, ForexResult, Money, entity::Rates};
+
+/// move of each currency against report currency, ±5%.
+pub const EXPOSURE_MOVE_PERCENT: Decimal = dec!(5);
+
+/// classify holdings by currency and value them in the currency with the rates.
+/// cash is net of imported transactions, accounts are asset and liability accounts of double-entry mode,
+/// income, expense and equity accounts aren't holdings.
+pub fn exposure_report(
+    transactions: &[Transaction],
+    accounts: &[Account],
+    entries: &[JournalEntry],
+    currency: Currency,
+    rates: &Rates,
+) -> ForexResult<ExposureReport> {
+    // (cash, accounts) per currency
+    let mut holdings: HashMap<Currency, (Decimal, Decimal)> = HashMap::new();
+    for tx in transactions {
+        holdings.entry(tx.amount.currency()).or_default().0 += tx.amount.amount();
+    }
+    for balance in double_entry::account_balances(accounts, entries, currency, rates)? {
+        if matches!(
+            balance.account.kind,
+            AccountKind::Asset | AccountKind::Liability
+        ) {
+            holdings.entry(balance.balance.currency()).or_default().1 += balance.balance.amount();
+        }
+    }
+
+    let mut exposures = Vec::with_capacity(holdings.len());
+    for (holding, (cash, accounts)) in holdings {
+        let value =
+            double_entry::convert(rates, &Money::new_money(holding, cash + accounts), currency)?;
+        let sensitivity = match holding == currency {
+            true => Decimal::ZERO,
+            false => value * EXPOSURE_MOVE_PERCENT / dec!(100),
+        };
+        exposures.push(CurrencyExposure {
+            currency: holding,
+            cash: Money::new_money(holding, cash),
+            accounts: Money::new_money(holding, accounts),
+            value: Money::new_money(currency, value),
+            share_percent: Decimal::ZERO,
+            sensitivity: Money::new_money(currency, sensitivity),
+        });
+    }
+
+    let net_worth: Decimal = exposures
+        .iter()
+        .map(|exposure| exposure.value.amount())
+        .sum();
+    if !net_worth.is_zero() {
+        for exposure in exposures.iter_mut() {
+            exposure.share_percent = (exposure.value.amount() / net_worth * dec!(100)).round_dp(2);
+        }
+    }
+    exposures.sort_by(|a, b| {
+        b.value
+            .amount()
+            .abs()
+            .cmp(&a.value.amount().abs())
+            .then_with(|| a.currency.code().cmp(b.currency.code()))
+    });
+
+    Ok(ExposureReport {
+        currency,
+        rates_date: rates.date,
+        net_worth: Money::new_money(currency, net_worth),
+        move_percent: EXPOSURE_MOVE_PERCENT,
+        exposures,
+    })
+}
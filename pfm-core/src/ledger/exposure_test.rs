@@ -0,0 +1,87 @@
+use chrono::{TimeZone, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::entity::{Account, AccountKind, JournalEntry, Posting, Transaction};
+use super::exposure::exposure_report;
+use crate::forex::{Currency, Money, entity::Rates};
+use crate::global::test_support;
+
+fn rates() -> Rates {
+    let date = Utc.with_ymd_and_hms(2025, 3, 4, 0, 0, 0).unwrap();
+    test_support::rates(date, &[Money::EUR(dec!(0.5)), Money::IDR(dec!(16000))]).data
+}
+
+#[test]
+fn test_exposure_report() {
+    let date = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+    let transactions = vec![
+        Transaction::new(date, Money::IDR(dec!(16000000)), "salary", None, "csv"),
+        Transaction::new(date, Money::IDR(dec!(-3200000)), "rent", None, "csv"),
+        Transaction::new(date, Money::EUR(dec!(100)), "refund", None, "csv"),
+    ];
+    let account = |name: &str, kind, currency| Account {
+        name: name.to_string(),
+        kind,
+        currency,
+    };
+    let accounts = vec![
+        account("assets:usd", AccountKind::Asset, Currency::USD),
+        account("liabilities:card", AccountKind::Liability, Currency::IDR),
+        account("equity:opening", AccountKind::Equity, Currency::USD),
+        account("expenses:food", AccountKind::Expense, Currency::IDR),
+    ];
+    let entry = |postings: &[(&str, Money)]| {
+        let postings = postings
+            .iter()
+            .map(|(account, amount)| Posting {
+                account: account.to_string(),
+                amount: *amount,
+            })
+            .collect();
+        JournalEntry::new(date, "test", postings)
+    };
+    let entries = vec![
+        entry(&[
+            ("assets:usd", Money::USD(dec!(1000))),
+            ("equity:opening", Money::USD(dec!(-1000))),
+        ]),
+        entry(&[
+            ("expenses:food", Money::IDR(dec!(1600000))),
+            ("liabilities:card", Money::IDR(dec!(-1600000))),
+        ]),
+    ];
+
+    let report =
+        exposure_report(&transactions, &accounts, &entries, Currency::USD, &rates()).unwrap();
+    assert_eq!(report.net_worth, Money::USD(dec!(1900)));
+    assert_eq!(report.move_percent, dec!(5));
+    let currencies: Vec<Currency> = report.exposures.iter().map(|e| e.currency).collect();
+    assert_eq!(
+        currencies,
+        vec![Currency::USD, Currency::IDR, Currency::EUR]
+    );
+
+    let usd = &report.exposures[0];
+    assert_eq!(usd.cash, Money::USD(Decimal::ZERO));
+    assert_eq!(usd.accounts, Money::USD(dec!(1000)));
+    assert_eq!(usd.share_percent, dec!(52.63));
+    // report currency doesn't move against itself
+    assert_eq!(usd.sensitivity, Money::USD(Decimal::ZERO));
+
+    let idr = &report.exposures[1];
+    assert_eq!(idr.cash, Money::IDR(dec!(12800000)));
+    assert_eq!(idr.accounts, Money::IDR(dec!(-1600000)));
+    assert_eq!(idr.value, Money::USD(dec!(700)));
+    assert_eq!(idr.share_percent, dec!(36.84));
+    assert_eq!(idr.sensitivity, Money::USD(dec!(35)));
+
+    let eur = &report.exposures[2];
+    assert_eq!(eur.value, Money::USD(dec!(200)));
+    assert_eq!(eur.share_percent, dec!(10.53));
+    assert_eq!(eur.sensitivity, Money::USD(dec!(10)));
+
+    let report = exposure_report(&[], &[], &[], Currency::IDR, &rates()).unwrap();
+    assert!(report.exposures.is_empty());
+    assert_eq!(report.net_worth, Money::IDR(Decimal::ZERO));
+}
@@ -0,0 +1,225 @@
+// import.rs parse bank statements exported as OFX, QIF or CSV into ledger transactions.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::entity::Transaction;
+use crate::forex::{Currency, ForexError, ForexResult, Money};
+
+pub const SOURCE_OFX: &str = "ofx";
+pub const SOURCE_QIF: &str = "qif";
+pub const SOURCE_CSV: &str = "csv";
+
+/// parse STMTTRN entries of OFX 1.x(SGML) or 2.x(XML) statement.
+/// currency is taken from CURDEF of the statement, falling back to the given one.
+pub fn parse_ofx(content: &str, default_currency: Currency) -> ForexResult<Vec<Transaction>> {
+    let currency = match ofx_value(content, "CURDEF") {
+        Some(code) => code.parse()?,
+        None => default_currency,
+    };
+
+    let mut transactions = vec![];
+    let mut rest = content;
+    while let Some(start) = rest.find("<STMTTRN>") {
+        let block_start = start + "<STMTTRN>".len();
+        let block_end = rest[block_start..]
+            .find("</STMTTRN>")
+            .map(|end| block_start + end)
+            .ok_or_else(|| ForexError::client_error("ofx STMTTRN is not closed"))?;
+        let block = &rest[block_start..block_end];
+        rest = &rest[block_end..];
+
+        let date = ofx_value(block, "DTPOSTED")
+            .ok_or_else(|| ForexError::client_error("ofx transaction has no DTPOSTED"))?;
+        let date = parse_date(date.get(..8).unwrap_or(&date), "%Y%m%d")?;
+        let amount = ofx_value(block, "TRNAMT")
+            .ok_or_else(|| ForexError::client_error("ofx transaction has no TRNAMT"))?;
+        // OFX allows comma as decimal separator, the last separator is the decimal one
+        let amount = parse_amount(&amount, amount.rfind(',') > amount.rfind('.'))?;
+        let payee = ofx_value(block, "NAME");
+        let memo = ofx_value(block, "MEMO")
+            .or_else(|| payee.clone())
+            .unwrap_or_default();
+
+        transactions.push(Transaction::new(
+            date,
+            Money::new_money(currency, amount),
+            &memo,
+            payee,
+            SOURCE_OFX,
+        ));
+    }
+    number_occurrences(&mut transactions);
+
+    Ok(transactions)
+}
+
+/// value of first `<TAG>value` in content, value ends at next tag or line end.
+fn ofx_value(content: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let start = content.find(&open)? + open.len();
+    let value = content[start..]
+        .split(['<', '\n', '\r'])
+        .next()
+        .unwrap_or_default()
+        .trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// parse QIF bank records, dates are in date_format(chrono), e.g. `%m/%d/%Y`.
+/// QIF has no currency, so all amounts are in the given currency.
+pub fn parse_qif(
+    content: &str,
+    currency: Currency,
+    date_format: &str,
+) -> ForexResult<Vec<Transaction>> {
+    let mut transactions = vec![];
+    let mut date = None;
+    let mut amount = None;
+    let mut payee = None;
+    let mut memo = None;
+    for line in content.trim_start_matches('\u{feff}').lines() {
+        let mut chars = line.trim().chars();
+        let Some(code) = chars.next() else {
+            continue;
+        };
+        let value = chars.as_str().trim();
+        match code {
+            // quicken writes years after 2000 with apostrophe, e.g. 1/5'24
+            'D' => date = Some(parse_date(&value.replace('\'', "/20"), date_format)?),
+            'T' | 'U' => amount = Some(parse_amount(value, false)?),
+            'P' => payee = Some(value.to_string()),
+            'M' => memo = Some(value.to_string()),
+            '^' => {
+                let (Some(date), Some(amount)) = (date.take(), amount.take()) else {
+                    return Err(ForexError::client_error(
+                        "qif record must have date and amount",
+                    ));
+                };
+                let memo = memo.take().or_else(|| payee.clone()).unwrap_or_default();
+                transactions.push(Transaction::new(
+                    date,
+                    Money::new_money(currency, amount),
+                    &memo,
+                    payee.take(),
+                    SOURCE_QIF,
+                ));
+            }
+            _ => {}
+        }
+    }
+    number_occurrences(&mut transactions);
+
+    Ok(transactions)
+}
+
+/// Columns mapping of bank's csv export, columns are referred by header names.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvMapping {
+    pub delimiter: u8,
+    pub date_column: String,
+
+    /// chrono format of date column, e.g. `%d/%m/%Y`
+    pub date_format: String,
+    pub amount_column: String,
+    pub memo_column: String,
+    pub payee_column: Option<String>,
+
+    /// amounts use comma as decimal separator and dot for thousands, e.g. 1.000,50
+    pub decimal_comma: bool,
+
+    /// negate amounts for exports having debits as positive numbers.
+    pub negate: bool,
+    pub currency: Currency,
+}
+
+/// parse csv bank export according to the mapping.
+pub fn parse_csv(content: &str, mapping: &CsvMapping) -> ForexResult<Vec<Transaction>> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(mapping.delimiter)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+
+    let headers = rdr
+        .headers()
+        .map_err(|err| ForexError::client_error(format!("csv headers: {}", err).as_str()))?
+        .clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header == name)
+            .ok_or_else(|| ForexError::client_error(format!("csv has no column {}", name).as_str()))
+    };
+    let date_idx = column(&mapping.date_column)?;
+    let amount_idx = column(&mapping.amount_column)?;
+    let memo_idx = column(&mapping.memo_column)?;
+    let payee_idx = mapping.payee_column.as_deref().map(column).transpose()?;
+
+    let mut transactions = vec![];
+    for (i, record) in rdr.records().enumerate() {
+        let record = record.map_err(|err| {
+            ForexError::client_error(format!("csv row {}: {}", i + 1, err).as_str())
+        })?;
+        let field = |idx: usize| record.get(idx).unwrap_or_default();
+
+        let date = parse_date(field(date_idx), &mapping.date_format)?;
+        let mut amount = parse_amount(field(amount_idx), mapping.decimal_comma)?;
+        if mapping.negate {
+            amount = -amount;
+        }
+        let payee = payee_idx
+            .map(|idx| field(idx).to_string())
+            .filter(|payee| !payee.is_empty());
+
+        transactions.push(Transaction::new(
+            date,
+            Money::new_money(mapping.currency, amount),
+            field(memo_idx),
+            payee,
+            SOURCE_CSV,
+        ));
+    }
+    number_occurrences(&mut transactions);
+
+    Ok(transactions)
+}
+
+/// count repeats of date, amount and memo within the statement, so repeated transactions get their own hash,
+/// while importing the same statement again gives the same hashes.
+fn number_occurrences(transactions: &mut [Transaction]) {
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for tx in transactions {
+        let count = counts.entry(tx.hash.clone()).or_default();
+        if *count > 0 {
+            tx.occurrence = *count;
+            tx.rehash();
+        }
+        *count += 1;
+    }
+}
+
+fn parse_date(value: &str, format: &str) -> ForexResult<DateTime<Utc>> {
+    let date = NaiveDate::parse_from_str(value.trim(), format).map_err(|err| {
+        ForexError::client_error(
+            format!("invalid date {} for format {}: {}", value, format, err).as_str(),
+        )
+    })?;
+
+    Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+}
+
+fn parse_amount(value: &str, decimal_comma: bool) -> ForexResult<Decimal> {
+    let value = value.trim();
+    let normalized = if decimal_comma {
+        value.replace('.', "").replace(',', ".")
+    } else {
+        value.replace(',', "")
+    };
+
+    normalized.parse::<Decimal>().map_err(|err| {
+        ForexError::client_error(format!("invalid amount {}: {}", value, err).as_str())
+    })
+}
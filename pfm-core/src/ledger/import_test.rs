@@ -0,0 +1,143 @@
+use chrono::{TimeZone, Utc};
+use rust_decimal_macros::dec;
+
+use super::import::{CsvMapping, parse_csv, parse_ofx, parse_qif};
+use crate::forex::{Currency, Money};
+
+const OFX: &str = "OFXHEADER:100
+DATA:OFXSGML
+<OFX>
+<BANKMSGSRSV1><STMTTRNRS><STMTRS>
+<CURDEF>USD
+<BANKTRANLIST>
+<STMTTRN>
+<TRNTYPE>DEBIT
+<DTPOSTED>20240105120000[-5:EST]
+<TRNAMT>-1,250.50
+<FITID>1001
+<NAME>Grocery Store
+<MEMO>weekly groceries
+</STMTTRN>
+<STMTTRN>
+<TRNTYPE>CREDIT
+<DTPOSTED>20240110
+<TRNAMT>3000.00
+<FITID>1002
+<NAME>Employer
+</STMTTRN>
+</BANKTRANLIST>
+</STMTRS></STMTTRNRS></BANKMSGSRSV1>
+</OFX>
+";
+
+#[test]
+fn test_parse_ofx() {
+    let ret = parse_ofx(OFX, Currency::IDR).unwrap();
+    assert_eq!(ret.len(), 2);
+    assert_eq!(
+        ret[0].date,
+        Utc.with_ymd_and_hms(2024, 1, 5, 0, 0, 0).unwrap()
+    );
+    assert_eq!(ret[0].amount, Money::USD(dec!(-1250.50)));
+    assert_eq!(ret[0].memo, "weekly groceries");
+    assert_eq!(ret[0].payee.as_deref(), Some("Grocery Store"));
+    assert_eq!(ret[1].amount, Money::USD(dec!(3000)));
+    assert_eq!(ret[1].memo, "Employer");
+
+    // same date, amount and memo are the same transaction
+    let again = parse_ofx(OFX, Currency::IDR).unwrap();
+    assert_eq!(ret[0].hash, again[0].hash);
+    assert_ne!(ret[0].id, again[0].id);
+    assert_ne!(ret[0].hash, ret[1].hash);
+}
+
+#[test]
+fn test_parse_qif() {
+    let qif = "!Type:Bank
+D01/05'24
+T-45.00
+PCoffee Shop
+^
+D01/06/2024
+T1,000.00
+MSalary
+^
+";
+    let ret = parse_qif(qif, Currency::USD, "%m/%d/%Y").unwrap();
+    assert_eq!(ret.len(), 2);
+    assert_eq!(
+        ret[0].date,
+        Utc.with_ymd_and_hms(2024, 1, 5, 0, 0, 0).unwrap()
+    );
+    assert_eq!(ret[0].amount, Money::USD(dec!(-45)));
+    assert_eq!(ret[0].memo, "Coffee Shop");
+    assert_eq!(ret[1].amount, Money::USD(dec!(1000)));
+    assert_eq!(ret[1].payee, None);
+
+    assert!(parse_qif("D01/05/2024\n^\n", Currency::USD, "%m/%d/%Y").is_err());
+
+    // byte order mark and lines starting with multibyte char
+    let qif = "\u{feff}!Type:Bank\n€ note\nD01/05/2024\nT-3.50\nMCafé\n^\n";
+    let ret = parse_qif(qif, Currency::EUR, "%m/%d/%Y").unwrap();
+    assert_eq!(ret.len(), 1);
+    assert_eq!(ret[0].amount, Money::EUR(dec!(-3.5)));
+    assert_eq!(ret[0].memo, "Café");
+}
+
+#[test]
+fn test_parse_ofx_decimal_comma() {
+    let ofx = OFX
+        .replace("-1,250.50", "-1250,50")
+        .replace("3000.00", "3.000,00");
+    let ret = parse_ofx(&ofx, Currency::IDR).unwrap();
+    assert_eq!(ret[0].amount, Money::USD(dec!(-1250.50)));
+    assert_eq!(ret[1].amount, Money::USD(dec!(3000)));
+}
+
+#[test]
+fn test_repeated_transactions_kept() {
+    let qif = "D01/05/2024\nT-4.00\nMCoffee\n^\nD01/05/2024\nT-4.00\nMCoffee\n^\n";
+    let ret = parse_qif(qif, Currency::USD, "%m/%d/%Y").unwrap();
+    assert_eq!(ret.len(), 2);
+    assert_eq!((ret[0].occurrence, ret[1].occurrence), (0, 1));
+    assert_ne!(ret[0].hash, ret[1].hash);
+
+    // importing the statement again gives the same hashes
+    let again = parse_qif(qif, Currency::USD, "%m/%d/%Y").unwrap();
+    assert_eq!(ret[0].hash, again[0].hash);
+    assert_eq!(ret[1].hash, again[1].hash);
+}
+
+#[test]
+fn test_parse_csv() {
+    let csv = "Tanggal;Keterangan;Jumlah
+05/01/2024;Transfer ke Budi;150.000,00
+06/01/2024;Gaji;-10.000.000,00
+";
+    let mapping = CsvMapping {
+        delimiter: b';',
+        date_column: "Tanggal".to_string(),
+        date_format: "%d/%m/%Y".to_string(),
+        amount_column: "Jumlah".to_string(),
+        memo_column: "Keterangan".to_string(),
+        payee_column: None,
+        decimal_comma: true,
+        negate: true,
+        currency: Currency::IDR,
+    };
+    let ret = parse_csv(csv, &mapping).unwrap();
+    assert_eq!(ret.len(), 2);
+    assert_eq!(
+        ret[0].date,
+        Utc.with_ymd_and_hms(2024, 1, 5, 0, 0, 0).unwrap()
+    );
+    assert_eq!(ret[0].amount, Money::IDR(dec!(-150000)));
+    assert_eq!(ret[1].amount, Money::IDR(dec!(10000000)));
+    assert_eq!(ret[1].memo, "Gaji");
+
+    let mapping = CsvMapping {
+        amount_column: "Amount".to_string(),
+        ..mapping
+    };
+    assert!(parse_csv(csv, &mapping).is_err());
+}
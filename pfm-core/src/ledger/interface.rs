@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use super::entity::{Account, CategoryRule, JournalEntry, Transaction};
+use crate::forex::ForexResult;
+
+#[async_trait]
+pub trait LedgerStorage {
+    /// insert new transactions or replace existing ones with the same id.
+    async fn upsert_transactions(&self, transactions: &[Transaction]) -> ForexResult<()>;
+
+    /// get transactions within range ordered by date, all transactions if range is None.
+    async fn get_transactions(
+        &self,
+        range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    ) -> ForexResult<Vec<Transaction>>;
+}
+
+#[async_trait]
+pub trait LedgerStorageCategoryRules {
+    /// get category rules in order they're applied, empty if none saved.
+    async fn get_category_rules(&self) -> ForexResult<Vec<CategoryRule>>;
+
+    /// replace all category rules.
+    async fn set_category_rules(&self, rules: &[CategoryRule]) -> ForexResult<()>;
+}
+
+#[async_trait]
+pub trait LedgerStorageDoubleEntry {
+    /// get accounts of double-entry mode ordered by name.
+    async fn get_accounts(&self) -> ForexResult<Vec<Account>>;
+
+    /// insert new account or replace existing one with the same name.
+    async fn upsert_account(&self, account: &Account) -> ForexResult<()>;
+
+    async fn insert_journal_entry(&self, entry: &JournalEntry) -> ForexResult<()>;
+
+    /// get journal entries up to the date ordered by date, all entries if until is None.
+    async fn get_journal_entries(
+        &self,
+        until: Option<DateTime<Utc>>,
+    ) -> ForexResult<Vec<JournalEntry>>;
+}
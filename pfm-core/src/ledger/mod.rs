@@ -0,0 +1,29 @@
+// ledger personal finance transactions, bootstrapped by importing bank statements.
+
+pub mod double_entry;
+#[cfg(test)]
+mod double_entry_test;
+
+pub mod entity;
+
+pub(crate) mod export;
+#[cfg(test)]
+mod export_test;
+
+pub mod exposure;
+#[cfg(test)]
+mod exposure_test;
+
+pub(crate) mod import;
+#[cfg(test)]
+mod import_test;
+
+pub mod interface;
+
+pub mod rules;
+#[cfg(test)]
+mod rules_test;
+
+pub(crate) mod service;
+#[cfg(test)]
+mod service_test;
@@ -0,0 +1,85 @@
+// rules.rs classify ledger transactions into categories with user defined rules.
+
+use regex::Regex;
+use rust_decimal::Decimal;
+
+use super::entity::{CategoryRule, RuleCondition, Transaction};
+use crate::forex::{Currency, ForexError, ForexResult};
+
+enum Matcher {
+    Substring(String),
+    Regex(Regex),
+    AmountRange {
+        min: Option<Decimal>,
+        max: Option<Decimal>,
+        currency: Option<Currency>,
+    },
+}
+
+/// Category rules compiled once to classify many transactions, first matching rule wins.
+pub struct Classifier {
+    rules: Vec<(String, Vec<Matcher>)>,
+}
+
+impl Classifier {
+    pub fn new(rules: &[CategoryRule]) -> ForexResult<Self> {
+        let mut compiled = Vec::with_capacity(rules.len());
+        for rule in rules {
+            if rule.category.trim().is_empty() {
+                return Err(ForexError::client_error("rule category must not be empty"));
+            }
+            if rule.conditions.is_empty() {
+                return Err(ForexError::client_error(
+                    format!("rule of {} has no conditions", rule.category).as_str(),
+                ));
+            }
+            let mut matchers = Vec::with_capacity(rule.conditions.len());
+            for condition in &rule.conditions {
+                let matcher = match condition {
+                    RuleCondition::Substring { text } => Matcher::Substring(text.to_lowercase()),
+                    RuleCondition::Regex { pattern } => {
+                        Matcher::Regex(Regex::new(pattern).map_err(|err| {
+                            ForexError::client_error(
+                                format!("invalid rule regex {}: {}", pattern, err).as_str(),
+                            )
+                        })?)
+                    }
+                    RuleCondition::AmountRange { min, max, currency } => Matcher::AmountRange {
+                        min: *min,
+                        max: *max,
+                        currency: *currency,
+                    },
+                };
+                matchers.push(matcher);
+            }
+            compiled.push((rule.category.trim().to_string(), matchers));
+        }
+
+        Ok(Self { rules: compiled })
+    }
+
+    /// category of first rule whose conditions all match the transaction.
+    pub fn classify(&self, tx: &Transaction) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|(_, matchers)| matchers.iter().all(|matcher| matches(matcher, tx)))
+            .map(|(category, _)| category.as_str())
+    }
+}
+
+fn matches(matcher: &Matcher, tx: &Transaction) -> bool {
+    let texts = [Some(tx.memo.as_str()), tx.payee.as_deref()];
+    match matcher {
+        Matcher::Substring(text) => texts
+            .iter()
+            .flatten()
+            .any(|value| value.to_lowercase().contains(text)),
+        Matcher::Regex(regex) => texts.iter().flatten().any(|value| regex.is_match(value)),
+        Matcher::AmountRange { min, max, currency } => {
+            let amount = tx.amount.amount();
+            currency.is_none_or(|currency| currency == tx.amount.currency())
+                && min.is_none_or(|min| amount >= min)
+                && max.is_none_or(|max| amount <= max)
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use chrono::{TimeZone, Utc};
+use rust_decimal_macros::dec;
+
+use super::entity::{CategoryRule, RuleCondition, Transaction};
+use super::rules::Classifier;
+use crate::forex::{Currency, Money};
+
+fn tx(memo: &str, payee: Option<&str>, amount: Money) -> Transaction {
+    let date = Utc.with_ymd_and_hms(2024, 1, 5, 0, 0, 0).unwrap();
+    Transaction::new(date, amount, memo, payee.map(str::to_string), "test")
+}
+
+#[test]
+fn test_classifier() {
+    let rules = vec![
+        CategoryRule {
+            category: "salary".to_string(),
+            conditions: vec![
+                RuleCondition::Substring {
+                    text: "GAJI".to_string(),
+                },
+                RuleCondition::AmountRange {
+                    min: Some(dec!(0)),
+                    max: None,
+                    currency: Some(Currency::IDR),
+                },
+            ],
+        },
+        CategoryRule {
+            category: "groceries".to_string(),
+            conditions: vec![RuleCondition::Regex {
+                pattern: "(?i)^(indomaret|alfamart)".to_string(),
+            }],
+        },
+        CategoryRule {
+            category: "small spending".to_string(),
+            conditions: vec![RuleCondition::AmountRange {
+                min: Some(dec!(-50000)),
+                max: Some(dec!(0)),
+                currency: None,
+            }],
+        },
+    ];
+    let classifier = Classifier::new(&rules).unwrap();
+
+    let salary = tx("Gaji Januari", None, Money::IDR(dec!(10000000)));
+    assert_eq!(classifier.classify(&salary), Some("salary"));
+
+    // first matching rule wins
+    let groceries = tx("weekly", Some("Indomaret Point"), Money::IDR(dec!(-25000)));
+    assert_eq!(classifier.classify(&groceries), Some("groceries"));
+
+    let coffee = tx("coffee", None, Money::IDR(dec!(-30000)));
+    assert_eq!(classifier.classify(&coffee), Some("small spending"));
+
+    let rent = tx("rent", None, Money::IDR(dec!(-5000000)));
+    assert_eq!(classifier.classify(&rent), None);
+}
+
+#[test]
+fn test_classifier_invalid_rules() {
+    let rules = vec![CategoryRule {
+        category: "broken".to_string(),
+        conditions: vec![RuleCondition::Regex {
+            pattern: "(".to_string(),
+        }],
+    }];
+    assert!(Classifier::new(&rules).is_err());
+
+    let rules = vec![CategoryRule {
+        category: "empty".to_string(),
+        conditions: vec![],
+    }];
+    assert!(Classifier::new(&rules).is_err());
+}
@@ -0,0 +1,319 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+
+use super::{
+    double_entry,
+    entity::{
+        Account, AnonymizeOptions, BalanceSheet, CategoryRule, ClassifyReport, ExposureReport,
+        ImportReport, JournalEntry, LedgerArchive, Posting, Transaction, TrialBalance,
+    },
+    export, exposure,
+    interface::{LedgerStorage, LedgerStorageCategoryRules, LedgerStorageDoubleEntry},
+    rules::Classifier,
+};
+use crate::forex::{Currency, ForexError, ForexResult, entity::Rates, interface::ForexStorage};
+
+/// store parsed transactions, skipping those already stored.
+/// imported transactions without category are classified with saved category rules.
+pub async fn import_transactions<LS>(
+    storage: &LS,
+    transactions: Vec<Transaction>,
+    dry_run: bool,
+) -> ForexResult<ImportReport>
+where
+    LS: LedgerStorage + LedgerStorageCategoryRules,
+{
+    let (Some(start), Some(end)) = (
+        transactions.iter().map(|tx| tx.date).min(),
+        transactions.iter().map(|tx| tx.date).max(),
+    ) else {
+        return Ok(ImportReport::default());
+    };
+
+    let mut seen: HashSet<String> = storage
+        .get_transactions(Some((start, end)))
+        .await?
+        .into_iter()
+        .map(|tx| tx.hash)
+        .collect();
+    let classifier = Classifier::new(&storage.get_category_rules().await?)?;
+
+    let mut report = ImportReport::default();
+    for mut tx in transactions {
+        if !seen.insert(tx.hash.clone()) {
+            report.duplicates.push(tx);
+            continue;
+        }
+        if tx.category.is_none() {
+            tx.category = classifier.classify(&tx).map(str::to_string);
+        }
+        report.imported.push(tx);
+    }
+
+    if !dry_run && !report.imported.is_empty() {
+        storage.upsert_transactions(&report.imported).await?;
+    }
+
+    Ok(report)
+}
+
+/// every ledger record in a single archive, anonymized per options.
+pub async fn export_ledger<LS>(
+    storage: &LS,
+    options: &AnonymizeOptions,
+) -> ForexResult<LedgerArchive>
+where
+    LS: LedgerStorage + LedgerStorageCategoryRules + LedgerStorageDoubleEntry,
+{
+    let mut archive = LedgerArchive {
+        exported_at: Utc::now(),
+        anonymized: false,
+        transactions: storage.get_transactions(None).await?,
+        category_rules: storage.get_category_rules().await?,
+        accounts: storage.get_accounts().await?,
+        journal_entries: storage.get_journal_entries(None).await?,
+    };
+    export::anonymize(&mut archive, options)?;
+
+    Ok(archive)
+}
+
+/// store records of archive exported from another install, its category rules are added after stored ones.
+/// transactions and accounts are upserted, journal entries already stored are skipped.
+/// anonymized transactions never replace stored ones.
+pub async fn import_ledger<LS>(storage: &LS, archive: LedgerArchive) -> ForexResult<()>
+where
+    LS: LedgerStorage + LedgerStorageCategoryRules + LedgerStorageDoubleEntry,
+{
+    Classifier::new(&archive.category_rules)?;
+
+    if archive.anonymized {
+        let stored: HashSet<_> = storage
+            .get_transactions(None)
+            .await?
+            .into_iter()
+            .map(|tx| tx.id)
+            .collect();
+        if archive
+            .transactions
+            .iter()
+            .any(|tx| stored.contains(&tx.id))
+        {
+            return Err(ForexError::client_error(
+                "anonymized archive has transactions of stored ones, import it into another install",
+            ));
+        }
+    }
+    if !archive.transactions.is_empty() {
+        storage.upsert_transactions(&archive.transactions).await?;
+    }
+    let mut rules = storage.get_category_rules().await?;
+    let stored_rules = rules.len();
+    for rule in archive.category_rules {
+        if !rules.contains(&rule) {
+            rules.push(rule);
+        }
+    }
+    if rules.len() > stored_rules {
+        storage.set_category_rules(&rules).await?;
+    }
+    for account in &archive.accounts {
+        storage.upsert_account(account).await?;
+    }
+    let stored: HashSet<_> = storage
+        .get_journal_entries(None)
+        .await?
+        .into_iter()
+        .map(|entry| entry.id)
+        .collect();
+    for entry in &archive.journal_entries {
+        if !stored.contains(&entry.id) {
+            storage.insert_journal_entry(entry).await?;
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn get_category_rules<LS>(storage: &LS) -> ForexResult<Vec<CategoryRule>>
+where
+    LS: LedgerStorageCategoryRules,
+{
+    storage.get_category_rules().await
+}
+
+/// replace category rules after validating them, e.g. regexes compile.
+pub async fn set_category_rules<LS>(storage: &LS, rules: Vec<CategoryRule>) -> ForexResult<()>
+where
+    LS: LedgerStorageCategoryRules,
+{
+    Classifier::new(&rules)?;
+    storage.set_category_rules(&rules).await
+}
+
+/// re-apply category rules to stored transactions, e.g. after rules are edited.
+/// categories set before are kept unless overwrite, transactions matching no rule are reported as unmatched.
+pub async fn reclassify_transactions<LS>(
+    storage: &LS,
+    overwrite: bool,
+    dry_run: bool,
+) -> ForexResult<ClassifyReport>
+where
+    LS: LedgerStorage + LedgerStorageCategoryRules,
+{
+    let classifier = Classifier::new(&storage.get_category_rules().await?)?;
+    let mut report = ClassifyReport {
+        dry_run,
+        ..Default::default()
+    };
+
+    for mut tx in storage.get_transactions(None).await? {
+        if tx.category.is_some() && !overwrite {
+            continue;
+        }
+        match classifier.classify(&tx) {
+            Some(category) if tx.category.as_deref() != Some(category) => {
+                tx.category = Some(category.to_string());
+                report.changed.push(tx);
+            }
+            Some(_) => {}
+            None => report.unmatched.push(tx),
+        }
+    }
+
+    if !dry_run && !report.changed.is_empty() {
+        storage.upsert_transactions(&report.changed).await?;
+    }
+
+    Ok(report)
+}
+
+pub async fn get_accounts<LS>(storage: &LS) -> ForexResult<Vec<Account>>
+where
+    LS: LedgerStorageDoubleEntry,
+{
+    storage.get_accounts().await
+}
+
+/// add account or change kind/currency of existing one, rejected if existing postings are in other currency.
+pub async fn add_account<LS>(storage: &LS, account: Account) -> ForexResult<Account>
+where
+    LS: LedgerStorageDoubleEntry,
+{
+    if account.name.trim().is_empty() {
+        return Err(ForexError::client_error("account name cannot be empty"));
+    }
+    let account = Account {
+        name: account.name.trim().to_string(),
+        ..account
+    };
+    let posted_other_currency = storage
+        .get_journal_entries(None)
+        .await?
+        .iter()
+        .flat_map(|entry| &entry.postings)
+        .any(|posting| {
+            posting.account == account.name && posting.amount.currency() != account.currency
+        });
+    if posted_other_currency {
+        return Err(ForexError::client_error(
+            format!("account {} has postings in other currency", account.name).as_str(),
+        ));
+    }
+
+    storage.upsert_account(&account).await?;
+
+    Ok(account)
+}
+
+/// post journal entry balanced in each of its currencies.
+pub async fn post_entry<LS>(
+    ledger_storage: &LS,
+    date: DateTime<Utc>,
+    memo: &str,
+    postings: Vec<Posting>,
+) -> ForexResult<JournalEntry>
+where
+    LS: LedgerStorageDoubleEntry,
+{
+    let entry = JournalEntry::new(date, memo, postings);
+    let accounts = ledger_storage.get_accounts().await?;
+
+    double_entry::validate_entry(&entry, &accounts)?;
+    ledger_storage.insert_journal_entry(&entry).await?;
+
+    Ok(entry)
+}
+
+/// trial balance of entries until date in currency, converted at rates of the date, or latest rates if date is None.
+pub async fn get_trial_balance<LS, FS>(
+    ledger_storage: &LS,
+    forex_storage: &FS,
+    currency: Currency,
+    date: Option<DateTime<Utc>>,
+) -> ForexResult<TrialBalance>
+where
+    LS: LedgerStorageDoubleEntry,
+    FS: ForexStorage,
+{
+    let accounts = ledger_storage.get_accounts().await?;
+    let entries = ledger_storage.get_journal_entries(date).await?;
+    let rates = rates_at(forex_storage, date).await?;
+
+    double_entry::trial_balance(&accounts, &entries, currency, &rates)
+}
+
+/// balance sheet of entries until date in currency, converted at rates of the date, or latest rates if date is None.
+pub async fn get_balance_sheet<LS, FS>(
+    ledger_storage: &LS,
+    forex_storage: &FS,
+    currency: Currency,
+    date: Option<DateTime<Utc>>,
+) -> ForexResult<BalanceSheet>
+where
+    LS: LedgerStorageDoubleEntry,
+    FS: ForexStorage,
+{
+    let accounts = ledger_storage.get_accounts().await?;
+    let entries = ledger_storage.get_journal_entries(date).await?;
+    let rates = rates_at(forex_storage, date).await?;
+
+    double_entry::balance_sheet(&accounts, &entries, currency, &rates)
+}
+
+/// currency exposure of cash and ledger accounts valued in the currency at latest rates.
+pub async fn get_exposure_report<LS, FS>(
+    ledger_storage: &LS,
+    forex_storage: &FS,
+    currency: Currency,
+) -> ForexResult<ExposureReport>
+where
+    LS: LedgerStorage + LedgerStorageDoubleEntry,
+    FS: ForexStorage,
+{
+    let transactions = ledger_storage.get_transactions(None).await?;
+    let accounts = ledger_storage.get_accounts().await?;
+    let entries = ledger_storage.get_journal_entries(None).await?;
+    let rates = rates_at(forex_storage, None).await?;
+
+    exposure::exposure_report(&transactions, &accounts, &entries, currency, &rates)
+}
+
+/// historical rates of past date, latest rates for today or None.
+async fn rates_at<FS>(forex_storage: &FS, date: Option<DateTime<Utc>>) -> ForexResult<Rates>
+where
+    FS: ForexStorage,
+{
+    let rates = match date {
+        Some(date) if date.date_naive() < Utc::now().date_naive() => {
+            forex_storage.get_historical(date).await?
+        }
+        _ => forex_storage.get_latest().await?,
+    };
+    if let Some(err) = rates.error {
+        return Err(ForexError::internal_error(err.as_str()));
+    }
+
+    Ok(rates.data)
+}
@@ -0,0 +1,67 @@
+use chrono::{TimeZone, Utc};
+use rust_decimal_macros::dec;
+
+use super::entity::{AnonymizeOptions, CategoryRule, LedgerArchive, RuleCondition, Transaction};
+use super::interface::{LedgerStorage, LedgerStorageCategoryRules};
+use super::service::{export_ledger, import_ledger};
+use crate::forex::Money;
+use crate::global::test_support::temp_storage;
+
+fn rule(category: &str, text: &str) -> CategoryRule {
+    CategoryRule {
+        category: category.to_string(),
+        conditions: vec![RuleCondition::Substring {
+            text: text.to_string(),
+        }],
+    }
+}
+
+#[tokio::test]
+async fn test_import_ledger() {
+    let (storage, _root) = temp_storage("import-ledger");
+    let date = Utc.with_ymd_and_hms(2025, 3, 4, 0, 0, 0).unwrap();
+    let salary = Transaction::new(date, Money::USD(dec!(2000)), "SALARY, ACME", None, "ofx");
+    storage
+        .upsert_transactions(std::slice::from_ref(&salary))
+        .await
+        .unwrap();
+    storage
+        .set_category_rules(&[rule("income", "salary")])
+        .await
+        .unwrap();
+
+    // rules of archive are added after stored ones
+    let archive = LedgerArchive {
+        exported_at: date,
+        anonymized: false,
+        transactions: vec![],
+        category_rules: vec![rule("transport", "gopay"), rule("income", "salary")],
+        accounts: vec![],
+        journal_entries: vec![],
+    };
+    import_ledger(&storage, archive).await.unwrap();
+    assert_eq!(
+        storage.get_category_rules().await.unwrap(),
+        vec![rule("income", "salary"), rule("transport", "gopay")]
+    );
+
+    // anonymized export imported back doesn't replace the real transaction
+    let options = AnonymizeOptions {
+        strip_descriptions: false,
+        fuzz_factor: Some(dec!(2)),
+    };
+    let anonymized = export_ledger(&storage, &options).await.unwrap();
+    import_ledger(&storage, anonymized.clone()).await.unwrap();
+    let stored = storage.get_transactions(None).await.unwrap();
+    assert_eq!(stored.len(), 2);
+    assert!(
+        stored
+            .iter()
+            .any(|tx| tx.id == salary.id && tx.amount == salary.amount)
+    );
+
+    // anonymized with ids of stored transactions is refused
+    let mut replacing = anonymized;
+    replacing.transactions[0].id = salary.id;
+    assert!(import_ledger(&storage, replacing).await.is_err());
+}
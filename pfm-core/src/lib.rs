@@ -1,5 +1,16 @@
+pub mod api;
+pub mod digest;
 mod error;
 pub mod forex;
-pub mod forex_impl;
+pub(crate) mod forex_impl;
+pub mod goals;
+pub mod ledger;
+pub mod prelude;
+pub mod profile;
+pub mod seed;
+pub mod sync;
+pub mod tax;
+pub mod users;
+pub mod watchlist;
 
 pub mod global;
@@ -0,0 +1,13 @@
+// prelude.rs types and traits commonly used with `api`, imported at once with `use pfm_core::prelude::*`.
+
+pub use crate::api;
+pub use crate::forex::entity::{
+    Job, JobTrigger, Order, OrderBy, PollMode, Rates, RatesData, RatesResponse,
+};
+pub use crate::forex::interface::{
+    ForexConverter, ForexCryptoPrices, ForexHistoricalRates, ForexProviderCapabilities, ForexRates,
+    ForexStorage, ForexTimeseriesRates,
+};
+pub use crate::forex::{Currency, CurrencyKind, ForexError, ForexResult, Money};
+pub use crate::forex_impl::forex_storage::ForexStorageImpl;
+pub use crate::global::{Clock, SystemClock, TenantId, TenantScoped, config};
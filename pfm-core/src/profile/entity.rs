@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::digest::entity::DigestSubscription;
+use crate::goals::entity::Goal;
+use crate::ledger::entity::LedgerArchive;
+use crate::sync::entity::Tombstone;
+use crate::watchlist::entity::Watchlist;
+
+/// version of archives written by this build, newer ones can't be imported.
+/// version 2 adds settings.
+pub const PROFILE_ARCHIVE_VERSION: u32 = 2;
+
+/// Client data of a profile in a single file, checked before being imported.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileArchive {
+    pub version: u32,
+    pub profile: String,
+    pub exported_at: DateTime<Utc>,
+
+    /// hex sha256 checksum of content as json.
+    pub checksum: String,
+    pub content: ProfileContent,
+}
+
+/// Records bundled into profile archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileContent {
+    pub goals: Vec<Goal>,
+
+    /// None if profile has no watchlist.
+    pub watchlist: Option<Watchlist>,
+
+    /// digest subscriptions, the settings of delivered summaries.
+    pub digest_subscriptions: Vec<DigestSubscription>,
+
+    /// cash records, never anonymized.
+    pub ledger: LedgerArchive,
+
+    /// left out of version 1 archives, so their checksums still match.
+    #[serde(default, skip_serializing_if = "ProfileSettings::is_empty")]
+    pub settings: ProfileSettings,
+}
+
+/// Sync settings of a profile, so the imported profile keeps syncing from where it stopped.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileSettings {
+    /// time of last sync by peer.
+    pub last_syncs: BTreeMap<String, DateTime<Utc>>,
+
+    /// deleted records, so their deletions still reach peers.
+    pub tombstones: Vec<Tombstone>,
+}
+
+impl ProfileSettings {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.last_syncs.is_empty() && self.tombstones.is_empty()
+    }
+}
+
+/// Number of records stored by profile import.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileImportReport {
+    pub goals: usize,
+    pub watchlist_pairs: usize,
+    pub digest_subscriptions: usize,
+    pub transactions: usize,
+    pub journal_entries: usize,
+    pub tombstones: usize,
+}
@@ -0,0 +1,7 @@
+// profile client data of a profile bundled into a single versioned archive, e.g. to move it to another device.
+
+pub mod entity;
+
+pub(crate) mod service;
+#[cfg(test)]
+mod service_test;
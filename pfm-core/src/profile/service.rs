@@ -0,0 +1,158 @@
+use anyhow::Context;
+use chrono::Utc;
+
+use super::entity::{
+    PROFILE_ARCHIVE_VERSION, ProfileArchive, ProfileContent, ProfileImportReport, ProfileSettings,
+};
+use crate::digest::interface::DigestStorage;
+use crate::error::AsInternalError;
+use crate::forex::{ForexError, ForexResult};
+use crate::forex_impl::forex_storage::checksum;
+use crate::global::{TenantId, TenantScoped};
+use crate::goals::interface::GoalsStorage;
+use crate::ledger::{
+    entity::AnonymizeOptions,
+    interface::{LedgerStorage, LedgerStorageCategoryRules, LedgerStorageDoubleEntry},
+    service as ledger_service,
+};
+use crate::sync::interface::SyncStorage;
+use crate::watchlist::{entity::DEFAULT_PROFILE, interface::WatchlistStorage};
+
+/// bundle goals, watchlist, digest subscriptions, ledger and settings of the profile into archive.
+pub async fn export_profile<PS>(storage: &PS, profile: &str) -> ForexResult<ProfileArchive>
+where
+    PS: GoalsStorage
+        + WatchlistStorage
+        + DigestStorage
+        + LedgerStorage
+        + LedgerStorageCategoryRules
+        + LedgerStorageDoubleEntry
+        + SyncStorage
+        + TenantScoped
+        + Clone,
+{
+    let profile = profile.trim();
+    let storage = profile_storage(storage, profile)?;
+
+    let content = ProfileContent {
+        goals: storage.get_goals().await?,
+        watchlist: storage.get_watchlist(profile).await?,
+        digest_subscriptions: storage.get_digest_subscriptions().await?,
+        ledger: ledger_service::export_ledger(&storage, &AnonymizeOptions::default()).await?,
+        settings: ProfileSettings {
+            last_syncs: storage.get_last_syncs().await?,
+            tombstones: storage.get_tombstones().await?,
+        },
+    };
+
+    seal(profile, content)
+}
+
+/// verify archive then store its records into the profile, replacing those with the same ids.
+pub async fn import_profile<PS>(
+    storage: &PS,
+    archive: ProfileArchive,
+) -> ForexResult<ProfileImportReport>
+where
+    PS: GoalsStorage
+        + WatchlistStorage
+        + DigestStorage
+        + LedgerStorage
+        + LedgerStorageCategoryRules
+        + LedgerStorageDoubleEntry
+        + SyncStorage
+        + TenantScoped
+        + Clone,
+{
+    verify(&archive)?;
+    let storage = profile_storage(storage, &archive.profile)?;
+
+    let content = archive.content;
+    let report = ProfileImportReport {
+        goals: content.goals.len(),
+        watchlist_pairs: content
+            .watchlist
+            .as_ref()
+            .map_or(0, |watchlist| watchlist.pairs.len()),
+        digest_subscriptions: content.digest_subscriptions.len(),
+        transactions: content.ledger.transactions.len(),
+        journal_entries: content.ledger.journal_entries.len(),
+        tombstones: content.settings.tombstones.len(),
+    };
+
+    for goal in &content.goals {
+        storage.upsert_goal(goal).await?;
+    }
+    if let Some(mut watchlist) = content.watchlist {
+        watchlist.profile = archive.profile;
+        storage.upsert_watchlist(&watchlist).await?;
+    }
+    for subscription in &content.digest_subscriptions {
+        storage.upsert_digest_subscription(subscription).await?;
+    }
+    ledger_service::import_ledger(&storage, content.ledger).await?;
+    for (peer, at) in content.settings.last_syncs {
+        storage.set_last_sync(&peer, at).await?;
+    }
+    for tombstone in &content.settings.tombstones {
+        storage.upsert_tombstone(tombstone).await?;
+    }
+
+    Ok(report)
+}
+
+/// storage of the profile's records, each profile is a tenant except the default one using unscoped storage.
+fn profile_storage<PS>(storage: &PS, profile: &str) -> ForexResult<PS>
+where
+    PS: TenantScoped + Clone,
+{
+    if profile == DEFAULT_PROFILE {
+        return Ok(storage.clone());
+    }
+    let tenant = TenantId::parse(profile).ok_or(ForexError::client_error(
+        "profile must be 1 to 64 characters of letters, digits, - or _",
+    ))?;
+
+    Ok(storage.for_tenant(&tenant))
+}
+
+/// archive of the content with its checksum.
+pub fn seal(profile: &str, content: ProfileContent) -> ForexResult<ProfileArchive> {
+    Ok(ProfileArchive {
+        version: PROFILE_ARCHIVE_VERSION,
+        profile: profile.to_string(),
+        exported_at: Utc::now(),
+        checksum: content_checksum(&content)?,
+        content,
+    })
+}
+
+/// error if archive is written by newer version, or its content doesn't match its checksum.
+pub fn verify(archive: &ProfileArchive) -> ForexResult<()> {
+    if archive.version == 0 || archive.version > PROFILE_ARCHIVE_VERSION {
+        return Err(ForexError::client_error(&format!(
+            "unsupported profile archive version {}, expected at most {}",
+            archive.version, PROFILE_ARCHIVE_VERSION
+        )));
+    }
+    if archive.profile.trim().is_empty() {
+        return Err(ForexError::client_error(
+            "profile archive has empty profile name",
+        ));
+    }
+    if content_checksum(&archive.content)? != archive.checksum {
+        return Err(ForexError::client_error(
+            "profile archive checksum mismatch, file is corrupted or modified",
+        ));
+    }
+
+    Ok(())
+}
+
+fn content_checksum(content: &ProfileContent) -> ForexResult<String> {
+    let json = serde_json::to_vec(content)
+        .context("profile archive content into json")
+        .as_internal_err()?;
+
+    Ok(checksum(&json))
+}
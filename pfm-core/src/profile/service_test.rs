@@ -0,0 +1,162 @@
+use chrono::{TimeZone, Utc};
+use rust_decimal_macros::dec;
+
+use super::entity::{PROFILE_ARCHIVE_VERSION, ProfileArchive, ProfileContent};
+use super::service::{export_profile, import_profile, seal, verify};
+use crate::forex::{Currency, Money};
+use crate::global::{TenantId, TenantScoped, test_support::temp_storage};
+use crate::goals::{entity::Goal, interface::GoalsStorage};
+use crate::ledger::{
+    entity::{LedgerArchive, Transaction},
+    interface::LedgerStorage,
+};
+use crate::sync::{
+    entity::{SyncKind, Tombstone},
+    interface::SyncStorage,
+};
+use crate::watchlist::{
+    entity::{WatchedPair, Watchlist},
+    interface::WatchlistStorage,
+};
+
+fn content() -> ProfileContent {
+    let date = Utc.with_ymd_and_hms(2025, 3, 4, 0, 0, 0).unwrap();
+    let mut watchlist = Watchlist::new("family");
+    watchlist.pairs.push(WatchedPair {
+        base: Currency::USD,
+        quote: Currency::IDR,
+        target: Some(dec!(15000)),
+    });
+
+    ProfileContent {
+        goals: vec![Goal::new(
+            "emergency fund",
+            Money::IDR(dec!(50000000.50)),
+            Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+        )],
+        watchlist: Some(watchlist),
+        digest_subscriptions: vec![],
+        ledger: LedgerArchive {
+            exported_at: date,
+            anonymized: false,
+            transactions: vec![Transaction::new(
+                date,
+                Money::USD(dec!(2000)),
+                "SALARY, ACME",
+                None,
+                "ofx",
+            )],
+            category_rules: vec![],
+            accounts: vec![],
+            journal_entries: vec![],
+        },
+        settings: Default::default(),
+    }
+}
+
+#[test]
+fn test_profile_archive_round_trip() {
+    let archive = seal("family", content()).unwrap();
+    assert_eq!(archive.version, PROFILE_ARCHIVE_VERSION);
+    assert_eq!(archive.profile, "family");
+    verify(&archive).unwrap();
+
+    // checksum holds after being written to and read from file
+    let file = serde_json::to_string_pretty(&archive).unwrap();
+    let ret: ProfileArchive = serde_json::from_str(&file).unwrap();
+    verify(&ret).unwrap();
+    assert_eq!(ret.checksum, archive.checksum);
+    assert_eq!(ret.content.goals[0].target, Money::IDR(dec!(50000000.50)));
+    assert_eq!(ret.content.watchlist.unwrap().pairs.len(), 1);
+    assert_eq!(
+        ret.content.ledger.transactions[0].hash,
+        archive.content.ledger.transactions[0].hash
+    );
+}
+
+#[test]
+fn test_profile_archive_verify() {
+    let archive = seal("family", content()).unwrap();
+
+    let mut modified = archive.clone();
+    modified.content.goals[0].target = Money::IDR(dec!(1));
+    assert!(verify(&modified).is_err());
+
+    let mut removed = archive.clone();
+    removed.content.watchlist = None;
+    assert!(verify(&removed).is_err());
+
+    let mut newer = archive.clone();
+    newer.version = PROFILE_ARCHIVE_VERSION + 1;
+    assert!(verify(&newer).is_err());
+
+    let mut unnamed = archive.clone();
+    unnamed.profile = " ".to_string();
+    assert!(verify(&unnamed).is_err());
+}
+
+#[tokio::test]
+async fn test_export_import_profile() {
+    let (device, _device_root) = temp_storage("profile-device");
+    let (other_device, _other_device_root) = temp_storage("profile-other-device");
+    let family = TenantId::parse("family").unwrap();
+    let content = content();
+    let synced_at = Utc.with_ymd_and_hms(2025, 3, 5, 0, 0, 0).unwrap();
+    let tombstone = Tombstone {
+        kind: SyncKind::Goal,
+        key: "deleted goal".to_string(),
+        deleted_at: synced_at,
+    };
+
+    let profile = device.for_tenant(&family);
+    profile.upsert_goal(&content.goals[0]).await.unwrap();
+    profile
+        .upsert_watchlist(content.watchlist.as_ref().unwrap())
+        .await
+        .unwrap();
+    profile
+        .upsert_transactions(&content.ledger.transactions)
+        .await
+        .unwrap();
+    profile.set_last_sync("server", synced_at).await.unwrap();
+    profile.upsert_tombstone(&tombstone).await.unwrap();
+    // records of other profiles are left out
+    let other_goal = Goal::new(
+        "car",
+        Money::USD(dec!(5000)),
+        Utc.with_ymd_and_hms(2027, 1, 1, 0, 0, 0).unwrap(),
+    );
+    device.upsert_goal(&other_goal).await.unwrap();
+
+    let archive = export_profile(&device, "family").await.unwrap();
+    assert_eq!(archive.content.goals.len(), 1);
+    let file = serde_json::to_string_pretty(&archive).unwrap();
+    let report = import_profile(&other_device, serde_json::from_str(&file).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(report.goals, 1);
+    assert_eq!(report.tombstones, 1);
+
+    let imported = other_device.for_tenant(&family);
+    assert_eq!(
+        imported.get_goals().await.unwrap()[0].id,
+        content.goals[0].id
+    );
+    assert_eq!(
+        imported
+            .get_watchlist("family")
+            .await
+            .unwrap()
+            .unwrap()
+            .pairs
+            .len(),
+        1
+    );
+    assert_eq!(imported.get_transactions(None).await.unwrap().len(), 1);
+    assert_eq!(
+        imported.get_last_sync("server").await.unwrap(),
+        Some(synced_at)
+    );
+    assert_eq!(imported.get_tombstones().await.unwrap(), vec![tombstone]);
+    assert!(other_device.get_goals().await.unwrap().is_empty());
+}
@@ -0,0 +1,55 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::forex::entity::{Rates, RatesResponse};
+
+/// version of bundles written by this build, newer ones can't be installed.
+pub const SEED_BUNDLE_VERSION: u32 = 1;
+
+/// How often rates are taken into seed bundle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeedInterval {
+    /// every stored date.
+    #[default]
+    Daily,
+
+    /// first stored date of each month, keeping bundle of decades small.
+    Monthly,
+}
+
+/// Historical USD based rates in a single file, checked before being installed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeedBundle {
+    pub version: u32,
+    pub interval: SeedInterval,
+    pub created_at: DateTime<Utc>,
+
+    /// hex sha256 checksum of rates as json.
+    pub checksum: String,
+
+    /// ordered by date.
+    pub rates: Vec<RatesResponse<Rates>>,
+}
+
+impl SeedBundle {
+    /// dates of the first and last rates, none if empty.
+    pub fn range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let first = self.rates.first()?;
+        let last = self.rates.last()?;
+
+        Some((first.data.date, last.data.date))
+    }
+}
+
+/// Dates installed from seed bundle, on dry run nothing is written.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SeedReport {
+    pub dry_run: bool,
+
+    /// dates written, or would be on dry run.
+    pub installed: Vec<DateTime<Utc>>,
+
+    /// dates already stored, never overwritten by bundle.
+    pub skipped: Vec<DateTime<Utc>>,
+}
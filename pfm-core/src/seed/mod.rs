@@ -0,0 +1,7 @@
+// seed bundle of historical rates published for new installs, so charts work right away without spending providers' quota.
+
+pub mod entity;
+
+pub(crate) mod service;
+#[cfg(test)]
+mod service_test;
@@ -0,0 +1,131 @@
+use std::collections::HashSet;
+
+use anyhow::Context;
+use chrono::{DateTime, Datelike, Utc};
+
+use super::entity::{SEED_BUNDLE_VERSION, SeedBundle, SeedInterval, SeedReport};
+use crate::error::AsInternalError;
+use crate::forex::{
+    Currency, ForexError, ForexResult,
+    entity::{Rates, RatesResponse},
+    interface::ForexStorage,
+};
+use crate::forex_impl::forex_storage::checksum;
+
+/// bundle stored USD based historical rates between start and end, taken at the interval.
+pub async fn build_bundle<FS>(
+    storage: &FS,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    interval: SeedInterval,
+) -> ForexResult<SeedBundle>
+where
+    FS: ForexStorage,
+{
+    if start > end {
+        return Err(ForexError::client_error(
+            "seed bundle start date is after end date",
+        ));
+    }
+
+    let mut rates: Vec<_> = storage
+        .get_historical_range(start, end)
+        .await?
+        .into_iter()
+        .filter(|rates| rates.error.is_none() && rates.data.base == Currency::USD)
+        .collect();
+    rates.sort_by_key(|rates| rates.data.date);
+    if interval == SeedInterval::Monthly {
+        rates.dedup_by_key(|rates| (rates.data.date.year(), rates.data.date.month()));
+    }
+
+    seal(interval, rates)
+}
+
+/// verify bundle then store its rates on dates not stored yet, stored rates are never overwritten.
+pub async fn install_bundle<FS>(
+    storage: &FS,
+    bundle: SeedBundle,
+    dry_run: bool,
+) -> ForexResult<SeedReport>
+where
+    FS: ForexStorage,
+{
+    verify(&bundle)?;
+
+    let mut report = SeedReport {
+        dry_run,
+        ..Default::default()
+    };
+    let Some((start, end)) = bundle.range() else {
+        return Ok(report);
+    };
+    let stored: HashSet<_> = storage
+        .get_historical_range(start, end)
+        .await?
+        .into_iter()
+        .map(|rates| rates.data.date.date_naive())
+        .collect();
+
+    let mut missing = vec![];
+    for rates in bundle.rates {
+        if stored.contains(&rates.data.date.date_naive()) {
+            report.skipped.push(rates.data.date);
+            continue;
+        }
+        report.installed.push(rates.data.date);
+        missing.push(rates);
+    }
+
+    if !dry_run && !missing.is_empty() {
+        storage.insert_historical_batch(missing).await?;
+    }
+
+    Ok(report)
+}
+
+/// bundle of the rates with their checksum.
+pub fn seal(interval: SeedInterval, rates: Vec<RatesResponse<Rates>>) -> ForexResult<SeedBundle> {
+    Ok(SeedBundle {
+        version: SEED_BUNDLE_VERSION,
+        interval,
+        created_at: Utc::now(),
+        checksum: rates_checksum(&rates)?,
+        rates,
+    })
+}
+
+/// error if bundle is written by newer version, its rates don't match its checksum or aren't USD based.
+pub fn verify(bundle: &SeedBundle) -> ForexResult<()> {
+    if bundle.version == 0 || bundle.version > SEED_BUNDLE_VERSION {
+        return Err(ForexError::client_error(&format!(
+            "unsupported seed bundle version {}, expected at most {}",
+            bundle.version, SEED_BUNDLE_VERSION
+        )));
+    }
+    if rates_checksum(&bundle.rates)? != bundle.checksum {
+        return Err(ForexError::client_error(
+            "seed bundle checksum mismatch, file is corrupted or modified",
+        ));
+    }
+    if let Some(rates) = bundle
+        .rates
+        .iter()
+        .find(|rates| rates.data.base != Currency::USD)
+    {
+        return Err(ForexError::client_error(&format!(
+            "seed bundle rates of {} are based on {}, expected USD",
+            rates.data.date, rates.data.base
+        )));
+    }
+
+    Ok(())
+}
+
+fn rates_checksum(rates: &[RatesResponse<Rates>]) -> ForexResult<String> {
+    let json = serde_json::to_vec(rates)
+        .context("seed bundle rates into json")
+        .as_internal_err()?;
+
+    Ok(checksum(&json))
+}
@@ -0,0 +1,89 @@
+use chrono::{DateTime, TimeZone, Utc};
+use rust_decimal_macros::dec;
+
+use super::entity::{SEED_BUNDLE_VERSION, SeedInterval};
+use super::service::{build_bundle, install_bundle, seal, verify};
+use crate::forex::{
+    Currency, Money,
+    entity::{Rates, RatesResponse},
+    interface::ForexStorage,
+};
+use crate::global::test_support::{self, temp_storage};
+
+fn date(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).unwrap()
+}
+
+fn rates(date: DateTime<Utc>) -> RatesResponse<Rates> {
+    test_support::rates(date, &[Money::IDR(dec!(15500))])
+}
+
+#[test]
+fn test_verify() {
+    let bundle = seal(SeedInterval::Daily, vec![rates(date(2024, 1, 1))]).unwrap();
+    assert_eq!(bundle.version, SEED_BUNDLE_VERSION);
+    verify(&bundle).unwrap();
+
+    let mut newer = bundle.clone();
+    newer.version = SEED_BUNDLE_VERSION + 1;
+    assert!(verify(&newer).is_err());
+
+    let mut modified = bundle.clone();
+    modified.rates[0]
+        .data
+        .rates
+        .set_rate(Money::new_money(Currency::IDR, dec!(1)));
+    assert!(verify(&modified).is_err());
+
+    let mut rebased = rates(date(2024, 1, 2));
+    rebased.data.base = Currency::EUR;
+    let bundle = seal(SeedInterval::Daily, vec![rebased]).unwrap();
+    assert!(verify(&bundle).is_err());
+}
+
+#[tokio::test]
+async fn test_build_and_install_bundle() {
+    let (storage, _root) = temp_storage("seed");
+
+    let bundle = seal(
+        SeedInterval::Daily,
+        vec![
+            rates(date(2024, 1, 1)),
+            rates(date(2024, 1, 2)),
+            rates(date(2024, 2, 1)),
+        ],
+    )
+    .unwrap();
+    storage
+        .insert_historical(date(2024, 1, 2), &rates(date(2024, 1, 2)))
+        .await
+        .unwrap();
+
+    let report = install_bundle(&storage, bundle.clone(), true)
+        .await
+        .unwrap();
+    assert_eq!(report.installed, vec![date(2024, 1, 1), date(2024, 2, 1)]);
+    assert_eq!(report.skipped, vec![date(2024, 1, 2)]);
+    // nothing written on dry run
+    assert!(storage.get_historical(date(2024, 1, 1)).await.is_err());
+
+    let report = install_bundle(&storage, bundle.clone(), false)
+        .await
+        .unwrap();
+    assert_eq!(report.installed.len(), 2);
+    let report = install_bundle(&storage, bundle, false).await.unwrap();
+    assert!(report.installed.is_empty());
+    assert_eq!(report.skipped.len(), 3);
+
+    let bundle = build_bundle(
+        &storage,
+        date(2024, 1, 1),
+        date(2024, 12, 31),
+        SeedInterval::Monthly,
+    )
+    .await
+    .unwrap();
+    verify(&bundle).unwrap();
+    assert_eq!(bundle.range(), Some((date(2024, 1, 1), date(2024, 2, 1))));
+    assert_eq!(bundle.rates.len(), 2);
+}
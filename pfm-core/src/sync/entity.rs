@@ -0,0 +1,210 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::goals::entity::Goal;
+use crate::watchlist::entity::Watchlist;
+
+/// Kind of synced records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncKind {
+    Goal,
+    Watchlist,
+}
+
+/// Deleted record, kept so its deletion reaches other storages instead of the record coming back.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tombstone {
+    pub kind: SyncKind,
+
+    /// id of goal or profile of watchlist.
+    pub key: String,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// Which storage of the sync.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncSide {
+    Local,
+    Remote,
+}
+
+/// Record changed in both storages since last sync, left as is until resolved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConflict {
+    pub kind: SyncKind,
+    pub key: String,
+
+    /// time of update or deletion in each storage.
+    pub local_changed_at: DateTime<Utc>,
+    pub remote_changed_at: DateTime<Utc>,
+    pub local_deleted: bool,
+    pub remote_deleted: bool,
+}
+
+/// Manual resolution of conflict, keeping the change of the side.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncResolution {
+    pub kind: SyncKind,
+    pub key: String,
+    pub keep: SyncSide,
+}
+
+/// Result of syncing two storages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub peer: String,
+
+    /// None if conflicts are left, so they are reported again on next sync.
+    pub synced_at: Option<DateTime<Utc>>,
+
+    /// records written or deleted into local storage.
+    pub pulled: usize,
+
+    /// records written or deleted into remote storage.
+    pub pushed: usize,
+    pub conflicts: Vec<SyncConflict>,
+}
+
+/// Record merged by sync.
+pub trait SyncRecord: Clone {
+    const KIND: SyncKind;
+
+    fn sync_key(&self) -> String;
+
+    fn updated_at(&self) -> DateTime<Utc>;
+}
+
+impl SyncRecord for Goal {
+    const KIND: SyncKind = SyncKind::Goal;
+
+    fn sync_key(&self) -> String {
+        self.id.to_string()
+    }
+
+    fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+}
+
+impl SyncRecord for Watchlist {
+    const KIND: SyncKind = SyncKind::Watchlist;
+
+    fn sync_key(&self) -> String {
+        self.profile.clone()
+    }
+
+    fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+}
+
+/// Record exchanged with remote storage, or its tombstone if deleted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "record", rename_all = "lowercase")]
+pub enum SyncData {
+    Goal(Goal),
+    Watchlist(Watchlist),
+    Deleted(Tombstone),
+}
+
+impl SyncData {
+    pub fn kind(&self) -> SyncKind {
+        match self {
+            Self::Goal(_) => SyncKind::Goal,
+            Self::Watchlist(_) => SyncKind::Watchlist,
+            Self::Deleted(tombstone) => tombstone.kind,
+        }
+    }
+
+    pub fn key(&self) -> String {
+        match self {
+            Self::Goal(goal) => goal.sync_key(),
+            Self::Watchlist(watchlist) => watchlist.sync_key(),
+            Self::Deleted(tombstone) => tombstone.key.clone(),
+        }
+    }
+
+    /// time of update, or deletion of tombstone.
+    pub fn changed_at(&self) -> DateTime<Utc> {
+        match self {
+            Self::Goal(goal) => goal.updated_at,
+            Self::Watchlist(watchlist) => watchlist.updated_at,
+            Self::Deleted(tombstone) => tombstone.deleted_at,
+        }
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        matches!(self, Self::Deleted(_))
+    }
+}
+
+/// Record of remote storage with revision assigned on its last change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteRecord {
+    pub revision: u64,
+    pub data: SyncData,
+}
+
+/// Records of remote storage changed after a revision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteChanges {
+    /// latest revision of remote storage.
+    pub revision: u64,
+    pub records: Vec<RemoteRecord>,
+}
+
+/// Change pushed into remote storage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemotePush {
+    /// revision of the record last pulled, 0 if never pulled.
+    pub base_revision: u64,
+    pub data: SyncData,
+}
+
+/// Result of push, changes of records changed by others since their base revision are rejected.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RemotePushReport {
+    pub accepted: Vec<RemoteRecord>,
+
+    /// current records of remote storage the rejected changes are based on.
+    pub rejected: Vec<RemoteRecord>,
+}
+
+/// Revisions of records, assigned by server and remembered by clients as last pulled.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RevisionLog {
+    /// latest revision, incremented on each change.
+    pub revision: u64,
+    pub records: Vec<RecordRevision>,
+}
+
+/// Revision of record with its change time at that revision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordRevision {
+    pub kind: SyncKind,
+    pub key: String,
+    pub revision: u64,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl RevisionLog {
+    pub fn get(&self, kind: SyncKind, key: &str) -> Option<&RecordRevision> {
+        self.records
+            .iter()
+            .find(|record| record.kind == kind && record.key == key)
+    }
+
+    /// set revision of the record, replacing the previous one.
+    pub fn set(&mut self, kind: SyncKind, key: &str, revision: u64, changed_at: DateTime<Utc>) {
+        self.records
+            .retain(|record| record.kind != kind || record.key != key);
+        self.records.push(RecordRevision {
+            kind,
+            key: key.to_string(),
+            revision,
+            changed_at,
+        });
+    }
+}
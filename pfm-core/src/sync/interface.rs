@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+
+use super::entity::{RemoteChanges, RemotePush, RemotePushReport, RevisionLog, Tombstone};
+use crate::forex::ForexResult;
+
+#[async_trait]
+pub trait SyncStorage {
+    /// get tombstones of deleted records.
+    async fn get_tombstones(&self) -> ForexResult<Vec<Tombstone>>;
+
+    /// insert tombstone or replace existing one of the same record.
+    async fn upsert_tombstone(&self, tombstone: &Tombstone) -> ForexResult<()>;
+
+    /// time of last sync completed with peer, None if never synced.
+    async fn get_last_sync(&self, peer: &str) -> ForexResult<Option<DateTime<Utc>>>;
+
+    async fn set_last_sync(&self, peer: &str, at: DateTime<Utc>) -> ForexResult<()>;
+
+    /// delete tombstones of records deleted before the time, returns number of deleted tombstones.
+    async fn delete_tombstones_before(&self, before: DateTime<Utc>) -> ForexResult<usize>;
+
+    /// time of last sync completed with each peer.
+    async fn get_last_syncs(&self) -> ForexResult<BTreeMap<String, DateTime<Utc>>>;
+}
+
+#[async_trait]
+pub trait SyncStorageRevisions {
+    /// get revision log by its name, empty if not stored yet.
+    async fn get_revision_log(&self, name: &str) -> ForexResult<RevisionLog>;
+
+    async fn set_revision_log(&self, name: &str, log: &RevisionLog) -> ForexResult<()>;
+}
+
+/// Remote storage served by pfm-http.
+#[async_trait]
+pub trait SyncRemote {
+    /// records changed after the revision.
+    async fn pull(&self, since: u64) -> ForexResult<RemoteChanges>;
+
+    async fn push(&self, pushes: &[RemotePush]) -> ForexResult<RemotePushReport>;
+}
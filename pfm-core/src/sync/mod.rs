@@ -0,0 +1,13 @@
+// sync merging client records of two storages, e.g. of laptop and desktop, by their update and deletion times.
+
+pub mod entity;
+
+pub mod interface;
+
+pub(crate) mod remote;
+#[cfg(test)]
+mod remote_test;
+
+pub(crate) mod service;
+#[cfg(test)]
+mod service_test;
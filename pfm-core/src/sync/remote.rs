@@ -0,0 +1,317 @@
+// remote.rs sync of client storage with remote storage served by pfm-http, detecting conflicts by revisions of records.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, LazyLock};
+
+use chrono::Utc;
+use tokio::sync::Mutex;
+
+use super::{
+    entity::{
+        RemoteChanges, RemotePush, RemotePushReport, RemoteRecord, RevisionLog, SyncConflict,
+        SyncData, SyncKind, SyncReport, SyncResolution, SyncSide,
+    },
+    interface::{SyncRemote, SyncStorage, SyncStorageRevisions},
+    service::{SyncChange, prune_tombstones, write_goal, write_watchlist},
+};
+use crate::forex::ForexResult;
+use crate::global::TenantId;
+use crate::goals::interface::GoalsStorage;
+use crate::watchlist::interface::WatchlistStorage;
+
+/// name of revision log of records served to clients.
+pub const SERVER_REVISION_LOG: &str = "_server";
+
+type TenantLocks = HashMap<Option<TenantId>, Arc<Mutex<()>>>;
+
+/// serializes writes of server revision log of each tenant, None for storage outside of tenants.
+static SERVER_LOCKS: LazyLock<std::sync::Mutex<TenantLocks>> = LazyLock::new(Default::default);
+
+fn server_lock(tenant: Option<&TenantId>) -> Arc<Mutex<()>> {
+    SERVER_LOCKS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .entry(tenant.cloned())
+        .or_default()
+        .clone()
+}
+
+type RecordKey = (SyncKind, String);
+
+/// current state of every record, tombstone if deleted after its last update.
+async fn current_records<S>(storage: &S) -> ForexResult<BTreeMap<RecordKey, SyncData>>
+where
+    S: GoalsStorage + WatchlistStorage + SyncStorage,
+{
+    let mut records: BTreeMap<RecordKey, SyncData> = BTreeMap::new();
+    for tombstone in storage.get_tombstones().await? {
+        records.insert(
+            (tombstone.kind, tombstone.key.clone()),
+            SyncData::Deleted(tombstone),
+        );
+    }
+    let stored = storage
+        .get_goals()
+        .await?
+        .into_iter()
+        .map(SyncData::Goal)
+        .chain(
+            storage
+                .get_watchlists()
+                .await?
+                .into_iter()
+                .map(SyncData::Watchlist),
+        );
+    for data in stored {
+        let key = (data.kind(), data.key());
+        match records.get(&key) {
+            Some(deleted) if deleted.changed_at() > data.changed_at() => {}
+            _ => {
+                records.insert(key, data);
+            }
+        }
+    }
+
+    Ok(records)
+}
+
+/// assign new revisions to records changed since logged, e.g. through admin endpoints.
+/// returns whether log is changed.
+pub(crate) fn reconcile(log: &mut RevisionLog, records: &BTreeMap<RecordKey, SyncData>) -> bool {
+    let mut changed = false;
+    for ((kind, key), data) in records {
+        let logged = log.get(*kind, key);
+        if logged.is_some_and(|logged| logged.changed_at == data.changed_at()) {
+            continue;
+        }
+        log.revision += 1;
+        log.set(*kind, key, log.revision, data.changed_at());
+        changed = true;
+    }
+
+    changed
+}
+
+/// write record into storage, deleting the stored one if data is tombstone.
+async fn write_data<S>(storage: &S, data: SyncData, stored: bool) -> ForexResult<()>
+where
+    S: GoalsStorage + WatchlistStorage + SyncStorage,
+{
+    match data {
+        SyncData::Goal(goal) => write_goal(storage, SyncChange::Upsert(goal)).await,
+        SyncData::Watchlist(watchlist) => {
+            write_watchlist(storage, SyncChange::Upsert(watchlist)).await
+        }
+        SyncData::Deleted(tombstone) => match tombstone.kind {
+            SyncKind::Goal => write_goal(storage, SyncChange::Delete { tombstone, stored }).await,
+            SyncKind::Watchlist => {
+                write_watchlist(storage, SyncChange::Delete { tombstone, stored }).await
+            }
+        },
+    }
+}
+
+/// records of server storage changed after the revision, without writing into storage.
+/// records changed since logged get revisions after the logged one until a push logs them,
+/// so they are served again after the returned revision.
+pub async fn remote_changes<S>(storage: &S, since: u64) -> ForexResult<RemoteChanges>
+where
+    S: GoalsStorage + WatchlistStorage + SyncStorage + SyncStorageRevisions,
+{
+    let records = current_records(storage).await?;
+    let mut log = storage.get_revision_log(SERVER_REVISION_LOG).await?;
+    let logged_revision = log.revision;
+    reconcile(&mut log, &records);
+
+    let mut changes: Vec<RemoteRecord> = log
+        .records
+        .iter()
+        .filter(|logged| logged.revision > since)
+        .filter_map(|logged| {
+            records
+                .get(&(logged.kind, logged.key.clone()))
+                .map(|data| RemoteRecord {
+                    revision: logged.revision,
+                    data: data.clone(),
+                })
+        })
+        .collect();
+    changes.sort_by_key(|record| record.revision);
+
+    Ok(RemoteChanges {
+        revision: logged_revision,
+        records: changes,
+    })
+}
+
+/// store pushed changes into server storage of the tenant, rejecting those not based on current revision of their records.
+/// pushes of different tenants run concurrently.
+pub async fn remote_push<S>(
+    storage: &S,
+    tenant: Option<&TenantId>,
+    pushes: Vec<RemotePush>,
+) -> ForexResult<RemotePushReport>
+where
+    S: GoalsStorage + WatchlistStorage + SyncStorage + SyncStorageRevisions,
+{
+    let lock = server_lock(tenant);
+    let _lock = lock.lock().await;
+    prune_tombstones(storage, Utc::now()).await?;
+    let mut records = current_records(storage).await?;
+    let mut log = storage.get_revision_log(SERVER_REVISION_LOG).await?;
+    reconcile(&mut log, &records);
+
+    let mut report = RemotePushReport::default();
+    for push in pushes {
+        let key = (push.data.kind(), push.data.key());
+        let current = log.get(key.0, &key.1).map_or(0, |logged| logged.revision);
+        if current != 0 && push.base_revision != current {
+            if let Some(data) = records.get(&key) {
+                report.rejected.push(RemoteRecord {
+                    revision: current,
+                    data: data.clone(),
+                });
+            }
+            continue;
+        }
+
+        let stored = records.get(&key).is_some_and(|data| !data.is_deleted());
+        write_data(storage, push.data.clone(), stored).await?;
+        log.revision += 1;
+        log.set(key.0, &key.1, log.revision, push.data.changed_at());
+        report.accepted.push(RemoteRecord {
+            revision: log.revision,
+            data: push.data.clone(),
+        });
+        records.insert(key, push.data);
+    }
+    storage.set_revision_log(SERVER_REVISION_LOG, &log).await?;
+
+    Ok(report)
+}
+
+/// pull changes of remote storage into local storage then push local changes into it.
+/// records changed locally and remotely since last pulled are reported as conflicts unless resolved,
+/// they are pulled again on next sync.
+pub async fn sync_remote<L, R>(
+    local: &L,
+    remote: &R,
+    peer: &str,
+    resolutions: &[SyncResolution],
+) -> ForexResult<SyncReport>
+where
+    L: GoalsStorage + WatchlistStorage + SyncStorage + SyncStorageRevisions,
+    R: SyncRemote,
+{
+    let started_at = Utc::now();
+    prune_tombstones(local, started_at).await?;
+    let mut log = local.get_revision_log(peer).await?;
+    let changes = remote.pull(log.revision).await?;
+    let mut records = current_records(local).await?;
+    let mut report = SyncReport {
+        peer: peer.to_string(),
+        synced_at: None,
+        pulled: 0,
+        pushed: 0,
+        conflicts: vec![],
+    };
+    let mut conflicted: BTreeSet<RecordKey> = BTreeSet::new();
+
+    for record in changes.records {
+        let key = (record.data.kind(), record.data.key());
+        let local_data = records.get(&key);
+        let last_pulled = log.get(key.0, &key.1);
+        let same = local_data.is_some_and(|data| {
+            data.changed_at() == record.data.changed_at()
+                && data.is_deleted() == record.data.is_deleted()
+        });
+        let local_changed = local_data.filter(|data| {
+            !same
+                && last_pulled.is_none_or(|last_pulled| data.changed_at() != last_pulled.changed_at)
+        });
+        let resolution = resolutions
+            .iter()
+            .find(|resolution| resolution.kind == key.0 && resolution.key == key.1)
+            .map(|resolution| resolution.keep);
+
+        match (local_changed, resolution) {
+            (None, _) | (Some(_), Some(SyncSide::Remote)) => {
+                if !same {
+                    let stored = local_data.is_some_and(|data| !data.is_deleted());
+                    write_data(local, record.data.clone(), stored).await?;
+                    report.pulled += 1;
+                }
+                log.set(key.0, &key.1, record.revision, record.data.changed_at());
+                records.insert(key, record.data);
+            }
+            // pushed below on top of remote revision
+            (Some(_), Some(SyncSide::Local)) => {
+                log.set(key.0, &key.1, record.revision, record.data.changed_at());
+            }
+            (Some(local_data), None) => {
+                report.conflicts.push(SyncConflict {
+                    kind: key.0,
+                    key: key.1.clone(),
+                    local_changed_at: local_data.changed_at(),
+                    remote_changed_at: record.data.changed_at(),
+                    local_deleted: local_data.is_deleted(),
+                    remote_deleted: record.data.is_deleted(),
+                });
+                conflicted.insert(key);
+            }
+        }
+    }
+
+    let pushes: Vec<RemotePush> = records
+        .iter()
+        .filter(|(key, _)| !conflicted.contains(*key))
+        .filter_map(|((kind, key), data)| {
+            let last_pulled = log.get(*kind, key);
+            if last_pulled.is_some_and(|last_pulled| last_pulled.changed_at == data.changed_at()) {
+                return None;
+            }
+            Some(RemotePush {
+                base_revision: last_pulled.map_or(0, |last_pulled| last_pulled.revision),
+                data: data.clone(),
+            })
+        })
+        .collect();
+    if !pushes.is_empty() {
+        let pushed = remote.push(&pushes).await?;
+        for record in pushed.accepted {
+            log.set(
+                record.data.kind(),
+                &record.data.key(),
+                record.revision,
+                record.data.changed_at(),
+            );
+            report.pushed += 1;
+        }
+        for record in pushed.rejected {
+            let key = (record.data.kind(), record.data.key());
+            let Some(local_data) = records.get(&key) else {
+                continue;
+            };
+            report.conflicts.push(SyncConflict {
+                kind: key.0,
+                key: key.1,
+                local_changed_at: local_data.changed_at(),
+                remote_changed_at: record.data.changed_at(),
+                local_deleted: local_data.is_deleted(),
+                remote_deleted: record.data.is_deleted(),
+            });
+        }
+    }
+
+    // conflicts are pulled again on next sync
+    if report.conflicts.is_empty() {
+        log.revision = changes.revision;
+    }
+    local.set_revision_log(peer, &log).await?;
+    if report.conflicts.is_empty() {
+        local.set_last_sync(peer, started_at).await?;
+        report.synced_at = Some(started_at);
+    }
+
+    Ok(report)
+}
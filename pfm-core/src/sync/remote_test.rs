@@ -0,0 +1,338 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+use super::entity::{
+    RemoteChanges, RemotePush, RemotePushReport, RevisionLog, SyncData, SyncKind, SyncResolution,
+    SyncSide, Tombstone,
+};
+use super::interface::{SyncRemote, SyncStorage, SyncStorageRevisions};
+use super::remote::{reconcile, remote_changes, remote_push, sync_remote};
+use crate::forex::{ForexError, ForexResult, Money};
+use crate::goals::{entity::Goal, interface::GoalsStorage};
+use crate::watchlist::{entity::Watchlist, interface::WatchlistStorage};
+
+#[derive(Default)]
+struct MemoryStorage {
+    goals: Mutex<Vec<Goal>>,
+    watchlists: Mutex<Vec<Watchlist>>,
+    tombstones: Mutex<Vec<Tombstone>>,
+    peers: Mutex<HashMap<String, DateTime<Utc>>>,
+    logs: Mutex<HashMap<String, RevisionLog>>,
+}
+
+#[async_trait]
+impl GoalsStorage for MemoryStorage {
+    async fn upsert_goal(&self, goal: &Goal) -> ForexResult<()> {
+        let mut goals = self.goals.lock().unwrap();
+        goals.retain(|stored| stored.id != goal.id);
+        goals.push(goal.clone());
+        Ok(())
+    }
+
+    async fn get_goal(&self, id: Uuid) -> ForexResult<Goal> {
+        self.goals
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|goal| goal.id == id)
+            .cloned()
+            .ok_or(ForexError::client_error("goal not found"))
+    }
+
+    async fn get_goals(&self) -> ForexResult<Vec<Goal>> {
+        Ok(self.goals.lock().unwrap().clone())
+    }
+
+    async fn delete_goal(&self, id: Uuid) -> ForexResult<()> {
+        self.goals.lock().unwrap().retain(|goal| goal.id != id);
+        self.upsert_tombstone(&Tombstone {
+            kind: SyncKind::Goal,
+            key: id.to_string(),
+            deleted_at: Utc::now(),
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl WatchlistStorage for MemoryStorage {
+    async fn get_watchlist(&self, profile: &str) -> ForexResult<Option<Watchlist>> {
+        Ok(self
+            .watchlists
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|watchlist| watchlist.profile == profile)
+            .cloned())
+    }
+
+    async fn upsert_watchlist(&self, watchlist: &Watchlist) -> ForexResult<()> {
+        let mut watchlists = self.watchlists.lock().unwrap();
+        watchlists.retain(|stored| stored.profile != watchlist.profile);
+        watchlists.push(watchlist.clone());
+        Ok(())
+    }
+
+    async fn delete_watchlist(&self, profile: &str) -> ForexResult<()> {
+        self.watchlists
+            .lock()
+            .unwrap()
+            .retain(|watchlist| watchlist.profile != profile);
+        Ok(())
+    }
+
+    async fn get_watchlists(&self) -> ForexResult<Vec<Watchlist>> {
+        Ok(self.watchlists.lock().unwrap().clone())
+    }
+}
+
+#[async_trait]
+impl SyncStorage for MemoryStorage {
+    async fn get_tombstones(&self) -> ForexResult<Vec<Tombstone>> {
+        Ok(self.tombstones.lock().unwrap().clone())
+    }
+
+    async fn upsert_tombstone(&self, tombstone: &Tombstone) -> ForexResult<()> {
+        let mut tombstones = self.tombstones.lock().unwrap();
+        tombstones.retain(|stored| stored.kind != tombstone.kind || stored.key != tombstone.key);
+        tombstones.push(tombstone.clone());
+        Ok(())
+    }
+
+    async fn delete_tombstones_before(&self, before: DateTime<Utc>) -> ForexResult<usize> {
+        let mut tombstones = self.tombstones.lock().unwrap();
+        let count = tombstones.len();
+        tombstones.retain(|tombstone| tombstone.deleted_at >= before);
+        Ok(count - tombstones.len())
+    }
+
+    async fn get_last_sync(&self, peer: &str) -> ForexResult<Option<DateTime<Utc>>> {
+        Ok(self.peers.lock().unwrap().get(peer).copied())
+    }
+
+    async fn set_last_sync(&self, peer: &str, at: DateTime<Utc>) -> ForexResult<()> {
+        self.peers.lock().unwrap().insert(peer.to_string(), at);
+        Ok(())
+    }
+
+    async fn get_last_syncs(&self) -> ForexResult<BTreeMap<String, DateTime<Utc>>> {
+        Ok(self
+            .peers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(peer, at)| (peer.clone(), *at))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl SyncStorageRevisions for MemoryStorage {
+    async fn get_revision_log(&self, name: &str) -> ForexResult<RevisionLog> {
+        Ok(self
+            .logs
+            .lock()
+            .unwrap()
+            .get(name)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    async fn set_revision_log(&self, name: &str, log: &RevisionLog) -> ForexResult<()> {
+        self.logs
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), log.clone());
+        Ok(())
+    }
+}
+
+/// server reached without http.
+struct DirectRemote<'a>(&'a MemoryStorage);
+
+#[async_trait]
+impl SyncRemote for DirectRemote<'_> {
+    async fn pull(&self, since: u64) -> ForexResult<RemoteChanges> {
+        remote_changes(self.0, since).await
+    }
+
+    async fn push(&self, pushes: &[RemotePush]) -> ForexResult<RemotePushReport> {
+        remote_push(self.0, None, pushes.to_vec()).await
+    }
+}
+
+/// day of the last month, so tombstones are within retention.
+fn at(day: u32) -> DateTime<Utc> {
+    let start = Utc::now().date_naive() - TimeDelta::days(31);
+    Utc.from_utc_datetime(&start.and_hms_opt(0, 0, 0).unwrap()) + TimeDelta::days(day as i64)
+}
+
+fn goal(name: &str, updated_at: DateTime<Utc>) -> Goal {
+    let mut goal = Goal::new(name, Money::IDR(dec!(1000000)), at(28));
+    goal.updated_at = updated_at;
+    goal
+}
+
+#[test]
+fn test_reconcile() {
+    let first = goal("first", at(1));
+    let mut records = BTreeMap::new();
+    records.insert(
+        (SyncKind::Goal, first.id.to_string()),
+        SyncData::Goal(first.clone()),
+    );
+
+    let mut log = RevisionLog::default();
+    assert!(reconcile(&mut log, &records));
+    assert_eq!(log.revision, 1);
+    assert!(!reconcile(&mut log, &records));
+
+    // edited without going through sync
+    let mut edited = first.clone();
+    edited.updated_at = at(2);
+    records.insert(
+        (SyncKind::Goal, first.id.to_string()),
+        SyncData::Goal(edited),
+    );
+    assert!(reconcile(&mut log, &records));
+    assert_eq!(log.revision, 2);
+    let logged = log.get(SyncKind::Goal, &first.id.to_string()).unwrap();
+    assert_eq!(logged.revision, 2);
+    assert_eq!(logged.changed_at, at(2));
+}
+
+#[tokio::test]
+async fn test_sync_remote() {
+    let server = MemoryStorage::default();
+    let laptop = MemoryStorage::default();
+    let desktop = MemoryStorage::default();
+    let remote = DirectRemote(&server);
+
+    // backed up from laptop, restored into desktop
+    let saved = goal("house", at(1));
+    laptop.upsert_goal(&saved).await.unwrap();
+    laptop
+        .upsert_watchlist(&Watchlist::new("family"))
+        .await
+        .unwrap();
+    let ret = sync_remote(&laptop, &remote, "server", &[]).await.unwrap();
+    assert_eq!(ret.pushed, 2);
+    assert!(ret.synced_at.is_some());
+    let ret = sync_remote(&desktop, &remote, "server", &[]).await.unwrap();
+    assert_eq!(ret.pulled, 2);
+    assert_eq!(ret.pushed, 0);
+    assert_eq!(desktop.get_goal(saved.id).await.unwrap().name, "house");
+
+    // nothing changed
+    let ret = sync_remote(&laptop, &remote, "server", &[]).await.unwrap();
+    assert_eq!((ret.pulled, ret.pushed), (0, 0));
+
+    // deleted on desktop reaches laptop
+    desktop.delete_watchlist("family").await.unwrap();
+    desktop
+        .upsert_tombstone(&Tombstone {
+            kind: SyncKind::Watchlist,
+            key: "family".to_string(),
+            deleted_at: at(3),
+        })
+        .await
+        .unwrap();
+    sync_remote(&desktop, &remote, "server", &[]).await.unwrap();
+    let ret = sync_remote(&laptop, &remote, "server", &[]).await.unwrap();
+    assert_eq!(ret.pulled, 1);
+    assert!(laptop.get_watchlist("family").await.unwrap().is_none());
+
+    // edited on both since last pulled
+    let mut on_laptop = saved.clone();
+    on_laptop.name = "bigger house".to_string();
+    on_laptop.updated_at = at(4);
+    laptop.upsert_goal(&on_laptop).await.unwrap();
+    let mut on_desktop = saved.clone();
+    on_desktop.name = "apartment".to_string();
+    on_desktop.updated_at = at(5);
+    desktop.upsert_goal(&on_desktop).await.unwrap();
+    sync_remote(&desktop, &remote, "server", &[]).await.unwrap();
+
+    let ret = sync_remote(&laptop, &remote, "server", &[]).await.unwrap();
+    assert_eq!(ret.conflicts.len(), 1);
+    assert_eq!(ret.conflicts[0].key, saved.id.to_string());
+    assert_eq!(ret.conflicts[0].local_changed_at, at(4));
+    assert_eq!(ret.conflicts[0].remote_changed_at, at(5));
+    assert!(ret.synced_at.is_none());
+    assert_eq!(
+        laptop.get_goal(saved.id).await.unwrap().name,
+        "bigger house"
+    );
+
+    // reported again until resolved
+    let ret = sync_remote(&laptop, &remote, "server", &[]).await.unwrap();
+    assert_eq!(ret.conflicts.len(), 1);
+
+    let keep_laptop = SyncResolution {
+        kind: SyncKind::Goal,
+        key: saved.id.to_string(),
+        keep: SyncSide::Local,
+    };
+    let ret = sync_remote(&laptop, &remote, "server", &[keep_laptop])
+        .await
+        .unwrap();
+    assert!(ret.conflicts.is_empty());
+    assert_eq!(ret.pushed, 1);
+    sync_remote(&desktop, &remote, "server", &[]).await.unwrap();
+    assert_eq!(
+        desktop.get_goal(saved.id).await.unwrap().name,
+        "bigger house"
+    );
+}
+
+#[tokio::test]
+async fn test_remote_push_rejected() {
+    let server = MemoryStorage::default();
+    let saved = goal("house", at(1));
+    let ret = remote_push(
+        &server,
+        None,
+        vec![RemotePush {
+            base_revision: 0,
+            data: SyncData::Goal(saved.clone()),
+        }],
+    )
+    .await
+    .unwrap();
+    assert_eq!(ret.accepted.len(), 1);
+    let revision = ret.accepted[0].revision;
+
+    let mut stale = saved.clone();
+    stale.updated_at = at(2);
+    let mut current = saved.clone();
+    current.updated_at = at(3);
+    let ret = remote_push(
+        &server,
+        None,
+        vec![
+            RemotePush {
+                base_revision: revision,
+                data: SyncData::Goal(current),
+            },
+            RemotePush {
+                base_revision: revision,
+                data: SyncData::Goal(stale),
+            },
+        ],
+    )
+    .await
+    .unwrap();
+    assert_eq!(ret.accepted.len(), 1);
+    assert_eq!(ret.rejected.len(), 1);
+    assert_eq!(ret.rejected[0].revision, revision + 1);
+    assert_eq!(ret.rejected[0].data.changed_at(), at(3));
+
+    let ret = remote_changes(&server, revision).await.unwrap();
+    assert_eq!(ret.revision, revision + 1);
+    assert_eq!(ret.records.len(), 1);
+}
@@ -0,0 +1,315 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Context;
+use chrono::{DateTime, TimeDelta, Utc};
+
+use super::{
+    entity::{SyncConflict, SyncRecord, SyncReport, SyncResolution, SyncSide, Tombstone},
+    interface::SyncStorage,
+};
+use crate::error::AsInternalError;
+use crate::forex::ForexResult;
+use crate::goals::{entity::Goal, interface::GoalsStorage};
+use crate::watchlist::{entity::Watchlist, interface::WatchlistStorage};
+
+/// tombstones are kept this long after their deletion,
+/// records deleted before last sync of a peer syncing less often come back to it.
+pub const TOMBSTONE_RETENTION_DAYS: i64 = 90;
+
+/// merge goals and watchlists of both storages, newer change of a record wins.
+/// other client records, e.g. ledger, have no update times to merge by, they are moved with profile export.
+/// records changed in both since last sync with peer are reported as conflicts unless resolved,
+/// last sync is only moved forward once no conflict is left.
+pub async fn sync<L, R>(
+    local: &L,
+    remote: &R,
+    peer: &str,
+    resolutions: &[SyncResolution],
+) -> ForexResult<SyncReport>
+where
+    L: GoalsStorage + WatchlistStorage + SyncStorage,
+    R: GoalsStorage + WatchlistStorage + SyncStorage,
+{
+    let started_at = Utc::now();
+    prune_tombstones(local, started_at).await?;
+    prune_tombstones(remote, started_at).await?;
+    let last_sync = match (
+        local.get_last_sync(peer).await?,
+        remote.get_last_sync(peer).await?,
+    ) {
+        (Some(local), Some(remote)) => Some(local.min(remote)),
+        _ => None,
+    };
+    let local_tombstones = local.get_tombstones().await?;
+    let remote_tombstones = remote.get_tombstones().await?;
+    let mut report = SyncReport {
+        peer: peer.to_string(),
+        synced_at: None,
+        pulled: 0,
+        pushed: 0,
+        conflicts: vec![],
+    };
+
+    let (changes, conflicts) = plan(
+        &local.get_goals().await?,
+        &remote.get_goals().await?,
+        &local_tombstones,
+        &remote_tombstones,
+        last_sync,
+        resolutions,
+    );
+    report.conflicts.extend(conflicts);
+    for (side, change) in changes {
+        match side {
+            SyncSide::Local => {
+                write_goal(local, change).await?;
+                report.pulled += 1;
+            }
+            SyncSide::Remote => {
+                write_goal(remote, change).await?;
+                report.pushed += 1;
+            }
+        }
+    }
+
+    let (changes, conflicts) = plan(
+        &local.get_watchlists().await?,
+        &remote.get_watchlists().await?,
+        &local_tombstones,
+        &remote_tombstones,
+        last_sync,
+        resolutions,
+    );
+    report.conflicts.extend(conflicts);
+    for (side, change) in changes {
+        match side {
+            SyncSide::Local => {
+                write_watchlist(local, change).await?;
+                report.pulled += 1;
+            }
+            SyncSide::Remote => {
+                write_watchlist(remote, change).await?;
+                report.pushed += 1;
+            }
+        }
+    }
+
+    if report.conflicts.is_empty() {
+        local.set_last_sync(peer, started_at).await?;
+        remote.set_last_sync(peer, started_at).await?;
+        report.synced_at = Some(started_at);
+    }
+
+    Ok(report)
+}
+
+/// delete tombstones older than retention.
+pub(crate) async fn prune_tombstones<S>(storage: &S, now: DateTime<Utc>) -> ForexResult<()>
+where
+    S: SyncStorage,
+{
+    let deleted = storage
+        .delete_tombstones_before(now - TimeDelta::days(TOMBSTONE_RETENTION_DAYS))
+        .await?;
+    if deleted > 0 {
+        tracing::info!("sync deleted {} tombstones past retention", deleted);
+    }
+
+    Ok(())
+}
+
+/// Change written into one storage.
+#[derive(Debug, Clone)]
+pub(crate) enum SyncChange<T> {
+    Upsert(T),
+
+    /// delete record if the storage has it, and keep its tombstone.
+    Delete {
+        tombstone: Tombstone,
+        stored: bool,
+    },
+}
+
+/// latest change of a record in one storage.
+enum RecordState<'a, T> {
+    Record(&'a T),
+    Deleted(&'a Tombstone),
+}
+
+impl<'a, T: SyncRecord> RecordState<'a, T> {
+    fn new(record: Option<&'a T>, tombstone: Option<&'a Tombstone>) -> Option<Self> {
+        match (record, tombstone) {
+            (Some(record), Some(tombstone)) if tombstone.deleted_at > record.updated_at() => {
+                Some(Self::Deleted(tombstone))
+            }
+            (Some(record), _) => Some(Self::Record(record)),
+            (None, Some(tombstone)) => Some(Self::Deleted(tombstone)),
+            (None, None) => None,
+        }
+    }
+
+    fn changed_at(&self) -> DateTime<Utc> {
+        match self {
+            Self::Record(record) => record.updated_at(),
+            Self::Deleted(tombstone) => tombstone.deleted_at,
+        }
+    }
+
+    fn is_deleted(&self) -> bool {
+        matches!(self, Self::Deleted(_))
+    }
+
+    /// both deleted, or updated at the same time.
+    fn is_same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Deleted(_), Self::Deleted(_)) => true,
+            (Self::Record(record), Self::Record(other)) => {
+                record.updated_at() == other.updated_at()
+            }
+            _ => false,
+        }
+    }
+
+    /// change applying this state to storage, stored if it has the record.
+    fn change(&self, stored: bool) -> SyncChange<T> {
+        match self {
+            Self::Record(record) => SyncChange::Upsert((*record).clone()),
+            Self::Deleted(tombstone) => SyncChange::Delete {
+                tombstone: (*tombstone).clone(),
+                stored,
+            },
+        }
+    }
+}
+
+/// changes into each side merging records of kind T of both sides, with unresolved conflicts.
+pub(crate) fn plan<T: SyncRecord>(
+    local: &[T],
+    remote: &[T],
+    local_tombstones: &[Tombstone],
+    remote_tombstones: &[Tombstone],
+    last_sync: Option<DateTime<Utc>>,
+    resolutions: &[SyncResolution],
+) -> (Vec<(SyncSide, SyncChange<T>)>, Vec<SyncConflict>) {
+    let records = |records: &[T]| -> BTreeMap<String, T> {
+        records
+            .iter()
+            .map(|record| (record.sync_key(), record.clone()))
+            .collect()
+    };
+    let tombstones = |tombstones: &[Tombstone]| -> BTreeMap<String, Tombstone> {
+        tombstones
+            .iter()
+            .filter(|tombstone| tombstone.kind == T::KIND)
+            .map(|tombstone| (tombstone.key.clone(), tombstone.clone()))
+            .collect()
+    };
+    let (local, remote) = (records(local), records(remote));
+    let (local_tombstones, remote_tombstones) =
+        (tombstones(local_tombstones), tombstones(remote_tombstones));
+    let keys: BTreeSet<&String> = local
+        .keys()
+        .chain(remote.keys())
+        .chain(local_tombstones.keys())
+        .chain(remote_tombstones.keys())
+        .collect();
+    let changed_since_sync = |at: DateTime<Utc>| last_sync.is_none_or(|last_sync| at > last_sync);
+
+    let mut changes = vec![];
+    let mut conflicts = vec![];
+    for key in keys {
+        let local_state = RecordState::new(local.get(key), local_tombstones.get(key));
+        let remote_state = RecordState::new(remote.get(key), remote_tombstones.get(key));
+        let (local_state, remote_state) = match (local_state, remote_state) {
+            (Some(local_state), None) => {
+                changes.push((SyncSide::Remote, local_state.change(false)));
+                continue;
+            }
+            (None, Some(remote_state)) => {
+                changes.push((SyncSide::Local, remote_state.change(false)));
+                continue;
+            }
+            (Some(local_state), Some(remote_state)) => (local_state, remote_state),
+            (None, None) => continue,
+        };
+        if local_state.is_same(&remote_state) {
+            continue;
+        }
+
+        let keep = if changed_since_sync(local_state.changed_at())
+            && changed_since_sync(remote_state.changed_at())
+        {
+            let resolution = resolutions
+                .iter()
+                .find(|resolution| resolution.kind == T::KIND && &resolution.key == key);
+            match resolution {
+                Some(resolution) => resolution.keep,
+                None => {
+                    conflicts.push(SyncConflict {
+                        kind: T::KIND,
+                        key: key.clone(),
+                        local_changed_at: local_state.changed_at(),
+                        remote_changed_at: remote_state.changed_at(),
+                        local_deleted: local_state.is_deleted(),
+                        remote_deleted: remote_state.is_deleted(),
+                    });
+                    continue;
+                }
+            }
+        } else if local_state.changed_at() > remote_state.changed_at() {
+            SyncSide::Local
+        } else {
+            SyncSide::Remote
+        };
+        match keep {
+            SyncSide::Local => changes.push((
+                SyncSide::Remote,
+                local_state.change(remote.contains_key(key)),
+            )),
+            SyncSide::Remote => changes.push((
+                SyncSide::Local,
+                remote_state.change(local.contains_key(key)),
+            )),
+        }
+    }
+
+    (changes, conflicts)
+}
+
+pub(crate) async fn write_goal<S>(storage: &S, change: SyncChange<Goal>) -> ForexResult<()>
+where
+    S: GoalsStorage + SyncStorage,
+{
+    match change {
+        SyncChange::Upsert(goal) => storage.upsert_goal(&goal).await,
+        SyncChange::Delete { tombstone, stored } => {
+            if stored {
+                let id = tombstone
+                    .key
+                    .parse()
+                    .context("sync goal tombstone key into id")
+                    .as_internal_err()?;
+                storage.delete_goal(id).await?;
+            }
+            storage.upsert_tombstone(&tombstone).await
+        }
+    }
+}
+
+pub(crate) async fn write_watchlist<S>(
+    storage: &S,
+    change: SyncChange<Watchlist>,
+) -> ForexResult<()>
+where
+    S: WatchlistStorage + SyncStorage,
+{
+    match change {
+        SyncChange::Upsert(watchlist) => storage.upsert_watchlist(&watchlist).await,
+        SyncChange::Delete { tombstone, stored } => {
+            if stored {
+                storage.delete_watchlist(&tombstone.key).await?;
+            }
+            storage.upsert_tombstone(&tombstone).await
+        }
+    }
+}
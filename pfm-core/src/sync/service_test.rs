@@ -0,0 +1,299 @@
+use chrono::{DateTime, TimeZone, Utc};
+use rust_decimal_macros::dec;
+
+use super::entity::{SyncKind, SyncResolution, SyncSide, Tombstone};
+use super::interface::SyncStorage;
+use super::service::{SyncChange, TOMBSTONE_RETENTION_DAYS, plan, sync};
+use crate::forex::Money;
+use crate::global::test_support::temp_storage;
+use crate::goals::{entity::Goal, interface::GoalsStorage};
+use crate::watchlist::entity::Watchlist;
+
+fn at(day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 3, day, 0, 0, 0).unwrap()
+}
+
+fn goal(name: &str, updated_at: DateTime<Utc>) -> Goal {
+    let mut goal = Goal::new(name, Money::IDR(dec!(1000000)), at(28));
+    goal.updated_at = updated_at;
+    goal
+}
+
+fn tombstone(goal: &Goal, deleted_at: DateTime<Utc>) -> Tombstone {
+    Tombstone {
+        kind: SyncKind::Goal,
+        key: goal.id.to_string(),
+        deleted_at,
+    }
+}
+
+#[test]
+fn test_plan_without_conflicts() {
+    let only_local = goal("laptop", at(5));
+    let only_remote = goal("desktop", at(5));
+    let unchanged = goal("unchanged", at(1));
+    let mut edited = goal("edited", at(1));
+    let deleted = goal("deleted", at(1));
+
+    let local = vec![
+        only_local.clone(),
+        unchanged.clone(),
+        edited.clone(),
+        deleted.clone(),
+    ];
+    edited.updated_at = at(6);
+    let remote = vec![only_remote.clone(), unchanged.clone(), edited.clone()];
+    let remote_tombstones = vec![tombstone(&deleted, at(7))];
+
+    let (changes, conflicts) = plan(&local, &remote, &[], &remote_tombstones, Some(at(2)), &[]);
+    assert!(conflicts.is_empty());
+    assert_eq!(changes.len(), 4);
+
+    let change = |goal: &Goal| {
+        let key = goal.id.to_string();
+        changes
+            .iter()
+            .find(|(_, change)| match change {
+                SyncChange::Upsert(goal) => goal.id.to_string() == key,
+                SyncChange::Delete { tombstone, .. } => tombstone.key == key,
+            })
+            .unwrap()
+    };
+    assert!(matches!(
+        change(&only_local),
+        (SyncSide::Remote, SyncChange::Upsert(_))
+    ));
+    assert!(matches!(
+        change(&only_remote),
+        (SyncSide::Local, SyncChange::Upsert(_))
+    ));
+    assert!(matches!(
+        change(&edited),
+        (SyncSide::Local, SyncChange::Upsert(goal)) if goal.updated_at == at(6)
+    ));
+    assert!(matches!(
+        change(&deleted),
+        (SyncSide::Local, SyncChange::Delete { stored: true, tombstone }) if tombstone.deleted_at == at(7)
+    ));
+}
+
+#[test]
+fn test_plan_tombstone_propagated() {
+    let deleted = goal("deleted", at(1));
+    let local_tombstones = vec![tombstone(&deleted, at(3))];
+
+    let (changes, conflicts) = plan::<Goal>(&[], &[], &local_tombstones, &[], None, &[]);
+    assert!(conflicts.is_empty());
+    assert!(matches!(
+        &changes[..],
+        [(SyncSide::Remote, SyncChange::Delete { stored: false, .. })]
+    ));
+
+    // deleted in both
+    let (changes, conflicts) = plan::<Goal>(
+        &[],
+        &[],
+        &local_tombstones,
+        &[tombstone(&deleted, at(4))],
+        None,
+        &[],
+    );
+    assert!(changes.is_empty());
+    assert!(conflicts.is_empty());
+
+    // recreated after being deleted
+    let recreated = goal("recreated", at(5));
+    let (changes, _) = plan(
+        std::slice::from_ref(&recreated),
+        &[],
+        &[tombstone(&recreated, at(3))],
+        &[],
+        None,
+        &[],
+    );
+    assert!(matches!(
+        &changes[..],
+        [(SyncSide::Remote, SyncChange::Upsert(_))]
+    ));
+}
+
+#[test]
+fn test_plan_conflicts() {
+    let mut both = goal("both", at(3));
+    let local = vec![both.clone()];
+    both.updated_at = at(4);
+    let remote = vec![both.clone()];
+    let deleted = goal("deleted", at(3));
+    let local_tombstones = vec![tombstone(&deleted, at(5))];
+    let remote_with_deleted = vec![both.clone(), deleted.clone()];
+
+    let (changes, conflicts) = plan(
+        &local,
+        &remote_with_deleted,
+        &local_tombstones,
+        &[],
+        Some(at(2)),
+        &[],
+    );
+    assert!(changes.is_empty());
+    assert_eq!(conflicts.len(), 2);
+    let deleted_conflict = conflicts
+        .iter()
+        .find(|conflict| conflict.key == deleted.id.to_string())
+        .unwrap();
+    assert!(deleted_conflict.local_deleted);
+    assert!(!deleted_conflict.remote_deleted);
+    assert_eq!(deleted_conflict.local_changed_at, at(5));
+    assert_eq!(deleted_conflict.remote_changed_at, at(3));
+
+    // first sync can't tell which changed
+    let (_, conflicts) = plan(&local, &remote, &[], &[], None, &[]);
+    assert_eq!(conflicts.len(), 1);
+
+    // only remote changed since last sync
+    let (changes, conflicts) = plan(&local, &remote, &[], &[], Some(at(3)), &[]);
+    assert!(conflicts.is_empty());
+    assert!(matches!(
+        &changes[..],
+        [(SyncSide::Local, SyncChange::Upsert(_))]
+    ));
+
+    let resolutions = vec![
+        SyncResolution {
+            kind: SyncKind::Goal,
+            key: both.id.to_string(),
+            keep: SyncSide::Local,
+        },
+        SyncResolution {
+            kind: SyncKind::Goal,
+            key: deleted.id.to_string(),
+            keep: SyncSide::Remote,
+        },
+    ];
+    let (changes, conflicts) = plan(
+        &local,
+        &remote_with_deleted,
+        &local_tombstones,
+        &[],
+        Some(at(2)),
+        &resolutions,
+    );
+    assert!(conflicts.is_empty());
+    assert_eq!(changes.len(), 2);
+    assert!(changes.iter().any(|change| matches!(
+        change,
+        (SyncSide::Remote, SyncChange::Upsert(goal)) if goal.updated_at == at(3)
+    )));
+    assert!(changes.iter().any(|change| matches!(
+        change,
+        (SyncSide::Local, SyncChange::Upsert(goal)) if goal.name == "deleted"
+    )));
+}
+
+#[test]
+fn test_plan_kind_of_tombstones() {
+    let watchlist = Watchlist::new("family");
+    let goal_tombstone = Tombstone {
+        kind: SyncKind::Goal,
+        key: "family".to_string(),
+        deleted_at: Utc::now(),
+    };
+
+    // goal tombstone doesn't delete watchlist of the same key
+    let (changes, conflicts) = plan(
+        std::slice::from_ref(&watchlist),
+        std::slice::from_ref(&watchlist),
+        &[goal_tombstone],
+        &[],
+        None,
+        &[],
+    );
+    assert!(changes.is_empty());
+    assert!(conflicts.is_empty());
+}
+
+#[tokio::test]
+async fn test_sync_storages() {
+    let (laptop, _laptop_root) = temp_storage("sync-laptop");
+    let (desktop, _desktop_root) = temp_storage("sync-desktop");
+    let names = |goals: Vec<Goal>| {
+        let mut names: Vec<String> = goals.into_iter().map(|goal| goal.name).collect();
+        names.sort();
+        names
+    };
+    let edited = |goal: &Goal, name: &str| {
+        let mut goal = goal.clone();
+        goal.name = name.to_string();
+        goal.updated_at = Utc::now();
+        goal
+    };
+
+    let house = goal("house", Utc::now());
+    let car = goal("car", Utc::now());
+    let trip = goal("trip", Utc::now());
+    laptop.upsert_goal(&house).await.unwrap();
+    laptop.upsert_goal(&car).await.unwrap();
+    desktop.upsert_goal(&trip).await.unwrap();
+    // tombstone past retention
+    let expired = tombstone(
+        &goal("old", Utc::now()),
+        Utc::now() - chrono::TimeDelta::days(TOMBSTONE_RETENTION_DAYS + 1),
+    );
+    laptop.upsert_tombstone(&expired).await.unwrap();
+
+    let report = sync(&laptop, &desktop, "desktop", &[]).await.unwrap();
+    assert!(report.synced_at.is_some());
+    assert_eq!((report.pulled, report.pushed), (1, 2));
+    assert_eq!(
+        names(laptop.get_goals().await.unwrap()),
+        ["car", "house", "trip"]
+    );
+    assert_eq!(
+        names(desktop.get_goals().await.unwrap()),
+        ["car", "house", "trip"]
+    );
+    assert!(laptop.get_tombstones().await.unwrap().is_empty());
+
+    // deleted and edited on either side, house edited on both
+    laptop.delete_goal(car.id).await.unwrap();
+    desktop
+        .upsert_goal(&edited(&trip, "long trip"))
+        .await
+        .unwrap();
+    laptop
+        .upsert_goal(&edited(&house, "laptop house"))
+        .await
+        .unwrap();
+    desktop
+        .upsert_goal(&edited(&house, "desktop house"))
+        .await
+        .unwrap();
+
+    let report = sync(&laptop, &desktop, "desktop", &[]).await.unwrap();
+    assert!(report.synced_at.is_none());
+    assert_eq!(report.conflicts.len(), 1);
+    assert_eq!(report.conflicts[0].key, house.id.to_string());
+    assert_eq!(
+        names(laptop.get_goals().await.unwrap()),
+        ["laptop house", "long trip"]
+    );
+    assert_eq!(
+        names(desktop.get_goals().await.unwrap()),
+        ["desktop house", "long trip"]
+    );
+
+    let resolutions = [SyncResolution {
+        kind: SyncKind::Goal,
+        key: house.id.to_string(),
+        keep: SyncSide::Local,
+    }];
+    let report = sync(&laptop, &desktop, "desktop", &resolutions)
+        .await
+        .unwrap();
+    assert!(report.synced_at.is_some());
+    assert!(report.conflicts.is_empty());
+    assert_eq!(
+        names(desktop.get_goals().await.unwrap()),
+        ["laptop house", "long trip"]
+    );
+}
@@ -0,0 +1,61 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::forex::{Currency, CurrencyKind, Money};
+
+/// Disposal of an asset, e.g. selling crypto for fiat.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sale {
+    /// amount of asset sold, e.g. BTC 0.5.
+    pub asset: Money,
+
+    pub acquired: DateTime<Utc>,
+
+    /// paid for the asset when acquired.
+    pub cost: Money,
+
+    pub sold: DateTime<Utc>,
+
+    /// received for the asset when sold.
+    pub proceeds: Money,
+}
+
+/// Sale valued in tax currency at rates of its transaction dates, loss is negative gain.
+/// amounts are none when the sale is unvalued.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealizedGain {
+    pub asset: Money,
+    pub acquired: DateTime<Utc>,
+    pub sold: DateTime<Utc>,
+    pub proceeds: Option<Decimal>,
+    pub cost: Option<Decimal>,
+    pub gain: Option<Decimal>,
+
+    /// why the sale couldn't be valued, e.g. no rates on its dates.
+    pub unvalued: Option<String>,
+}
+
+/// Realized gains of sales of assets of the same class, totals exclude unvalued sales.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetClassGains {
+    pub class: CurrencyKind,
+    pub proceeds: Decimal,
+    pub cost: Decimal,
+    pub gain: Decimal,
+
+    /// ordered by date sold.
+    pub sales: Vec<RealizedGain>,
+}
+
+/// Realized gains of sales within a fiscal year in tax currency, grouped per asset class.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxReport {
+    /// first and last day of the fiscal year, inclusive.
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+
+    pub currency: Currency,
+    pub classes: Vec<AssetClassGains>,
+    pub gain: Decimal,
+}
@@ -0,0 +1,7 @@
+// tax reports of realized gains of assets, e.g. crypto and precious metals, in currency of tax authority.
+
+pub mod entity;
+
+pub(crate) mod service;
+#[cfg(test)]
+mod service_test;
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use chrono::{DateTime, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use strum::IntoEnumIterator;
+
+use super::entity::{AssetClassGains, RealizedGain, Sale, TaxReport};
+use crate::error::AsInternalError;
+use crate::forex::{
+    Currency, CurrencyKind, ForexError, ForexResult, Money, interface::ForexStorage,
+    service as forex_service,
+};
+
+/// realized gains of sales sold within the fiscal year starting at the date, in the tax currency.
+/// proceeds are converted at rates of the date sold and costs at rates of the date acquired.
+/// sales without rates on their dates are reported unvalued instead of failing the report.
+pub async fn tax_report<FS>(
+    storage: &FS,
+    sales: &[Sale],
+    fiscal_year_start: NaiveDate,
+    currency: Currency,
+) -> ForexResult<TaxReport>
+where
+    FS: ForexStorage,
+{
+    let Some(end) = fiscal_year_start
+        .checked_add_months(Months::new(12))
+        .and_then(|next| next.pred_opt())
+    else {
+        return Err(ForexError::client_error("fiscal year is out of range"));
+    };
+
+    let mut sales: Vec<&Sale> = sales
+        .iter()
+        .filter(|sale| (fiscal_year_start..=end).contains(&sale.sold.date_naive()))
+        .collect();
+    sales.sort_by_key(|sale| sale.sold);
+
+    let mut by_class: HashMap<CurrencyKind, AssetClassGains> = HashMap::new();
+    for sale in sales {
+        if sale.acquired > sale.sold {
+            return Err(ForexError::client_error(&format!(
+                "sale of {} is acquired after sold",
+                sale.asset
+            )));
+        }
+        let valued = async {
+            let proceeds = value_in(storage, sale.proceeds, sale.sold, currency).await?;
+            let cost = value_in(storage, sale.cost, sale.acquired, currency).await?;
+            Ok::<_, ForexError>((proceeds, cost))
+        }
+        .await;
+        let (proceeds, cost, unvalued) = match valued {
+            Ok((proceeds, cost)) => (Some(proceeds), Some(cost), None),
+            Err(err @ ForexError::ClientError(_)) => (None, None, Some(err.message())),
+            Err(err) => return Err(err),
+        };
+        let gain = proceeds.zip(cost).map(|(proceeds, cost)| proceeds - cost);
+        let class = sale.asset.currency().kind();
+        let gains = by_class.entry(class).or_insert_with(|| AssetClassGains {
+            class,
+            proceeds: Decimal::ZERO,
+            cost: Decimal::ZERO,
+            gain: Decimal::ZERO,
+            sales: vec![],
+        });
+        gains.proceeds += proceeds.unwrap_or_default();
+        gains.cost += cost.unwrap_or_default();
+        gains.gain += gain.unwrap_or_default();
+        gains.sales.push(RealizedGain {
+            asset: sale.asset,
+            acquired: sale.acquired,
+            sold: sale.sold,
+            proceeds,
+            cost,
+            gain,
+            unvalued,
+        });
+    }
+
+    let classes: Vec<AssetClassGains> = CurrencyKind::iter()
+        .filter_map(|class| by_class.remove(&class))
+        .collect();
+    Ok(TaxReport {
+        start: fiscal_year_start,
+        end,
+        currency,
+        gain: classes.iter().map(|class| class.gain).sum(),
+        classes,
+    })
+}
+
+/// amount of money in the currency at rates of the date.
+async fn value_in<FS>(
+    storage: &FS,
+    money: Money,
+    date: DateTime<Utc>,
+    currency: Currency,
+) -> ForexResult<Decimal>
+where
+    FS: ForexStorage,
+{
+    if money.currency() == currency {
+        return Ok(money.amount());
+    }
+    let rate = forex_service::rate_on(storage, date, money.currency(), currency).await?;
+
+    Ok(money.amount() * rate)
+}
+
+/// tax report as csv with columns of capital gains forms, e.g. IRS form 8949.
+/// a row per sale followed by total of its asset class, amounts are rounded to 2 decimal places.
+/// unvalued sales have empty amounts.
+pub fn tax_report_csv(report: &TaxReport) -> ForexResult<String> {
+    let mut wtr = csv::Writer::from_writer(vec![]);
+    wtr.write_record([
+        "asset class",
+        "description of property",
+        "date acquired",
+        "date sold",
+        "proceeds",
+        "cost or other basis",
+        "gain or (loss)",
+    ])
+    .context("tax report csv write header")
+    .as_internal_err()?;
+
+    let amount = |amount: Decimal| amount.round_dp(2).to_string();
+    let date = |date: DateTime<Utc>| date.format("%Y-%m-%d").to_string();
+    for class in &report.classes {
+        let class_name = format!("{:?}", class.class).to_lowercase();
+        for sale in &class.sales {
+            wtr.write_record([
+                class_name.clone(),
+                sale.asset.to_string(),
+                date(sale.acquired),
+                date(sale.sold),
+                sale.proceeds.map(amount).unwrap_or_default(),
+                sale.cost.map(amount).unwrap_or_default(),
+                sale.gain.map(amount).unwrap_or_default(),
+            ])
+            .context("tax report csv write sale")
+            .as_internal_err()?;
+        }
+        wtr.write_record([
+            class_name,
+            format!("total in {}", report.currency.code()),
+            String::new(),
+            String::new(),
+            amount(class.proceeds),
+            amount(class.cost),
+            amount(class.gain),
+        ])
+        .context("tax report csv write total")
+        .as_internal_err()?;
+    }
+
+    let bytes = wtr
+        .into_inner()
+        .context("tax report csv flush")
+        .as_internal_err()?;
+    let content = String::from_utf8(bytes)
+        .context("tax report csv into string")
+        .as_internal_err()?;
+
+    Ok(content)
+}
@@ -0,0 +1,103 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use rust_decimal_macros::dec;
+
+use super::{
+    entity::Sale,
+    service::{tax_report, tax_report_csv},
+};
+use crate::forex::{Currency, CurrencyKind, Money};
+
+#[tokio::test]
+async fn test_tax_report() {
+    let storage = crate::forex::mock::ForexStorageSuccessMock;
+    let sales = vec![
+        Sale {
+            asset: Money::new_money(Currency::BTC, dec!(0.1)),
+            acquired: Utc.with_ymd_and_hms(2021, 12, 21, 0, 0, 0).unwrap(),
+            cost: Money::new_money(Currency::USD, dec!(4000)),
+            sold: Utc.with_ymd_and_hms(2022, 12, 26, 10, 0, 0).unwrap(),
+            proceeds: Money::new_money(Currency::USD, dec!(1700)),
+        },
+        Sale {
+            asset: Money::new_money(Currency::XAU, dec!(1)),
+            acquired: Utc.with_ymd_and_hms(2021, 7, 8, 0, 0, 0).unwrap(),
+            cost: Money::new_money(Currency::IDR, dec!(26000000)),
+            sold: Utc.with_ymd_and_hms(2022, 12, 26, 12, 0, 0).unwrap(),
+            proceeds: Money::new_money(Currency::USD, dec!(1800)),
+        },
+        // outside of the fiscal year
+        Sale {
+            asset: Money::new_money(Currency::ETH, dec!(1)),
+            acquired: Utc.with_ymd_and_hms(2021, 12, 21, 0, 0, 0).unwrap(),
+            cost: Money::new_money(Currency::USD, dec!(4000)),
+            sold: Utc.with_ymd_and_hms(2025, 3, 4, 12, 0, 0).unwrap(),
+            proceeds: Money::new_money(Currency::USD, dec!(2200)),
+        },
+    ];
+
+    let fiscal_year_start = NaiveDate::from_ymd_opt(2022, 4, 1).unwrap();
+    let ret = tax_report(&storage, &sales, fiscal_year_start, Currency::IDR)
+        .await
+        .unwrap();
+    assert_eq!(ret.end, NaiveDate::from_ymd_opt(2023, 3, 31).unwrap());
+    assert_eq!(ret.classes.len(), 2);
+
+    let metal = &ret.classes[0];
+    assert_eq!(metal.class, CurrencyKind::Metal);
+    assert_eq!(metal.cost, dec!(26000000));
+    assert_eq!(metal.proceeds, dec!(1800) * dec!(15588.665563));
+
+    let crypto = &ret.classes[1];
+    assert_eq!(crypto.class, CurrencyKind::Crypto);
+    assert_eq!(crypto.sales.len(), 1);
+    assert_eq!(crypto.cost, dec!(4000) * dec!(14388.75));
+    assert_eq!(crypto.proceeds, dec!(1700) * dec!(15588.665563));
+    assert!(crypto.gain.is_sign_negative());
+    assert_eq!(ret.gain, metal.gain + crypto.gain);
+
+    let csv = tax_report_csv(&ret).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[0].starts_with("asset class,description of property,"));
+    assert!(lines[2].starts_with("metal,total in IDR,,,"));
+    assert!(lines[3].starts_with("crypto,"));
+    assert!(lines[3].contains(",2021-12-21,2022-12-26,"));
+
+    // no rates of date acquired
+    let mut gap = sales[0].clone();
+    gap.acquired = Utc.with_ymd_and_hms(2021, 10, 1, 0, 0, 0).unwrap();
+    let ret = tax_report(
+        &storage,
+        &[gap, sales[1].clone()],
+        fiscal_year_start,
+        Currency::IDR,
+    )
+    .await
+    .unwrap();
+    let crypto = &ret.classes[1];
+    assert!(crypto.sales[0].gain.is_none());
+    assert!(
+        crypto.sales[0]
+            .unvalued
+            .as_ref()
+            .unwrap()
+            .contains("USD/IDR")
+    );
+    assert_eq!(crypto.gain, dec!(0));
+    assert_eq!(ret.gain, metal.gain);
+    let csv = tax_report_csv(&ret).unwrap();
+    assert!(
+        csv.lines()
+            .nth(3)
+            .unwrap()
+            .ends_with(",2021-10-01,2022-12-26,,,")
+    );
+
+    let mut invalid = sales[0].clone();
+    invalid.acquired = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+    assert!(
+        tax_report(&storage, &[invalid], fiscal_year_start, Currency::IDR)
+            .await
+            .is_err()
+    );
+}
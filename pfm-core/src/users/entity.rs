@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Local user account, allowed to sign in to dashboard.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct User {
+    pub username: String,
+
+    /// argon2 hash in PHC string format, including its salt and params.
+    pub password_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl User {
+    pub(crate) fn new(username: &str, password_hash: String) -> Self {
+        let now = Utc::now();
+        Self {
+            username: username.to_string(),
+            password_hash,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+// password hash is left out, so it doesn't end up in logs.
+impl std::fmt::Debug for User {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("User")
+            .field("username", &self.username)
+            .field("created_at", &self.created_at)
+            .field("updated_at", &self.updated_at)
+            .finish()
+    }
+}
@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+
+use super::entity::User;
+use crate::forex::ForexResult;
+
+#[async_trait]
+pub trait UsersStorage {
+    /// insert new user or replace existing one with the same username.
+    async fn upsert_user(&self, user: &User) -> ForexResult<()>;
+
+    /// get user by username, none if not exists.
+    async fn get_user(&self, username: &str) -> ForexResult<Option<User>>;
+
+    /// get usernames of all users, ordered.
+    async fn get_usernames(&self) -> ForexResult<Vec<String>>;
+
+    /// delete user by username.
+    async fn delete_user(&self, username: &str) -> ForexResult<()>;
+}
@@ -0,0 +1,9 @@
+// users local accounts of self-hosted instance, authenticated with argon2 hashed passwords.
+
+pub mod entity;
+
+pub mod interface;
+
+pub(crate) mod service;
+#[cfg(test)]
+mod service_test;
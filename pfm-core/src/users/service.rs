@@ -0,0 +1,129 @@
+use argon2::{
+    Argon2,
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
+};
+use chrono::Utc;
+
+use super::{entity::User, interface::UsersStorage};
+use crate::forex::{ForexError, ForexResult};
+
+const USERNAME_MAX_LEN: usize = 64;
+
+const PASSWORD_MIN_LEN: usize = 8;
+
+pub async fn create_user<US>(storage: &US, username: &str, password: &str) -> ForexResult<User>
+where
+    US: UsersStorage,
+{
+    validate_username(username)?;
+    validate_password(password)?;
+    if storage.get_user(username).await?.is_some() {
+        return Err(ForexError::client_error(
+            format!("user {} already exists", username).as_str(),
+        ));
+    }
+
+    let user = User::new(username, hash_password(password)?);
+    storage.upsert_user(&user).await?;
+
+    Ok(user)
+}
+
+pub async fn change_password<US>(storage: &US, username: &str, password: &str) -> ForexResult<User>
+where
+    US: UsersStorage,
+{
+    validate_password(password)?;
+    let Some(mut user) = storage.get_user(username).await? else {
+        return Err(ForexError::client_error(
+            format!("user {} not found", username).as_str(),
+        ));
+    };
+    user.password_hash = hash_password(password)?;
+    user.updated_at = Utc::now();
+    storage.upsert_user(&user).await?;
+
+    Ok(user)
+}
+
+pub async fn delete_user<US>(storage: &US, username: &str) -> ForexResult<()>
+where
+    US: UsersStorage,
+{
+    if storage.get_user(username).await?.is_none() {
+        return Err(ForexError::client_error(
+            format!("user {} not found", username).as_str(),
+        ));
+    }
+
+    storage.delete_user(username).await
+}
+
+pub async fn get_usernames<US>(storage: &US) -> ForexResult<Vec<String>>
+where
+    US: UsersStorage,
+{
+    storage.get_usernames().await
+}
+
+/// user whose password matches, the same error for unknown user and wrong password.
+pub async fn authenticate<US>(storage: &US, username: &str, password: &str) -> ForexResult<User>
+where
+    US: UsersStorage,
+{
+    let user = match validate_username(username) {
+        Ok(()) => storage.get_user(username).await?,
+        Err(_) => None,
+    };
+
+    match user {
+        Some(user) if verify_password(password, &user.password_hash) => Ok(user),
+        _ => Err(ForexError::client_error("invalid username or password")),
+    }
+}
+
+/// username is used as filename, hence only ascii letters, digits, `-`, `_` and `.` are allowed.
+fn validate_username(username: &str) -> ForexResult<()> {
+    let valid = !username.is_empty()
+        && username.len() <= USERNAME_MAX_LEN
+        && !username.starts_with('.')
+        && username
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+    if !valid {
+        return Err(ForexError::client_error(
+            "username must be 1-64 characters of letters, digits, `-`, `_` or `.`",
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_password(password: &str) -> ForexResult<()> {
+    if password.chars().count() < PASSWORD_MIN_LEN {
+        return Err(ForexError::client_error(
+            format!("password must be at least {} characters", PASSWORD_MIN_LEN).as_str(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn hash_password(password: &str) -> ForexResult<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|err| ForexError::internal_error(format!("hashing password: {}", err).as_str()))?;
+
+    Ok(hash.to_string())
+}
+
+pub(crate) fn verify_password(password: &str, password_hash: &str) -> bool {
+    let Ok(hash) = PasswordHash::new(password_hash) else {
+        return false;
+    };
+
+    Argon2::default()
+        .verify_password(password.as_bytes(), &hash)
+        .is_ok()
+}
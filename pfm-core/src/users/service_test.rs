@@ -0,0 +1,13 @@
+use super::service::{hash_password, verify_password};
+
+#[test]
+fn test_hash_and_verify_password() {
+    let hash = hash_password("correct horse").unwrap();
+    assert!(hash.starts_with("$argon2"));
+    assert!(verify_password("correct horse", &hash));
+    assert!(!verify_password("wrong horse", &hash));
+    assert!(!verify_password("correct horse", "not a hash"));
+
+    // salted, the same password hashes differently
+    assert_ne!(hash, hash_password("correct horse").unwrap());
+}
@@ -0,0 +1,64 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::forex::Currency;
+
+/// profile used when none given.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Pair in watchlist, rate is price of 1 base in quote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchedPair {
+    pub base: Currency,
+    pub quote: Currency,
+
+    /// rate the user is waiting for.
+    pub target: Option<Decimal>,
+}
+
+/// Ordered pairs watched by a profile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Watchlist {
+    pub profile: String,
+    pub pairs: Vec<WatchedPair>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Watchlist {
+    pub(crate) fn new(profile: &str) -> Self {
+        Self {
+            profile: profile.to_string(),
+            pairs: vec![],
+            updated_at: Utc::now(),
+        }
+    }
+
+    pub(crate) fn position(&self, base: Currency, quote: Currency) -> Option<usize> {
+        self.pairs
+            .iter()
+            .position(|pair| pair.base == base && pair.quote == quote)
+    }
+}
+
+/// Latest rate of watched pair with its changes in percent, None if no rates stored back then.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchQuote {
+    pub base: Currency,
+    pub quote: Currency,
+    pub rate: Decimal,
+    pub change_24h: Option<Decimal>,
+    pub change_7d: Option<Decimal>,
+    pub target: Option<Decimal>,
+
+    /// percent the rate must move to reach target.
+    pub to_target: Option<Decimal>,
+}
+
+/// Quotes of all pairs in watchlist at the same latest rates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchlistQuotes {
+    pub profile: String,
+    pub date: DateTime<Utc>,
+    pub quotes: Vec<WatchQuote>,
+}
@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+
+use super::entity::Watchlist;
+use crate::forex::ForexResult;
+
+#[async_trait]
+pub trait WatchlistStorage {
+    /// get watchlist of profile, None if profile has none.
+    async fn get_watchlist(&self, profile: &str) -> ForexResult<Option<Watchlist>>;
+
+    /// insert or replace watchlist of its profile.
+    async fn upsert_watchlist(&self, watchlist: &Watchlist) -> ForexResult<()>;
+
+    /// delete watchlist of profile.
+    async fn delete_watchlist(&self, profile: &str) -> ForexResult<()>;
+
+    /// get watchlists of every profile ordered by profile.
+    async fn get_watchlists(&self) -> ForexResult<Vec<Watchlist>>;
+}
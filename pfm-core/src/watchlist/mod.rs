@@ -0,0 +1,9 @@
+// watchlist favorite pairs of a profile, quoted with their recent changes.
+
+pub mod entity;
+
+pub mod interface;
+
+pub(crate) mod service;
+#[cfg(test)]
+mod service_test;
@@ -0,0 +1,215 @@
+use chrono::{TimeDelta, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::{
+    entity::{WatchQuote, WatchedPair, Watchlist, WatchlistQuotes},
+    interface::WatchlistStorage,
+};
+use crate::forex::{
+    Currency, ForexError, ForexResult, entity::Rates, interface::ForexStorage,
+    service as forex_service,
+};
+
+/// watchlist of profile, empty if profile has none yet.
+pub async fn get_watchlist<WS>(storage: &WS, profile: &str) -> ForexResult<Watchlist>
+where
+    WS: WatchlistStorage,
+{
+    validate_profile(profile)?;
+    Ok(storage
+        .get_watchlist(profile)
+        .await?
+        .unwrap_or_else(|| Watchlist::new(profile)))
+}
+
+/// append pair to watchlist, or only update its target if already watched.
+pub async fn add_pair<WS>(
+    storage: &WS,
+    profile: &str,
+    base: Currency,
+    quote: Currency,
+    target: Option<Decimal>,
+) -> ForexResult<Watchlist>
+where
+    WS: WatchlistStorage,
+{
+    if base == quote {
+        return Err(ForexError::client_error(
+            "base and quote of pair must be different",
+        ));
+    }
+    if target.is_some_and(|target| target <= Decimal::ZERO) {
+        return Err(ForexError::client_error("target rate must be positive"));
+    }
+    let mut watchlist = get_watchlist(storage, profile).await?;
+    match watchlist.position(base, quote) {
+        Some(index) => watchlist.pairs[index].target = target,
+        None => watchlist.pairs.push(WatchedPair {
+            base,
+            quote,
+            target,
+        }),
+    }
+    watchlist.updated_at = Utc::now();
+    storage.upsert_watchlist(&watchlist).await?;
+
+    Ok(watchlist)
+}
+
+pub async fn remove_pair<WS>(
+    storage: &WS,
+    profile: &str,
+    base: Currency,
+    quote: Currency,
+) -> ForexResult<Watchlist>
+where
+    WS: WatchlistStorage,
+{
+    let mut watchlist = get_watchlist(storage, profile).await?;
+    let Some(index) = watchlist.position(base, quote) else {
+        return Err(ForexError::client_error(
+            format!("pair {}/{} is not watched", base.code(), quote.code()).as_str(),
+        ));
+    };
+    watchlist.pairs.remove(index);
+    watchlist.updated_at = Utc::now();
+    storage.upsert_watchlist(&watchlist).await?;
+
+    Ok(watchlist)
+}
+
+/// reorder watchlist into order of pairs, which must be exactly the watched pairs.
+pub async fn reorder_pairs<WS>(
+    storage: &WS,
+    profile: &str,
+    order: &[(Currency, Currency)],
+) -> ForexResult<Watchlist>
+where
+    WS: WatchlistStorage,
+{
+    let mut watchlist = get_watchlist(storage, profile).await?;
+    let mut pairs = Vec::with_capacity(order.len());
+    for (base, quote) in order {
+        let Some(index) = watchlist.position(*base, *quote) else {
+            return Err(ForexError::client_error(
+                format!("pair {}/{} is not watched", base.code(), quote.code()).as_str(),
+            ));
+        };
+        if !pairs.contains(&watchlist.pairs[index]) {
+            pairs.push(watchlist.pairs[index]);
+        }
+    }
+    if pairs.len() != watchlist.pairs.len() {
+        return Err(ForexError::client_error(
+            "order must contain all watched pairs",
+        ));
+    }
+    watchlist.pairs = pairs;
+    watchlist.updated_at = Utc::now();
+    storage.upsert_watchlist(&watchlist).await?;
+
+    Ok(watchlist)
+}
+
+pub async fn delete_watchlist<WS>(storage: &WS, profile: &str) -> ForexResult<()>
+where
+    WS: WatchlistStorage,
+{
+    validate_profile(profile)?;
+    storage.delete_watchlist(profile).await
+}
+
+/// latest rates of watched pairs with their changes from historical rates a day and a week before.
+pub async fn get_watchlist_quotes<WS, FS>(
+    watchlist_storage: &WS,
+    forex_storage: &FS,
+    profile: &str,
+) -> ForexResult<WatchlistQuotes>
+where
+    WS: WatchlistStorage,
+    FS: ForexStorage,
+{
+    let watchlist = get_watchlist(watchlist_storage, profile).await?;
+    let latest = forex_storage.get_latest().await?;
+    if let Some(err) = latest.error {
+        return Err(ForexError::internal_error(err.as_str()));
+    }
+
+    let mut previous = vec![];
+    for days in [1, 7] {
+        let rates = forex_storage
+            .get_historical(latest.data.date - TimeDelta::days(days))
+            .await
+            .ok()
+            .filter(|rates| rates.error.is_none())
+            .map(|rates| rates.data);
+        previous.push(rates);
+    }
+
+    quotes(
+        &watchlist,
+        &latest.data,
+        previous[0].as_ref(),
+        previous[1].as_ref(),
+    )
+}
+
+/// quote watched pairs with latest rates, changes are None without rates of the day or the week before.
+pub fn quotes(
+    watchlist: &Watchlist,
+    latest: &Rates,
+    day_ago: Option<&Rates>,
+    week_ago: Option<&Rates>,
+) -> ForexResult<WatchlistQuotes> {
+    let mut quotes = Vec::with_capacity(watchlist.pairs.len());
+    for pair in &watchlist.pairs {
+        let rate = forex_service::pair_rate(latest, pair.base, pair.quote)?;
+        let change = |rates: Option<&Rates>| -> ForexResult<Option<Decimal>> {
+            let Some(rates) = rates else {
+                return Ok(None);
+            };
+            let previous = forex_service::pair_rate(rates, pair.base, pair.quote)?;
+            Ok(percent_change(previous, rate))
+        };
+        quotes.push(WatchQuote {
+            base: pair.base,
+            quote: pair.quote,
+            rate,
+            change_24h: change(day_ago)?,
+            change_7d: change(week_ago)?,
+            target: pair.target,
+            to_target: pair.target.and_then(|target| percent_change(rate, target)),
+        });
+    }
+
+    Ok(WatchlistQuotes {
+        profile: watchlist.profile.clone(),
+        date: latest.date,
+        quotes,
+    })
+}
+
+/// profile names are used as file names, hence restricted.
+fn validate_profile(profile: &str) -> ForexResult<()> {
+    let valid = !profile.is_empty()
+        && profile.len() <= 64
+        && profile
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(ForexError::client_error(
+            "profile must be 1 to 64 characters of letters, digits, - or _",
+        ));
+    }
+
+    Ok(())
+}
+
+fn percent_change(from: Decimal, to: Decimal) -> Option<Decimal> {
+    if from.is_zero() {
+        return None;
+    }
+
+    Some((to - from) / from * dec!(100))
+}
@@ -0,0 +1,66 @@
+use chrono::{TimeZone, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::entity::{WatchedPair, Watchlist};
+use super::service::quotes;
+use crate::forex::{Currency, Money, entity::Rates};
+use crate::global::test_support;
+
+fn rates(day: u32, idr: Decimal) -> Rates {
+    let date = Utc.with_ymd_and_hms(2025, 3, day, 0, 0, 0).unwrap();
+    test_support::rates(date, &[Money::IDR(idr)]).data
+}
+
+#[test]
+fn test_watchlist_quotes() {
+    let mut watchlist = Watchlist::new("family");
+    watchlist.pairs.push(WatchedPair {
+        base: Currency::USD,
+        quote: Currency::IDR,
+        target: Some(dec!(15000)),
+    });
+    watchlist.pairs.push(WatchedPair {
+        base: Currency::IDR,
+        quote: Currency::USD,
+        target: None,
+    });
+
+    let ret = quotes(
+        &watchlist,
+        &rates(8, dec!(16000)),
+        Some(&rates(7, dec!(16160))),
+        Some(&rates(1, dec!(15500))),
+    )
+    .unwrap();
+    assert_eq!(ret.profile, "family");
+    assert_eq!(ret.quotes.len(), 2);
+
+    let usd_idr = &ret.quotes[0];
+    assert_eq!(usd_idr.rate, dec!(16000));
+    assert_eq!(usd_idr.change_24h.unwrap().round_dp(4), dec!(-0.9901));
+    assert_eq!(usd_idr.change_7d.unwrap().round_dp(4), dec!(3.2258));
+    assert_eq!(usd_idr.to_target, Some(dec!(-6.25)));
+
+    let idr_usd = &ret.quotes[1];
+    assert_eq!(idr_usd.rate, dec!(1) / dec!(16000));
+    assert!(idr_usd.change_24h.unwrap() > Decimal::ZERO);
+    assert!(idr_usd.to_target.is_none());
+
+    let ret = quotes(&watchlist, &rates(8, dec!(16000)), None, None).unwrap();
+    assert!(ret.quotes[0].change_24h.is_none());
+    assert!(ret.quotes[0].change_7d.is_none());
+}
+
+#[test]
+fn test_watchlist_position() {
+    let mut watchlist = Watchlist::new("default");
+    watchlist.pairs.push(WatchedPair {
+        base: Currency::XAU,
+        quote: Currency::IDR,
+        target: None,
+    });
+
+    assert_eq!(watchlist.position(Currency::XAU, Currency::IDR), Some(0));
+    assert_eq!(watchlist.position(Currency::IDR, Currency::XAU), None);
+}
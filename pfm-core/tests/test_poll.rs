@@ -1,30 +1,41 @@
 use chrono::{TimeZone, Utc};
 use pfm_core::{
+    api::{
+        forex::{poll_historical_rates, poll_rates},
+        providers,
+        storage::ForexStorageImpl,
+    },
     forex::{
         self, Money,
+        entity::PollMode,
         interface::{ForexHistoricalRates, ForexStorage, ForexTimeseriesRates},
-        service::{poll_historical_rates, poll_rates},
-    },
-    forex_impl::{
-        self,
-        forex_storage::{self, ForexStorageImpl},
-        tradermade,
     },
     global::{self, constants::BASE_CURRENCY},
 };
 use rust_decimal_macros::dec;
+use tokio_util::sync::CancellationToken;
 
 #[tokio::test]
 async fn test_currency_api_historical() {
     let http_client = global::http_client();
     let fs = global::storage_fs();
-    let exchange_api_impl =
-        forex_impl::currency_api::Api::new(&global::config().forex_currency_api_key, http_client);
-    let storage_impl = forex_storage::ForexStorageImpl::new(fs);
+    let exchange_api_impl = providers::CurrencyApi::new(
+        global::secrets().get(global::SECRET_FOREX_CURRENCY_API_KEY),
+        http_client,
+    );
+    let storage_impl = ForexStorageImpl::new(fs);
 
     let date = Utc.with_ymd_and_hms(2019, 6, 6, 0, 0, 0).unwrap();
 
-    let ret = poll_historical_rates(&exchange_api_impl, &storage_impl, date, BASE_CURRENCY).await;
+    let ret = poll_historical_rates(
+        &exchange_api_impl,
+        &storage_impl,
+        date,
+        BASE_CURRENCY,
+        PollMode::Always,
+        &CancellationToken::new(),
+    )
+    .await;
 
     dbg!(&ret);
 
@@ -37,13 +48,20 @@ async fn test_currency_api_historical() {
 async fn test_open_exchange_rates_latest() {
     let http_client = global::http_client();
     let fs = global::storage_fs();
-    let exchange_api_impl = forex_impl::open_exchange_api::Api::new(
-        &global::config().forex_open_exchange_api_key,
+    let exchange_api_impl = providers::OpenExchangeRates::new(
+        global::secrets().get(global::SECRET_FOREX_OPEN_EXCHANGE_API_KEY),
         http_client,
     );
-    let storage_impl = forex_storage::ForexStorageImpl::new(fs);
+    let storage_impl = ForexStorageImpl::new(fs);
 
-    let ret = poll_rates(&exchange_api_impl, &storage_impl, BASE_CURRENCY).await;
+    let ret = poll_rates(
+        &exchange_api_impl,
+        &storage_impl,
+        &global::SystemClock,
+        BASE_CURRENCY,
+        &CancellationToken::new(),
+    )
+    .await;
 
     dbg!(&ret);
 
@@ -56,15 +74,23 @@ async fn test_open_exchange_rates_latest() {
 async fn test_open_exchange_rates_historical() {
     let http_client = global::http_client();
     let fs = global::storage_fs();
-    let exchange_api_impl = forex_impl::open_exchange_api::Api::new(
-        &global::config().forex_open_exchange_api_key,
+    let exchange_api_impl = providers::OpenExchangeRates::new(
+        global::secrets().get(global::SECRET_FOREX_OPEN_EXCHANGE_API_KEY),
         http_client,
     );
-    let storage_impl = forex_storage::ForexStorageImpl::new(fs);
+    let storage_impl = ForexStorageImpl::new(fs);
 
     let date = Utc.with_ymd_and_hms(2000, 6, 6, 0, 0, 0).unwrap();
 
-    let ret = poll_historical_rates(&exchange_api_impl, &storage_impl, date, BASE_CURRENCY).await;
+    let ret = poll_historical_rates(
+        &exchange_api_impl,
+        &storage_impl,
+        date,
+        BASE_CURRENCY,
+        PollMode::Always,
+        &CancellationToken::new(),
+    )
+    .await;
 
     dbg!(&ret);
 
@@ -75,12 +101,19 @@ async fn test_open_exchange_rates_historical() {
 
 #[tokio::test]
 pub async fn test_currencybeacon_latest_rates() {
-    let api = forex_impl::currencybeacon::Api::new(
-        &global::config().forex_currencybeacon_api_key,
+    let api = providers::CurrencyBeacon::new(
+        global::secrets().get(global::SECRET_FOREX_CURRENCYBEACON_API_KEY),
         global::http_client(),
     );
-    let storage = forex_storage::ForexStorageImpl::new(global::storage_fs());
-    let ret = poll_rates(&api, &storage, global::constants::BASE_CURRENCY).await;
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let ret = poll_rates(
+        &api,
+        &storage,
+        &global::SystemClock,
+        global::constants::BASE_CURRENCY,
+        &CancellationToken::new(),
+    )
+    .await;
     dbg!(&ret);
 
     assert!(&ret.is_ok());
@@ -90,13 +123,21 @@ pub async fn test_currencybeacon_latest_rates() {
 
 #[tokio::test]
 pub async fn test_currencybeacon_historical_rates() {
-    let api = forex_impl::currencybeacon::Api::new(
-        &global::config().forex_currencybeacon_api_key,
+    let api = providers::CurrencyBeacon::new(
+        global::secrets().get(global::SECRET_FOREX_CURRENCYBEACON_API_KEY),
         global::http_client(),
     );
-    let storage = forex_storage::ForexStorageImpl::new(global::storage_fs());
+    let storage = ForexStorageImpl::new(global::storage_fs());
     let date = Utc.with_ymd_and_hms(2022, 6, 6, 0, 0, 0).unwrap();
-    let ret = poll_historical_rates(&api, &storage, date, global::constants::BASE_CURRENCY).await;
+    let ret = poll_historical_rates(
+        &api,
+        &storage,
+        date,
+        global::constants::BASE_CURRENCY,
+        PollMode::Always,
+        &CancellationToken::new(),
+    )
+    .await;
     dbg!(&ret);
 
     assert!(&ret.is_ok());
@@ -107,8 +148,8 @@ pub async fn test_currencybeacon_historical_rates() {
 /// test currencybeacon timeseries api
 #[tokio::test]
 pub async fn test_currencybeacon_timeseries_rates() {
-    let api = forex_impl::currencybeacon::Api::new(
-        &global::config().forex_currencybeacon_api_key,
+    let api = providers::CurrencyBeacon::new(
+        global::secrets().get(global::SECRET_FOREX_CURRENCYBEACON_API_KEY),
         global::http_client(),
     );
 
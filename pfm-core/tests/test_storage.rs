@@ -2,11 +2,11 @@ use core::panic;
 
 use chrono::{TimeDelta, TimeZone, Utc};
 use pfm_core::{
+    api::{providers, storage::ForexStorageImpl},
     forex::{
-        interface::{ForexStorage, ForexStorageDeletion, ForexTimeseriesRates},
         Money,
+        interface::{ForexStorage, ForexStorageDeletion, ForexTimeseriesRates},
     },
-    forex_impl::{self, forex_storage::ForexStorageImpl},
     global,
 };
 use rust_decimal_macros::dec;
@@ -20,8 +20,8 @@ pub async fn test_storage_update_historical() {
     let storage_impl = ForexStorageImpl::new(global::storage_fs());
     let date = Utc.with_ymd_and_hms(2002, 2, 25, 0, 0, 0).unwrap();
     let new_data = vec![
-        Money::XAU(dec!(0.04220322222)),
-        Money::XAG(dec!(0.23011116)),
+        (Money::XAU(dec!(0.04220322222)), "test".to_string()),
+        (Money::XAG(dec!(0.23011116)), "test".to_string()),
     ];
     let before = ForexStorage::get_historical(&storage_impl, date)
         .await
@@ -31,14 +31,43 @@ pub async fn test_storage_update_historical() {
         ForexStorage::update_historical_rates_data(&storage_impl, date, new_data.clone()).await;
     dbg!(&after);
 
-    assert_eq!(after.as_ref().unwrap().data.rates.xau, new_data[0].amount());
-    assert_eq!(after.as_ref().unwrap().data.rates.xag, new_data[1].amount());
+    assert_eq!(
+        after.as_ref().unwrap().data.rates.xau,
+        new_data[0].0.amount()
+    );
+    assert_eq!(
+        after.as_ref().unwrap().data.rates.xag,
+        new_data[1].0.amount()
+    );
+    assert_eq!(after.as_ref().unwrap().provenance.len(), 2);
+}
+
+#[tokio::test]
+pub async fn test_storage_update_historical_bulk() {
+    let storage_impl = ForexStorageImpl::new(global::storage_fs());
+    let first = Utc.with_ymd_and_hms(2002, 2, 25, 0, 0, 0).unwrap();
+    let second = Utc.with_ymd_and_hms(2003, 2, 25, 0, 0, 0).unwrap();
+    let updates = vec![
+        (second, vec![(Money::XAU(dec!(0.003)), "test".to_string())]),
+        (first, vec![(Money::XAU(dec!(0.004)), "test".to_string())]),
+        (first, vec![(Money::XAG(dec!(0.2)), "test".to_string())]),
+    ];
+
+    let after = ForexStorage::update_historical_bulk(&storage_impl, updates)
+        .await
+        .unwrap();
+
+    assert_eq!(after.len(), 2);
+    assert_eq!(after[0].data.date.date_naive(), first.date_naive());
+    assert_eq!(after[0].data.rates.xau, dec!(0.004));
+    assert_eq!(after[0].data.rates.xag, dec!(0.2));
+    assert_eq!(after[1].data.rates.xau, dec!(0.003));
 }
 
 #[tokio::test]
 pub async fn test_storage_insert_batch() {
-    let api = forex_impl::currencybeacon::Api::new(
-        &global::config().forex_currencybeacon_api_key,
+    let api = providers::CurrencyBeacon::new(
+        global::secrets().get(global::SECRET_FOREX_CURRENCYBEACON_API_KEY),
         global::http_client(),
     );
 
@@ -86,7 +115,10 @@ pub async fn test_storage_get_historical_range() {
     // assert all data are within date range(inclusive)
     for v in ret.as_ref().unwrap() {
         if v.data.date < start_date || v.data.date > end_date {
-            panic!("historical range contains date smaller than start date, or bigger than end date: {}",v.data.date);
+            panic!(
+                "historical range contains date smaller than start date, or bigger than end date: {}",
+                v.data.date
+            );
         }
     }
 }
@@ -1,16 +1,40 @@
 use crate::Config;
 use anyhow::{Context, Result};
 use chrono::{DateTime, TimeDelta, Utc};
+#[cfg(feature = "email")]
+use pfm_core::digest::interface::{DigestSender, DigestStorage};
 use pfm_core::{
+    api,
     forex::{
-        self, Currency,
-        interface::{ForexHistoricalRates, ForexRates, ForexStorage, ForexStorageDeletion},
+        Currency,
+        entity::{FreshnessSlo, Job as JobName, JobTrigger, PollMode},
+        interface::{
+            ForexCryptoPrices, ForexHistoricalRates, ForexProviderCapabilities, ForexRates,
+            ForexSloAlerts, ForexStorage, ForexStorageDeletion, ForexStorageJobLock,
+            ForexStorageJobRuns, ForexStorageSlo, ForexStorageStats,
+        },
+        market_hours::MarketCalendar,
     },
-    global,
+    global::{self, Clock},
 };
 use tokio_cron_scheduler::{Job, JobScheduler};
+use tokio_util::sync::CancellationToken;
 use tracing::instrument;
 
+/// Shared by all jobs of this instance.
+#[derive(Clone)]
+pub(crate) struct JobContext {
+    /// identifies this instance when taking lock of jobs on shared storage.
+    pub instance_id: String,
+    pub lock_ttl: TimeDelta,
+
+    /// cancelled on shutdown, aborting in-flight 3rd API calls of running jobs.
+    pub cancel: CancellationToken,
+
+    /// latest rates are polled only while market of any enabled currency is open, always polled if none.
+    pub market_calendar: Option<MarketCalendar>,
+}
+
 /// ----------------------------- JOBS AND HANDLERS -----------------------------
 // run at every hour
 // 0 0 * * * *
@@ -20,16 +44,19 @@ pub(crate) async fn poll_latest_rates_job<'a, API, STORAGE>(
     cron_cfg: &Config,
     forex_api: API,
     forex_storage: STORAGE,
+    ctx: JobContext,
 ) -> Result<&'a JobScheduler, anyhow::Error>
 where
-    API: ForexRates + Clone + Send + Sync + 'static,
-    STORAGE: ForexStorage + Clone + Send + Sync + 'static,
+    API: ForexRates + ForexProviderCapabilities + Clone + Send + Sync + 'static,
+    STORAGE:
+        ForexStorage + ForexStorageJobLock + ForexStorageJobRuns + Clone + Send + Sync + 'static,
 {
     let latest_rates_job = Job::new_async(&cron_cfg.crontab_poll_rates, move |_uuid, _lock| {
         Box::pin(poll_latest_rates_handler(
             forex_api.clone(),
             forex_storage.clone(),
             global::constants::BASE_CURRENCY,
+            ctx.clone(),
         ))
     })
     .context("cron creating poll_latest_rates_job")?;
@@ -53,9 +80,43 @@ where
 }
 
 #[instrument(skip_all)]
-async fn poll_latest_rates_handler(fx: impl ForexRates, fs: impl ForexStorage, base: Currency) {
+async fn poll_latest_rates_handler(
+    fx: impl ForexRates + ForexProviderCapabilities,
+    fs: impl ForexStorage + ForexStorageJobLock + ForexStorageJobRuns,
+    base: Currency,
+    ctx: JobContext,
+) {
     tracing::info!("cron job poll_latest_rates_job invoked");
-    let _ = forex::service::poll_rates(&fx, &fs, base).await;
+    if let Some(calendar) = &ctx.market_calendar
+        && !calendar.any_open(
+            global::config().enabled_currencies(),
+            global::SystemClock.now(),
+        )
+    {
+        tracing::info!(
+            "cron markets of enabled currencies are closed, skipping poll_latest_rates_job"
+        );
+        return;
+    }
+    if !api::forex::lock_job(
+        &fs,
+        JobName::PollLatestRates,
+        &ctx.instance_id,
+        ctx.lock_ttl,
+    )
+    .await
+    {
+        tracing::info!("cron poll_latest_rates_job is locked by other instance, skipping");
+        return;
+    }
+    // polled rates are published by subscriber of storage events
+    let _ = api::forex::run_job(
+        &fs,
+        JobName::PollLatestRates,
+        JobTrigger::Cron,
+        api::forex::poll_rates(&fx, &fs, &global::SystemClock, base, &ctx.cancel),
+    )
+    .await;
 }
 
 // run at every 01:10 AM UTC
@@ -67,12 +128,20 @@ pub(crate) async fn poll_historical_rates_job<'a, API, STORAGE, STORAGE_DELETION
     forex_api: API,
     forex_storage: STORAGE,
     forex_storage_deletion: STORAGE_DELETION,
+    ctx: JobContext,
 ) -> Result<&'a JobScheduler, anyhow::Error>
 where
-    API: ForexHistoricalRates + Clone + Send + Sync + 'static,
-    STORAGE: ForexStorage + Clone + Send + Sync + 'static,
-    STORAGE_DELETION: ForexStorageDeletion + Clone + Send + Sync + 'static,
+    API: ForexHistoricalRates + ForexProviderCapabilities + Clone + Send + Sync + 'static,
+    STORAGE:
+        ForexStorage + ForexStorageJobLock + ForexStorageJobRuns + Clone + Send + Sync + 'static,
+    STORAGE_DELETION: ForexStorageDeletion + ForexStorageStats + Clone + Send + Sync + 'static,
 {
+    let poll_mode = if cron_cfg.cron_poll_historical_rates_skip_existing {
+        PollMode::SkipExisting
+    } else {
+        PollMode::Always
+    };
+
     let historical_rates_job = Job::new_async(
         &cron_cfg.crontab_poll_historical_rates,
         move |_uuid, _lock| {
@@ -85,6 +154,8 @@ where
                 forex_storage_deletion.clone(),
                 date,
                 global::constants::BASE_CURRENCY,
+                poll_mode,
+                ctx.clone(),
             ))
         },
     )
@@ -110,14 +181,333 @@ where
 
 #[instrument(skip_all)]
 async fn poll_historical_rates_handler(
-    fx: impl ForexHistoricalRates,
-    fs: impl ForexStorage,
-    fs_deletion: impl ForexStorageDeletion,
+    fx: impl ForexHistoricalRates + ForexProviderCapabilities,
+    fs: impl ForexStorage + ForexStorageJobLock + ForexStorageJobRuns,
+    fs_deletion: impl ForexStorageDeletion + ForexStorageStats,
     date: DateTime<Utc>,
     base: Currency,
+    mode: PollMode,
+    ctx: JobContext,
 ) {
     tracing::info!("cron job poll_historical_rates_job invoked");
+    if !api::forex::lock_job(
+        &fs,
+        JobName::PollHistoricalRates,
+        &ctx.instance_id,
+        ctx.lock_ttl,
+    )
+    .await
+    {
+        tracing::info!("cron poll_historical_rates_job is locked by other instance, skipping");
+        return;
+    }
     let _ = fs_deletion.clear_latest().await;
-    let _ = forex::service::poll_historical_rates(&fx, &fs, date, base).await;
+    match api::forex::enforce_disk_quota(&fs_deletion, global::config().storage_max_disk_mb).await {
+        Ok(Some(report)) => tracing::info!(
+            "cron storage exceeded disk quota, {} bytes before cleanup, {} bytes after, pruned {} archived latest rates files",
+            report.bytes_before,
+            report.bytes_after,
+            report.pruned.len()
+        ),
+        Ok(None) => {}
+        Err(err) => tracing::error!("cron enforcing storage disk quota failed: {}", err),
+    }
+    let _ = api::forex::run_job(
+        &fs,
+        JobName::PollHistoricalRates,
+        JobTrigger::Cron,
+        api::forex::poll_historical_rates(&fx, &fs, date, base, mode, &ctx.cancel),
+    )
+    .await;
+}
+
+// run at every 01:30 AM UTC, after historical rates of yesterday polled
+// 0 30 1 * * *
+#[instrument(skip_all)]
+pub(crate) async fn capture_crypto_prices_job<'a, CRYPTO, STORAGE>(
+    scheduler: &'a JobScheduler,
+    cron_cfg: &Config,
+    crypto_api: CRYPTO,
+    forex_storage: STORAGE,
+    ctx: JobContext,
+) -> Result<&'a JobScheduler, anyhow::Error>
+where
+    CRYPTO: ForexCryptoPrices + Clone + Send + Sync + 'static,
+    STORAGE:
+        ForexStorage + ForexStorageJobLock + ForexStorageJobRuns + Clone + Send + Sync + 'static,
+{
+    let crypto_prices_job = Job::new_async(
+        &cron_cfg.crontab_capture_crypto_prices,
+        move |_uuid, _lock| {
+            // everytime this cron run, capture close prices of yesterday
+            let date = Utc::now() - TimeDelta::days(1);
+
+            Box::pin(capture_crypto_prices_handler(
+                crypto_api.clone(),
+                forex_storage.clone(),
+                date,
+                ctx.clone(),
+            ))
+        },
+    )
+    .context("cron creating capture_crypto_prices_job")?;
+
+    let crypto_prices_job_id = crypto_prices_job.guid();
+    if !cron_cfg.cron_enable_capture_crypto_prices {
+        tracing::info!("cron capture_crypto_prices_job is disabled, removing from job scheduler");
+        scheduler
+            .remove(&crypto_prices_job_id)
+            .await
+            .context("cron removing capture_crypto_prices_job")?;
+        return Ok(scheduler);
+    }
+
+    tracing::info!("cron capture_crypto_prices_job add into job scheduler");
+    scheduler
+        .add(crypto_prices_job)
+        .await
+        .context("cron registering capture_crypto_prices_job")?;
+    Ok(scheduler)
+}
+
+#[instrument(skip_all)]
+async fn capture_crypto_prices_handler(
+    crypto: impl ForexCryptoPrices,
+    fs: impl ForexStorage + ForexStorageJobLock + ForexStorageJobRuns,
+    date: DateTime<Utc>,
+    ctx: JobContext,
+) {
+    tracing::info!("cron job capture_crypto_prices_job invoked");
+    if !api::forex::lock_job(
+        &fs,
+        JobName::CaptureCryptoPrices,
+        &ctx.instance_id,
+        ctx.lock_ttl,
+    )
+    .await
+    {
+        tracing::info!("cron capture_crypto_prices_job is locked by other instance, skipping");
+        return;
+    }
+    match api::forex::capture_crypto_prices(&crypto, &fs, date, JobTrigger::Cron, &ctx.cancel).await
+    {
+        Ok(report) => tracing::info!(
+            "cron capture_crypto_prices_job merged {} rates, rejected {}",
+            report.applied.len(),
+            report.rejected.len()
+        ),
+        Err(err) => tracing::error!("cron capture_crypto_prices_job failed: {}", err),
+    }
+}
+
+// run every 5 minutes
+// 0 */5 * * * *
+#[instrument(skip_all)]
+pub(crate) async fn poll_crypto_rates_job<'a, CRYPTO, STORAGE>(
+    scheduler: &'a JobScheduler,
+    cron_cfg: &Config,
+    crypto_api: CRYPTO,
+    forex_storage: STORAGE,
+    ctx: JobContext,
+) -> Result<&'a JobScheduler, anyhow::Error>
+where
+    CRYPTO: ForexCryptoPrices + Clone + Send + Sync + 'static,
+    STORAGE:
+        ForexStorage + ForexStorageJobLock + ForexStorageJobRuns + Clone + Send + Sync + 'static,
+{
+    let crypto_rates_job =
+        Job::new_async(&cron_cfg.crontab_poll_crypto_rates, move |_uuid, _lock| {
+            Box::pin(poll_crypto_rates_handler(
+                crypto_api.clone(),
+                forex_storage.clone(),
+                ctx.clone(),
+            ))
+        })
+        .context("cron creating poll_crypto_rates_job")?;
+
+    let crypto_rates_job_id = crypto_rates_job.guid();
+    if !cron_cfg.cron_enable_poll_crypto_rates {
+        tracing::info!("cron poll_crypto_rates_job is disabled, removing from job scheduler");
+        scheduler
+            .remove(&crypto_rates_job_id)
+            .await
+            .context("cron removing poll_crypto_rates_job")?;
+        return Ok(scheduler);
+    }
+
+    tracing::info!("cron poll_crypto_rates_job add into job scheduler");
+    scheduler
+        .add(crypto_rates_job)
+        .await
+        .context("cron registering poll_crypto_rates_job")?;
+    Ok(scheduler)
+}
+
+#[instrument(skip_all)]
+async fn poll_crypto_rates_handler(
+    crypto: impl ForexCryptoPrices,
+    fs: impl ForexStorage + ForexStorageJobLock + ForexStorageJobRuns,
+    ctx: JobContext,
+) {
+    tracing::info!("cron job poll_crypto_rates_job invoked");
+    if !api::forex::lock_job(
+        &fs,
+        JobName::PollCryptoRates,
+        &ctx.instance_id,
+        ctx.lock_ttl,
+    )
+    .await
+    {
+        tracing::info!("cron poll_crypto_rates_job is locked by other instance, skipping");
+        return;
+    }
+    // merged rates are published by subscriber of storage events
+    match api::forex::poll_crypto_rates(
+        &crypto,
+        &fs,
+        &global::SystemClock,
+        JobTrigger::Cron,
+        &ctx.cancel,
+    )
+    .await
+    {
+        Ok(rates) => tracing::info!(
+            "cron poll_crypto_rates_job merged crypto prices into latest rates {}",
+            rates.id
+        ),
+        Err(err) => tracing::error!("cron poll_crypto_rates_job failed: {}", err),
+    }
+}
+
+// run every 10 minutes
+// 0 */10 * * * *
+#[instrument(skip_all)]
+pub(crate) async fn evaluate_slo_job<'a, STORAGE, ALERTS>(
+    scheduler: &'a JobScheduler,
+    cron_cfg: &Config,
+    forex_storage: STORAGE,
+    slo: FreshnessSlo,
+    alerts: Option<ALERTS>,
+    ctx: JobContext,
+) -> Result<&'a JobScheduler, anyhow::Error>
+where
+    STORAGE: ForexStorage + ForexStorageJobLock + ForexStorageSlo + Clone + Send + Sync + 'static,
+    ALERTS: ForexSloAlerts + Clone + Send + Sync + 'static,
+{
+    let slo_job = Job::new_async(&cron_cfg.crontab_evaluate_slo, move |_uuid, _lock| {
+        Box::pin(evaluate_slo_handler(
+            forex_storage.clone(),
+            slo,
+            alerts.clone(),
+            ctx.clone(),
+        ))
+    })
+    .context("cron creating evaluate_slo_job")?;
+
+    let slo_job_id = slo_job.guid();
+    if !cron_cfg.cron_enable_evaluate_slo {
+        tracing::info!("cron evaluate_slo_job is disabled, removing from job scheduler");
+        scheduler
+            .remove(&slo_job_id)
+            .await
+            .context("cron removing evaluate_slo_job")?;
+        return Ok(scheduler);
+    }
+
+    tracing::info!("cron evaluate_slo_job add into job scheduler");
+    scheduler
+        .add(slo_job)
+        .await
+        .context("cron registering evaluate_slo_job")?;
+    Ok(scheduler)
+}
+
+#[instrument(skip_all)]
+async fn evaluate_slo_handler(
+    fs: impl ForexStorage + ForexStorageJobLock + ForexStorageSlo,
+    slo: FreshnessSlo,
+    alerts: Option<impl ForexSloAlerts>,
+    ctx: JobContext,
+) {
+    tracing::info!("cron job evaluate_slo_job invoked");
+    if !api::forex::lock_job(&fs, JobName::EvaluateSlo, &ctx.instance_id, ctx.lock_ttl).await {
+        tracing::info!("cron evaluate_slo_job is locked by other instance, skipping");
+        return;
+    }
+    let report = match api::slo::evaluate(
+        &fs,
+        &global::SystemClock,
+        &slo,
+        ctx.market_calendar.as_ref(),
+        alerts.as_ref(),
+    )
+    .await
+    {
+        Ok(report) => report,
+        Err(err) => {
+            tracing::error!("cron evaluate_slo_job failed: {}", err);
+            return;
+        }
+    };
+    if report.breach_unalerted {
+        tracing::warn!("cron evaluate_slo_job alerting breach failed, retrying at next evaluation");
+    }
+}
+
+// run at every 02:00 AM UTC, after historical rates of yesterday polled
+// 0 0 2 * * *
+#[cfg(feature = "email")]
+#[instrument(skip_all)]
+pub(crate) async fn send_digest_job<'a, STORAGE, SENDER>(
+    scheduler: &'a JobScheduler,
+    cron_cfg: &Config,
+    storage: STORAGE,
+    sender: SENDER,
+    ctx: JobContext,
+) -> Result<&'a JobScheduler, anyhow::Error>
+where
+    STORAGE: ForexStorage
+        + ForexStorageJobLock
+        + ForexStorageJobRuns
+        + DigestStorage
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    SENDER: DigestSender + Clone + Send + Sync + 'static,
+{
+    let digest_job = Job::new_async(&cron_cfg.crontab_send_digest, move |_uuid, _lock| {
+        Box::pin(send_digest_handler(
+            storage.clone(),
+            sender.clone(),
+            ctx.clone(),
+        ))
+    })
+    .context("cron creating send_digest_job")?;
+
+    tracing::info!("cron send_digest_job add into job scheduler");
+    scheduler
+        .add(digest_job)
+        .await
+        .context("cron registering send_digest_job")?;
+    Ok(scheduler)
+}
+
+#[cfg(feature = "email")]
+#[instrument(skip_all)]
+async fn send_digest_handler(
+    fs: impl ForexStorage + ForexStorageJobLock + ForexStorageJobRuns + DigestStorage,
+    sender: impl DigestSender,
+    ctx: JobContext,
+) {
+    tracing::info!("cron job send_digest_job invoked");
+    if !api::forex::lock_job(&fs, JobName::SendDigest, &ctx.instance_id, ctx.lock_ttl).await {
+        tracing::info!("cron send_digest_job is locked by other instance, skipping");
+        return;
+    }
+    match api::digest::send_digests(&fs, &fs, &sender, JobTrigger::Cron, Utc::now()).await {
+        Ok(sent) => tracing::info!("cron send_digest_job sent {} digests", sent),
+        Err(err) => tracing::error!("cron send_digest_job failed: {}", err),
+    }
 }
 // ----------------------------- END -----------------------------
@@ -1,27 +1,77 @@
+use std::sync::Arc;
+
 use anyhow::Result;
-use pfm_core::{forex_impl, global};
-use pfm_utils::tracing_util;
+use chrono::TimeDelta;
+use pfm_core::{
+    api,
+    forex::{CurrencyKind, entity::FreshnessSlo, market_hours::MarketCalendar},
+    global,
+};
+use pfm_utils::{graceful_util, tracing_util};
+use rust_decimal::Decimal;
 use serde::Deserialize;
-use tokio::signal;
+use tokio::sync::Notify;
 use tokio_cron_scheduler::JobScheduler;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
 
 mod job;
+mod publisher;
 
 const ENV_PREFIX: &str = "CRON_";
 
+/// storage events buffered for slow subscribers, e.g. retrying webhooks.
+const STORAGE_EVENTS_CAPACITY: usize = 64;
+
 #[tokio::main]
 async fn main() {
+    let build_info = build_info();
+    if std::env::args().any(|arg| arg == "--version") {
+        println!("pfm-cron {}", build_info.detail());
+        return;
+    }
+
     tracing_util::init_tracing("pfm-cron");
+    tracing::info!("starting pfm-cron {}", build_info.detail());
+    global::validate_storage().expect("cron validating storage");
+    let cancel = CancellationToken::new();
+    global::init_secrets(cancel.clone())
+        .await
+        .expect("cron resolving secrets");
 
     let core_cfg = global::config();
     let cron_config = init_config().expect("cron initializing config");
 
     // dependencies
-    let forex_api = forex_impl::currencybeacon::Api::new(
-        &core_cfg.forex_currencybeacon_api_key,
+    let events = global::EventBus::new(STORAGE_EVENTS_CAPACITY);
+    let mut forex_storage =
+        api::storage::ForexStorageImpl::new(global::storage_fs()).with_events(events.clone());
+    if !core_cfg.forex_signing_key.is_empty() {
+        let signer = api::integrations::RatesSigner::from_seed_hex(&core_cfg.forex_signing_key)
+            .expect("cron parsing forex signing key");
+        forex_storage = forex_storage.with_signer(signer);
+    }
+    let forex_api = api::providers::provider_from_config(
+        core_cfg,
         global::http_client(),
-    );
-    let forex_storage = forex_impl::forex_storage::ForexStorageImpl::new(global::storage_fs());
+        forex_storage.clone(),
+    )
+    .expect("cron creating forex provider");
+    let instance_id = Uuid::new_v4().to_string();
+    tracing::info!("cron instance id: {}", instance_id);
+    let publishers = publisher::Publishers {
+        webhook: init_webhook(&cron_config),
+        http: init_http_internal(&cron_config),
+        #[cfg(feature = "mqtt")]
+        mqtt: init_mqtt(&cron_config, &instance_id),
+    };
+    let job_ctx = job::JobContext {
+        instance_id,
+        lock_ttl: TimeDelta::seconds(cron_config.cron_lock_ttl_secs as i64),
+        cancel: cancel.clone(),
+        market_calendar: init_market_calendar(&cron_config),
+    };
+    tokio::spawn(publishers.subscribe(forex_storage.clone(), events.subscribe(), cancel.clone()));
     // END
 
     let scheduler = JobScheduler::new()
@@ -34,6 +84,7 @@ async fn main() {
         &cron_config,
         forex_api.clone(),
         forex_storage.clone(),
+        job_ctx.clone(),
     )
     .await
     .expect("cron registering poll_latest_rates_job");
@@ -43,27 +94,249 @@ async fn main() {
         &cron_config,
         forex_api,
         forex_storage.clone(),
-        forex_storage,
+        forex_storage.clone(),
+        job_ctx.clone(),
     )
     .await
     .expect("cron registering poll_historical_rates_job");
+
+    let scheduler = job::capture_crypto_prices_job(
+        scheduler,
+        &cron_config,
+        api::providers::TwelveData::new(
+            global::secrets().get(global::SECRET_FOREX_TWELVEDATA_API_KEY),
+            global::http_client(),
+        ),
+        forex_storage.clone(),
+        job_ctx.clone(),
+    )
+    .await
+    .expect("cron registering capture_crypto_prices_job");
+
+    let scheduler = job::poll_crypto_rates_job(
+        scheduler,
+        &cron_config,
+        api::providers::TwelveData::new(
+            global::secrets().get(global::SECRET_FOREX_TWELVEDATA_API_KEY),
+            global::http_client(),
+        ),
+        forex_storage.clone(),
+        job_ctx.clone(),
+    )
+    .await
+    .expect("cron registering poll_crypto_rates_job");
+
+    let scheduler = job::evaluate_slo_job(
+        scheduler,
+        &cron_config,
+        forex_storage.clone(),
+        init_slo(&cron_config),
+        init_slo_alerts(&cron_config),
+        job_ctx.clone(),
+    )
+    .await
+    .expect("cron registering evaluate_slo_job");
+
+    #[cfg(feature = "email")]
+    let scheduler = match init_smtp(&cron_config) {
+        Some(smtp) => job::send_digest_job(scheduler, &cron_config, forex_storage, smtp, job_ctx)
+            .await
+            .expect("cron registering send_digest_job"),
+        None => scheduler,
+    };
     // END
 
     scheduler.start().await.expect("failed starting scheduler");
 
-    signal::ctrl_c()
-        .await
-        .expect("failed reading interrupting signal");
+    let notify_shutdown = Arc::new(Notify::new());
+    graceful_util::graceful_shutdown(
+        notify_shutdown.clone(),
+        Some(async move { cancel.cancel() }),
+    )
+    .await;
+    graceful_util::wait_for_shutdown(notify_shutdown).await;
 
     tracing::info!("cron Shutting down gracefully...");
 }
 
+/// versions and build information of pfm-cron with its enabled features.
+fn build_info() -> global::BuildInfo {
+    global::BuildInfo::from_flags(
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        &[
+            ("mqtt", cfg!(feature = "mqtt")),
+            ("email", cfg!(feature = "email")),
+        ],
+    )
+}
+
 fn init_config() -> Result<Config, anyhow::Error> {
     let cfg = pfm_utils::config_util::get_config::<Config>(ENV_PREFIX);
 
     cfg
 }
 
+/// webhooks notified after each successful poll, None if no url configured.
+fn init_webhook(cfg: &Config) -> Option<api::integrations::Webhook> {
+    let urls = api::integrations::Webhook::parse_urls(&cfg.cron_webhook_urls);
+    if urls.is_empty() {
+        return None;
+    }
+    if cfg.cron_webhook_secret.is_empty() {
+        panic!("cron CRON_WEBHOOK_SECRET must be set for signing webhook payloads");
+    }
+
+    tracing::info!("cron publishing polled rates to {} webhooks", urls.len());
+    Some(api::integrations::Webhook::new(
+        urls,
+        &cfg.cron_webhook_secret,
+        cfg.cron_webhook_max_attempts,
+        global::http_client(),
+    ))
+}
+
+/// objectives of rates freshness evaluated by evaluate_slo_job.
+fn init_slo(cfg: &Config) -> FreshnessSlo {
+    let historical_min_percent: Decimal = cfg
+        .cron_slo_historical_min_percent
+        .parse()
+        .unwrap_or_else(|err| panic!("cron parsing CRON_SLO_HISTORICAL_MIN_PERCENT: {}", err));
+    if historical_min_percent < Decimal::ZERO || historical_min_percent > Decimal::ONE_HUNDRED {
+        panic!("cron CRON_SLO_HISTORICAL_MIN_PERCENT must be between 0 and 100");
+    }
+
+    FreshnessSlo {
+        latest_max_age_secs: cfg.cron_slo_latest_max_age_secs,
+        historical_days: cfg.cron_slo_historical_days,
+        historical_min_percent,
+    }
+}
+
+/// webhooks alerted when freshness SLO breach starts, None if no url configured.
+fn init_slo_alerts(cfg: &Config) -> Option<api::integrations::Webhook> {
+    let urls = api::integrations::Webhook::parse_urls(&cfg.cron_slo_webhook_urls);
+    if urls.is_empty() {
+        return None;
+    }
+    if cfg.cron_webhook_secret.is_empty() {
+        panic!("cron CRON_WEBHOOK_SECRET must be set for signing slo alerts");
+    }
+
+    tracing::info!("cron alerting slo breaches to {} webhooks", urls.len());
+    Some(api::integrations::Webhook::new(
+        urls,
+        &cfg.cron_webhook_secret,
+        cfg.cron_webhook_max_attempts,
+        global::http_client(),
+    ))
+}
+
+/// trading hours consulted before polling latest rates, None if polling regardless of markets.
+fn init_market_calendar(cfg: &Config) -> Option<MarketCalendar> {
+    if !cfg.cron_skip_closed_markets {
+        return None;
+    }
+
+    let overrides = [
+        (CurrencyKind::Fiat, &cfg.cron_market_hours_fiat),
+        (CurrencyKind::Metal, &cfg.cron_market_hours_metal),
+        (CurrencyKind::Crypto, &cfg.cron_market_hours_crypto),
+    ];
+    let mut calendar = MarketCalendar::default();
+    for (kind, hours) in overrides {
+        if hours.is_empty() {
+            continue;
+        }
+        let hours = hours
+            .parse()
+            .unwrap_or_else(|err| panic!("cron parsing market hours of {:?}: {}", kind, err));
+        calendar = calendar.with_hours(kind, hours);
+    }
+
+    tracing::info!(
+        "cron polling latest rates only while markets are open, fiat: {}, metal: {}, crypto: {}",
+        calendar.fiat,
+        calendar.metal,
+        calendar.crypto
+    );
+    Some(calendar)
+}
+
+/// pfm-http admin endpoints triggered with signed internal requests, None if no url configured.
+fn init_http_internal(cfg: &Config) -> Option<api::integrations::InternalClient> {
+    if cfg.cron_http_internal_url.is_empty() {
+        return None;
+    }
+    let core_cfg = global::config();
+    let signer = api::integrations::InternalSigner::new(
+        global::secrets().get(global::SECRET_INTERNAL_SECRET),
+        TimeDelta::seconds(core_cfg.internal_max_skew_secs as i64),
+    );
+    if !signer.is_enabled() {
+        panic!("cron CORE_INTERNAL_SECRET must be set for signing internal requests to pfm-http");
+    }
+
+    tracing::info!(
+        "cron warming cache of pfm-http at {} after polls",
+        cfg.cron_http_internal_url
+    );
+    Some(api::integrations::InternalClient::new(
+        &cfg.cron_http_internal_url,
+        signer,
+        global::http_client(),
+    ))
+}
+
+/// mqtt broker receiving rates of selected pairs, None if no host configured.
+#[cfg(feature = "mqtt")]
+fn init_mqtt(cfg: &Config, instance_id: &str) -> Option<api::integrations::Mqtt> {
+    if cfg.cron_mqtt_host.is_empty() {
+        return None;
+    }
+    let pairs = api::integrations::Mqtt::parse_pairs(&cfg.cron_mqtt_pairs)
+        .expect("cron parsing CRON_MQTT_PAIRS");
+
+    tracing::info!(
+        "cron publishing {} pairs to mqtt broker {}:{}",
+        pairs.len(),
+        cfg.cron_mqtt_host,
+        cfg.cron_mqtt_port
+    );
+    // client id must be unique per connection, otherwise broker drops the other instance
+    Some(api::integrations::Mqtt::new(
+        &cfg.cron_mqtt_host,
+        cfg.cron_mqtt_port,
+        &format!("{}-{}", cfg.cron_mqtt_client_id, instance_id),
+        &cfg.cron_mqtt_topic_prefix,
+        pairs,
+    ))
+}
+
+/// smtp relay sending digests, None if no host configured.
+#[cfg(feature = "email")]
+fn init_smtp(cfg: &Config) -> Option<api::integrations::Smtp> {
+    if cfg.cron_smtp_host.is_empty() {
+        return None;
+    }
+
+    tracing::info!(
+        "cron sending digests through smtp {}:{}",
+        cfg.cron_smtp_host,
+        cfg.cron_smtp_port
+    );
+    Some(
+        api::integrations::Smtp::new(
+            &cfg.cron_smtp_host,
+            cfg.cron_smtp_port,
+            &cfg.cron_smtp_username,
+            &cfg.cron_smtp_password,
+            &cfg.cron_smtp_from,
+        )
+        .expect("cron initializing smtp"),
+    )
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub(crate) struct Config {
     #[serde(alias = "CRON_TAB_POLL_RATES")]
@@ -77,4 +350,224 @@ pub(crate) struct Config {
 
     #[serde(alias = "CRON_ENABLE_POLL_HISTORICAL_RATES")]
     pub cron_enable_poll_historical_rates: bool,
+
+    /// daily close prices of enabled crypto currencies merged into historical rates of yesterday.
+    #[serde(
+        alias = "CRON_TAB_CAPTURE_CRYPTO_PRICES",
+        default = "default_crontab_capture_crypto_prices"
+    )]
+    pub crontab_capture_crypto_prices: String,
+
+    #[serde(alias = "CRON_ENABLE_CAPTURE_CRYPTO_PRICES", default)]
+    pub cron_enable_capture_crypto_prices: bool,
+
+    /// current prices of enabled crypto currencies merged into latest rates, more often than latest polls.
+    #[serde(
+        alias = "CRON_TAB_POLL_CRYPTO_RATES",
+        default = "default_crontab_poll_crypto_rates"
+    )]
+    pub crontab_poll_crypto_rates: String,
+
+    #[serde(alias = "CRON_ENABLE_POLL_CRYPTO_RATES", default)]
+    pub cron_enable_poll_crypto_rates: bool,
+
+    /// evaluation of freshness SLO, stored for compliance metrics served by pfm-http.
+    #[serde(
+        alias = "CRON_TAB_EVALUATE_SLO",
+        default = "default_crontab_evaluate_slo"
+    )]
+    pub crontab_evaluate_slo: String,
+
+    #[serde(alias = "CRON_ENABLE_EVALUATE_SLO", default)]
+    pub cron_enable_evaluate_slo: bool,
+
+    /// latest rates older than this breach the SLO, unless markets are closed while skipping closed markets.
+    #[serde(
+        alias = "CRON_SLO_LATEST_MAX_AGE_SECS",
+        default = "default_cron_slo_latest_max_age_secs"
+    )]
+    pub cron_slo_latest_max_age_secs: u64,
+
+    /// days before today whose historical rates must be complete, 0 disables the objective.
+    #[serde(
+        alias = "CRON_SLO_HISTORICAL_DAYS",
+        default = "default_cron_slo_historical_days"
+    )]
+    pub cron_slo_historical_days: u32,
+
+    /// percentage of those days each enabled currency must have rate, 0 to 100.
+    #[serde(
+        alias = "CRON_SLO_HISTORICAL_MIN_PERCENT",
+        default = "default_cron_slo_historical_min_percent"
+    )]
+    pub cron_slo_historical_min_percent: String,
+
+    /// comma separated urls receiving POST of evaluation when SLO breach starts, signed with CRON_WEBHOOK_SECRET.
+    #[serde(alias = "CRON_SLO_WEBHOOK_URLS", default)]
+    pub cron_slo_webhook_urls: String,
+
+    /// skip latest polls while markets of all enabled currencies are closed, e.g. on weekends without crypto currencies.
+    #[serde(
+        alias = "CRON_SKIP_CLOSED_MARKETS",
+        default = "default_cron_skip_closed_markets"
+    )]
+    pub cron_skip_closed_markets: bool,
+
+    /// trading hours of fiat currencies in UTC overriding `sun 22:00 - fri 22:00`.
+    #[serde(alias = "CRON_MARKET_HOURS_FIAT", default)]
+    pub cron_market_hours_fiat: String,
+
+    /// trading hours of metals in UTC overriding `sun 23:00 - fri 22:00 break 22:00 - 23:00`.
+    #[serde(alias = "CRON_MARKET_HOURS_METAL", default)]
+    pub cron_market_hours_metal: String,
+
+    /// trading hours of crypto currencies in UTC overriding `always`.
+    #[serde(alias = "CRON_MARKET_HOURS_CRYPTO", default)]
+    pub cron_market_hours_crypto: String,
+
+    /// skip polling historical rates if valid data for the date already stored.
+    #[serde(alias = "CRON_POLL_HISTORICAL_RATES_SKIP_EXISTING", default)]
+    pub cron_poll_historical_rates_skip_existing: bool,
+
+    /// how long an instance keeps lock of a job, only the holder runs it until it expires.
+    /// must be longer than a job run and shorter than interval between runs.
+    #[serde(alias = "CRON_LOCK_TTL_SECS", default = "default_cron_lock_ttl_secs")]
+    pub cron_lock_ttl_secs: u64,
+
+    /// comma separated urls receiving POST of newly polled rates.
+    #[serde(alias = "CRON_WEBHOOK_URLS", default)]
+    pub cron_webhook_urls: String,
+
+    /// secret for HMAC-SHA256 signature of webhook payloads.
+    #[serde(alias = "CRON_WEBHOOK_SECRET", default)]
+    pub cron_webhook_secret: String,
+
+    /// attempts of each webhook delivery before giving up, retried with exponential backoff.
+    #[serde(
+        alias = "CRON_WEBHOOK_MAX_ATTEMPTS",
+        default = "default_cron_webhook_max_attempts"
+    )]
+    pub cron_webhook_max_attempts: u32,
+
+    /// base url of pfm-http admin listener, e.g. http://localhost:8081, triggered after polls with signed internal requests.
+    #[serde(alias = "CRON_HTTP_INTERNAL_URL", default)]
+    pub cron_http_internal_url: String,
+
+    /// mqtt broker host, publishing to mqtt is disabled if empty.
+    #[cfg(feature = "mqtt")]
+    #[serde(alias = "CRON_MQTT_HOST", default)]
+    pub cron_mqtt_host: String,
+
+    #[cfg(feature = "mqtt")]
+    #[serde(alias = "CRON_MQTT_PORT", default = "default_cron_mqtt_port")]
+    pub cron_mqtt_port: u16,
+
+    #[cfg(feature = "mqtt")]
+    #[serde(alias = "CRON_MQTT_CLIENT_ID", default = "default_cron_mqtt_client_id")]
+    pub cron_mqtt_client_id: String,
+
+    /// topics are {prefix}/{BASE}/{QUOTE}, e.g. pfm/forex/USD/IDR
+    #[cfg(feature = "mqtt")]
+    #[serde(
+        alias = "CRON_MQTT_TOPIC_PREFIX",
+        default = "default_cron_mqtt_topic_prefix"
+    )]
+    pub cron_mqtt_topic_prefix: String,
+
+    /// comma separated pairs to publish, e.g. USD/IDR,XAU/IDR
+    #[cfg(feature = "mqtt")]
+    #[serde(alias = "CRON_MQTT_PAIRS", default)]
+    pub cron_mqtt_pairs: String,
+
+    /// smtp relay host, sending digests is disabled if empty.
+    #[cfg(feature = "email")]
+    #[serde(alias = "CRON_SMTP_HOST", default)]
+    pub cron_smtp_host: String,
+
+    #[cfg(feature = "email")]
+    #[serde(alias = "CRON_SMTP_PORT", default = "default_cron_smtp_port")]
+    pub cron_smtp_port: u16,
+
+    #[cfg(feature = "email")]
+    #[serde(alias = "CRON_SMTP_USERNAME", default)]
+    pub cron_smtp_username: String,
+
+    #[cfg(feature = "email")]
+    #[serde(alias = "CRON_SMTP_PASSWORD", default)]
+    pub cron_smtp_password: String,
+
+    /// sender address of digests, e.g. pfm <pfm@example.com>
+    #[cfg(feature = "email")]
+    #[serde(alias = "CRON_SMTP_FROM", default)]
+    pub cron_smtp_from: String,
+
+    /// daily and weekly digests due are sent on each run.
+    #[cfg(feature = "email")]
+    #[serde(
+        alias = "CRON_TAB_SEND_DIGEST",
+        default = "default_crontab_send_digest"
+    )]
+    pub crontab_send_digest: String,
+}
+
+fn default_crontab_capture_crypto_prices() -> String {
+    "0 30 1 * * *".to_string()
+}
+
+// every 15 minutes, 5 coins stay within 800 daily credits of twelvedata's free plan
+fn default_crontab_poll_crypto_rates() -> String {
+    "0 */15 * * * *".to_string()
+}
+
+fn default_crontab_evaluate_slo() -> String {
+    "0 */10 * * * *".to_string()
+}
+
+fn default_cron_slo_latest_max_age_secs() -> u64 {
+    2 * 60 * 60
+}
+
+fn default_cron_slo_historical_days() -> u32 {
+    7
+}
+
+fn default_cron_slo_historical_min_percent() -> String {
+    "100".to_string()
+}
+
+fn default_cron_lock_ttl_secs() -> u64 {
+    300
+}
+
+fn default_cron_skip_closed_markets() -> bool {
+    true
+}
+
+fn default_cron_webhook_max_attempts() -> u32 {
+    3
+}
+
+#[cfg(feature = "mqtt")]
+fn default_cron_mqtt_port() -> u16 {
+    1883
+}
+
+#[cfg(feature = "mqtt")]
+fn default_cron_mqtt_client_id() -> String {
+    "pfm-cron".to_string()
+}
+
+#[cfg(feature = "mqtt")]
+fn default_cron_mqtt_topic_prefix() -> String {
+    "pfm/forex".to_string()
+}
+
+#[cfg(feature = "email")]
+fn default_cron_smtp_port() -> u16 {
+    587
+}
+
+#[cfg(feature = "email")]
+fn default_crontab_send_digest() -> String {
+    "0 0 2 * * *".to_string()
 }
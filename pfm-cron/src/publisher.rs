@@ -0,0 +1,133 @@
+use pfm_core::{
+    api,
+    forex::{
+        entity::{Rates, RatesResponse},
+        interface::{ForexStorage, ForexStorageWebhookDeliveries},
+    },
+    global::StorageEvent,
+};
+use std::{sync::Arc, time::Duration};
+
+use tokio::sync::broadcast::{Receiver, error::RecvError};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+/// admin endpoint of pfm-http loading rates into its cache.
+const WARM_CACHE_PATH: &str = "/admin/cache/warm";
+
+/// rates inserted within this delay of the first one are published and warmed once, e.g. during backfills.
+const PUBLISH_DEBOUNCE: Duration = Duration::from_secs(5);
+
+/// Integrations notified with rates after each successful poll.
+#[derive(Clone, Default)]
+pub(crate) struct Publishers {
+    pub webhook: Option<api::integrations::Webhook>,
+
+    /// pfm-http warming its cache with newly polled rates.
+    pub http: Option<api::integrations::InternalClient>,
+
+    #[cfg(feature = "mqtt")]
+    pub mqtt: Option<api::integrations::Mqtt>,
+}
+
+impl Publishers {
+    pub(crate) async fn publish(
+        &self,
+        fs: &impl ForexStorageWebhookDeliveries,
+        rates: &RatesResponse<Rates>,
+    ) {
+        if let Some(webhook) = &self.webhook {
+            api::forex::publish_rates(webhook, fs, rates).await;
+        }
+
+        #[cfg(feature = "mqtt")]
+        if let Some(mqtt) = &self.mqtt {
+            mqtt.publish(rates).await;
+        }
+    }
+
+    async fn warm_cache(&self) {
+        if let Some(http) = &self.http
+            && let Err(err) = http.post(WARM_CACHE_PATH, vec![]).await
+        {
+            tracing::warn!("cron failed warming cache of pfm-http: {}", err);
+        }
+    }
+
+    /// publish latest and historical rates inserted into storage, until cancelled.
+    /// rates inserted within debounce are coalesced into the newest latest and historical rates.
+    pub(crate) async fn subscribe(
+        self,
+        fs: impl ForexStorage + ForexStorageWebhookDeliveries,
+        mut events: Receiver<StorageEvent>,
+        cancel: CancellationToken,
+    ) {
+        let mut pending = Pending::default();
+        let mut publish_at: Option<Instant> = None;
+        loop {
+            let event = tokio::select! {
+                _ = cancel.cancelled() => return,
+                _ = tokio::time::sleep_until(publish_at.unwrap_or_else(Instant::now)), if publish_at.is_some() => {
+                    publish_at = None;
+                    self.flush(&fs, std::mem::take(&mut pending)).await;
+                    continue;
+                }
+                event = events.recv() => event,
+            };
+            match event {
+                Ok(StorageEvent::LatestInserted(rates)) => pending.latest = Some(rates),
+                Ok(StorageEvent::HistoricalInserted(rates)) => {
+                    if pending
+                        .historical
+                        .as_ref()
+                        .is_none_or(|newest| newest.data.date <= rates.data.date)
+                    {
+                        pending.historical = Some(rates);
+                    }
+                }
+                Ok(_) => continue,
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!(
+                        "cron publishers missed {} storage events, resyncing latest rates from storage",
+                        missed
+                    );
+                    pending.missed = true;
+                }
+                Err(RecvError::Closed) => return,
+            }
+            if publish_at.is_none() {
+                publish_at = Some(Instant::now() + PUBLISH_DEBOUNCE);
+            }
+        }
+    }
+
+    async fn flush(
+        &self,
+        fs: &(impl ForexStorage + ForexStorageWebhookDeliveries),
+        pending: Pending,
+    ) {
+        let mut latest = pending.latest;
+        if pending.missed {
+            match fs.get_latest().await {
+                Ok(rates) => latest = Some(Arc::new(rates)),
+                Err(err) => tracing::warn!("cron publishers failed reading latest rates: {}", err),
+            }
+        }
+        for rates in [pending.historical, latest].into_iter().flatten() {
+            self.publish(fs, &rates).await;
+        }
+        self.warm_cache().await;
+    }
+}
+
+/// rates inserted since last publish.
+#[derive(Default)]
+struct Pending {
+    latest: Option<Arc<RatesResponse<Rates>>>,
+
+    /// historical rates of the newest date.
+    historical: Option<Arc<RatesResponse<Rates>>>,
+
+    /// events were missed, latest rates are read from storage instead.
+    missed: bool,
+}
@@ -1,18 +1,25 @@
-use std::{fmt, marker::PhantomData};
+use std::{collections::HashSet, convert::Infallible, fmt, marker::PhantomData, str::FromStr};
 
 use async_trait::async_trait;
 use axum::{
-    extract::{FromRequestParts, Query},
-    http::{request::Parts, HeaderValue},
+    Json,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
 };
 use axum::{
-    http::{HeaderMap, StatusCode},
-    response::IntoResponse,
-    Json,
+    extract::{FromRequestParts, Query},
+    http::{HeaderValue, request::Parts},
 };
 use chrono::{DateTime, NaiveDate, TimeZone, Utc};
-use pfm_core::forex::ForexError;
-use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use pfm_core::forex::{Currency, ForexError, entity::RatesData};
+use pfm_core::global::{TenantId, TenantScoped};
+use quick_xml::escape::escape;
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::DeserializeOwned,
+    ser::{Error as _, SerializeMap, SerializeSeq},
+};
+use serde_json::{Map, Value, value::RawValue};
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -22,7 +29,7 @@ pub struct HttpResponse<T> {
     pub data: Option<T>,
 
     #[serde(rename = "error", skip_serializing_if = "Option::is_none")]
-    pub error: Option<String>,
+    pub error: Option<ErrorDTO>,
 
     #[serde(skip)]
     _marker: PhantomData<T>,
@@ -36,6 +43,30 @@ impl<T> HttpResponse<T> {
         (StatusCode::OK, headers, Json(Self::new(data)))
     }
 
+    /// ok response serialized into format negotiated from request's `Accept` header,
+    /// with decimals as strings or numbers negotiated by [`DecimalFormat`].
+    pub fn ok_as(
+        format: ResponseFormat,
+        decimals: DecimalFormat,
+        data: T,
+        headers: Option<HeaderMap>,
+    ) -> Result<Response, AppError>
+    where
+        T: Serialize,
+    {
+        let bytes = encode(format, decimals, &Self::new(data)).map_err(|err| {
+            AppError::InternalServerError(format!("failed serializing response: {}", err))
+        })?;
+
+        Ok((
+            StatusCode::OK,
+            headers,
+            [(header::CONTENT_TYPE, format.content_type())],
+            bytes,
+        )
+            .into_response())
+    }
+
     fn new(data: T) -> Self {
         Self {
             data: Some(data),
@@ -44,7 +75,7 @@ impl<T> HttpResponse<T> {
         }
     }
 
-    fn err(error: String) -> Self {
+    fn err(error: ErrorDTO) -> Self {
         Self {
             data: None,
             error: Some(error),
@@ -53,31 +84,266 @@ impl<T> HttpResponse<T> {
     }
 }
 
+/// body serialized into the format, with decimals as strings or numbers.
+pub(crate) fn encode<B>(
+    format: ResponseFormat,
+    decimals: DecimalFormat,
+    body: &B,
+) -> Result<Vec<u8>, String>
+where
+    B: Serialize,
+{
+    match (format, decimals) {
+        (ResponseFormat::Json, DecimalFormat::String) => {
+            serde_json::to_vec(body).map_err(|err| err.to_string())
+        }
+        (ResponseFormat::Json, DecimalFormat::Number) => serde_json::to_value(body)
+            .and_then(|value| serde_json::to_vec(&DecimalsAsNumbers::new(&value, true)))
+            .map_err(|err| err.to_string()),
+        (ResponseFormat::MsgPack, DecimalFormat::String) => {
+            rmp_serde::to_vec_named(body).map_err(|err| err.to_string())
+        }
+        (ResponseFormat::MsgPack, DecimalFormat::Number) => serde_json::to_value(body)
+            .map_err(|err| err.to_string())
+            .and_then(|value| {
+                rmp_serde::to_vec_named(&DecimalsAsNumbers::new(&value, false))
+                    .map_err(|err| err.to_string())
+            }),
+        // xml is text, decimals are written as they are
+        (ResponseFormat::Xml, _) => serde_json::to_value(body)
+            .map(|value| {
+                let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+                write_xml_element(&mut xml, "response", &value);
+                xml.into_bytes()
+            })
+            .map_err(|err| err.to_string()),
+    }
+}
+
+/// fields of decimals besides the ones named by currency codes, i.e. rates and money.
+const DECIMAL_FIELDS: &[&str] = &[
+    "amount",
+    "change",
+    "change_percent",
+    "completeness",
+    "cost",
+    "gain",
+    "historical_min_percent",
+    "historical_percent",
+    "latest",
+    "max",
+    "mean",
+    "min",
+    "move_percent",
+    "new",
+    "old",
+    "open",
+    "percent",
+    "percentile",
+    "previous",
+    "proceeds",
+    "rate",
+    "remaining",
+    "required_monthly",
+    "saved",
+    "sensitivity",
+    "share_percent",
+    "shocked_total",
+    "shocked_value",
+    "success_rate",
+    "target",
+    "threshold_percent",
+    "total",
+    "value",
+    "volatility",
+];
+
+fn is_decimal_field(name: &str) -> bool {
+    DECIMAL_FIELDS.contains(&name) || Currency::from_str(&name.to_uppercase()).is_ok()
+}
+
+/// decimals are serialized as strings, serializes strings of decimal fields as numbers.
+/// exact numbers are written as they are, keeping the precision of the decimals in json,
+/// otherwise they are floating point numbers, e.g. in msgpack.
+pub(crate) struct DecimalsAsNumbers<'a> {
+    value: &'a Value,
+    exact: bool,
+
+    /// value is of a decimal field, or an item of it.
+    decimal: bool,
+}
+
+impl<'a> DecimalsAsNumbers<'a> {
+    pub(crate) fn new(value: &'a Value, exact: bool) -> Self {
+        Self {
+            value,
+            exact,
+            decimal: false,
+        }
+    }
+
+    fn of(&self, value: &'a Value, decimal: bool) -> Self {
+        Self {
+            value,
+            exact: self.exact,
+            decimal,
+        }
+    }
+}
+
+impl Serialize for DecimalsAsNumbers<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.value {
+            Value::Array(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(&self.of(item, self.decimal))?;
+                }
+                seq.end()
+            }
+            Value::Object(fields) => {
+                let mut map = serializer.serialize_map(Some(fields.len()))?;
+                for (name, value) in fields {
+                    map.serialize_entry(name, &self.of(value, is_decimal_field(name)))?;
+                }
+                map.end()
+            }
+            Value::String(text) if self.decimal && is_decimal(text) => {
+                if self.exact {
+                    RawValue::from_string(text.clone())
+                        .map_err(S::Error::custom)?
+                        .serialize(serializer)
+                } else {
+                    text.parse::<f64>()
+                        .map_err(S::Error::custom)?
+                        .serialize(serializer)
+                }
+            }
+            value => value.serialize(serializer),
+        }
+    }
+}
+
+/// `-?digits(.digits)?` without leading zeros, the way decimals are serialized.
+fn is_decimal(text: &str) -> bool {
+    let unsigned = text.strip_prefix('-').unwrap_or(text);
+    let (int, frac) = unsigned.split_once('.').unwrap_or((unsigned, "0"));
+
+    !int.is_empty()
+        && !frac.is_empty()
+        && (int == "0" || !int.starts_with('0'))
+        && int.bytes().all(|b| b.is_ascii_digit())
+        && frac.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// write json value as xml element, array items are written as repeated elements of the same name.
+fn write_xml_element(xml: &mut String, name: &str, value: &Value) {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                write_xml_element(xml, name, item);
+            }
+        }
+        Value::Null => {
+            xml.push_str(&format!("<{}/>", name));
+        }
+        Value::Object(fields) => {
+            xml.push_str(&format!("<{}>", name));
+            for (field_name, field_value) in fields {
+                write_xml_element(xml, field_name, field_value);
+            }
+            xml.push_str(&format!("</{}>", name));
+        }
+        Value::String(text) => {
+            xml.push_str(&format!("<{}>{}</{}>", name, escape(text.as_str()), name));
+        }
+        Value::Bool(_) | Value::Number(_) => {
+            xml.push_str(&format!("<{}>{}</{}>", name, value, name));
+        }
+    }
+}
+
+/// Error of failed response, clients match on its stable `code` rather than its message.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorDTO {
+    pub code: String,
+
+    pub message: String,
+
+    /// causes of invalid input, e.g. why a value couldn't be parsed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+/// message of internal errors, their causes are only logged.
+const INTERNAL_ERROR_MESSAGE: &str = "Internal error, please try again later";
+
 #[derive(Debug, Error, Serialize)]
 pub enum AppError {
-    #[error("Not found: {0}")]
-    NoContent(String),
-
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
     #[error("Invalid input: {0}")]
     BadRequest(String),
 
+    #[error("Invalid input: {message}, {details}")]
+    InvalidInput { message: String, details: String },
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
     #[error("Internal error: {0}")]
     InternalServerError(String),
+
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
+}
+
+impl AppError {
+    /// stable machine readable code of the error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Unauthorized(_) => "unauthorized",
+            Self::Forbidden(_) => "forbidden",
+            Self::BadRequest(_) | Self::InvalidInput { .. } => "invalid_input",
+            Self::NotFound(_) => "not_found",
+            Self::InternalServerError(_) => "internal_error",
+            Self::ServiceUnavailable(_) => "unavailable",
+        }
+    }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> axum::response::Response {
-        let (status_code, err_msg) = match self {
-            Self::NoContent(err) => (StatusCode::NO_CONTENT, err),
-            Self::Unauthorized(err) => (StatusCode::UNAUTHORIZED, err),
-            Self::BadRequest(err) => (StatusCode::BAD_REQUEST, err),
-            Self::InternalServerError(err) => (StatusCode::INTERNAL_SERVER_ERROR, err),
+        let code = self.code().to_string();
+        let (status_code, message, details) = match self {
+            Self::Unauthorized(err) => (StatusCode::UNAUTHORIZED, err, None),
+            Self::Forbidden(err) => (StatusCode::FORBIDDEN, err, None),
+            Self::BadRequest(err) => (StatusCode::BAD_REQUEST, err, None),
+            Self::InvalidInput { message, details } => {
+                (StatusCode::BAD_REQUEST, message, Some(details))
+            }
+            Self::NotFound(err) => (StatusCode::NOT_FOUND, err, None),
+            // internals, e.g. storage paths or providers' payloads, never reach clients
+            Self::InternalServerError(err) => {
+                tracing::error!("internal error: {}", err);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    INTERNAL_ERROR_MESSAGE.to_string(),
+                    None,
+                )
+            }
+            Self::ServiceUnavailable(err) => (StatusCode::SERVICE_UNAVAILABLE, err, None),
         };
 
-        let resp = HttpResponse::<((), ())>::err(err_msg);
+        let resp = HttpResponse::<((), ())>::err(ErrorDTO {
+            code,
+            message,
+            details,
+        });
 
         (status_code, Json(resp)).into_response()
     }
@@ -85,11 +351,162 @@ impl IntoResponse for AppError {
 
 impl From<ForexError> for AppError {
     fn from(value: ForexError) -> Self {
-        tracing::error!("ForexError: {}", value);
+        let details = value.details();
+        tracing::error!("ForexError: {}, caused by: {:?}", value, details);
+        match (&value, details) {
+            (ForexError::ClientError(_), None) => Self::BadRequest(value.message()),
+            (ForexError::ClientError(_), Some(details)) => Self::InvalidInput {
+                message: value.message(),
+                details,
+            },
+            (ForexError::NotFoundError(_), _) => Self::NotFound(value.message()),
+            (ForexError::Error(_) | ForexError::InternalError(_), _) => {
+                Self::InternalServerError(value.to_string())
+            }
+        }
+    }
+}
+
+/// Serialization format of response and error bodies, negotiated from `Accept` header.
+/// Default to JSON if header is missing or contains no supported media type.
+/// XML is only picked when preferred over every listed media type, so browsers accepting it below html get JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResponseFormat {
+    #[default]
+    Json,
+    MsgPack,
+    Xml,
+}
+
+impl ResponseFormat {
+    fn from_media_type(media_type: &str) -> Option<Self> {
+        match media_type {
+            "application/json" | "application/*" | "*/*" => Some(Self::Json),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(Self::MsgPack)
+            }
+            "application/xml" | "text/xml" => Some(Self::Xml),
+            _ => None,
+        }
+    }
+
+    /// pick supported media type with highest quality value, earlier one wins on ties.
+    pub fn from_accept(headers: &HeaderMap) -> Self {
+        let Some(accept) = headers
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+        else {
+            return Self::default();
+        };
+
+        let mut best: Option<(Self, f32)> = None;
+        let mut top_quality: f32 = 0.0;
+        for item in accept.split(',') {
+            let mut parts = item.split(';').map(str::trim);
+            let media_type = parts.next().unwrap_or_default().to_lowercase();
+            let quality = parts
+                .find_map(|param| param.strip_prefix("q="))
+                .and_then(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if !media_type.is_empty() {
+                top_quality = top_quality.max(quality);
+            }
+
+            if let Some(format) = Self::from_media_type(&media_type)
+                && quality > 0.0
+                && best.is_none_or(|(_, best_quality)| quality > best_quality)
+            {
+                best = Some((format, quality));
+            }
+        }
+
+        match best {
+            Some((Self::Xml, quality)) if quality < top_quality => Self::Json,
+            best => best.map(|(format, _)| format).unwrap_or_default(),
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::MsgPack => "application/msgpack",
+            Self::Xml => "application/xml",
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ResponseFormat
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_accept(&parts.headers))
+    }
+}
+
+/// Representation of decimal rates and amounts in response body.
+/// Default to strings so JS clients don't lose precision parsing them as floating point numbers.
+/// Numbers are negotiated with `decimals=number` query param, or `decimals=number` parameter of `Accept` media type,
+/// e.g. `Accept: application/json; decimals=number`. Query param wins over `Accept`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DecimalFormat {
+    #[default]
+    String,
+    Number,
+}
+
+impl DecimalFormat {
+    const PARAM: &str = "decimals";
+
+    fn from_value(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "string" => Some(Self::String),
+            "number" => Some(Self::Number),
+            _ => None,
+        }
+    }
+
+    fn from_query(query: &str) -> Option<&str> {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(name, _)| *name == Self::PARAM)
+            .map(|(_, value)| value)
+    }
+
+    fn from_accept(headers: &HeaderMap) -> Option<&str> {
+        headers
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())?
+            .split([',', ';'])
+            .filter_map(|param| param.split_once('='))
+            .find(|(name, _)| name.trim() == Self::PARAM)
+            .map(|(_, value)| value)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for DecimalFormat
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let value = parts
+            .uri
+            .query()
+            .and_then(Self::from_query)
+            .or_else(|| Self::from_accept(&parts.headers));
+
         match value {
-            ForexError::Error(v) => Self::NoContent(v.to_string()),
-            ForexError::ClientError(v) => Self::BadRequest(v.to_string()),
-            ForexError::InternalError(v) => Self::InternalServerError(v.to_string()),
+            Some(value) => Self::from_value(value).ok_or(AppError::BadRequest(
+                "Invalid decimals, expected string or number".to_string(),
+            )),
+            None => Ok(Self::default()),
         }
     }
 }
@@ -172,6 +589,102 @@ where
     }
 }
 
+/// Role of api key, each role is allowed everything lower roles are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// read rates, goals, watchlists and admin reports.
+    Reader,
+    /// mutate goals and watchlists.
+    Writer,
+    /// trigger polls and job reruns.
+    Admin,
+}
+
+/// minimum role required by routes.
+pub trait RequiredRole: Send + Sync {
+    const ROLE: Role;
+}
+
+pub struct ReaderRole;
+
+impl RequiredRole for ReaderRole {
+    const ROLE: Role = Role::Reader;
+}
+
+pub struct WriterRole;
+
+impl RequiredRole for WriterRole {
+    const ROLE: Role = Role::Writer;
+}
+
+pub struct AdminRole;
+
+impl RequiredRole for AdminRole {
+    const ROLE: Role = Role::Admin;
+}
+
+/// rejects request whose role resolved by auth middleware is lower than required,
+/// used as route layer: `route_layer(from_extractor::<Authorized<WriterRole>>())`.
+pub struct Authorized<R>(PhantomData<R>);
+
+#[async_trait]
+impl<S, R> FromRequestParts<S> for Authorized<R>
+where
+    S: Send + Sync,
+    R: RequiredRole,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(role) = parts.extensions.get::<Role>() else {
+            return Err(AppError::Unauthorized(
+                "request requires api key or admin password".to_string(),
+            ));
+        };
+        if *role < R::ROLE {
+            return Err(AppError::Forbidden(format!(
+                "request requires {:?} role",
+                R::ROLE
+            )));
+        }
+
+        Ok(Self(PhantomData))
+    }
+}
+
+pub const TENANT_ID_HEADER_NAME: &str = "x-tenant-id";
+
+/// tenant resolved by tenant middleware from request's credential, none if multi-tenancy is disabled.
+#[derive(Debug, Clone, Default)]
+pub struct Tenant(pub Option<TenantId>);
+
+impl Tenant {
+    /// storage scoped to this tenant, or shared storage if there's no tenant.
+    pub fn scope<T: TenantScoped + Clone>(&self, storage: &T) -> T {
+        match &self.0 {
+            Some(tenant) => storage.for_tenant(tenant),
+            None => storage.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Tenant
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<Tenant>()
+            .cloned()
+            .unwrap_or_default())
+    }
+}
+
 // deserialize date from YYYY-MM-DD into YYYY-MM-DDThh:mm:ssZ utc
 pub fn deserialize_optional_date<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
 where
@@ -212,3 +725,72 @@ where
 
     Ok(dt)
 }
+
+// deserialize comma separated dates from YYYY-MM-DD into utc dates, e.g. 2024-01-31,2024-02-29
+pub fn deserialize_dates<'de, D>(deserializer: D) -> Result<Vec<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: String = String::deserialize(deserializer)?;
+    s.split(',')
+        .map(|date| {
+            NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+                .map(|date| date.and_time(Default::default()).and_utc())
+                .map_err(|_| serde::de::Error::custom("Invalid date format, expected YYYY-MM-DD"))
+        })
+        .collect()
+}
+
+/// rates of currencies enabled in this deployment, keyed by their codes as serialized in rates data.
+pub fn enabled_rates(rates: &RatesData) -> Result<Map<String, Value>, AppError> {
+    let Ok(Value::Object(rates)) = serde_json::to_value(rates) else {
+        return Err(AppError::InternalServerError(
+            "failed serializing rates".to_string(),
+        ));
+    };
+    let enabled = pfm_core::global::config().enabled_currencies();
+
+    Ok(rates
+        .into_iter()
+        .filter(|(code, _)| enabled.iter().any(|c| c.code().eq_ignore_ascii_case(code)))
+        .collect())
+}
+
+/// currencies of comma separated codes, e.g. `IDR,EUR,XAU`, to project rates into.
+pub fn parse_symbols(symbols: &str) -> Result<HashSet<Currency>, AppError> {
+    Ok(symbols
+        .split(',')
+        .map(|code| Currency::from_str(code.trim().to_uppercase().as_str()))
+        .collect::<Result<HashSet<Currency>, _>>()?)
+}
+
+/// pair in form of BASE/QUOTE, e.g. USD/IDR
+pub fn parse_pair(pair: &str) -> Result<(Currency, Currency), AppError> {
+    let Some((base, quote)) = pair.split_once('/') else {
+        return Err(AppError::BadRequest(format!(
+            "invalid pair {}, expected <BASE>/<QUOTE>",
+            pair
+        )));
+    };
+
+    Ok((base.trim().parse()?, quote.trim().parse()?))
+}
+
+/// only rates of the currencies, all rates if none, shrinking payloads of clients needing few of them.
+pub fn project_rates(
+    rates: Map<String, Value>,
+    currencies: Option<&HashSet<Currency>>,
+) -> Map<String, Value> {
+    let Some(currencies) = currencies else {
+        return rates;
+    };
+
+    rates
+        .into_iter()
+        .filter(|(code, _)| {
+            currencies
+                .iter()
+                .any(|c| c.code().eq_ignore_ascii_case(code))
+        })
+        .collect()
+}
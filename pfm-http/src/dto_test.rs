@@ -0,0 +1,193 @@
+use std::collections::HashSet;
+
+use axum::body::to_bytes;
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::IntoResponse;
+use pfm_core::forex::{Currency, ForexError, entity::RatesData};
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+
+use crate::dto::{
+    AppError, DecimalFormat, HttpResponse, ResponseFormat, parse_symbols, project_rates,
+};
+
+fn data() -> Value {
+    json!({
+        "id": "12345",
+        "date": "2025-03-04",
+        "rates": {"usd": "1", "idr": "16250.123456789012345678"},
+        "points": [{"rate": "1.5", "label": "7"}],
+        "amount": "007",
+    })
+}
+
+async fn response_body(format: ResponseFormat, decimals: DecimalFormat) -> Vec<u8> {
+    let response = HttpResponse::ok_as(format, decimals, data(), None).unwrap();
+    to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap()
+        .to_vec()
+}
+
+#[tokio::test]
+async fn test_decimals_as_numbers_json() {
+    let body = String::from_utf8(response_body(ResponseFormat::Json, DecimalFormat::Number).await)
+        .unwrap();
+
+    // decimals are written as they are, keeping their precision
+    assert!(
+        body.contains(r#""idr":16250.123456789012345678"#),
+        "{}",
+        body
+    );
+    assert!(body.contains(r#""usd":1"#), "{}", body);
+    assert!(body.contains(r#""rate":1.5"#), "{}", body);
+    // strings of other fields, or not serialized from decimals, are kept
+    assert!(body.contains(r#""id":"12345""#), "{}", body);
+    assert!(body.contains(r#""label":"7""#), "{}", body);
+    assert!(body.contains(r#""amount":"007""#), "{}", body);
+
+    let body = String::from_utf8(response_body(ResponseFormat::Json, DecimalFormat::String).await)
+        .unwrap();
+    assert!(
+        body.contains(r#""idr":"16250.123456789012345678""#),
+        "{}",
+        body
+    );
+}
+
+#[tokio::test]
+async fn test_decimals_as_numbers_msgpack() {
+    let body: Value =
+        rmp_serde::from_slice(&response_body(ResponseFormat::MsgPack, DecimalFormat::Number).await)
+            .unwrap();
+
+    assert_eq!(
+        body["data"]["rates"]["idr"],
+        json!("16250.123456789012345678".parse::<f64>().unwrap())
+    );
+    assert_eq!(body["data"]["points"][0]["rate"], json!(1.5));
+    assert_eq!(body["data"]["id"], json!("12345"));
+}
+
+#[tokio::test]
+async fn test_forex_error_mapping() {
+    let cases = [
+        (
+            ForexError::error("provider returned garbage"),
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal_error",
+        ),
+        (
+            ForexError::client_error("unknown currency"),
+            StatusCode::BAD_REQUEST,
+            "invalid_input",
+        ),
+        (
+            ForexError::not_found_error("latest rates not found"),
+            StatusCode::NOT_FOUND,
+            "not_found",
+        ),
+        (
+            ForexError::internal_error("failed reading /data/latest.json"),
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal_error",
+        ),
+    ];
+
+    for (err, status, code) in cases {
+        let response = AppError::from(err).into_response();
+        assert_eq!(response.status(), status);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["code"], code);
+        if status == StatusCode::INTERNAL_SERVER_ERROR {
+            let message = body["error"]["message"].as_str().unwrap();
+            assert!(!message.contains("garbage") && !message.contains("/data"));
+        }
+    }
+}
+
+#[test]
+fn test_response_format_from_accept() {
+    let cases = [
+        ("", ResponseFormat::Json),
+        ("application/xml", ResponseFormat::Xml),
+        ("text/xml, application/json;q=0.5", ResponseFormat::Xml),
+        (
+            "application/xml;q=0.5, application/json",
+            ResponseFormat::Json,
+        ),
+        (
+            "application/json;q=0.5, application/msgpack",
+            ResponseFormat::MsgPack,
+        ),
+        (
+            "application/msgpack;q=0, application/xml;q=0.1",
+            ResponseFormat::Xml,
+        ),
+        ("image/png", ResponseFormat::Json),
+        // browsers
+        (
+            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+            ResponseFormat::Json,
+        ),
+        (
+            "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8",
+            ResponseFormat::Json,
+        ),
+    ];
+
+    for (accept, expected) in cases {
+        let mut headers = HeaderMap::new();
+        if !accept.is_empty() {
+            headers.insert(header::ACCEPT, HeaderValue::from_static(accept));
+        }
+        assert_eq!(
+            ResponseFormat::from_accept(&headers),
+            expected,
+            "{}",
+            accept
+        );
+    }
+}
+
+#[test]
+fn test_parse_symbols_and_project_rates() {
+    let symbols = parse_symbols("idr, EUR,IDR,xau").unwrap();
+    assert_eq!(
+        symbols,
+        HashSet::from([Currency::IDR, Currency::EUR, Currency::XAU])
+    );
+    for invalid in ["", "IDR,", "IDR,XYZ", "US D"] {
+        assert!(
+            matches!(
+                parse_symbols(invalid),
+                Err(AppError::BadRequest(_) | AppError::InvalidInput { .. })
+            ),
+            "{}",
+            invalid
+        );
+    }
+
+    let rates = RatesData {
+        usd: Decimal::ONE,
+        idr: Decimal::from(16000),
+        eur: Decimal::new(9, 1),
+        ..Default::default()
+    };
+    let rates = serde_json::to_value(&rates)
+        .unwrap()
+        .as_object()
+        .unwrap()
+        .clone();
+    let all = project_rates(rates.clone(), None);
+    assert_eq!(all, rates);
+
+    // currencies without rates are still projected as they're serialized
+    let projected = project_rates(rates, Some(&symbols));
+    let mut codes: Vec<&str> = projected.keys().map(String::as_str).collect();
+    codes.sort();
+    assert_eq!(codes, vec!["eur", "idr", "xau"]);
+    assert_eq!(projected["idr"], "16000");
+}
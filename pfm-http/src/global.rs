@@ -1,36 +1,146 @@
-use std::sync::LazyLock;
+use std::sync::{
+    LazyLock,
+    atomic::{AtomicBool, Ordering},
+};
 
 use pfm_core::{
-    forex_impl::currencybeacon::Api as CurrencyBeaconApi,
-    forex_impl::{
-        self,
-        forex_storage::{self, ForexStorageImpl},
+    api::{
+        integrations::RatesSigner,
+        providers::{self, Provider},
+        storage::ForexStorageImpl,
     },
-    global,
+    global::{self, BuildInfo},
 };
 use pfm_utils::config_util;
 use serde::Deserialize;
 
 #[derive(Debug, Clone, Deserialize)]
 pub(crate) struct AppConfig {
+    /// bind address of public(read-only) endpoints
+    #[serde(alias = "HTTP_HOST", default = "default_http_host")]
+    pub http_host: String,
+
     #[serde(alias = "HTTP_PORT")]
     pub http_port: u16,
 
+    /// if set, admin endpoints are served on this port instead of the public one
+    #[serde(alias = "HTTP_ADMIN_PORT", default)]
+    pub http_admin_port: Option<u16>,
+
+    /// bind address of admin endpoints, used only if http_admin_port is set
+    #[serde(alias = "HTTP_ADMIN_HOST", default = "default_http_host")]
+    pub http_admin_host: String,
+
+    /// if enabled without http_admin_port, admin endpoints are not served at all
+    #[serde(alias = "HTTP_PUBLIC_ONLY", default)]
+    pub http_public_only: bool,
+
+    /// if enabled, responses are gzip or brotli compressed as accepted by client
+    #[serde(alias = "HTTP_COMPRESSION", default = "default_compression")]
+    pub compression: bool,
+
+    /// max size of request body, larger requests are rejected with 413
+    #[serde(alias = "HTTP_BODY_LIMIT_BYTES", default = "default_body_limit_bytes")]
+    pub body_limit_bytes: usize,
+
     /// if enabled, accessing APIs must provide valid api key
     #[serde(alias = "HTTP_ENABLE_API_KEY")]
     pub enable_api_key: bool,
 
+    /// if enabled, goals and watchlists are scoped to tenant bound to api key, or given in x-tenant-id header with admin password
+    #[serde(alias = "HTTP_MULTI_TENANCY", default)]
+    pub multi_tenancy: bool,
+
+    /// if enabled, dashboard requires signing in with local user account
+    #[cfg(feature = "dashboard")]
+    #[serde(alias = "HTTP_DASHBOARD_AUTH", default)]
+    pub dashboard_auth: bool,
+
+    /// lifetime of dashboard session
+    #[cfg(feature = "dashboard")]
+    #[serde(alias = "HTTP_SESSION_TTL_SECS", default = "default_session_ttl_secs")]
+    pub session_ttl_secs: u64,
+
+    /// if enabled and storage is empty on startup, latest and recent historical rates are fetched from provider
+    #[serde(alias = "HTTP_BOOTSTRAP_FETCH", default)]
+    pub bootstrap_fetch: bool,
+
+    /// days of historical rates fetched on bootstrap
+    #[serde(alias = "HTTP_BOOTSTRAP_DAYS", default = "default_bootstrap_days")]
+    pub bootstrap_days: u32,
+
+    /// Retry-After of 503 responses while storage is empty
+    #[serde(
+        alias = "HTTP_BOOTSTRAP_RETRY_AFTER_SECS",
+        default = "default_bootstrap_retry_after_secs"
+    )]
+    pub bootstrap_retry_after_secs: u64,
+
+    /// max number of rates files kept in memory, 0 disables caching
+    #[serde(alias = "HTTP_CACHE_CAPACITY", default = "default_cache_capacity")]
+    pub cache_capacity: usize,
+
+    /// days of historical rates loaded into cache on startup, before listening
+    #[serde(alias = "HTTP_CACHE_WARM_DAYS", default = "default_cache_warm_days")]
+    pub cache_warm_days: u32,
+
     /// provided from env var, NOT file
     #[serde(alias = "HTTP_ADMIN_PASSWORD")]
     pub admin_password: String,
 }
 
+fn default_http_host() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_bootstrap_days() -> u32 {
+    7
+}
+
+fn default_bootstrap_retry_after_secs() -> u64 {
+    60
+}
+
+fn default_cache_capacity() -> usize {
+    512
+}
+
+fn default_cache_warm_days() -> u32 {
+    30
+}
+
+fn default_compression() -> bool {
+    true
+}
+
+fn default_body_limit_bytes() -> usize {
+    2 * 1024 * 1024
+}
+
+#[cfg(feature = "dashboard")]
+fn default_session_ttl_secs() -> u64 {
+    7 * 24 * 60 * 60
+}
+
 static CONFIG: LazyLock<AppConfig> = LazyLock::new(|| {
     let cfg =
         config_util::get_config::<AppConfig>("HTTP_").expect("pfm-http failed reading config");
     cfg
 });
 
+static BUILD_INFO: LazyLock<BuildInfo> = LazyLock::new(|| {
+    BuildInfo::from_flags(
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        &[("dashboard", cfg!(feature = "dashboard"))],
+    )
+});
+
+/// get versions and build information of pfm-http
+pub(crate) fn build_info() -> &'static BuildInfo {
+    &BUILD_INFO
+}
+
 /// get configs of pfm-http
 pub(crate) fn config() -> &'static AppConfig {
     &CONFIG
@@ -42,12 +152,22 @@ pub(crate) struct AppContext<FS, FH> {
     pub forex_historical: FH,
 }
 
-static CONTEXT: LazyLock<AppContext<ForexStorageImpl, CurrencyBeaconApi>> = LazyLock::new(|| {
-    let forex_storage = forex_storage::ForexStorageImpl::new(global::storage_fs());
-    let forex_historical = forex_impl::currencybeacon::Api::new(
-        &global::config().forex_currencybeacon_api_key,
+static CONTEXT: LazyLock<AppContext<ForexStorageImpl, Provider>> = LazyLock::new(|| {
+    let mut forex_storage = ForexStorageImpl::new(global::storage_fs());
+    if !global::config().forex_signing_key.is_empty() {
+        let signer = RatesSigner::from_seed_hex(&global::config().forex_signing_key)
+            .expect("pfm-http failed parsing forex signing key");
+        forex_storage = forex_storage.with_signer(signer);
+    }
+    if config().cache_capacity > 0 {
+        forex_storage = forex_storage.with_cache(config().cache_capacity);
+    }
+    let forex_historical = providers::provider_from_config(
+        global::config(),
         global::http_client(),
-    );
+        forex_storage.clone(),
+    )
+    .expect("pfm-http failed creating forex provider");
     let ctx = AppContext {
         forex_storage,
         forex_historical,
@@ -57,6 +177,18 @@ static CONTEXT: LazyLock<AppContext<ForexStorageImpl, CurrencyBeaconApi>> = Lazy
 });
 
 /// get dependencies of pfm-http
-pub(crate) fn context() -> AppContext<ForexStorageImpl, CurrencyBeaconApi> {
+pub(crate) fn context() -> AppContext<ForexStorageImpl, Provider> {
     CONTEXT.clone()
 }
+
+// set on startup if storage is empty, cleared once rates are stored
+static BOOTSTRAPPING: AtomicBool = AtomicBool::new(false);
+
+/// whether storage had no rates yet when last checked
+pub(crate) fn is_bootstrapping() -> bool {
+    BOOTSTRAPPING.load(Ordering::Relaxed)
+}
+
+pub(crate) fn set_bootstrapping(bootstrapping: bool) {
+    BOOTSTRAPPING.store(bootstrapping, Ordering::Relaxed);
+}
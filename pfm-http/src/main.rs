@@ -1,40 +1,158 @@
 mod dto;
+#[cfg(test)]
+mod dto_test;
 mod global;
 mod middlewares;
+#[cfg(test)]
+mod middlewares_test;
 mod routes;
+#[cfg(test)]
+mod routes_test;
+#[cfg(feature = "dashboard")]
+mod session;
 
 use std::sync::Arc;
 
+use axum::Router;
+use pfm_core::api;
+use pfm_core::forex::interface::ForexStorageBootstrap;
 use pfm_utils::{graceful_util, tracing_util};
 use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
 
 #[tokio::main]
 async fn main() {
+    if std::env::args().any(|arg| arg == "--version") {
+        println!("pfm-http {}", global::build_info().detail());
+        return;
+    }
+
     tracing_util::init_tracing("pfm-http");
+    tracing::info!("starting pfm-http {}", global::build_info().detail());
+    pfm_core::global::validate_storage().expect("http validating storage");
 
     // graceful shutdown
     let notify_signal = Arc::new(Notify::new());
     graceful_util::graceful_shutdown(notify_signal.clone(), Some(do_cleanup())).await;
 
-    let routes = routes::register_routes();
+    // every listener shuts down gracefully on the same signal
+    let shutdown = CancellationToken::new();
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            graceful_util::wait_for_shutdown(notify_signal).await;
+            shutdown.cancel();
+        }
+    });
+
+    pfm_core::global::init_secrets(shutdown.clone())
+        .await
+        .expect("http resolving secrets");
+    bootstrap(&shutdown).await;
+    warm_cache().await;
 
-    let addr = ("127.0.0.1", global::config().http_port);
+    let cfg = global::config();
+    let public_addr = (cfg.http_host.as_str(), cfg.http_port);
+    match (cfg.http_admin_port, cfg.http_public_only) {
+        (Some(admin_port), _) => {
+            let admin_addr = (cfg.http_admin_host.as_str(), admin_port);
+            let public_routes = routes::register_public_routes();
+            let admin_routes = routes::register_admin_routes();
+            tokio::join!(
+                serve("public", public_addr, public_routes, &shutdown),
+                serve("admin", admin_addr, admin_routes, &shutdown),
+            );
+        }
+        (None, true) => {
+            tracing::info!("pfm-http running in public only mode, admin endpoints disabled");
+            let public_routes = routes::register_public_routes();
+            serve("public", public_addr, public_routes, &shutdown).await;
+        }
+        (None, false) => {
+            let all_routes = routes::register_routes();
+            serve("pfm-http", public_addr, all_routes, &shutdown).await;
+        }
+    }
+}
 
+async fn serve(name: &str, addr: (&str, u16), routes: Router, shutdown: &CancellationToken) {
     let listener = tokio::net::TcpListener::bind(addr)
         .await
         .expect("httpserver: failed listening to tcp");
 
     tracing::info!(
-        "pfm-http server is running listening on {}",
+        "{} server is running listening on {}",
+        name,
         listener.local_addr().expect("httpserver: invalid address")
     );
 
     axum::serve(listener, routes)
-        .with_graceful_shutdown(graceful_util::wait_for_shutdown(notify_signal))
+        .with_graceful_shutdown(shutdown.clone().cancelled_owned())
         .await
         .expect("httpserver failed");
 }
 
+/// detect fresh deployment with empty storage, optionally fetching initial rates in background.
+/// forex endpoints respond 503 until rates are stored.
+async fn bootstrap(shutdown: &CancellationToken) {
+    let ctx = global::context();
+    match ctx.forex_storage.is_empty().await {
+        Ok(false) => return,
+        Ok(true) => {}
+        Err(err) => {
+            tracing::error!("bootstrap failed checking storage: {}", err);
+            return;
+        }
+    }
+
+    tracing::warn!("storage is empty, forex endpoints respond 503 until rates are stored");
+    global::set_bootstrapping(true);
+    if !global::config().bootstrap_fetch {
+        return;
+    }
+
+    let cancel = shutdown.clone();
+    tokio::spawn(async move {
+        match api::forex::bootstrap(
+            &ctx.forex_historical,
+            &ctx.forex_storage,
+            &pfm_core::global::SystemClock,
+            pfm_core::global::constants::BASE_CURRENCY,
+            global::config().bootstrap_days,
+            &cancel,
+        )
+        .await
+        {
+            Ok(()) => {
+                tracing::info!("bootstrap done, initial rates are stored");
+                global::set_bootstrapping(false);
+            }
+            Err(err) => tracing::error!("bootstrap failed fetching initial rates: {}", err),
+        }
+    });
+}
+
+/// load latest and recent historical rates into cache before listening, avoiding slow first requests after deploys.
+async fn warm_cache() {
+    let cfg = global::config();
+    if cfg.cache_capacity == 0 || global::is_bootstrapping() {
+        return;
+    }
+
+    let ctx = global::context();
+    let warmed = api::forex::warm_cache(
+        &ctx.forex_storage,
+        &pfm_core::global::SystemClock,
+        cfg.cache_warm_days,
+    )
+    .await;
+    tracing::info!(
+        "cache warmed with {} rates, {} files cached",
+        warmed,
+        ctx.forex_storage.cached()
+    );
+}
+
 /// cleanup routine to run before shutdown
 async fn do_cleanup() {
     tracing::info!("cleanup start...");
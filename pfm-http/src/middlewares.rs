@@ -4,13 +4,28 @@ use std::{
     sync::{Arc, LazyLock},
 };
 
-use axum::{body::Body, extract::Request, http::HeaderValue, middleware::Next, response::Response};
-use chrono::{DateTime, Utc};
+#[cfg(feature = "dashboard")]
+use axum::response::Redirect;
+use axum::{
+    body::Body,
+    extract::{OriginalUri, Request},
+    http::{HeaderValue, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use pfm_core::api::integrations::{
+    INTERNAL_SIGNATURE_HEADER, INTERNAL_TIMESTAMP_HEADER, InternalSigner,
+};
+use pfm_core::forex::{ForexError, interface::ForexStorageBootstrap};
+use pfm_core::global::TenantId;
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
-use tracing::{info_span, Instrument};
+use tracing::{Instrument, info_span};
 use uuid::Uuid;
 
+#[cfg(feature = "dashboard")]
+use crate::session;
 use crate::{dto::*, global};
 
 pub(crate) async fn processing_time_middleware(req: Request<Body>, next: Next) -> Response {
@@ -29,26 +44,205 @@ pub(crate) async fn processing_time_middleware(req: Request<Body>, next: Next) -
     response
 }
 
-pub(crate) async fn admin_password_middleware(
+/// error bodies re-encoded into format negotiated from request's `Accept` header, as ok bodies of handlers are.
+pub(crate) async fn error_format_middleware(req: Request<Body>, next: Next) -> Response {
+    let format = ResponseFormat::from_accept(req.headers());
+    let response = next.run(req).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+    if format == ResponseFormat::Json || response.status().is_success() || !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, usize::MAX).await else {
+        return AppError::InternalServerError("failed reading error response".to_string())
+            .into_response();
+    };
+    let encoded = serde_json::from_slice::<serde_json::Value>(&bytes)
+        .map_err(|err| err.to_string())
+        .and_then(|value| encode(format, DecimalFormat::String, &value));
+    match encoded {
+        Ok(encoded) => {
+            parts.headers.remove(header::CONTENT_LENGTH);
+            parts.headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(format.content_type()),
+            );
+            Response::from_parts(parts, Body::from(encoded))
+        }
+        Err(err) => {
+            tracing::warn!("error response kept as json, failed encoding: {}", err);
+            Response::from_parts(parts, Body::from(bytes))
+        }
+    }
+}
+
+// verifies signed internal requests of pfm-cron, shared so replayed signatures are remembered across requests
+static INTERNAL_SIGNER: LazyLock<InternalSigner> = LazyLock::new(|| {
+    let cfg = pfm_core::global::config();
+    InternalSigner::new(
+        pfm_core::global::secrets().get(pfm_core::global::SECRET_INTERNAL_SECRET),
+        TimeDelta::seconds(cfg.internal_max_skew_secs as i64),
+    )
+});
+
+/// endpoints triggered by pfm-cron with signed internal requests, internal secret grants nothing else.
+const INTERNAL_ENDPOINTS: &[(&str, &str)] = &[("POST", "/admin/cache/warm")];
+
+/// resolve role of admin request: admin password grants admin role,
+/// otherwise role of api key configured with one if api keys are enabled.
+pub(crate) async fn admin_auth_middleware(
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, AppError> {
+    let cfg = global::config();
+    let api_keys = cfg.enable_api_key.then(|| API_KEYS.as_ref());
+    admin_auth(&cfg.admin_password, api_keys, req, next).await
+}
+
+/// valid signed internal request of pfm-cron grants admin role, otherwise resolved like other admin requests.
+/// layered only on internal endpoints.
+pub(crate) async fn internal_auth_middleware(
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, AppError> {
+    internal_auth(&INTERNAL_SIGNER, req, next).await
+}
+
+pub(crate) async fn admin_auth(
+    admin_password: &str,
+    api_keys: Option<&HashMap<String, ApiKey>>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Result<Response, AppError> {
+    let (role, credential_tenant) = admin_role(&req, admin_password, api_keys)?;
+    req.extensions_mut().insert(role);
+    req.extensions_mut().insert(credential_tenant);
+
+    Ok(next.run(req).await)
+}
+
+pub(crate) async fn internal_auth(
+    signer: &InternalSigner,
     req: Request<Body>,
     next: Next,
 ) -> Result<Response, AppError> {
-    let admin_pass_header = req
+    if !req.headers().contains_key(INTERNAL_SIGNATURE_HEADER) {
+        return admin_auth_middleware(req, next).await;
+    }
+
+    let path = original_uri(&req).path();
+    if !INTERNAL_ENDPOINTS
+        .iter()
+        .any(|(method, endpoint)| req.method().as_str() == *method && path == *endpoint)
+    {
+        return Err(AppError::Unauthorized(
+            "internal requests are not allowed for this endpoint".to_string(),
+        ));
+    }
+    let mut req = verify_internal_request(signer, req).await?;
+    req.extensions_mut().insert(Role::Admin);
+    req.extensions_mut().insert(CredentialTenant::Any);
+
+    Ok(next.run(req).await)
+}
+
+/// tenants a credential may act for when multi-tenancy is enabled.
+#[derive(Debug, Clone)]
+pub(crate) enum CredentialTenant {
+    /// admin password and internal requests, acting for tenant given in x-tenant-id header
+    Any,
+    /// api key bound to its tenant
+    Bound(TenantId),
+    /// api key without tenant, not allowed on tenant scoped routes
+    Unbound,
+}
+
+fn admin_role(
+    req: &Request<Body>,
+    admin_password: &str,
+    api_keys: Option<&HashMap<String, ApiKey>>,
+) -> Result<(Role, CredentialTenant), AppError> {
+    if let Some(pass) = req
         .headers()
         .get("x-admin-password")
-        .and_then(|v| v.to_str().ok());
-
-    if let Some(pass) = admin_pass_header {
-        if pass != global::config().admin_password.as_str() {
+        .and_then(|v| v.to_str().ok())
+    {
+        if pass != admin_password {
             return Err(AppError::Unauthorized("admin unauthorized".to_string()));
         }
-    } else {
-        return Err(AppError::BadRequest(
-            "admin password not provided".to_string(),
-        ));
+        return Ok((Role::Admin, CredentialTenant::Any));
     }
 
-    Ok(next.run(req).await)
+    if let Some(api_keys) = api_keys
+        && let Some(api_key) = request_api_key(req)
+    {
+        let Some(api_key) = api_keys.values().find(|v| v.key() == api_key) else {
+            return Err(AppError::Unauthorized(
+                "request's api key is invalid".to_string(),
+            ));
+        };
+        let role = api_key
+            .admin_role()
+            .ok_or(AppError::Forbidden("api key has no admin role".to_string()))?;
+        return Ok((role, api_key.tenant()));
+    }
+
+    Err(AppError::BadRequest(
+        "admin password not provided".to_string(),
+    ))
+}
+
+// signed with full path, while nested routers see it without their prefix
+fn original_uri(req: &Request<Body>) -> &axum::http::Uri {
+    req.extensions()
+        .get::<OriginalUri>()
+        .map_or(req.uri(), |original| &original.0)
+}
+
+/// verify signature and timestamp of internal request against its method, path and body.
+async fn verify_internal_request(
+    signer: &InternalSigner,
+    req: Request<Body>,
+) -> Result<Request<Body>, AppError> {
+    let (parts, body) = req.into_parts();
+    // size is already limited by body limit layer
+    let body = axum::body::to_bytes(body, usize::MAX)
+        .await
+        .map_err(|err| AppError::BadRequest(format!("failed reading request body: {}", err)))?;
+
+    let header = |name: &str| {
+        parts
+            .headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+    };
+    let uri = parts
+        .extensions
+        .get::<OriginalUri>()
+        .map_or(&parts.uri, |original| &original.0);
+    let path = uri
+        .path_and_query()
+        .map_or_else(|| uri.path(), |path| path.as_str());
+    signer
+        .verify(
+            parts.method.as_str(),
+            path,
+            header(INTERNAL_TIMESTAMP_HEADER),
+            header(INTERNAL_SIGNATURE_HEADER),
+            &body,
+            Utc::now(),
+        )
+        .map_err(|err| match err {
+            ForexError::ClientError(_) => AppError::Unauthorized(err.message()),
+            err => AppError::from(err),
+        })?;
+
+    Ok(Request::from_parts(parts, Body::from(body)))
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -127,47 +321,195 @@ fn check_rate_limit(data: &mut RateLimitData) -> bool {
     false
 }
 
+/// api key, either plain key reading /forex endpoints or key with its role, also accessing admin endpoints.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum ApiKey {
+    Key(String),
+    WithRole {
+        key: String,
+        role: Role,
+        /// tenant the key acts for when multi-tenancy is enabled
+        #[serde(default)]
+        tenant: Option<TenantId>,
+    },
+}
+
+impl ApiKey {
+    fn key(&self) -> &str {
+        match self {
+            Self::Key(key) => key,
+            Self::WithRole { key, .. } => key,
+        }
+    }
+
+    fn role(&self) -> Role {
+        match self {
+            Self::Key(_) => Role::Reader,
+            Self::WithRole { role, .. } => *role,
+        }
+    }
+
+    fn tenant(&self) -> CredentialTenant {
+        match self {
+            Self::WithRole {
+                tenant: Some(tenant),
+                ..
+            } => CredentialTenant::Bound(tenant.clone()),
+            _ => CredentialTenant::Unbound,
+        }
+    }
+
+    /// role on admin endpoints, only keys configured with role have one.
+    fn admin_role(&self) -> Option<Role> {
+        match self {
+            Self::Key(_) => None,
+            Self::WithRole { role, .. } => Some(*role),
+        }
+    }
+}
+
 // contains api keys for client to access these apis
-static API_KEYS: LazyLock<Arc<HashMap<String, String>>> = LazyLock::new(|| {
+static API_KEYS: LazyLock<Arc<HashMap<String, ApiKey>>> = LazyLock::new(|| {
     let content = fs::read_to_string("api_keys.json")
         .expect("Loading api_keys.json: Failed to read api_keys.json");
-    let parsed: HashMap<String, String> =
+    let parsed: HashMap<String, ApiKey> =
         serde_json::from_str(&content).expect("Loading api_keys.json: Invalid JSON format");
     Arc::new(parsed)
 });
 
+/// api key from x-api-key header, or api_key query param if header not set
+fn request_api_key(req: &Request<Body>) -> Option<String> {
+    if let Some(header_api_key) = req.headers().get("x-api-key").and_then(|v| v.to_str().ok()) {
+        return Some(header_api_key.to_string());
+    }
+
+    req.uri().query().and_then(|query| {
+        url::form_urlencoded::parse(query.as_bytes())
+            .find(|(k, _)| k == "api_key")
+            .map(|(_, v)| v.to_string())
+    })
+}
+
+fn api_key_role(api_key: &str) -> Option<Role> {
+    API_KEYS
+        .values()
+        .find(|v| v.key() == api_key)
+        .map(ApiKey::role)
+}
+
 pub(crate) async fn api_key_middleware(
-    req: Request<Body>,
+    mut req: Request<Body>,
     next: Next,
 ) -> Result<Response, AppError> {
-    let header_api_key = req.headers().get("x-api-key").and_then(|v| v.to_str().ok());
-
-    let api_key_val = if let Some(header_api_key_val) = header_api_key {
-        header_api_key_val.to_string()
-    } else {
-        // check from query param if header not set
-        let Some(query_param_api_key_val) = req.uri().query().and_then(|query| {
-            url::form_urlencoded::parse(query.as_bytes())
-                .find(|(k, _)| k == "api_key")
-                .map(|(_, v)| v.to_string())
-        }) else {
-            return Err(AppError::Unauthorized(
-                "request requires api key set in header as x-api-key OR in query param as api_key"
-                    .to_string(),
-            ));
-        };
-        query_param_api_key_val
+    let Some(api_key_val) = request_api_key(&req) else {
+        // dashboard reads rates with session of signed in user instead
+        #[cfg(feature = "dashboard")]
+        if session::request_username(req.headers()).await.is_some() {
+            req.extensions_mut().insert(Role::Reader);
+            return Ok(next.run(req).await);
+        }
+        return Err(AppError::Unauthorized(
+            "request requires api key set in header as x-api-key OR in query param as api_key"
+                .to_string(),
+        ));
     };
 
-    if !API_KEYS.values().any(|v| v == &api_key_val) {
+    let Some(role) = api_key_role(&api_key_val) else {
         return Err(AppError::Unauthorized(
             "request's api key is invalid".to_string(),
         ));
+    };
+    req.extensions_mut().insert(role);
+
+    Ok(next.run(req).await)
+}
+
+/// respond 503 with Retry-After while storage has no rates yet, instead of opaque internal errors.
+pub(crate) async fn bootstrap_middleware(
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, AppError> {
+    if global::is_bootstrapping() {
+        // rates may have been stored since, e.g. by cron
+        if global::context().forex_storage.is_empty().await? {
+            let mut response = AppError::ServiceUnavailable(
+                "no rates stored yet, storage is being bootstrapped".to_string(),
+            )
+            .into_response();
+            if let Ok(retry_after) =
+                HeaderValue::from_str(&global::config().bootstrap_retry_after_secs.to_string())
+            {
+                response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, retry_after);
+            }
+            return Ok(response);
+        }
+        global::set_bootstrapping(false);
     }
 
     Ok(next.run(req).await)
 }
 
+/// resolve tenant of request from its credential, layered inside admin auth only if multi-tenancy is enabled.
+/// api keys act for their bound tenant only, admin password must select tenant with x-tenant-id header.
+pub(crate) async fn tenant_middleware(
+    mut req: Request<Body>,
+    next: Next,
+) -> Result<Response, AppError> {
+    let header = match req.headers().get(TENANT_ID_HEADER_NAME) {
+        Some(value) => {
+            let Some(tenant) = value.to_str().ok().and_then(TenantId::parse) else {
+                return Err(AppError::BadRequest(
+                    "x-tenant-id must be 1-64 characters of letters, digits, `-` or `_`"
+                        .to_string(),
+                ));
+            };
+            Some(tenant)
+        }
+        None => None,
+    };
+    let credential = req
+        .extensions()
+        .get::<CredentialTenant>()
+        .cloned()
+        .unwrap_or(CredentialTenant::Unbound);
+    let tenant = match (credential, header) {
+        (CredentialTenant::Bound(bound), None) => bound,
+        (CredentialTenant::Bound(bound), Some(tenant)) if tenant == bound => bound,
+        (CredentialTenant::Bound(_), Some(_)) => {
+            return Err(AppError::Forbidden(
+                "api key is not allowed for this tenant".to_string(),
+            ));
+        }
+        (CredentialTenant::Any, Some(tenant)) => tenant,
+        (CredentialTenant::Any, None) => {
+            return Err(AppError::BadRequest(
+                "x-tenant-id is required when multi-tenancy is enabled".to_string(),
+            ));
+        }
+        (CredentialTenant::Unbound, _) => {
+            return Err(AppError::Forbidden(
+                "api key is not bound to a tenant".to_string(),
+            ));
+        }
+    };
+    req.extensions_mut().insert(Tenant(Some(tenant)));
+
+    Ok(next.run(req).await)
+}
+
+/// redirect to login page if request isn't signed in, used only if dashboard auth is enabled.
+#[cfg(feature = "dashboard")]
+pub(crate) async fn session_middleware(req: Request<Body>, next: Next) -> Response {
+    if session::request_username(req.headers()).await.is_none() {
+        return Redirect::to("/login").into_response();
+    }
+
+    next.run(req).await
+}
+
 const REQUEST_ID_HEADER_NAME: &str = "x-request-id";
 
 const CORRELATION_ID_HEADER_NAME: &str = "x-correlation-id";
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    body::Body,
+    extract::Request,
+    http::{Method, StatusCode},
+    middleware::{Next, from_extractor, from_fn},
+    routing::any,
+};
+use chrono::{TimeDelta, Utc};
+use pfm_core::api::integrations::{
+    INTERNAL_SIGNATURE_HEADER, INTERNAL_TIMESTAMP_HEADER, InternalSigner,
+};
+use pfm_core::global::Secret;
+use tower::ServiceExt;
+
+use crate::dto::{AdminRole, Authorized, ReaderRole, RequiredRole, Tenant, WriterRole};
+use crate::middlewares::{ApiKey, admin_auth, internal_auth, tenant_middleware};
+
+const ADMIN_PASSWORD: &str = "admin-secret";
+
+fn api_keys() -> Arc<HashMap<String, ApiKey>> {
+    Arc::new(
+        serde_json::from_value(serde_json::json!({
+            "public": "plain-key",
+            "reporting": {"key": "reader-key", "role": "reader"},
+            "mobile": {"key": "writer-key", "role": "writer"},
+            "ops": {"key": "admin-key", "role": "admin"},
+            "family": {"key": "family-key", "role": "writer", "tenant": "family"},
+        }))
+        .unwrap(),
+    )
+}
+
+/// admin route group requiring role R, authenticated like admin_routes.
+fn group<R: RequiredRole + 'static>(api_keys: Arc<HashMap<String, ApiKey>>) -> Router {
+    let routes = Router::new()
+        .route("/group", any(|| async { StatusCode::OK }))
+        .route_layer(from_extractor::<Authorized<R>>())
+        .layer(from_fn(move |req: Request, next: Next| {
+            let api_keys = api_keys.clone();
+            async move { admin_auth(ADMIN_PASSWORD, Some(&api_keys), req, next).await }
+        }));
+
+    Router::new().nest("/admin", routes)
+}
+
+async fn status(router: &Router, req: Request<Body>) -> StatusCode {
+    router.clone().oneshot(req).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn test_admin_role_matrix() {
+    let keys = api_keys();
+    let groups = [
+        ("read", group::<ReaderRole>(keys.clone())),
+        ("write", group::<WriterRole>(keys.clone())),
+        ("admin", group::<AdminRole>(keys)),
+    ];
+    let (ok, bad, unauthorized, forbidden) = (
+        StatusCode::OK,
+        StatusCode::BAD_REQUEST,
+        StatusCode::UNAUTHORIZED,
+        StatusCode::FORBIDDEN,
+    );
+    // expected status of read, write and admin groups
+    let matrix = [
+        (None, [bad, bad, bad]),
+        (Some(("x-api-key", "plain-key")), [forbidden; 3]),
+        (Some(("x-api-key", "unknown-key")), [unauthorized; 3]),
+        (
+            Some(("x-api-key", "reader-key")),
+            [ok, forbidden, forbidden],
+        ),
+        (Some(("x-api-key", "writer-key")), [ok, ok, forbidden]),
+        (Some(("x-api-key", "admin-key")), [ok; 3]),
+        (Some(("x-admin-password", ADMIN_PASSWORD)), [ok; 3]),
+        (Some(("x-admin-password", "wrong")), [unauthorized; 3]),
+    ];
+
+    for (credential, expected) in matrix {
+        for ((name, router), expected) in groups.iter().zip(expected) {
+            let mut req = Request::get("/admin/group");
+            if let Some((header, value)) = credential {
+                req = req.header(header, value);
+            }
+            let status = status(router, req.body(Body::empty()).unwrap()).await;
+            assert_eq!(status, expected, "{:?} on {} group", credential, name);
+        }
+    }
+}
+
+fn signer() -> InternalSigner {
+    InternalSigner::new(Secret::from("internal-secret"), TimeDelta::seconds(300))
+}
+
+/// internal route group authenticated like admin_routes' internal routes.
+fn internal_router(signer: InternalSigner) -> Router {
+    let routes = Router::new()
+        .route("/cache/warm", any(|| async { StatusCode::OK }))
+        .route("/sync", any(|| async { StatusCode::OK }))
+        .route_layer(from_extractor::<Authorized<AdminRole>>())
+        .layer(from_fn(move |req: Request, next: Next| {
+            let signer = signer.clone();
+            async move { internal_auth(&signer, req, next).await }
+        }));
+
+    Router::new().nest("/admin", routes)
+}
+
+fn signed(signer: &InternalSigner, method: Method, path: &str) -> Request<Body> {
+    let timestamp = Utc::now().timestamp();
+    Request::builder()
+        .method(method.clone())
+        .uri(path)
+        .header(INTERNAL_TIMESTAMP_HEADER, timestamp.to_string())
+        .header(
+            INTERNAL_SIGNATURE_HEADER,
+            signer.sign(method.as_str(), path, timestamp, b""),
+        )
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_internal_signature_accepted_at_warm_cache() {
+    let router = internal_router(signer());
+
+    let req = signed(&signer(), Method::POST, "/admin/cache/warm");
+    let replayed = Request::post("/admin/cache/warm")
+        .header(
+            INTERNAL_TIMESTAMP_HEADER,
+            req.headers()[INTERNAL_TIMESTAMP_HEADER].clone(),
+        )
+        .header(
+            INTERNAL_SIGNATURE_HEADER,
+            req.headers()[INTERNAL_SIGNATURE_HEADER].clone(),
+        )
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(status(&router, req).await, StatusCode::OK);
+    assert_eq!(status(&router, replayed).await, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_internal_signature_rejected() {
+    let router = internal_router(signer());
+    let other_signer = InternalSigner::new(Secret::from("other"), TimeDelta::seconds(300));
+
+    // signed with other secret
+    let req = signed(&other_signer, Method::POST, "/admin/cache/warm");
+    assert_eq!(status(&router, req).await, StatusCode::UNAUTHORIZED);
+
+    // signed for other path or method than the internal endpoint
+    let req = signed(&signer(), Method::POST, "/admin/sync");
+    assert_eq!(status(&router, req).await, StatusCode::UNAUTHORIZED);
+    let req = signed(&signer(), Method::GET, "/admin/cache/warm");
+    assert_eq!(status(&router, req).await, StatusCode::UNAUTHORIZED);
+
+    // admin routes other than internal ones ignore signatures
+    let router = group::<ReaderRole>(api_keys());
+    let req = signed(&signer(), Method::GET, "/admin/group");
+    assert_eq!(status(&router, req).await, StatusCode::BAD_REQUEST);
+}
+
+/// tenant scoped route group responding resolved tenant, layered like admin_routes' local routes.
+fn tenant_router(api_keys: Arc<HashMap<String, ApiKey>>) -> Router {
+    let routes = Router::new()
+        .route(
+            "/goals",
+            any(|tenant: Tenant| async move { tenant.0.unwrap().to_string() }),
+        )
+        .route_layer(from_extractor::<Authorized<ReaderRole>>())
+        .layer(from_fn(tenant_middleware))
+        .layer(from_fn(move |req: Request, next: Next| {
+            let api_keys = api_keys.clone();
+            async move { admin_auth(ADMIN_PASSWORD, Some(&api_keys), req, next).await }
+        }));
+
+    Router::new().nest("/admin", routes)
+}
+
+#[tokio::test]
+async fn test_tenant_bound_to_credentials() {
+    let router = tenant_router(api_keys());
+    let request = |credential: (&str, &str), tenant: Option<&str>| {
+        let mut req = Request::get("/admin/goals").header(credential.0, credential.1);
+        if let Some(tenant) = tenant {
+            req = req.header("x-tenant-id", tenant);
+        }
+        req.body(Body::empty()).unwrap()
+    };
+    let tenant_of = |req: Request<Body>| {
+        let router = router.clone();
+        async move {
+            let resp = router.oneshot(req).await.unwrap();
+            let status = resp.status();
+            let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            (status, String::from_utf8(body.to_vec()).unwrap())
+        }
+    };
+
+    // key bound to tenant acts for it, with or without matching header
+    let key = ("x-api-key", "family-key");
+    assert_eq!(
+        tenant_of(request(key, None)).await,
+        (StatusCode::OK, "family".to_string())
+    );
+    assert_eq!(
+        tenant_of(request(key, Some("family"))).await,
+        (StatusCode::OK, "family".to_string())
+    );
+    assert_eq!(
+        status(&router, request(key, Some("other"))).await,
+        StatusCode::FORBIDDEN
+    );
+
+    // key without tenant is rejected
+    let key = ("x-api-key", "writer-key");
+    assert_eq!(
+        status(&router, request(key, Some("family"))).await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        status(&router, request(key, None)).await,
+        StatusCode::FORBIDDEN
+    );
+
+    // admin password selects tenant with header, which is required
+    let password = ("x-admin-password", ADMIN_PASSWORD);
+    assert_eq!(
+        tenant_of(request(password, Some("other"))).await,
+        (StatusCode::OK, "other".to_string())
+    );
+    assert_eq!(
+        status(&router, request(password, None)).await,
+        StatusCode::BAD_REQUEST
+    );
+}
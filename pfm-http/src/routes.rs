@@ -1,20 +1,125 @@
-use axum::{routing::get, Router};
-use pfm_core::forex::interface::{ForexHistoricalRates, ForexStorage};
+use axum::{
+    Router,
+    extract::DefaultBodyLimit,
+    middleware::from_extractor,
+    routing::{delete, get, post, put},
+};
+use pfm_core::api::{providers::Provider, storage::ForexStorageImpl};
+use pfm_core::forex::interface::{
+    ForexHistoricalRates, ForexProviderCapabilities, ForexRates, ForexStorage, ForexStorageJobLock,
+    ForexStorageJobRuns, ForexStorageJournal, ForexStoragePagination, ForexStorageProvenance,
+    ForexStorageProviderHealth, ForexStorageRebased, ForexStorageSlo, ForexStorageSnapshots,
+    ForexStorageSparklines, ForexStorageSparse, ForexStorageStats, ForexStorageValidation,
+    ForexStorageVersions,
+};
+use pfm_core::global::TenantScoped;
+use pfm_core::goals::interface::GoalsStorage;
+use pfm_core::ledger::interface::{LedgerStorage, LedgerStorageDoubleEntry};
+use pfm_core::sync::interface::{SyncStorage, SyncStorageRevisions};
+use pfm_core::users::interface::UsersStorage;
+use pfm_core::watchlist::interface::WatchlistStorage;
+use tower_http::{compression::CompressionLayer, limit::RequestBodyLimitLayer};
 // use tower::ServiceBuilder;
 
+use crate::dto::{AdminRole, Authorized, ReaderRole, WriterRole};
 use crate::global::{self, AppContext};
 use crate::middlewares;
 
 mod admin_routes;
+#[cfg(feature = "dashboard")]
+mod dashboard_routes;
 mod forex_routes;
 mod root_routes;
 
+/// all endpoints served in a single listener.
 pub fn register_routes() -> Router {
-    Router::new()
-        .nest("/", root_routes())
-        .nest("/admin", admin_routes())
-        .nest("/forex", forex_routes())
+    with_common_layers(with_dashboard(
+        Router::new()
+            .nest("/", root_routes())
+            .nest("/admin", admin_routes())
+            .nest("/forex", forex_routes()),
+    ))
+}
+
+/// read-only endpoints, safe to be exposed publicly.
+pub fn register_public_routes() -> Router {
+    with_common_layers(with_dashboard(
+        Router::new()
+            .nest("/", root_routes())
+            .nest("/forex", forex_routes()),
+    ))
+}
+
+/// mutation/admin endpoints, served in separate listener.
+pub fn register_admin_routes() -> Router {
+    with_common_layers(
+        Router::new()
+            .nest("/", root_routes())
+            .nest("/admin", admin_routes()),
+    )
+}
+
+/// dashboard page is served next to /forex endpoints it reads from.
+/// portfolio summary read by the page is as protected as the page, by session when dashboard auth is enabled.
+fn with_dashboard<FS, FH>(router: Router<AppContext<FS, FH>>) -> Router<AppContext<FS, FH>>
+where
+    FS: ForexStorage
+        + UsersStorage
+        + LedgerStorage
+        + LedgerStorageDoubleEntry
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    FH: ForexHistoricalRates + Clone + Send + Sync + 'static,
+{
+    #[cfg(feature = "dashboard")]
+    let router = {
+        let dashboard = Router::new()
+            .route(
+                "/dashboard",
+                get(dashboard_routes::dashboard::dashboard_handler),
+            )
+            .route(
+                "/dashboard/portfolio",
+                get(dashboard_routes::portfolio::get_portfolio_handler),
+            );
+        let dashboard = if global::config().dashboard_auth {
+            dashboard.layer(axum::middleware::from_fn(middlewares::session_middleware))
+        } else {
+            dashboard
+        };
+
+        router
+            .merge(dashboard)
+            .route(
+                "/login",
+                get(dashboard_routes::auth::login_page_handler)
+                    .post(dashboard_routes::auth::login_handler),
+            )
+            .route("/logout", post(dashboard_routes::auth::logout_handler))
+    };
+
+    router
+}
+
+fn with_common_layers(router: Router<AppContext<ForexStorageImpl, Provider>>) -> Router {
+    let router = router
         .with_state(global::context())
+        .layer(DefaultBodyLimit::disable())
+        .layer(RequestBodyLimitLayer::new(
+            global::config().body_limit_bytes,
+        ))
+        .layer(axum::middleware::from_fn(
+            middlewares::error_format_middleware,
+        ));
+    let router = if global::config().compression {
+        router.layer(CompressionLayer::new())
+    } else {
+        router
+    };
+
+    router
         .layer(axum::middleware::from_fn(
             middlewares::processing_time_middleware,
         ))
@@ -28,40 +133,223 @@ where
     FS: ForexStorage + Clone + Send + Sync + 'static,
     FH: ForexHistoricalRates + Clone + Send + Sync + 'static,
 {
-    Router::new().route("/ping", get(root_routes::ping::ping_handler))
+    Router::new()
+        .route("/ping", get(root_routes::ping::ping_handler))
+        .route("/version", get(root_routes::version::get_version_handler))
+        .route("/schemas", get(root_routes::schemas::get_schemas_handler))
+        .route(
+            "/schemas/:name",
+            get(root_routes::schemas::get_schema_handler),
+        )
 }
 
 pub fn admin_routes<FS, FH>() -> Router<AppContext<FS, FH>>
 where
-    FS: ForexStorage + Clone + Send + Sync + 'static,
-    FH: ForexHistoricalRates + Clone + Send + Sync + 'static,
+    FS: ForexStorage
+        + ForexStorageJobLock
+        + ForexStorageJobRuns
+        + ForexStorageJournal
+        + ForexStorageVersions
+        + ForexStorageProviderHealth
+        + ForexStorageStats
+        + ForexStorageValidation
+        + GoalsStorage
+        + WatchlistStorage
+        + SyncStorage
+        + SyncStorageRevisions
+        + TenantScoped
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    FH: ForexHistoricalRates
+        + ForexRates
+        + ForexProviderCapabilities
+        + Clone
+        + Send
+        + Sync
+        + 'static,
 {
+    // inspecting job runs, journal, versions, provider health and disk usage, managing and syncing goals and watchlists don't call 3rd party APIs, hence not rate limited
+    let read_routes = Router::new()
+        .route("/jobs", get(admin_routes::jobs::get_job_runs_handler))
+        .route("/journal", get(admin_routes::journal::get_journal_handler))
+        .route(
+            "/versions",
+            get(admin_routes::versions::get_versions_handler),
+        )
+        .route(
+            "/versions/:version",
+            get(admin_routes::versions::get_version_handler),
+        )
+        .route(
+            "/providers/health",
+            get(admin_routes::providers::get_provider_health_handler),
+        )
+        .route("/status", get(admin_routes::status::get_status_handler))
+        .route("/goals", get(admin_routes::goals::get_goals_handler))
+        .route(
+            "/goals/:id",
+            get(admin_routes::goals::get_goal_progress_handler),
+        )
+        .route(
+            "/watchlist",
+            get(admin_routes::watchlist::get_watchlist_handler),
+        )
+        .route(
+            "/watchlist/quotes",
+            get(admin_routes::watchlist::get_watchlist_quotes_handler),
+        )
+        .route("/sync", get(admin_routes::sync::get_sync_changes_handler))
+        .route_layer(from_extractor::<Authorized<ReaderRole>>());
+
+    let write_routes = Router::new()
+        .route("/goals", post(admin_routes::goals::create_goal_handler))
+        .route(
+            "/goals/:id",
+            put(admin_routes::goals::update_goal_handler)
+                .delete(admin_routes::goals::delete_goal_handler),
+        )
+        .route(
+            "/goals/:id/contributions",
+            post(admin_routes::goals::add_contribution_handler),
+        )
+        .route(
+            "/goals/:id/contributions/:contribution_id",
+            delete(admin_routes::goals::remove_contribution_handler),
+        )
+        .route(
+            "/watchlist",
+            delete(admin_routes::watchlist::delete_watchlist_handler),
+        )
+        .route(
+            "/watchlist/pairs",
+            post(admin_routes::watchlist::add_pair_handler)
+                .delete(admin_routes::watchlist::remove_pair_handler),
+        )
+        .route(
+            "/watchlist/order",
+            put(admin_routes::watchlist::reorder_pairs_handler),
+        )
+        .route("/sync", post(admin_routes::sync::push_sync_changes_handler))
+        .route_layer(from_extractor::<Authorized<WriterRole>>());
+
+    let local_routes = read_routes.merge(write_routes);
+    let local_routes = if global::config().multi_tenancy {
+        local_routes.layer(axum::middleware::from_fn(middlewares::tenant_middleware))
+    } else {
+        local_routes
+    }
+    .layer(axum::middleware::from_fn(
+        middlewares::admin_auth_middleware,
+    ));
+
+    // triggered by pfm-cron with signed internal requests, not calling 3rd party APIs
+    let internal_routes = Router::new()
+        .route("/cache/warm", post(admin_routes::cache::warm_cache_handler))
+        .route_layer(from_extractor::<Authorized<AdminRole>>())
+        .layer(axum::middleware::from_fn(
+            middlewares::internal_auth_middleware,
+        ));
+
+    // polling 3rd party APIs and rerunning jobs
     Router::new()
         .route(
             "/forex/fetch_historical_rates",
             get(admin_routes::historical_rates::fetch_historical_rates_handler),
         )
+        .route("/jobs/rerun", post(admin_routes::jobs::rerun_job_handler))
+        .route_layer(from_extractor::<Authorized<AdminRole>>())
         .layer(axum::middleware::from_fn(
-            middlewares::admin_password_middleware,
+            middlewares::admin_auth_middleware,
         ))
         .layer(axum::middleware::from_fn(
             middlewares::forex_admin_rate_limit_middleware,
         ))
+        .merge(local_routes)
+        .merge(internal_routes)
 }
 
 fn forex_routes<FS, FH>() -> Router<AppContext<FS, FH>>
 where
-    FS: ForexStorage + Clone + Send + Sync + 'static,
+    FS: ForexStorage
+        + ForexStoragePagination
+        + ForexStorageProvenance
+        + ForexStorageRebased
+        + ForexStorageSlo
+        + ForexStorageSnapshots
+        + ForexStorageSparklines
+        + ForexStorageSparse
+        + Clone
+        + Send
+        + Sync
+        + 'static,
     FH: ForexHistoricalRates + Clone + Send + Sync + 'static,
 {
     let routes = Router::new()
         .route("/convert", get(forex_routes::convert::convert_handler))
+        .route(
+            "/convert/dates",
+            get(forex_routes::convert::convert_dates_handler),
+        )
+        .route(
+            "/evaluate",
+            get(forex_routes::expression::get_expression_handler),
+        )
         .route("/rates", get(forex_routes::rates::get_rates_handler))
+        .route(
+            "/latest/delta",
+            get(forex_routes::delta::get_latest_delta_handler),
+        )
         .route(
             "/timeseries",
             get(forex_routes::timeseries::get_timeseries_handler),
+        )
+        .route(
+            "/latest_list",
+            get(forex_routes::list::get_latest_list_handler),
+        )
+        .route(
+            "/historical_list",
+            get(forex_routes::list::get_historical_list_handler),
+        )
+        .route(
+            "/extremes",
+            get(forex_routes::extremes::get_extremes_handler),
+        )
+        .route(
+            "/aggregates",
+            get(forex_routes::aggregates::get_aggregates_handler),
+        )
+        .route(
+            "/travel_budget",
+            get(forex_routes::travel_budget::get_travel_budget_handler),
+        )
+        .route("/sources", get(forex_routes::sources::get_sources_handler))
+        .route(
+            "/scenario",
+            post(forex_routes::scenario::shock_scenario_handler),
+        )
+        .route(
+            "/sparklines",
+            get(forex_routes::sparklines::get_sparklines_handler),
+        )
+        .route(
+            "/completeness",
+            get(forex_routes::completeness::get_completeness_handler),
+        )
+        .route(
+            "/completeness/metrics",
+            get(forex_routes::completeness::get_completeness_metrics_handler),
+        )
+        .route("/slo", get(forex_routes::slo::get_slo_handler))
+        .route(
+            "/slo/metrics",
+            get(forex_routes::slo::get_slo_metrics_handler),
         );
 
+    let routes = routes.layer(axum::middleware::from_fn(middlewares::bootstrap_middleware));
+
     if global::config().enable_api_key {
         return routes.layer(axum::middleware::from_fn(middlewares::api_key_middleware));
     }
@@ -0,0 +1,40 @@
+use axum::{extract::State, response::IntoResponse};
+use pfm_core::{
+    api,
+    forex::interface::{ForexHistoricalRates, ForexStorage},
+    global::SystemClock,
+};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::{dto::*, global};
+
+#[derive(Debug, Serialize)]
+pub(crate) struct WarmCacheDTO {
+    pub message: String,
+
+    /// rates read into cache, 0 if cache is disabled.
+    pub warmed: usize,
+}
+
+// POST /admin/cache/warm
+// load latest and recent historical rates into cache, e.g. triggered by pfm-cron after polling with signed internal request
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn warm_cache_handler(
+    State(ctx): State<global::AppContext<impl ForexStorage, impl ForexHistoricalRates>>,
+) -> Result<impl IntoResponse, AppError> {
+    let cfg = global::config();
+    let warmed = if cfg.cache_capacity == 0 {
+        0
+    } else {
+        api::forex::warm_cache(&ctx.forex_storage, &SystemClock, cfg.cache_warm_days).await
+    };
+
+    Ok(HttpResponse::ok(
+        WarmCacheDTO {
+            message: "Cache warmed".to_string(),
+            warmed,
+        },
+        None,
+    ))
+}
@@ -0,0 +1,260 @@
+use std::str::FromStr;
+
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use pfm_core::{
+    api,
+    forex::{
+        Money,
+        interface::{ForexHistoricalRates, ForexStorage},
+    },
+    global::TenantScoped,
+    goals::{entity::Goal, interface::GoalsStorage},
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{dto::*, global::AppContext};
+
+#[derive(Debug, Serialize)]
+pub(crate) struct GoalsDTO {
+    pub message: String,
+    pub goals: Vec<Goal>,
+}
+
+// GET /admin/goals
+// all savings goals ordered by deadline
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_goals_handler(
+    State(ctx): State<
+        AppContext<
+            impl ForexStorage + GoalsStorage + TenantScoped + Clone,
+            impl ForexHistoricalRates,
+        >,
+    >,
+    tenant: Tenant,
+) -> Result<impl IntoResponse, AppError> {
+    let storage = tenant.scope(&ctx.forex_storage);
+    let goals = api::goals::get_goals(&storage).await?;
+
+    Ok(HttpResponse::ok(
+        GoalsDTO {
+            message: "Goals".to_string(),
+            goals,
+        },
+        None,
+    ))
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct CreateGoalQuery {
+    #[serde(rename = "name")]
+    pub name: String,
+
+    /// money format ISO 4217 <CURRENCY_CODE> <AMOUNT>
+    #[serde(rename = "target")]
+    pub target: String,
+
+    #[serde(rename = "deadline", deserialize_with = "deserialize_date")]
+    pub deadline: DateTime<Utc>,
+}
+
+impl Validate for CreateGoalQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for CreateGoalQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "`name`, `target` and `deadline` are required. `target` must be in form: <CODE> <AMOUNT>, e.g. IDR 100,000,000. `deadline` must be in form of YYYY-MM-DD."
+    }
+}
+
+// POST /admin/goals
+// create savings goal
+// query: `name`, `target`(e.g. IDR 100,000,000), `deadline`(YYYY-MM-DD)
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn create_goal_handler(
+    State(ctx): State<
+        AppContext<
+            impl ForexStorage + GoalsStorage + TenantScoped + Clone,
+            impl ForexHistoricalRates,
+        >,
+    >,
+    tenant: Tenant,
+    CustomQuery(params): CustomQuery<CreateGoalQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let storage = tenant.scope(&ctx.forex_storage);
+    let target = Money::from_str(&params.target)?;
+    let goal = api::goals::create_goal(&storage, &params.name, target, params.deadline).await?;
+
+    Ok(HttpResponse::ok(goal, None))
+}
+
+// GET /admin/goals/{id}
+// goal with its progress valued at latest rates
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_goal_progress_handler(
+    State(ctx): State<
+        AppContext<
+            impl ForexStorage + GoalsStorage + TenantScoped + Clone,
+            impl ForexHistoricalRates,
+        >,
+    >,
+    tenant: Tenant,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let storage = tenant.scope(&ctx.forex_storage);
+    let progress = api::goals::get_goal_progress(&storage, &storage, id).await?;
+
+    Ok(HttpResponse::ok(progress, None))
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct UpdateGoalQuery {
+    #[serde(rename = "name", default)]
+    pub name: Option<String>,
+
+    #[serde(rename = "target", default)]
+    pub target: Option<String>,
+
+    #[serde(
+        rename = "deadline",
+        default,
+        deserialize_with = "deserialize_optional_date"
+    )]
+    pub deadline: Option<DateTime<Utc>>,
+}
+
+impl Validate for UpdateGoalQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.name.is_none() && self.target.is_none() && self.deadline.is_none() {
+            return Err(AppError::BadRequest(
+                "at least one of name, target or deadline must be provided".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for UpdateGoalQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "`name`, `target` and `deadline` are optional. `target` must be in form: <CODE> <AMOUNT>, e.g. IDR 100,000,000. `deadline` must be in form of YYYY-MM-DD."
+    }
+}
+
+// PUT /admin/goals/{id}
+// update name, target or deadline of goal
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn update_goal_handler(
+    State(ctx): State<
+        AppContext<
+            impl ForexStorage + GoalsStorage + TenantScoped + Clone,
+            impl ForexHistoricalRates,
+        >,
+    >,
+    tenant: Tenant,
+    Path(id): Path<Uuid>,
+    CustomQuery(params): CustomQuery<UpdateGoalQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let storage = tenant.scope(&ctx.forex_storage);
+    let target = params.target.as_deref().map(Money::from_str).transpose()?;
+    let goal = api::goals::update_goal(&storage, id, params.name, target, params.deadline).await?;
+
+    Ok(HttpResponse::ok(goal, None))
+}
+
+// DELETE /admin/goals/{id}
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn delete_goal_handler(
+    State(ctx): State<
+        AppContext<
+            impl ForexStorage + GoalsStorage + TenantScoped + Clone,
+            impl ForexHistoricalRates,
+        >,
+    >,
+    tenant: Tenant,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let storage = tenant.scope(&ctx.forex_storage);
+    api::goals::delete_goal(&storage, id).await?;
+
+    Ok(HttpResponse::ok(format!("goal {} deleted", id), None))
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct ContributionQuery {
+    /// money format ISO 4217 <CURRENCY_CODE> <AMOUNT>, may differ from goal target currency
+    #[serde(rename = "amount")]
+    pub amount: String,
+
+    /// date of contribution, default to now
+    #[serde(
+        rename = "date",
+        default,
+        deserialize_with = "deserialize_optional_date"
+    )]
+    pub date: Option<DateTime<Utc>>,
+
+    #[serde(rename = "note", default)]
+    pub note: Option<String>,
+}
+
+impl Validate for ContributionQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for ContributionQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "`amount` is required in form: <CODE> <AMOUNT>, e.g. USD 1,000. `date` is optional in form of YYYY-MM-DD. `note` is optional."
+    }
+}
+
+// POST /admin/goals/{id}/contributions
+// add contribution to goal
+// query: `amount`(e.g. USD 1,000), `date`(YYYY-MM-DD), `note`
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn add_contribution_handler(
+    State(ctx): State<
+        AppContext<
+            impl ForexStorage + GoalsStorage + TenantScoped + Clone,
+            impl ForexHistoricalRates,
+        >,
+    >,
+    tenant: Tenant,
+    Path(id): Path<Uuid>,
+    CustomQuery(params): CustomQuery<ContributionQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let storage = tenant.scope(&ctx.forex_storage);
+    let amount = Money::from_str(&params.amount)?;
+    let date = params.date.unwrap_or(Utc::now());
+    let goal = api::goals::add_contribution(&storage, id, amount, date, params.note).await?;
+
+    Ok(HttpResponse::ok(goal, None))
+}
+
+// DELETE /admin/goals/{id}/contributions/{contribution_id}
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn remove_contribution_handler(
+    State(ctx): State<
+        AppContext<
+            impl ForexStorage + GoalsStorage + TenantScoped + Clone,
+            impl ForexHistoricalRates,
+        >,
+    >,
+    tenant: Tenant,
+    Path((id, contribution_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, AppError> {
+    let storage = tenant.scope(&ctx.forex_storage);
+    let goal = api::goals::remove_contribution(&storage, id, contribution_id).await?;
+
+    Ok(HttpResponse::ok(goal, None))
+}
@@ -3,9 +3,9 @@ use axum::{extract::State, response::IntoResponse};
 use chrono::{DateTime, Utc};
 use pfm_core::{
     forex::{
-        entity::{Rates, RatesData, RatesResponse},
-        interface::{ForexHistoricalRates, ForexStorage},
         Currency,
+        entity::{Rates, RatesData, RatesResponse},
+        interface::{ForexHistoricalRates, ForexStorage, ForexStorageValidation},
     },
     global,
 };
@@ -16,6 +16,10 @@ use tracing::instrument;
 pub struct HistoricalRatesQuery {
     #[serde(rename = "date", default, deserialize_with = "deserialize_date")]
     pub date: DateTime<Utc>,
+
+    /// store the rates even if they fail validation
+    #[serde(rename = "force", default)]
+    pub force: bool,
 }
 
 impl Validate for HistoricalRatesQuery {
@@ -26,7 +30,7 @@ impl Validate for HistoricalRatesQuery {
 
 impl BadRequestErrMsg for HistoricalRatesQuery {
     fn bad_request_err_msg() -> &'static str {
-        "Date required for historical rates, format is YYYY-MM-DD"
+        "Date required for historical rates, format is YYYY-MM-DD. `force` is optional boolean"
     }
 }
 
@@ -50,9 +54,12 @@ impl From<RatesResponse<Rates>> for HistoricalRatesDTO {
 }
 
 /// fetch and store historical rates data from 3rd party api
+/// rates failing validation are rejected unless `force=true`
 #[instrument(skip(ctx), ret)]
 pub(crate) async fn fetch_historical_rates_handler(
-    State(ctx): State<AppContext<impl ForexStorage, impl ForexHistoricalRates>>,
+    State(ctx): State<
+        AppContext<impl ForexStorage + ForexStorageValidation, impl ForexHistoricalRates>,
+    >,
     CustomQuery(params): CustomQuery<HistoricalRatesQuery>,
 ) -> Result<impl IntoResponse, AppError> {
     let ret = match ctx
@@ -60,13 +67,20 @@ pub(crate) async fn fetch_historical_rates_handler(
         .historical_rates(params.date, global::constants::BASE_CURRENCY)
         .await
     {
+        Ok(val) if params.force => {
+            ctx.forex_storage
+                .forced()
+                .insert_historical(val.data.date, &val)
+                .await?;
+            Ok(HttpResponse::ok(HistoricalRatesDTO::from(val), None))
+        }
         Ok(val) => {
             ctx.forex_storage
                 .insert_historical(val.data.date, &val)
                 .await?;
             Ok(HttpResponse::ok(HistoricalRatesDTO::from(val), None))
         }
-        Err(error) => Err(AppError::InternalServerError(error.to_string())),
+        Err(error) => Err(error.into()),
     };
 
     ret
@@ -0,0 +1,218 @@
+use axum::{extract::State, response::IntoResponse};
+use chrono::{DateTime, TimeDelta, Utc};
+use pfm_core::{
+    api,
+    forex::{
+        entity::{Job, JobRun, JobTrigger, PollMode},
+        interface::{
+            ForexHistoricalRates, ForexProviderCapabilities, ForexRates, ForexStorage,
+            ForexStorageJobLock, ForexStorageJobRuns,
+        },
+    },
+    global,
+};
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use tracing::instrument;
+
+use crate::{dto::*, global::AppContext};
+
+const DEFAULT_LIMIT: u32 = 20;
+const MAX_LIMIT: u32 = 200;
+
+/// holder of job locks taken by admin reruns, sharing locks with pfm-cron instances.
+const RERUN_LOCK_HOLDER: &str = "pfm-http-rerun";
+
+/// seconds a rerun holds lock of its job, scheduled runs are skipped meanwhile.
+const RERUN_LOCK_TTL_SECS: i64 = 300;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct JobRunsQuery {
+    /// optional job name, e.g. poll_latest_rates
+    #[serde(rename = "job", default)]
+    pub job: Option<Job>,
+
+    #[serde(rename = "limit", default = "default_limit")]
+    pub limit: u32,
+}
+
+fn default_limit() -> u32 {
+    DEFAULT_LIMIT
+}
+
+impl Validate for JobRunsQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.limit == 0 || self.limit > MAX_LIMIT {
+            return Err(AppError::BadRequest(format!(
+                "limit must be between 1 and {}",
+                MAX_LIMIT
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for JobRunsQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "`job` is optional, either poll_latest_rates or poll_historical_rates. `limit` is optional positive number."
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct JobRunsDTO {
+    pub message: String,
+    pub runs: Vec<JobRun>,
+}
+
+// GET /admin/jobs
+// latest runs of background jobs
+// query: `job`(poll_latest_rates|poll_historical_rates), `limit`
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_job_runs_handler(
+    State(ctx): State<
+        AppContext<impl ForexStorage + ForexStorageJobRuns, impl ForexHistoricalRates>,
+    >,
+    CustomQuery(params): CustomQuery<JobRunsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let runs = api::forex::get_job_runs(&ctx.forex_storage, params.job, params.limit).await?;
+
+    Ok(HttpResponse::ok(
+        JobRunsDTO {
+            message: "Job runs".to_string(),
+            runs,
+        },
+        None,
+    ))
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct RerunJobQuery {
+    #[serde(rename = "job")]
+    pub job: Job,
+
+    /// date of historical rates to poll, default to yesterday like the cron job
+    #[serde(
+        rename = "date",
+        default,
+        deserialize_with = "deserialize_optional_date"
+    )]
+    pub date: Option<DateTime<Utc>>,
+}
+
+impl Validate for RerunJobQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.job == Job::PollLatestRates && self.date.is_some() {
+            return Err(AppError::BadRequest(
+                "date is only for poll_historical_rates".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for RerunJobQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "`job` is required, either poll_latest_rates or poll_historical_rates. `date` is optional for poll_historical_rates in form of YYYY-MM-DD."
+    }
+}
+
+// POST /admin/jobs/rerun
+// run a job now, recorded as admin triggered run
+// query: `job`(poll_latest_rates|poll_historical_rates), `date`(YYYY-MM-DD) for poll_historical_rates
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn rerun_job_handler(
+    State(ctx): State<
+        AppContext<
+            impl ForexStorage + ForexStorageJobLock + ForexStorageJobRuns,
+            impl ForexHistoricalRates + ForexRates + ForexProviderCapabilities,
+        >,
+    >,
+    CustomQuery(params): CustomQuery<RerunJobQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    if matches!(params.job, Job::PollLatestRates | Job::PollHistoricalRates)
+        && !api::forex::lock_job(
+            &ctx.forex_storage,
+            params.job,
+            RERUN_LOCK_HOLDER,
+            TimeDelta::seconds(RERUN_LOCK_TTL_SECS),
+        )
+        .await
+    {
+        return Err(AppError::ServiceUnavailable(format!(
+            "job {} is locked by other instance, try again later",
+            params.job.name()
+        )));
+    }
+
+    let cancel = CancellationToken::new();
+    let base = global::constants::BASE_CURRENCY;
+    match params.job {
+        Job::PollLatestRates => {
+            api::forex::run_job(
+                &ctx.forex_storage,
+                params.job,
+                JobTrigger::Admin,
+                api::forex::poll_rates(
+                    &ctx.forex_historical,
+                    &ctx.forex_storage,
+                    &global::SystemClock,
+                    base,
+                    &cancel,
+                ),
+            )
+            .await?;
+        }
+        Job::PollHistoricalRates => {
+            let date = params.date.unwrap_or(Utc::now() - TimeDelta::days(1));
+            api::forex::run_job(
+                &ctx.forex_storage,
+                params.job,
+                JobTrigger::Admin,
+                api::forex::poll_historical_rates(
+                    &ctx.forex_historical,
+                    &ctx.forex_storage,
+                    date,
+                    base,
+                    PollMode::Always,
+                    &cancel,
+                ),
+            )
+            .await?;
+        }
+        // sent by pfm-cron with its smtp sender
+        Job::SendDigest => {
+            return Err(AppError::BadRequest(
+                "send_digest cannot be rerun from admin api".to_string(),
+            ));
+        }
+        // captured by pfm-cron with its crypto prices provider
+        Job::CaptureCryptoPrices => {
+            return Err(AppError::BadRequest(
+                "capture_crypto_prices cannot be rerun from admin api".to_string(),
+            ));
+        }
+        Job::PollCryptoRates => {
+            return Err(AppError::BadRequest(
+                "poll_crypto_rates cannot be rerun from admin api".to_string(),
+            ));
+        }
+        Job::EvaluateSlo => {
+            return Err(AppError::BadRequest(
+                "evaluate_slo cannot be rerun from admin api".to_string(),
+            ));
+        }
+    }
+
+    // the run just recorded, reporting whether rates were polled without error
+    let runs = api::forex::get_job_runs(&ctx.forex_storage, Some(params.job), 1).await?;
+
+    Ok(HttpResponse::ok(
+        JobRunsDTO {
+            message: format!("Job {} rerun finished", params.job.name()),
+            runs,
+        },
+        None,
+    ))
+}
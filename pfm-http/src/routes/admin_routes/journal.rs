@@ -0,0 +1,64 @@
+use axum::{extract::State, response::IntoResponse};
+use chrono::{DateTime, Utc};
+use pfm_core::forex::{
+    entity::{RatesChange, RatesChangeReplay},
+    interface::{ForexHistoricalRates, ForexStorageJournal},
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{dto::*, global::AppContext};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct JournalQuery {
+    #[serde(rename = "date", deserialize_with = "deserialize_date")]
+    pub date: DateTime<Utc>,
+}
+
+impl Validate for JournalQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for JournalQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "`date` is required in form of YYYY-MM-DD."
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct JournalDTO {
+    pub message: String,
+
+    /// changes of latest and historical rates files of the date.
+    pub changes: Vec<RatesChange>,
+
+    /// historical rates changes of the date replayed against stored file.
+    pub replay: RatesChangeReplay,
+}
+
+// GET /admin/journal
+// changes made to stored rates of the date, for forensic audits
+// query: `date`(YYYY-MM-DD)
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_journal_handler(
+    State(ctx): State<AppContext<impl ForexStorageJournal, impl ForexHistoricalRates>>,
+    CustomQuery(params): CustomQuery<JournalQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let changes = ctx.forex_storage.get_changes(params.date).await?;
+    let replay = ctx.forex_storage.replay_changes(params.date).await?;
+
+    Ok(HttpResponse::ok(
+        JournalDTO {
+            message: if replay.is_consistent() {
+                "Rates changes".to_string()
+            } else {
+                "Rates changes, journal doesn't match stored rates".to_string()
+            },
+            changes,
+            replay,
+        },
+        None,
+    ))
+}
@@ -1 +1,12 @@
+pub(super) mod cache;
+pub(super) mod goals;
 pub(super) mod historical_rates;
+pub(super) mod jobs;
+pub(super) mod journal;
+pub(super) mod providers;
+pub(super) mod status;
+pub(super) mod sync;
+#[cfg(test)]
+mod sync_test;
+pub(super) mod versions;
+pub(super) mod watchlist;
@@ -0,0 +1,55 @@
+use axum::{extract::State, response::IntoResponse};
+use chrono::{DateTime, Utc};
+use pfm_core::api;
+use pfm_core::forex::interface::{ForexHistoricalRates, ForexStorageProviderHealth};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::{dto::*, global::AppContext};
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ProviderHealthDTO {
+    pub provider: String,
+    pub calls: usize,
+    pub success_rate: Decimal,
+    pub completeness: Decimal,
+    pub avg_latency_ms: Option<u64>,
+    pub score: Decimal,
+    pub last_call_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ProvidersHealthDTO {
+    pub message: String,
+    pub providers: Vec<ProviderHealthDTO>,
+}
+
+// GET /admin/providers/health
+// health of 3rd party providers over their latest calls, from the healthiest
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_provider_health_handler(
+    State(ctx): State<AppContext<impl ForexStorageProviderHealth, impl ForexHistoricalRates>>,
+) -> Result<impl IntoResponse, AppError> {
+    let providers = api::forex::get_provider_health(&ctx.forex_storage)
+        .await?
+        .into_iter()
+        .map(|health| ProviderHealthDTO {
+            calls: health.samples.len(),
+            success_rate: health.success_rate().round_dp(4),
+            completeness: health.completeness().round_dp(4),
+            avg_latency_ms: health.avg_latency_ms(),
+            score: health.score(),
+            last_call_at: health.samples.last().map(|sample| sample.at),
+            provider: health.provider,
+        })
+        .collect();
+
+    Ok(HttpResponse::ok(
+        ProvidersHealthDTO {
+            message: "Provider health".to_string(),
+            providers,
+        },
+        None,
+    ))
+}
@@ -0,0 +1,44 @@
+use axum::{extract::State, response::IntoResponse};
+use pfm_core::{
+    api,
+    forex::{
+        entity::StorageStats,
+        interface::{ForexHistoricalRates, ForexStorage, ForexStorageStats},
+    },
+    global,
+};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::{dto::*, global::AppContext};
+
+#[derive(Debug, Serialize)]
+pub(crate) struct StatusDTO {
+    pub storage: StorageStats,
+
+    /// max disk usage of storage in bytes, none if unlimited.
+    pub storage_max_bytes: Option<u64>,
+    pub storage_exceeded: bool,
+}
+
+// GET /admin/status
+// disk usage of storage per subtree against its max disk usage
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_status_handler(
+    State(ctx): State<AppContext<impl ForexStorage + ForexStorageStats, impl ForexHistoricalRates>>,
+) -> Result<impl IntoResponse, AppError> {
+    let storage = api::forex::get_storage_stats(&ctx.forex_storage).await?;
+    let storage_max_bytes = match global::config().storage_max_disk_mb {
+        0 => None,
+        max_mb => Some(max_mb.saturating_mul(1024 * 1024)),
+    };
+
+    Ok(HttpResponse::ok(
+        StatusDTO {
+            storage_exceeded: storage_max_bytes.is_some_and(|max| storage.quota_bytes() > max),
+            storage,
+            storage_max_bytes,
+        },
+        None,
+    ))
+}
@@ -0,0 +1,95 @@
+use axum::{
+    Json,
+    extract::{State, rejection::JsonRejection},
+    response::IntoResponse,
+};
+use pfm_core::{
+    api,
+    forex::interface::{ForexHistoricalRates, ForexStorage},
+    global::TenantScoped,
+    goals::interface::GoalsStorage,
+    sync::{
+        entity::RemotePush,
+        interface::{SyncStorage, SyncStorageRevisions},
+    },
+    watchlist::interface::WatchlistStorage,
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{dto::*, global::AppContext};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct SyncQuery {
+    /// revision last pulled, 0 to pull every record
+    #[serde(rename = "since", default)]
+    pub since: u64,
+}
+
+impl Validate for SyncQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for SyncQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "`since` is optional revision, default to 0."
+    }
+}
+
+// GET /admin/sync
+// goals, watchlists and tombstones of deleted ones changed after revision, with their revisions, read only
+// query: `since`
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_sync_changes_handler(
+    State(ctx): State<
+        AppContext<
+            impl ForexStorage
+            + GoalsStorage
+            + WatchlistStorage
+            + SyncStorage
+            + SyncStorageRevisions
+            + TenantScoped
+            + Clone,
+            impl ForexHistoricalRates,
+        >,
+    >,
+    tenant: Tenant,
+    CustomQuery(params): CustomQuery<SyncQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let storage = tenant.scope(&ctx.forex_storage);
+    let changes = api::sync::remote_changes(&storage, params.since).await?;
+
+    Ok(HttpResponse::ok(changes, None))
+}
+
+// POST /admin/sync
+// store changes of client, each based on revision of the record it last pulled
+// body: json array of `base_revision` and `data`
+#[instrument(skip(ctx, body))]
+pub(crate) async fn push_sync_changes_handler(
+    State(ctx): State<
+        AppContext<
+            impl ForexStorage
+            + GoalsStorage
+            + WatchlistStorage
+            + SyncStorage
+            + SyncStorageRevisions
+            + TenantScoped
+            + Clone,
+            impl ForexHistoricalRates,
+        >,
+    >,
+    tenant: Tenant,
+    body: Result<Json<Vec<RemotePush>>, JsonRejection>,
+) -> Result<impl IntoResponse, AppError> {
+    let Json(pushes) = body.map_err(|err| AppError::InvalidInput {
+        message: "body must be json array of pushed changes".to_string(),
+        details: err.body_text(),
+    })?;
+    let storage = tenant.scope(&ctx.forex_storage);
+    let report = api::sync::remote_push(&storage, tenant.0.as_ref(), pushes).await?;
+
+    Ok(HttpResponse::ok(report, None))
+}
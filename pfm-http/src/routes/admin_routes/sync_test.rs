@@ -0,0 +1,96 @@
+use axum::{
+    Router,
+    body::{self, Body},
+    http::{Request, StatusCode},
+    routing,
+};
+use chrono::{TimeDelta, Utc};
+use pfm_core::{
+    api::{self, providers::Provider, storage::ForexStorageImpl, sync::SERVER_REVISION_LOG},
+    forex::Money,
+    global::{TenantId, TenantScoped},
+    sync::interface::SyncStorageRevisions,
+};
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+
+use super::sync::{get_sync_changes_handler, push_sync_changes_handler};
+use crate::global::AppContext;
+use crate::routes_test::{get, init_test_storage};
+
+fn sync_router(storage: ForexStorageImpl) -> Router {
+    let ctx = AppContext {
+        forex_storage: storage,
+        forex_historical: Provider::new("none"),
+    };
+
+    Router::new()
+        .route(
+            "/admin/sync",
+            routing::get(get_sync_changes_handler).post(push_sync_changes_handler),
+        )
+        .with_state(ctx)
+}
+
+async fn post(router: &Router, uri: &str, body: Value) -> (StatusCode, Value) {
+    let resp = tower::ServiceExt::oneshot(
+        router.clone(),
+        Request::post(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+    let status = resp.status();
+    let body = body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_sync_handlers() {
+    init_test_storage();
+    let storage = ForexStorageImpl::new(pfm_core::global::storage_fs())
+        .for_tenant(&TenantId::parse("sync-handlers").unwrap());
+    let router = sync_router(storage.clone());
+    // changed through admin endpoints, not logged yet
+    let goal = api::goals::create_goal(
+        &storage,
+        "house",
+        Money::USD(Decimal::from(1000)),
+        Utc::now() + TimeDelta::days(365),
+    )
+    .await
+    .unwrap();
+
+    // pulling doesn't write revision log
+    for _ in 0..2 {
+        let (status, body) = get(&router, "/admin/sync?since=0").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["revision"], 0);
+        assert_eq!(body["data"]["records"].as_array().unwrap().len(), 1);
+        assert_eq!(body["data"]["records"][0]["revision"], 1);
+    }
+    let log = storage.get_revision_log(SERVER_REVISION_LOG).await.unwrap();
+    assert_eq!(log.revision, 0);
+
+    // pushing on top of pulled revision logs it
+    let mut changed = goal.clone();
+    changed.name = "bigger house".to_string();
+    changed.updated_at = Utc::now();
+    let push = json!([{"base_revision": 1, "data": {"type": "goal", "record": changed}}]);
+    let (status, body) = post(&router, "/admin/sync", push.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["accepted"].as_array().unwrap().len(), 1);
+    let revision = body["data"]["accepted"][0]["revision"].as_u64().unwrap();
+
+    // pushing on top of stale revision is rejected
+    let (status, body) = post(&router, "/admin/sync", push).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["rejected"].as_array().unwrap().len(), 1);
+
+    let (_, body) = get(&router, &format!("/admin/sync?since={}", revision)).await;
+    assert_eq!(body["data"]["revision"], revision);
+    assert!(body["data"]["records"].as_array().unwrap().is_empty());
+}
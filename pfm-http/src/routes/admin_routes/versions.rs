@@ -0,0 +1,92 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use pfm_core::forex::{
+    entity::{HistoricalVersion, Rates, RatesResponse},
+    interface::{ForexHistoricalRates, ForexStorageVersions},
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{dto::*, global::AppContext};
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct VersionsQuery {
+    #[serde(rename = "date", deserialize_with = "deserialize_date")]
+    pub date: DateTime<Utc>,
+}
+
+impl Validate for VersionsQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for VersionsQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "`date` is required in form of YYYY-MM-DD."
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct VersionsDTO {
+    pub message: String,
+
+    /// stored versions of historical rates of the date, ordered from the oldest.
+    pub versions: Vec<HistoricalVersion>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct VersionDTO {
+    pub message: String,
+    pub version: u32,
+    pub rates: RatesResponse<Rates>,
+}
+
+// GET /admin/versions
+// versions of historical rates of the date kept by immutable storage, for compliance audits
+// query: `date`(YYYY-MM-DD)
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_versions_handler(
+    State(ctx): State<AppContext<impl ForexStorageVersions, impl ForexHistoricalRates>>,
+    CustomQuery(params): CustomQuery<VersionsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let versions = ctx
+        .forex_storage
+        .get_historical_versions(params.date)
+        .await?;
+
+    Ok(HttpResponse::ok(
+        VersionsDTO {
+            message: "Historical rates versions".to_string(),
+            versions,
+        },
+        None,
+    ))
+}
+
+// GET /admin/versions/:version
+// historical rates of the date as written in the version
+// query: `date`(YYYY-MM-DD)
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_version_handler(
+    State(ctx): State<AppContext<impl ForexStorageVersions, impl ForexHistoricalRates>>,
+    Path(version): Path<u32>,
+    CustomQuery(params): CustomQuery<VersionsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let rates = ctx
+        .forex_storage
+        .get_historical_version(params.date, version)
+        .await?;
+
+    Ok(HttpResponse::ok(
+        VersionDTO {
+            message: "Historical rates version".to_string(),
+            version,
+            rates,
+        },
+        None,
+    ))
+}
@@ -0,0 +1,213 @@
+use axum::{extract::State, response::IntoResponse};
+use pfm_core::{
+    api,
+    forex::interface::{ForexHistoricalRates, ForexStorage},
+    global::TenantScoped,
+    watchlist::{entity::DEFAULT_PROFILE, interface::WatchlistStorage},
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{dto::*, global::AppContext};
+
+fn default_profile() -> String {
+    DEFAULT_PROFILE.to_string()
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct ProfileQuery {
+    #[serde(rename = "profile", default = "default_profile")]
+    pub profile: String,
+}
+
+impl Validate for ProfileQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for ProfileQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "`profile` is optional, default to `default`."
+    }
+}
+
+// GET /admin/watchlist
+// pairs watched by profile in their order
+// query: `profile`
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_watchlist_handler(
+    State(ctx): State<
+        AppContext<
+            impl ForexStorage + WatchlistStorage + TenantScoped + Clone,
+            impl ForexHistoricalRates,
+        >,
+    >,
+    tenant: Tenant,
+    CustomQuery(params): CustomQuery<ProfileQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let storage = tenant.scope(&ctx.forex_storage);
+    let watchlist = api::watchlist::get_watchlist(&storage, &params.profile).await?;
+
+    Ok(HttpResponse::ok(watchlist, None))
+}
+
+// DELETE /admin/watchlist
+// query: `profile`
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn delete_watchlist_handler(
+    State(ctx): State<
+        AppContext<
+            impl ForexStorage + WatchlistStorage + TenantScoped + Clone,
+            impl ForexHistoricalRates,
+        >,
+    >,
+    tenant: Tenant,
+    CustomQuery(params): CustomQuery<ProfileQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let storage = tenant.scope(&ctx.forex_storage);
+    api::watchlist::delete_watchlist(&storage, &params.profile).await?;
+
+    Ok(HttpResponse::ok(
+        format!("watchlist of {} deleted", params.profile),
+        None,
+    ))
+}
+
+// GET /admin/watchlist/quotes
+// latest rate with 24h and 7d changes of each watched pair
+// query: `profile`
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_watchlist_quotes_handler(
+    State(ctx): State<
+        AppContext<
+            impl ForexStorage + WatchlistStorage + TenantScoped + Clone,
+            impl ForexHistoricalRates,
+        >,
+    >,
+    tenant: Tenant,
+    format: ResponseFormat,
+    decimals: DecimalFormat,
+    CustomQuery(params): CustomQuery<ProfileQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let storage = tenant.scope(&ctx.forex_storage);
+    let quotes = api::watchlist::get_watchlist_quotes(&storage, &storage, &params.profile).await?;
+
+    HttpResponse::ok_as(format, decimals, quotes, None)
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct PairQuery {
+    #[serde(rename = "profile", default = "default_profile")]
+    pub profile: String,
+
+    #[serde(rename = "pair")]
+    pub pair: String,
+
+    #[serde(rename = "target", default)]
+    pub target: Option<Decimal>,
+}
+
+impl Validate for PairQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for PairQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "`pair` is required in form of <BASE>/<QUOTE>, e.g. USD/IDR. `target` is optional rate, e.g. 15000. `profile` is optional, default to `default`."
+    }
+}
+
+// POST /admin/watchlist/pairs
+// watch pair at end of watchlist, or update its target if already watched
+// query: `profile`, `pair`(e.g. USD/IDR), `target`(e.g. 15000)
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn add_pair_handler(
+    State(ctx): State<
+        AppContext<
+            impl ForexStorage + WatchlistStorage + TenantScoped + Clone,
+            impl ForexHistoricalRates,
+        >,
+    >,
+    tenant: Tenant,
+    CustomQuery(params): CustomQuery<PairQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let storage = tenant.scope(&ctx.forex_storage);
+    let (base, quote) = parse_pair(&params.pair)?;
+    let watchlist =
+        api::watchlist::add_pair(&storage, &params.profile, base, quote, params.target).await?;
+
+    Ok(HttpResponse::ok(watchlist, None))
+}
+
+// DELETE /admin/watchlist/pairs
+// query: `profile`, `pair`(e.g. USD/IDR)
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn remove_pair_handler(
+    State(ctx): State<
+        AppContext<
+            impl ForexStorage + WatchlistStorage + TenantScoped + Clone,
+            impl ForexHistoricalRates,
+        >,
+    >,
+    tenant: Tenant,
+    CustomQuery(params): CustomQuery<PairQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let storage = tenant.scope(&ctx.forex_storage);
+    let (base, quote) = parse_pair(&params.pair)?;
+    let watchlist = api::watchlist::remove_pair(&storage, &params.profile, base, quote).await?;
+
+    Ok(HttpResponse::ok(watchlist, None))
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct OrderQuery {
+    #[serde(rename = "profile", default = "default_profile")]
+    pub profile: String,
+
+    /// comma separated pairs, e.g. XAU/IDR,USD/IDR
+    #[serde(rename = "pairs")]
+    pub pairs: String,
+}
+
+impl Validate for OrderQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for OrderQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "`pairs` is required, all watched pairs comma separated in new order, e.g. XAU/IDR,USD/IDR. `profile` is optional, default to `default`."
+    }
+}
+
+// PUT /admin/watchlist/order
+// reorder watched pairs
+// query: `profile`, `pairs`(e.g. XAU/IDR,USD/IDR)
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn reorder_pairs_handler(
+    State(ctx): State<
+        AppContext<
+            impl ForexStorage + WatchlistStorage + TenantScoped + Clone,
+            impl ForexHistoricalRates,
+        >,
+    >,
+    tenant: Tenant,
+    CustomQuery(params): CustomQuery<OrderQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let storage = tenant.scope(&ctx.forex_storage);
+    let order = params
+        .pairs
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(parse_pair)
+        .collect::<Result<Vec<_>, _>>()?;
+    let watchlist = api::watchlist::reorder_pairs(&storage, &params.profile, &order).await?;
+
+    Ok(HttpResponse::ok(watchlist, None))
+}
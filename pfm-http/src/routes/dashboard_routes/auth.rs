@@ -0,0 +1,65 @@
+use axum::{
+    Form,
+    extract::State,
+    http::{HeaderMap, header},
+    response::{Html, IntoResponse, Redirect},
+};
+use pfm_core::{
+    api,
+    forex::interface::{ForexHistoricalRates, ForexStorage},
+    users::interface::UsersStorage,
+};
+use serde::Deserialize;
+use tracing::instrument;
+
+use crate::{dto::*, global::AppContext, session};
+
+const LOGIN_HTML: &str = include_str!("../../../static/login.html");
+
+// GET /login
+pub(crate) async fn login_page_handler() -> impl IntoResponse {
+    Html(LOGIN_HTML)
+}
+
+#[derive(Deserialize)]
+pub(crate) struct LoginForm {
+    pub username: String,
+    pub password: String,
+}
+
+// POST /login
+// form: `username`, `password`
+// start session of local user, redirected to dashboard, or back to login page if credentials are invalid.
+#[instrument(skip(ctx, form), fields(username = %form.username))]
+pub(crate) async fn login_handler(
+    State(ctx): State<AppContext<impl ForexStorage + UsersStorage, impl ForexHistoricalRates>>,
+    Form(form): Form<LoginForm>,
+) -> Result<impl IntoResponse, AppError> {
+    if api::users::authenticate(&ctx.forex_storage, &form.username, &form.password)
+        .await
+        .is_err()
+    {
+        tracing::warn!("failed signing in");
+        return Ok(Redirect::to("/login?failed=true").into_response());
+    }
+
+    let token = session::create_session(&form.username).await?;
+
+    Ok((
+        [(header::SET_COOKIE, session::session_cookie(&token))],
+        Redirect::to("/dashboard"),
+    )
+        .into_response())
+}
+
+// POST /logout
+pub(crate) async fn logout_handler(headers: HeaderMap) -> impl IntoResponse {
+    if let Some(token) = session::session_token(&headers) {
+        session::remove_session(&token).await;
+    }
+
+    (
+        [(header::SET_COOKIE, session::expired_session_cookie())],
+        Redirect::to("/login"),
+    )
+}
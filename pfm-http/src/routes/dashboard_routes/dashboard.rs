@@ -0,0 +1,23 @@
+use std::sync::LazyLock;
+
+use axum::response::{Html, IntoResponse};
+use pfm_core::forex::Currency;
+use strum::IntoEnumIterator;
+
+const DASHBOARD_HTML: &str = include_str!("../../../static/dashboard.html");
+
+/// dashboard page with supported currencies filled in, rendered once.
+static DASHBOARD: LazyLock<String> = LazyLock::new(|| {
+    let currencies: Vec<&str> = Currency::iter().map(|c| c.code()).collect();
+    let currencies =
+        serde_json::to_string(&currencies).expect("dashboard: failed serializing currencies");
+
+    DASHBOARD_HTML.replace("__CURRENCIES__", &currencies)
+});
+
+// GET /dashboard
+// static page showing latest rates and sparklines of the last 30 days, data fetched from /forex/rates and /forex/sparklines,
+// and portfolio summary fetched from /dashboard/portfolio.
+pub(crate) async fn dashboard_handler() -> impl IntoResponse {
+    Html(DASHBOARD.as_str())
+}
@@ -0,0 +1,3 @@
+pub(super) mod auth;
+pub(super) mod dashboard;
+pub(super) mod portfolio;
@@ -0,0 +1,54 @@
+use axum::{extract::State, response::IntoResponse};
+use pfm_core::{
+    api,
+    forex::{
+        Currency,
+        interface::{ForexHistoricalRates, ForexStorage},
+    },
+    ledger::interface::{LedgerStorage, LedgerStorageDoubleEntry},
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::dto::*;
+use crate::global::AppContext;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct PortfolioQuery {
+    #[serde(rename = "currency")]
+    currency: Currency,
+}
+
+impl Validate for PortfolioQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for PortfolioQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "`currency` is required, e.g. IDR."
+    }
+}
+
+// GET /dashboard/portfolio
+// holdings of cash and ledger accounts per currency with net worth, valued in currency at latest rates.
+// e.g. ?currency=IDR
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_portfolio_handler(
+    State(ctx): State<
+        AppContext<
+            impl ForexStorage + LedgerStorage + LedgerStorageDoubleEntry,
+            impl ForexHistoricalRates,
+        >,
+    >,
+    format: ResponseFormat,
+    decimals: DecimalFormat,
+    CustomQuery(params): CustomQuery<PortfolioQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let ret =
+        api::ledger::get_exposure_report(&ctx.forex_storage, &ctx.forex_storage, params.currency)
+            .await?;
+
+    HttpResponse::ok_as(format, decimals, ret, None)
+}
@@ -0,0 +1,70 @@
+use axum::{extract::State, response::IntoResponse};
+use chrono::{DateTime, Utc};
+use pfm_core::api::analytics;
+use pfm_core::forex::{
+    entity::AggregationPeriod,
+    interface::{ForexHistoricalRates, ForexStorage, ForexStorageSparse},
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::dto::*;
+use crate::global::AppContext;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct AggregatesQuery {
+    #[serde(rename = "pair")]
+    pair: String,
+
+    /// week(ISO 8601) or month
+    #[serde(rename = "period")]
+    period: AggregationPeriod,
+
+    #[serde(rename = "start", deserialize_with = "deserialize_date")]
+    start: DateTime<Utc>,
+
+    #[serde(rename = "end", deserialize_with = "deserialize_date")]
+    end: DateTime<Utc>,
+}
+
+impl Validate for AggregatesQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.start > self.end {
+            return Err(AppError::BadRequest(
+                "start must not bigger than end".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for AggregatesQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "Invalid input of `pair`, `period`, `start` or `end`. `pair` must be in form of <BASE>/<QUOTE>, e.g. USD/IDR. `period` must be `week` or `month`. `start` and `end` must be in form of YYYY-MM-DD."
+    }
+}
+
+// GET /forex/aggregates
+// open, close, min, max and average rate of a pair per ISO week or calendar month within period.
+// e.g. ?pair=USD/IDR&period=week&start=2024-01-01&end=2024-12-31
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_aggregates_handler(
+    State(ctx): State<
+        AppContext<impl ForexStorage + ForexStorageSparse, impl ForexHistoricalRates>,
+    >,
+    format: ResponseFormat,
+    decimals: DecimalFormat,
+    CustomQuery(params): CustomQuery<AggregatesQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let ret = analytics::aggregate(
+        &ctx.forex_storage,
+        parse_pair(&params.pair)?,
+        params.period,
+        params.start,
+        params.end,
+    )
+    .await?;
+
+    HttpResponse::ok_as(format, decimals, ret, None)
+}
@@ -0,0 +1,176 @@
+use std::fmt::Write;
+use std::sync::LazyLock;
+
+use axum::{
+    extract::State,
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use pfm_core::api::analytics;
+use pfm_core::forex::{
+    entity::CompletenessReport,
+    interface::{ForexHistoricalRates, ForexStorage},
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::instrument;
+
+use crate::dto::*;
+use crate::global::AppContext;
+
+/// days of period when start is not set.
+const DEFAULT_PERIOD_DAYS: i64 = 365;
+
+pub(super) const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// seconds metrics of a period are served from memory, so scrapes don't re-read every historical file of it.
+const METRICS_CACHE_TTL_SECS: i64 = 300;
+
+struct CachedReport {
+    period: (NaiveDate, NaiveDate),
+    expires_at: DateTime<Utc>,
+    report: CompletenessReport,
+}
+
+static METRICS_CACHE: LazyLock<RwLock<Option<CachedReport>>> = LazyLock::new(|| RwLock::new(None));
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct CompletenessQuery {
+    /// optional inclusive start date, defaults to 365 days before end
+    #[serde(
+        rename = "start",
+        default,
+        deserialize_with = "deserialize_optional_date"
+    )]
+    start: Option<DateTime<Utc>>,
+
+    /// optional inclusive end date, defaults to today
+    #[serde(
+        rename = "end",
+        default,
+        deserialize_with = "deserialize_optional_date"
+    )]
+    end: Option<DateTime<Utc>>,
+}
+
+impl CompletenessQuery {
+    fn period(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        let end = self.end.unwrap_or_else(Utc::now);
+        let start = self
+            .start
+            .unwrap_or_else(|| end - TimeDelta::days(DEFAULT_PERIOD_DAYS));
+
+        (start, end)
+    }
+}
+
+impl Validate for CompletenessQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        let (start, end) = self.period();
+        if start > end {
+            return Err(AppError::BadRequest(
+                "start must not bigger than end".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for CompletenessQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "Invalid input of `start` or `end`. `start` and `end` must be in form of YYYY-MM-DD."
+    }
+}
+
+// GET /forex/completeness
+// percentage of days each currency has non-zero rate in historical rates of a period.
+// e.g. ?start=2019-01-01&end=2019-12-31
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_completeness_handler(
+    State(ctx): State<AppContext<impl ForexStorage, impl ForexHistoricalRates>>,
+    format: ResponseFormat,
+    decimals: DecimalFormat,
+    CustomQuery(params): CustomQuery<CompletenessQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let (start, end) = params.period();
+    let ret = analytics::completeness(&ctx.forex_storage, start, end).await?;
+
+    HttpResponse::ok_as(format, decimals, ret, None)
+}
+
+// GET /forex/completeness/metrics
+// same report as /forex/completeness as prometheus gauges, to be scraped by prometheus.
+#[instrument(skip(ctx))]
+pub(crate) async fn get_completeness_metrics_handler(
+    State(ctx): State<AppContext<impl ForexStorage, impl ForexHistoricalRates>>,
+    CustomQuery(params): CustomQuery<CompletenessQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let (start, end) = params.period();
+    let period = (start.date_naive(), end.date_naive());
+    let now = Utc::now();
+    let cached = METRICS_CACHE
+        .read()
+        .await
+        .as_ref()
+        .filter(|cached| cached.period == period && cached.expires_at > now)
+        .map(|cached| cached.report.clone());
+    let ret = match cached {
+        Some(report) => report,
+        None => {
+            let report = analytics::completeness(&ctx.forex_storage, start, end).await?;
+            *METRICS_CACHE.write().await = Some(CachedReport {
+                period,
+                expires_at: now + TimeDelta::seconds(METRICS_CACHE_TTL_SECS),
+                report: report.clone(),
+            });
+            report
+        }
+    };
+
+    Ok((
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)],
+        prometheus_gauges(&ret),
+    ))
+}
+
+// render report in prometheus text exposition format.
+fn prometheus_gauges(report: &CompletenessReport) -> String {
+    let mut ret = String::new();
+    let _ = writeln!(
+        ret,
+        "# HELP pfm_rates_completeness_percent percentage of days in period having non-zero historical rate."
+    );
+    let _ = writeln!(ret, "# TYPE pfm_rates_completeness_percent gauge");
+    for completeness in &report.currencies {
+        let _ = writeln!(
+            ret,
+            "pfm_rates_completeness_percent{{currency=\"{}\"}} {}",
+            completeness.currency.code(),
+            completeness.percent.normalize()
+        );
+    }
+    let _ = writeln!(
+        ret,
+        "# HELP pfm_rates_completeness_days days in period having non-zero historical rate."
+    );
+    let _ = writeln!(ret, "# TYPE pfm_rates_completeness_days gauge");
+    for completeness in &report.currencies {
+        let _ = writeln!(
+            ret,
+            "pfm_rates_completeness_days{{currency=\"{}\"}} {}",
+            completeness.currency.code(),
+            completeness.days_with_rate
+        );
+    }
+    let _ = writeln!(
+        ret,
+        "# HELP pfm_rates_completeness_period_days days in period."
+    );
+    let _ = writeln!(ret, "# TYPE pfm_rates_completeness_period_days gauge");
+    let _ = writeln!(ret, "pfm_rates_completeness_period_days {}", report.days);
+
+    ret
+}
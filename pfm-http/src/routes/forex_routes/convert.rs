@@ -2,9 +2,13 @@ use std::str::FromStr;
 
 use axum::{extract::State, response::IntoResponse};
 use chrono::{DateTime, Utc};
-use pfm_core::forex::{
-    interface::{ForexHistoricalRates, ForexStorage},
-    service, Money,
+use pfm_core::{
+    api,
+    forex::{
+        Money,
+        interface::{ForexHistoricalRates, ForexStorage},
+    },
+    global::SystemClock,
 };
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
@@ -27,6 +31,10 @@ pub struct ConvertQuery {
         deserialize_with = "deserialize_optional_date"
     )]
     pub date: Option<DateTime<Utc>>,
+
+    /// include intermediate steps of conversion
+    #[serde(rename = "debug", default)]
+    pub debug: bool,
 }
 
 impl Validate for ConvertQuery {
@@ -37,7 +45,7 @@ impl Validate for ConvertQuery {
 
 impl BadRequestErrMsg for ConvertQuery {
     fn bad_request_err_msg() -> &'static str {
-        r#"Invalid from, to, or date. `from` must be in form: <CODE> <AMOUNT>, CODE is ISO 4217 standard. AMOUNT may be separated by comma for thousands, and dot for fractions. `to` must be in form: <CODE>, CODE is ISO 4217 standard. `date` is optional denoting historical convert. Must be in form YYYY-MM-DD.
+        r#"Invalid from, to, or date. `from` must be in form: <CODE> <AMOUNT>, CODE is ISO 4217 standard. AMOUNT may be separated by comma for thousands, and dot for fractions. `to` must be in form: <CODE>, CODE is ISO 4217 standard. `date` is optional denoting historical convert. Must be in form YYYY-MM-DD. `debug` is optional, true or false.
         "#
     }
 }
@@ -47,27 +55,100 @@ impl BadRequestErrMsg for ConvertQuery {
 // query 1: `from` money format ISO 4217 <CURRENCY_CODE> <AMOUNT>, amount may be separated by comma for thousands and dot for fractionals, e.g. ?from=USD 1,000
 // query 2: `to` currency of target conversion: e.g. ?to=USD
 // query 3(OPTIONAL); `date`(YYYY-MM-DD) for historical convert. e.g. ?date=2020-02-02
+// query 4(OPTIONAL); `debug` to include amount in base currency, and both rates used with their dates. e.g. ?debug=true
 #[instrument(skip(ctx), ret)]
 pub(crate) async fn convert_handler(
     State(ctx): State<AppContext<impl ForexStorage, impl ForexHistoricalRates>>,
+    format: ResponseFormat,
+    decimals: DecimalFormat,
     CustomQuery(params): CustomQuery<ConvertQuery>,
 ) -> Result<impl IntoResponse, AppError> {
     match params.date {
         Some(date) => {
             let from_money = Money::from_str(&params.from)?;
             let to_currency = params.to.parse()?;
-            let ret =
-                service::convert_historical(&ctx.forex_storage, from_money, to_currency, date)
-                    .await?;
+            let ret = api::forex::convert_historical(
+                &ctx.forex_storage,
+                &SystemClock,
+                from_money,
+                to_currency,
+                date,
+                params.debug,
+            )
+            .await?;
 
-            Ok(HttpResponse::ok(ret, None))
+            HttpResponse::ok_as(format, decimals, ret, None)
         }
         None => {
             let from_money = Money::from_str(&params.from)?;
             let to_currency = params.to.parse()?;
-            let ret = service::convert(&ctx.forex_storage, from_money, to_currency).await?;
+            let ret = api::forex::convert(
+                &ctx.forex_storage,
+                &SystemClock,
+                from_money,
+                to_currency,
+                params.debug,
+            )
+            .await?;
 
-            Ok(HttpResponse::ok(ret, None))
+            HttpResponse::ok_as(format, decimals, ret, None)
         }
     }
 }
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ConvertDatesQuery {
+    #[serde(rename = "from")]
+    pub from: String,
+
+    #[serde(rename = "to")]
+    pub to: String,
+
+    /// comma separated dates to convert on
+    #[serde(rename = "dates", deserialize_with = "deserialize_dates")]
+    pub dates: Vec<DateTime<Utc>>,
+}
+
+impl Validate for ConvertDatesQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.dates.is_empty() || self.dates.len() > api::forex::CONVERT_ON_DATES_MAX {
+            return Err(AppError::BadRequest(format!(
+                "dates must contain between 1 and {} dates",
+                api::forex::CONVERT_ON_DATES_MAX
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for ConvertDatesQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "Invalid from, to, or dates. `from` must be in form: <CODE> <AMOUNT>, e.g. USD 1,000. `to` must be in form: <CODE>. `dates` are comma separated dates in form YYYY-MM-DD, e.g. 2024-01-31,2024-02-29."
+    }
+}
+
+// GET /forex/convert/dates
+// convert the same money on each date in one call, e.g. revaluing monthly installments of foreign currency loan.
+// dates without rates, e.g. weekends, use rates of the previous business day.
+// e.g. ?from=USD 1,000&to=IDR&dates=2024-01-31,2024-02-29,2024-03-31
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn convert_dates_handler(
+    State(ctx): State<AppContext<impl ForexStorage, impl ForexHistoricalRates>>,
+    format: ResponseFormat,
+    decimals: DecimalFormat,
+    CustomQuery(params): CustomQuery<ConvertDatesQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let from_money = Money::from_str(&params.from)?;
+    let to_currency = params.to.parse()?;
+    let ret = api::forex::convert_on_dates(
+        &ctx.forex_storage,
+        &SystemClock,
+        from_money,
+        to_currency,
+        &params.dates,
+    )
+    .await?;
+
+    HttpResponse::ok_as(format, decimals, ret, None)
+}
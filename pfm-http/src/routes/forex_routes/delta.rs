@@ -0,0 +1,45 @@
+use axum::{extract::State, response::IntoResponse};
+use pfm_core::api;
+use pfm_core::forex::interface::{ForexHistoricalRates, ForexStorage, ForexStorageSnapshots};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::dto::*;
+use crate::global::AppContext;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct DeltaQuery {
+    /// id of latest rates snapshot client has
+    #[serde(rename = "since")]
+    since: Uuid,
+}
+
+impl Validate for DeltaQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for DeltaQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "Invalid input of `since`. `since` must be id of latest rates, e.g. 0b8f1c3e-5d3a-4c8e-9a4b-2f6d7e8a9b0c."
+    }
+}
+
+// GET /forex/latest/delta
+// only rates changed in current latest rates since the snapshot with the id, for frequently polling clients.
+// e.g. ?since=0b8f1c3e-5d3a-4c8e-9a4b-2f6d7e8a9b0c
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_latest_delta_handler(
+    State(ctx): State<
+        AppContext<impl ForexStorage + ForexStorageSnapshots, impl ForexHistoricalRates>,
+    >,
+    format: ResponseFormat,
+    decimals: DecimalFormat,
+    CustomQuery(params): CustomQuery<DeltaQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let ret = api::forex::latest_delta(&ctx.forex_storage, params.since).await?;
+
+    HttpResponse::ok_as(format, decimals, ret, None)
+}
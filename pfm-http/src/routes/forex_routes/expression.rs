@@ -0,0 +1,53 @@
+use axum::{extract::State, response::IntoResponse};
+use pfm_core::{
+    api,
+    forex::interface::{ForexHistoricalRates, ForexStorage},
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::dto::*;
+use crate::global::AppContext;
+
+/// longest expression accepted.
+const MAX_EXPRESSION_LEN: usize = 256;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct ExpressionQuery {
+    #[serde(rename = "expression")]
+    expression: String,
+}
+
+impl Validate for ExpressionQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.expression.trim().is_empty() || self.expression.len() > MAX_EXPRESSION_LEN {
+            return Err(AppError::BadRequest(format!(
+                "expression must be between 1 and {} characters",
+                MAX_EXPRESSION_LEN
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for ExpressionQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "Invalid input of `expression`. `expression` must be in form: <MONEY> [+|-|*|/ <MONEY>|<NUMBER> ...] [in <CODE>] [@YYYY-MM-DD], e.g. (USD 100 + EUR 50) in IDR."
+    }
+}
+
+// GET /forex/evaluate
+// evaluate rate expression of calculator box into money, using latest rates or historical rates at its date.
+// e.g. ?expression=(USD 100 %2B EUR 50) in IDR, ?expression=XAU 1 * 0.5 in USD @2023-01-01
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_expression_handler(
+    State(ctx): State<AppContext<impl ForexStorage, impl ForexHistoricalRates>>,
+    format: ResponseFormat,
+    decimals: DecimalFormat,
+    CustomQuery(params): CustomQuery<ExpressionQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let ret = api::expression::evaluate(&ctx.forex_storage, &params.expression).await?;
+
+    HttpResponse::ok_as(format, decimals, ret, None)
+}
@@ -0,0 +1,62 @@
+use axum::{extract::State, response::IntoResponse};
+use chrono::{DateTime, Utc};
+use pfm_core::api::analytics;
+use pfm_core::forex::interface::{ForexHistoricalRates, ForexStorage, ForexStorageSparse};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::dto::*;
+use crate::global::AppContext;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct ExtremesQuery {
+    #[serde(rename = "pair")]
+    pair: String,
+
+    #[serde(rename = "start", deserialize_with = "deserialize_date")]
+    start: DateTime<Utc>,
+
+    #[serde(rename = "end", deserialize_with = "deserialize_date")]
+    end: DateTime<Utc>,
+}
+
+impl Validate for ExtremesQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.start > self.end {
+            return Err(AppError::BadRequest(
+                "start must not bigger than end".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for ExtremesQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "Invalid input of `pair`, `start` or `end`. `pair` must be in form of <BASE>/<QUOTE>, e.g. USD/IDR. `start` and `end` must be in form of YYYY-MM-DD."
+    }
+}
+
+// GET /forex/extremes
+// lowest and highest rate of a pair within period, and percentile of current rate within it.
+// e.g. ?pair=USD/IDR&start=2024-01-01&end=2024-12-31
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_extremes_handler(
+    State(ctx): State<
+        AppContext<impl ForexStorage + ForexStorageSparse, impl ForexHistoricalRates>,
+    >,
+    format: ResponseFormat,
+    decimals: DecimalFormat,
+    CustomQuery(params): CustomQuery<ExtremesQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let ret = analytics::extremes(
+        &ctx.forex_storage,
+        parse_pair(&params.pair)?,
+        params.start,
+        params.end,
+    )
+    .await?;
+
+    HttpResponse::ok_as(format, decimals, ret, None)
+}
@@ -0,0 +1,335 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use axum::{extract::State, response::IntoResponse};
+use chrono::{DateTime, Utc};
+use pfm_core::forex::{
+    Currency,
+    entity::{Cursor, Order, OrderBy, Rates, RatesList, RatesPage, RatesResponse, sort_rates},
+    interface::{ForexHistoricalRates, ForexStorage, ForexStoragePagination},
+};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use tracing::instrument;
+
+use crate::dto::*;
+use crate::global::AppContext;
+
+const DEFAULT_PAGE: u32 = 1;
+const DEFAULT_SIZE: u32 = 10;
+const MAX_SIZE: u32 = 100;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct RatesListQuery {
+    #[serde(rename = "page", default = "default_page")]
+    pub page: u32,
+
+    #[serde(rename = "size", default = "default_size")]
+    pub size: u32,
+
+    /// `asc` or `desc` by rates date, default to `desc`
+    #[serde(
+        rename = "order",
+        default = "default_order",
+        deserialize_with = "deserialize_order"
+    )]
+    pub order: Order,
+
+    /// `data_date` or `poll_date` the `order` applies to, default to `data_date`
+    #[serde(
+        rename = "order_by",
+        default,
+        deserialize_with = "deserialize_order_by"
+    )]
+    pub order_by: OrderBy,
+
+    /// optional inclusive start date of rates
+    #[serde(
+        rename = "start",
+        default,
+        deserialize_with = "deserialize_optional_date"
+    )]
+    pub start: Option<DateTime<Utc>>,
+
+    /// optional inclusive end date of rates
+    #[serde(
+        rename = "end",
+        default,
+        deserialize_with = "deserialize_optional_date"
+    )]
+    pub end: Option<DateTime<Utc>>,
+
+    /// optional comma separated currency codes to project, e.g. USD,EUR,IDR
+    #[serde(rename = "fields", alias = "symbols", default)]
+    pub fields: Option<String>,
+
+    /// optional opaque cursor from `next_cursor` of previous response, `page` is ignored when set
+    #[serde(rename = "cursor", default)]
+    pub cursor: Option<String>,
+}
+
+fn default_page() -> u32 {
+    DEFAULT_PAGE
+}
+
+fn default_size() -> u32 {
+    DEFAULT_SIZE
+}
+
+fn default_order() -> Order {
+    Order::DESC
+}
+
+fn deserialize_order<'de, D>(deserializer: D) -> Result<Order, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: String = String::deserialize(deserializer)?;
+    match s.to_lowercase().as_str() {
+        "asc" => Ok(Order::ASC),
+        "desc" => Ok(Order::DESC),
+        _ => Err(serde::de::Error::custom(
+            "Invalid order, expected asc or desc",
+        )),
+    }
+}
+
+fn deserialize_order_by<'de, D>(deserializer: D) -> Result<OrderBy, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: String = String::deserialize(deserializer)?;
+    match s.to_lowercase().as_str() {
+        "data_date" => Ok(OrderBy::DataDate),
+        "poll_date" => Ok(OrderBy::PollDate),
+        _ => Err(serde::de::Error::custom(
+            "Invalid order_by, expected data_date or poll_date",
+        )),
+    }
+}
+
+impl RatesListQuery {
+    fn has_date_range(&self) -> bool {
+        self.start.is_some() || self.end.is_some()
+    }
+
+    fn in_date_range(&self, date: DateTime<Utc>) -> bool {
+        self.start.is_none_or(|start| date >= start) && self.end.is_none_or(|end| date <= end)
+    }
+
+    /// selected currencies from `fields`, None means all currencies.
+    fn currencies(&self) -> Result<Option<HashSet<Currency>>, AppError> {
+        self.fields.as_deref().map(parse_symbols).transpose()
+    }
+
+    fn cursor(&self) -> Result<Option<Cursor>, AppError> {
+        let Some(cursor) = &self.cursor else {
+            return Ok(None);
+        };
+
+        Ok(Some(Cursor::from_str(cursor)?))
+    }
+}
+
+impl Validate for RatesListQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.page == 0 {
+            return Err(AppError::BadRequest("page starts from 1".to_string()));
+        }
+
+        if self.size == 0 || self.size > MAX_SIZE {
+            return Err(AppError::BadRequest(format!(
+                "size must be between 1 and {}",
+                MAX_SIZE
+            )));
+        }
+
+        if let (Some(start), Some(end)) = (self.start, self.end)
+            && start > end
+        {
+            return Err(AppError::BadRequest(
+                "start must not bigger than end".to_string(),
+            ));
+        }
+
+        self.currencies()?;
+        self.cursor()?;
+
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for RatesListQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "Invalid input. `page` and `size` must be positive numbers. `order` is either asc or desc. `order_by` is either data_date or poll_date. `start` and `end` are optional in form of YYYY-MM-DD. `fields`(or `symbols`) is optional comma separated currency codes. `cursor` is optional opaque value of previous `next_cursor`."
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct RatesListItemDTO {
+    pub rates_date: DateTime<Utc>,
+    pub base: Currency,
+    pub rates: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct RatesListDTO {
+    pub message: String,
+    pub page: u32,
+    pub size: u32,
+    pub has_prev: bool,
+    pub has_next: bool,
+    pub next_cursor: Option<String>,
+    pub rates_list: Vec<RatesListItemDTO>,
+}
+
+impl RatesListDTO {
+    fn new(
+        message: &str,
+        params: &RatesListQuery,
+        list: RatesList<RatesResponse<Rates>>,
+    ) -> Result<Self, AppError> {
+        let currencies = params.currencies()?;
+        let next_cursor = list
+            .rates_list
+            .last()
+            .filter(|_| list.has_next)
+            .map(|rate| Cursor::of(rate).to_string());
+
+        let rates_list = list
+            .rates_list
+            .into_iter()
+            .map(|rate| {
+                let rates = project_rates(enabled_rates(&rate.data.rates)?, currencies.as_ref());
+
+                Ok(RatesListItemDTO {
+                    rates_date: rate.data.date,
+                    base: rate.data.base,
+                    rates,
+                })
+            })
+            .collect::<Result<Vec<RatesListItemDTO>, AppError>>()?;
+
+        Ok(RatesListDTO {
+            message: message.to_string(),
+            page: params.page,
+            size: params.size,
+            has_prev: list.has_prev,
+            has_next: list.has_next,
+            next_cursor,
+            rates_list,
+        })
+    }
+}
+
+/// cursor page as list, there is always previous page when paging with cursor.
+fn page_as_list(page: RatesPage<RatesResponse<Rates>>) -> RatesList<RatesResponse<Rates>> {
+    RatesList {
+        has_prev: true,
+        has_next: page.next_cursor.is_some(),
+        rates_list: page.rates_list,
+    }
+}
+
+// GET /forex/latest_list
+// list of polled latest rates
+// query: `page`, `size`, `order`(asc|desc), `order_by`(data_date|poll_date), `start`(YYYY-MM-DD), `end`(YYYY-MM-DD), `fields`(e.g. USD,EUR), `cursor`
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_latest_list_handler(
+    State(ctx): State<
+        AppContext<impl ForexStorage + ForexStoragePagination, impl ForexHistoricalRates>,
+    >,
+    format: ResponseFormat,
+    decimals: DecimalFormat,
+    CustomQuery(params): CustomQuery<RatesListQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let cursor = params.cursor()?;
+    let list = if params.has_date_range() {
+        let mut rates = ctx
+            .forex_storage
+            .get_latest_list(1, u32::MAX, params.order, params.order_by)
+            .await?
+            .rates_list;
+        rates.retain(|rate| params.in_date_range(rate.data.date));
+        match cursor {
+            Some(cursor) => page_as_list(RatesPage::after(
+                rates,
+                Some(cursor),
+                params.size,
+                params.order,
+                params.order_by,
+            )),
+            None => RatesList::paginate(&rates, params.page, params.size),
+        }
+    } else if cursor.is_some() {
+        let page = ctx
+            .forex_storage
+            .get_latest_page(cursor, params.size, params.order, params.order_by)
+            .await?;
+        page_as_list(page)
+    } else {
+        ctx.forex_storage
+            .get_latest_list(params.page, params.size, params.order, params.order_by)
+            .await?
+    };
+
+    let ret = RatesListDTO::new("Latest rates list", &params, list)?;
+
+    HttpResponse::ok_as(format, decimals, ret, None)
+}
+
+// GET /forex/historical_list
+// list of stored historical rates
+// query: `page`, `size`, `order`(asc|desc), `order_by`(data_date|poll_date), `start`(YYYY-MM-DD), `end`(YYYY-MM-DD), `fields`(e.g. USD,EUR), `cursor`
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_historical_list_handler(
+    State(ctx): State<
+        AppContext<impl ForexStorage + ForexStoragePagination, impl ForexHistoricalRates>,
+    >,
+    format: ResponseFormat,
+    decimals: DecimalFormat,
+    CustomQuery(params): CustomQuery<RatesListQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let cursor = params.cursor()?;
+    let rates = match (params.start, params.end) {
+        (Some(start), Some(end)) => {
+            let mut rates = ctx.forex_storage.get_historical_range(start, end).await?;
+            sort_rates(&mut rates, params.order, params.order_by);
+            Some(rates)
+        }
+        (None, None) => None,
+        _ => {
+            let mut rates = ctx
+                .forex_storage
+                .get_historical_list(1, u32::MAX, params.order, params.order_by)
+                .await?
+                .rates_list;
+            rates.retain(|rate| params.in_date_range(rate.data.date));
+            Some(rates)
+        }
+    };
+    let list = match (rates, cursor) {
+        (Some(rates), Some(cursor)) => page_as_list(RatesPage::after(
+            rates,
+            Some(cursor),
+            params.size,
+            params.order,
+            params.order_by,
+        )),
+        (Some(rates), None) => RatesList::paginate(&rates, params.page, params.size),
+        (None, Some(cursor)) => page_as_list(
+            ctx.forex_storage
+                .get_historical_page(Some(cursor), params.size, params.order, params.order_by)
+                .await?,
+        ),
+        (None, None) => {
+            ctx.forex_storage
+                .get_historical_list(params.page, params.size, params.order, params.order_by)
+                .await?
+        }
+    };
+
+    let ret = RatesListDTO::new("Historical rates list", &params, list)?;
+
+    HttpResponse::ok_as(format, decimals, ret, None)
+}
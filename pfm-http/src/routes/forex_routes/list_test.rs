@@ -0,0 +1,168 @@
+use std::path::PathBuf;
+
+use axum::{Router, http::StatusCode, routing};
+use chrono::{DateTime, TimeZone, Utc};
+use pfm_core::{
+    api::{providers::Provider, storage::ForexStorageImpl},
+    forex::{
+        Currency,
+        entity::{Rates, RatesData, RatesResponse},
+        interface::ForexStorage,
+    },
+};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::list::{get_historical_list_handler, get_latest_list_handler};
+use crate::global::AppContext;
+use crate::routes_test::{get, init_test_storage};
+
+/// list endpoints backed by storage at its own root, so rates of other tests don't show up.
+fn router(name: &str) -> (Router, ForexStorageImpl, PathBuf) {
+    init_test_storage();
+    let root = std::env::temp_dir().join(format!("pfm-http-list-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let storage = ForexStorageImpl::new(pfm_core::global::storage_fs_at(root.clone()).unwrap());
+    let ctx = AppContext {
+        forex_storage: storage.clone(),
+        forex_historical: Provider::new("none"),
+    };
+
+    let router = Router::new()
+        .route("/forex/latest_list", routing::get(get_latest_list_handler))
+        .route(
+            "/forex/historical_list",
+            routing::get(get_historical_list_handler),
+        )
+        .with_state(ctx);
+
+    (router, storage, root)
+}
+
+fn rates(date: DateTime<Utc>) -> RatesResponse<Rates> {
+    RatesResponse {
+        id: Uuid::new_v4(),
+        source: "test".to_string(),
+        poll_date: date,
+        data: Rates {
+            date,
+            base: Currency::USD,
+            rates: RatesData {
+                usd: Decimal::ONE,
+                idr: Decimal::from(16000),
+                ..Default::default()
+            },
+        },
+        error: None,
+        provenance: vec![],
+    }
+}
+
+fn dates(list: &Value) -> Vec<DateTime<Utc>> {
+    list["rates_list"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["rates_date"].as_str().unwrap().parse().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_lists_of_empty_storage() {
+    let (router, _, root) = router("empty");
+
+    for uri in [
+        "/forex/latest_list",
+        "/forex/historical_list",
+        "/forex/historical_list?start=2024-01-01&end=2024-12-31",
+    ] {
+        let (status, body) = get(&router, uri).await;
+        assert_eq!(status, StatusCode::OK, "{}", uri);
+        assert!(dates(&body["data"]).is_empty(), "{}", uri);
+        assert_eq!(body["data"]["has_next"], false, "{}", uri);
+        assert!(body["data"]["next_cursor"].is_null(), "{}", uri);
+    }
+
+    let _ = std::fs::remove_dir_all(root);
+}
+
+#[tokio::test]
+async fn test_historical_list_paged() {
+    let (router, storage, root) = router("historical");
+    let days: Vec<DateTime<Utc>> = (1..=3)
+        .map(|day| Utc.with_ymd_and_hms(2024, 3, day, 0, 0, 0).unwrap())
+        .collect();
+    for day in &days {
+        storage.insert_historical(*day, &rates(*day)).await.unwrap();
+    }
+
+    let (status, body) = get(&router, "/forex/historical_list?size=2&order=asc").await;
+    assert_eq!(status, StatusCode::OK);
+    let first = &body["data"];
+    assert_eq!(dates(first), days[0..2]);
+    assert_eq!(first["has_prev"], false);
+    assert_eq!(first["has_next"], true);
+    assert_eq!(first["rates_list"][0]["rates"]["idr"], "16000");
+
+    // cursor continues after the last item of previous page
+    let cursor = first["next_cursor"].as_str().unwrap();
+    let (status, body) = get(
+        &router,
+        &format!("/forex/historical_list?size=2&order=asc&cursor={}", cursor),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let second = &body["data"];
+    assert_eq!(dates(second), days[2..3]);
+    assert_eq!(second["has_prev"], true);
+    assert_eq!(second["has_next"], false);
+    assert!(second["next_cursor"].is_null());
+
+    let (status, body) = get(&router, "/forex/historical_list?page=2&size=2").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(dates(&body["data"]), days[0..1]);
+    assert_eq!(body["data"]["has_prev"], true);
+
+    let (status, _) = get(&router, "/forex/historical_list?cursor=garbage").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let _ = std::fs::remove_dir_all(root);
+}
+
+#[tokio::test]
+async fn test_latest_list_paged() {
+    let (router, storage, root) = router("latest");
+    let polls: Vec<DateTime<Utc>> = (1..=3)
+        .map(|hour| Utc.with_ymd_and_hms(2024, 3, 1, hour, 0, 0).unwrap())
+        .collect();
+    for poll in &polls {
+        storage.insert_latest(*poll, &rates(*poll)).await.unwrap();
+    }
+
+    let (status, body) = get(&router, "/forex/latest_list?size=2").await;
+    assert_eq!(status, StatusCode::OK);
+    let first = &body["data"];
+    assert_eq!(dates(first), vec![polls[2], polls[1]]);
+    assert_eq!(first["has_next"], true);
+
+    let cursor = first["next_cursor"].as_str().unwrap();
+    let (status, body) = get(
+        &router,
+        &format!("/forex/latest_list?size=2&cursor={}", cursor),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(dates(&body["data"]), vec![polls[0]]);
+    assert_eq!(body["data"]["has_next"], false);
+
+    let (status, body) = get(&router, "/forex/latest_list?page=2&size=2&fields=IDR").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(dates(&body["data"]), vec![polls[0]]);
+    assert!(body["data"]["rates_list"][0]["rates"].get("usd").is_none());
+
+    let (status, _) = get(&router, "/forex/latest_list?size=0").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let _ = std::fs::remove_dir_all(root);
+}
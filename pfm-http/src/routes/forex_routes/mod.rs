@@ -1,3 +1,18 @@
+pub(super) mod aggregates;
+pub(super) mod completeness;
 pub(super) mod convert;
+pub(super) mod delta;
+pub(super) mod expression;
+pub(super) mod extremes;
+pub(super) mod list;
+#[cfg(test)]
+mod list_test;
 pub(super) mod rates;
+#[cfg(test)]
+mod rates_test;
+pub(super) mod scenario;
+pub(super) mod slo;
+pub(super) mod sources;
+pub(super) mod sparklines;
 pub(super) mod timeseries;
+pub(super) mod travel_budget;
@@ -1,17 +1,19 @@
+use std::collections::HashSet;
 use std::str::FromStr;
 
 use axum::{extract::State, response::IntoResponse};
 use chrono::{DateTime, Datelike, Utc};
 use pfm_core::{
+    api,
     forex::{
         Currency,
-        entity::{Rates, RatesData, RatesResponse},
-        interface::{ForexHistoricalRates, ForexStorage},
-        service,
+        entity::{Rates, RatesResponse},
+        interface::{ForexHistoricalRates, ForexStorage, ForexStorageRebased},
     },
-    global::constants,
+    global::{Clock, SystemClock, constants},
 };
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use tracing::instrument;
 
 use crate::dto::*;
@@ -29,17 +31,30 @@ pub(crate) struct RatesQuery {
         deserialize_with = "deserialize_optional_date"
     )]
     pub date: Option<DateTime<Utc>>,
+
+    /// optional comma separated currency codes to project rates into, e.g. IDR,EUR,XAU
+    #[serde(rename = "symbols", default)]
+    pub symbols: Option<String>,
+}
+
+impl RatesQuery {
+    /// selected currencies from `symbols`, None means all currencies.
+    fn currencies(&self) -> Result<Option<HashSet<Currency>>, AppError> {
+        self.symbols.as_deref().map(parse_symbols).transpose()
+    }
 }
 
 impl Validate for RatesQuery {
     fn validate(&self) -> Result<(), AppError> {
+        self.currencies()?;
+
         Ok(())
     }
 }
 
 impl BadRequestErrMsg for RatesQuery {
     fn bad_request_err_msg() -> &'static str {
-        "`date` is optional denoting historical rates, must be in form of YYYY-MM-DD."
+        "`date` is optional denoting historical rates, must be in form of YYYY-MM-DD. `symbols` is optional comma separated currency codes."
     }
 }
 
@@ -48,35 +63,63 @@ pub(crate) struct RatesDTO {
     pub message: String,
     pub rates_date: DateTime<Utc>,
     pub base: Currency,
-    pub rates: RatesData,
+    pub rates: Map<String, Value>,
+
+    /// seconds since rates were published.
+    pub rates_age_seconds: i64,
+
+    /// rates are older than freshness threshold.
+    pub stale: bool,
 }
 
-impl From<RatesResponse<Rates>> for RatesDTO {
-    fn from(value: RatesResponse<Rates>) -> Self {
-        RatesDTO {
+impl RatesDTO {
+    /// rates of requested date, or latest ones if none, with their staleness as of now.
+    /// only rates of the currencies if any.
+    fn new(
+        value: RatesResponse<Rates>,
+        date: Option<DateTime<Utc>>,
+        currencies: Option<&HashSet<Currency>>,
+        now: DateTime<Utc>,
+    ) -> Result<Self, AppError> {
+        let (rates_age_seconds, stale) = api::forex::rates_staleness(value.data.date, date, now);
+
+        Ok(RatesDTO {
             message: "Successfully get rates".to_string(),
             rates_date: value.data.date,
             base: value.data.base,
-            rates: value.data.rates,
-        }
+            rates: project_rates(enabled_rates(&value.data.rates)?, currencies),
+            rates_age_seconds,
+            stale,
+        })
     }
 }
 
 // GET /forex/rates
 // get latest and historical rates
 // query 1: `date`(YYYY-MM-DD) date for historical rates, e.g. ?date=2020-02-02
+// query 2: `symbols` currencies of rates responded, e.g. ?symbols=IDR,EUR,XAU
 #[instrument(skip(ctx), ret)]
 pub(crate) async fn get_rates_handler(
-    State(ctx): State<AppContext<impl ForexStorage, impl ForexHistoricalRates>>,
+    State(ctx): State<
+        AppContext<impl ForexStorage + ForexStorageRebased, impl ForexHistoricalRates>,
+    >,
+    format: ResponseFormat,
+    decimals: DecimalFormat,
     CustomQuery(params): CustomQuery<RatesQuery>,
 ) -> Result<impl IntoResponse, AppError> {
+    let currencies = params.currencies()?;
     let base = if let Some(base) = params.base {
         Currency::from_str(base.as_str())?
     } else {
         constants::BASE_CURRENCY
     };
 
-    let ret = service::get_rates(&ctx.forex_storage, base, params.date).await?;
+    let ret = api::forex::get_rates(&ctx.forex_storage, &SystemClock, base, params.date).await?;
 
-    Ok(HttpResponse::ok(RatesDTO::from(ret), None))
+    HttpResponse::ok_as(
+        format,
+        decimals,
+        RatesDTO::new(ret, params.date, currencies.as_ref(), SystemClock.now())?,
+        None,
+    )
 }
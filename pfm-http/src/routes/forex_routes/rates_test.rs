@@ -0,0 +1,42 @@
+use axum::{Router, http::StatusCode, routing};
+use pfm_core::api::{
+    providers::Provider,
+    storage::{FaultyStorage, ForexStorageImpl, INJECTED_FAULT},
+};
+
+use super::{convert::convert_handler, rates::get_rates_handler};
+use crate::global::AppContext;
+use crate::routes_test::{get, init_test_storage};
+
+/// rates endpoints backed by storage failing every call.
+fn failing_router() -> Router {
+    init_test_storage();
+    let ctx = AppContext {
+        forex_storage: FaultyStorage::new(ForexStorageImpl::new(pfm_core::global::storage_fs()))
+            .with_error_rate(1.0),
+        forex_historical: Provider::new("none"),
+    };
+
+    Router::new()
+        .route("/forex/rates", routing::get(get_rates_handler))
+        .route("/forex/convert", routing::get(convert_handler))
+        .with_state(ctx)
+}
+
+#[tokio::test]
+async fn test_storage_errors_mapped_to_internal_error() {
+    let router = failing_router();
+
+    for uri in ["/forex/rates", "/forex/convert?from=USD%2010&to=IDR"] {
+        let (status, body) = get(&router, uri).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR, "{}", uri);
+        assert_eq!(body["error"]["code"], "internal_error");
+        // causes of internal errors are only logged
+        assert!(
+            !body["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains(INJECTED_FAULT)
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use axum::{
+    Json,
+    extract::{State, rejection::JsonRejection},
+    response::IntoResponse,
+};
+use pfm_core::{
+    api,
+    forex::{
+        Currency, Money,
+        interface::{ForexHistoricalRates, ForexStorage},
+    },
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::dto::*;
+use crate::global::AppContext;
+
+/// most holdings revalued in one scenario.
+const MAX_HOLDINGS: usize = 100;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct ScenarioBody {
+    /// report currency, shocks are against it
+    currency: Currency,
+
+    /// percent change of currencies' value against report currency, e.g. {"USD": "10", "EUR": "-5"}
+    shocks: HashMap<Currency, Decimal>,
+
+    /// holdings in form of <CODE> <AMOUNT>, e.g. ["USD 1,000", "IDR 10,000,000"]
+    #[serde(default)]
+    holdings: Vec<String>,
+}
+
+impl Validate for ScenarioBody {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.shocks.is_empty() {
+            return Err(AppError::BadRequest(
+                "shocks must contain at least 1 currency".to_string(),
+            ));
+        }
+        if self.holdings.len() > MAX_HOLDINGS {
+            return Err(AppError::BadRequest(format!(
+                "holdings must contain at most {} monies",
+                MAX_HOLDINGS
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+// POST /forex/scenario
+// what-if analysis: shock latest rates based in report currency, and revalue holdings before and after the shocks.
+// body: {"currency": "IDR", "shocks": {"USD": "10"}, "holdings": ["USD 1,000", "IDR 10,000,000"]}
+#[instrument(skip(ctx, body))]
+pub(crate) async fn shock_scenario_handler(
+    State(ctx): State<AppContext<impl ForexStorage, impl ForexHistoricalRates>>,
+    format: ResponseFormat,
+    decimals: DecimalFormat,
+    body: Result<Json<ScenarioBody>, JsonRejection>,
+) -> Result<impl IntoResponse, AppError> {
+    let Json(body) = body.map_err(|err| AppError::InvalidInput {
+        message: "body must be json of `currency`, `shocks` and `holdings`".to_string(),
+        details: err.body_text(),
+    })?;
+    body.validate()?;
+    let holdings = body
+        .holdings
+        .iter()
+        .map(|holding| Money::from_str(holding))
+        .collect::<Result<Vec<Money>, _>>()?;
+    let ret =
+        api::analytics::shock_scenario(&ctx.forex_storage, &body.shocks, &holdings, body.currency)
+            .await?;
+
+    HttpResponse::ok_as(format, decimals, ret, None)
+}
@@ -0,0 +1,160 @@
+use std::fmt::Write;
+
+use axum::{
+    extract::State,
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use pfm_core::{
+    api,
+    forex::{
+        entity::SloCompliance,
+        interface::{ForexHistoricalRates, ForexStorageSlo},
+    },
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use super::completeness::PROMETHEUS_CONTENT_TYPE;
+use crate::dto::*;
+use crate::global::AppContext;
+
+/// days of period when days is not set.
+const DEFAULT_PERIOD_DAYS: u32 = 7;
+
+/// evaluations are kept for SLO_RETENTION_DAYS.
+const MAX_PERIOD_DAYS: u32 = api::slo::SLO_RETENTION_DAYS;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct SloQuery {
+    /// optional days until now compliance is computed over, defaults to 7
+    #[serde(rename = "days", default)]
+    days: Option<u32>,
+}
+
+impl SloQuery {
+    fn since(&self) -> DateTime<Utc> {
+        let days = self.days.unwrap_or(DEFAULT_PERIOD_DAYS);
+
+        Utc::now() - TimeDelta::days(days as i64)
+    }
+}
+
+impl Validate for SloQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        if self
+            .days
+            .is_some_and(|days| days == 0 || days > MAX_PERIOD_DAYS)
+        {
+            return Err(AppError::BadRequest(format!(
+                "days must be between 1 and {}",
+                MAX_PERIOD_DAYS
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for SloQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "Invalid input of `days`. `days` must be a number between 1 and 90."
+    }
+}
+
+// GET /forex/slo
+// share of freshness SLO evaluations of pfm-cron meeting each objective over latest days, with the last evaluation.
+// e.g. ?days=30
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_slo_handler(
+    State(ctx): State<AppContext<impl ForexStorageSlo, impl ForexHistoricalRates>>,
+    format: ResponseFormat,
+    decimals: DecimalFormat,
+    CustomQuery(params): CustomQuery<SloQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let ret = api::slo::compliance(&ctx.forex_storage, params.since()).await?;
+
+    HttpResponse::ok_as(format, decimals, ret, None)
+}
+
+// GET /forex/slo/metrics
+// same compliance as /forex/slo as prometheus gauges, to be scraped by prometheus.
+#[instrument(skip(ctx))]
+pub(crate) async fn get_slo_metrics_handler(
+    State(ctx): State<AppContext<impl ForexStorageSlo, impl ForexHistoricalRates>>,
+    CustomQuery(params): CustomQuery<SloQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let ret = api::slo::compliance(&ctx.forex_storage, params.since()).await?;
+
+    Ok((
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)],
+        prometheus_gauges(&ret),
+    ))
+}
+
+// render compliance in prometheus text exposition format.
+fn prometheus_gauges(compliance: &SloCompliance) -> String {
+    let mut ret = String::new();
+    let _ = writeln!(
+        ret,
+        "# HELP pfm_slo_compliance_ratio share of evaluations in period meeting the objective."
+    );
+    let _ = writeln!(ret, "# TYPE pfm_slo_compliance_ratio gauge");
+    for (objective, ratio) in [
+        ("latest", compliance.latest),
+        ("historical", compliance.historical),
+        ("overall", compliance.overall),
+    ] {
+        let _ = writeln!(
+            ret,
+            "pfm_slo_compliance_ratio{{objective=\"{}\"}} {}",
+            objective,
+            ratio.normalize()
+        );
+    }
+    let _ = writeln!(ret, "# HELP pfm_slo_evaluations evaluations in period.");
+    let _ = writeln!(ret, "# TYPE pfm_slo_evaluations gauge");
+    let _ = writeln!(ret, "pfm_slo_evaluations {}", compliance.evaluations);
+
+    let Some(last) = &compliance.last else {
+        return ret;
+    };
+    let _ = writeln!(
+        ret,
+        "# HELP pfm_slo_met whether the last evaluation met the objective."
+    );
+    let _ = writeln!(ret, "# TYPE pfm_slo_met gauge");
+    for (objective, met) in [
+        ("latest", last.latest_met),
+        ("historical", last.historical_met),
+        ("overall", last.is_met()),
+    ] {
+        let _ = writeln!(
+            ret,
+            "pfm_slo_met{{objective=\"{}\"}} {}",
+            objective, met as u8
+        );
+    }
+    if let Some(age) = last.latest_age_seconds {
+        let _ = writeln!(
+            ret,
+            "# HELP pfm_slo_latest_age_seconds age of latest rates at the last evaluation."
+        );
+        let _ = writeln!(ret, "# TYPE pfm_slo_latest_age_seconds gauge");
+        let _ = writeln!(ret, "pfm_slo_latest_age_seconds {}", age);
+    }
+    let _ = writeln!(
+        ret,
+        "# HELP pfm_slo_historical_completeness_percent lowest completeness of historical rates among enabled currencies at the last evaluation."
+    );
+    let _ = writeln!(ret, "# TYPE pfm_slo_historical_completeness_percent gauge");
+    let _ = writeln!(
+        ret,
+        "pfm_slo_historical_completeness_percent {}",
+        last.historical_percent.normalize()
+    );
+
+    ret
+}
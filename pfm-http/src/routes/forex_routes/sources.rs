@@ -0,0 +1,52 @@
+use axum::{extract::State, response::IntoResponse};
+use chrono::{DateTime, Utc};
+use pfm_core::{
+    api,
+    forex::interface::{ForexHistoricalRates, ForexStorage, ForexStorageProvenance},
+    global::SystemClock,
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::dto::*;
+use crate::global::AppContext;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct SourcesQuery {
+    /// optional date for sources of historical rates
+    #[serde(
+        rename = "date",
+        default,
+        deserialize_with = "deserialize_optional_date"
+    )]
+    pub date: Option<DateTime<Utc>>,
+}
+
+impl Validate for SourcesQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for SourcesQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "`date` is optional denoting historical rates, must be in form of YYYY-MM-DD."
+    }
+}
+
+// GET /forex/sources
+// provider, poll date and per currency overrides of latest rates, or historical rates of the date along with their write history.
+// query 1: `date`(YYYY-MM-DD) date for historical rates, e.g. ?date=2020-02-02
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_sources_handler(
+    State(ctx): State<
+        AppContext<impl ForexStorage + ForexStorageProvenance, impl ForexHistoricalRates>,
+    >,
+    format: ResponseFormat,
+    decimals: DecimalFormat,
+    CustomQuery(params): CustomQuery<SourcesQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let ret = api::forex::rates_sources(&ctx.forex_storage, &SystemClock, params.date).await?;
+
+    HttpResponse::ok_as(format, decimals, ret, None)
+}
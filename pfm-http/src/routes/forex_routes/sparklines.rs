@@ -0,0 +1,86 @@
+use axum::{extract::State, response::IntoResponse};
+use pfm_core::api::analytics;
+use pfm_core::forex::{
+    Currency,
+    interface::{ForexHistoricalRates, ForexStorage, ForexStorageSparklines},
+};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use tracing::instrument;
+
+use crate::dto::*;
+use crate::global::AppContext;
+
+fn default_days() -> usize {
+    30
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct SparklinesQuery {
+    #[serde(rename = "base")]
+    base: Currency,
+
+    /// comma separated quote currencies, all supported currencies if empty
+    #[serde(rename = "quotes", default)]
+    quotes: String,
+
+    #[serde(rename = "days", default = "default_days")]
+    days: usize,
+}
+
+impl SparklinesQuery {
+    fn quotes(&self) -> Result<Vec<Currency>, AppError> {
+        if self.quotes.trim().is_empty() {
+            return Ok(Currency::iter().collect());
+        }
+
+        let mut quotes = vec![];
+        for quote in self.quotes.split(',').map(str::trim) {
+            quotes.push(quote.parse()?);
+        }
+
+        Ok(quotes)
+    }
+}
+
+impl Validate for SparklinesQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.days == 0 || self.days > analytics::SPARKLINE_MAX_DAYS {
+            return Err(AppError::BadRequest(format!(
+                "days must be between 1 and {}",
+                analytics::SPARKLINE_MAX_DAYS
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for SparklinesQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "`base` is required, e.g. USD. `quotes` is optional comma separated currencies, e.g. IDR,XAU. `days` is optional number of latest days, default to 30."
+    }
+}
+
+// GET /forex/sparklines
+// daily rates of base in quotes over latest days, read from precomputed sparklines.
+// e.g. ?base=USD&quotes=IDR,XAU&days=30
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_sparklines_handler(
+    State(ctx): State<
+        AppContext<impl ForexStorage + ForexStorageSparklines, impl ForexHistoricalRates>,
+    >,
+    format: ResponseFormat,
+    decimals: DecimalFormat,
+    CustomQuery(params): CustomQuery<SparklinesQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let ret = analytics::sparklines(
+        &ctx.forex_storage,
+        params.base,
+        &params.quotes()?,
+        params.days,
+    )
+    .await?;
+
+    HttpResponse::ok_as(format, decimals, ret, None)
+}
@@ -2,27 +2,31 @@ use crate::dto::*;
 use crate::global::AppContext;
 use axum::{extract::State, response::IntoResponse};
 use chrono::{DateTime, Duration, Utc};
+use pfm_core::api;
 use pfm_core::forex::{
-    entity::{Rates, RatesData, RatesResponse},
+    entity::{Rates, RatesResponse},
     interface::{ForexHistoricalRates, ForexStorage},
 };
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use tracing::instrument;
 
 #[derive(Debug, Serialize)]
 pub(crate) struct TimeseriesRatesDTO {
     pub message: String,
     pub rates_date: DateTime<Utc>,
-    pub rates: RatesData,
+    pub rates: Map<String, Value>,
 }
 
-impl From<RatesResponse<Rates>> for TimeseriesRatesDTO {
-    fn from(value: RatesResponse<Rates>) -> Self {
-        TimeseriesRatesDTO {
+impl TryFrom<RatesResponse<Rates>> for TimeseriesRatesDTO {
+    type Error = AppError;
+
+    fn try_from(value: RatesResponse<Rates>) -> Result<Self, Self::Error> {
+        Ok(TimeseriesRatesDTO {
             message: "Timeseries rates".to_string(),
             rates_date: value.data.date,
-            rates: value.data.rates,
-        }
+            rates: enabled_rates(&value.data.rates)?,
+        })
     }
 }
 
@@ -33,6 +37,10 @@ pub(crate) struct TimeseriesQuery {
 
     #[serde(rename = "end", deserialize_with = "deserialize_date")]
     end: DateTime<Utc>,
+
+    /// rates as stored, without adjusting rates before redenominations into current units
+    #[serde(rename = "raw", default)]
+    raw: bool,
 }
 
 impl Validate for TimeseriesQuery {
@@ -58,22 +66,25 @@ impl Validate for TimeseriesQuery {
 
 impl BadRequestErrMsg for TimeseriesQuery {
     fn bad_request_err_msg() -> &'static str {
-        "Invalid input of `start` or `end`. `start` must be in form of YYYY-MM-DD. `end` must be in form of YYYY-MM-DD."
+        "Invalid input of `start` or `end`. `start` must be in form of YYYY-MM-DD. `end` must be in form of YYYY-MM-DD. `raw` is optional boolean."
     }
 }
 
 #[instrument(skip(ctx), ret)]
 pub(crate) async fn get_timeseries_handler(
     State(ctx): State<AppContext<impl ForexStorage, impl ForexHistoricalRates>>,
+    format: ResponseFormat,
+    decimals: DecimalFormat,
     CustomQuery(params): CustomQuery<TimeseriesQuery>,
 ) -> Result<impl IntoResponse, AppError> {
-    Ok(HttpResponse::ok(
-        ctx.forex_storage
-            .get_historical_range(params.start, params.end)
+    HttpResponse::ok_as(
+        format,
+        decimals,
+        api::forex::get_historical_range(&ctx.forex_storage, params.start, params.end, params.raw)
             .await?
             .into_iter()
-            .map(|rate| TimeseriesRatesDTO::from(rate))
-            .collect::<Vec<TimeseriesRatesDTO>>(),
+            .map(TimeseriesRatesDTO::try_from)
+            .collect::<Result<Vec<TimeseriesRatesDTO>, AppError>>()?,
         None,
-    ))
+    )
 }
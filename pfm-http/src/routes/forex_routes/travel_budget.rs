@@ -0,0 +1,73 @@
+use std::str::FromStr;
+
+use axum::{extract::State, response::IntoResponse};
+use chrono::{DateTime, Utc};
+use pfm_core::api::analytics;
+use pfm_core::forex::{
+    Money,
+    interface::{ForexHistoricalRates, ForexStorage, ForexStorageSparse},
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::dto::*;
+use crate::global::AppContext;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct TravelBudgetQuery {
+    #[serde(rename = "budget")]
+    budget: String,
+
+    #[serde(rename = "destination")]
+    destination: String,
+
+    #[serde(rename = "start", deserialize_with = "deserialize_date")]
+    start: DateTime<Utc>,
+
+    #[serde(rename = "end", deserialize_with = "deserialize_date")]
+    end: DateTime<Utc>,
+}
+
+impl Validate for TravelBudgetQuery {
+    fn validate(&self) -> Result<(), AppError> {
+        if self.start > self.end {
+            return Err(AppError::BadRequest(
+                "start must not bigger than end".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl BadRequestErrMsg for TravelBudgetQuery {
+    fn bad_request_err_msg() -> &'static str {
+        "Invalid input of `budget`, `destination`, `start` or `end`. `budget` must be in form: <CODE> <AMOUNT>, e.g. IDR 10,000,000. `destination` must be in form: <CODE>. `start` and `end` are trip dates in form of YYYY-MM-DD."
+    }
+}
+
+// GET /forex/travel_budget
+// plan spending of home currency budget in destination currency during a trip, using recent rates average and volatility.
+// e.g. ?budget=IDR 10,000,000&destination=JPY&start=2025-04-01&end=2025-04-10
+#[instrument(skip(ctx), ret)]
+pub(crate) async fn get_travel_budget_handler(
+    State(ctx): State<
+        AppContext<impl ForexStorage + ForexStorageSparse, impl ForexHistoricalRates>,
+    >,
+    format: ResponseFormat,
+    decimals: DecimalFormat,
+    CustomQuery(params): CustomQuery<TravelBudgetQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let budget = Money::from_str(&params.budget)?;
+    let destination = params.destination.parse()?;
+    let ret = analytics::travel_budget(
+        &ctx.forex_storage,
+        budget,
+        destination,
+        params.start,
+        params.end,
+    )
+    .await?;
+
+    HttpResponse::ok_as(format, decimals, ret, None)
+}
@@ -1 +1,3 @@
 pub(super) mod ping;
+pub(super) mod schemas;
+pub(super) mod version;
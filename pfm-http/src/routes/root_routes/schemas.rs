@@ -0,0 +1,30 @@
+use std::collections::BTreeMap;
+
+use axum::{Json, extract::Path, response::IntoResponse};
+use pfm_core::forex::schema;
+use serde_json::Value;
+
+use crate::dto::*;
+
+// GET /schemas
+// JSON Schemas of forex payloads keyed by their names
+pub(crate) async fn get_schemas_handler() -> impl IntoResponse {
+    let schemas: BTreeMap<&str, Value> = schema::schemas();
+
+    HttpResponse::ok(schemas, None)
+}
+
+// GET /schemas/{name}
+// raw JSON Schema of single payload, usable directly by validators
+pub(crate) async fn get_schema_handler(
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    match schema::schema(&name) {
+        Some(schema) => Ok(Json(schema)),
+        None => Err(AppError::BadRequest(format!(
+            "unknown schema {}, available schemas: {}",
+            name,
+            schema::SCHEMA_NAMES.join(", ")
+        ))),
+    }
+}
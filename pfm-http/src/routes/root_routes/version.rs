@@ -0,0 +1,9 @@
+use axum::response::IntoResponse;
+
+use crate::{dto::*, global};
+
+// GET /version
+// versions, git hash, build date, enabled features and storage schema version of running pfm-http
+pub(crate) async fn get_version_handler() -> impl IntoResponse {
+    HttpResponse::ok(global::build_info().clone(), None)
+}
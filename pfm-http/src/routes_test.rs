@@ -0,0 +1,258 @@
+// routes_test.rs rates polled through the same pfm-core calls as poll_latest_rates job of pfm-cron, stored at
+// temporary storage root, then served by the router in-process, catching wiring regressions across pfm-core
+// storage and pfm-http. pfm-cron scheduling and job handlers themselves are not exercised here.
+// runs with `cargo test -p pfm-http`.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use async_trait::async_trait;
+use axum::{
+    Router,
+    body::{self, Body},
+    http::{Request, StatusCode, header},
+};
+use chrono::Utc;
+use pfm_core::{
+    api::{self, providers::Provider, storage::ForexStorageImpl},
+    forex::{
+        Currency, ForexResult, Money,
+        entity::{Job, JobTrigger, Rates, RatesData, RatesResponse},
+        interface::{ForexRates, ForexStorageJobRuns},
+    },
+    global::{SystemClock, constants::BASE_CURRENCY},
+};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use tokio_util::sync::CancellationToken;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::routes;
+
+/// provider responding fixed rates, standing in for 3rd party provider polled by pfm-cron.
+struct FixedRates;
+
+#[async_trait]
+impl ForexRates for FixedRates {
+    async fn rates(&self, base: Currency) -> ForexResult<RatesResponse<Rates>> {
+        Ok(RatesResponse {
+            id: Uuid::new_v4(),
+            source: "fixed".to_string(),
+            poll_date: Utc::now(),
+            data: Rates {
+                date: Utc::now(),
+                base,
+                rates: RatesData {
+                    usd: Decimal::ONE,
+                    eur: Decimal::new(9, 1),
+                    idr: Decimal::from(16000),
+                    ..Default::default()
+                },
+            },
+            error: None,
+            provenance: vec![],
+        })
+    }
+}
+
+/// storage root of this test process, set before configs are first read so every test sees it.
+pub(crate) fn init_test_storage() -> &'static Path {
+    static ROOT: OnceLock<PathBuf> = OnceLock::new();
+    ROOT.get_or_init(|| {
+        let root = std::env::temp_dir().join(format!("pfm-http-routes-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        // SAFETY: set once before configs are first read, other tests wait for it.
+        unsafe {
+            std::env::set_var("CORE_STORAGE_ROOT", &root);
+            std::env::set_var("HTTP_PORT", "0");
+        }
+        pfm_core::global::validate_storage().unwrap();
+        root
+    })
+}
+
+pub(crate) async fn get(router: &Router, uri: &str) -> (StatusCode, Value) {
+    let resp = router
+        .clone()
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = resp.status();
+    let body = body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+fn decimal(value: &Value) -> Decimal {
+    value.as_str().unwrap().parse().unwrap()
+}
+
+#[tokio::test]
+async fn test_polled_rates_served() {
+    init_test_storage();
+
+    // poll latest rates with the calls poll_latest_rates job of pfm-cron makes
+    let storage = ForexStorageImpl::new(pfm_core::global::storage_fs());
+    let provider = Provider::new("fixed").with_rates(FixedRates);
+    let polled = api::forex::run_job(
+        &storage,
+        Job::PollLatestRates,
+        JobTrigger::Cron,
+        api::forex::poll_rates(
+            &provider,
+            &storage,
+            &SystemClock,
+            BASE_CURRENCY,
+            &CancellationToken::new(),
+        ),
+    )
+    .await
+    .unwrap();
+    let runs = storage
+        .get_job_runs(Some(Job::PollLatestRates), 1)
+        .await
+        .unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].trigger, JobTrigger::Cron);
+    assert!(runs[0].success);
+
+    let router = routes::register_routes();
+
+    let (status, body) = get(&router, "/forex/rates?symbols=EUR,IDR").await;
+    assert_eq!(status, StatusCode::OK);
+    let data = &body["data"];
+    assert_eq!(
+        data["rates_date"]
+            .as_str()
+            .unwrap()
+            .parse::<chrono::DateTime<Utc>>()
+            .unwrap(),
+        polled.data.date
+    );
+    assert_eq!(decimal(&data["rates"]["idr"]), Decimal::from(16000));
+    assert_eq!(decimal(&data["rates"]["eur"]), Decimal::new(9, 1));
+    assert!(data["rates"].get("usd").is_none());
+
+    let (status, body) = get(&router, "/forex/convert?from=USD%2010&to=IDR").await;
+    assert_eq!(status, StatusCode::OK);
+    let to: Money = serde_json::from_value(body["data"]["to"].clone()).unwrap();
+    assert_eq!(to, Money::IDR(Decimal::from(160000)));
+
+    let resp = router
+        .clone()
+        .oneshot(
+            Request::get("/admin/status")
+                .header("x-admin-password", &crate::global::config().admin_password)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let (status, _) = get(&router, "/admin/status").await;
+    assert_ne!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_listeners_split_routes() {
+    init_test_storage();
+    let status = |router: Router, uri: &'static str| async move {
+        router
+            .oneshot(
+                Request::get(uri)
+                    .header("x-admin-password", &crate::global::config().admin_password)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .status()
+    };
+
+    // public listener serves no admin endpoints
+    let public = routes::register_public_routes();
+    assert_eq!(
+        status(public.clone(), "/admin/status").await,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        status(public.clone(), "/forex/rates?symbols=XYZ").await,
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(status(public, "/ping").await, StatusCode::OK);
+
+    // admin listener serves no forex endpoints
+    let admin = routes::register_admin_routes();
+    assert_eq!(
+        status(admin.clone(), "/forex/rates?symbols=XYZ").await,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(status(admin.clone(), "/admin/status").await, StatusCode::OK);
+    assert_eq!(status(admin, "/ping").await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_error_bodies_negotiated() {
+    init_test_storage();
+    let router = routes::register_routes();
+    let request = |accept: &'static str| {
+        Request::get("/forex/rates?symbols=XYZ")
+            .header(header::ACCEPT, accept)
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let resp = router
+        .clone()
+        .oneshot(request("application/xml"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], "application/xml");
+    let body = body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert!(body.starts_with("<?xml"), "{}", body);
+    assert!(body.contains("<code>invalid_input</code>"), "{}", body);
+
+    // browsers accept xml below html
+    let resp = router
+        .clone()
+        .oneshot(request(
+            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], "application/json");
+    let body = body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["code"], "invalid_input");
+}
+
+#[cfg(feature = "dashboard")]
+#[tokio::test]
+async fn test_dashboard_portfolio_served() {
+    init_test_storage();
+
+    let storage = ForexStorageImpl::new(pfm_core::global::storage_fs());
+    let provider = Provider::new("fixed").with_rates(FixedRates);
+    api::forex::poll_rates(
+        &provider,
+        &storage,
+        &SystemClock,
+        BASE_CURRENCY,
+        &CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+
+    let router = routes::register_routes();
+    let (status, body) = get(&router, "/dashboard/portfolio?currency=IDR").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["currency"], "IDR");
+    assert!(body["data"]["exposures"].is_array());
+
+    let (status, _) = get(&router, "/dashboard/portfolio?currency=XYZ").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
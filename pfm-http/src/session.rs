@@ -0,0 +1,95 @@
+// session.rs cookie sessions of users signed in to dashboard.
+// sessions are kept in memory, hence every user is signed out on restart.
+
+use std::{collections::HashMap, sync::LazyLock};
+
+use axum::http::{HeaderMap, header};
+use chrono::{DateTime, TimeDelta, Utc};
+use ring::rand::{SecureRandom, SystemRandom};
+use tokio::sync::RwLock;
+
+use crate::{dto::AppError, global};
+
+pub(crate) const SESSION_COOKIE_NAME: &str = "pfm_session";
+
+const SESSION_TOKEN_LEN: usize = 32;
+
+struct Session {
+    username: String,
+    expires_at: DateTime<Utc>,
+}
+
+static SESSIONS: LazyLock<RwLock<HashMap<String, Session>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// start session of user, returning its random token.
+pub(crate) async fn create_session(username: &str) -> Result<String, AppError> {
+    let mut token = [0u8; SESSION_TOKEN_LEN];
+    SystemRandom::new()
+        .fill(&mut token)
+        .map_err(|_| AppError::InternalServerError("failed generating session".to_string()))?;
+    let token: String = token.iter().map(|b| format!("{:02x}", b)).collect();
+
+    let now = Utc::now();
+    let mut sessions = SESSIONS.write().await;
+    sessions.retain(|_, session| session.expires_at > now);
+    sessions.insert(
+        token.clone(),
+        Session {
+            username: username.to_string(),
+            expires_at: now + TimeDelta::seconds(global::config().session_ttl_secs as i64),
+        },
+    );
+
+    Ok(token)
+}
+
+pub(crate) async fn remove_session(token: &str) {
+    SESSIONS.write().await.remove(token);
+}
+
+/// username of unexpired session.
+pub(crate) async fn session_username(token: &str) -> Option<String> {
+    SESSIONS
+        .read()
+        .await
+        .get(token)
+        .filter(|session| session.expires_at > Utc::now())
+        .map(|session| session.username.clone())
+}
+
+/// session token from request's Cookie header.
+pub(crate) fn session_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .find_map(|cookie| {
+            let (name, value) = cookie.trim().split_once('=')?;
+            (name == SESSION_COOKIE_NAME).then(|| value.to_string())
+        })
+}
+
+/// username of request's session, if signed in.
+pub(crate) async fn request_username(headers: &HeaderMap) -> Option<String> {
+    session_username(&session_token(headers)?).await
+}
+
+/// Set-Cookie value of session, not readable by scripts and not sent cross-site.
+pub(crate) fn session_cookie(token: &str) -> String {
+    format!(
+        "{}={}; Max-Age={}; Path=/; HttpOnly; SameSite=Strict",
+        SESSION_COOKIE_NAME,
+        token,
+        global::config().session_ttl_secs
+    )
+}
+
+/// Set-Cookie value removing session cookie.
+pub(crate) fn expired_session_cookie() -> String {
+    format!(
+        "{}=; Max-Age=0; Path=/; HttpOnly; SameSite=Strict",
+        SESSION_COOKIE_NAME
+    )
+}
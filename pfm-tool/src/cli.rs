@@ -0,0 +1,135 @@
+// cli.rs command line of pfm-tool: global flags, subcommands printing rates, conversion, lists and stats,
+// subcommands of ledger's double-entry mode, and subcommands generating shell completions and man page.
+// maintenance commands are still selected by uncommenting their calls in main().
+
+use std::sync::LazyLock;
+
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use pfm_core::forex::Currency;
+use pfm_core::global::BuildInfo;
+use pfm_core::ledger::entity::AccountKind;
+
+use crate::output::OutputFormat;
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "pfm-tool",
+    version,
+    long_version = long_version(),
+    about = "Maintenance tools of pfm storage"
+)]
+pub(crate) struct Cli {
+    /// output of commands printing rates, conversion, lists and stats
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    pub(crate) format: OutputFormat,
+
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum Command {
+    /// print rates of base currency, e.g. `pfm-tool --format csv rates USD --symbols IDR,XAU > rates.csv`
+    Rates {
+        base: Currency,
+        /// date of historical rates in YYYY-MM-DD, latest rates if not set
+        #[arg(long)]
+        date: Option<String>,
+        /// currencies to print, every currency if not set
+        #[arg(long, value_delimiter = ',')]
+        symbols: Vec<Currency>,
+    },
+
+    /// convert money with latest rates, e.g. `pfm-tool convert "USD 1,000" IDR`
+    Convert { from: String, to: Currency },
+
+    /// print page of stored rates, newest first
+    List {
+        #[arg(long, default_value_t = 1)]
+        page: u32,
+        #[arg(long, default_value_t = 10)]
+        size: u32,
+        /// list latest rates instead of historical ones
+        #[arg(long)]
+        latest: bool,
+    },
+
+    /// print files and bytes of each storage subtree
+    Stats,
+
+    /// fetch missing and error-marked historical rates within rate limit and remaining quota of the provider,
+    /// resumable from its checkpoint, e.g. `pfm-tool backfill currencybeacon --from 2000-01-01 --dry-run`
+    Backfill {
+        provider: String,
+        /// first date in YYYY-MM-DD, newest stored date or 2000-01-01 if not set
+        #[arg(long)]
+        from: Option<String>,
+        /// last date in YYYY-MM-DD, today if not set
+        #[arg(long)]
+        to: Option<String>,
+        /// fetch stored dates again too, ignoring the checkpoint
+        #[arg(long)]
+        refetch: bool,
+        /// print the plan(batches, pauses, deferred dates and eta) without calling provider nor writing
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// add double-entry account, e.g. `pfm-tool account assets:bank:bca asset IDR`
+    Account {
+        name: String,
+        /// asset, liability, equity, income or expense
+        #[arg(value_parser = parse_account_kind)]
+        kind: AccountKind,
+        currency: Currency,
+    },
+
+    /// post journal entry balanced in each of its currencies,
+    /// e.g. `pfm-tool post salary "income:salary=IDR -20,000,000" "assets:bank:bca=IDR 20,000,000"`
+    Post {
+        memo: String,
+        /// postings as account=money, debit positive and credit negative
+        #[arg(required = true, num_args = 2..)]
+        postings: Vec<String>,
+        /// date of entry in YYYY-MM-DD, today if not set
+        #[arg(long)]
+        date: Option<String>,
+    },
+
+    /// print balance sheet of double-entry accounts in currency at latest rates
+    BalanceSheet { currency: Currency },
+
+    /// print shell completions, e.g. `pfm-tool completions zsh > ~/.zfunc/_pfm-tool`
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+
+    /// print man page in roff, e.g. `pfm-tool man > pfm-tool.1`
+    Man,
+}
+
+fn parse_account_kind(kind: &str) -> Result<AccountKind, String> {
+    serde_json::from_value(serde_json::Value::String(kind.to_lowercase()))
+        .map_err(|_| "expected asset, liability, equity, income or expense".to_string())
+}
+
+static LONG_VERSION: LazyLock<String> = LazyLock::new(|| {
+    BuildInfo::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"), &[]).detail()
+});
+
+/// detail of `--version`: git hash, build date, enabled features and storage schema version.
+fn long_version() -> &'static str {
+    LONG_VERSION.as_str()
+}
+
+pub(crate) fn print_completions(shell: Shell) {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+}
+
+pub(crate) fn print_man() -> std::io::Result<()> {
+    clap_mangen::Man::new(Cli::command()).render(&mut std::io::stdout())
+}
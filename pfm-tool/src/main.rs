@@ -1,27 +1,84 @@
-use chrono::Months;
+mod cli;
+mod output;
+
 use chrono::{DateTime, TimeDelta, TimeZone, Timelike, Utc};
-use pfm_core::forex::interface::{ForexHistoricalRates, ForexStorage, ForexTimeseriesRates};
-use pfm_core::forex::{Currency, ForexError, service};
-use pfm_core::forex_impl::forex_storage::ForexStorageImpl;
+use clap::Parser;
+use pfm_core::api::{self, storage::ForexStorageImpl};
+use pfm_core::forex::entity::{PollMode, PriceUnit};
+use pfm_core::forex::interface::{ForexHistoricalRates, ForexProviderCapabilities, ForexStorage};
+use pfm_core::forex::{Currency, ForexResult};
 use pfm_core::global;
-use pfm_core::{
-    forex::ForexResult, forex_impl::currency_api::Api as CurrencyAPI,
-    forex_impl::currencybeacon::Api as CurrencyBeaconAPI,
-    forex_impl::open_exchange_api::Api as OpenExchangeRatesAPI,
-};
-use rust_decimal_macros::dec;
+use pfm_core::ledger::entity::{Account, AccountKind, Posting};
+use pfm_core::seed::entity::{SeedBundle, SeedInterval};
+use rust_decimal::Decimal;
 use sha2::Digest;
-use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 
 #[tokio::main]
 async fn main() {
-    // fetch historical data to populate historical data split into its rate limit
-    // do_fetch_historical_data().await;
+    let cli = cli::Cli::parse();
+    let command = match cli.command {
+        Some(cli::Command::Completions { shell }) => return cli::print_completions(shell),
+        Some(cli::Command::Man) => return cli::print_man().expect("tool rendering man page"),
+        command => command,
+    };
+    output::set_format(cli.format);
+
+    global::validate_storage().expect("tool validating storage");
+    global::init_secrets(CancellationToken::new())
+        .await
+        .expect("tool resolving secrets");
+
+    // print rates, conversion, stored rates and storage usage as table, or as json or csv with `--format json|csv`
+    // e.g. `cargo run -p pfm-tool -- --format csv rates USD > rates.csv`
+    match command {
+        Some(cli::Command::Rates {
+            base,
+            date,
+            symbols,
+        }) => return do_rates(base, date.as_deref(), &symbols).await,
+        Some(cli::Command::Convert { from, to }) => return do_convert(&from, to).await,
+        Some(cli::Command::List { page, size, latest }) => {
+            return do_list(page, size, !latest).await;
+        }
+        Some(cli::Command::Stats) => return do_stats().await,
+        Some(cli::Command::Backfill {
+            provider,
+            from,
+            to,
+            refetch,
+            dry_run,
+        }) => {
+            let mode = match refetch {
+                true => PollMode::Always,
+                false => PollMode::SkipExisting,
+            };
+            return do_fetch_historical_data(
+                &provider,
+                from.as_deref(),
+                to.as_deref(),
+                mode,
+                dry_run,
+            )
+            .await;
+        }
+        Some(cli::Command::Account {
+            name,
+            kind,
+            currency,
+        }) => return do_add_account(&name, kind, currency).await,
+        Some(cli::Command::Post {
+            memo,
+            postings,
+            date,
+        }) => return do_post_entry(&memo, &postings, date.as_deref()).await,
+        Some(cli::Command::BalanceSheet { currency }) => return do_balance_sheet(currency).await,
+        _ => {}
+    }
 
     // fetch timeseries data and store them
-    // do_fetch_timeseries_and_store().await;
+    // do_fetch_timeseries_and_store(true).await;
 
     // read csv data of crypto prices
     // do_update_crypto_data().await;
@@ -31,77 +88,107 @@ async fn main() {
 
     // check checksum
     // do_compare_checksums();
+
+    // write JSON Schemas of forex payloads into schemas/{name}.json
+    // do_generate_schemas();
+
+    // fill metal rates before providers' coverage from static dataset of LBMA fixes
+    // do_backfill_metals(true).await;
+
+    // publish stored historical rates as seed bundle, then install it into storage of new installs from file or url
+    // do_publish_seed("2000-01-01", "2025-01-01", SeedInterval::Monthly, "seed-monthly.json").await;
+    // do_seed("https://pfm.example.com/seed-monthly.json", true).await;
+
+    // check stored rates have rate of base currency 1, set repair to true to rescale violating rates
+    // do_normalize(false).await;
+
+    // plan daily spending of home currency budget during a trip abroad
+    // do_travel_budget("IDR 10,000,000", "JPY", "2025-04-01", "2025-04-10").await;
+
+    // write monthly average and end of month rates of the year into csv, e.g. for accounting
+    // do_monthly_rates(2024, Currency::IDR, "monthly-rates-2024.csv").await;
+
+    // import bank statement into ledger, skipping already imported transactions
+    // do_import_bank_statement("/Users/mfirhas/pfm_backup/statements/2024-01.ofx", true).await;
+
+    // export every ledger record into json archive and its transactions into csv next to it, optionally anonymized for bug reports.
+    // import the json archive into another install.
+    // do_export_ledger("ledger-archive.json", true, Some("1.37")).await;
+    // do_import_ledger("ledger-archive.json").await;
+
+    // bundle goals, watchlist, digest subscriptions and ledger of profile to move them to another device
+    // do_export_profile("family", "family.pfm").await;
+    // do_import_profile("family.pfm").await;
+
+    // merge goals and watchlists with storage of another device, e.g. mounted desktop storage
+    // do_sync("/mnt/desktop/pfm-data", "desktop", &[]).await;
+
+    // back up goals and watchlists into own pfm-http server and restore changes of other devices from it
+    // do_sync_remote("https://pfm.example.com", "writer api key", &[]).await;
+
+    // re-apply category rules to stored transactions and list unmatched ones
+    // do_reclassify_transactions(false, true).await;
+    // do_exposure_report(Currency::IDR).await;
+    // do_subscribe_digest("user@example.com").await;
+    // do_rebuild_sparklines().await;
+
+    // generate Ed25519 key to sign stored rates with, then verify stored rates against its public key
+    // do_generate_signing_key().await;
+    // do_verify_signatures("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a").await;
+
+    // rename latest rates files from `latest-YYYY-MM-DDThh:mm:ssZ.json` into `latest-YYYYMMDDThhmmssZ.json`
+    // do_migrate_latest_filenames().await;
+
+    // create local user allowed to sign in to dashboard
+    // do_create_user("admin", "change me please").await;
+
+    // compare rates of the date from two providers, reporting differences above threshold percent
+    // do_check_provider_consistency("currencybeacon", "openexchangerates", "2025-01-02", "0.5").await;
+
+    // calculate rate expression with latest rates, or historical rates at @YYYY-MM-DD
+    // do_evaluate("(USD 100 + EUR 50) in IDR @2023-01-01").await;
 }
 
-async fn do_fetch_historical_data() {
+async fn do_fetch_historical_data(
+    provider: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+    mode: PollMode,
+    dry_run: bool,
+) {
+    let parse = |date: &str| {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc()
+    };
     let storage = ForexStorageImpl::new(global::storage_fs());
-    let latest_historical =
-        ForexStorage::get_historical_list(&storage, 1, 1, pfm_core::forex::entity::Order::DESC)
+    let from = match from {
+        Some(from) => parse(from),
+        None => {
+            let latest_historical = ForexStorage::get_historical_list(
+                &storage,
+                1,
+                1,
+                pfm_core::forex::entity::Order::DESC,
+                pfm_core::forex::entity::OrderBy::DataDate,
+            )
             .await
             .unwrap();
-    let start_date = {
-        if !latest_historical.rates_list.is_empty() {
-            latest_historical.rates_list[0].data.date
-        } else {
-            Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap()
+            match latest_historical.rates_list.first() {
+                Some(latest) => latest.data.date,
+                None => Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap(),
+            }
         }
     };
-    println!("{}", start_date);
+    let to = to.map_or_else(Utc::now, parse);
+    println!("backfill {} from {} to {}", provider, from, to);
 
-    let from = start_date;
-    tokio::time::sleep(Duration::from_secs(5)).await;
-    // let from = Utc.with_ymd_and_hms(2003, 9, 29, 0, 0, 0).unwrap();
-    let to = Utc.with_ymd_and_hms(2025, 3, 30, 23, 59, 59).unwrap();
-    let storage = ForexStorageImpl::new(global::storage_fs());
-    let apiname = ApisName::CurrencyBeaconAPI;
-    let forex_api = select_api(apiname);
-    let ret = fetch_historical_data(forex_api, storage, from, to).await;
+    let ret = fetch_historical_data(provider, storage, from, to, mode, dry_run).await;
     println!("{:?}", ret);
 }
 
-fn select_api(apiname: ApisName) -> Apis {
-    match apiname {
-        ApisName::ExchangeAPI => {
-            unimplemented!()
-        }
-        ApisName::CurrencyAPI => {
-            let currency_api = CurrencyAPI::new(
-                &global::config().forex_currency_api_key,
-                global::http_client(),
-            );
-            Apis::CurrencyAPI(currency_api)
-        }
-        ApisName::OpenExchangeRatesAPI => {
-            let oxrapi = OpenExchangeRatesAPI::new(
-                &global::config().forex_open_exchange_api_key,
-                global::http_client(),
-            );
-            Apis::OpenExchangeRatesAPI(oxrapi)
-        }
-        ApisName::CurrencyBeaconAPI => {
-            let currencybeaconapi = CurrencyBeaconAPI::new(
-                &global::config().forex_currencybeacon_api_key,
-                global::http_client(),
-            );
-            Apis::CurrencyBeacon(currencybeaconapi)
-        }
-    }
-}
-
-pub enum ApisName {
-    ExchangeAPI,
-    CurrencyAPI,
-    OpenExchangeRatesAPI,
-    CurrencyBeaconAPI,
-}
-
-#[derive(Clone)]
-pub enum Apis {
-    CurrencyAPI(CurrencyAPI),
-    OpenExchangeRatesAPI(OpenExchangeRatesAPI),
-    CurrencyBeacon(CurrencyBeaconAPI),
-}
-
 fn alldays(from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<DateTime<Utc>> {
     let start_date = from;
     let end_date = to;
@@ -120,126 +207,83 @@ fn alldays(from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<DateTime<Utc>> {
     dates
 }
 
+/// backfill historical rates from provider registered under the name, e.g. `currencybeacon`.
 async fn fetch_historical_data(
-    api: Apis,
+    name: &str,
     storage: ForexStorageImpl,
     from: DateTime<Utc>,
     to: DateTime<Utc>,
+    mode: PollMode,
+    dry_run: bool,
 ) -> ForexResult<()> {
-    match api {
-        Apis::OpenExchangeRatesAPI(api) => {
-            let quota_remaining = api.status().await?.data.usage.requests_remaining;
-            // 4 reqs/sec
-            let rate_limit = 4;
-            let seconds_per_batch = 1;
-            let ret = fetch_historical_rates_data(
-                api,
-                storage,
-                from,
-                to,
-                quota_remaining,
-                rate_limit,
-                seconds_per_batch,
-            )
-            .await;
-            ret
-        }
-        Apis::CurrencyAPI(api) => {
-            let quota_remaining = api.status().await?.quotas.month.remaining;
-            // 9 reqs/minute
-            let rate_limit = 10;
-            let seconds_per_batch = 62;
-            let ret = fetch_historical_rates_data(
-                api,
-                storage,
-                from,
-                to,
-                quota_remaining,
-                rate_limit,
-                seconds_per_batch,
-            )
-            .await;
-            ret
-        }
-        Apis::CurrencyBeacon(api) => {
-            let quota_remaining = 1000 as u32;
-            let rate_limit = 5;
-            let seconds_per_batch = 5;
-            let ret = fetch_historical_rates_data(
-                api,
-                storage,
-                from,
-                to,
-                quota_remaining,
-                rate_limit,
-                seconds_per_batch,
-            )
-            .await;
-            ret
-        }
-        _ => return Err(ForexError::internal_error("not implemented yet")),
-    }
+    let forex_api =
+        api::providers::provider_from_name(name, global::config(), global::http_client())?;
+    backfill_historical_data(forex_api, storage, name, from, to, mode, dry_run).await
 }
 
-async fn fetch_historical_rates_data<A, S>(
+/// plan missing dates within rate limit and remaining quota of the provider, then fetch them resuming from its checkpoint.
+async fn backfill_historical_data<A>(
     forex_api: A,
-    storage: S,
+    storage: ForexStorageImpl,
+    provider: &str,
     from: DateTime<Utc>,
     to: DateTime<Utc>,
-    quota_remaining: u32,
-    rate_limit: u32,
-    seconds_per_batch: u32,
+    mode: PollMode,
+    dry_run: bool,
 ) -> ForexResult<()>
 where
-    A: ForexHistoricalRates + Clone + Send + Sync + 'static,
-    S: ForexStorage + Clone + Send + Sync + 'static,
+    A: ForexHistoricalRates + ForexProviderCapabilities + Clone + Send + Sync + 'static,
 {
-    let alldays = alldays(from, to);
-    if quota_remaining <= 0 {
-        return Err(ForexError::internal_error("no quota remained"));
+    let base = global::constants::BASE_CURRENCY;
+    let (rate_limit, quota_remaining) = api::backfill::limits(&forex_api).await?;
+    let checkpoint = api::backfill::read_checkpoint(&storage, provider, base).await?;
+    let plan = api::backfill::plan(
+        &forex_api,
+        &storage,
+        alldays(from, to),
+        base,
+        mode,
+        quota_remaining,
+        rate_limit,
+        &checkpoint,
+    )
+    .await?;
+    println!(
+        "backfill plan: {} batches, {} skipped, {} deferred beyond quota, quota cost {}, eta {}s",
+        plan.batches.len(),
+        plan.skipped.len(),
+        plan.deferred.len(),
+        plan.quota_cost,
+        plan.eta_secs
+    );
+    if dry_run {
+        println!("{}", serde_json::to_string_pretty(&plan).unwrap());
+        return Ok(());
     }
 
-    let total_requests = std::cmp::min(alldays.len() as u32, quota_remaining);
-    let sleep = seconds_per_batch as u64;
-
-    let mut completed_requests = 0;
-    let mut allday_index = 0;
-
-    while completed_requests < total_requests && allday_index < alldays.len() {
-        let batch_size = std::cmp::min(total_requests - completed_requests, rate_limit);
-        let batch_size = std::cmp::min(batch_size, alldays.len() as u32 - allday_index as u32);
-
-        let mut handles = Vec::new();
-        for _ in 0..batch_size {
-            let api_clone = forex_api.clone();
-            let storage_clone = storage.clone();
-            let date = alldays[allday_index];
-            let index = allday_index;
-
-            handles.push(tokio::spawn(async move {
-                let ret = service::poll_historical_rates(
-                    &api_clone,
-                    &storage_clone,
-                    date,
-                    global::constants::BASE_CURRENCY,
-                )
-                .await;
-                println!("{}. Result date {}: {:?}", index, date, ret);
-            }));
-            allday_index += 1;
-        }
-
-        for handle in handles {
-            let _ = handle.await;
-        }
-
-        completed_requests += batch_size;
-
-        tokio::time::sleep(Duration::from_secs(sleep)).await;
-    }
+    let report = api::backfill::run(
+        &forex_api,
+        &storage,
+        &plan,
+        provider,
+        base,
+        &CancellationToken::new(),
+    )
+    .await?;
+    println!(
+        "fetched {} dates, {} failed, quota cost {}",
+        report.dates.len(),
+        report.failed.len(),
+        report.quota_cost
+    );
 
     let latest = storage
-        .get_historical_list(1, 1, pfm_core::forex::entity::Order::DESC)
+        .get_historical_list(
+            1,
+            1,
+            pfm_core::forex::entity::Order::DESC,
+            pfm_core::forex::entity::OrderBy::DataDate,
+        )
         .await?;
     if !latest.rates_list.is_empty() {
         println!(
@@ -253,216 +297,75 @@ where
     Ok(())
 }
 
-async fn do_fetch_timeseries_and_store() {
+async fn do_fetch_timeseries_and_store(dry_run: bool) {
     let start_date = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
     let end_date = Utc.with_ymd_and_hms(2025, 3, 24, 23, 59, 59).unwrap();
     // let end_date = Utc.with_ymd_and_hms(1999, 12, 31, 23, 59, 59).unwrap();
-    let ranges = split_date_range_yearly(start_date, end_date, 5);
-    for range in ranges {
-        let from = range.0;
-        let to = range.1;
-        println!("fetching historical data from {} till {}", from, to);
-        fetch_timeseries_and_store(from, to).await;
-        tokio::time::sleep(Duration::from_secs(62)).await;
-    }
-}
-
-async fn fetch_timeseries_and_store(start_date: DateTime<Utc>, end_date: DateTime<Utc>) {
     let storage_impl = ForexStorageImpl::new(global::storage_fs());
-    let forex_api = CurrencyBeaconAPI::new(
-        &global::config().forex_currencybeacon_api_key,
+    let forex_api = api::providers::provider_from_name(
+        "currencybeacon",
+        global::config(),
         global::http_client(),
-    );
-    let ret = forex_api
-        .timeseries_rates(start_date, end_date, global::constants::BASE_CURRENCY)
-        .await;
-    let rates = ret.unwrap();
-    let stored = ForexStorage::insert_historical_batch(&storage_impl, rates).await;
-    dbg!(&stored);
-    stored.unwrap();
-}
-
-fn split_date_range_yearly(
-    start_date: DateTime<Utc>,
-    end_date: DateTime<Utc>,
-    max_years: u32,
-) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
-    let mut ranges = Vec::new();
-    let max_months = Months::new(max_years * 12);
-
-    let mut current_start = start_date;
-
-    while current_start < end_date {
-        // Calculate end date by adding max_months (but subtract 1 second to avoid overlap)
-        let mut current_end = (current_start + max_months) - Duration::from_secs(1);
-
-        // Don't let the end date exceed the original end_date
-        if current_end > end_date {
-            current_end = end_date;
-        }
-
-        ranges.push((current_start, current_end));
-
-        // Move to the next second after current_end for the next range
-        current_start = current_end + Duration::from_secs(1);
-    }
-
-    ranges
+    )
+    .unwrap();
+    let query =
+        api::forex::TimeseriesQuery::new(start_date, end_date, global::constants::BASE_CURRENCY)
+            .with_dry_run(dry_run);
+    let ret =
+        api::forex::fetch_timeseries(&forex_api, &storage_impl, query, &CancellationToken::new())
+            .await;
+    println!("{:?}", ret);
 }
 
-// csv parser
 // Parsing data from coinmarketcap.com
-// This will parse the rates data from csv and convert into (date, Vec<Money>)
-use rust_decimal::Decimal;
+// Daily prices are ingested into historical rates, only filling zero crypto rates.
 use serde::{Deserialize, Serialize};
-use std::error::Error;
-use std::fs::{self, File};
-use std::io::{BufReader, Write};
-
-#[derive(Debug, Deserialize)]
-struct CryptoRecord {
-    #[serde(rename = "timeOpen")]
-    time_open: String,
-    #[serde(rename = "timeClose")]
-    time_close: String,
-    #[serde(rename = "timeHigh")]
-    time_high: String,
-    #[serde(rename = "timeLow")]
-    time_low: String,
-    name: String,
-    open: Decimal,
-    high: Decimal,
-    low: Decimal,
-    close: Decimal,
-    volume: Decimal,
-    marketCap: Decimal,
-    timestamp: String,
-}
-
-fn read_csv(
-    currency: Currency,
-    file_path: &str,
-) -> Result<HashMap<(Currency, DateTime<Utc>), Decimal>, Box<dyn Error>> {
-    let file = File::open(file_path)?;
-    let mut rdr = csv::ReaderBuilder::new()
-        .delimiter(b';')
-        .from_reader(BufReader::new(file));
-
-    let mut ret: Vec<((Currency, DateTime<Utc>), Decimal)> = vec![];
-    for result in rdr.deserialize() {
-        let record: CryptoRecord = result?;
-        let date = record
-            .timestamp
-            .parse::<DateTime<Utc>>()?
-            .format("%Y-%m-%dT%H:%M:%SZ")
-            .to_string()
-            .parse()?;
-        let usd_crypto_rate = dec!(1).checked_div(record.close).unwrap_or_default();
-        let data = ((currency, date), usd_crypto_rate);
-        ret.push(data);
-    }
-
-    let map = ret.into_iter().collect();
-    Ok(map)
-}
-
-fn iterate_and_parse(
-    currency: Currency,
-    dir: impl AsRef<Path>,
-) -> Result<HashMap<(Currency, DateTime<Utc>), Decimal>, Box<dyn Error>> {
-    let mut aggregated_data = HashMap::new();
-
-    for entry in fs::read_dir(dir)? {
-        let entry = entry?;
-        let path = entry.path();
-
-        if path.is_file() {
-            match read_csv(currency, path.to_str().ok_or("Invalid path")?) {
-                Ok(data) => aggregated_data.extend(data),
-                Err(e) => eprintln!("Failed to read {:?}: {}", path, e),
-            }
-        }
-    }
-
-    Ok(aggregated_data)
-}
+use std::fs;
+use std::io::Write;
 
 async fn do_update_crypto_data() {
     let forex_storage = ForexStorageImpl::new(global::storage_fs());
-    let start_date = Utc.with_ymd_and_hms(2010, 1, 1, 0, 0, 0).unwrap();
-    let end_date = Utc.with_ymd_and_hms(2025, 4, 10, 23, 59, 59).unwrap();
-
-    let csv_btc = (
-        Currency::BTC,
-        "/Users/mfirhas/pfm_backup/crypto_prices_history/btc",
-    );
-    let csv_eth = (
-        Currency::ETH,
-        "/Users/mfirhas/pfm_backup/crypto_prices_history/eth",
-    );
-    let csv_sol = (
-        Currency::SOL,
-        "/Users/mfirhas/pfm_backup/crypto_prices_history/sol",
-    );
-    let csv_xrp = (
-        Currency::XRP,
-        "/Users/mfirhas/pfm_backup/crypto_prices_history/xrp",
-    );
-    let csv_ada = (
-        Currency::ADA,
-        "/Users/mfirhas/pfm_backup/crypto_prices_history/ada",
-    );
-
-    let mut crypto_data = HashMap::new();
-    let btc_data = iterate_and_parse(csv_btc.0, csv_btc.1).unwrap();
-    let eth_data = iterate_and_parse(csv_eth.0, csv_eth.1).unwrap();
-    let sol_data = iterate_and_parse(csv_sol.0, csv_sol.1).unwrap();
-    let xrp_data = iterate_and_parse(csv_xrp.0, csv_xrp.1).unwrap();
-    let ada_data = iterate_and_parse(csv_ada.0, csv_ada.1).unwrap();
-    crypto_data.extend(btc_data);
-    crypto_data.extend(eth_data);
-    crypto_data.extend(sol_data);
-    crypto_data.extend(xrp_data);
-    crypto_data.extend(ada_data);
-
-    let mut ret = ForexStorage::get_historical_range(&forex_storage, start_date, end_date)
-        .await
-        .unwrap();
-    for rate in ret.iter_mut() {
-        if rate.data.rates.btc.is_zero() {
-            rate.data.rates.btc = *crypto_data
-                .get(&(Currency::BTC, rate.data.date))
-                .unwrap_or(&dec!(0));
-        }
-
-        if rate.data.rates.eth.is_zero() {
-            rate.data.rates.eth = *crypto_data
-                .get(&(Currency::ETH, rate.data.date))
-                .unwrap_or(&dec!(0));
-        }
-
-        if rate.data.rates.sol.is_zero() {
-            rate.data.rates.sol = *crypto_data
-                .get(&(Currency::SOL, rate.data.date))
-                .unwrap_or(&dec!(0));
-        }
-
-        if rate.data.rates.xrp.is_zero() {
-            rate.data.rates.xrp = *crypto_data
-                .get(&(Currency::XRP, rate.data.date))
-                .unwrap_or(&dec!(0));
-        }
-
-        if rate.data.rates.ada.is_zero() {
-            rate.data.rates.ada = *crypto_data
-                .get(&(Currency::ADA, rate.data.date))
-                .unwrap_or(&dec!(0));
-        }
-
-        ForexStorage::insert_historical(&forex_storage, rate.data.date, &rate)
-            .await
-            .unwrap();
+    let dry_run = true;
+
+    let datasets = [
+        (
+            Currency::BTC,
+            "/Users/mfirhas/pfm_backup/crypto_prices_history/btc",
+        ),
+        (
+            Currency::ETH,
+            "/Users/mfirhas/pfm_backup/crypto_prices_history/eth",
+        ),
+        (
+            Currency::SOL,
+            "/Users/mfirhas/pfm_backup/crypto_prices_history/sol",
+        ),
+        (
+            Currency::XRP,
+            "/Users/mfirhas/pfm_backup/crypto_prices_history/xrp",
+        ),
+        (
+            Currency::ADA,
+            "/Users/mfirhas/pfm_backup/crypto_prices_history/ada",
+        ),
+    ];
+
+    let mut prices = vec![];
+    for (currency, dir) in datasets {
+        // coinmarketcap exports close price in USD per coin
+        let parsed = api::datasets::crypto_from_dir(currency, dir, PriceUnit::UsdPerCoin).unwrap();
+        prices.extend(parsed);
     }
+
+    let report = api::forex::ingest_crypto_history(
+        &forex_storage,
+        prices,
+        api::forex::CRYPTO_MAX_JUMP,
+        dry_run,
+    )
+    .await
+    .unwrap();
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
 }
 
 fn do_calculate_and_store_checksum() {
@@ -620,3 +523,711 @@ fn compare_checksums(new_checksums: &PathBuf, prev_checksums: &PathBuf) -> Vec<S
 
     results
 }
+
+fn do_generate_schemas() {
+    let schemas_dir = PathBuf::from("schemas");
+    std::fs::create_dir_all(&schemas_dir).unwrap();
+    for (name, schema) in pfm_core::forex::schema::schemas() {
+        let path = schemas_dir.join(format!("{}.json", name));
+        let content = serde_json::to_string_pretty(&schema).unwrap();
+        std::fs::write(&path, content).unwrap();
+        println!("written schema {} into {:?}", name, path);
+    }
+}
+
+async fn do_backfill_metals(dry_run: bool) {
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let dataset = api::datasets::MetalsDataset::from_path(
+        "/Users/mfirhas/pfm_backup/metals_prices_history/lbma.csv",
+    )
+    .unwrap();
+    let report = api::forex::backfill_metals(&dataset, &storage, dataset.dates(), dry_run)
+        .await
+        .unwrap();
+    println!(
+        "backfilled metal rates on {} dates, skipped {}, dry run: {}",
+        report.dates.len(),
+        report.skipped.len(),
+        report.dry_run
+    );
+}
+
+async fn do_publish_seed(start: &str, end: &str, interval: SeedInterval, out: &str) {
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let parse_date = |date: &str| {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc()
+    };
+    let bundle = api::seed::build_bundle(&storage, parse_date(start), parse_date(end), interval)
+        .await
+        .unwrap();
+    std::fs::write(out, serde_json::to_string(&bundle).unwrap()).unwrap();
+    println!(
+        "published {} rates between {:?} into {}, checksum: {}",
+        bundle.rates.len(),
+        bundle.range(),
+        out,
+        bundle.checksum
+    );
+}
+
+async fn do_seed(location: &str, dry_run: bool) {
+    let content = if location.starts_with("http://") || location.starts_with("https://") {
+        global::http_client()
+            .get(location)
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .text()
+            .await
+            .unwrap()
+    } else {
+        std::fs::read_to_string(location).unwrap()
+    };
+    let bundle: SeedBundle = serde_json::from_str(&content).unwrap();
+
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let report = api::seed::install_bundle(&storage, bundle, dry_run)
+        .await
+        .unwrap();
+    println!(
+        "installed rates of {} dates, skipped {} already stored, dry run: {}",
+        report.installed.len(),
+        report.skipped.len(),
+        report.dry_run
+    );
+}
+
+async fn do_normalize(repair: bool) {
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let violations = api::forex::audit_base_rates(&storage, repair)
+        .await
+        .unwrap();
+    println!("{}", serde_json::to_string_pretty(&violations).unwrap());
+    println!("found {} violations", violations.len());
+}
+
+async fn do_travel_budget(budget: &str, destination: &str, start: &str, end: &str) {
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let parse_date = |date: &str| {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc()
+    };
+    let ret = api::analytics::travel_budget(
+        &storage,
+        budget.parse().unwrap(),
+        destination.parse().unwrap(),
+        parse_date(start),
+        parse_date(end),
+    )
+    .await
+    .unwrap();
+    println!("{}", serde_json::to_string_pretty(&ret).unwrap());
+}
+
+async fn do_monthly_rates(year: i32, base: Currency, path: &str) {
+    use pfm_core::api::analytics;
+
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let table = analytics::monthly_rates(&storage, year, base)
+        .await
+        .unwrap();
+    let content = analytics::monthly_rates_csv(&table).unwrap();
+    std::fs::write(path, content).unwrap();
+    println!(
+        "written monthly rates of {} months in {} into {}",
+        table.months.len(),
+        year,
+        path
+    );
+}
+
+async fn do_import_bank_statement(path: &str, dry_run: bool) {
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let content = std::fs::read_to_string(path).unwrap();
+    let transactions = match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+        Some("ofx") | Some("qfx") => api::ledger::parse_ofx(&content, Currency::IDR).unwrap(),
+        Some("qif") => api::ledger::parse_qif(&content, Currency::IDR, "%m/%d/%Y").unwrap(),
+        _ => {
+            let mapping = api::ledger::CsvMapping {
+                delimiter: b',',
+                date_column: "Date".to_string(),
+                date_format: "%Y-%m-%d".to_string(),
+                amount_column: "Amount".to_string(),
+                memo_column: "Description".to_string(),
+                payee_column: None,
+                decimal_comma: false,
+                negate: false,
+                currency: Currency::IDR,
+            };
+            api::ledger::parse_csv(&content, &mapping).unwrap()
+        }
+    };
+
+    let report = api::ledger::import_transactions(&storage, transactions, dry_run)
+        .await
+        .unwrap();
+    println!(
+        "imported {} transactions, skipped {} duplicates, dry run: {}",
+        report.imported.len(),
+        report.duplicates.len(),
+        dry_run
+    );
+}
+
+async fn do_reclassify_transactions(overwrite: bool, dry_run: bool) {
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let report = api::ledger::reclassify_transactions(&storage, overwrite, dry_run)
+        .await
+        .unwrap();
+    for tx in &report.unmatched {
+        println!(
+            "unmatched: {} {} {}",
+            tx.date.date_naive(),
+            tx.amount.amount(),
+            tx.memo
+        );
+    }
+    println!(
+        "{} transactions changed, {} unmatched, dry run: {}",
+        report.changed.len(),
+        report.unmatched.len(),
+        dry_run
+    );
+}
+
+async fn do_export_ledger(path: &str, strip_descriptions: bool, fuzz_factor: Option<&str>) {
+    use pfm_core::ledger::entity::AnonymizeOptions;
+
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let options = AnonymizeOptions {
+        strip_descriptions,
+        fuzz_factor: fuzz_factor.map(|factor| factor.parse().unwrap()),
+    };
+    let archive = api::ledger::export_ledger(&storage, &options)
+        .await
+        .unwrap();
+    std::fs::write(path, serde_json::to_string_pretty(&archive).unwrap()).unwrap();
+    let csv_path = Path::new(path).with_extension("csv");
+    std::fs::write(
+        &csv_path,
+        api::ledger::transactions_csv(&archive.transactions).unwrap(),
+    )
+    .unwrap();
+    println!(
+        "exported {} transactions and {} journal entries into {} and {:?}, anonymized: {}",
+        archive.transactions.len(),
+        archive.journal_entries.len(),
+        path,
+        csv_path,
+        archive.anonymized
+    );
+}
+
+async fn do_import_ledger(path: &str) {
+    use pfm_core::ledger::entity::LedgerArchive;
+
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let content = std::fs::read_to_string(path).unwrap();
+    let archive: LedgerArchive = serde_json::from_str(&content).unwrap();
+    if archive.anonymized {
+        println!("warning: importing anonymized archive");
+    }
+    let transactions = archive.transactions.len();
+    api::ledger::import_ledger(&storage, archive).await.unwrap();
+    println!("imported {} transactions from {}", transactions, path);
+}
+
+async fn do_export_profile(profile: &str, out: &str) {
+    let storage = ForexStorageImpl::new(global::client_storage_fs());
+    let archive = api::profile::export_profile(&storage, profile)
+        .await
+        .unwrap();
+    std::fs::write(out, serde_json::to_string_pretty(&archive).unwrap()).unwrap();
+    println!(
+        "exported profile {} with {} goals and {} transactions into {}, checksum: {}",
+        archive.profile,
+        archive.content.goals.len(),
+        archive.content.ledger.transactions.len(),
+        out,
+        archive.checksum
+    );
+}
+
+async fn do_import_profile(path: &str) {
+    use pfm_core::profile::entity::ProfileArchive;
+
+    let storage = ForexStorageImpl::new(global::client_storage_fs());
+    let content = std::fs::read_to_string(path).unwrap();
+    let archive: ProfileArchive = serde_json::from_str(&content).unwrap();
+    let profile = archive.profile.clone();
+    let report = api::profile::import_profile(&storage, archive)
+        .await
+        .unwrap();
+    println!("imported profile {} from {}: {:?}", profile, path, report);
+}
+
+/// resolutions of sync conflicts in form of (goal|watchlist, key, local|remote).
+fn sync_resolutions(
+    resolutions: &[(&str, &str, &str)],
+) -> Vec<pfm_core::sync::entity::SyncResolution> {
+    use pfm_core::sync::entity::{SyncKind, SyncResolution, SyncSide};
+
+    resolutions
+        .iter()
+        .map(|&(kind, key, keep)| SyncResolution {
+            kind: match kind {
+                "goal" => SyncKind::Goal,
+                "watchlist" => SyncKind::Watchlist,
+                _ => panic!("invalid sync kind {}, expected goal or watchlist", kind),
+            },
+            key: key.to_string(),
+            keep: match keep {
+                "local" => SyncSide::Local,
+                "remote" => SyncSide::Remote,
+                _ => panic!("invalid sync side {}, expected local or remote", keep),
+            },
+        })
+        .collect()
+}
+
+async fn do_sync(remote_root: &str, peer: &str, resolutions: &[(&str, &str, &str)]) {
+    let local = ForexStorageImpl::new(global::client_storage_fs());
+    let remote = ForexStorageImpl::new(global::storage_fs_at(remote_root.into()).unwrap());
+    let report = api::sync::sync(&local, &remote, peer, &sync_resolutions(resolutions))
+        .await
+        .unwrap();
+    println!(
+        "synced with {}: pulled {}, pushed {}",
+        report.peer, report.pulled, report.pushed
+    );
+    for conflict in &report.conflicts {
+        println!(
+            "conflict {:?} {}: local changed at {}(deleted: {}), remote changed at {}(deleted: {})",
+            conflict.kind,
+            conflict.key,
+            conflict.local_changed_at,
+            conflict.local_deleted,
+            conflict.remote_changed_at,
+            conflict.remote_deleted
+        );
+    }
+    if !report.conflicts.is_empty() {
+        eprintln!(
+            "{} conflicts left unresolved, sync again with their resolutions",
+            report.conflicts.len()
+        );
+        std::process::exit(1);
+    }
+}
+
+async fn do_sync_remote(url: &str, api_key: &str, resolutions: &[(&str, &str, &str)]) {
+    use pfm_core::api::integrations::HttpSyncRemote;
+
+    let local = ForexStorageImpl::new(global::client_storage_fs());
+    let server = HttpSyncRemote::new(url, api_key, global::http_client());
+    let report = api::sync::sync_remote(&local, &server, "server", &sync_resolutions(resolutions))
+        .await
+        .unwrap();
+    println!(
+        "synced with {}: pulled {}, pushed {}",
+        url, report.pulled, report.pushed
+    );
+    for conflict in &report.conflicts {
+        println!(
+            "conflict {:?} {}: local changed at {}(deleted: {}), server changed at {}(deleted: {})",
+            conflict.kind,
+            conflict.key,
+            conflict.local_changed_at,
+            conflict.local_deleted,
+            conflict.remote_changed_at,
+            conflict.remote_deleted
+        );
+    }
+    if !report.conflicts.is_empty() {
+        eprintln!(
+            "{} conflicts left unresolved, sync again with their resolutions",
+            report.conflicts.len()
+        );
+        std::process::exit(1);
+    }
+}
+
+async fn do_add_account(name: &str, kind: AccountKind, currency: Currency) {
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let account = api::ledger::add_account(
+        &storage,
+        Account {
+            name: name.to_string(),
+            kind,
+            currency,
+        },
+    )
+    .await
+    .unwrap();
+    println!(
+        "added {:?} account {} in {}",
+        account.kind,
+        account.name,
+        account.currency.code()
+    );
+}
+
+/// postings are `account=money`, e.g. `assets:bank=IDR 20,000,000`.
+async fn do_post_entry(memo: &str, postings: &[String], date: Option<&str>) {
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let date = match date {
+        Some(date) => chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc(),
+        None => Utc::now(),
+    };
+    let postings = postings
+        .iter()
+        .map(|posting| {
+            let (account, amount) = posting
+                .split_once('=')
+                .expect("posting must be account=money");
+            Posting {
+                account: account.trim().to_string(),
+                amount: amount.parse().unwrap(),
+            }
+        })
+        .collect();
+    let entry = api::ledger::post_entry(&storage, date, memo, postings)
+        .await
+        .unwrap();
+    println!("posted entry {} at {}", entry.id, entry.date.date_naive());
+}
+
+async fn do_balance_sheet(currency: Currency) {
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let sheet = api::ledger::get_balance_sheet(&storage, &storage, currency, None)
+        .await
+        .unwrap();
+    for balance in sheet
+        .assets
+        .iter()
+        .chain(&sheet.liabilities)
+        .chain(&sheet.equity)
+    {
+        println!(
+            "{}: {} ({})",
+            balance.account.name,
+            balance.converted.amount(),
+            balance.balance.amount()
+        );
+    }
+    println!(
+        "assets: {}, liabilities: {}, equity: {}, net income: {}",
+        sheet.total_assets.amount(),
+        sheet.total_liabilities.amount(),
+        sheet.total_equity.amount(),
+        sheet.net_income.amount()
+    );
+}
+
+#[derive(Serialize)]
+struct ExposureRow {
+    currency: Currency,
+    cash: Decimal,
+    accounts: Decimal,
+    value: Decimal,
+    share_percent: Decimal,
+    sensitivity: Decimal,
+}
+
+/// share of net worth of each currency held and change of net worth when it moves 5% against the currency.
+async fn do_exposure_report(currency: Currency) {
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let report = api::ledger::get_exposure_report(&storage, &storage, currency)
+        .await
+        .unwrap();
+    let rows: Vec<ExposureRow> = report
+        .exposures
+        .iter()
+        .map(|exposure| ExposureRow {
+            currency: exposure.currency,
+            cash: exposure.cash.amount(),
+            accounts: exposure.accounts.amount(),
+            value: exposure.value.amount(),
+            share_percent: exposure.share_percent,
+            sensitivity: exposure.sensitivity.amount(),
+        })
+        .collect();
+    output::print(&rows);
+}
+
+async fn do_subscribe_digest(email: &str) {
+    use pfm_core::digest::entity::{DigestFrequency, DigestPair};
+
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let watchlist = vec![
+        DigestPair {
+            base: Currency::USD,
+            quote: Currency::IDR,
+        },
+        DigestPair {
+            base: Currency::XAU,
+            quote: Currency::IDR,
+        },
+    ];
+    let subscription = api::digest::create_digest_subscription(
+        &storage,
+        email,
+        DigestFrequency::Weekly,
+        watchlist,
+        vec![],
+        Currency::IDR,
+    )
+    .await
+    .unwrap();
+    println!("digest subscription {} created", subscription.id);
+}
+
+async fn do_rebuild_sparklines() {
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    api::analytics::rebuild_sparklines(&storage).await.unwrap();
+    println!("sparklines rebuilt");
+}
+
+async fn do_generate_signing_key() {
+    let seed = api::integrations::RatesSigner::generate_seed_hex().unwrap();
+    let signer = api::integrations::RatesSigner::from_seed_hex(&seed).unwrap();
+    println!("CORE_FOREX_SIGNING_KEY={}", seed);
+    println!("public key: {}", signer.public_key_hex());
+}
+
+async fn do_verify_signatures(public_key: &str) {
+    use pfm_core::forex::interface::ForexStorageSignatures;
+
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let report = storage.verify_signatures(public_key).await.unwrap();
+    println!(
+        "verified: {}, unsigned: {}, invalid: {}",
+        report.verified,
+        report.unsigned.len(),
+        report.invalid.len()
+    );
+    for path in &report.invalid {
+        println!("invalid signature: {}", path);
+    }
+    if !report.is_valid() {
+        std::process::exit(1);
+    }
+}
+
+async fn do_create_user(username: &str, password: &str) {
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let user = api::users::create_user(&storage, username, password)
+        .await
+        .unwrap();
+    println!("created user {}", user.username);
+}
+
+async fn do_migrate_latest_filenames() {
+    use pfm_core::forex::interface::ForexStorageMigration;
+
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let migrated = storage.migrate_latest_filenames().await.unwrap();
+    for path in &migrated {
+        println!("migrated: {}", path);
+    }
+    println!("migrated {} latest files", migrated.len());
+}
+
+async fn do_check_provider_consistency(
+    first: &str,
+    second: &str,
+    date: &str,
+    threshold_percent: &str,
+) {
+    use pfm_core::api::providers::provider_from_name;
+
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let first = provider_from_name(first, global::config(), global::http_client()).unwrap();
+    let second = provider_from_name(second, global::config(), global::http_client()).unwrap();
+    let date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+        .and_utc();
+    let threshold_percent = threshold_percent.parse().unwrap();
+
+    let report =
+        api::forex::check_provider_consistency(&first, &second, &storage, date, threshold_percent)
+            .await
+            .unwrap();
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    println!(
+        "compared {} currencies, {} discrepancies, {} missing",
+        report.compared,
+        report.discrepancies.len(),
+        report.missing.len()
+    );
+}
+
+#[derive(Serialize)]
+struct RateRow {
+    date: DateTime<Utc>,
+    base: Currency,
+    currency: Currency,
+    rate: Decimal,
+}
+
+/// rates of base at the date, or latest if date is none. empty symbols print every currency.
+async fn do_rates(base: Currency, date: Option<&str>, symbols: &[Currency]) {
+    use strum::IntoEnumIterator;
+
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let date = date.map(|date| {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc()
+    });
+    let rates = api::forex::get_rates(&storage, &global::SystemClock, base, date)
+        .await
+        .unwrap();
+    let rows: Vec<RateRow> = Currency::iter()
+        .filter(|currency| symbols.is_empty() || symbols.contains(currency))
+        .map(|currency| RateRow {
+            date: rates.data.date,
+            base: rates.data.base,
+            currency,
+            rate: rates.data.rates.rate(currency),
+        })
+        .collect();
+    output::print(&rows);
+}
+
+#[derive(Serialize)]
+struct ConvertRow {
+    date: DateTime<Utc>,
+    from: Currency,
+    from_amount: Decimal,
+    to: Currency,
+    to_amount: Decimal,
+}
+
+/// convert money, e.g. `USD 1,000`, with latest rates.
+async fn do_convert(from: &str, to: Currency) {
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let ret = api::forex::convert(
+        &storage,
+        &global::SystemClock,
+        from.parse().unwrap(),
+        to,
+        false,
+    )
+    .await
+    .unwrap();
+    output::print(&[ConvertRow {
+        date: ret.date,
+        from: ret.from.currency(),
+        from_amount: ret.from.amount(),
+        to: ret.to.currency(),
+        to_amount: ret.to.amount(),
+    }]);
+}
+
+#[derive(Serialize)]
+struct ListRow {
+    id: String,
+    date: DateTime<Utc>,
+    poll_date: DateTime<Utc>,
+    source: String,
+    error: Option<String>,
+}
+
+/// page of stored historical or latest rates, newest first.
+async fn do_list(page: u32, size: u32, historical: bool) {
+    use pfm_core::forex::entity::{Order, OrderBy};
+
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let list = match historical {
+        true => storage.get_historical_list(page, size, Order::DESC, OrderBy::DataDate),
+        false => storage.get_latest_list(page, size, Order::DESC, OrderBy::DataDate),
+    }
+    .await
+    .unwrap();
+    let rows: Vec<ListRow> = list
+        .rates_list
+        .into_iter()
+        .map(|rates| ListRow {
+            id: rates.id.to_string(),
+            date: rates.data.date,
+            poll_date: rates.poll_date,
+            source: rates.source,
+            error: rates.error,
+        })
+        .collect();
+    output::print(&rows);
+}
+
+#[derive(Serialize)]
+struct StatsRow {
+    subtree: String,
+    files: u64,
+    bytes: u64,
+    oldest: Option<DateTime<Utc>>,
+    newest: Option<DateTime<Utc>>,
+}
+
+/// files and bytes of each storage subtree, followed by total.
+async fn do_stats() {
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let stats = api::forex::get_storage_stats(&storage).await.unwrap();
+    let total = StatsRow {
+        subtree: "total".to_string(),
+        files: stats.files,
+        bytes: stats.bytes,
+        oldest: None,
+        newest: None,
+    };
+    let rows: Vec<StatsRow> = stats
+        .subtrees
+        .into_iter()
+        .map(|subtree| StatsRow {
+            subtree: subtree.name,
+            files: subtree.files,
+            bytes: subtree.bytes,
+            oldest: subtree.oldest,
+            newest: subtree.newest,
+        })
+        .chain([total])
+        .collect();
+    output::print(&rows);
+}
+
+#[derive(Serialize)]
+struct ExpressionRow {
+    expression: String,
+    date: DateTime<Utc>,
+    currency: Currency,
+    amount: Decimal,
+}
+
+async fn do_evaluate(expression: &str) {
+    let storage = ForexStorageImpl::new(global::storage_fs());
+    let ret = api::expression::evaluate(&storage, expression)
+        .await
+        .unwrap();
+    output::print(&[ExpressionRow {
+        expression: ret.expression,
+        date: ret.date,
+        currency: ret.result.currency(),
+        amount: ret.result.amount(),
+    }]);
+}
@@ -0,0 +1,94 @@
+// output.rs output of commands in format selected with global `--format` flag: json, csv, or human readable table by default,
+// so commands can be scripted in shell pipelines, e.g. `cargo run -p pfm-tool -- --format csv rates USD > rates.csv`.
+// rows are flat structs, csv and table columns follow their fields' order.
+
+use std::sync::OnceLock;
+
+use anyhow::Context;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub(crate) enum OutputFormat {
+    Json,
+    Csv,
+    #[default]
+    Table,
+}
+
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+/// set format of this run once from parsed command line.
+pub(crate) fn set_format(format: OutputFormat) {
+    let _ = FORMAT.set(format);
+}
+
+/// format of this run, table if not set.
+pub(crate) fn format() -> OutputFormat {
+    FORMAT.get().copied().unwrap_or_default()
+}
+
+/// print rows in format of this run.
+pub(crate) fn print<T: Serialize>(rows: &[T]) {
+    let output = match format() {
+        OutputFormat::Json => serde_json::to_string_pretty(rows)
+            .map(|json| json + "\n")
+            .context("tool serializing rows into json"),
+        OutputFormat::Csv => to_csv(rows),
+        OutputFormat::Table => to_table(rows),
+    };
+    print!("{}", output.unwrap());
+}
+
+fn to_csv<T: Serialize>(rows: &[T]) -> anyhow::Result<String> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    for row in rows {
+        writer
+            .serialize(row)
+            .context("tool serializing row into csv")?;
+    }
+    let content = writer.into_inner().context("tool flushing csv")?;
+
+    String::from_utf8(content).context("tool reading csv as utf-8")
+}
+
+/// columns padded to their widest cell, headers uppercased.
+fn to_table<T: Serialize>(rows: &[T]) -> anyhow::Result<String> {
+    let csv = to_csv(rows)?;
+    let mut reader = csv::Reader::from_reader(csv.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .context("tool reading csv headers")?
+        .iter()
+        .map(str::to_uppercase)
+        .collect();
+    let mut records = vec![];
+    for record in reader.records() {
+        let record = record.context("tool reading csv record")?;
+        records.push(record.iter().map(str::to_string).collect::<Vec<_>>());
+    }
+
+    let mut widths: Vec<usize> = headers
+        .iter()
+        .map(|header| header.chars().count())
+        .collect();
+    for record in &records {
+        for (width, cell) in widths.iter_mut().zip(record) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: &[String]| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        padded.join("  ").trim_end().to_string() + "\n"
+    };
+    let mut ret = line(&headers);
+    for record in &records {
+        ret += &line(record);
+    }
+
+    Ok(ret)
+}
@@ -1,7 +1,6 @@
 use configrs::config::Config as configrs;
 use serde::Deserialize;
 use std::fs;
-use std::os::unix::fs::PermissionsExt;
 use std::{
     fmt::Debug,
     path::{Path, PathBuf},
@@ -77,25 +76,39 @@ fn is_workspace_root(dir: &Path) -> bool {
     false
 }
 
-pub fn set_root(root: PathBuf, permission: u32) -> Result<PathBuf, anyhow::Error> {
+/// set permission mode of path, no-op on platforms without unix permission bits.
+pub fn set_mode(path: &Path, mode: u32) -> Result<(), anyhow::Error> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    }
+    #[cfg(not(unix))]
+    let _ = (path, mode);
+
+    Ok(())
+}
+
+/// create root directory if not exists, permission is left as is if none.
+pub fn set_root(root: PathBuf, permission: Option<u32>) -> Result<PathBuf, anyhow::Error> {
     let is_exist = root.try_exists()?;
 
     if !is_exist {
         fs::create_dir_all(&root)?;
     }
 
-    let metadata = fs::metadata(&root)?;
-    let mut new_permissions = metadata.permissions();
-    new_permissions.set_mode(permission);
-    fs::set_permissions(&root, new_permissions)?;
+    if let Some(permission) = permission {
+        set_mode(&root, permission)?;
+    }
 
     Ok(root)
 }
 
+/// create sub directory if not exists, permission is left as is if none.
 pub fn set_sub_dir(
     parent: &PathBuf,
     sub_dir_name: &str,
-    permission: u32,
+    permission: Option<u32>,
 ) -> Result<PathBuf, anyhow::Error> {
     let sub_dir = parent.join(sub_dir_name);
 
@@ -105,10 +118,9 @@ pub fn set_sub_dir(
         fs::create_dir_all(&sub_dir)?;
     }
 
-    let metadata = fs::metadata(&sub_dir)?;
-    let mut new_permissions = metadata.permissions();
-    new_permissions.set_mode(permission);
-    fs::set_permissions(&sub_dir, new_permissions)?;
+    if let Some(permission) = permission {
+        set_mode(&sub_dir, permission)?;
+    }
 
     Ok(sub_dir)
 }
@@ -37,7 +37,7 @@ pub async fn wait_for_shutdown(notify: Arc<Notify>) {
 async fn shutdown_signal() -> Result<(), std::io::Error> {
     #[cfg(unix)]
     {
-        use tokio::signal::unix::{signal, SignalKind};
+        use tokio::signal::unix::{SignalKind, signal};
 
         let mut sigterm = signal(SignalKind::terminate())?;
         let ctrl_c = signal::ctrl_c();
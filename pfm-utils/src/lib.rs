@@ -1,3 +1,3 @@
 pub mod config_util;
-pub mod tracing_util;
 pub mod graceful_util;
+pub mod tracing_util;
@@ -1,9 +1,9 @@
 use tracing::info;
 use tracing_subscriber::{
+    EnvFilter, Registry,
     fmt::{self, format::FmtSpan},
     layer::SubscriberExt,
     util::SubscriberInitExt,
-    EnvFilter, Registry,
 };
 
 #[cfg(feature = "otel")]